@@ -1,13 +1,174 @@
 extern crate bindgen;
 
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-fn main() {
-    let target = env::var("TARGET").unwrap();
+// Oldest ReadStat accepted by the system-readstat feature
+const MIN_SYSTEM_VERSION: &str = "1.1.9";
+
+// Functions that every supported version of ReadStat declares, i.e. every function called but
+//   those within OPTIONAL_FUNCTIONS, so that an older header fails here rather than within bindgen
+//   or the linker
+const REQUIRED_FUNCTIONS: &[&str] = &[
+    // Parsing
+    "readstat_error_message",
+    "readstat_parse_dta",
+    "readstat_parse_sas7bcat",
+    "readstat_parse_sas7bdat",
+    "readstat_parse_sav",
+    "readstat_parse_xport",
+    "readstat_parser_free",
+    "readstat_parser_init",
+    "readstat_set_file_character_encoding",
+    "readstat_set_metadata_handler",
+    "readstat_set_progress_handler",
+    "readstat_set_row_limit",
+    "readstat_set_row_offset",
+    "readstat_set_value_handler",
+    "readstat_set_value_label_handler",
+    "readstat_set_variable_handler",
+    // I/O
+    "readstat_set_close_handler",
+    "readstat_set_io_ctx",
+    "readstat_set_open_handler",
+    "readstat_set_read_handler",
+    "readstat_set_seek_handler",
+    "readstat_set_update_handler",
+    // Metadata
+    "readstat_get_compression",
+    "readstat_get_creation_time",
+    "readstat_get_endianness",
+    "readstat_get_file_format_is_64bit",
+    "readstat_get_file_format_version",
+    "readstat_get_file_label",
+    "readstat_get_modified_time",
+    "readstat_get_row_count",
+    "readstat_get_table_name",
+    "readstat_get_var_count",
+    // Variables
+    "readstat_variable_get_alignment",
+    "readstat_variable_get_display_width",
+    "readstat_variable_get_format",
+    "readstat_variable_get_index",
+    "readstat_variable_get_index_after_skipping",
+    "readstat_variable_get_label",
+    "readstat_variable_get_measure",
+    "readstat_variable_get_missing_range_hi",
+    "readstat_variable_get_missing_range_lo",
+    "readstat_variable_get_missing_ranges_count",
+    "readstat_variable_get_name",
+    "readstat_variable_get_storage_width",
+    "readstat_variable_get_type",
+    "readstat_variable_get_type_class",
+    // Values
+    "readstat_double_value",
+    "readstat_float_value",
+    "readstat_int16_value",
+    "readstat_int32_value",
+    "readstat_int8_value",
+    "readstat_string_value",
+    "readstat_value_is_defined_missing",
+    "readstat_value_is_system_missing",
+    "readstat_value_is_tagged_missing",
+    "readstat_value_tag",
+    "readstat_value_type",
+    "readstat_value_type_class",
+    // Writing
+    "readstat_add_variable",
+    "readstat_begin_row",
+    "readstat_begin_writing_dta",
+    "readstat_begin_writing_sav",
+    "readstat_end_row",
+    "readstat_end_writing",
+    "readstat_insert_double_value",
+    "readstat_insert_missing_value",
+    "readstat_insert_string_value",
+    "readstat_set_data_writer",
+    "readstat_variable_set_format",
+    "readstat_variable_set_label",
+    "readstat_writer_free",
+    "readstat_writer_init",
+];
+
+// Accessors only newer versions of ReadStat declare
+//   Each found is made known as the cfg readstat_has_<name>, and to dependents as
+//   DEP_READSTAT_HAS_<NAME>, where name is the function without its readstat_ prefix
+const OPTIONAL_FUNCTIONS: &[&str] = &["readstat_get_file_encoding"];
 
+fn main() {
     let project_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
 
+    // Compile the vendored ReadStat or link against a system one
+    let (header, includes, readstat_version) =
+        if env::var_os("CARGO_FEATURE_SYSTEM_READSTAT").is_some() {
+            system()
+        } else {
+            vendored(&project_dir)
+        };
+    println!("cargo:rustc-env=READSTAT_VERSION={}", readstat_version);
+
+    // Functions declared by the header
+    let declared = std::fs::read_to_string(&header).unwrap_or_else(|e| {
+        panic!(
+            "Unable to read the ReadStat header {}: {}",
+            header.display(),
+            e
+        )
+    });
+    for f in REQUIRED_FUNCTIONS {
+        if !declares(&declared, f) {
+            panic!(
+                "The ReadStat header {} does not declare {}; ReadStat {} or newer is required",
+                header.display(),
+                f,
+                MIN_SYSTEM_VERSION
+            );
+        }
+    }
+    for f in OPTIONAL_FUNCTIONS {
+        let name = f.trim_start_matches("readstat_");
+        println!("cargo:rustc-check-cfg=cfg(readstat_has_{})", name);
+        if declares(&declared, f) {
+            println!("cargo:rustc-cfg=readstat_has_{}", name);
+            println!("cargo:has_{}=1", name);
+        }
+    }
+
+    bindings(&header, &includes);
+}
+
+// Link against the ReadStat located by pkg-config
+fn system() -> (PathBuf, Vec<PathBuf>, String) {
+    let lib = pkg_config::Config::new()
+        .atleast_version(MIN_SYSTEM_VERSION)
+        .probe("readstat")
+        .unwrap_or_else(|e| {
+            panic!(
+                "The system-readstat feature requires ReadStat {} or newer, located via pkg-config: {}",
+                MIN_SYSTEM_VERSION, e
+            )
+        });
+
+    let header = lib
+        .include_paths
+        .iter()
+        .map(|p| p.join("readstat.h"))
+        .chain(std::iter::once(PathBuf::from("/usr/include/readstat.h")))
+        .find(|p| p.exists())
+        .unwrap_or_else(|| {
+            panic!(
+                "ReadStat {} was located via pkg-config but its header readstat.h was not found within {:?}; install the development package for ReadStat",
+                lib.version, lib.include_paths
+            )
+        });
+
+    (header, lib.include_paths, lib.version)
+}
+
+// Compile the ReadStat source within vendor/ReadStat
+fn vendored(project_dir: &Path) -> (PathBuf, Vec<PathBuf>, String) {
+    let target = env::var("TARGET").unwrap();
+
     let src = project_dir.join("vendor").join("ReadStat").join("src");
     let sas = src.join("sas");
     let spss = src.join("spss");
@@ -90,18 +251,31 @@ fn main() {
     // Compile
     cc.compile("readstat");
 
-    // Tell cargo to invalidate the built crate whenever the wrapper changes
-    println!("cargo:rerun-if-changed=wrapper.h");
+    // ReadStat version, as declared within the vendored source
+    let configure_ac = project_dir
+        .join("vendor")
+        .join("ReadStat")
+        .join("configure.ac");
+    let readstat_version = std::fs::read_to_string(&configure_ac)
+        .ok()
+        .and_then(|s| parse_readstat_version(&s))
+        .unwrap_or_else(|| String::from("unknown"));
+    println!("cargo:rerun-if-changed={}", configure_ac.display());
 
     // Linking
     println!("cargo:rustc-link-lib=static=readstat");
 
+    (src.join("readstat.h"), vec![src], readstat_version)
+}
+
+fn bindings(header: &Path, includes: &[PathBuf]) {
     // The bindgen::Builder is the main entry point
     // to bindgen, and lets you build up options for
     // the resulting bindings.
     let bindings = bindgen::Builder::default()
         // The input header we would like to generate bindings for
-        .header("wrapper.h")
+        .header(header.to_string_lossy())
+        .clang_args(includes.iter().map(|i| format!("-I{}", i.display())))
         // Select which functions and types to build bindings for
         // Register callbacks
         .allowlist_function("readstat_set_metadata_handler")
@@ -114,6 +288,14 @@ fn main() {
         .allowlist_function("readstat_set_progress_handler")
         .allowlist_function("readstat_set_row_limit")
         .allowlist_function("readstat_set_row_offset")
+        .allowlist_function("readstat_set_file_character_encoding")
+        // I/O
+        .allowlist_function("readstat_set_open_handler")
+        .allowlist_function("readstat_set_close_handler")
+        .allowlist_function("readstat_set_seek_handler")
+        .allowlist_function("readstat_set_read_handler")
+        .allowlist_function("readstat_set_update_handler")
+        .allowlist_function("readstat_set_io_ctx")
         // Metadata
         .allowlist_function("readstat_get_row_count")
         .allowlist_function("readstat_get_var_count")
@@ -134,6 +316,13 @@ fn main() {
         .allowlist_function("readstat_variable_get_format")
         .allowlist_function("readstat_variable_get_type")
         .allowlist_function("readstat_variable_get_type_class")
+        .allowlist_function("readstat_variable_get_storage_width")
+        .allowlist_function("readstat_variable_get_display_width")
+        .allowlist_function("readstat_variable_get_alignment")
+        .allowlist_function("readstat_variable_get_measure")
+        .allowlist_function("readstat_variable_get_missing_ranges_count")
+        .allowlist_function("readstat_variable_get_missing_range_lo")
+        .allowlist_function("readstat_variable_get_missing_range_hi")
         // Values
         .allowlist_function("readstat_value_type")
         .allowlist_function("readstat_value_type_class")
@@ -154,9 +343,28 @@ fn main() {
         .allowlist_function("readstat_parse_sas7bdat")
         .allowlist_function("readstat_parse_sas7bcat")
         .allowlist_function("readstat_parse_xport")
+        .allowlist_function("readstat_parse_sav")
+        .allowlist_function("readstat_parse_dta")
         .allowlist_function("readstat_parser_free")
         // Parsing - Format
         .allowlist_function("xport_parse_format")
+        // Writing
+        .allowlist_function("readstat_writer_init")
+        .allowlist_function("readstat_set_data_writer")
+        .allowlist_function("readstat_add_variable")
+        .allowlist_function("readstat_variable_set_label")
+        .allowlist_function("readstat_variable_set_format")
+        .allowlist_function("readstat_begin_writing_sav")
+        .allowlist_function("readstat_begin_writing_dta")
+        .allowlist_function("readstat_begin_row")
+        .allowlist_function("readstat_insert_double_value")
+        .allowlist_function("readstat_insert_string_value")
+        .allowlist_function("readstat_insert_missing_value")
+        .allowlist_function("readstat_end_row")
+        .allowlist_function("readstat_end_writing")
+        .allowlist_function("readstat_writer_free")
+        // Error
+        .allowlist_function("readstat_error_message")
         // Types
         // Error
         .allowlist_type("readstat_error_t")
@@ -166,12 +374,17 @@ fn main() {
         .allowlist_type("readstat_endian_t")
         // Variables
         .allowlist_type("readstat_variable_t")
+        .allowlist_type("readstat_alignment_t")
+        .allowlist_type("readstat_measure_t")
         // Values
         .allowlist_type("readstat_type_t")
         .allowlist_type("readstat_type_class_t")
         .allowlist_type("readstat_value_t")
         // Parsing
         .allowlist_type("readstat_parser_t")
+        .allowlist_type("readstat_io_flags_t")
+        // Writing
+        .allowlist_type("readstat_writer_t")
         // Tell cargo to invalidate the built crate whenever any of the
         // included header files changed
         .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
@@ -186,3 +399,26 @@ fn main() {
         .write_to_file(out_path.join("bindings.rs"))
         .expect("Couldn't write bindings!");
 }
+
+// Whether the header declares the function `name`, i.e. `name` is followed by an opening paren
+fn declares(header: &str, name: &str) -> bool {
+    header.match_indices(name).any(|(i, _)| {
+        let before = header[..i].chars().next_back();
+        let after = header[i + name.len()..].trim_start().chars().next();
+        !matches!(before, Some(c) if c.is_alphanumeric() || c == '_') && after == Some('(')
+    })
+}
+
+// Version is the second bracketed argument of AC_INIT
+//   e.g. AC_INIT([ReadStat],[1.1.9],[evan@wizardmac.com],[readstat],[https://github.com/WizardMac/ReadStat])
+fn parse_readstat_version(configure_ac: &str) -> Option<String> {
+    let line = configure_ac
+        .lines()
+        .find(|l| l.trim_start().starts_with("AC_INIT"))?;
+    line.split('[')
+        .nth(2)?
+        .split(']')
+        .next()
+        .map(|v| v.trim().to_owned())
+        .filter(|v| !v.is_empty())
+}
@@ -6,3 +6,10 @@
 #![allow(non_snake_case)]
 
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+
+/// Version of the ReadStat C library, whether vendored or located via pkg-config
+pub const READSTAT_VERSION: &str = env!("READSTAT_VERSION");
+
+/// Whether linked against a system ReadStat (the `system-readstat` feature) rather than the
+/// vendored source
+pub const READSTAT_SYSTEM: bool = cfg!(feature = "system-readstat");
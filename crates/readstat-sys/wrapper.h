@@ -1 +0,0 @@
-#include "vendor/ReadStat/src/readstat.h"
\ No newline at end of file
@@ -0,0 +1,43 @@
+//! Reading 2 of the 110 columns of rand_ds_largepage_ok, skipped while parsing, against reading
+//! every column
+//!
+//! ```sh
+//! cargo bench -p readstat-tests --bench column_selection
+//! ```
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use readstat::prelude::{ReadStatData, ReadStatMetadata, ReadStatPath};
+use std::path::PathBuf;
+
+fn read(rsp: &ReadStatPath, md: &ReadStatMetadata, columns: Option<&[String]>) {
+    let d = ReadStatData::new().set_no_progress(true);
+    let d = match columns {
+        Some(c) => d.set_columns(c.to_vec()),
+        None => d,
+    };
+    let mut d = d.init(md.clone(), 0, md.row_count as u32);
+    d.read_data(rsp).unwrap();
+    black_box(d.chunk());
+}
+
+fn bench_column_selection(c: &mut Criterion) {
+    let input =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/rand_ds_largepage_ok.sas7bdat");
+    let rsp = ReadStatPath::new(input, None, None, false, false, None, None).unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    assert_eq!(md.var_count, 110);
+
+    // the first variable and the last, whose value ends each row
+    let names: Vec<String> = md.vars.values().map(|vm| vm.var_name.clone()).collect();
+    let selected = vec![names[0].clone(), names[names.len() - 1].clone()];
+
+    let mut group = c.benchmark_group("column_selection");
+    group.sample_size(20);
+    group.bench_function("all_110_columns", |b| b.iter(|| read(&rsp, &md, None)));
+    group.bench_function("2_of_110_columns", |b| b.iter(|| read(&rsp, &md, Some(&selected))));
+    group.finish();
+}
+
+criterion_group!(benches, bench_column_selection);
+criterion_main!(benches);
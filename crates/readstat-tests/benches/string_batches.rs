@@ -0,0 +1,106 @@
+//! Reading a wide file of strings a batch at a time, with the strings of each batch grown from
+//! nothing as before, and sized from the batch before as conversions now do
+//!
+//! The file, of 50,000 rows of 20 strings of up to 60 bytes, is generated ahead of measuring.
+//!
+//! ```sh
+//! cargo bench -p readstat-tests --bench string_batches
+//! ```
+
+use arrow2::{
+    array::{Array, Utf8Array},
+    chunk::Chunk,
+    datatypes::{DataType, Field, Schema},
+};
+use assert_fs::TempDir;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use readstat::prelude::{OutFormat, ReadStatData, ReadStatMetadata, ReadStatPath, ReadStatWriter};
+use std::path::{Path, PathBuf};
+
+const COLUMNS: usize = 20;
+const ROWS: u32 = 50_000;
+const BATCH_ROWS: u32 = 5_000;
+
+// Strings of 8 to 60 bytes, the same upon every run
+fn strings(column: usize) -> Utf8Array<i32> {
+    let mut state = column as u64 + 1;
+    (0..ROWS)
+        .map(|row| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            let len = 8 + (state >> 58) as usize % 53;
+            let value: String = format!("r{}c{}-", row, column)
+                .chars()
+                .cycle()
+                .take(len)
+                .collect();
+            Some(value)
+        })
+        .collect()
+}
+
+fn generate(dir: &Path) -> PathBuf {
+    let out_path = dir.join("wide_strings.sav");
+    let input = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/cars.sas7bdat");
+    let rsp = ReadStatPath::new(
+        input,
+        Some(out_path.clone()),
+        Some(OutFormat::sav),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let schema = Schema::from(
+        (0..COLUMNS)
+            .map(|i| Field::new(format!("s{:02}", i), DataType::Utf8, true))
+            .collect::<Vec<_>>(),
+    );
+    let arrays: Vec<Box<dyn Array>> = (0..COLUMNS).map(|i| strings(i).boxed()).collect();
+    let d = ReadStatData::new().set_chunk(schema, Chunk::new(arrays));
+
+    let mut wtr = ReadStatWriter::new().set_quiet(true);
+    wtr.write(&d, &rsp).unwrap();
+    wtr.finish(&d, &rsp).unwrap();
+
+    out_path
+}
+
+fn read_batches(rsp: &ReadStatPath, md: &ReadStatMetadata, sized: bool) {
+    let mut capacities = None;
+    for row_start in (0..ROWS).step_by(BATCH_ROWS as usize) {
+        let row_end = std::cmp::min(row_start + BATCH_ROWS, ROWS);
+        let mut d = ReadStatData::new()
+            .set_no_progress(true)
+            .set_string_capacities(capacities.take())
+            .init(md.clone(), row_start, row_end);
+        d.read_data(rsp).unwrap();
+
+        if sized {
+            capacities = d.estimate_string_capacities(BATCH_ROWS as usize);
+        }
+        black_box(d.chunk());
+    }
+}
+
+fn bench_string_batches(c: &mut Criterion) {
+    let dir = TempDir::new().unwrap();
+    let rsp =
+        ReadStatPath::new(generate(dir.path()), None, None, false, false, None, None).unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+
+    let mut group = c.benchmark_group("string_batches");
+    group.sample_size(10);
+    group.bench_function("grown_from_nothing", |b| b.iter(|| read_batches(&rsp, &md, false)));
+    group.bench_function("sized_from_previous_batch", |b| {
+        b.iter(|| read_batches(&rsp, &md, true))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_string_batches);
+criterion_main!(benches);
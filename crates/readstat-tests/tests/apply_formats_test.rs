@@ -0,0 +1,133 @@
+use arrow2::datatypes::DataType;
+use assert_cmd::Command;
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert, convert_collect, OutFormat, OutputMode, ReadStatConvertOptions, ReadStatFormatSpec,
+    ReadStatPath,
+};
+
+mod common;
+
+fn write_rates(apply_formats: bool) -> String {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("rates.csv");
+    let rsp = ReadStatPath::new(
+        common::setup_path("rates.xpt").unwrap().path,
+        Some(out_path.clone()),
+        Some(OutFormat::csv),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let options = ReadStatConvertOptions {
+        apply_formats,
+        ..ReadStatConvertOptions::default()
+    };
+    convert(&rsp, &options).unwrap();
+
+    let text = std::fs::read_to_string(&out_path).unwrap();
+    out_dir.close().unwrap();
+    text
+}
+
+fn render(format: &str, v: f64) -> Option<String> {
+    ReadStatFormatSpec::parse(format).unwrap().render(v)
+}
+
+#[test]
+fn formats_are_not_applied_by_default() {
+    let csv = write_rates(false);
+    let expected = "\
+region,amount,rate,total,score
+North,1234.5,0.125,1234567.891,0.5
+South,0.1,-0.032,42,1.25
+East,,1,-1500.5,
+";
+    assert_eq!(csv, expected);
+}
+
+#[test]
+fn formats_are_applied() {
+    let csv = write_rates(true);
+    // E10. is not applied, so score is written as without formats
+    let expected = "\
+region,amount,rate,total,score
+North,1234.50,12.5%,\"$1,234,567.89\",0.5
+South,0.10,(3.2%),$42.00,1.25
+East,,100.0%,\"-$1,500.50\",
+";
+    assert_eq!(csv, expected);
+}
+
+#[test]
+fn arrow_types_are_unchanged() {
+    let rsp = common::setup_path("rates.xpt").unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        apply_formats: true,
+        ..ReadStatConvertOptions::default()
+    };
+
+    let c = convert_collect(&rsp, &options).unwrap();
+    for f in &c.schema.fields[1..] {
+        assert_eq!(f.data_type(), &DataType::Float64);
+    }
+}
+
+#[test]
+fn numerics_rendered_by_format() {
+    assert_eq!(render("8.2", 2.5).as_deref(), Some("2.50"));
+    assert_eq!(render("F8.", 2.5).as_deref(), Some("3"));
+    assert_eq!(render("8.2", -0.001).as_deref(), Some("0.00"));
+    assert_eq!(render("COMMA10.2", -1234567.5).as_deref(), Some("-1,234,567.50"));
+    assert_eq!(render("COMMA6.", 999.0).as_deref(), Some("999"));
+    assert_eq!(render("DOLLAR8.", 1000.0).as_deref(), Some("$1,000"));
+    assert_eq!(render("PERCENT8.1", 0.125).as_deref(), Some("12.5%"));
+    assert_eq!(render("PERCENT6.", -0.5).as_deref(), Some("(50%)"));
+    assert_eq!(render("Z5.", 42.0).as_deref(), Some("00042"));
+    assert_eq!(render("Z6.1", -4.25).as_deref(), Some("-004.3"));
+}
+
+#[test]
+fn best_fits_within_its_width() {
+    assert_eq!(render("BEST12.", 0.5).as_deref(), Some("0.5"));
+    assert_eq!(render("BEST12.", 1.0 / 3.0).as_deref(), Some("0.3333333333"));
+    assert_eq!(render("BEST5.", 1234.5678).as_deref(), Some("1235"));
+    assert_eq!(render("BEST8.", 1.5e20).as_deref(), Some("1.5E20"));
+    // defaults to a width of 12
+    assert_eq!(render("BEST", 123456.0).as_deref(), Some("123456"));
+}
+
+#[test]
+fn other_formats_are_not_rendered() {
+    assert!(render("E10.", 0.5).is_none());
+    assert!(render("DATE9.", 22000.0).is_none());
+    assert!(render("8.2", f64::NAN).is_none());
+}
+
+#[test]
+fn cli_applies_formats() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("rates.csv");
+
+    let output = Command::cargo_bin("readstat")
+        .unwrap()
+        .arg("data")
+        .arg("tests/data/rates.xpt")
+        .arg("--output")
+        .arg(&out_path)
+        .args(["--format", "csv"])
+        .arg("--apply-formats")
+        .arg("--no-progress")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let csv = std::fs::read_to_string(&out_path).unwrap();
+    assert!(csv.lines().nth(1).unwrap().contains(",12.5%,"));
+
+    out_dir.close().unwrap();
+}
@@ -0,0 +1,138 @@
+use arrow2::{
+    array::Array,
+    chunk::Chunk,
+    io::ipc::read::{read_stream_metadata, StreamReader, StreamState},
+};
+use assert_cmd::Command;
+use readstat::prelude::{
+    OutFormat, OutputWriter, ReadStatData, ReadStatMetadata, ReadStatPath, ReadStatWriter,
+    STDOUT_PATH,
+};
+use std::{
+    io::{self, Cursor, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+mod common;
+
+// Output held in memory, as standard out would be read by the other end of a pipe
+#[derive(Clone, Default)]
+struct Sink(Arc<Mutex<Vec<u8>>>);
+
+impl Sink {
+    fn bytes(&self) -> Vec<u8> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl OutputWriter for Sink {
+    fn reopen(&mut self, _len: u64) -> io::Result<()> {
+        Err(io::Error::from(io::ErrorKind::Unsupported))
+    }
+}
+
+// End-of-stream marker
+const EOS: &[u8] = b"\xFF\xFF\xFF\xFF\x00\x00\x00\x00";
+
+// Batches received so far, as a reader polling the stream would
+fn read_stream(bytes: Vec<u8>) -> Vec<Chunk<Box<dyn Array>>> {
+    let mut reader = Cursor::new(bytes);
+    let metadata = read_stream_metadata(&mut reader).unwrap();
+
+    StreamReader::new(reader, metadata, None)
+        .map_while(|s| match s.unwrap() {
+            StreamState::Some(c) => Some(c),
+            StreamState::Waiting => None,
+        })
+        .collect()
+}
+
+fn rsp() -> ReadStatPath {
+    ReadStatPath::new(
+        common::setup_path("all_types.sas7bdat").unwrap().path,
+        Some(PathBuf::from(STDOUT_PATH)),
+        Some(OutFormat::arrow),
+        false,
+        false,
+        None,
+        None,
+    )
+    .unwrap()
+}
+
+#[test]
+fn stdout_is_accepted_as_output() {
+    let rsp = rsp();
+
+    assert!(rsp.writes_to_stdout());
+    assert_eq!(rsp.out_path, Some(PathBuf::from("-")));
+}
+
+#[test]
+fn each_batch_is_written_to_the_stream_as_read() {
+    let rsp = rsp();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+
+    let sink = Sink::default();
+    let mut wtr = ReadStatWriter::new()
+        .set_quiet(true)
+        .set_output(Box::new(sink.clone()));
+
+    // a batch per row
+    let mut chunks = Vec::new();
+    let mut last = None;
+    for row in 0..3 {
+        let mut d = ReadStatData::new()
+            .set_no_progress(true)
+            .init(md.clone(), row, row + 1);
+        d.read_data(&rsp).unwrap();
+        wtr.write(&d, &rsp).unwrap();
+        chunks.push(d.chunk().unwrap().clone());
+
+        // readable before the stream is finished
+        assert_eq!(read_stream(sink.bytes()), chunks);
+        assert!(!sink.bytes().ends_with(EOS));
+        last = Some(d);
+    }
+    wtr.finish(&last.unwrap(), &rsp).unwrap();
+
+    assert_eq!(read_stream(sink.bytes()), chunks);
+    assert!(sink.bytes().ends_with(EOS));
+}
+
+#[test]
+fn cli_writes_stream_to_stdout_and_messages_to_stderr() {
+    let output = Command::cargo_bin("readstat")
+        .unwrap()
+        .arg("data")
+        .arg("tests/data/all_types.sas7bdat")
+        .args(["--format", "arrow"])
+        .args(["--output", "-"])
+        .args(["--stream-rows", "1"])
+        .arg("--no-progress")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    assert!(output.stdout.ends_with(EOS));
+    let chunks = read_stream(output.stdout);
+    assert_eq!(chunks.len(), 3);
+    assert!(chunks.iter().all(|c| c.len() == 1));
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Writing parsed data to standard out"));
+    assert!(stderr.contains("In total, wrote"));
+}
@@ -0,0 +1,99 @@
+use futures::StreamExt;
+use readstat::prelude::{read_async, read_batches, ReadStatConvertOptions, Reader};
+use std::time::Duration;
+
+mod common;
+
+fn options() -> ReadStatConvertOptions {
+    ReadStatConvertOptions {
+        stream_rows: 10,
+        channel_depth: 1,
+        ..ReadStatConvertOptions::default()
+    }
+}
+
+#[tokio::test]
+async fn stream_matches_read_batches() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let batches = read_batches(&rsp, &options()).unwrap();
+    let schema = batches.schema().clone();
+    let expected: Vec<_> = batches.map(|c| c.unwrap()).collect();
+
+    // schema is known ahead of the first chunk
+    let mut stream = read_async(&rsp, &options()).await.unwrap();
+    assert_eq!(stream.schema(), &schema);
+
+    let mut chunks = Vec::new();
+    while let Some(c) = stream.next().await {
+        chunks.push(c.unwrap());
+    }
+    assert_eq!(chunks.len(), 109);
+    assert_eq!(chunks.iter().map(|c| c.len()).sum::<usize>(), 1081);
+    assert_eq!(chunks, expected);
+
+    let summary = stream.collected().await.unwrap();
+    assert_eq!(summary.rows, 1081);
+    assert!(summary.chunks.is_empty());
+}
+
+#[tokio::test]
+async fn stream_read_in_parallel_is_in_row_order() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let parallel = ReadStatConvertOptions {
+        parallel: true,
+        parallelism: Some(4),
+        parallel_min_rows: 0,
+        ..options()
+    };
+
+    let expected: Vec<_> = read_batches(&rsp, &options())
+        .unwrap()
+        .map(|c| c.unwrap())
+        .collect();
+    let chunks: Vec<_> = read_async(&rsp, &parallel)
+        .await
+        .unwrap()
+        .map(|c| c.unwrap())
+        .collect()
+        .await;
+    assert_eq!(chunks, expected);
+}
+
+#[tokio::test]
+async fn stopping_early_stops_reading() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+
+    let mut stream = read_async(&rsp, &options()).await.unwrap();
+    assert_eq!(stream.next().await.unwrap().unwrap().len(), 10);
+
+    // the parse gives up rather than reading on to the end, so nothing is summarized
+    let collected = tokio::time::timeout(Duration::from_secs(10), stream.collected())
+        .await
+        .unwrap();
+    assert!(collected.is_none());
+
+    // a dropped stream stops reading alike
+    let rows: usize = read_async(&rsp, &options())
+        .await
+        .unwrap()
+        .take(2)
+        .map(|c| c.unwrap().len())
+        .fold(0, |acc, rows| async move { acc + rows })
+        .await;
+    assert_eq!(rows, 20);
+}
+
+#[tokio::test]
+async fn errors_while_reading_end_the_stream() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let options = ReadStatConvertOptions {
+        reader: Reader::mem,
+        mem_limit: Some(1024),
+        ..options()
+    };
+
+    let mut stream = read_async(&rsp, &options).await.unwrap();
+    let e = stream.next().await.unwrap().unwrap_err().to_string();
+    assert!(e.contains("above the limit"));
+    assert!(stream.next().await.is_none());
+}
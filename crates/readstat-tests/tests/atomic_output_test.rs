@@ -0,0 +1,240 @@
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert_collect, OutFormat, OutputWriter, ReadStatConvertOptions, ReadStatFileOutput,
+    ReadStatPath,
+};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+mod common;
+
+// cars.sas7bdat has a 1,024 byte header followed by 34 pages of 4,608 bytes
+const HEADER_BYTES: usize = 1024;
+const PAGE_BYTES: usize = 4608;
+
+const ORIGINAL: &str = "the original file\n";
+
+// Copy of cars.sas7bdat whose later pages are zeroed, so that reading fails part way through
+fn damaged_cars(dir: &Path) -> PathBuf {
+    let original = common::setup_path("cars.sas7bdat").unwrap().path;
+    let mut bytes = std::fs::read(original).unwrap();
+
+    let damaged_from = HEADER_BYTES + 20 * PAGE_BYTES;
+    bytes[damaged_from..].iter_mut().for_each(|b| *b = 0);
+
+    let path = dir.join("cars_damaged.sas7bdat");
+    std::fs::write(&path, bytes).unwrap();
+    path
+}
+
+fn rsp(input: PathBuf, out_path: &Path, format: OutFormat, overwrite: bool) -> ReadStatPath {
+    ReadStatPath::new(
+        input,
+        Some(out_path.to_path_buf()),
+        Some(format),
+        overwrite,
+        false,
+        None,
+        None,
+    )
+    .unwrap()
+}
+
+// names of every file within dir, sorted
+fn files(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn existing_output_without_overwrite_is_an_error() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("all_types.csv");
+    std::fs::write(&out_path, ORIGINAL).unwrap();
+
+    let e = ReadStatPath::new(
+        common::setup_path("all_types.sas7bdat").unwrap().path,
+        Some(out_path.clone()),
+        Some(OutFormat::csv),
+        false,
+        false,
+        None,
+        None,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(e.contains("already exists"), "{}", e);
+    assert_eq!(std::fs::read_to_string(&out_path).unwrap(), ORIGINAL);
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn output_appearing_while_converting_is_not_overwritten() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("all_types.parquet");
+    let input = common::setup_path("all_types.sas7bdat").unwrap().path;
+
+    // validated while the output did not yet exist
+    let rsp = rsp(input, &out_path, OutFormat::parquet, true).set_overwrite(false);
+    std::fs::write(&out_path, ORIGINAL).unwrap();
+
+    let e = convert_collect(&rsp, &ReadStatConvertOptions::default())
+        .unwrap_err()
+        .to_string();
+    assert!(e.contains("already exists"), "{}", e);
+    assert_eq!(std::fs::read_to_string(&out_path).unwrap(), ORIGINAL);
+    assert_eq!(files(out_dir.path()), vec!["all_types.parquet"]);
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn overwriting_replaces_the_file_without_debris() {
+    let out_dir = TempDir::new().unwrap();
+    let input = common::setup_path("all_types.sas7bdat").unwrap().path;
+
+    for (format, name) in [
+        (OutFormat::arrow, "all_types.arrow"),
+        (OutFormat::csv, "all_types.csv"),
+        (OutFormat::feather, "all_types.feather"),
+        (OutFormat::ndjson, "all_types.ndjson"),
+        (OutFormat::parquet, "all_types.parquet"),
+        (OutFormat::sav, "all_types.sav"),
+        (OutFormat::dta, "all_types.dta"),
+    ] {
+        let out_path = out_dir.path().join(name);
+        std::fs::write(&out_path, ORIGINAL).unwrap();
+
+        let rsp = rsp(input.clone(), &out_path, format, true);
+        convert_collect(&rsp, &ReadStatConvertOptions::default()).unwrap();
+
+        let written = std::fs::read(&out_path).unwrap();
+        assert_ne!(written, ORIGINAL.as_bytes(), "{}", name);
+        assert!(!files(out_dir.path()).iter().any(|f| f.ends_with(".tmp")), "{}", name);
+    }
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn failed_conversion_leaves_the_original_untouched() {
+    let out_dir = TempDir::new().unwrap();
+    let input = damaged_cars(out_dir.path());
+
+    for (format, name) in [
+        (OutFormat::csv, "cars.csv"),
+        (OutFormat::feather, "cars.feather"),
+        (OutFormat::parquet, "cars.parquet"),
+    ] {
+        let out_path = out_dir.path().join(name);
+        std::fs::write(&out_path, ORIGINAL).unwrap();
+
+        // the first batches are written ahead of reading failing
+        let options = ReadStatConvertOptions {
+            stream_rows: 100,
+            ..ReadStatConvertOptions::default()
+        };
+        let rsp = rsp(input.clone(), &out_path, format, true);
+        assert!(convert_collect(&rsp, &options).is_err(), "{}", name);
+
+        assert_eq!(std::fs::read_to_string(&out_path).unwrap(), ORIGINAL, "{}", name);
+        assert!(!files(out_dir.path()).iter().any(|f| f.ends_with(".tmp")), "{}", name);
+    }
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn file_output_is_moved_into_place_once_committed() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("out.csv");
+
+    // dropped uncommitted, as when a write fails
+    let mut output = ReadStatFileOutput::create(&out_path, false).unwrap();
+    let temp_path = output.temp_path().to_path_buf();
+    assert_eq!(temp_path.parent(), out_path.parent());
+    output.write_all(b"partial").unwrap();
+    assert!(temp_path.exists());
+    assert!(!out_path.exists());
+    drop(output);
+    assert!(files(out_dir.path()).is_empty());
+
+    let mut output = ReadStatFileOutput::create(&out_path, false).unwrap();
+    output.write_all(b"a,b\n1,2\n").unwrap();
+    output.commit().unwrap();
+    drop(output);
+    assert_eq!(std::fs::read_to_string(&out_path).unwrap(), "a,b\n1,2\n");
+    assert_eq!(files(out_dir.path()), vec!["out.csv"]);
+
+    // the file now exists
+    let e = ReadStatFileOutput::create(&out_path, false).err().unwrap();
+    assert_eq!(e.kind(), std::io::ErrorKind::AlreadyExists);
+
+    out_dir.close().unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn named_pipes_are_written_directly() {
+    use readstat::prelude::is_special_file;
+    use std::io::Read;
+
+    let out_dir = TempDir::new().unwrap();
+    let fifo = out_dir.path().join("cars.csv");
+    let made = std::process::Command::new("mkfifo").arg(&fifo).status().unwrap();
+    assert!(made.success());
+
+    // the pipe is read as it is written, so that writing never blocks for want of a reader
+    let reading = {
+        let fifo = fifo.clone();
+        std::thread::spawn(move || {
+            let mut csv = String::new();
+            std::fs::File::open(fifo).unwrap().read_to_string(&mut csv).unwrap();
+            csv
+        })
+    };
+
+    // the pipe already exists, yet is not refused without overwrite
+    let input = common::setup_path("cars.sas7bdat").unwrap().path;
+    let rsp = rsp(input, &fifo, OutFormat::csv, false);
+    assert!(rsp.writes_to_special_file());
+    let c = convert_collect(&rsp, &ReadStatConvertOptions::default()).unwrap();
+    assert!(c.warnings.iter().any(|w| w.contains("written directly")));
+
+    let csv = reading.join().unwrap();
+    assert!(csv.starts_with("Brand,Model"));
+    assert_eq!(csv.lines().count(), 1082);
+
+    // the pipe is neither replaced by a regular file nor accompanied by a temporary file
+    assert!(is_special_file(&fifo));
+    assert_eq!(files(out_dir.path()), vec!["cars.csv"]);
+
+    out_dir.close().unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn dev_stdout_is_written_directly() {
+    use assert_cmd::Command;
+
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("data")
+        .arg("tests/data/cars.sas7bdat")
+        .args(["--output", "/dev/stdout"])
+        .args(["--format", "csv"])
+        .arg("--no-progress");
+    let output = cmd.assert().success().get_output().clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Brand,Model"), "{}", stdout);
+    // messages are kept apart from the data
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("written directly"), "{}", stderr);
+}
@@ -0,0 +1,59 @@
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert_collect, OutFormat, OutputMode, ReadStatCollected, ReadStatConvertOptions, ReadStatPath,
+};
+
+mod common;
+
+// Reads in parallel while writing each small chunk as its own parquet row group, so the
+// writer is the slower side and the channel between them fills
+fn convert_with_depth(channel_depth: usize) -> ReadStatCollected {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("out.parquet");
+
+    let rsp = ReadStatPath::new(
+        common::setup_path("rand_ds_largepage_ok.sas7bdat").unwrap().path,
+        Some(out_path),
+        Some(OutFormat::parquet),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let options = ReadStatConvertOptions {
+        stream_rows: 10,
+        parallel: true,
+        parallel_min_rows: 0,
+        channel_depth,
+        output_mode: OutputMode::WriteAndCollect,
+        ..ReadStatConvertOptions::default()
+    };
+    let collected = convert_collect(&rsp, &options).unwrap();
+
+    out_dir.close().unwrap();
+    collected
+}
+
+#[test]
+fn channel_depth_defaults_to_two_batches() {
+    assert_eq!(ReadStatConvertOptions::default().channel_depth, 2);
+}
+
+#[test]
+fn buffered_batches_never_exceed_default_depth() {
+    let collected = convert_with_depth(2);
+
+    assert_eq!(collected.rows, 2000);
+    assert_eq!(collected.chunks.len(), 200);
+    assert!(collected.peak_buffered <= 2);
+}
+
+#[test]
+fn buffered_batches_never_exceed_depth_of_one() {
+    let collected = convert_with_depth(1);
+
+    assert_eq!(collected.rows, 2000);
+    assert!(collected.peak_buffered <= 1);
+}
@@ -0,0 +1,126 @@
+use arrow2::io::parquet::read::read_metadata;
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert_collect, OutFormat, ReadStatCollected, ReadStatConvertOptions, ReadStatMetadata,
+    ReadStatPath,
+};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+mod common;
+
+// cars.sas7bdat has a 1,024 byte header followed by 34 pages of 4,608 bytes
+const HEADER_BYTES: usize = 1024;
+const PAGE_BYTES: usize = 4608;
+
+// Copy of cars.sas7bdat whose header and early pages are intact but whose later pages are zeroed
+fn damaged_cars(dir: &Path) -> PathBuf {
+    let original = common::setup_path("cars.sas7bdat").unwrap().path;
+    let mut bytes = std::fs::read(original).unwrap();
+
+    let damaged_from = HEADER_BYTES + 20 * PAGE_BYTES;
+    bytes[damaged_from..].iter_mut().for_each(|b| *b = 0);
+
+    let path = dir.join("cars_damaged.sas7bdat");
+    std::fs::write(&path, bytes).unwrap();
+    path
+}
+
+fn convert_damaged(
+    stream_rows: u32,
+    best_effort: bool,
+) -> (
+    Result<ReadStatCollected, Box<dyn std::error::Error + Send + Sync>>,
+    Option<usize>,
+) {
+    let dir = TempDir::new().unwrap();
+    let out_path = dir.path().join("cars_damaged.parquet");
+
+    let rsp = ReadStatPath::new(
+        damaged_cars(dir.path()),
+        Some(out_path.clone()),
+        Some(OutFormat::parquet),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let options = ReadStatConvertOptions {
+        stream_rows,
+        best_effort,
+        ..ReadStatConvertOptions::default()
+    };
+    let collected = convert_collect(&rsp, &options);
+
+    // rows within the output, should a valid file have been written
+    let rows_written = File::open(&out_path)
+        .ok()
+        .and_then(|mut f| read_metadata(&mut f).ok())
+        .map(|md| md.num_rows);
+
+    dir.close().unwrap();
+    (collected, rows_written)
+}
+
+#[test]
+fn damaged_data_is_an_error() {
+    let (collected, _) = convert_damaged(10000, false);
+
+    assert!(collected.is_err());
+}
+
+#[test]
+fn best_effort_metadata_of_damaged_file() {
+    let dir = TempDir::new().unwrap();
+    let rsp = ReadStatPath::new(
+        damaged_cars(dir.path()),
+        None,
+        None,
+        false,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let mut md = ReadStatMetadata::new().set_best_effort(true);
+    md.read_metadata(&rsp, false).unwrap();
+
+    assert_eq!(md.row_count, 1081);
+    assert_eq!(md.var_count, 13);
+    assert_eq!(md.schema.fields.len(), 13);
+
+    dir.close().unwrap();
+}
+
+#[test]
+fn best_effort_recovers_rows_ahead_of_damage() {
+    let (collected, rows_written) = convert_damaged(10000, true);
+    let collected = collected.unwrap();
+
+    let salvage = collected.salvage.unwrap();
+    assert_eq!(salvage.rows_declared, 1081);
+    assert!(salvage.rows_recovered > 0);
+    assert!(salvage.rows_recovered < 1081);
+    assert_eq!(salvage.rows_recovered, collected.rows);
+
+    // a valid, truncated parquet file
+    assert_eq!(rows_written, Some(salvage.rows_recovered));
+    assert!(collected.warnings.iter().any(|w| w.contains("of 1,081 rows")));
+}
+
+#[test]
+fn best_effort_recovers_same_rows_when_streaming() {
+    let (whole, _) = convert_damaged(10000, true);
+    let (streamed, rows_written) = convert_damaged(100, true);
+
+    let recovered = whole.unwrap().salvage.unwrap().rows_recovered;
+    let streamed = streamed.unwrap();
+
+    assert_eq!(streamed.salvage.unwrap().rows_recovered, recovered);
+    assert_eq!(rows_written, Some(recovered));
+}
@@ -0,0 +1,107 @@
+use arrow2::{
+    array::{Array, Float64Array, Utf8Array},
+    chunk::Chunk,
+    datatypes::{DataType, Field, Schema},
+};
+use readstat::prelude::{
+    apply_booleans, detect_boolean_columns, Booleans, ReadStatBooleanDetector, ReadStatData,
+    ReadStatMetadata, ReadStatTypeReason,
+};
+
+mod common;
+
+#[test]
+fn booleans_detector_auto_qualification() {
+    let schema = Schema::from(vec![
+        Field::new("flag", DataType::Float64, true),
+        Field::new("count", DataType::Float64, true),
+        Field::new("empty", DataType::Float64, true),
+        Field::new("name", DataType::Utf8, true),
+    ]);
+
+    let mut detector = ReadStatBooleanDetector::new(&schema);
+
+    // values are observed across chunks
+    detector.push_chunk(&Chunk::new(vec![
+        Float64Array::from(vec![Some(0.0), Some(1.0)]).boxed(),
+        Float64Array::from(vec![Some(0.0), Some(1.0)]).boxed(),
+        Float64Array::from(vec![None, None]).boxed(),
+        Utf8Array::<i32>::from_slice(["0", "1"]).boxed(),
+    ]));
+    detector.push_chunk(&Chunk::new(vec![
+        Float64Array::from(vec![None, Some(1.0)]).boxed(),
+        Float64Array::from(vec![Some(2.0), Some(0.0)]).boxed(),
+        Float64Array::from(vec![None, None]).boxed(),
+        Utf8Array::<i32>::from_slice(["1", "0"]).boxed(),
+    ]));
+
+    // only flag qualifies; count has a 2, empty has no values, and name is not numeric
+    assert_eq!(detector.qualifying(), vec![0]);
+}
+
+#[test]
+fn booleans_auto_on_fixture() {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+
+    // _int contains 1234 and so does not qualify
+    let names = detect_boolean_columns(&rsp, &md, 10000).unwrap();
+    assert!(!names.contains(&String::from("_int")));
+
+    apply_booleans(&rsp, &mut md, &Booleans::Auto, 10000).unwrap();
+    assert!(matches!(md.schema.fields[0].data_type(), DataType::Float64));
+}
+
+#[test]
+fn booleans_explicit_schema() {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    md.set_boolean_columns(&[String::from("_int")], ReadStatTypeReason::UserOverride)
+        .unwrap();
+
+    assert!(matches!(md.schema.fields[0].data_type(), DataType::Boolean));
+    assert_eq!(md.type_decisions[&0].reason, ReadStatTypeReason::UserOverride);
+}
+
+#[test]
+fn booleans_explicit_value_error_names_row() {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    md.set_boolean_columns(&[String::from("_int")], ReadStatTypeReason::UserOverride)
+        .unwrap();
+
+    let mut d = ReadStatData::new()
+        .set_no_progress(true)
+        .init(md.clone(), 0, md.row_count as u32);
+
+    // _int is 1234 within the first row
+    let e = d.read_data(&rsp).unwrap_err().to_string();
+    assert!(e.contains("_int"));
+    assert!(e.contains("row 0"));
+    assert!(e.contains("1234"));
+}
+
+#[test]
+fn booleans_explicit_rejects_non_numeric() {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+
+    // strings and dates cannot be booleans
+    assert!(md
+        .set_boolean_columns(&[String::from("_string")], ReadStatTypeReason::UserOverride)
+        .is_err());
+    assert!(md
+        .set_boolean_columns(&[String::from("_date")], ReadStatTypeReason::UserOverride)
+        .is_err());
+    assert!(md
+        .set_boolean_columns(&[String::from("not_a_var")], ReadStatTypeReason::UserOverride)
+        .is_err());
+}
@@ -0,0 +1,135 @@
+use readstat::prelude::{
+    convert_collect, OutputMode, ReadStatCache, ReadStatCollected, ReadStatConvertOptions,
+};
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+mod common;
+
+fn options() -> ReadStatConvertOptions {
+    ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        ..ReadStatConvertOptions::default()
+    }
+}
+
+fn collect_cached(cache: &ReadStatCache, ds: &str) -> Arc<ReadStatCollected> {
+    let rsp = common::setup_path(ds).unwrap();
+    cache.get_or_collect(&rsp, &options()).unwrap()
+}
+
+#[test]
+fn concurrent_parses_of_same_file_agree() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let expected = convert_collect(&rsp, &options()).unwrap();
+
+    // no cache; every thread parses the same path at once
+    let barrier = Arc::new(Barrier::new(4));
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let barrier = barrier.clone();
+            let rsp = rsp.clone();
+            thread::spawn(move || {
+                barrier.wait();
+                convert_collect(&rsp, &options()).unwrap()
+            })
+        })
+        .collect();
+
+    for h in handles {
+        let c = h.join().unwrap();
+        assert_eq!(c.rows, expected.rows);
+        assert_eq!(c.chunks, expected.chunks);
+    }
+}
+
+#[test]
+fn concurrent_identical_requests_parse_once() {
+    let cache = Arc::new(ReadStatCache::new(usize::MAX));
+    let barrier = Arc::new(Barrier::new(2));
+
+    let handles: Vec<_> = (0..2)
+        .map(|_| {
+            let cache = cache.clone();
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                barrier.wait();
+                collect_cached(&cache, "cars.sas7bdat")
+            })
+        })
+        .collect();
+    let results: Vec<Arc<ReadStatCollected>> =
+        handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+    // both callers receive every row of the one parse
+    for c in &results {
+        assert_eq!(c.rows, 1081);
+    }
+    assert!(Arc::ptr_eq(&results[0], &results[1]));
+
+    // the second caller either waited upon the parse or arrived once it had finished
+    let stats = cache.stats();
+    assert_eq!(stats.parses, 1);
+    assert_eq!(stats.waits + stats.hits, 1);
+}
+
+#[test]
+fn different_options_are_cached_separately() {
+    let cache = ReadStatCache::new(usize::MAX);
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+
+    let all = cache.get_or_collect(&rsp, &options()).unwrap();
+    let some = cache
+        .get_or_collect(
+            &rsp,
+            &ReadStatConvertOptions {
+                rows: Some(10),
+                ..options()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(all.rows, 1081);
+    assert_eq!(some.rows, 10);
+    assert_eq!(cache.stats().parses, 2);
+
+    cache.get_or_collect(&rsp, &options()).unwrap();
+    assert_eq!(cache.stats().parses, 2);
+    assert_eq!(cache.stats().hits, 1);
+}
+
+#[test]
+fn least_recently_used_evicted_beyond_max_bytes() {
+    // bytes of cars alone
+    let sizing = ReadStatCache::new(usize::MAX);
+    collect_cached(&sizing, "cars.sas7bdat");
+    let cars_bytes = sizing.stats().bytes;
+    assert!(cars_bytes > 0);
+
+    let cache = ReadStatCache::new(cars_bytes);
+    collect_cached(&cache, "cars.sas7bdat");
+    collect_cached(&cache, "all_types.sas7bdat");
+
+    // cars is older, so it made room for all_types
+    let stats = cache.stats();
+    assert_eq!(stats.evictions, 1);
+    assert!(stats.bytes <= cars_bytes);
+
+    collect_cached(&cache, "all_types.sas7bdat");
+    assert_eq!(cache.stats().hits, 1);
+    collect_cached(&cache, "cars.sas7bdat");
+    assert_eq!(cache.stats().parses, 3);
+}
+
+#[test]
+fn results_larger_than_max_bytes_are_not_kept() {
+    let cache = ReadStatCache::new(0);
+    for _ in 0..2 {
+        assert_eq!(collect_cached(&cache, "cars.sas7bdat").rows, 1081);
+    }
+
+    let stats = cache.stats();
+    assert_eq!(stats.parses, 2);
+    assert_eq!(stats.bytes, 0);
+    assert_eq!(stats.evictions, 0);
+}
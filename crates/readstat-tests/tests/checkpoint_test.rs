@@ -0,0 +1,224 @@
+use arrow2::io::{
+    ipc::read::{read_file_metadata, FileReader},
+    parquet::read::read_metadata,
+};
+use assert_cmd::Command;
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert_collect, convert_for_each, segment_path, OutFormat, ReadStatCheckpoint,
+    ReadStatConvertOptions, ReadStatPath, ReadStatResumeMismatch,
+};
+use std::{fs::File, path::Path};
+
+mod common;
+
+fn path(out_path: &Path, format: OutFormat, overwrite: bool) -> ReadStatPath {
+    ReadStatPath::new(
+        common::setup_path("cars.sas7bdat").unwrap().path,
+        Some(out_path.to_path_buf()),
+        Some(format),
+        overwrite,
+        false,
+        None,
+        None,
+    )
+    .unwrap()
+}
+
+// 1,081 rows in batches of 100, checkpointed once 200 rows are written, i.e. every second batch
+fn options(resume: bool) -> ReadStatConvertOptions {
+    ReadStatConvertOptions {
+        stream_rows: 100,
+        checkpoint: true,
+        checkpoint_rows: 200,
+        resume,
+        ..ReadStatConvertOptions::default()
+    }
+}
+
+// Convert, failing as the fifth batch is handed over, by when two checkpoints are taken and a
+//   single batch is written since
+fn interrupt(out_path: &Path, format: OutFormat) {
+    let rsp = path(out_path, format, false);
+    let mut batches = 0;
+    let e = convert_for_each(&rsp, &options(false), |_| {
+        batches += 1;
+        if batches == 5 {
+            Err(From::from("Interrupted"))
+        } else {
+            Ok(())
+        }
+    })
+    .unwrap_err();
+    assert!(e.to_string().contains("Interrupted"), "{}", e);
+}
+
+#[test]
+fn interrupted_conversions_leave_a_checkpoint_and_segments() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("cars.csv");
+
+    interrupt(&out_path, OutFormat::csv);
+    assert!(!out_path.exists());
+
+    // csv is appended to a single segment, holding the batch written since the last checkpoint
+    let c = ReadStatCheckpoint::read(&ReadStatCheckpoint::sidecar_path(&out_path)).unwrap();
+    assert_eq!(c.rows_read, 400);
+    assert_eq!(c.rows_written, 400);
+    assert_eq!(c.segments, 0);
+    assert_eq!(c.format, "csv");
+    assert!(c.bytes > 0);
+    assert!(std::fs::metadata(segment_path(&out_path, 0)).unwrap().len() > c.bytes);
+    assert!(!segment_path(&out_path, 1).exists());
+
+    // parquet is written as segments, each finished
+    let out_path = out_dir.path().join("cars.parquet");
+    interrupt(&out_path, OutFormat::parquet);
+    assert!(!out_path.exists());
+
+    let c = ReadStatCheckpoint::read(&ReadStatCheckpoint::sidecar_path(&out_path)).unwrap();
+    assert_eq!(c.rows_written, 400);
+    assert_eq!(c.segments, 2);
+    assert_eq!(c.bytes, 0);
+    assert!(segment_path(&out_path, 0).exists());
+    assert!(segment_path(&out_path, 1).exists());
+    // the unfinished segment is removed
+    assert!(!segment_path(&out_path, 2).exists());
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn resumed_csv_matches_an_uninterrupted_conversion() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("cars.csv");
+    let expected_path = out_dir.path().join("expected.csv");
+
+    convert_collect(
+        &path(&expected_path, OutFormat::csv, false),
+        &ReadStatConvertOptions::default(),
+    )
+    .unwrap();
+
+    interrupt(&out_path, OutFormat::csv);
+    let c = convert_collect(&path(&out_path, OutFormat::csv, false), &options(true)).unwrap();
+    assert_eq!(c.stats.rows_resumed, 400);
+    assert_eq!(c.rows, 681);
+
+    assert_eq!(
+        std::fs::read(&out_path).unwrap(),
+        std::fs::read(&expected_path).unwrap()
+    );
+    // the rows written after the last checkpoint are dropped, and the segment moved into place
+    assert!(!ReadStatCheckpoint::sidecar_path(&out_path).exists());
+    assert!(!segment_path(&out_path, 0).exists());
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn resumed_parquet_and_feather_hold_every_row() {
+    let out_dir = TempDir::new().unwrap();
+
+    let out_path = out_dir.path().join("cars.parquet");
+    interrupt(&out_path, OutFormat::parquet);
+    convert_collect(&path(&out_path, OutFormat::parquet, false), &options(true)).unwrap();
+    let md = read_metadata(&mut File::open(&out_path).unwrap()).unwrap();
+    assert_eq!(md.num_rows, 1081);
+
+    let out_path = out_dir.path().join("cars.feather");
+    interrupt(&out_path, OutFormat::feather);
+    convert_collect(&path(&out_path, OutFormat::feather, false), &options(true)).unwrap();
+    let mut f = File::open(&out_path).unwrap();
+    let md = read_file_metadata(&mut f).unwrap();
+    let rows: usize = FileReader::new(f, md, None, None)
+        .map(|c| c.unwrap().len())
+        .sum();
+    assert_eq!(rows, 1081);
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn resuming_with_different_options_is_refused() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("cars.csv");
+
+    interrupt(&out_path, OutFormat::csv);
+    let e = convert_collect(
+        &path(&out_path, OutFormat::csv, false),
+        &ReadStatConvertOptions {
+            rows: Some(500),
+            ..options(true)
+        },
+    )
+    .unwrap_err();
+    let mismatch = e.downcast_ref::<ReadStatResumeMismatch>().unwrap();
+    assert_eq!(mismatch.checkpoint, ReadStatCheckpoint::sidecar_path(&out_path));
+
+    // options that only change how the conversion runs are not compared
+    let c = convert_collect(
+        &path(&out_path, OutFormat::csv, false),
+        &ReadStatConvertOptions {
+            stream_rows: 300,
+            ..options(true)
+        },
+    )
+    .unwrap();
+    assert_eq!(c.stats.rows_resumed, 400);
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn an_existing_checkpoint_needs_resume_or_overwrite() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("cars.csv");
+
+    interrupt(&out_path, OutFormat::csv);
+    let e = convert_collect(&path(&out_path, OutFormat::csv, false), &options(false)).unwrap_err();
+    assert!(e.to_string().contains("--resume"), "{}", e);
+
+    // starting afresh discards the checkpoint
+    let c = convert_collect(&path(&out_path, OutFormat::csv, true), &options(false)).unwrap();
+    assert_eq!(c.stats.rows_resumed, 0);
+    assert_eq!(c.rows, 1081);
+    assert!(!ReadStatCheckpoint::sidecar_path(&out_path).exists());
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn cli_checkpoints_and_cleans_up() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("cars.parquet");
+
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("data")
+        .arg("tests/data/cars.sas7bdat")
+        .arg("--output")
+        .arg(&out_path)
+        .args(["--format", "parquet"])
+        .args(["--stream-rows", "100"])
+        .args(["--checkpoint-rows", "200"])
+        .arg("--resume")
+        .arg("--no-progress");
+    cmd.assert().success();
+
+    let md = read_metadata(&mut File::open(&out_path).unwrap()).unwrap();
+    assert_eq!(md.num_rows, 1081);
+    assert!(!ReadStatCheckpoint::sidecar_path(&out_path).exists());
+    assert!(!segment_path(&out_path, 0).exists());
+
+    // checkpoints cannot be kept for split output
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("data")
+        .arg("tests/data/cars.sas7bdat")
+        .arg("--output")
+        .arg(out_dir.path().join("split.csv"))
+        .args(["--split-rows", "200"])
+        .arg("--checkpoint");
+    cmd.assert().failure();
+
+    out_dir.close().unwrap();
+}
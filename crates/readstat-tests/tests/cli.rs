@@ -13,3 +13,15 @@ fn cli_file_does_not_exist() -> Result<(), Box<dyn std::error::Error>> {
     }
     Ok(())
 }
+
+#[test]
+fn cli_version_info() -> Result<(), Box<dyn std::error::Error>> {
+    if let Ok(mut cmd) = Command::cargo_bin("readstat") {
+        cmd.arg("--version-info");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("ReadStat"))
+            .stdout(predicate::str::contains("parquet"));
+    }
+    Ok(())
+}
@@ -2,7 +2,7 @@ use ::predicates::prelude::*; // Used for writing assertions
 use assert_cmd::Command; // Add methods on commands
 use assert_fs::NamedTempFile;
 use polars::prelude::*;
-use readstat::ParquetCompression;
+use readstat::prelude::ParquetCompression;
 use std::{fs::File, path::PathBuf, result::Result};
 
 enum OverwriteOption {
@@ -0,0 +1,119 @@
+use arrow2::array::Utf8Array;
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert_collect, OutFormat, OutputMode, ReadStatConvertOptions, ReadStatPath,
+};
+
+mod common;
+
+// _string column
+const VAR_INDEX: usize = 3;
+
+fn strings(chunk: &arrow2::chunk::Chunk<Box<dyn arrow2::array::Array>>) -> Vec<Option<String>> {
+    chunk.arrays()[VAR_INDEX]
+        .as_any()
+        .downcast_ref::<Utf8Array<i32>>()
+        .unwrap()
+        .iter()
+        .map(|s| s.map(String::from))
+        .collect()
+}
+
+#[test]
+fn collect_retains_every_chunk_in_order() {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+
+    // a chunk for every row
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        stream_rows: 1,
+        ..ReadStatConvertOptions::default()
+    };
+    let collected = convert_collect(&rsp, &options).unwrap();
+
+    assert_eq!(collected.rows, 3);
+    assert_eq!(collected.chunks.len(), 3);
+    assert!(collected.chunks.iter().all(|c| c.len() == 1));
+    assert_eq!(collected.schema.fields.len(), 8);
+
+    // in file order
+    let values: Vec<Option<String>> = collected.chunks.iter().flat_map(strings).collect();
+    assert_eq!(values[0], Some(String::from("string")));
+    assert_eq!(values[1], Some(String::from("stringy string")));
+}
+
+#[test]
+fn collect_in_parallel_is_in_order() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+
+    let serial = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        stream_rows: 10,
+        ..ReadStatConvertOptions::default()
+    };
+    let parallel = ReadStatConvertOptions {
+        parallel: true,
+        parallel_min_rows: 0,
+        ..serial.clone()
+    };
+
+    let s = convert_collect(&rsp, &serial).unwrap();
+    let p = convert_collect(&rsp, &parallel).unwrap();
+
+    assert_eq!(s.rows, 1081);
+    assert_eq!(p.chunks.len(), 109);
+    assert_eq!(s.chunks, p.chunks);
+}
+
+#[test]
+fn write_and_collect_does_both() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("all_types.csv");
+
+    let rsp = ReadStatPath::new(
+        common::setup_path("all_types.sas7bdat").unwrap().path,
+        Some(out_path.clone()),
+        Some(OutFormat::csv),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::WriteAndCollect,
+        ..ReadStatConvertOptions::default()
+    };
+    let collected = convert_collect(&rsp, &options).unwrap();
+
+    assert_eq!(collected.chunks.len(), 1);
+    assert!(out_path.exists());
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn write_collects_nothing() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("all_types.csv");
+
+    let rsp = ReadStatPath::new(
+        common::setup_path("all_types.sas7bdat").unwrap().path,
+        Some(out_path.clone()),
+        Some(OutFormat::csv),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let collected = convert_collect(&rsp, &ReadStatConvertOptions::default()).unwrap();
+
+    assert_eq!(collected.rows, 3);
+    assert!(collected.chunks.is_empty());
+    assert!(out_path.exists());
+
+    out_dir.close().unwrap();
+}
@@ -0,0 +1,163 @@
+use arrow2::datatypes::DataType;
+use readstat::prelude::{
+    convert_collect, NameTransform, OutputMode, ReadStatCollected, ReadStatColumnSpec,
+    ReadStatConvertOptions, ReadStatMetadata, ReadStatRenameReason,
+};
+
+mod common;
+
+// drops _char, renames _string and _float, and casts _int and _float
+const SPEC: &str = "\
+variable,keep,new_name,type
+_char,no,,
+_string,,text,
+_int,yes,,Int32
+_float,,ratio,Float32
+";
+
+fn spec(text: &str) -> ReadStatColumnSpec {
+    ReadStatColumnSpec::from_reader("spec.csv", text.as_bytes()).unwrap()
+}
+
+fn all_types_metadata() -> ReadStatMetadata {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    md
+}
+
+fn collect_all_types(options: ReadStatConvertOptions) -> ReadStatCollected {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        ..options
+    };
+    convert_collect(&rsp, &options).unwrap()
+}
+
+fn names(c: &ReadStatCollected) -> Vec<&str> {
+    c.schema.fields.iter().map(|f| f.name.as_str()).collect()
+}
+
+#[test]
+fn spec_selects_renames_and_casts() {
+    let c = collect_all_types(ReadStatConvertOptions {
+        column_spec: Some(spec(SPEC)),
+        ..ReadStatConvertOptions::default()
+    });
+
+    assert_eq!(
+        names(&c),
+        vec!["_int", "ratio", "text", "_date", "_datetime", "_datetime_with_ms", "_time"]
+    );
+    assert_eq!(c.schema.fields[0].data_type(), &DataType::Int32);
+    assert_eq!(c.schema.fields[1].data_type(), &DataType::Float32);
+    assert_eq!(c.schema.fields[2].data_type(), &DataType::Utf8);
+    assert!(!c.warnings.iter().any(|w| w.contains("column spec")));
+
+    // arrays agree with the schema
+    assert!(c.rows > 0);
+    for chunk in &c.chunks {
+        assert_eq!(chunk.arrays().len(), 7);
+        for (array, field) in chunk.arrays().iter().zip(c.schema.fields.iter()) {
+            assert_eq!(array.data_type(), field.data_type());
+        }
+    }
+}
+
+#[test]
+fn spec_populates_metadata() {
+    let mut md = all_types_metadata();
+    let warnings = spec(SPEC).apply(&mut md, &[]).unwrap();
+    assert!(warnings.is_empty());
+
+    // variables keep their indices within the file
+    let selection = md.selection.clone().unwrap();
+    assert!(!selection.contains(&2));
+    assert_eq!(selection.len(), 7);
+    assert_eq!(md.casts[&0], DataType::Int32);
+    assert_eq!(md.casts[&1], DataType::Float32);
+    assert_eq!(md.renames[&3].final_name, "text");
+    assert_eq!(md.renames[&3].reason, ReadStatRenameReason::ColumnSpec);
+
+    // only the selected variables, with their casts
+    let selected = md.selected();
+    assert_eq!(selected.var_count, 7);
+    assert!(!selected.vars.contains_key(&2));
+    assert_eq!(selected.schema.fields.len(), 7);
+    assert_eq!(selected.schema.fields[0].data_type(), &DataType::Int32);
+}
+
+#[test]
+fn every_problem_reported_with_its_line() {
+    let mut md = all_types_metadata();
+    let s = spec(
+        "\
+variable,keep,new_name,type
+_nope,,,
+_int,,_float,
+_int,no,,
+",
+    );
+
+    let e = s.apply(&mut md, &[]).unwrap_err().to_string();
+    assert!(e.contains("spec.csv"));
+    assert!(e.contains("line 2: the variable"));
+    assert!(e.contains("does not exist"));
+    assert!(e.contains("line 3: "));
+    assert!(e.contains("would name both the variables"));
+    assert!(e.contains("line 4: the variable"));
+    assert!(e.contains("is already listed on line 3"));
+
+    // nothing is changed
+    assert!(md.renames.is_empty());
+    assert!(md.selection.is_none());
+    assert!(md.casts.is_empty());
+}
+
+#[test]
+fn malformed_rows_reported_while_parsing() {
+    let e = ReadStatColumnSpec::from_reader(
+        "spec.csv",
+        "variable,keep,new_name,type\n_int,,,Integer\n_float,maybe,,\n,,other,\n".as_bytes(),
+    )
+    .unwrap_err()
+    .to_string();
+
+    assert!(e.contains("line 2: The Arrow type"));
+    assert!(e.contains("is not supported"));
+    assert!(e.contains("line 3: keep is"));
+    assert!(e.contains("line 4: the variable is missing"));
+
+    // headers are matched regardless of case, yet variable is required
+    let s = ReadStatColumnSpec::from_reader("spec.csv", "Variable,Keep\n_int,no\n".as_bytes());
+    assert!(!s.unwrap().rows[0].keep);
+    let e = ReadStatColumnSpec::from_reader("spec.csv", "name,keep\n_int,no\n".as_bytes())
+        .unwrap_err()
+        .to_string();
+    assert!(e.contains("has no"));
+}
+
+#[test]
+fn flags_naming_a_variable_take_precedence_over_the_spec() {
+    let c = collect_all_types(ReadStatConvertOptions {
+        column_spec: Some(spec(SPEC)),
+        renames: vec![(String::from("_string"), String::from("s"))],
+        name_transform: NameTransform::Uppercase,
+        ..ReadStatConvertOptions::default()
+    });
+
+    // --rename wins over the spec, which wins over --name-transform
+    assert_eq!(
+        names(&c),
+        vec!["_INT", "ratio", "s", "_DATE", "_DATETIME", "_DATETIME_WITH_MS", "_TIME"]
+    );
+    let warnings: Vec<&String> = c
+        .warnings
+        .iter()
+        .filter(|w| w.contains("column spec"))
+        .collect();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("line 3 of the column spec"));
+    assert!(warnings[0].contains("new_name is ignored"));
+}
@@ -1,31 +1,35 @@
 use path_abs::PathAbs;
+use readstat::prelude::{
+    ReadStatData, ReadStatPath, ReadStatVarFormatClass, ReadStatVarMetadata, ReadStatVarType,
+    ReadStatVarTypeClass,
+};
 use std::{error::Error, result::Result};
 
 #[allow(dead_code)]
-pub fn contains_var(d: &readstat::ReadStatData, var_index: i32) -> bool {
+pub fn contains_var(d: &ReadStatData, var_index: i32) -> bool {
     // contains variable
-    d.vars.contains_key(&var_index)
+    d.vars().contains_key(&var_index)
 }
 
 #[allow(dead_code)]
-pub fn get_metadata(d: &readstat::ReadStatData, var_index: i32) -> &readstat::ReadStatVarMetadata {
+pub fn get_metadata(d: &ReadStatData, var_index: i32) -> &ReadStatVarMetadata {
     // contains variable
-    d.vars.get(&var_index).unwrap()
+    d.vars().get(&var_index).unwrap()
 }
 
 #[allow(dead_code)]
 pub fn get_var_attrs(
-    d: &readstat::ReadStatData,
+    d: &ReadStatData,
     var_index: i32,
 ) -> (
-    readstat::ReadStatVarTypeClass,
-    readstat::ReadStatVarType,
-    Option<readstat::ReadStatVarFormatClass>,
+    ReadStatVarTypeClass,
+    ReadStatVarType,
+    Option<ReadStatVarFormatClass>,
     String,
     &arrow2::datatypes::DataType,
 ) {
     let m = get_metadata(d, var_index);
-    let s = &d.schema;
+    let s = d.schema();
     (
         m.var_type_class,
         m.var_type,
@@ -36,7 +40,7 @@ pub fn get_var_attrs(
 }
 
 #[allow(dead_code)]
-pub fn setup_path<P>(ds: P) -> Result<readstat::ReadStatPath, Box<dyn Error + Send + Sync>>
+pub fn setup_path<P>(ds: P) -> Result<ReadStatPath, Box<dyn Error + Send + Sync>>
 where
     P: AsRef<std::path::Path>,
 {
@@ -47,5 +51,5 @@ where
         .join("tests")
         .join("data")
         .join(ds);
-    readstat::ReadStatPath::new(sas_path, None, None, false, false, None, None)
+    ReadStatPath::new(sas_path, None, None, false, false, None, None)
 }
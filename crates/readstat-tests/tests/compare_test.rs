@@ -0,0 +1,131 @@
+use assert_cmd::Command;
+use readstat::prelude::{compare_metadata, PropertyDiff, ReadStatMetadata, VariableDiff};
+use serde_json::Value;
+
+mod common;
+
+fn metadata(file: &str) -> ReadStatMetadata {
+    let rsp = common::setup_path(file).unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, true).unwrap();
+    md
+}
+
+fn change(property: &str, left: &str, right: &str) -> PropertyDiff {
+    PropertyDiff {
+        property: String::from(property),
+        left: String::from(left),
+        right: String::from(right),
+    }
+}
+
+fn changed(name: &str, changes: Vec<PropertyDiff>) -> VariableDiff {
+    VariableDiff {
+        name: String::from(name),
+        changes,
+    }
+}
+
+#[test]
+fn differences_are_reported_by_variable_and_file() {
+    let diff = compare_metadata(
+        &metadata("all_types.sas7bdat"),
+        &metadata("all_types_modified.xpt"),
+    );
+
+    assert_eq!(diff.added, vec!["_flag"]);
+    assert_eq!(diff.removed, vec!["_char", "_datetime", "_datetime_with_ms"]);
+    assert_eq!(
+        diff.changed,
+        vec![
+            changed("_int", vec![change("label", "", "Integer")]),
+            changed("_string", vec![change("format", "$30", "$40")]),
+            changed("_date", vec![change("format", "YYMMDD10", "DATE9")]),
+            changed(
+                "_time",
+                vec![
+                    change("type", "Double", "String"),
+                    change("format", "TIME", "$8"),
+                    change("arrow_type", "Time32(Second)", "Utf8"),
+                ]
+            ),
+        ]
+    );
+    assert_eq!(diff.file, vec![change("encoding", "UTF-8", ""), change("version", "9", "5")]);
+    assert_eq!(diff.len(), 12);
+
+    assert_eq!(
+        diff.lines(),
+        vec![
+            "~ encoding: \"UTF-8\" -> \"\"",
+            "~ version: \"9\" -> \"5\"",
+            "- _char",
+            "- _datetime",
+            "- _datetime_with_ms",
+            "+ _flag",
+            "~ _int label: \"\" -> \"Integer\"",
+            "~ _string format: \"$30\" -> \"$40\"",
+            "~ _date format: \"YYMMDD10\" -> \"DATE9\"",
+            "~ _time type: \"Double\" -> \"String\"",
+            "~ _time format: \"TIME\" -> \"$8\"",
+            "~ _time arrow_type: \"Time32(Second)\" -> \"Utf8\"",
+        ]
+    );
+}
+
+#[test]
+fn comparing_the_other_way_swaps_each_difference() {
+    let diff = compare_metadata(
+        &metadata("all_types_modified.xpt"),
+        &metadata("all_types.sas7bdat"),
+    );
+
+    assert_eq!(diff.added, vec!["_char", "_datetime", "_datetime_with_ms"]);
+    assert_eq!(diff.removed, vec!["_flag"]);
+    assert_eq!(diff.changed[0], changed("_int", vec![change("label", "Integer", "")]));
+    assert_eq!(diff.file[1], change("version", "5", "9"));
+}
+
+#[test]
+fn a_file_does_not_differ_from_itself() {
+    let md = metadata("all_types.sas7bdat");
+    let diff = compare_metadata(&md, &md);
+
+    assert!(diff.is_empty());
+    assert!(diff.lines().is_empty());
+}
+
+#[test]
+fn cli_fails_when_the_files_differ() {
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("compare")
+        .arg("tests/data/all_types.sas7bdat")
+        .arg("tests/data/all_types_modified.xpt");
+    let output = cmd.assert().failure().get_output().clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("- _char"), "{}", stdout);
+    assert!(stdout.contains("+ _flag"), "{}", stdout);
+    assert!(String::from_utf8(output.stderr).unwrap().contains("12 differences"));
+
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("compare")
+        .arg("tests/data/all_types.sas7bdat")
+        .arg("tests/data/all_types_modified.xpt")
+        .arg("--as-json");
+    let output = cmd.assert().failure().get_output().stdout.clone();
+    let v: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(v["added"][0], "_flag");
+    assert_eq!(v["changed"][3]["name"], "_time");
+    assert_eq!(v["changed"][3]["changes"][2]["right"], "Utf8");
+    assert_eq!(v["file"][1]["property"], "version");
+}
+
+#[test]
+fn cli_succeeds_when_the_files_do_not_differ() {
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("compare")
+        .arg("tests/data/all_types.sas7bdat")
+        .arg("tests/data/all_types.sas7bdat");
+    let output = cmd.assert().success().get_output().stdout.clone();
+    assert!(String::from_utf8(output).unwrap().contains("do not differ"));
+}
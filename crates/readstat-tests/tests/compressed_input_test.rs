@@ -0,0 +1,148 @@
+use assert_cmd::Command;
+use assert_fs::TempDir;
+use flate2::{write::GzEncoder, Compression};
+use readstat::prelude::{
+    convert_collect, convert_many, InputCompression, OutFormat, ReadStatConvertOptions,
+    ReadStatMetadata, ReadStatPath,
+};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+mod common;
+
+fn all_types_bytes() -> Vec<u8> {
+    std::fs::read(common::setup_path("all_types.sas7bdat").unwrap().path).unwrap()
+}
+
+fn gzip(bytes: &[u8]) -> Vec<u8> {
+    let mut e = GzEncoder::new(Vec::new(), Compression::default());
+    e.write_all(bytes).unwrap();
+    e.finish().unwrap()
+}
+
+fn zstd(bytes: &[u8]) -> Vec<u8> {
+    zstd::encode_all(bytes, 3).unwrap()
+}
+
+fn write(dir: &Path, name: &str, bytes: &[u8]) -> PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, bytes).unwrap();
+    path
+}
+
+// csv written from the input at path
+fn to_csv(path: PathBuf, out_path: &Path) -> String {
+    let rsp = ReadStatPath::new(
+        path,
+        Some(out_path.to_path_buf()),
+        Some(OutFormat::csv),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+    convert_collect(&rsp, &ReadStatConvertOptions::default()).unwrap();
+    std::fs::read_to_string(out_path).unwrap()
+}
+
+#[test]
+fn compressed_inputs_are_written_as_the_uncompressed_file() {
+    let dir = TempDir::new().unwrap();
+    let bytes = all_types_bytes();
+    let expected = to_csv(
+        common::setup_path("all_types.sas7bdat").unwrap().path,
+        &dir.path().join("plain.csv"),
+    );
+
+    for (name, compressed, compression) in [
+        ("all_types.sas7bdat.gz", gzip(&bytes), InputCompression::Gzip),
+        ("all_types.sas7bdat.zst", zstd(&bytes), InputCompression::Zstd),
+        // detected by its magic bytes alone
+        ("all_types.sas7bdat", gzip(&bytes), InputCompression::Gzip),
+    ] {
+        let sub = dir.path().join(name.replace('.', "_"));
+        std::fs::create_dir(&sub).unwrap();
+        let path = write(&sub, name, &compressed);
+
+        let rsp = ReadStatPath::new(path.clone(), None, None, false, false, None, None).unwrap();
+        assert_eq!(rsp.input_compression, Some(compression), "{}", name);
+        assert_eq!(rsp.extension, "sas7bdat");
+
+        let mut md = ReadStatMetadata::new();
+        md.read_metadata(&rsp, false).unwrap();
+        assert_eq!(md.row_count, 3);
+
+        assert_eq!(to_csv(path, &sub.join("all_types.csv")), expected, "{}", name);
+    }
+
+    dir.close().unwrap();
+}
+
+#[test]
+fn uncompressed_inputs_are_read_as_files() {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    assert_eq!(rsp.input_compression, None);
+    assert!(rsp.source().unwrap().is_none());
+}
+
+#[test]
+fn decompressing_beyond_the_limit_is_an_error() {
+    let dir = TempDir::new().unwrap();
+    let bytes = all_types_bytes();
+    let path = write(dir.path(), "all_types.sas7bdat.gz", &gzip(&bytes));
+
+    let rsp = ReadStatPath::new(path, None, None, false, false, None, None)
+        .unwrap()
+        .set_max_decompressed_bytes(bytes.len() as u64 - 1);
+    let mut md = ReadStatMetadata::new();
+    let e = md.read_metadata(&rsp, false).unwrap_err().to_string();
+    assert!(e.contains("decompresses to more than"), "{}", e);
+
+    // exactly the limit is read
+    let rsp = rsp.set_max_decompressed_bytes(bytes.len() as u64);
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+
+    dir.close().unwrap();
+}
+
+#[test]
+fn outputs_are_named_without_the_compression_extension() {
+    let dir = TempDir::new().unwrap();
+    let path = write(dir.path(), "all_types.sas7bdat.gz", &gzip(&all_types_bytes()));
+    let out_dir = dir.path().join("out");
+    std::fs::create_dir(&out_dir).unwrap();
+
+    let options = ReadStatConvertOptions {
+        out_dir: Some(out_dir.clone()),
+        format: OutFormat::parquet,
+        ..ReadStatConvertOptions::default()
+    };
+    let results = convert_many(&[path], &options);
+    assert!(results[0].is_ok());
+    assert_eq!(results[0].rows, 3);
+    assert_eq!(results[0].output, Some(out_dir.join("all_types.parquet")));
+
+    dir.close().unwrap();
+}
+
+#[test]
+fn cli_reads_compressed_inputs() {
+    let dir = TempDir::new().unwrap();
+    let path = write(dir.path(), "all_types.sas7bdat.gz", &gzip(&all_types_bytes()));
+    let out_path = dir.path().join("all_types.csv");
+
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("data")
+        .arg(&path)
+        .arg("--output")
+        .arg(&out_path)
+        .arg("--no-progress");
+    cmd.assert().success();
+    assert_eq!(std::fs::read_to_string(&out_path).unwrap().lines().count(), 4);
+
+    dir.close().unwrap();
+}
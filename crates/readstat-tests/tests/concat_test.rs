@@ -0,0 +1,239 @@
+use arrow2::array::{Float64Array, Utf8Array};
+use assert_cmd::Command;
+use assert_fs::TempDir;
+use readstat::prelude::{
+    OutFormat, OutputMode, ReadStatCancelled, ReadStatConcat, ReadStatConvertOptions,
+    ReadStatPath, ReadStatSchema, ReadStatSchemaMismatch,
+};
+use std::{
+    ops::ControlFlow,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+mod common;
+
+fn inputs(months: &[&str]) -> Vec<ReadStatPath> {
+    months
+        .iter()
+        .map(|m| common::setup_path(format!("sales_2023_{}.xpt", m)).unwrap())
+        .collect()
+}
+
+fn output(out_path: &Path, format: OutFormat) -> ReadStatPath {
+    ReadStatPath::new(
+        common::setup_path("sales_2023_01.xpt").unwrap().path,
+        Some(out_path.to_path_buf()),
+        Some(format),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap()
+}
+
+fn collect() -> ReadStatConvertOptions {
+    ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        ..ReadStatConvertOptions::default()
+    }
+}
+
+#[test]
+fn rows_of_every_input_are_stacked_in_order() {
+    let rsp = common::setup_path("sales_2023_01.xpt").unwrap();
+    let c = ReadStatConcat::new(inputs(&["01", "02"]))
+        .convert(&rsp, &collect())
+        .unwrap();
+
+    assert_eq!(c.rows, 5);
+    assert_eq!(c.inputs.len(), 2);
+    assert_eq!(c.inputs[0].rows, 2);
+    assert_eq!(c.inputs[1].rows, 3);
+
+    let names: Vec<&str> = c.schema.fields.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, vec!["id", "region", "amount"]);
+
+    let ids: Vec<Option<f64>> = c
+        .chunks
+        .iter()
+        .flat_map(|chunk| {
+            chunk.arrays()[0]
+                .as_any()
+                .downcast_ref::<Float64Array>()
+                .unwrap()
+                .iter()
+                .map(|v| v.copied())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    assert_eq!(ids, vec![Some(1.0), Some(2.0), Some(3.0), Some(4.0), Some(5.0)]);
+
+    let regions = c.chunks.last().unwrap().arrays()[1]
+        .as_any()
+        .downcast_ref::<Utf8Array<i32>>()
+        .unwrap();
+    assert_eq!(regions.value(regions.len() - 1), "north");
+}
+
+#[test]
+fn a_type_mismatch_is_an_error_naming_the_columns() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("sales.parquet");
+    let rsp = output(&out_path, OutFormat::parquet);
+
+    let e = ReadStatConcat::new(inputs(&["01", "02", "03"]))
+        .convert(&rsp, &ReadStatConvertOptions::default())
+        .unwrap_err();
+
+    let mismatch = e.downcast_ref::<ReadStatSchemaMismatch>().unwrap();
+    assert!(mismatch.expected.ends_with("sales_2023_01.xpt"));
+    assert!(mismatch.found.ends_with("sales_2023_03.xpt"));
+    assert_eq!(
+        mismatch.diff,
+        vec![
+            String::from("- 3: amount Float64"),
+            String::from("+ 3: amount Utf8")
+        ]
+    );
+    assert!(e.to_string().contains("- 3: amount Float64\n+ 3: amount Utf8"));
+
+    // schemas are compared ahead of writing anything
+    assert!(!out_path.exists());
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn parquet_output_holds_every_row() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("sales.parquet");
+    let rsp = output(&out_path, OutFormat::parquet);
+
+    let options = ReadStatConvertOptions {
+        stream_rows: 1,
+        ..ReadStatConvertOptions::default()
+    };
+    let c = ReadStatConcat::new(inputs(&["01", "02"]))
+        .convert(&rsp, &options)
+        .unwrap();
+    assert_eq!(c.rows, 5);
+    assert!(c.chunks.is_empty());
+
+    let mut f = std::fs::File::open(&out_path).unwrap();
+    let md = arrow2::io::parquet::read::read_metadata(&mut f).unwrap();
+    assert_eq!(md.num_rows, 5);
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn labels_of_the_first_input_win() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("sales.csv");
+    let rsp = output(&out_path, OutFormat::csv);
+
+    let options = ReadStatConvertOptions {
+        schema_sidecar: true,
+        ..ReadStatConvertOptions::default()
+    };
+    ReadStatConcat::new(inputs(&["01", "02"]))
+        .convert(&rsp, &options)
+        .unwrap();
+
+    // the header is written once
+    let csv = std::fs::read_to_string(&out_path).unwrap();
+    assert_eq!(csv.lines().count(), 6);
+    assert_eq!(csv.matches("region").count(), 1);
+
+    let schema = ReadStatSchema::read(&ReadStatSchema::sidecar_path(&out_path)).unwrap();
+    assert_eq!(schema.columns[2].label, "Sale amount");
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn progress_accounts_for_every_input() {
+    let rsp = common::setup_path("sales_2023_01.xpt").unwrap();
+    let options = ReadStatConvertOptions {
+        stream_rows: 1,
+        ..collect()
+    };
+
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let seen = calls.clone();
+    ReadStatConcat::new(inputs(&["01", "02"]))
+        .set_progress_callback(Box::new(move |fraction, rows| {
+            seen.lock().unwrap().push((fraction, rows));
+            ControlFlow::Continue(())
+        }))
+        .convert(&rsp, &options)
+        .unwrap();
+
+    let calls = calls.lock().unwrap();
+    assert_eq!(calls.len(), 5);
+    assert_eq!(calls[1], (0.4, 2));
+    assert_eq!(calls[4], (1.0, 5));
+
+    // cancelling stops across inputs as within one
+    let e = ReadStatConcat::new(inputs(&["01", "02"]))
+        .set_progress_callback(Box::new(|_, rows| {
+            if rows < 3 {
+                ControlFlow::Continue(())
+            } else {
+                ControlFlow::Break(())
+            }
+        }))
+        .convert(&rsp, &options)
+        .unwrap_err();
+    assert_eq!(e.downcast_ref::<ReadStatCancelled>().unwrap().rows, 3);
+}
+
+#[test]
+fn cli_concatenates_every_input() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("sales.csv");
+
+    let output = Command::cargo_bin("readstat")
+        .unwrap()
+        .arg("data")
+        .arg("tests/data/sales_2023_01.xpt")
+        .arg("tests/data/sales_2023_02.xpt")
+        .arg("--output")
+        .arg(&out_path)
+        .arg("--no-progress")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let csv = std::fs::read_to_string(&out_path).unwrap();
+    assert_eq!(csv.lines().count(), 6);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("from 2 files"));
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn cli_displays_the_schema_diff() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("sales.csv");
+
+    let output = Command::cargo_bin("readstat")
+        .unwrap()
+        .arg("data")
+        .arg("tests/data/sales_2023_01.xpt")
+        .arg("tests/data/sales_2023_03.xpt")
+        .arg("--output")
+        .arg(&out_path)
+        .arg("--no-progress")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("+ 3: amount Utf8"));
+    assert!(!out_path.exists());
+
+    out_dir.close().unwrap();
+}
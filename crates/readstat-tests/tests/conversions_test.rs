@@ -0,0 +1,107 @@
+use arrow2::datatypes::TimeUnit;
+use chrono::{Duration, NaiveDate};
+use readstat::prelude::{
+    sas_days_to_date32, sas_seconds_to_time32, sas_seconds_to_time64, sas_seconds_to_timestamp,
+    sas_seconds_to_timestamp_in,
+};
+
+fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn sas_epoch() -> NaiveDate {
+    ymd(1960, 1, 1)
+}
+
+fn unix_epoch() -> NaiveDate {
+    ymd(1970, 1, 1)
+}
+
+#[test]
+fn every_date_from_1800_to_2200_matches_chrono() {
+    let mut date = ymd(1800, 1, 1);
+    while date <= ymd(2200, 12, 31) {
+        let sas = date.signed_duration_since(sas_epoch()).num_days() as f64;
+        let expected = date.signed_duration_since(unix_epoch()).num_days() as i32;
+
+        // any time within the day is the same day
+        for fraction in [0.0, 0.25, 0.999] {
+            assert_eq!(sas_days_to_date32(sas + fraction), Some(expected), "{}", date);
+        }
+
+        date += Duration::days(1);
+    }
+}
+
+#[test]
+fn datetimes_from_1800_to_2200_match_chrono() {
+    let mut date = ymd(1800, 1, 1);
+    while date <= ymd(2200, 12, 31) {
+        for seconds in [0, 1, 43_199, 86_399] {
+            let dt = date.and_hms_opt(0, 0, 0).unwrap() + Duration::seconds(seconds);
+            let sas = dt
+                .signed_duration_since(sas_epoch().and_hms_opt(0, 0, 0).unwrap())
+                .num_seconds() as f64;
+            let expected = dt.and_utc().timestamp();
+
+            assert_eq!(sas_seconds_to_timestamp(sas), Some(expected), "{}", dt);
+            assert_eq!(sas_seconds_to_timestamp(sas + 0.5), Some(expected), "{}", dt);
+            assert_eq!(
+                sas_seconds_to_timestamp_in(sas + 0.5, TimeUnit::Millisecond),
+                Some(expected * 1000 + 500),
+                "{}",
+                dt
+            );
+        }
+
+        date += Duration::days(7);
+    }
+}
+
+#[test]
+fn dates_before_the_sas_epoch_are_truncated_to_the_start_of_their_day() {
+    assert_eq!(sas_days_to_date32(0.0), Some(-3653));
+    assert_eq!(sas_days_to_date32(-0.5), Some(-3654));
+    assert_eq!(sas_days_to_date32(-1.0), Some(-3654));
+    assert_eq!(sas_seconds_to_timestamp(-0.5), Some(-315_619_201));
+    assert_eq!(sas_seconds_to_time32(-0.5), Some(-1));
+}
+
+#[test]
+fn dates_around_1582_and_1920_are_proleptic_gregorian() {
+    // 1582-10-04 was followed by 1582-10-15, yet SAS counts every day of the proleptic calendar
+    let before = ymd(1582, 10, 4).signed_duration_since(sas_epoch()).num_days() as f64;
+    let after = ymd(1582, 10, 15).signed_duration_since(sas_epoch()).num_days() as f64;
+    assert_eq!(after - before, 11.0);
+    assert_eq!(sas_days_to_date32(before), Some(-141_438));
+    assert_eq!(sas_days_to_date32(after), Some(-141_427));
+
+    // the default window of two-digit years of SAS begins in 1920
+    assert_eq!(sas_days_to_date32(-14_610.0), Some(-18_263));
+    assert_eq!(sas_days_to_date32(-14_611.0), Some(-18_264));
+}
+
+#[test]
+fn values_that_cannot_be_represented_are_none() {
+    for v in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1e300, -1e300, 1e11, -1e11] {
+        assert_eq!(sas_days_to_date32(v), None, "{}", v);
+        assert_eq!(sas_seconds_to_time32(v), None, "{}", v);
+    }
+    for v in [f64::NAN, f64::INFINITY, 1e300, -1e300, 1e18] {
+        assert_eq!(sas_seconds_to_timestamp(v), None, "{}", v);
+        assert_eq!(sas_seconds_to_time64(v), None, "{}", v);
+    }
+
+    // nanosecond timestamps end during 2262
+    let y2262 = ymd(2262, 1, 1).signed_duration_since(sas_epoch()).num_seconds() as f64;
+    let y2263 = ymd(2263, 1, 1).signed_duration_since(sas_epoch()).num_seconds() as f64;
+    assert!(sas_seconds_to_timestamp_in(y2262, TimeUnit::Nanosecond).is_some());
+    assert_eq!(sas_seconds_to_timestamp_in(y2263, TimeUnit::Nanosecond), None);
+    assert!(sas_seconds_to_timestamp_in(y2263, TimeUnit::Microsecond).is_some());
+}
+
+#[test]
+fn times_round_to_the_nearest_microsecond() {
+    assert_eq!(sas_seconds_to_time32(3_661.9), Some(3_661));
+    assert_eq!(sas_seconds_to_time64(3_661.123_456_7), Some(3_661_123_457));
+}
@@ -0,0 +1,82 @@
+use assert_fs::TempDir;
+use path_abs::PathAbs;
+use readstat::prelude::{convert_many, OutFormat, ReadStatConvertOptions};
+use std::path::PathBuf;
+
+mod common;
+
+fn data_path(ds: &str) -> PathBuf {
+    PathAbs::new(env!("CARGO_MANIFEST_DIR"))
+        .unwrap()
+        .as_path()
+        .join("tests")
+        .join("data")
+        .join(ds)
+}
+
+#[test]
+fn convert_many_reports_each_file() {
+    let out_dir = TempDir::new().unwrap();
+
+    let inputs = vec![
+        data_path("cars.sas7bdat"),
+        data_path("does_not_exist.sas7bdat"),
+        data_path("all_types.sas7bdat"),
+    ];
+
+    let options = ReadStatConvertOptions {
+        out_dir: Some(out_dir.path().to_path_buf()),
+        format: OutFormat::csv,
+        ..ReadStatConvertOptions::default()
+    };
+
+    let results = convert_many(&inputs, &options);
+
+    // one result per input, in order
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].input, inputs[0]);
+    assert_eq!(results[1].input, inputs[1]);
+    assert_eq!(results[2].input, inputs[2]);
+
+    // cars
+    assert!(results[0].is_ok());
+    assert_eq!(results[0].rows, 1081);
+    let cars_out = results[0].output.clone().unwrap();
+    assert_eq!(cars_out.file_name().unwrap(), "cars.csv");
+    assert!(cars_out.exists());
+
+    // missing file fails without stopping the batch
+    assert!(!results[1].is_ok());
+    assert!(results[1].error.is_some());
+    assert_eq!(results[1].rows, 0);
+
+    // all_types
+    assert!(results[2].is_ok());
+    assert_eq!(results[2].rows, 3);
+    assert!(results[2].output.clone().unwrap().exists());
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn convert_many_collects_warnings() {
+    let out_dir = TempDir::new().unwrap();
+
+    let inputs = vec![data_path("all_types.sas7bdat")];
+
+    let options = ReadStatConvertOptions {
+        out_dir: Some(out_dir.path().to_path_buf()),
+        format: OutFormat::csv,
+        compression_level: Some(5),
+        ..ReadStatConvertOptions::default()
+    };
+
+    let results = convert_many(&inputs, &options);
+
+    // compression level without compression is ignored, and reported as a warning
+    assert!(results[0].is_ok());
+    assert_eq!(results[0].warnings.len(), 1);
+    assert!(results[0].warnings[0].contains("--compression-level"));
+
+    out_dir.close().unwrap();
+}
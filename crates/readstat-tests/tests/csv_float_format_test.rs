@@ -0,0 +1,118 @@
+use arrow2::{
+    array::{Array, Float32Array, Float64Array, Utf8Array},
+    chunk::Chunk,
+    datatypes::{DataType, Field, Schema},
+};
+use assert_fs::TempDir;
+use readstat::prelude::*;
+use std::path::PathBuf;
+
+mod common;
+
+fn write_csv(values: Box<dyn Array>, labels: Vec<&str>, out_path: PathBuf) -> String {
+    // input is only used to satisfy path validation; data is supplied directly
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    let rsp = ReadStatPath::new(
+        rsp.path,
+        Some(out_path.clone()),
+        Some(OutFormat::csv),
+        false,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let schema = Schema::from(vec![
+        Field::new("value", values.data_type().clone(), true),
+        Field::new("label", DataType::Utf8, true),
+    ]);
+    let chunk = Chunk::new(vec![values, Utf8Array::<i32>::from_slice(labels).boxed()]);
+    let d = ReadStatData::new().set_chunk(schema, chunk);
+
+    let mut wtr = ReadStatWriter::new().set_quiet(true);
+    wtr.write(&d, &rsp).unwrap();
+    wtr.finish(&d, &rsp).unwrap();
+
+    std::fs::read_to_string(out_path).unwrap()
+}
+
+#[test]
+fn csv_float64_golden() {
+    let out_dir = TempDir::new().unwrap();
+
+    // value, expected rendering
+    let table: Vec<(Option<f64>, &str)> = vec![
+        (Some(0.0), "0"),
+        (Some(-0.0), "0"),
+        (Some(1.0), "1"),
+        (Some(1234.0), "1234"),
+        (Some(-1.5), "-1.5"),
+        (Some(4.6), "4.6"),
+        (Some(0.1 + 0.2), "0.30000000000000004"),
+        (Some(1e-5), "0.00001"),
+        (Some(0.00001234), "0.00001234"),
+        (Some(1e-7), "1e-7"),
+        (Some(-2.5e-10), "-2.5e-10"),
+        (Some(123456789012345.0), "123456789012345"),
+        (Some(1e15), "1e15"),
+        (Some(1.5e20), "1.5e20"),
+        (Some(f64::MAX), "1.7976931348623157e308"),
+        (Some(f64::MIN_POSITIVE), "2.2250738585072014e-308"),
+        (Some(5e-324), "5e-324"),
+        (Some(f64::INFINITY), "inf"),
+        (Some(f64::NEG_INFINITY), "-inf"),
+        (None, ""),
+    ];
+
+    let labels: Vec<String> = (0..table.len()).map(|i| format!("row{}", i)).collect();
+    let values = Float64Array::from(table.iter().map(|(v, _)| *v).collect::<Vec<_>>()).boxed();
+
+    let csv = write_csv(
+        values,
+        labels.iter().map(|l| l.as_str()).collect(),
+        out_dir.path().join("floats.csv"),
+    );
+
+    let mut expected = String::from("value,label\n");
+    for (i, (_, e)) in table.iter().enumerate() {
+        expected.push_str(&format!("{},row{}\n", e, i));
+    }
+
+    assert_eq!(csv, expected);
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn csv_float32_golden() {
+    let out_dir = TempDir::new().unwrap();
+
+    // value, expected rendering
+    let table: Vec<(Option<f32>, &str)> = vec![
+        (Some(-0.0), "0"),
+        (Some(0.1), "0.1"),
+        (Some(16777216.0), "16777216"),
+        (Some(1e-7), "1e-7"),
+        (Some(f32::MAX), "3.4028235e38"),
+        (None, ""),
+    ];
+
+    let labels: Vec<String> = (0..table.len()).map(|i| format!("row{}", i)).collect();
+    let values = Float32Array::from(table.iter().map(|(v, _)| *v).collect::<Vec<_>>()).boxed();
+
+    let csv = write_csv(
+        values,
+        labels.iter().map(|l| l.as_str()).collect(),
+        out_dir.path().join("floats.csv"),
+    );
+
+    let mut expected = String::from("value,label\n");
+    for (i, (_, e)) in table.iter().enumerate() {
+        expected.push_str(&format!("{},row{}\n", e, i));
+    }
+
+    assert_eq!(csv, expected);
+
+    out_dir.close().unwrap();
+}
@@ -0,0 +1,134 @@
+use assert_cmd::Command;
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert, CsvQuoteStyle, OutFormat, ReadStatConvertOptions, ReadStatCsvOptions, ReadStatPath,
+};
+
+mod common;
+
+// all_types written as csv a row at a time, so that the output is streamed in several batches
+fn write_all_types(csv: ReadStatCsvOptions) -> String {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("all_types.csv");
+    let rsp = ReadStatPath::new(
+        common::setup_path("all_types.sas7bdat").unwrap().path,
+        Some(out_path.clone()),
+        Some(OutFormat::csv),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let options = ReadStatConvertOptions {
+        stream_rows: 1,
+        csv,
+        ..ReadStatConvertOptions::default()
+    };
+    convert(&rsp, &options).unwrap();
+
+    let text = std::fs::read_to_string(&out_path).unwrap();
+    out_dir.close().unwrap();
+    text
+}
+
+fn tsv_with_na() -> ReadStatCsvOptions {
+    ReadStatCsvOptions {
+        delimiter: b'\t',
+        null_value: String::from("NA"),
+        ..ReadStatCsvOptions::default()
+    }
+}
+
+#[test]
+fn defaults_are_unchanged() {
+    let csv = write_all_types(ReadStatCsvOptions::default());
+    assert!(csv.starts_with("_int,"));
+    assert_eq!(csv.lines().count(), 4);
+
+    // missing values are empty fields
+    assert!(csv.lines().any(|l| l.contains(",,")));
+    assert!(!csv.contains("NA"));
+}
+
+#[test]
+fn tsv_with_na_for_missing_values() {
+    let csv = write_all_types(tsv_with_na());
+    let records: Vec<Vec<&str>> = csv.lines().map(|l| l.split('\t').collect()).collect();
+
+    // the header is written once, however many batches are streamed
+    assert_eq!(records.len(), 4);
+    assert_eq!(records[0][0], "_int");
+    assert_eq!(csv.matches("_int").count(), 1);
+
+    // every record is tab delimited, with missing values written as NA rather than left empty
+    assert!(records.iter().all(|r| r.len() == 8));
+    assert!(!csv.contains(','));
+    assert_eq!(records[3][0], "NA");
+    assert_eq!(records[3][5], "NA");
+    assert_eq!(records[3][6], "NA");
+    assert_eq!(records[1][0], "1234");
+}
+
+#[test]
+fn header_may_be_left_out() {
+    let with = write_all_types(tsv_with_na());
+    let without = write_all_types(ReadStatCsvOptions {
+        write_header: false,
+        ..tsv_with_na()
+    });
+
+    assert_eq!(without.lines().count(), 3);
+    assert!(!without.contains("_int"));
+    assert_eq!(with.split_once('\n').unwrap().1, without);
+}
+
+#[test]
+fn strings_may_always_be_quoted() {
+    assert!(!write_all_types(ReadStatCsvOptions::default()).contains("\"another string\""));
+
+    let csv = write_all_types(ReadStatCsvOptions {
+        quote_style: CsvQuoteStyle::Always,
+        ..ReadStatCsvOptions::default()
+    });
+    assert!(csv.contains("\"another string\""));
+}
+
+#[test]
+fn datetimes_follow_the_format() {
+    let csv = write_all_types(ReadStatCsvOptions {
+        datetime_format: Some(String::from("%Y-%m-%dT%H:%M:%S")),
+        ..ReadStatCsvOptions::default()
+    });
+    assert!(csv.contains(",2021-06-01T13:42:25,"));
+}
+
+#[test]
+fn cli_writes_tsv_with_na() {
+    let out_dir = TempDir::new().unwrap();
+    // tab separated, despite the extension csv
+    let out_path = out_dir.path().join("all_types.csv");
+
+    let output = Command::cargo_bin("readstat")
+        .unwrap()
+        .arg("data")
+        .arg("tests/data/all_types.sas7bdat")
+        .arg("--output")
+        .arg(&out_path)
+        .args(["--format", "csv"])
+        .args(["--delimiter", "tab"])
+        .args(["--null-value", "NA"])
+        .arg("--no-header")
+        .arg("--no-progress")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let tsv = std::fs::read_to_string(&out_path).unwrap();
+    assert_eq!(tsv.lines().count(), 3);
+    assert!(tsv.lines().all(|l| l.split('\t').count() == 8));
+    assert!(tsv.lines().last().unwrap().starts_with("NA\t"));
+
+    out_dir.close().unwrap();
+}
@@ -0,0 +1,155 @@
+use arrow2::{
+    array::{Array, PrimitiveArray, Utf8Array},
+    chunk::Chunk,
+    datatypes::{DataType, Field, Schema},
+};
+use readstat::prelude::{
+    convert_collect, DetectDates, OutputMode, ReadStatConvertOptions, ReadStatDateColumn,
+    ReadStatDateDetection, ReadStatVarFormatClass, DETECT_DATES_THRESHOLD,
+};
+
+mod common;
+
+const ROWS: usize = 500;
+
+fn schema() -> Schema {
+    Schema::from(vec![
+        Field::new("visit", DataType::Float64, true),
+        Field::new("row_id", DataType::Float64, true),
+        Field::new("stamp", DataType::Float64, true),
+        Field::new("amount", DataType::Float64, true),
+        Field::new("name", DataType::Utf8, true),
+    ])
+}
+
+// visit holds scattered days since 1960, row_id counts up from 20,000, stamp holds seconds since
+//   1960, and amount is spread from 0 to 1,000,000
+fn chunk(first_out_of_range: bool) -> Chunk<Box<dyn Array>> {
+    let visit: Vec<Option<f64>> = (0..ROWS)
+        .map(|i| match i {
+            0 if first_out_of_range => Some(-5.0),
+            _ if i % 10 == 0 => None,
+            _ => Some(15_000.0 + ((i * 7919) % 9000) as f64),
+        })
+        .collect();
+    let row_id: Vec<f64> = (0..ROWS).map(|i| 20_000.0 + i as f64).collect();
+    let stamp: Vec<f64> = (0..ROWS)
+        .map(|i| 1.7e9 + ((i * 104_729) % 86_400) as f64 * 3.5)
+        .collect();
+    let amount: Vec<f64> = (0..ROWS).map(|i| ((i * 2654) % 1_000_000) as f64).collect();
+    let name = Utf8Array::<i32>::from_iter_values((0..ROWS).map(|_| "x"));
+
+    Chunk::new(vec![
+        PrimitiveArray::<f64>::from(visit).boxed(),
+        PrimitiveArray::<f64>::from_vec(row_id).boxed(),
+        PrimitiveArray::<f64>::from_vec(stamp).boxed(),
+        PrimitiveArray::<f64>::from_vec(amount).boxed(),
+        name.boxed(),
+    ])
+}
+
+fn detect(threshold: f64, first_out_of_range: bool) -> ReadStatDateDetection {
+    let mut detection = ReadStatDateDetection::new(&schema(), threshold);
+    detection.push_chunk(&chunk(first_out_of_range));
+    detection
+}
+
+fn names<'a>(columns: &[&'a ReadStatDateColumn]) -> Vec<&'a str> {
+    columns.iter().map(|c| c.var_name.as_str()).collect()
+}
+
+#[test]
+fn date_like_columns_are_candidates() {
+    let detection = detect(DETECT_DATES_THRESHOLD, false);
+    assert_eq!(detection.sampled_rows, ROWS);
+
+    // only plain numerics are sampled
+    assert_eq!(detection.columns.len(), 4);
+
+    let candidates = detection.candidates();
+    assert_eq!(names(&candidates), vec!["visit", "row_id", "stamp"]);
+    assert_eq!(candidates[0].format_class(), ReadStatVarFormatClass::Date);
+    assert_eq!(candidates[0].values, 450);
+    assert_eq!(candidates[2].format_class(), ReadStatVarFormatClass::DateTime);
+}
+
+#[test]
+fn row_ids_within_the_date_range_are_never_applied() {
+    let detection = detect(DETECT_DATES_THRESHOLD, false);
+
+    let row_id = detection
+        .columns
+        .iter()
+        .find(|c| c.var_name == "row_id")
+        .unwrap();
+    assert_eq!(row_id.fraction(), 1.0);
+    assert!(row_id.sequential());
+
+    assert_eq!(names(&detection.applicable()), vec!["visit", "stamp"]);
+    assert_eq!(detection.warnings().len(), 2);
+    assert!(detection.warnings().iter().all(|w| !w.contains("row_id")));
+}
+
+#[test]
+fn threshold_is_configurable() {
+    // a single value out of range among 451 is within the default threshold
+    let detection = detect(DETECT_DATES_THRESHOLD, true);
+    assert!(names(&detection.candidates()).contains(&"visit"));
+
+    let detection = detect(1.0, true);
+    assert!(!names(&detection.candidates()).contains(&"visit"));
+}
+
+#[test]
+fn report_lists_each_candidate() {
+    let report = detect(DETECT_DATES_THRESHOLD, false).report();
+    let lines: Vec<&str> = report.lines().collect();
+
+    // header, dashes, then a line for each candidate
+    assert_eq!(lines.len(), 5);
+    assert!(lines[0].starts_with("Column"));
+    assert!(lines[2].starts_with("visit") && lines[2].ends_with("yes"));
+    assert!(lines[3].starts_with("row_id") && lines[3].ends_with("no (row IDs?)"));
+    assert!(lines[4].starts_with("stamp") && lines[4].contains("DateTime"));
+    assert!(!report.contains("amount"));
+}
+
+#[test]
+fn report_leaves_types_unchanged() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let plain = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        ..ReadStatConvertOptions::default()
+    };
+    let c = convert_collect(&rsp, &plain).unwrap();
+    assert!(c.date_detection.is_none());
+
+    let options = ReadStatConvertOptions {
+        detect_dates: Some(DetectDates::Report),
+        ..plain.clone()
+    };
+    let reported = convert_collect(&rsp, &options).unwrap();
+    assert_eq!(reported.schema, c.schema);
+
+    let detection = reported.date_detection.unwrap();
+    assert_eq!(detection.sampled_rows, 1081);
+
+    // applied columns, if any, are read as dates
+    let options = ReadStatConvertOptions {
+        detect_dates: Some(DetectDates::Apply),
+        ..plain
+    };
+    let applied = convert_collect(&rsp, &options).unwrap();
+    for column in detection.applicable() {
+        let f = applied
+            .schema
+            .fields
+            .iter()
+            .find(|f| f.name == column.var_name)
+            .unwrap();
+        assert!(matches!(
+            f.data_type(),
+            DataType::Date32 | DataType::Timestamp(_, _)
+        ));
+    }
+}
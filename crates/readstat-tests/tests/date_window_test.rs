@@ -0,0 +1,185 @@
+use arrow2::array::{Int32Array, Int64Array};
+use assert_cmd::Command;
+use assert_fs::TempDir;
+use chrono::NaiveDate;
+use readstat::prelude::{
+    convert_collect, OutputMode, ReadStatCollected, ReadStatConvertOptions, ReadStatData,
+    ReadStatDateWindow, ReadStatMetadata,
+};
+
+mod common;
+
+// absurd_dates.xpt holds id, the datetime happened, and the date day
+const HAPPENED: usize = 1;
+const DAY: usize = 2;
+
+fn collect(date_window: Option<ReadStatDateWindow>) -> ReadStatCollected {
+    let rsp = common::setup_path("absurd_dates.xpt").unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        date_window,
+        ..ReadStatConvertOptions::default()
+    };
+    convert_collect(&rsp, &options).unwrap()
+}
+
+fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn days(date: NaiveDate) -> i32 {
+    date.signed_duration_since(ymd(1970, 1, 1)).num_days() as i32
+}
+
+fn millis(date: NaiveDate, h: u32, m: u32, s: u32) -> i64 {
+    date.and_hms_opt(h, m, s).unwrap().and_utc().timestamp_millis()
+}
+
+fn happened(c: &ReadStatCollected) -> Vec<Option<i64>> {
+    let col = c.chunks[0].arrays()[HAPPENED]
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    col.iter().map(|v| v.copied()).collect()
+}
+
+fn day(c: &ReadStatCollected) -> Vec<Option<i32>> {
+    let col = c.chunks[0].arrays()[DAY]
+        .as_any()
+        .downcast_ref::<Int32Array>()
+        .unwrap();
+    col.iter().map(|v| v.copied()).collect()
+}
+
+#[test]
+fn values_beyond_the_default_window_are_null_with_a_warning() {
+    let c = collect(Some(ReadStatDateWindow::default()));
+    assert_eq!(c.rows, 3);
+
+    let june = ymd(2021, 6, 1);
+    assert_eq!(happened(&c), vec![Some(millis(june, 13, 42, 25)), None, None]);
+    assert_eq!(day(&c), vec![Some(days(june)), None, None]);
+
+    // in file order, each with the value as stored
+    let warned: Vec<(usize, &str)> = c
+        .date_range_warnings
+        .iter()
+        .map(|w| (w.row, w.column.as_str()))
+        .collect();
+    assert_eq!(warned, vec![(1, "happened"), (1, "day"), (2, "happened"), (2, "day")]);
+
+    let w = &c.date_range_warnings;
+    assert!(w[0].value > 1e69);
+    // 1500-01-01 as days since 1960-01-01
+    assert_eq!(w[1].value, -168_011.0);
+    assert!(w[3].value > 1e69);
+
+    // also counted among the warnings of the run
+    let g = c.warning_summary.groups();
+    assert!(g.iter().any(|g| g.column.as_deref() == Some("happened") && g.count == 2));
+}
+
+#[test]
+fn values_within_their_type_are_read_without_a_window() {
+    let c = collect(None);
+
+    assert_eq!(happened(&c)[2], Some(millis(ymd(12000, 1, 1), 0, 0, 0)));
+    assert_eq!(day(&c)[1], Some(days(ymd(1500, 1, 1))));
+
+    // 1e70 is beyond the range of both types whatever the window
+    assert_eq!(happened(&c)[1], None);
+    assert_eq!(day(&c)[2], None);
+    let warned: Vec<(usize, &str)> = c
+        .date_range_warnings
+        .iter()
+        .map(|w| (w.row, w.column.as_str()))
+        .collect();
+    assert_eq!(warned, vec![(1, "happened"), (2, "day")]);
+}
+
+#[test]
+fn a_narrower_window_nulls_more_values() {
+    let window = ReadStatDateWindow::new(ymd(2022, 1, 1), ymd(2100, 12, 31)).unwrap();
+    let c = collect(Some(window));
+
+    assert_eq!(happened(&c), vec![None, None, None]);
+    assert_eq!(day(&c), vec![None, None, None]);
+    assert_eq!(c.date_range_warnings.len(), 6);
+}
+
+#[test]
+fn strict_dates_fail_at_the_first_value_out_of_range() {
+    let rsp = common::setup_path("absurd_dates.xpt").unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        strict_dates: true,
+        ..ReadStatConvertOptions::default()
+    };
+
+    let e = convert_collect(&rsp, &options).unwrap_err().to_string();
+    assert!(e.contains("happened"), "{}", e);
+    assert!(e.contains("row 1"), "{}", e);
+    assert!(e.contains("beyond the range of"), "{}", e);
+}
+
+#[test]
+fn date_range_warnings_are_available_once_read() {
+    let rsp = common::setup_path("absurd_dates.xpt").unwrap();
+
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+
+    let mut d = ReadStatData::new()
+        .set_no_progress(true)
+        .init(md.clone(), 0, md.row_count as u32);
+    d.read_data(&rsp).unwrap();
+
+    assert_eq!(d.chunk().unwrap().len(), 3);
+    assert_eq!(d.date_range_warnings().len(), 4);
+    assert_eq!(d.date_range_warnings()[0].row, 1);
+    assert_eq!(d.date_range_warnings()[0].column, "happened");
+}
+
+#[test]
+fn date_windows_parse_as_min_and_max() {
+    let w: ReadStatDateWindow = "1900-01-01..2100-12-31".parse().unwrap();
+    assert_eq!(w, ReadStatDateWindow::new(ymd(1900, 1, 1), ymd(2100, 12, 31)).unwrap());
+    assert_eq!(w.to_string(), "1900-01-01..2100-12-31");
+    assert!(w.contains(ymd(2100, 12, 31)) && !w.contains(ymd(2101, 1, 1)));
+
+    assert_eq!(ReadStatDateWindow::default().to_string(), "1582-01-01..9999-12-31");
+
+    for bad in ["1900-01-01", "1900-01-01..tomorrow", "2100-01-01..1900-01-01"] {
+        assert!(bad.parse::<ReadStatDateWindow>().is_err(), "{}", bad);
+    }
+}
+
+#[test]
+fn cli_prints_date_warnings_and_fails_when_strict() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("absurd_dates.csv");
+
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("data")
+        .arg("tests/data/absurd_dates.xpt")
+        .arg("--output")
+        .arg(&out_path)
+        .arg("--print-date-warnings")
+        .arg("--no-progress");
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let stdout = String::from_utf8(output).unwrap();
+    assert_eq!(stdout.matches("was written as null").count(), 4, "{}", stdout);
+
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("data")
+        .arg("tests/data/absurd_dates.xpt")
+        .arg("--output")
+        .arg(&out_path)
+        .arg("--overwrite")
+        .args(["--date-window", "1400-01-01..2100-12-31"])
+        .arg("--strict-dates")
+        .arg("--no-progress");
+    cmd.assert().failure();
+
+    out_dir.close().unwrap();
+}
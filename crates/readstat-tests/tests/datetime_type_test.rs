@@ -0,0 +1,84 @@
+use arrow2::{
+    array::Int64Array,
+    datatypes::{DataType, TimeUnit},
+};
+use chrono::NaiveDate;
+use readstat::prelude::{convert_collect, DateTimeType, OutputMode, ReadStatConvertOptions};
+
+mod common;
+
+// _datetime column
+const VAR_INDEX: usize = 5;
+
+fn read_datetime(datetime_type: DateTimeType) -> (DataType, i64) {
+    // setup path
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+
+    // read the entire dataset, keeping it in memory rather than writing
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        datetime_type: Some(datetime_type),
+        ..ReadStatConvertOptions::default()
+    };
+    let collected = convert_collect(&rsp, &options).unwrap();
+    assert_eq!(collected.chunks.len(), 1);
+
+    let data_type = collected.schema.fields[VAR_INDEX].data_type().clone();
+
+    let arrays = collected.chunks[0].arrays();
+    let col = arrays
+        .get(VAR_INDEX)
+        .unwrap()
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+
+    // the array type must match the schema
+    assert_eq!(col.data_type(), &data_type);
+
+    (data_type, col.value(1))
+}
+
+fn expected_seconds() -> i64 {
+    NaiveDate::from_ymd_opt(2021, 6, 1)
+        .unwrap()
+        .and_hms_opt(13, 42, 25)
+        .unwrap()
+        .and_utc()
+        .timestamp()
+}
+
+#[test]
+fn datetime_type_timestamp_s() {
+    let (dt, v) = read_datetime(DateTimeType::TimestampS);
+    assert_eq!(dt, DataType::Timestamp(TimeUnit::Second, None));
+    assert_eq!(v, expected_seconds());
+}
+
+#[test]
+fn datetime_type_timestamp_ms() {
+    let (dt, v) = read_datetime(DateTimeType::TimestampMs);
+    assert_eq!(dt, DataType::Timestamp(TimeUnit::Millisecond, None));
+    assert_eq!(v, expected_seconds() * 1_000);
+}
+
+#[test]
+fn datetime_type_timestamp_us() {
+    let (dt, v) = read_datetime(DateTimeType::TimestampUs);
+    assert_eq!(dt, DataType::Timestamp(TimeUnit::Microsecond, None));
+    assert_eq!(v, expected_seconds() * 1_000_000);
+}
+
+#[test]
+fn datetime_type_timestamp_ns() {
+    let (dt, v) = read_datetime(DateTimeType::TimestampNs);
+    assert_eq!(dt, DataType::Timestamp(TimeUnit::Nanosecond, None));
+    assert_eq!(v, expected_seconds() * 1_000_000_000);
+}
+
+#[test]
+fn datetime_type_date64() {
+    let (dt, v) = read_datetime(DateTimeType::Date64);
+    assert_eq!(dt, DataType::Date64);
+    assert_eq!(v, expected_seconds() * 1_000);
+}
@@ -0,0 +1,182 @@
+use arrow2::{
+    array::Utf8Array,
+    compute::cast::{cast, CastOptions},
+    datatypes::{DataType, IntegerType},
+};
+use assert_cmd::Command;
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert_collect, OutFormat, OutputMode, ReadStatCollected, ReadStatConvertOptions,
+    ReadStatPath, StringEncoding,
+};
+use std::path::Path;
+
+mod common;
+
+fn dictionary() -> DataType {
+    DataType::Dictionary(IntegerType::Int32, Box::new(DataType::Utf8), false)
+}
+
+fn collect(string_encoding: StringEncoding) -> ReadStatCollected {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        stream_rows: 500,
+        string_encoding,
+        ..ReadStatConvertOptions::default()
+    };
+    convert_collect(&rsp, &options).unwrap()
+}
+
+fn convert_to(out_path: &Path, format: OutFormat, string_encoding: StringEncoding) -> u64 {
+    let rsp = ReadStatPath::new(
+        common::setup_path("cars.sas7bdat").unwrap().path,
+        Some(out_path.to_path_buf()),
+        Some(format),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+    let options = ReadStatConvertOptions {
+        string_encoding,
+        ..ReadStatConvertOptions::default()
+    };
+
+    convert_collect(&rsp, &options).unwrap();
+    std::fs::metadata(out_path).unwrap().len()
+}
+
+fn data_type(c: &ReadStatCollected, name: &str) -> DataType {
+    let f = c.schema.fields.iter().find(|f| f.name == name).unwrap();
+    f.data_type().clone()
+}
+
+// every value of the named string column, in file order, whether or not a dictionary
+fn strings(c: &ReadStatCollected, name: &str) -> Vec<Option<String>> {
+    let i = c.schema.fields.iter().position(|f| f.name == name).unwrap();
+    c.chunks
+        .iter()
+        .flat_map(|chunk| {
+            let a = cast(chunk.arrays()[i].as_ref(), &DataType::Utf8, CastOptions::default())
+                .unwrap();
+            a.as_any()
+                .downcast_ref::<Utf8Array<i32>>()
+                .unwrap()
+                .iter()
+                .map(|v| v.map(String::from))
+                .collect::<Vec<Option<String>>>()
+        })
+        .collect()
+}
+
+#[test]
+fn every_string_column_is_a_dictionary_without_a_cap() {
+    let plain = collect(StringEncoding::Plain);
+    let c = collect(StringEncoding::Dictionary {
+        max_cardinality: None,
+    });
+
+    for name in ["Brand", "Model"] {
+        assert_eq!(data_type(&plain, name), DataType::Utf8);
+        assert_eq!(data_type(&c, name), dictionary());
+        assert_eq!(strings(&c, name), strings(&plain, name));
+    }
+    assert_eq!(data_type(&c, "EngineSize"), DataType::Float64);
+
+    // every batch has the same types as the schema
+    assert_eq!(c.chunks.len(), 3);
+    for chunk in &c.chunks {
+        assert_eq!(chunk.arrays()[0].data_type(), &dictionary());
+    }
+}
+
+#[test]
+fn columns_beyond_the_cap_are_left_as_strings() {
+    let plain = collect(StringEncoding::Plain);
+    let c = collect(StringEncoding::Dictionary {
+        max_cardinality: Some(100),
+    });
+
+    // a few dozen brands, yet hundreds of models
+    assert_eq!(data_type(&c, "Brand"), dictionary());
+    assert_eq!(data_type(&c, "Model"), DataType::Utf8);
+    assert_eq!(strings(&c, "Brand"), strings(&plain, "Brand"));
+    assert_eq!(strings(&c, "Model"), strings(&plain, "Model"));
+
+    let c = collect(StringEncoding::Dictionary {
+        max_cardinality: Some(1),
+    });
+    assert_eq!(data_type(&c, "Brand"), DataType::Utf8);
+}
+
+#[test]
+fn dictionaries_shrink_feather_and_parquet_output() {
+    let out_dir = TempDir::new().unwrap();
+    let encoding = StringEncoding::Dictionary {
+        max_cardinality: None,
+    };
+
+    for (format, ext) in [(OutFormat::feather, "feather"), (OutFormat::parquet, "parquet")] {
+        let plain = convert_to(
+            &out_dir.path().join(format!("plain.{}", ext)),
+            format,
+            StringEncoding::Plain,
+        );
+        let dict = convert_to(&out_dir.path().join(format!("dict.{}", ext)), format, encoding);
+        assert!(dict < plain, "{}: {} is not smaller than {}", ext, dict, plain);
+    }
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn csv_output_is_written_with_the_values() {
+    let out_dir = TempDir::new().unwrap();
+    let plain_path = out_dir.path().join("plain.csv");
+    let dict_path = out_dir.path().join("dict.csv");
+
+    convert_to(&plain_path, OutFormat::csv, StringEncoding::Plain);
+    convert_to(
+        &dict_path,
+        OutFormat::csv,
+        StringEncoding::Dictionary {
+            max_cardinality: None,
+        },
+    );
+    assert_eq!(
+        std::fs::read_to_string(&dict_path).unwrap(),
+        std::fs::read_to_string(&plain_path).unwrap()
+    );
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn cli_writes_dictionaries() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("cars.csv");
+
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("data")
+        .arg("tests/data/cars.sas7bdat")
+        .arg("--output")
+        .arg(&out_path)
+        .arg("--dictionary-strings")
+        .args(["--dictionary-max-cardinality", "100"])
+        .arg("--no-progress");
+    cmd.assert().success();
+    assert!(std::fs::read_to_string(&out_path).unwrap().starts_with("Brand,Model,"));
+
+    // a cap alone is an error
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("data")
+        .arg("tests/data/cars.sas7bdat")
+        .arg("--output")
+        .arg(&out_path)
+        .args(["--dictionary-max-cardinality", "100"]);
+    cmd.assert().failure();
+
+    out_dir.close().unwrap();
+}
@@ -0,0 +1,92 @@
+use assert_cmd::Command;
+use assert_fs::TempDir;
+use predicates::prelude::*;
+use readstat::prelude::{
+    convert, OutFormat, ReadStatConvertOptions, ReadStatMapping, ReadStatMetadata, ReadStatPath,
+    ReadStatRenameReason,
+};
+use serde_json::Value;
+
+mod common;
+
+#[test]
+fn duplicate_names_are_an_error() {
+    let rsp = common::setup_path("duplicate_names.xpt").unwrap();
+    let mut md = ReadStatMetadata::new();
+
+    let e = md.read_metadata(&rsp, false).unwrap_err().to_string();
+    assert!(e.contains("amount"));
+    assert!(e.contains("(indices 1, 2)"));
+}
+
+#[test]
+fn later_duplicates_are_renamed() {
+    let rsp = common::setup_path("duplicate_names.xpt").unwrap();
+    let mut md = ReadStatMetadata::new().set_dedup_columns(true);
+    md.read_metadata(&rsp, false).unwrap();
+
+    let names: Vec<&str> = md.schema.fields.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, vec!["id", "amount", "amount_1"]);
+
+    // the names within the file are kept
+    assert_eq!(md.vars[&2].var_name, "amount");
+    assert_eq!(md.renames[&2].final_name, "amount_1");
+    assert_eq!(md.renames[&2].reason, ReadStatRenameReason::Deduplication);
+}
+
+#[test]
+fn renamed_duplicates_are_written_with_a_mapping() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("amounts.csv");
+    let rsp = ReadStatPath::new(
+        common::setup_path("duplicate_names.xpt").unwrap().path,
+        Some(out_path.clone()),
+        Some(OutFormat::csv),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let options = ReadStatConvertOptions {
+        dedup_columns: true,
+        ..ReadStatConvertOptions::default()
+    };
+    convert(&rsp, &options).unwrap();
+
+    let csv = std::fs::read_to_string(&out_path).unwrap();
+    assert!(csv.starts_with("id,amount,amount_1"));
+
+    let sidecar = ReadStatMapping::sidecar_path(&out_path);
+    let mapping: Value =
+        serde_json::from_str(&std::fs::read_to_string(&sidecar).unwrap()).unwrap();
+    let renamed = mapping["variables"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|v| v["final_name"] == "amount_1")
+        .unwrap();
+    assert_eq!(renamed["original_name"], "amount");
+    assert_eq!(renamed["renamed_by"], "deduplication");
+    assert_eq!(renamed["label"], "Amount (EUR)");
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn cli_dedups_columns() {
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("metadata").arg("tests/data/duplicate_names.xpt");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--dedup-columns"));
+
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("preview")
+        .arg("tests/data/duplicate_names.xpt")
+        .arg("--dedup-columns");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("id,amount,amount_1"));
+}
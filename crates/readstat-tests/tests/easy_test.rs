@@ -0,0 +1,93 @@
+use assert_fs::TempDir;
+use readstat::prelude::{easy, OutFormat, ReadStatMetadata};
+use std::path::PathBuf;
+
+mod common;
+
+fn cars() -> PathBuf {
+    common::setup_path("cars.sas7bdat").unwrap().path
+}
+
+fn row_count() -> usize {
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&common::setup_path("cars.sas7bdat").unwrap(), false).unwrap();
+    md.row_count as usize
+}
+
+#[test]
+fn metadata_is_read_with_its_row_count() {
+    let md = easy::read_metadata(cars()).unwrap();
+    assert_eq!(md.row_count as usize, row_count());
+    assert_eq!(md.vars.get(&0).unwrap().var_name, "Brand");
+
+    assert!(easy::read_metadata("does_not_exist.sas7bdat").is_err());
+}
+
+#[test]
+fn batches_hold_every_row_by_default() {
+    let (schema, batches) = easy::read_to_batches(cars(), &easy::ReadOptions::default()).unwrap();
+    assert_eq!(schema.fields.len(), 13);
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].len(), row_count());
+}
+
+#[test]
+fn batches_are_limited_by_the_options() {
+    let options = easy::ReadOptions {
+        rows: Some(250),
+        columns: vec![String::from("EngineSize"), String::from("Brand")],
+        batch_rows: 100,
+        ..easy::ReadOptions::default()
+    };
+    let (schema, batches) = easy::read_to_batches(cars(), &options).unwrap();
+
+    // in file order, whatever the order listed
+    let names: Vec<&str> = schema.fields.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, vec!["Brand", "EngineSize"]);
+
+    let lens: Vec<usize> = batches.iter().map(|b| b.len()).collect();
+    assert_eq!(lens, vec![100, 100, 50]);
+    assert!(batches.iter().all(|b| b.arrays().len() == 2));
+}
+
+#[test]
+fn files_are_converted_and_not_overwritten_unless_asked() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("cars.csv");
+
+    let options = easy::ReadOptions::default();
+    let stats = easy::convert(cars(), &out_path, OutFormat::csv, &options).unwrap();
+    assert_eq!(stats.rows_written, row_count());
+    assert_eq!(stats.total_bytes_written(), std::fs::metadata(&out_path).unwrap().len());
+    let csv = std::fs::read_to_string(&out_path).unwrap();
+    assert_eq!(csv.lines().count(), row_count() + 1);
+
+    let e = easy::convert(cars(), &out_path, OutFormat::csv, &options)
+        .unwrap_err()
+        .to_string();
+    assert!(e.contains("already exists"), "{}", e);
+
+    let options = easy::ReadOptions {
+        rows: Some(10),
+        overwrite: true,
+        ..easy::ReadOptions::default()
+    };
+    let stats = easy::convert(cars(), &out_path, OutFormat::csv, &options).unwrap();
+    assert_eq!(stats.rows_written, 10);
+    assert_eq!(std::fs::read_to_string(&out_path).unwrap().lines().count(), 11);
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn parquet_and_feather_are_written_with_the_defaults() {
+    let out_dir = TempDir::new().unwrap();
+    let parquet = out_dir.path().join("cars.parquet");
+    let feather = out_dir.path().join("cars.feather");
+
+    assert_eq!(easy::to_parquet(cars(), &parquet).unwrap().rows_written, row_count());
+    assert_eq!(easy::to_feather(cars(), &feather).unwrap().rows_written, row_count());
+    assert!(parquet.exists() && feather.exists());
+
+    out_dir.close().unwrap();
+}
@@ -0,0 +1,128 @@
+use arrow2::{
+    datatypes::Field,
+    io::{
+        ipc::read::read_file_metadata,
+        parquet::read::{infer_schema, read_metadata},
+    },
+};
+use assert_cmd::Command;
+use assert_fs::TempDir;
+use predicates::prelude::*;
+use readstat::prelude::{
+    convert, read_batches, OutFormat, ReadStatConvertOptions, ReadStatData, ReadStatMetadata,
+    ReadStatPath,
+};
+use std::{fs::File, path::PathBuf};
+
+mod common;
+
+fn names(fields: &[Field]) -> Vec<&str> {
+    fields.iter().map(|f| f.name.as_str()).collect()
+}
+
+// no_rows written as `format`, returning the path written to while the directory is kept
+fn write_no_rows(out_dir: &TempDir, format: OutFormat, extension: &str) -> PathBuf {
+    let out_path = out_dir.path().join(format!("no_rows.{}", extension));
+    let rsp = ReadStatPath::new(
+        common::setup_path("no_rows.xpt").unwrap().path,
+        Some(out_path.clone()),
+        Some(format),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let rows = convert(&rsp, &ReadStatConvertOptions::default()).unwrap();
+    assert_eq!(rows, 0);
+    out_path
+}
+
+#[test]
+fn no_rows_preview_is_empty() {
+    let rsp = common::setup_path("no_rows.xpt").unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    assert_eq!((md.row_count, md.var_count), (0, 3));
+
+    let d = ReadStatData::new()
+        .set_no_progress(true)
+        .get_preview(md, &rsp, 10)
+        .unwrap();
+    assert_eq!(d.chunk().unwrap().len(), 0);
+    assert_eq!(d.chunk().unwrap().arrays().len(), 3);
+    assert_eq!(names(&d.schema().fields), vec!["id", "name", "score"]);
+}
+
+#[test]
+fn no_rows_batches_are_a_single_empty_batch() {
+    let rsp = common::setup_path("no_rows.xpt").unwrap();
+    let mut batches = read_batches(&rsp, &ReadStatConvertOptions::default()).unwrap();
+    assert_eq!(names(&batches.schema().fields), vec!["id", "name", "score"]);
+
+    let chunks: Vec<_> = (&mut batches).map(|c| c.unwrap()).collect();
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0].len(), 0);
+    assert_eq!(chunks[0].arrays().len(), 3);
+    assert_eq!(batches.collected().unwrap().rows, 0);
+}
+
+#[test]
+fn no_rows_csv_is_only_a_header() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = write_no_rows(&out_dir, OutFormat::csv, "csv");
+
+    assert_eq!(std::fs::read_to_string(&out_path).unwrap(), "id,name,score\n");
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn no_rows_feather_and_parquet_hold_the_schema() {
+    let out_dir = TempDir::new().unwrap();
+
+    let out_path = write_no_rows(&out_dir, OutFormat::feather, "feather");
+    let metadata = read_file_metadata(&mut File::open(&out_path).unwrap()).unwrap();
+    assert_eq!(names(&metadata.schema.fields), vec!["id", "name", "score"]);
+
+    let out_path = write_no_rows(&out_dir, OutFormat::parquet, "parquet");
+    let md = read_metadata(&mut File::open(&out_path).unwrap()).unwrap();
+    assert_eq!(md.num_rows, 0);
+    assert_eq!(names(&infer_schema(&md).unwrap().fields), vec!["id", "name", "score"]);
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn no_variables_is_an_error() {
+    let rsp = common::setup_path("no_variables.xpt").unwrap();
+    let e = convert(&rsp, &ReadStatConvertOptions::default())
+        .unwrap_err()
+        .to_string();
+    assert!(e.contains("holds no variables"));
+
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    assert_eq!(md.var_count, 0);
+    let e = ReadStatData::new()
+        .set_no_progress(true)
+        .get_preview(md, &rsp, 10)
+        .unwrap_err()
+        .to_string();
+    assert!(e.contains("holds no variables"));
+}
+
+#[test]
+fn cli_no_rows_and_no_variables() {
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("preview").arg("tests/data/no_rows.xpt");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("id,name,score"));
+
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("preview").arg("tests/data/no_variables.xpt");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("holds no variables"));
+}
@@ -0,0 +1,141 @@
+use arrow2::{array::Array, chunk::Chunk};
+use readstat::prelude::{
+    convert_collect, guess_encoding, OutputMode, ReadStatConvertOptions, ReadStatData,
+    ReadStatEncodingDetector, ReadStatEncodingGuess, ReadStatMetadata, ReadStatPath,
+};
+
+mod common;
+
+// WINDOWS-1252 encoded strings
+const CP1252: [&[u8]; 3] = [b"caf\xe9", b"\x93quoted\x94", b"na\xefve r\xe9sum\xe9"];
+
+#[test]
+fn guess_ascii_and_utf8() {
+    assert_eq!(guess_encoding(b"plain"), ReadStatEncodingGuess::Ascii);
+    assert_eq!(guess_encoding("café".as_bytes()), ReadStatEncodingGuess::Utf8);
+}
+
+#[test]
+fn guess_windows_1252() {
+    for bytes in CP1252 {
+        assert_eq!(guess_encoding(bytes), ReadStatEncodingGuess::Windows1252);
+    }
+
+    // 0x81 is undefined within WINDOWS-1252
+    assert_eq!(guess_encoding(b"\x81\xe9"), ReadStatEncodingGuess::Unknown);
+}
+
+#[test]
+fn guess_double_encoded_utf8() {
+    // café as UTF-8, decoded as WINDOWS-1252, then encoded again as UTF-8
+    assert_eq!(
+        guess_encoding("cafÃ©".as_bytes()),
+        ReadStatEncodingGuess::DoubleEncodedUtf8
+    );
+    assert_eq!(
+        guess_encoding("â€œquotedâ€".as_bytes()),
+        ReadStatEncodingGuess::DoubleEncodedUtf8
+    );
+}
+
+#[test]
+fn detector_warns_when_declared_utf8() {
+    let mut detector = ReadStatEncodingDetector::new();
+    detector.push(b"plain");
+    for bytes in CP1252 {
+        detector.push(bytes);
+    }
+
+    assert_eq!(detector.non_ascii, 3);
+    assert_eq!(detector.lossy, 3);
+    assert_eq!(detector.guess(), Some(ReadStatEncodingGuess::Windows1252));
+
+    let warning = detector.warning("UTF-8").unwrap();
+    assert!(warning.contains("UTF-8"));
+    assert!(warning.contains("--encoding WINDOWS-1252"));
+}
+
+#[test]
+fn detector_silent_when_declared_matches() {
+    let mut detector = ReadStatEncodingDetector::new();
+    for bytes in CP1252 {
+        detector.push(bytes);
+    }
+
+    // names as written within sas7bdat files
+    assert!(detector.warning("WLATIN1").is_none());
+    assert!(detector.warning("WINDOWS-1252").is_none());
+}
+
+#[test]
+fn detector_silent_for_ascii_and_utf8() {
+    let mut detector = ReadStatEncodingDetector::new();
+    detector.push(b"plain");
+    detector.push("café".as_bytes());
+
+    assert_eq!(detector.guess(), None);
+    assert!(detector.warning("UTF-8").is_none());
+}
+
+#[test]
+fn detector_merges_chunks() {
+    let mut first = ReadStatEncodingDetector::new();
+    first.push(CP1252[0]);
+    let mut second = ReadStatEncodingDetector::new();
+    second.push(CP1252[1]);
+
+    first.merge(&second);
+    assert_eq!(first.lossy, 2);
+    assert_eq!(first.guess(), Some(ReadStatEncodingGuess::Windows1252));
+}
+
+#[test]
+fn all_types_has_no_encoding_warning() {
+    let rsp = ReadStatPath::new(
+        common::setup_path("all_types.sas7bdat").unwrap().path,
+        None,
+        None,
+        false,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        ..ReadStatConvertOptions::default()
+    };
+    let collected = convert_collect(&rsp, &options).unwrap();
+
+    assert!(collected.warnings.is_empty());
+}
+
+// cars, declared as WINDOWS-1252, read with the encoding given to ReadStatData
+fn read_cars(encoding: Option<&str>) -> Result<Chunk<Box<dyn Array>>, String> {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+
+    let mut d = ReadStatData::new()
+        .set_no_progress(true)
+        .set_encoding(encoding.map(String::from))
+        .init(md, 0, 100);
+    d.read_data(&rsp).map_err(|e| e.to_string())?;
+    Ok(d.into_chunk().unwrap())
+}
+
+#[test]
+fn encoding_override_applies_to_data() {
+    // strings within cars are ASCII, so read alike whichever ASCII-compatible encoding is named
+    let declared = read_cars(None).unwrap();
+    let overridden = read_cars(Some("ISO-8859-1")).unwrap();
+    assert_eq!(declared.arrays(), overridden.arrays());
+}
+
+#[test]
+fn unknown_encoding_names_the_remedy() {
+    let e = read_cars(Some("NOT-AN-ENCODING")).unwrap_err();
+    assert!(e.contains("READSTAT_ERROR_UNSUPPORTED_CHARSET"));
+    assert!(e.contains("--encoding"));
+}
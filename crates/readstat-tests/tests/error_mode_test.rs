@@ -0,0 +1,117 @@
+use arrow2::{
+    array::{Array, BooleanArray},
+    datatypes::DataType,
+};
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert, convert_collect, Booleans, ErrorMode, OutFormat, OutputMode, ReadStatConvertOptions,
+    ReadStatData, ReadStatErrorReport, ReadStatMetadata, ReadStatPath, ReadStatTypeReason,
+};
+
+mod common;
+
+// _int holds 1234 and so cannot be read as a boolean
+fn options(error_mode: ErrorMode) -> ReadStatConvertOptions {
+    ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        booleans: Some(Booleans::Explicit(vec![String::from("_int")])),
+        error_mode,
+        ..ReadStatConvertOptions::default()
+    }
+}
+
+#[test]
+fn fail_fast_remains_the_default() {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    assert_eq!(ReadStatConvertOptions::default().error_mode, ErrorMode::FailFast);
+
+    let e = convert_collect(&rsp, &options(ErrorMode::FailFast))
+        .unwrap_err()
+        .to_string();
+    assert!(e.contains("_int"));
+    assert!(e.contains("row 0"));
+}
+
+#[test]
+fn values_that_cannot_be_read_are_null_and_recorded() {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+
+    let c = convert_collect(&rsp, &options(ErrorMode::Collect { max: 10 })).unwrap();
+    assert_eq!(c.rows, 3);
+    assert!(!c.cell_errors.is_empty());
+    assert_eq!(c.cell_errors[0].row, 0);
+    assert!(c.cell_errors.iter().all(|e| e.column == "_int"));
+    assert!(c.cell_errors[0].description.contains("1234"));
+
+    // the column is null where its values could not be read, while the rest are read as usual
+    let col = c.chunks[0].arrays()[0]
+        .as_any()
+        .downcast_ref::<BooleanArray>()
+        .unwrap();
+    assert_eq!(col.data_type(), &DataType::Boolean);
+    assert!(c.cell_errors.iter().all(|e| col.is_null(e.row)));
+    assert_eq!(c.chunks[0].arrays().len(), 8);
+}
+
+#[test]
+fn reading_stops_once_more_than_the_maximum_are_recorded() {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+
+    let e = convert_collect(&rsp, &options(ErrorMode::Collect { max: 0 }))
+        .unwrap_err()
+        .to_string();
+    assert!(e.contains("more than 0 values could not be read"));
+}
+
+#[test]
+fn cell_errors_are_available_once_read() {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    md.set_boolean_columns(&[String::from("_int")], ReadStatTypeReason::UserOverride)
+        .unwrap();
+
+    let mut d = ReadStatData::new()
+        .set_no_progress(true)
+        .set_error_mode(ErrorMode::Collect { max: 10 })
+        .init(md.clone(), 0, md.row_count as u32);
+    d.read_data(&rsp).unwrap();
+
+    assert!(!d.cell_errors().is_empty());
+    assert_eq!(d.cell_errors()[0].column, "_int");
+    assert_eq!(d.chunk().unwrap().len(), 3);
+}
+
+#[test]
+fn error_report_is_written_alongside_the_output() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("all_types.csv");
+    let rsp = ReadStatPath::new(
+        common::setup_path("all_types.sas7bdat").unwrap().path,
+        Some(out_path.clone()),
+        Some(OutFormat::csv),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Write,
+        error_report: true,
+        ..options(ErrorMode::Collect { max: 10 })
+    };
+    convert(&rsp, &options).unwrap();
+
+    let report_path = ReadStatErrorReport::sidecar_path(&out_path);
+    assert!(report_path.ends_with("all_types.csv.errors.json"));
+    let report: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(report_path).unwrap()).unwrap();
+    assert_eq!(report["table_name"], "ALL_TYPES");
+    assert_eq!(report["errors"][0]["row"], 0);
+    assert_eq!(report["errors"][0]["column"], "_int");
+
+    out_dir.close().unwrap();
+}
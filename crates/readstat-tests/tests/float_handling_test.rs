@@ -0,0 +1,94 @@
+use arrow2::array::Float64Array;
+use readstat::prelude::{
+    convert_collect, FloatHandling, OutputMode, ReadStatConvertOptions, ReadStatData,
+    ReadStatMetadata, ReadStatPath,
+};
+
+mod common;
+
+fn read(rsp: &ReadStatPath, float_handling: FloatHandling) -> ReadStatData {
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(rsp, false).unwrap();
+    let rows = md.row_count as u32;
+
+    let mut d = ReadStatData::new()
+        .set_no_progress(true)
+        .set_float_handling(float_handling)
+        .init(md, 0, rows);
+    d.read_data(rsp).unwrap();
+    d
+}
+
+// Every Float64 column of the chunk, by index
+fn floats(d: &ReadStatData) -> Vec<(usize, Float64Array)> {
+    d.chunk()
+        .unwrap()
+        .arrays()
+        .iter()
+        .enumerate()
+        .filter_map(|(i, a)| {
+            a.as_any()
+                .downcast_ref::<Float64Array>()
+                .map(|a| (i, a.clone()))
+        })
+        .collect()
+}
+
+#[test]
+fn all_types_is_unchanged_by_default() {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    assert_eq!(
+        ReadStatConvertOptions::default().float_handling,
+        FloatHandling::ReadStatDigits
+    );
+
+    let d = read(&rsp, FloatHandling::ReadStatDigits);
+    let ints = &floats(&d)[0].1;
+    assert_eq!(ints.value(0), 1234f64);
+    assert!(ints.is_null(2));
+
+    // the same as through a conversion left at its defaults
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        ..ReadStatConvertOptions::default()
+    };
+    let c = convert_collect(&rsp, &options).unwrap();
+    assert_eq!(c.chunks[0], *d.chunk().unwrap());
+}
+
+#[test]
+fn rounding_matches_formatting_to_14_decimal_places() {
+    for file in ["cars.sas7bdat", "scientific_notation.sas7bdat", "hasmissing.sas7bdat"] {
+        let rsp = common::setup_path(file).unwrap();
+        let rounded = read(&rsp, FloatHandling::ReadStatDigits);
+        let raw = read(&rsp, FloatHandling::Raw);
+
+        for ((i, r), (_, v)) in floats(&rounded).into_iter().zip(floats(&raw)) {
+            for (row, (r, v)) in r.iter().zip(v.iter()).enumerate() {
+                // as values were previously rounded, by way of a string
+                let expected = v.map(|v| format!("{:.14}", v).parse::<f64>().unwrap());
+                assert_eq!(
+                    r.map(|r| r.to_bits()),
+                    expected.map(|e| e.to_bits()),
+                    "{} column {} row {}",
+                    file,
+                    i,
+                    row
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn raw_keeps_values_as_stored() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let raw = read(&rsp, FloatHandling::Raw);
+
+    // cars was written on Windows, where values such as 4.6 are stored as 4.600000000000001
+    let differs = floats(&read(&rsp, FloatHandling::ReadStatDigits))
+        .into_iter()
+        .zip(floats(&raw))
+        .any(|((_, r), (_, v))| r.iter().zip(v.iter()).any(|(r, v)| r != v));
+    assert!(differs);
+}
@@ -0,0 +1,92 @@
+use readstat::prelude::{ReadStatFormatSpec, ReadStatMetadata, ReadStatSchema};
+use serde_json::Value;
+
+mod common;
+
+fn all_types_metadata() -> ReadStatMetadata {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    md
+}
+
+fn spec(name: &str, width: Option<u32>, decimals: Option<u32>) -> ReadStatFormatSpec {
+    ReadStatFormatSpec {
+        name: String::from(name),
+        width,
+        decimals,
+    }
+}
+
+#[test]
+fn formats_are_verbatim_with_parts() {
+    let md = all_types_metadata();
+    let var = |name: &str| md.vars.values().find(|vm| vm.var_name == name).unwrap();
+
+    for (name, raw, parts) in [
+        ("_float", "BEST12", spec("BEST", Some(12), None)),
+        ("_string", "$30", spec("$", Some(30), None)),
+        ("_date", "YYMMDD10", spec("YYMMDD", Some(10), None)),
+        ("_datetime", "DATETIME22", spec("DATETIME", Some(22), None)),
+        ("_time", "TIME", spec("TIME", None, None)),
+    ] {
+        let vm = var(name);
+        assert_eq!(vm.var_format, raw);
+        assert_eq!(vm.var_format_raw, raw);
+        assert_eq!(vm.var_format_parts, Some(parts));
+    }
+
+    // within the metadata json and the schema sidecar
+    let json = serde_json::to_value(&md).unwrap();
+    let datetime = json["vars"]
+        .as_object()
+        .unwrap()
+        .values()
+        .find(|v| v["var_name"] == "_datetime")
+        .unwrap();
+    assert_eq!(datetime["var_format_raw"], "DATETIME22");
+    assert_eq!(datetime["var_format_parts"]["name"], "DATETIME");
+    assert_eq!(datetime["var_format_parts"]["width"], 22);
+    assert_eq!(datetime["var_format_parts"]["decimals"], Value::Null);
+
+    let schema = ReadStatSchema::new(&md);
+    let string = schema.columns.iter().find(|c| c.name == "_string").unwrap();
+    assert_eq!(string.sas_format, "$30");
+    assert_eq!(string.sas_format_parts, Some(spec("$", Some(30), None)));
+}
+
+#[test]
+fn raw_format_survives_truncation() {
+    let mut md = all_types_metadata();
+    md.limit_label_lengths(8, false).unwrap();
+
+    let vm = md
+        .vars
+        .values()
+        .find(|vm| vm.var_name == "_datetime")
+        .unwrap();
+    assert_eq!(vm.var_format, "DATETIME");
+    assert_eq!(vm.var_format_raw, "DATETIME22");
+    assert_eq!(vm.var_format_parts, Some(spec("DATETIME", Some(22), None)));
+}
+
+#[test]
+fn formats_are_split_into_parts() {
+    assert_eq!(
+        ReadStatFormatSpec::parse("DATETIME22.3"),
+        Some(spec("DATETIME", Some(22), Some(3)))
+    );
+    assert_eq!(
+        ReadStatFormatSpec::parse("DATETIME22."),
+        Some(spec("DATETIME", Some(22), None))
+    );
+    assert_eq!(
+        ReadStatFormatSpec::parse("8.2"),
+        Some(spec("", Some(8), Some(2)))
+    );
+    assert_eq!(
+        ReadStatFormatSpec::parse("E8601DA"),
+        Some(spec("E8601DA", None, None))
+    );
+    assert_eq!(ReadStatFormatSpec::parse(""), None);
+}
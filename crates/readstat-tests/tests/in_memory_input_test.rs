@@ -0,0 +1,110 @@
+use readstat::prelude::{
+    convert_collect, OutputMode, ReadStatCancelled, ReadStatConvertOptions, ReadStatData,
+    ReadStatMetadata, ReadStatPath,
+};
+use std::{io::Cursor, ops::ControlFlow, path::PathBuf};
+
+mod common;
+
+fn read(rsp: &ReadStatPath) -> (ReadStatMetadata, ReadStatData) {
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(rsp, false).unwrap();
+    let rows = md.row_count as u32;
+
+    let mut d = ReadStatData::new().set_no_progress(true).init(md.clone(), 0, rows);
+    d.read_data(rsp).unwrap();
+    (md, d)
+}
+
+fn bytes(ds: &str) -> Vec<u8> {
+    std::fs::read(common::setup_path(ds).unwrap().path).unwrap()
+}
+
+#[test]
+fn bytes_are_read_as_the_file_is() {
+    let from_file = common::setup_path("all_types.sas7bdat").unwrap();
+    let from_bytes = ReadStatPath::from_bytes(
+        PathBuf::from("all_types.sas7bdat"),
+        bytes("all_types.sas7bdat"),
+    )
+    .unwrap();
+    assert!(from_file.input.is_none());
+    assert!(from_bytes.input.is_some());
+
+    let (file_md, file_d) = read(&from_file);
+    let (bytes_md, bytes_d) = read(&from_bytes);
+
+    assert_eq!(bytes_md.row_count, 3);
+    assert_eq!(bytes_md.to_json().unwrap(), file_md.to_json().unwrap());
+    assert_eq!(bytes_d.schema(), file_d.schema());
+    assert_eq!(bytes_d.chunk(), file_d.chunk());
+}
+
+#[test]
+fn metadata_may_be_read_alone() {
+    let rsp = ReadStatPath::from_reader(
+        PathBuf::from("cars.sas7bdat"),
+        Cursor::new(bytes("cars.sas7bdat")),
+    )
+    .unwrap();
+
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    assert_eq!(md.row_count, 1081);
+    assert_eq!(md.var_count, 13);
+    assert_eq!(md.table_name, "CARS");
+}
+
+#[test]
+fn transport_files_are_counted_and_read() {
+    // transport files are parsed with the xport parser, and their rows counted by reading them
+    let from_file = common::setup_path("scores.xpt").unwrap();
+    let from_bytes =
+        ReadStatPath::from_bytes(PathBuf::from("scores.xpt"), bytes("scores.xpt")).unwrap();
+
+    let (file_md, file_d) = read(&from_file);
+    let (bytes_md, bytes_d) = read(&from_bytes);
+
+    assert_eq!(bytes_md.row_count, file_md.row_count);
+    assert_eq!(bytes_d.chunk(), file_d.chunk());
+}
+
+#[test]
+fn conversions_read_the_input() {
+    let rsp =
+        ReadStatPath::from_bytes(PathBuf::from("cars.sas7bdat"), bytes("cars.sas7bdat")).unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        stream_rows: 100,
+        ..ReadStatConvertOptions::default()
+    };
+
+    let from_bytes = convert_collect(&rsp, &options).unwrap();
+    let from_file =
+        convert_collect(&common::setup_path("cars.sas7bdat").unwrap(), &options).unwrap();
+
+    assert_eq!(from_bytes.rows, 1081);
+    assert_eq!(from_bytes.chunks, from_file.chunks);
+}
+
+#[test]
+fn progress_is_reported_and_may_cancel() {
+    let rsp =
+        ReadStatPath::from_bytes(PathBuf::from("cars.sas7bdat"), bytes("cars.sas7bdat")).unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    let rows = md.row_count as u32;
+
+    let mut d = ReadStatData::new()
+        .set_no_progress(true)
+        .set_progress_callback(Box::new(|_, _| ControlFlow::Break(())))
+        .init(md, 0, rows);
+    let e = d.read_data(&rsp).unwrap_err();
+    assert!(e.downcast_ref::<ReadStatCancelled>().is_some());
+}
+
+#[test]
+fn names_must_have_a_known_extension() {
+    assert!(ReadStatPath::from_bytes(PathBuf::from("cars.csv"), bytes("cars.sas7bdat")).is_err());
+    assert!(ReadStatPath::from_bytes(PathBuf::from("cars"), bytes("cars.sas7bdat")).is_err());
+}
@@ -0,0 +1,126 @@
+use arrow2::{
+    array::{Float64Array, Int64Array},
+    datatypes::DataType,
+};
+use assert_cmd::Command;
+use predicates::prelude::*;
+use readstat::prelude::{
+    convert_collect, read_batches, OutputMode, ReadStatConvertOptions, ReadStatFormatSpec,
+    ReadStatMetadata, ReadStatTypeReason,
+};
+
+mod common;
+
+fn options(infer_integers: bool) -> ReadStatConvertOptions {
+    ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        infer_integers,
+        ..ReadStatConvertOptions::default()
+    }
+}
+
+fn data_types(infer_integers: bool) -> Vec<DataType> {
+    let rsp = common::setup_path("orders.xpt").unwrap();
+    let mut md = ReadStatMetadata::new().set_infer_integers(infer_integers);
+    md.read_metadata(&rsp, false).unwrap();
+    md.schema.fields.iter().map(|f| f.data_type().clone()).collect()
+}
+
+#[test]
+fn formats_without_decimals_are_integral() {
+    let integral = |format: &str| ReadStatFormatSpec::parse(format).unwrap().is_integral();
+
+    assert!(integral("8"));
+    assert!(integral("F8"));
+    assert!(integral("F8.0"));
+    assert!(integral("COMMA6"));
+    assert!(!integral("8.2"));
+    assert!(!integral("COMMA10.2"));
+    assert!(!integral("BEST12"));
+    assert!(!integral("DOLLAR8"));
+}
+
+#[test]
+fn numerics_are_float64_by_default() {
+    assert!(data_types(false).iter().all(|dt| dt == &DataType::Float64));
+}
+
+#[test]
+fn numerics_formatted_without_decimals_are_int64() {
+    // id is F8, quantity COMMA6, price 8.2, and weight BEST12
+    assert_eq!(
+        data_types(true),
+        vec![DataType::Int64, DataType::Int64, DataType::Float64, DataType::Float64]
+    );
+
+    let rsp = common::setup_path("orders.xpt").unwrap();
+    let mut md = ReadStatMetadata::new().set_infer_integers(true);
+    md.read_metadata(&rsp, false).unwrap();
+    assert_eq!(md.type_decisions[&1].reason, ReadStatTypeReason::IntegerInference);
+    assert_eq!(md.type_decisions[&2].reason, ReadStatTypeReason::DefaultMapping);
+    assert!(md.type_report().contains("integer inference"));
+}
+
+#[test]
+fn values_are_read_as_integers() {
+    let rsp = common::setup_path("orders.xpt").unwrap();
+    let c = convert_collect(&rsp, &options(true)).unwrap();
+    let arrays = c.chunks[0].arrays();
+
+    let quantity = arrays[1].as_any().downcast_ref::<Int64Array>().unwrap();
+    assert_eq!(
+        quantity.iter().map(|v| v.copied()).collect::<Vec<_>>(),
+        vec![Some(3), Some(1), None, Some(12000)]
+    );
+
+    let price = arrays[2].as_any().downcast_ref::<Float64Array>().unwrap();
+    assert_eq!(price.value(2), 7.5);
+}
+
+#[test]
+fn every_batch_holds_the_same_types() {
+    // the third batch holds only a missing quantity, yet is Int64 alike
+    let rsp = common::setup_path("orders.xpt").unwrap();
+    let options = ReadStatConvertOptions {
+        stream_rows: 1,
+        ..options(true)
+    };
+
+    let chunks: Vec<_> = read_batches(&rsp, &options)
+        .unwrap()
+        .map(|c| c.unwrap())
+        .collect();
+    assert_eq!(chunks.len(), 4);
+    for c in &chunks {
+        assert_eq!(c.arrays()[0].data_type(), &DataType::Int64);
+        assert_eq!(c.arrays()[1].data_type(), &DataType::Int64);
+    }
+    assert_eq!(chunks[2].arrays()[1].null_count(), 1);
+}
+
+#[test]
+fn a_fraction_is_an_error() {
+    let rsp = common::setup_path("orders_fractional.xpt").unwrap();
+
+    let e = convert_collect(&rsp, &options(true)).unwrap_err().to_string();
+    assert!(e.contains("quantity"));
+    assert!(e.contains("2.5"));
+    assert!(e.contains("not a whole number"));
+
+    // read as Float64 without inferring integers
+    assert!(convert_collect(&rsp, &options(false)).is_ok());
+}
+
+#[test]
+fn cli_infers_integers() {
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("preview")
+        .arg("tests/data/orders.xpt")
+        .arg("--infer-integers")
+        .arg("--table");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("12000"))
+        .stdout(predicate::str::contains("12000.0").not())
+        .stdout(predicate::str::contains("7.5"));
+}
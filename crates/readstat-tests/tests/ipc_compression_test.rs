@@ -0,0 +1,65 @@
+use arrow2::{
+    array::Array,
+    chunk::Chunk,
+    io::ipc::read::{read_file_metadata, FileReader},
+};
+use assert_fs::TempDir;
+use readstat::prelude::{convert, IpcCompression, OutFormat, ReadStatConvertOptions, ReadStatPath};
+use std::fs::File;
+
+mod common;
+
+// (file size, chunks read back) of cars written as feather
+fn roundtrip(ipc_compression: IpcCompression) -> (u64, Vec<Chunk<Box<dyn Array>>>) {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("cars.feather");
+
+    let rsp = ReadStatPath::new(
+        common::setup_path("cars.sas7bdat").unwrap().path,
+        Some(out_path.clone()),
+        Some(OutFormat::feather),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let options = ReadStatConvertOptions {
+        ipc_compression,
+        stream_rows: 500,
+        ..ReadStatConvertOptions::default()
+    };
+    let rows = convert(&rsp, &options).unwrap();
+    assert_eq!(rows, 1081);
+
+    let size = std::fs::metadata(&out_path).unwrap().len();
+
+    let mut f = File::open(&out_path).unwrap();
+    let metadata = read_file_metadata(&mut f).unwrap();
+    let chunks = FileReader::new(f, metadata, None, None)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    out_dir.close().unwrap();
+    (size, chunks)
+}
+
+#[test]
+fn ipc_compression_roundtrips() {
+    let (none_size, none) = roundtrip(IpcCompression::None);
+    let (lz4_size, lz4) = roundtrip(IpcCompression::Lz4);
+    let (zstd_size, zstd) = roundtrip(IpcCompression::Zstd);
+
+    // 1,081 rows streamed 500 at a time
+    assert_eq!(none.len(), 3);
+    assert_eq!(none.iter().map(|c| c.len()).sum::<usize>(), 1081);
+
+    // compressed output reads back identically to uncompressed output
+    assert_eq!(lz4, none);
+    assert_eq!(zstd, none);
+
+    // and is in fact compressed
+    assert!(lz4_size < none_size);
+    assert!(zstd_size < none_size);
+}
@@ -0,0 +1,111 @@
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert, OutFormat, ReadStatConvertOptions, ReadStatMapping, ReadStatMetadata, ReadStatPath,
+};
+use serde_json::Value;
+use std::path::Path;
+
+mod common;
+
+// all_types with a 300 character label given to its first variable
+fn long_label_metadata() -> ReadStatMetadata {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    md.vars.get_mut(&0).unwrap().var_label = "é".repeat(300);
+    md
+}
+
+fn convert_all_types(out_path: &Path, strict: bool) -> Result<usize, String> {
+    let rsp = ReadStatPath::new(
+        common::setup_path("all_types.sas7bdat").unwrap().path,
+        Some(out_path.to_path_buf()),
+        Some(OutFormat::csv),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    // DATETIME22 is the only format longer than 8 characters
+    let options = ReadStatConvertOptions {
+        max_label_length: Some(8),
+        strict,
+        ..ReadStatConvertOptions::default()
+    };
+    convert(&rsp, &options).map_err(|e| e.to_string())
+}
+
+#[test]
+fn long_label_truncated_with_warning() {
+    let mut md = long_label_metadata();
+    let warnings = md.limit_label_lengths(256, false).unwrap();
+
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("from 300 to 256 characters"));
+
+    // characters rather than bytes are counted
+    assert_eq!(md.vars[&0].var_label, "é".repeat(256));
+    assert_eq!(md.truncations[&0].label, Some("é".repeat(300)));
+    assert_eq!(md.truncations[&0].format, None);
+    assert_eq!(md.truncations.len(), 1);
+
+    // within budget, so unchanged
+    let warnings = md.limit_label_lengths(256, false).unwrap();
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn long_label_is_an_error_when_strict() {
+    let mut md = long_label_metadata();
+    let name = md.vars[&0].var_name.clone();
+
+    let e = md.limit_label_lengths(256, true).unwrap_err().to_string();
+    assert!(e.contains(&name));
+    assert!(e.contains("256"));
+
+    // nothing is truncated
+    assert_eq!(md.vars[&0].var_label.chars().count(), 300);
+    assert!(md.truncations.is_empty());
+}
+
+#[test]
+fn truncation_recorded_within_mapping_sidecar() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("all_types.csv");
+    convert_all_types(&out_path, false).unwrap();
+
+    let text = std::fs::read_to_string(ReadStatMapping::sidecar_path(&out_path)).unwrap();
+    let mapping: Value = serde_json::from_str(&text).unwrap();
+
+    let mut truncated = 0;
+    for v in mapping["variables"].as_array().unwrap() {
+        assert!(v["format"].as_str().unwrap().chars().count() <= 8);
+        match v.get("original_format") {
+            Some(original) => {
+                assert_eq!(original, "DATETIME22");
+                assert_eq!(v["format"], "DATETIME");
+                truncated += 1;
+            }
+            None => assert_ne!(v["format"], "DATETIME"),
+        }
+        assert!(v.get("original_label").is_none());
+    }
+    assert!(truncated > 0);
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn strict_conversion_fails_without_writing() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("all_types.csv");
+
+    let e = convert_all_types(&out_path, true).unwrap_err();
+    assert!(e.contains("exceeds 8 characters"));
+    assert!(!out_path.exists());
+    assert!(!ReadStatMapping::sidecar_path(&out_path).exists());
+
+    out_dir.close().unwrap();
+}
@@ -0,0 +1,143 @@
+use arrow2::{
+    array::Utf8Array,
+    chunk::Chunk,
+    datatypes::{DataType, Field, Schema},
+};
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert, LineEnding, OutFormat, ReadStatConvertOptions, ReadStatData, ReadStatPath,
+    ReadStatWriter,
+};
+use std::path::Path;
+
+mod common;
+
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+fn convert_cars(
+    out_path: &Path,
+    format: OutFormat,
+    bom: bool,
+    line_ending: LineEnding,
+) -> Vec<u8> {
+    let rsp = ReadStatPath::new(
+        common::setup_path("cars.sas7bdat").unwrap().path,
+        Some(out_path.to_path_buf()),
+        Some(format),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    // many batches, so that the file is appended to
+    let options = ReadStatConvertOptions {
+        stream_rows: 100,
+        bom,
+        line_ending,
+        ..ReadStatConvertOptions::default()
+    };
+    convert(&rsp, &options).unwrap();
+
+    std::fs::read(out_path).unwrap()
+}
+
+// every line terminator is \r\n, and there are as many as there are lines
+fn assert_crlf(bytes: &[u8], lines: usize) {
+    let newlines: Vec<usize> = (0..bytes.len()).filter(|&i| bytes[i] == b'\n').collect();
+    assert_eq!(newlines.len(), lines);
+    assert!(newlines.iter().all(|&i| i > 0 && bytes[i - 1] == b'\r'));
+    assert!(bytes.ends_with(b"\r\n"));
+}
+
+#[test]
+fn csv_with_bom_and_crlf() {
+    let out_dir = TempDir::new().unwrap();
+    let bytes = convert_cars(
+        &out_dir.path().join("cars.csv"),
+        OutFormat::csv,
+        true,
+        LineEnding::Crlf,
+    );
+
+    // byte order mark only once, ahead of the header
+    assert_eq!(&bytes[..3], BOM);
+    assert!(bytes[3..].starts_with(b"Brand,Model,"));
+    assert!(!bytes[3..].windows(3).any(|w| w == BOM));
+
+    // header and 1081 rows
+    assert_crlf(&bytes, 1082);
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn ndjson_with_bom_and_crlf() {
+    let out_dir = TempDir::new().unwrap();
+    let bytes = convert_cars(
+        &out_dir.path().join("cars.ndjson"),
+        OutFormat::ndjson,
+        true,
+        LineEnding::Crlf,
+    );
+
+    assert_eq!(&bytes[..3], BOM);
+    assert_eq!(bytes[3], b'{');
+    assert_crlf(&bytes, 1081);
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn defaults_are_lf_without_bom() {
+    let out_dir = TempDir::new().unwrap();
+
+    for (name, format, lines) in [
+        ("cars.csv", OutFormat::csv, 1082),
+        ("cars.ndjson", OutFormat::ndjson, 1081),
+    ] {
+        let bytes = convert_cars(&out_dir.path().join(name), format, false, LineEnding::Lf);
+
+        assert_ne!(&bytes[..3], BOM);
+        assert!(!bytes.contains(&b'\r'));
+        assert_eq!(bytes.iter().filter(|&&b| b == b'\n').count(), lines);
+    }
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn newlines_within_quoted_csv_values_are_kept() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("quoted.csv");
+
+    // input is only used to satisfy path validation; data is supplied directly
+    let rsp = ReadStatPath::new(
+        common::setup_path("all_types.sas7bdat").unwrap().path,
+        Some(out_path.clone()),
+        Some(OutFormat::csv),
+        false,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let schema = Schema::from(vec![Field::new("note", DataType::Utf8, true)]);
+    let values = Utf8Array::<i32>::from_slice(["one\ntwo", "say \"hi\"\nthen", "plain"]);
+    let d = ReadStatData::new().set_chunk(schema, Chunk::new(vec![values.boxed()]));
+
+    let mut wtr = ReadStatWriter::new()
+        .set_quiet(true)
+        .set_line_ending(LineEnding::Crlf);
+    wtr.write(&d, &rsp).unwrap();
+    wtr.finish(&d, &rsp).unwrap();
+
+    assert_eq!(
+        std::fs::read_to_string(&out_path).unwrap(),
+        "note\r\n\"one\ntwo\"\r\n\"say \"\"hi\"\"\nthen\"\r\nplain\r\n"
+    );
+
+    out_dir.close().unwrap();
+}
@@ -0,0 +1,116 @@
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert, NameTransform, OutFormat, ReadStatConvertOptions, ReadStatMapping, ReadStatMetadata,
+    ReadStatPath,
+};
+use serde_json::Value;
+use std::path::Path;
+
+mod common;
+
+fn convert_all_types(out_path: &Path, options: &ReadStatConvertOptions) {
+    let input = common::setup_path("all_types.sas7bdat").unwrap().path;
+    let rsp = ReadStatPath::new(
+        input,
+        Some(out_path.to_path_buf()),
+        Some(OutFormat::csv),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    convert(&rsp, options).unwrap();
+}
+
+fn find<'a>(mapping: &'a Value, original_name: &str) -> &'a Value {
+    mapping["variables"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|v| v["original_name"] == original_name)
+        .unwrap()
+}
+
+#[test]
+fn mapping_sidecar_records_every_rename() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("all_types.csv");
+
+    // two renaming mechanisms - a transform of every name and an explicit rename
+    let options = ReadStatConvertOptions {
+        name_transform: NameTransform::Uppercase,
+        renames: vec![(String::from("_int"), String::from("id"))],
+        ..ReadStatConvertOptions::default()
+    };
+    convert_all_types(&out_path, &options);
+
+    let sidecar = ReadStatMapping::sidecar_path(&out_path);
+    assert_eq!(sidecar.file_name().unwrap(), "all_types.csv.mapping.json");
+
+    let mapping: Value =
+        serde_json::from_str(&std::fs::read_to_string(&sidecar).unwrap()).unwrap();
+    assert_eq!(mapping["variables"].as_array().unwrap().len(), 8);
+
+    // explicit rename takes precedence over the transform
+    let int = find(&mapping, "_int");
+    assert_eq!(int["final_name"], "id");
+    assert_eq!(int["renamed_by"], "user rename");
+    assert_eq!(int["arrow_type"], "Float64");
+
+    let date = find(&mapping, "_date");
+    assert_eq!(date["final_name"], "_DATE");
+    assert_eq!(date["renamed_by"], "name transform");
+    assert_eq!(date["format"], "YYMMDD10");
+    assert_eq!(date["arrow_type"], "Date32");
+
+    // header reflects the final names
+    let csv = std::fs::read_to_string(&out_path).unwrap();
+    assert!(csv.starts_with("id,_FLOAT,_CHAR,_STRING,_DATE,"));
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn mapping_sidecar_not_written_without_renames() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("all_types.csv");
+
+    convert_all_types(&out_path, &ReadStatConvertOptions::default());
+
+    assert!(out_path.exists());
+    assert!(!ReadStatMapping::sidecar_path(&out_path).exists());
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn mapping_sidecar_can_be_disabled() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("all_types.csv");
+
+    let options = ReadStatConvertOptions {
+        name_transform: NameTransform::Lowercase,
+        renames: vec![(String::from("_int"), String::from("id"))],
+        mapping_sidecar: false,
+        ..ReadStatConvertOptions::default()
+    };
+    convert_all_types(&out_path, &options);
+
+    assert!(out_path.exists());
+    assert!(!ReadStatMapping::sidecar_path(&out_path).exists());
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn rename_to_an_existing_name_is_an_error() {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+
+    let renames = vec![(String::from("_int"), String::from("_float"))];
+    assert!(md.rename_vars(&renames).is_err());
+}
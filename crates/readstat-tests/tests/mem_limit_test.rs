@@ -0,0 +1,253 @@
+use assert_cmd::Command;
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert_collect, resolve_reader, MemLimitAction, OutFormat, ReadStatConvertOptions,
+    ReadStatData, ReadStatMetadata, ReadStatPath, ReadStatStats, ReadStatVarTypeClass, Reader,
+};
+use std::{num::NonZeroU32, path::Path};
+
+mod common;
+
+fn cars_metadata() -> ReadStatMetadata {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    md
+}
+
+#[test]
+fn estimate_scales_with_rows() {
+    let md = cars_metadata();
+
+    let one = md.estimate_memory_bytes(1);
+    assert!(one > 0);
+    assert_eq!(md.estimate_memory_bytes(0), 0);
+    assert_eq!(md.estimate_memory_bytes(1000), one * 1000);
+}
+
+#[test]
+fn estimate_counts_string_widths() {
+    let md = cars_metadata();
+
+    // widening a string format increases the estimate by the added width, held twice
+    let (k, _) = md
+        .vars
+        .iter()
+        .find(|(_, vm)| matches!(vm.var_type_class, ReadStatVarTypeClass::String))
+        .unwrap();
+    let mut wider = md.clone();
+    wider.vars.get_mut(k).unwrap().var_format = String::from("$1000");
+
+    assert!(wider.estimate_memory_bytes(1) > md.estimate_memory_bytes(1) + 1000);
+}
+
+#[test]
+fn estimate_falls_back_upon_storage_widths() {
+    let md = cars_metadata();
+    let (k, _) = md
+        .vars
+        .iter()
+        .find(|(_, vm)| matches!(vm.var_type_class, ReadStatVarTypeClass::String))
+        .unwrap();
+
+    // without a width within the format, the storage width is used, or else 8 bytes
+    let mut stored = md.clone();
+    let vm = stored.vars.get_mut(k).unwrap();
+    vm.var_format = String::from("$");
+    vm.var_storage_width = 1000;
+    let mut unknown = stored.clone();
+    unknown.vars.get_mut(k).unwrap().var_storage_width = 0;
+
+    let difference = stored.estimate_memory_bytes(1) - unknown.estimate_memory_bytes(1);
+    assert_eq!(difference, 2 * (1000 - 8));
+}
+
+#[test]
+fn mem_reader_refused_on_huge_file() {
+    let mut md = cars_metadata();
+
+    // mock a file far larger than any limit
+    md.row_count = i32::MAX;
+    let rows = md.row_count as u32;
+    let limit = Some(1024 * 1024 * 1024);
+
+    let refused = resolve_reader(&md, rows, Reader::mem, limit, MemLimitAction::Error);
+    assert!(refused.unwrap_err().to_string().contains("--reader stream"));
+
+    let (reader, warning) =
+        resolve_reader(&md, rows, Reader::mem, limit, MemLimitAction::Stream).unwrap();
+    assert!(matches!(reader, Reader::stream));
+    assert!(warning.unwrap().contains("streaming instead"));
+}
+
+#[test]
+fn mem_reader_allowed_within_limit() {
+    let md = cars_metadata();
+    let rows = md.row_count as u32;
+
+    let (reader, warning) = resolve_reader(
+        &md,
+        rows,
+        Reader::mem,
+        Some(1024 * 1024 * 1024),
+        MemLimitAction::Error,
+    )
+    .unwrap();
+    assert!(matches!(reader, Reader::mem));
+    assert!(warning.is_none());
+
+    // the stream reader is never limited
+    let (reader, _) =
+        resolve_reader(&md, u32::MAX, Reader::stream, Some(1), MemLimitAction::Error).unwrap();
+    assert!(matches!(reader, Reader::stream));
+}
+
+#[test]
+fn data_refused_ahead_of_allocating_above_memory_limit() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let md = cars_metadata();
+    let rows = md.row_count as u32;
+    let estimate = md.estimate_memory_bytes(rows);
+
+    let mut d = ReadStatData::new()
+        .set_no_progress(true)
+        .set_memory_limit(1024)
+        .init(md.clone(), 0, rows);
+    assert_eq!(d.memory_estimate(), Some(estimate));
+    let e = d.read_data(&rsp).unwrap_err().to_string();
+    assert!(e.contains("above the memory limit"), "{}", e);
+    assert!(d.chunk().is_none());
+
+    let mut d = ReadStatData::new()
+        .set_no_progress(true)
+        .set_memory_limit(estimate)
+        .init(md, 0, rows);
+    d.read_data(&rsp).unwrap();
+    assert_eq!(d.chunk().unwrap().len(), rows as usize);
+}
+
+// Convert cars into csv within dir, returning the csv written and the statistics of converting
+fn convert_cars(
+    dir: &Path,
+    name: &str,
+    options: &ReadStatConvertOptions,
+) -> (String, ReadStatStats) {
+    let out_path = dir.join(name);
+    let rsp = ReadStatPath::new(
+        common::setup_path("cars.sas7bdat").unwrap().path,
+        Some(out_path.clone()),
+        Some(OutFormat::csv),
+        false,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+    let collected = convert_collect(&rsp, options).unwrap();
+    (std::fs::read_to_string(out_path).unwrap(), collected.stats)
+}
+
+#[test]
+fn mem_reader_spills_to_stream_reader_with_identical_output() {
+    let md = cars_metadata();
+    let rows = md.row_count as u32;
+    let out_dir = TempDir::new().unwrap();
+
+    let options = ReadStatConvertOptions {
+        reader: Reader::mem,
+        no_progress: true,
+        ..ReadStatConvertOptions::default()
+    };
+    let (mem, stats) = convert_cars(out_dir.path(), "mem.csv", &options);
+    assert_eq!(stats.reader, Reader::mem);
+    assert_eq!(stats.batches, 1);
+    assert_eq!(stats.memory_estimate, md.estimate_memory_bytes(rows));
+
+    // only 100 rows fit within the limit, so batches of 100 rows are streamed
+    let options = ReadStatConvertOptions {
+        mem_limit: Some(md.estimate_memory_bytes(100)),
+        mem_limit_action: MemLimitAction::Stream,
+        ..options
+    };
+    let (streamed, stats) = convert_cars(out_dir.path(), "streamed.csv", &options);
+    assert_eq!(stats.reader, Reader::stream);
+    assert_eq!(stats.batch_rows, 100);
+    assert_eq!(stats.batches, (rows as usize).div_ceil(100));
+    assert_eq!(stats.rows_written, rows as usize);
+    assert_eq!(streamed, mem);
+
+    // batches are never larger than stream_rows
+    let options = ReadStatConvertOptions {
+        stream_rows: 50,
+        ..options
+    };
+    let (streamed, stats) = convert_cars(out_dir.path(), "smaller.csv", &options);
+    assert_eq!(stats.batch_rows, 50);
+    assert_eq!(streamed, mem);
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn batch_rows_override_the_reader_and_limit() {
+    let md = cars_metadata();
+    let rows = md.row_count as u32;
+    let out_dir = TempDir::new().unwrap();
+
+    let options = ReadStatConvertOptions {
+        reader: Reader::mem,
+        no_progress: true,
+        ..ReadStatConvertOptions::default()
+    };
+    let (mem, _) = convert_cars(out_dir.path(), "mem.csv", &options);
+
+    // the mem reader reads batches of the chosen rows, as does a streamed fallback
+    let options = ReadStatConvertOptions {
+        batch_rows: NonZeroU32::new(250),
+        ..options
+    };
+    let (batched, stats) = convert_cars(out_dir.path(), "batched.csv", &options);
+    assert_eq!(stats.reader, Reader::mem);
+    assert_eq!(stats.batch_rows, 250);
+    assert_eq!(stats.batches, (rows as usize).div_ceil(250));
+    assert_eq!(batched, mem);
+
+    let options = ReadStatConvertOptions {
+        mem_limit: Some(md.estimate_memory_bytes(100)),
+        mem_limit_action: MemLimitAction::Stream,
+        ..options
+    };
+    let (streamed, stats) = convert_cars(out_dir.path(), "streamed.csv", &options);
+    assert_eq!(stats.reader, Reader::stream);
+    assert_eq!(stats.batch_rows, 250);
+    assert_eq!(streamed, mem);
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn cli_batch_rows_must_be_at_least_one() {
+    let out_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("data")
+        .arg("tests/data/cars.sas7bdat")
+        .arg("--output")
+        .arg(out_dir.path().join("cars.csv"))
+        .args(["--batch-rows", "0"]);
+    cmd.assert().failure();
+
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("data")
+        .arg("tests/data/cars.sas7bdat")
+        .arg("--output")
+        .arg(out_dir.path().join("cars.csv"))
+        .args(["--reader", "mem"])
+        .args(["--batch-rows", "250"])
+        .arg("--no-progress");
+    cmd.assert().success();
+    let csv = std::fs::read_to_string(out_dir.path().join("cars.csv")).unwrap();
+    assert_eq!(csv.lines().count(), 1082);
+
+    out_dir.close().unwrap();
+}
@@ -0,0 +1,91 @@
+use assert_cmd::Command;
+use readstat::prelude::ReadStatMetadata;
+use serde_json::Value;
+
+mod common;
+
+fn metadata(file: &str) -> ReadStatMetadata {
+    let rsp = common::setup_path(file).unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    md
+}
+
+#[test]
+fn variables_are_ordered_by_index() {
+    let json = metadata("cars.sas7bdat").to_json().unwrap();
+
+    // serde_json sorts the keys of objects it parses, so the order is checked within the text
+    let positions: Vec<usize> = (0..13)
+        .map(|i| json.find(&format!("\"var_index\": {},", i)).unwrap())
+        .collect();
+    assert!(positions.windows(2).all(|w| w[0] < w[1]));
+
+    let v: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(v["vars"]["12"]["var_index"], 12);
+    assert_eq!(v["vars"]["0"]["var_name"], "Brand");
+}
+
+#[test]
+fn timestamps_are_iso_8601_and_enums_are_names() {
+    let md = metadata("cars.sas7bdat");
+    let v: Value = serde_json::from_str(&md.to_json().unwrap()).unwrap();
+
+    // held as displayed, written as ISO-8601
+    assert_eq!(md.creation_time, "2008-09-30 14:55:01");
+    assert_eq!(v["creation_time"], "2008-09-30T14:55:01");
+    assert_eq!(v["modified_time"], "2008-09-30T14:55:01");
+
+    assert_eq!(v["compression"], "None");
+    assert_eq!(v["endianness"], "Little");
+    assert_eq!(v["vars"]["0"]["var_type"], "String");
+    assert_eq!(v["vars"]["0"]["var_type_class"], "String");
+    assert_eq!(v["vars"]["2"]["var_type"], "Double");
+    assert_eq!(v["vars"]["2"]["var_type_class"], "Numeric");
+}
+
+#[test]
+fn variables_include_their_arrow_type() {
+    let json = metadata("all_types.sas7bdat").to_json().unwrap();
+    let v: Value = serde_json::from_str(&json).unwrap();
+
+    let arrow_type = |name: &str| {
+        v["vars"]
+            .as_object()
+            .unwrap()
+            .values()
+            .find(|vm| vm["var_name"] == name)
+            .unwrap()["var_arrow_type"]
+            .clone()
+    };
+    assert_eq!(arrow_type("_int"), "Float64");
+    assert_eq!(arrow_type("_string"), "Utf8");
+    assert_eq!(arrow_type("_date"), "Date32");
+    assert_eq!(arrow_type("_datetime_with_ms"), "Timestamp(Millisecond)");
+    assert_eq!(v["vars"]["0"]["var_format_class"], Value::Null);
+
+    // unknown alignments and measures are null rather than zero
+    let string = v["vars"]["3"].as_object().unwrap();
+    assert_eq!(string["var_storage_width"], 30);
+    assert!(string.contains_key("var_display_width"));
+    assert_eq!(string["var_alignment"], Value::Null);
+    assert_eq!(string["var_measure"], Value::Null);
+}
+
+#[test]
+fn cli_json_matches_the_library() {
+    let output = Command::cargo_bin("readstat")
+        .unwrap()
+        .arg("metadata")
+        .arg("tests/data/cars.sas7bdat")
+        .arg("--json")
+        .arg("--no-progress")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let v: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(v["table_name"], "CARS");
+    assert_eq!(v["creation_time"], "2008-09-30T14:55:01");
+    assert_eq!(v["vars"].as_object().unwrap().len(), 13);
+}
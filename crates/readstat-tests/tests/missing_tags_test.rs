@@ -0,0 +1,156 @@
+use arrow2::array::{Float64Array, Utf8Array};
+use assert_cmd::Command;
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert_collect, missing_tag_name, OutputMode, ReadStatConvertOptions, ReadStatData,
+    ReadStatMetadata, ReadStatPath,
+};
+use std::collections::BTreeSet;
+
+mod common;
+
+fn read(rsp: &ReadStatPath, missing_tags: bool) -> ReadStatData {
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(rsp, false).unwrap();
+    let rows = md.row_count as u32;
+
+    let mut d = ReadStatData::new()
+        .set_no_progress(true)
+        .set_missing_tags(missing_tags)
+        .init(md, 0, rows);
+    d.read_data(rsp).unwrap();
+    d
+}
+
+fn floats(d: &ReadStatData, i: usize) -> Vec<Option<f64>> {
+    d.chunk().unwrap().arrays()[i]
+        .as_any()
+        .downcast_ref::<Float64Array>()
+        .unwrap()
+        .iter()
+        .map(|v| v.copied())
+        .collect()
+}
+
+fn strings(d: &ReadStatData, i: usize) -> Vec<Option<String>> {
+    d.chunk().unwrap().arrays()[i]
+        .as_any()
+        .downcast_ref::<Utf8Array<i32>>()
+        .unwrap()
+        .iter()
+        .map(|v| v.map(String::from))
+        .collect()
+}
+
+fn tags(tags: &[char]) -> BTreeSet<char> {
+    tags.iter().copied().collect()
+}
+
+#[test]
+fn output_is_unchanged_by_default() {
+    let rsp = common::setup_path("tagged_missing.xpt").unwrap();
+    let d = read(&rsp, false);
+
+    assert_eq!(d.schema().fields.len(), 4);
+    assert_eq!(d.chunk().unwrap().arrays().len(), 4);
+
+    // special missing values are missing, as are system missing values
+    assert_eq!(floats(&d, 1), vec![Some(1.0), None, None, None, Some(0.0)]);
+    assert_eq!(floats(&d, 2), vec![Some(34.0), Some(51.0), None, None, None]);
+}
+
+#[test]
+fn tags_observed_are_recorded_upon_the_metadata() {
+    let rsp = common::setup_path("tagged_missing.xpt").unwrap();
+
+    // only known once read
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    assert!(md.vars.values().all(|vm| vm.var_missing_tags.is_empty()));
+
+    let d = read(&rsp, false);
+    assert_eq!(common::get_metadata(&d, 0).var_missing_tags, tags(&[]));
+    assert_eq!(common::get_metadata(&d, 1).var_missing_tags, tags(&['A', 'B']));
+    assert_eq!(common::get_metadata(&d, 2).var_missing_tags, tags(&['A', 'B']));
+    assert_eq!(common::get_metadata(&d, 3).var_missing_tags, tags(&[]));
+}
+
+#[test]
+fn companion_columns_hold_the_tags() {
+    let rsp = common::setup_path("tagged_missing.xpt").unwrap();
+    let d = read(&rsp, true);
+
+    // following every column read, one for each numeric column
+    let names: Vec<&str> = d.schema().fields.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "id",
+            "answer",
+            "age",
+            "name",
+            "id__missing_tag",
+            "answer__missing_tag",
+            "age__missing_tag"
+        ]
+    );
+    assert_eq!(missing_tag_name("answer"), "answer__missing_tag");
+
+    // the columns read are as without
+    assert_eq!(floats(&d, 1), vec![Some(1.0), None, None, None, Some(0.0)]);
+
+    // system missing values, like any other value, have no tag
+    let a = |s: &str| Some(String::from(s));
+    assert_eq!(strings(&d, 4), vec![None; 5]);
+    assert_eq!(strings(&d, 5), vec![None, a("A"), a("B"), None, None]);
+    assert_eq!(strings(&d, 6), vec![None, None, None, a("B"), a("A")]);
+}
+
+#[test]
+fn conversions_hold_the_tags_of_each_column_as_written() {
+    let rsp = common::setup_path("tagged_missing.xpt").unwrap();
+
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        missing_tags: true,
+        renames: vec![(String::from("answer"), String::from("response"))],
+        ..ReadStatConvertOptions::default()
+    };
+    let c = convert_collect(&rsp, &options).unwrap();
+
+    assert_eq!(c.schema.fields.len(), 7);
+    assert_eq!(c.schema.fields[5].name, "response__missing_tag");
+    assert_eq!(c.chunks[0].arrays().len(), 7);
+
+    assert_eq!(c.missing_tags.len(), 2);
+    assert_eq!(c.missing_tags["response"], tags(&['A', 'B']));
+    assert_eq!(c.missing_tags["age"], tags(&['A', 'B']));
+}
+
+#[test]
+fn cli_writes_the_companion_columns() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("tagged_missing.csv");
+
+    let output = Command::cargo_bin("readstat")
+        .unwrap()
+        .arg("data")
+        .arg("tests/data/tagged_missing.xpt")
+        .arg("--output")
+        .arg(&out_path)
+        .arg("--missing-tags")
+        .arg("--no-progress")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let csv = std::fs::read_to_string(&out_path).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next().unwrap(),
+        "id,answer,age,name,id__missing_tag,answer__missing_tag,age__missing_tag"
+    );
+    assert_eq!(lines.count(), 5);
+
+    out_dir.close().unwrap();
+}
@@ -0,0 +1,106 @@
+use readstat::prelude::{
+    convert_collect, NameTransform, OutputMode, ReadStatConvertOptions, ReadStatMetadata,
+    ReadStatRenameReason,
+};
+
+mod common;
+
+fn read(transform: NameTransform, dedup: bool) -> Result<ReadStatMetadata, String> {
+    let rsp = common::setup_path("mixed_case_names.xpt").unwrap();
+    let mut md = ReadStatMetadata::new()
+        .set_name_transform(transform)
+        .set_dedup_columns(dedup);
+    md.read_metadata(&rsp, false).map_err(|e| e.to_string())?;
+    Ok(md)
+}
+
+fn names(md: &ReadStatMetadata) -> Vec<&str> {
+    md.schema.fields.iter().map(|f| f.name.as_str()).collect()
+}
+
+#[test]
+fn snake_case_joins_lowercase_words() {
+    let snake = |name: &str| NameTransform::SnakeCase.apply(name);
+
+    assert_eq!(snake("TotalAmt"), "total_amt");
+    assert_eq!(snake("TotalHTTPCount"), "total_http_count");
+    assert_eq!(snake("UNIT_PRC"), "unit_prc");
+    assert_eq!(snake("Unit Price ($)"), "unit_price");
+    assert_eq!(snake("var2Name"), "var2_name");
+    assert_eq!(snake("2ndQtr"), "_2nd_qtr");
+    assert_eq!(snake("_N_"), "_n");
+    assert_eq!(snake("$$"), "_");
+}
+
+#[test]
+fn names_are_kept_without_a_transform() {
+    let md = read(NameTransform::None, false).unwrap();
+
+    assert_eq!(names(&md), vec!["VarA", "vara", "TotalAmt", "UNIT_PRC"]);
+    assert!(md.renames.is_empty());
+    assert!(md.schema.fields.iter().all(|f| f.metadata.is_empty()));
+}
+
+#[test]
+fn names_are_snake_cased() {
+    let md = read(NameTransform::SnakeCase, false).unwrap();
+
+    assert_eq!(names(&md), vec!["var_a", "vara", "total_amt", "unit_prc"]);
+    assert_eq!(md.renames[&0].reason, ReadStatRenameReason::NameTransform);
+    // vara is left as is, so is not renamed
+    assert!(!md.renames.contains_key(&1));
+}
+
+#[test]
+fn original_names_are_kept() {
+    let md = read(NameTransform::SnakeCase, false).unwrap();
+
+    assert_eq!(md.vars[&2].var_name, "TotalAmt");
+    assert_eq!(md.final_name(2), "total_amt");
+    assert_eq!(md.schema.fields[2].metadata["original_name"], "TotalAmt");
+    assert!(md.schema.fields[1].metadata.is_empty());
+}
+
+#[test]
+fn case_transforms_that_collide_are_an_error() {
+    for transform in [NameTransform::Lowercase, NameTransform::Uppercase] {
+        let e = read(transform, false).unwrap_err();
+        assert!(e.contains("Transforming names results in more than one variable named"));
+        assert!(e.contains("(VarA at index 0, vara at index 1)"));
+        assert!(e.contains("--dedup-columns"));
+    }
+}
+
+#[test]
+fn case_transforms_that_collide_are_suffixed() {
+    let md = read(NameTransform::Lowercase, true).unwrap();
+    assert_eq!(names(&md), vec!["vara", "vara_1", "totalamt", "unit_prc"]);
+    assert_eq!(md.renames[&1].reason, ReadStatRenameReason::Deduplication);
+    assert_eq!(md.schema.fields[1].metadata["original_name"], "vara");
+
+    let md = read(NameTransform::Uppercase, true).unwrap();
+    assert_eq!(names(&md), vec!["VARA", "VARA_1", "TOTALAMT", "UNIT_PRC"]);
+}
+
+#[test]
+fn columns_are_selected_by_original_name() {
+    let rsp = common::setup_path("mixed_case_names.xpt").unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        name_transform: NameTransform::SnakeCase,
+        columns: vec![String::from("TotalAmt"), String::from("UNIT_PRC")],
+        ..ReadStatConvertOptions::default()
+    };
+
+    let c = convert_collect(&rsp, &options).unwrap();
+    let names: Vec<&str> = c.schema.fields.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, vec!["total_amt", "unit_prc"]);
+
+    // transformed names are not matched
+    let options = ReadStatConvertOptions {
+        columns: vec![String::from("total_amt")],
+        ..options
+    };
+    let e = convert_collect(&rsp, &options).unwrap_err().to_string();
+    assert!(e.contains("does not exist"));
+}
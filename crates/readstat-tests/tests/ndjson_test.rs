@@ -0,0 +1,73 @@
+use assert_fs::TempDir;
+use readstat::prelude::{convert, OutFormat, ReadStatConvertOptions, ReadStatPath};
+use serde_json::Value;
+
+mod common;
+
+fn all_types_as_ndjson() -> Vec<Value> {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("all_types.ndjson");
+
+    let rsp = ReadStatPath::new(
+        common::setup_path("all_types.sas7bdat").unwrap().path,
+        Some(out_path.clone()),
+        Some(OutFormat::ndjson),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    // a batch per row, so that the file is appended to
+    let options = ReadStatConvertOptions {
+        stream_rows: 1,
+        ..ReadStatConvertOptions::default()
+    };
+    convert(&rsp, &options).unwrap();
+
+    std::fs::read_to_string(&out_path)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect()
+}
+
+#[test]
+fn each_row_is_an_object_keyed_by_variable_name() {
+    let rows = all_types_as_ndjson();
+
+    // every row once, none repeated across batches
+    assert_eq!(rows.len(), 3);
+    assert!(rows.iter().all(|r| r.as_object().unwrap().len() == 8));
+
+    assert_eq!(rows[0]["_int"], Value::from(1234f64));
+    assert_eq!(rows[0]["_string"], Value::from("string"));
+    assert_eq!(rows[2]["_string"], Value::from("stringy string"));
+}
+
+#[test]
+fn missing_values_are_null() {
+    let rows = all_types_as_ndjson();
+
+    assert!(rows[2]["_int"].is_null());
+    assert!(rows[2]["_datetime_with_ms"].is_null());
+}
+
+#[test]
+fn dates_and_times_are_iso_8601_strings() {
+    let rows = all_types_as_ndjson();
+
+    assert_eq!(rows[1]["_datetime"], Value::from("2021-06-01T13:42:25"));
+    assert_eq!(
+        rows[0]["_datetime_with_ms"],
+        Value::from("2021-01-01T10:49:39.333")
+    );
+
+    // YYYY-MM-DD and HH:MM:SS
+    let date = rows[0]["_date"].as_str().unwrap();
+    assert_eq!(date.len(), 10);
+    assert!(chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok());
+    let time = rows[0]["_time"].as_str().unwrap();
+    assert!(chrono::NaiveTime::parse_from_str(time, "%H:%M:%S").is_ok());
+}
@@ -0,0 +1,168 @@
+use arrow2::{array::Utf8Array, datatypes::DataType};
+use readstat::prelude::{
+    convert_collect, OutputMode, ReadStatCollected, ReadStatConvertOptions,
+    ReadStatStringNormalize, StringCase,
+};
+
+mod common;
+
+fn collect(ds: &str, normalize_strings: ReadStatStringNormalize) -> ReadStatCollected {
+    let rsp = common::setup_path(ds).unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        normalize_strings,
+        ..ReadStatConvertOptions::default()
+    };
+    convert_collect(&rsp, &options).unwrap()
+}
+
+// non-missing values of every string column, keyed by column name
+fn string_values(c: &ReadStatCollected) -> Vec<(String, Vec<String>)> {
+    c.schema
+        .fields
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| f.data_type() == &DataType::Utf8)
+        .map(|(i, f)| {
+            let values = c
+                .chunks
+                .iter()
+                .flat_map(|chunk| {
+                    chunk.arrays()[i]
+                        .as_any()
+                        .downcast_ref::<Utf8Array<i32>>()
+                        .unwrap()
+                        .iter()
+                        .flatten()
+                        .map(String::from)
+                        .collect::<Vec<String>>()
+                })
+                .collect();
+            (f.name.clone(), values)
+        })
+        .collect()
+}
+
+#[test]
+fn normalize_applies_case_and_collapses_whitespace() {
+    let n = ReadStatStringNormalize {
+        case: Some(StringCase::Upper),
+        collapse_whitespace: true,
+        ..ReadStatStringNormalize::default()
+    };
+
+    assert_eq!(
+        n.apply("  Mixed   case\tValue "),
+        Some(String::from("  MIXED CASE VALUE "))
+    );
+    assert_eq!(n.apply("ALREADY NORMAL"), None);
+
+    let lower = ReadStatStringNormalize {
+        case: Some(StringCase::Lower),
+        ..ReadStatStringNormalize::default()
+    };
+    assert_eq!(
+        lower.apply("Mixed   Case"),
+        Some(String::from("mixed   case"))
+    );
+    assert!(!ReadStatStringNormalize::default().is_active());
+}
+
+#[test]
+fn strings_unchanged_without_normalization() {
+    let c = collect("messydata.sas7bdat", ReadStatStringNormalize::default());
+    assert!(c.strings_normalized.is_empty());
+}
+
+#[test]
+fn uppercase_every_string_column() {
+    let before = collect("messydata.sas7bdat", ReadStatStringNormalize::default());
+    let after = collect(
+        "messydata.sas7bdat",
+        ReadStatStringNormalize {
+            case: Some(StringCase::Upper),
+            ..ReadStatStringNormalize::default()
+        },
+    );
+
+    assert!(!after.strings_normalized.is_empty());
+
+    let before = string_values(&before);
+    for ((name, b), (_, a)) in before.iter().zip(string_values(&after).iter()) {
+        let expected: Vec<String> = b.iter().map(|s| s.to_uppercase()).collect();
+        assert_eq!(a, &expected);
+
+        // every modified cell is counted against its column
+        let modified = b.iter().filter(|s| s.to_uppercase() != **s).count();
+        let counted = after.strings_normalized.get(name).copied().unwrap_or(0);
+        assert_eq!(counted, modified);
+    }
+}
+
+#[test]
+fn collapse_whitespace_leaves_no_internal_runs() {
+    let after = collect(
+        "messydata.sas7bdat",
+        ReadStatStringNormalize {
+            collapse_whitespace: true,
+            ..ReadStatStringNormalize::default()
+        },
+    );
+
+    for (_, values) in string_values(&after) {
+        assert!(values.iter().all(|s| !s.trim().contains("  ")));
+    }
+}
+
+#[test]
+fn normalize_selected_columns_only() {
+    let after = collect(
+        "all_types.sas7bdat",
+        ReadStatStringNormalize {
+            case: Some(StringCase::Upper),
+            columns: vec![String::from("_string")],
+            ..ReadStatStringNormalize::default()
+        },
+    );
+
+    let values = string_values(&after);
+    let (_, strings) = values.iter().find(|(name, _)| name == "_string").unwrap();
+    assert_eq!(strings[0], "STRING");
+    assert!(strings.iter().all(|s| s.to_uppercase() == *s));
+    assert_eq!(after.strings_normalized.len(), 1);
+    assert!(after.strings_normalized.contains_key("_string"));
+}
+
+#[test]
+fn excluded_columns_are_not_normalized() {
+    let after = collect(
+        "all_types.sas7bdat",
+        ReadStatStringNormalize {
+            case: Some(StringCase::Upper),
+            exclude: Some(String::from("^_str")),
+            ..ReadStatStringNormalize::default()
+        },
+    );
+
+    let values = string_values(&after);
+    let (_, strings) = values.iter().find(|(name, _)| name == "_string").unwrap();
+    assert_eq!(strings[0], "string");
+    assert!(!after.strings_normalized.contains_key("_string"));
+}
+
+#[test]
+fn normalizing_a_numeric_column_is_an_error() {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        normalize_strings: ReadStatStringNormalize {
+            case: Some(StringCase::Lower),
+            columns: vec![String::from("_int")],
+            ..ReadStatStringNormalize::default()
+        },
+        ..ReadStatConvertOptions::default()
+    };
+
+    let e = convert_collect(&rsp, &options).unwrap_err().to_string();
+    assert!(e.contains("is not a string"));
+}
@@ -0,0 +1,113 @@
+use arrow2::array::PrimitiveArray;
+use assert_fs::TempDir;
+use readstat::prelude::{
+    check_numeric_sanity, convert_collect, OutputMode, ReadStatConvertOptions, ReadStatPath,
+    NUMERIC_SANITY_THRESHOLD,
+};
+use std::collections::HashMap;
+
+mod common;
+
+// every non-missing value of each numeric column of cars, by name
+fn cars_values() -> HashMap<String, Vec<f64>> {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        ..ReadStatConvertOptions::default()
+    };
+    let c = convert_collect(&rsp, &options).unwrap();
+
+    let mut values: HashMap<String, Vec<f64>> = HashMap::new();
+    for chunk in &c.chunks {
+        for (f, a) in c.schema.fields.iter().zip(chunk.arrays()) {
+            if let Some(a) = a.as_any().downcast_ref::<PrimitiveArray<f64>>() {
+                values
+                    .entry(f.name.clone())
+                    .or_default()
+                    .extend(a.iter().flatten());
+            }
+        }
+    }
+    values
+}
+
+#[test]
+fn pristine_file_is_not_flagged() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let report = check_numeric_sanity(&rsp, NUMERIC_SANITY_THRESHOLD, 100).unwrap();
+
+    // only floating point columns are checked
+    assert_eq!(report.columns.len(), 11);
+    assert!(report.flagged().is_empty());
+    for c in &report.columns {
+        assert_eq!(c.suspect(), 0, "{} has suspect values", c.var_name);
+        assert!(c.samples.is_empty());
+    }
+}
+
+#[test]
+fn injected_huge_values_are_flagged() {
+    let values = cars_values();
+
+    // most common engine size found within no other column
+    let mut counts: HashMap<u64, usize> = HashMap::new();
+    for v in &values["EngineSize"] {
+        *counts.entry(v.to_bits()).or_default() += 1;
+    }
+    let (bits, n) = counts
+        .into_iter()
+        .filter(|(b, _)| {
+            values
+                .iter()
+                .filter(|(name, _)| name.as_str() != "EngineSize")
+                .all(|(_, vs)| vs.iter().all(|v| v.to_bits() != *b))
+        })
+        .max_by_key(|(b, n)| (*n, *b))
+        .unwrap();
+
+    // doctor a copy of cars, replacing that value with one of 1e308 wherever it is stored
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let mut bytes = std::fs::read(&rsp.path).unwrap();
+    let from = f64::from_bits(bits).to_le_bytes();
+    let to = 1e308f64.to_le_bytes();
+    let mut i = 0;
+    while i + 8 <= bytes.len() {
+        if bytes[i..i + 8] == from {
+            bytes[i..i + 8].copy_from_slice(&to);
+            i += 8;
+        } else {
+            i += 1;
+        }
+    }
+
+    let dir = TempDir::new().unwrap();
+    let doctored = dir.path().join("cars_doctored.sas7bdat");
+    std::fs::write(&doctored, bytes).unwrap();
+    let doctored_rsp = ReadStatPath::new(doctored, None, None, false, false, None, None).unwrap();
+
+    // counted across many chunks
+    let threshold = n as f64 / values["EngineSize"].len() as f64;
+    let report = check_numeric_sanity(&doctored_rsp, threshold, 100).unwrap();
+    let engine = report
+        .columns
+        .iter()
+        .find(|c| c.var_name == "EngineSize")
+        .unwrap();
+
+    assert_eq!(engine.huge, n as u64);
+    assert_eq!(engine.subnormal + engine.nan, 0);
+    assert_eq!(engine.values, values["EngineSize"].len() as u64);
+    assert_eq!(engine.samples.len(), n.min(5));
+    assert_eq!(engine.samples[0].value, 1e308);
+    assert_eq!(engine.samples[0].bits, "0x7fe1ccf385ebc8a0");
+
+    let flagged = report.flagged();
+    assert_eq!(flagged.len(), 1);
+    assert_eq!(flagged[0].var_name, "EngineSize");
+
+    // at the default threshold the minority of values injected are not enough
+    let report = check_numeric_sanity(&doctored_rsp, NUMERIC_SANITY_THRESHOLD, 100).unwrap();
+    assert!(report.flagged().is_empty());
+
+    dir.close().unwrap();
+}
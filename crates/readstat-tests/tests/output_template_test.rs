@@ -0,0 +1,166 @@
+use assert_fs::TempDir;
+use path_abs::PathAbs;
+use readstat::prelude::{
+    convert_many, OutFormat, ReadStatConvertOptions, ReadStatMetadata, ReadStatOutputTemplate,
+};
+use std::path::PathBuf;
+
+mod common;
+
+fn data_path(ds: &str) -> PathBuf {
+    PathAbs::new(env!("CARGO_MANIFEST_DIR"))
+        .unwrap()
+        .as_path()
+        .join("tests")
+        .join("data")
+        .join(ds)
+}
+
+// cars along with a copy under another name, so both share a creation date
+fn cars_and_copy(dir: &TempDir) -> Vec<PathBuf> {
+    let copy = dir.path().join("cars_copy.sas7bdat");
+    std::fs::copy(data_path("cars.sas7bdat"), &copy).unwrap();
+    vec![data_path("cars.sas7bdat"), copy]
+}
+
+#[test]
+fn template_renders_every_placeholder() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+
+    let t = ReadStatOutputTemplate::parse("{stem}_{table_name}_{date}_{subdir}_{index}.parquet")
+        .unwrap();
+    assert!(t.needs_metadata());
+    assert_eq!(t.extension(), Some("parquet"));
+    assert_eq!(
+        t.render(&rsp.path, Some(&md), 3),
+        "cars_CARS_2008-09-30_data_3.parquet"
+    );
+
+    // without metadata
+    let t = ReadStatOutputTemplate::parse("{stem}-{index}").unwrap();
+    assert!(!t.needs_metadata());
+    assert_eq!(t.extension(), None);
+    assert_eq!(t.render(&rsp.path, None, 1), "cars-1");
+}
+
+#[test]
+fn template_parsing_errors() {
+    for template in ["{nope}.csv", "{stem.csv", "stem}.csv", ""] {
+        assert!(
+            ReadStatOutputTemplate::parse(template).is_err(),
+            "{} was parsed",
+            template
+        );
+    }
+}
+
+#[test]
+fn template_appends_extension_and_checks_format() {
+    let out_dir = TempDir::new().unwrap();
+    let inputs = vec![data_path("cars.sas7bdat")];
+
+    let t = ReadStatOutputTemplate::parse("{stem}_{date}").unwrap();
+    let outputs = t
+        .resolve(&inputs, Some(out_dir.path()), OutFormat::csv)
+        .unwrap();
+    assert_eq!(
+        outputs[0].as_ref().unwrap(),
+        &out_dir.path().join("cars_2008-09-30.csv")
+    );
+
+    let t = ReadStatOutputTemplate::parse("{stem}.parquet").unwrap();
+    let e = t
+        .resolve(&inputs, Some(out_dir.path()), OutFormat::csv)
+        .unwrap_err()
+        .to_string();
+    assert!(e.contains("extension"));
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn template_collision_on_same_day_is_detected() {
+    let dir = TempDir::new().unwrap();
+    let out_dir = TempDir::new().unwrap();
+    let inputs = cars_and_copy(&dir);
+
+    // date only
+    let t = ReadStatOutputTemplate::parse("{date}.csv").unwrap();
+    let e = t
+        .resolve(&inputs, Some(out_dir.path()), OutFormat::csv)
+        .unwrap_err()
+        .to_string();
+    assert!(e.contains("would both be written to"));
+
+    // stem disambiguates
+    let t = ReadStatOutputTemplate::parse("{stem}_{date}.csv").unwrap();
+    let outputs = t
+        .resolve(&inputs, Some(out_dir.path()), OutFormat::csv)
+        .unwrap();
+    assert_eq!(outputs.len(), 2);
+    assert_ne!(outputs[0], outputs[1]);
+
+    dir.close().unwrap();
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn template_collision_differing_only_in_case_is_detected() {
+    let out_dir = TempDir::new().unwrap();
+
+    // no metadata is needed, so the inputs need not exist
+    let inputs = vec![PathBuf::from("a/cars.sas7bdat"), PathBuf::from("b/CARS.sas7bdat")];
+    let t = ReadStatOutputTemplate::parse("{stem}.csv").unwrap();
+    let e = t
+        .resolve(&inputs, Some(out_dir.path()), OutFormat::csv)
+        .unwrap_err()
+        .to_string();
+    assert!(e.contains("would both be written to"));
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn convert_many_stops_before_converting_on_collision() {
+    let dir = TempDir::new().unwrap();
+    let out_dir = TempDir::new().unwrap();
+    let inputs = cars_and_copy(&dir);
+
+    let options = ReadStatConvertOptions {
+        out_dir: Some(out_dir.path().to_path_buf()),
+        output_template: Some(String::from("{date}.csv")),
+        format: OutFormat::csv,
+        ..ReadStatConvertOptions::default()
+    };
+    let results = convert_many(&inputs, &options);
+
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| !r.is_ok()));
+    assert!(!out_dir.path().join("2008-09-30.csv").exists());
+
+    dir.close().unwrap();
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn convert_many_names_outputs_from_template() {
+    let out_dir = TempDir::new().unwrap();
+    let inputs = vec![data_path("cars.sas7bdat"), data_path("all_types.sas7bdat")];
+
+    let options = ReadStatConvertOptions {
+        out_dir: Some(out_dir.path().to_path_buf()),
+        output_template: Some(String::from("{index}_{table_name}")),
+        format: OutFormat::csv,
+        ..ReadStatConvertOptions::default()
+    };
+    let results = convert_many(&inputs, &options);
+
+    assert!(results.iter().all(|r| r.is_ok()));
+    let cars_out = results[0].output.clone().unwrap();
+    assert_eq!(cars_out.file_name().unwrap(), "1_CARS.csv");
+    assert!(cars_out.exists());
+
+    out_dir.close().unwrap();
+}
@@ -0,0 +1,125 @@
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert_collect, read_batches, OutFormat, OutputMode, ReadStatConvertOptions, ReadStatPath,
+};
+use std::path::{Path, PathBuf};
+
+mod common;
+
+fn serial() -> ReadStatConvertOptions {
+    ReadStatConvertOptions {
+        stream_rows: 10,
+        ..ReadStatConvertOptions::default()
+    }
+}
+
+fn parallel(parallelism: usize) -> ReadStatConvertOptions {
+    ReadStatConvertOptions {
+        parallel: true,
+        parallelism: Some(parallelism),
+        parallel_min_rows: 0,
+        ..serial()
+    }
+}
+
+fn convert_to(
+    input: PathBuf,
+    out_path: &Path,
+    format: OutFormat,
+    options: &ReadStatConvertOptions,
+) -> Vec<u8> {
+    let rsp = ReadStatPath::new(
+        input,
+        Some(out_path.to_path_buf()),
+        Some(format),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+    convert_collect(&rsp, options).unwrap();
+    std::fs::read(out_path).unwrap()
+}
+
+#[test]
+fn feather_is_identical_to_reading_serially() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("cars.feather");
+    let input = common::setup_path("cars.sas7bdat").unwrap().path;
+
+    let s = convert_to(input.clone(), &out_path, OutFormat::feather, &serial());
+    for parallelism in [2, 3, 8] {
+        let p = convert_to(input.clone(), &out_path, OutFormat::feather, &parallel(parallelism));
+        assert!(s == p, "feather differs when read upon {} threads", parallelism);
+    }
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn csv_is_identical_to_reading_serially() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("rand_ds.csv");
+    let input = common::setup_path("rand_ds_largepage_ok.sas7bdat").unwrap().path;
+
+    let s = convert_to(input.clone(), &out_path, OutFormat::csv, &serial());
+    let p = convert_to(input, &out_path, OutFormat::csv, &parallel(4));
+    assert!(s == p);
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn batches_arrive_in_row_order() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+
+    let s: Vec<_> = read_batches(&rsp, &serial())
+        .unwrap()
+        .map(|c| c.unwrap())
+        .collect();
+    let p: Vec<_> = read_batches(&rsp, &parallel(4))
+        .unwrap()
+        .map(|c| c.unwrap())
+        .collect();
+
+    assert_eq!(p.len(), 109);
+    assert_eq!(p.iter().map(|c| c.len()).sum::<usize>(), 1081);
+    assert_eq!(p, s);
+}
+
+// cars.sas7bdat has a 1,024 byte header followed by 34 pages of 4,608 bytes; zero the pages
+//   from the 21st on
+fn damaged_cars(dir: &Path) -> PathBuf {
+    let original = common::setup_path("cars.sas7bdat").unwrap().path;
+    let mut bytes = std::fs::read(original).unwrap();
+    bytes[1024 + 20 * 4608..].iter_mut().for_each(|b| *b = 0);
+
+    let path = dir.join("cars_damaged.sas7bdat");
+    std::fs::write(&path, bytes).unwrap();
+    path
+}
+
+#[test]
+fn the_error_of_a_worker_stops_reading() {
+    let dir = TempDir::new().unwrap();
+    let rsp = ReadStatPath::new(damaged_cars(dir.path()), None, None, false, false, None, None)
+        .unwrap();
+
+    let collect = |options: ReadStatConvertOptions, best_effort: bool| ReadStatConvertOptions {
+        best_effort,
+        output_mode: OutputMode::Collect,
+        ..options
+    };
+
+    assert!(convert_collect(&rsp, &collect(parallel(4), false)).is_err());
+
+    // rows ahead of the failure are kept alike whether read serially or in parallel
+    let s = convert_collect(&rsp, &collect(serial(), true)).unwrap();
+    let p = convert_collect(&rsp, &collect(parallel(4), true)).unwrap();
+    assert!(s.salvage.is_some());
+    assert_eq!(p.rows, s.rows);
+    assert_eq!(p.chunks, s.chunks);
+
+    dir.close().unwrap();
+}
@@ -0,0 +1,56 @@
+use arrow2::io::parquet::read::{infer_schema, read_metadata, FileReader};
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert_collect, OutFormat, OutputMode, ParquetCompression, ReadStatConvertOptions,
+    ReadStatPath,
+};
+use std::fs::File;
+
+mod common;
+
+#[test]
+fn all_types_round_trips_through_parquet() {
+    for compression in [
+        ParquetCompression::Uncompressed,
+        ParquetCompression::Snappy,
+        ParquetCompression::Zstd,
+    ] {
+        let out_dir = TempDir::new().unwrap();
+        let out_path = out_dir.path().join("all_types.parquet");
+
+        let rsp = ReadStatPath::new(
+            common::setup_path("all_types.sas7bdat").unwrap().path,
+            Some(out_path.clone()),
+            Some(OutFormat::parquet),
+            true,
+            false,
+            Some(compression),
+            None,
+        )
+        .unwrap();
+
+        // written and kept, with a row group for each of the chunks of a single row
+        let options = ReadStatConvertOptions {
+            output_mode: OutputMode::WriteAndCollect,
+            stream_rows: 1,
+            ..ReadStatConvertOptions::default()
+        };
+        let collected = convert_collect(&rsp, &options).unwrap();
+
+        let mut f = File::open(&out_path).unwrap();
+        let md = read_metadata(&mut f).unwrap();
+        assert_eq!(md.num_rows, 3);
+        assert_eq!(md.row_groups.len(), 3);
+
+        // dates, times, and datetimes keep their Arrow types
+        let schema = infer_schema(&md).unwrap();
+        assert_eq!(schema.fields, collected.schema.fields);
+
+        let chunks = FileReader::new(f, md.row_groups, schema, None, None, None)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(chunks, collected.chunks, "{:?}", compression);
+
+        out_dir.close().unwrap();
+    }
+}
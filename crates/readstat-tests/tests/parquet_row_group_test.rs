@@ -0,0 +1,104 @@
+use arrow2::io::parquet::read::read_metadata;
+use assert_fs::TempDir;
+use readstat::prelude::{convert, OutFormat, ReadStatConvertOptions, ReadStatPath};
+use std::fs::File;
+
+mod common;
+
+// (rows, uncompressed bytes) of each row group written
+fn write_row_groups(
+    ds: &str,
+    stream_rows: u32,
+    row_group_bytes: Option<usize>,
+) -> Vec<(usize, usize)> {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("out.parquet");
+
+    let rsp = ReadStatPath::new(
+        common::setup_path(ds).unwrap().path,
+        Some(out_path.clone()),
+        Some(OutFormat::parquet),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let options = ReadStatConvertOptions {
+        stream_rows,
+        row_group_bytes,
+        ..ReadStatConvertOptions::default()
+    };
+    convert(&rsp, &options).unwrap();
+
+    let md = read_metadata(&mut File::open(&out_path).unwrap()).unwrap();
+    let groups = md
+        .row_groups
+        .iter()
+        .map(|rg| (rg.num_rows(), rg.total_byte_size()))
+        .collect();
+
+    out_dir.close().unwrap();
+    groups
+}
+
+fn assert_near_target(
+    groups: &[(usize, usize)],
+    total_rows: usize,
+    stream_rows: u32,
+    target: usize,
+) {
+    // all rows are written
+    assert_eq!(groups.iter().map(|(rows, _)| rows).sum::<usize>(), total_rows);
+
+    // chunks are combined, so there are fewer groups than chunks but still more than one
+    let chunks = total_rows.div_ceil(stream_rows as usize);
+    assert!(groups.len() > 1);
+    assert!(groups.len() < chunks);
+
+    // every group but the last is closed only after crossing the target
+    for (rows, bytes) in &groups[..groups.len() - 1] {
+        assert_eq!(rows % stream_rows as usize, 0);
+        assert!(
+            *bytes >= target / 2,
+            "{} bytes is well below the target of {}",
+            bytes,
+            target
+        );
+        assert!(
+            *bytes <= target * 2,
+            "{} bytes is well above the target of {}",
+            bytes,
+            target
+        );
+    }
+}
+
+#[test]
+fn row_groups_follow_chunks_by_default() {
+    let groups = write_row_groups("cars.sas7bdat", 100, None);
+
+    // 1,081 rows streamed 100 at a time
+    assert_eq!(groups.len(), 11);
+    assert!(groups[..10].iter().all(|(rows, _)| *rows == 100));
+    assert_eq!(groups[10].0, 81);
+}
+
+#[test]
+fn row_groups_near_target_narrow() {
+    // cars - 13 columns
+    let target = 16 * 1024;
+    let groups = write_row_groups("cars.sas7bdat", 25, Some(target));
+
+    assert_near_target(&groups, 1081, 25, target);
+}
+
+#[test]
+fn row_groups_near_target_wide() {
+    // rand_ds_largepage_ok - 110 columns
+    let target = 256 * 1024;
+    let groups = write_row_groups("rand_ds_largepage_ok.sas7bdat", 50, Some(target));
+
+    assert_near_target(&groups, 2000, 50, target);
+}
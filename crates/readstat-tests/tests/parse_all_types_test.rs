@@ -1,10 +1,13 @@
 use arrow2::{
     array::{Float64Array, Int64Array, Utf8Array},
     datatypes::{DataType, TimeUnit},
-    temporal_conversions::timestamp_s_to_datetime,
+    temporal_conversions::timestamp_ms_to_datetime,
 };
 use chrono::NaiveDate;
-use readstat::{ReadStatData, ReadStatMetadata, ReadStatPath};
+use readstat::prelude::{
+    ReadStatCompress, ReadStatData, ReadStatEndian, ReadStatMetadata, ReadStatPath,
+    ReadStatVarFormatClass, ReadStatVarType, ReadStatVarTypeClass,
+};
 
 mod common;
 
@@ -18,7 +21,7 @@ fn init() -> (ReadStatPath, ReadStatMetadata, ReadStatData) {
 
     // parse sas7bdat
     // read the entire dataset
-    let d = readstat::ReadStatData::new().set_no_progress(true).init(
+    let d = ReadStatData::new().set_no_progress(true).init(
         md.clone(),
         0,
         md.row_count as u32,
@@ -45,13 +48,10 @@ fn parse_all_types_int() {
     let m = common::get_metadata(&d, var_index);
 
     // variable type class
-    assert!(matches!(
-        m.var_type_class,
-        readstat::ReadStatVarTypeClass::Numeric
-    ));
+    assert!(matches!(m.var_type_class, ReadStatVarTypeClass::Numeric));
 
     // variable type
-    assert!(matches!(m.var_type, readstat::ReadStatVarType::Double));
+    assert!(matches!(m.var_type, ReadStatVarType::Double));
 
     // variable format class
     assert!(m.var_format_class.is_none());
@@ -59,14 +59,18 @@ fn parse_all_types_int() {
     // variable format
     assert_eq!(m.var_format, String::from("BEST12"));
 
+    // storage width and measure
+    assert_eq!(m.storage_width(), Some(8));
+    assert!(m.measure().is_none());
+
     // arrow data type
     assert!(matches!(
-        d.schema.fields[var_index as usize].data_type(),
+        d.schema().fields[var_index as usize].data_type(),
         DataType::Float64
     ));
 
     // arrays
-    let arrays = d.chunk.unwrap().into_arrays();
+    let arrays = d.into_chunk().unwrap().into_arrays();
 
     // int column
     let col = arrays
@@ -103,13 +107,10 @@ fn parse_all_types_string() {
     let m = common::get_metadata(&d, var_index);
 
     // variable type class
-    assert!(matches!(
-        m.var_type_class,
-        readstat::ReadStatVarTypeClass::String
-    ));
+    assert!(matches!(m.var_type_class, ReadStatVarTypeClass::String));
 
     // variable type
-    assert!(matches!(m.var_type, readstat::ReadStatVarType::String));
+    assert!(matches!(m.var_type, ReadStatVarType::String));
 
     // variable format class
     assert!(m.var_format_class.is_none());
@@ -117,14 +118,20 @@ fn parse_all_types_string() {
     // variable format
     assert_eq!(m.var_format, String::from("$30"));
 
+    // storage width, display width, alignment, and measure
+    assert_eq!(m.storage_width(), Some(30));
+    assert_ne!(m.display_width(), Some(0));
+    assert!(m.alignment().is_none());
+    assert!(m.measure().is_none());
+
     // arrow data type
     assert!(matches!(
-        d.schema.fields[var_index as usize].data_type(),
+        d.schema().fields[var_index as usize].data_type(),
         DataType::Utf8
     ));
 
     // arrays
-    let arrays = d.chunk.unwrap().into_arrays();
+    let arrays = d.into_chunk().unwrap().into_arrays();
 
     // string column
     let col = arrays
@@ -159,18 +166,15 @@ fn parse_all_types_datetime() {
     let m = common::get_metadata(&d, var_index);
 
     // variable type class
-    assert!(matches!(
-        m.var_type_class,
-        readstat::ReadStatVarTypeClass::Numeric
-    ));
+    assert!(matches!(m.var_type_class, ReadStatVarTypeClass::Numeric));
 
     // variable type
-    assert!(matches!(m.var_type, readstat::ReadStatVarType::Double));
+    assert!(matches!(m.var_type, ReadStatVarType::Double));
 
     // variable format class
     assert!(matches!(
         m.var_format_class,
-        Some(readstat::ReadStatVarFormatClass::DateTime)
+        Some(ReadStatVarFormatClass::DateTime)
     ));
 
     // variable format
@@ -178,12 +182,12 @@ fn parse_all_types_datetime() {
 
     // arrow data type
     assert!(matches!(
-        d.schema.fields[var_index as usize].data_type(),
-        DataType::Timestamp(TimeUnit::Second, None)
+        d.schema().fields[var_index as usize].data_type(),
+        DataType::Timestamp(TimeUnit::Millisecond, None)
     ));
 
     // arrays
-    let arrays = d.chunk.unwrap().into_arrays();
+    let arrays = d.into_chunk().unwrap().into_arrays();
 
     // datetime column
     let col = arrays
@@ -193,10 +197,10 @@ fn parse_all_types_datetime() {
         .downcast_ref::<Int64Array>()
         .unwrap()
         .to_owned()
-        .to(DataType::Timestamp(TimeUnit::Second, None));
+        .to(DataType::Timestamp(TimeUnit::Millisecond, None));
 
     // non-missing value
-    let dt = timestamp_s_to_datetime(col.value(1));
+    let dt = timestamp_ms_to_datetime(col.value(1));
     let dt_literal = NaiveDate::from_ymd_opt(2021, 6, 1)
         .unwrap()
         .and_hms_milli_opt(13, 42, 25, 0)
@@ -205,6 +209,51 @@ fn parse_all_types_datetime() {
     assert_eq!(dt, dt_literal);
 }
 
+#[test]
+fn parse_all_types_datetime_with_ms() {
+    let (rsp, _md, mut d) = init();
+
+    let error = d.read_data(&rsp);
+    assert!(error.is_ok());
+
+    // variable index and name
+    let var_index = 6;
+
+    // the format carries no decimals, yet the value holds fractional seconds
+    let m = common::get_metadata(&d, var_index);
+    assert_eq!(m.var_format, String::from("DATETIME22"));
+    assert!(matches!(
+        d.schema().fields[var_index as usize].data_type(),
+        DataType::Timestamp(TimeUnit::Millisecond, None)
+    ));
+
+    // arrays
+    let arrays = d.into_chunk().unwrap().into_arrays();
+
+    // datetime column
+    let col = arrays
+        .get(var_index as usize)
+        .unwrap()
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap()
+        .to_owned()
+        .to(DataType::Timestamp(TimeUnit::Millisecond, None));
+
+    // milliseconds are not truncated
+    for (row, month, (h, m, s, ms)) in [(0, 1, (10, 49, 39, 333)), (1, 6, (13, 42, 25, 943))] {
+        let dt = timestamp_ms_to_datetime(col.value(row));
+        let dt_literal = NaiveDate::from_ymd_opt(2021, month, 1)
+            .unwrap()
+            .and_hms_milli_opt(h, m, s, ms)
+            .unwrap();
+        assert_eq!(dt, dt_literal);
+    }
+
+    // missing value
+    assert!(col.is_null(2));
+}
+
 #[test]
 fn parse_all_types_metadata() {
     let (rsp, md, mut d) = init();
@@ -240,10 +289,10 @@ fn parse_all_types_metadata() {
     assert_eq!(md.modified_time, "2022-01-08 19:40:48");
 
     // compression
-    assert!(matches!(md.compression, readstat::ReadStatCompress::None));
+    assert!(matches!(md.compression, ReadStatCompress::None));
 
     // endianness
-    assert!(matches!(md.endianness, readstat::ReadStatEndian::Little));
+    assert!(matches!(md.endianness, ReadStatEndian::Little));
 
     // variables - contains variable
     assert!(common::contains_var(&d, 0));
@@ -255,71 +304,71 @@ fn parse_all_types_metadata() {
 
     // 0 - _int
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 0);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from("BEST12"));
     assert!(matches!(adt, DataType::Float64));
 
     // 1 - _float
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 1);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from("BEST12"));
     assert!(matches!(adt, DataType::Float64));
 
     // 2 - _char
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 2);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::String));
-    assert!(matches!(vt, readstat::ReadStatVarType::String));
+    assert!(matches!(vtc, ReadStatVarTypeClass::String));
+    assert!(matches!(vt, ReadStatVarType::String));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from("$1"));
     assert!(matches!(adt, DataType::Utf8));
 
     // 3 - _string
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 3);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::String));
-    assert!(matches!(vt, readstat::ReadStatVarType::String));
+    assert!(matches!(vtc, ReadStatVarTypeClass::String));
+    assert!(matches!(vt, ReadStatVarType::String));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from("$30"));
     assert!(matches!(adt, DataType::Utf8));
 
     // 4 - _date
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 4);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
-    assert_eq!(vfc, Some(readstat::ReadStatVarFormatClass::Date));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
+    assert_eq!(vfc, Some(ReadStatVarFormatClass::Date));
     assert_eq!(vf, String::from("YYMMDD10"));
     assert!(matches!(adt, DataType::Date32));
 
     // 5 - _datetime
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 5);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
-    assert_eq!(vfc, Some(readstat::ReadStatVarFormatClass::DateTime));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
+    assert_eq!(vfc, Some(ReadStatVarFormatClass::DateTime));
     assert_eq!(vf, String::from("DATETIME22"));
     assert!(matches!(
         adt,
-        DataType::Timestamp(arrow2::datatypes::TimeUnit::Second, None)
+        DataType::Timestamp(arrow2::datatypes::TimeUnit::Millisecond, None)
     ));
 
     // 6 - _datetime_with_ms
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 6);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
-    assert_eq!(vfc, Some(readstat::ReadStatVarFormatClass::DateTime));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
+    assert_eq!(vfc, Some(ReadStatVarFormatClass::DateTime));
     assert_eq!(vf, String::from("DATETIME22"));
     assert!(matches!(
         adt,
-        DataType::Timestamp(arrow2::datatypes::TimeUnit::Second, None)
+        DataType::Timestamp(arrow2::datatypes::TimeUnit::Millisecond, None)
     ));
 
     // 7 - _time
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 7);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
-    assert_eq!(vfc, Some(readstat::ReadStatVarFormatClass::Time));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
+    assert_eq!(vfc, Some(ReadStatVarFormatClass::Time));
     assert_eq!(vf, String::from("TIME"));
     assert!(matches!(
         adt,
@@ -1,5 +1,8 @@
 use arrow2::datatypes::DataType;
-use readstat::{ReadStatData, ReadStatMetadata, ReadStatPath};
+use readstat::prelude::{
+    ReadStatCompress, ReadStatData, ReadStatEndian, ReadStatMetadata, ReadStatPath, ReadStatVarType,
+    ReadStatVarTypeClass,
+};
 
 mod common;
 
@@ -13,7 +16,7 @@ fn init() -> (ReadStatPath, ReadStatMetadata, ReadStatData) {
 
     // parse sas7bdat
     // read the entire dataset
-    let d = readstat::ReadStatData::new().set_no_progress(true).init(
+    let d = ReadStatData::new().set_no_progress(true).init(
         md.clone(),
         0,
         md.row_count as u32,
@@ -57,10 +60,10 @@ fn parse_cars_metadata() {
     assert_eq!(md.modified_time, "2008-09-30 14:55:01");
 
     // compression
-    assert!(matches!(md.compression, readstat::ReadStatCompress::None));
+    assert!(matches!(md.compression, ReadStatCompress::None));
 
     // endianness
-    assert!(matches!(md.endianness, readstat::ReadStatEndian::Little));
+    assert!(matches!(md.endianness, ReadStatEndian::Little));
 
     // variables - contains variable
     assert!(common::contains_var(&d, 0));
@@ -72,104 +75,104 @@ fn parse_cars_metadata() {
 
     // 0 - Brand
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 0);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::String));
-    assert!(matches!(vt, readstat::ReadStatVarType::String));
+    assert!(matches!(vtc, ReadStatVarTypeClass::String));
+    assert!(matches!(vt, ReadStatVarType::String));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from(""));
     assert!(matches!(adt, DataType::Utf8));
 
     // 1 - Model
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 1);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::String));
-    assert!(matches!(vt, readstat::ReadStatVarType::String));
+    assert!(matches!(vtc, ReadStatVarTypeClass::String));
+    assert!(matches!(vt, ReadStatVarType::String));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from(""));
     assert!(matches!(adt, DataType::Utf8));
 
     // 2 - Minivan
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 2);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from(""));
     assert!(matches!(adt, DataType::Float64));
 
     // 3 - Wagon
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 3);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from(""));
     assert!(matches!(adt, DataType::Float64));
 
     // 4 - Pickup
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 4);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from(""));
     assert!(matches!(adt, DataType::Float64));
 
     // 5 - Automatic
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 5);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from(""));
     assert!(matches!(adt, DataType::Float64));
 
     // 6 - EngineSize
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 6);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from(""));
     assert!(matches!(adt, DataType::Float64));
 
     // 7 - Cylinders
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 7);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from(""));
     assert!(matches!(adt, DataType::Float64));
 
     // 8 - CityMPG
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 8);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from(""));
     assert!(matches!(adt, DataType::Float64));
 
     // 9 - HwyMPG
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 9);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from(""));
     assert!(matches!(adt, DataType::Float64));
 
     // 10 - SUV
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 10);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from(""));
     assert!(matches!(adt, DataType::Float64));
 
     // 11 - AWD
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 11);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from(""));
     assert!(matches!(adt, DataType::Float64));
 
     // 12 - Hybrid
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 12);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from(""));
     assert!(matches!(adt, DataType::Float64));
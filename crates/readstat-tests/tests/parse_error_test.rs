@@ -0,0 +1,62 @@
+use assert_cmd::Command;
+use assert_fs::TempDir;
+use readstat::prelude::{ReadStatError, ReadStatMetadata, ReadStatParseError, ReadStatPath};
+use std::path::{Path, PathBuf};
+
+mod common;
+
+// Copy of cars.sas7bdat whose header is zeroed, so that it is not recognized as a sas7bdat
+fn corrupt_cars(dir: &Path) -> PathBuf {
+    let original = common::setup_path("cars.sas7bdat").unwrap().path;
+    let mut bytes = std::fs::read(original).unwrap();
+    bytes[..1024].iter_mut().for_each(|b| *b = 0);
+
+    let path = dir.join("cars_corrupt.sas7bdat");
+    std::fs::write(&path, bytes).unwrap();
+    path
+}
+
+fn parse_error(path: PathBuf) -> ReadStatParseError {
+    let rsp = ReadStatPath::new(path, None, None, false, false, None, None).unwrap();
+    let e = ReadStatMetadata::new().read_metadata(&rsp, false).unwrap_err();
+
+    e.downcast_ref::<ReadStatParseError>().unwrap().clone()
+}
+
+#[test]
+fn parse_error_holds_the_code_and_message() {
+    let dir = TempDir::new().unwrap();
+    let e = parse_error(corrupt_cars(dir.path()));
+
+    assert_ne!(e.code, ReadStatError::READSTAT_OK);
+    assert!(e.code as i32 > 0);
+    assert!(!e.message.is_empty());
+
+    // both within the text displayed
+    let text = e.to_string();
+    assert!(text.contains(&e.message));
+    assert!(text.contains(&format!("{:?}", e.code)));
+
+    dir.close().unwrap();
+}
+
+#[test]
+fn cli_displays_the_message_and_fails() {
+    let dir = TempDir::new().unwrap();
+    let path = corrupt_cars(dir.path());
+    let e = parse_error(path.clone());
+
+    let output = Command::cargo_bin("readstat")
+        .unwrap()
+        .arg("metadata")
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Stopping with error"));
+    assert!(stderr.contains(&e.message));
+
+    dir.close().unwrap();
+}
@@ -2,7 +2,9 @@ use arrow2::{
     array::{Float64Array, Utf8Array},
     datatypes::DataType,
 };
-use readstat::{ReadStatData, ReadStatMetadata, ReadStatPath};
+use readstat::prelude::{
+    ReadStatData, ReadStatMetadata, ReadStatPath, ReadStatVarType, ReadStatVarTypeClass,
+};
 
 mod common;
 
@@ -16,7 +18,7 @@ fn init() -> (ReadStatPath, ReadStatMetadata, ReadStatData) {
 
     // parse sas7bdat
     // read only up to the 5th row
-    let d = readstat::ReadStatData::new()
+    let d = ReadStatData::new()
         .set_no_progress(true)
         .init(md.clone(), 0, 5);
 
@@ -31,11 +33,11 @@ fn parse_hasmissing() {
     assert!(error.is_ok());
 
     // variable count
-    let var_count = d.var_count;
+    let var_count = d.var_count();
     assert_eq!(var_count, 9);
 
     // row count
-    let row_count = d.chunk_rows_to_process;
+    let row_count = d.chunk_rows_to_process();
     assert_eq!(row_count, 5);
 
     // contains variable
@@ -46,13 +48,10 @@ fn parse_hasmissing() {
     let m = common::get_metadata(&d, 0);
 
     // variable type class
-    assert!(matches!(
-        m.var_type_class,
-        readstat::ReadStatVarTypeClass::String
-    ));
+    assert!(matches!(m.var_type_class, ReadStatVarTypeClass::String));
 
     // variable type
-    assert!(matches!(m.var_type, readstat::ReadStatVarType::String));
+    assert!(matches!(m.var_type, ReadStatVarType::String));
 
     // variable format class
     assert!(m.var_format_class.is_none());
@@ -61,10 +60,10 @@ fn parse_hasmissing() {
     assert_eq!(m.var_format, String::from("$"));
 
     // arrow data type
-    assert!(matches!(d.schema.fields[0].data_type(), DataType::Utf8));
+    assert!(matches!(d.schema().fields[0].data_type(), DataType::Utf8));
 
     // arrays
-    let arrays = d.chunk.unwrap().into_arrays();
+    let arrays = d.into_chunk().unwrap().into_arrays();
 
     // non-missing column value from column that has no missing values
     // column = 1 (index 0) -> row = 1 (index 0)
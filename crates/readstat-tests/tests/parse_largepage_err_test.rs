@@ -1,4 +1,6 @@
-use readstat::{ReadStatData, ReadStatMetadata, ReadStatPath};
+use readstat::prelude::{
+    ReadStatCompress, ReadStatData, ReadStatEndian, ReadStatMetadata, ReadStatPath,
+};
 
 mod common;
 
@@ -12,7 +14,7 @@ fn init() -> (ReadStatPath, ReadStatMetadata, ReadStatData) {
 
     // parse sas7bdat
     // read the entire dataset
-    let d = readstat::ReadStatData::new().set_no_progress(true).init(
+    let d = ReadStatData::new().set_no_progress(true).init(
         md.clone(),
         0,
         md.row_count as u32,
@@ -56,8 +58,8 @@ fn parse_largepage_err() {
     assert_eq!(md.modified_time, "2021-07-25 22:02:02");
 
     // compression
-    assert!(matches!(md.compression, readstat::ReadStatCompress::None));
+    assert!(matches!(md.compression, ReadStatCompress::None));
 
     // endianness
-    assert!(matches!(md.endianness, readstat::ReadStatEndian::Little));
+    assert!(matches!(md.endianness, ReadStatEndian::Little));
 }
@@ -1,4 +1,6 @@
-use readstat::{ReadStatData, ReadStatMetadata, ReadStatPath};
+use readstat::prelude::{
+    ReadStatCompress, ReadStatData, ReadStatEndian, ReadStatMetadata, ReadStatPath,
+};
 
 mod common;
 
@@ -12,7 +14,7 @@ fn init() -> (ReadStatPath, ReadStatMetadata, ReadStatData) {
 
     // parse sas7bdat
     // read the entire dataset
-    let d = readstat::ReadStatData::new().set_no_progress(true).init(
+    let d = ReadStatData::new().set_no_progress(true).init(
         md.clone(),
         0,
         md.row_count as u32,
@@ -56,8 +58,24 @@ fn parse_largepage_ok() {
     assert_eq!(md.modified_time, "2021-07-25 22:02:30");
 
     // compression
-    assert!(matches!(md.compression, readstat::ReadStatCompress::None));
+    assert!(matches!(md.compression, ReadStatCompress::None));
 
     // endianness
-    assert!(matches!(md.endianness, readstat::ReadStatEndian::Little));
+    assert!(matches!(md.endianness, ReadStatEndian::Little));
+}
+
+#[test]
+fn parse_largepage_ok_schema() {
+    let (_rsp, md, d) = init();
+
+    // a field for each variable, in index order, with the type decided for it
+    assert_eq!(md.schema.fields.len(), 110);
+    for ((k, vm), f) in md.vars.iter().zip(md.schema.fields.iter()) {
+        assert_eq!(f.name, vm.var_name);
+        assert_eq!(f.data_type(), &md.type_decisions.get(k).unwrap().data_type);
+        assert!(f.is_nullable);
+    }
+
+    // schema of the data is that of the metadata
+    assert_eq!(d.schema(), &md.schema);
 }
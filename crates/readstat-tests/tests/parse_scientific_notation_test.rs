@@ -1,5 +1,7 @@
 use arrow2::{array::Float64Array, datatypes::DataType};
-use readstat::{ReadStatData, ReadStatMetadata, ReadStatPath};
+use readstat::prelude::{
+    ReadStatData, ReadStatMetadata, ReadStatPath, ReadStatVarType, ReadStatVarTypeClass,
+};
 
 mod common;
 
@@ -13,7 +15,7 @@ fn init() -> (ReadStatPath, ReadStatMetadata, ReadStatData) {
 
     // parse sas7bdat
     // read the entire dataset
-    let d = readstat::ReadStatData::new().set_no_progress(true).init(
+    let d = ReadStatData::new().set_no_progress(true).init(
         md.clone(),
         0,
         md.row_count as u32,
@@ -40,13 +42,10 @@ fn parse_scientific_notation() {
     let m = common::get_metadata(&d, var_index);
 
     // variable type class
-    assert!(matches!(
-        m.var_type_class,
-        readstat::ReadStatVarTypeClass::Numeric
-    ));
+    assert!(matches!(m.var_type_class, ReadStatVarTypeClass::Numeric));
 
     // variable type
-    assert!(matches!(m.var_type, readstat::ReadStatVarType::Double));
+    assert!(matches!(m.var_type, ReadStatVarType::Double));
 
     // variable format class
     assert!(m.var_format_class.is_none());
@@ -56,12 +55,12 @@ fn parse_scientific_notation() {
 
     // arrow data type
     assert!(matches!(
-        d.schema.fields[var_index as usize].data_type(),
+        d.schema().fields[var_index as usize].data_type(),
         DataType::Float64
     ));
 
     // arrays
-    let arrays = d.chunk.unwrap().into_arrays();
+    let arrays = d.into_chunk().unwrap().into_arrays();
 
     // float column
     let float_col = arrays
@@ -0,0 +1,78 @@
+use arrow2::{
+    array::{Float64Array, Utf8Array},
+    datatypes::DataType,
+};
+use readstat::prelude::{
+    convert_collect, OutputMode, ReadStatConvertOptions, ReadStatData, ReadStatMetadata,
+    ReadStatVarType, ReadStatVarTypeClass,
+};
+
+mod common;
+
+#[test]
+fn parse_xpt_metadata() {
+    let rsp = common::setup_path("scores.xpt").unwrap();
+
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+
+    assert_eq!(md.row_count, 3);
+    assert_eq!(md.var_count, 3);
+    assert_eq!(md.table_name, "SCORES");
+
+    let names: Vec<&str> = md.vars.values().map(|vm| vm.var_name.as_str()).collect();
+    assert_eq!(names, vec!["id", "name", "score"]);
+    assert_eq!(md.vars[&1].var_label, "Student name");
+
+    // variable types
+    assert!(matches!(md.vars[&0].var_type_class, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(md.vars[&1].var_type_class, ReadStatVarTypeClass::String));
+    assert!(matches!(md.vars[&1].var_type, ReadStatVarType::String));
+
+    // arrow data types
+    assert!(matches!(md.schema.fields[0].data_type(), DataType::Float64));
+    assert!(matches!(md.schema.fields[1].data_type(), DataType::Utf8));
+    assert!(matches!(md.schema.fields[2].data_type(), DataType::Float64));
+}
+
+#[test]
+fn parse_xpt_values() {
+    let rsp = common::setup_path("scores.xpt").unwrap();
+
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+
+    let mut d = ReadStatData::new()
+        .set_no_progress(true)
+        .init(md.clone(), 0, 3);
+    d.read_data(&rsp).unwrap();
+
+    let arrays = d.into_chunk().unwrap().into_arrays();
+
+    let id = arrays[0].as_any().downcast_ref::<Float64Array>().unwrap();
+    assert_eq!(id.values().as_slice(), &[1.0, 2.0, 3.0]);
+
+    let name = arrays[1].as_any().downcast_ref::<Utf8Array<i32>>().unwrap();
+    let names: Vec<&str> = name.values_iter().collect();
+    assert_eq!(names, vec!["alpha", "beta", "gamma"]);
+
+    // the second score is missing
+    let score = arrays[2].as_any().downcast_ref::<Float64Array>().unwrap();
+    assert_eq!(score.iter().collect::<Vec<_>>(), vec![Some(&9.5), None, Some(&-12.25)]);
+}
+
+#[test]
+fn convert_xpt_in_batches() {
+    // the last batch holds the single remaining row
+    let rsp = common::setup_path("scores.xpt").unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        stream_rows: 2,
+        ..ReadStatConvertOptions::default()
+    };
+    let c = convert_collect(&rsp, &options).unwrap();
+
+    assert_eq!(c.rows, 3);
+    let lens: Vec<usize> = c.chunks.iter().map(|c| c.len()).collect();
+    assert_eq!(lens, vec![2, 1]);
+}
@@ -0,0 +1,98 @@
+use polars::prelude::{DataType, TimeUnit};
+use readstat::prelude::{
+    chunk_to_polars, read_batches, ReadStatConvertOptions, ReadStatData, ReadStatMetadata,
+};
+
+mod common;
+
+fn all_types() -> ReadStatData {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+
+    let rows = md.row_count as u32;
+    let mut d = ReadStatData::new().set_no_progress(true).init(md, 0, rows);
+    d.read_data(&rsp).unwrap();
+    d
+}
+
+#[test]
+fn all_types_to_polars_dtypes() {
+    let df = all_types().to_polars().unwrap();
+    assert_eq!(df.shape(), (3, 8));
+
+    let dtypes: Vec<(&str, DataType)> = df
+        .get_columns()
+        .iter()
+        .map(|s| (s.name().as_str(), s.dtype().clone()))
+        .collect();
+    assert_eq!(
+        dtypes,
+        vec![
+            ("_int", DataType::Float64),
+            ("_float", DataType::Float64),
+            ("_char", DataType::String),
+            ("_string", DataType::String),
+            ("_date", DataType::Date),
+            ("_datetime", DataType::Datetime(TimeUnit::Milliseconds, None)),
+            ("_datetime_with_ms", DataType::Datetime(TimeUnit::Milliseconds, None)),
+            ("_time", DataType::Time),
+        ]
+    );
+}
+
+#[test]
+fn all_types_to_polars_values() {
+    let df = all_types().to_polars().unwrap();
+
+    // missing values are null
+    let int = df.column("_int").unwrap().f64().unwrap();
+    assert_eq!(int.get(0), Some(1234.0));
+    assert_eq!(int.get(2), None);
+    assert_eq!(int.null_count(), 1);
+
+    let string = df.column("_string").unwrap().str().unwrap();
+    assert_eq!(string.get(0), Some("string"));
+    assert_eq!(string.get(2), Some("stringy string"));
+
+    // 2021-06-01 13:42:25, as milliseconds since the epoch
+    let datetime = df.column("_datetime").unwrap().datetime().unwrap();
+    assert_eq!(datetime.get(1), Some(1_622_554_945_000));
+    assert_eq!(datetime.null_count(), 1);
+
+    // 19:54:42 at the latest, as nanoseconds since midnight
+    let time = df.column("_time").unwrap().time().unwrap();
+    let latest = time.into_iter().flatten().max();
+    assert_eq!(latest, Some((19 * 3600 + 54 * 60 + 42) * 1_000_000_000));
+}
+
+#[test]
+fn unread_data_is_an_empty_frame() {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+
+    let df = ReadStatData::new().init(md, 0, 3).to_polars().unwrap();
+    assert_eq!(df.shape(), (0, 8));
+    assert_eq!(df.column("_date").unwrap().dtype(), &DataType::Date);
+}
+
+#[test]
+fn batches_to_polars() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let options = ReadStatConvertOptions {
+        stream_rows: 500,
+        ..ReadStatConvertOptions::default()
+    };
+
+    let mut batches = read_batches(&rsp, &options).unwrap();
+    let schema = batches.schema().clone();
+    let heights: Vec<usize> = (&mut batches)
+        .map(|c| chunk_to_polars(&schema, &c.unwrap()).unwrap().height())
+        .collect();
+    assert_eq!(heights, vec![500, 500, 81]);
+
+    let first = read_batches(&rsp, &options).unwrap().next().unwrap().unwrap();
+    let df = chunk_to_polars(&schema, &first).unwrap();
+    assert_eq!(df.column("Brand").unwrap().dtype(), &DataType::String);
+}
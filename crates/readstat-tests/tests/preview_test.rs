@@ -0,0 +1,72 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use readstat::prelude::{ReadStatData, ReadStatMetadata};
+
+mod common;
+
+fn preview(ds: &str, n: usize, skip_row_count: bool) -> (ReadStatMetadata, ReadStatData) {
+    let rsp = common::setup_path(ds).unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, skip_row_count).unwrap();
+
+    let d = ReadStatData::new()
+        .set_no_progress(true)
+        .get_preview(md.clone(), &rsp, n)
+        .unwrap();
+    (md, d)
+}
+
+#[test]
+fn preview_holds_at_most_n_rows() {
+    for (n, rows) in [(2, 2), (3, 3), (10, 3)] {
+        let (md, d) = preview("all_types.sas7bdat", n, false);
+        assert_eq!(md.row_count, 3);
+        assert_eq!(d.chunk().unwrap().len(), rows);
+        assert_eq!(d.schema(), &md.schema);
+    }
+}
+
+#[test]
+fn preview_of_no_rows_keeps_the_schema() {
+    let (md, d) = preview("all_types.sas7bdat", 0, false);
+    let c = d.chunk().unwrap();
+    assert_eq!(c.len(), 0);
+    assert_eq!(c.arrays().len(), md.schema.fields.len());
+    for (a, f) in c.arrays().iter().zip(&md.schema.fields) {
+        assert_eq!(a.data_type(), f.data_type());
+    }
+}
+
+#[test]
+fn preview_needs_no_row_count() {
+    // the rows of transport files are only known once counted
+    let (_, d) = preview("scores.xpt", 1000, true);
+    let (counted, _) = preview("scores.xpt", 1000, false);
+    assert_eq!(d.chunk().unwrap().len(), counted.row_count as usize);
+
+    let (_, d) = preview("scores.xpt", 1, true);
+    assert_eq!(d.chunk().unwrap().len(), 1);
+}
+
+#[test]
+fn preview_reads_past_a_skipped_row_count() {
+    // sas7bdat metadata read with skip_row_count holds a row count of at most 1
+    let (md, d) = preview("all_types.sas7bdat", 10, true);
+    assert_eq!(md.row_count, 1);
+    assert_eq!(d.chunk().unwrap().len(), 3);
+}
+
+#[test]
+fn cli_preview_displays_a_table() {
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("preview")
+        .arg("tests/data/all_types.sas7bdat")
+        .arg("--rows")
+        .arg("2")
+        .arg("--table");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("_datetime"))
+        .stdout(predicate::str::contains("2021-06-01 13:42:25"))
+        .stdout(predicate::str::contains("stringy string").not());
+}
@@ -0,0 +1,61 @@
+use readstat::prelude::ReadStatProfile;
+
+mod common;
+
+#[test]
+fn profile_histogram_counts_sum_to_non_null_count() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+
+    let profile = ReadStatProfile::new(&rsp, Some(20), 100).unwrap();
+
+    assert_eq!(profile.row_count, 1081);
+    assert_eq!(profile.columns.len(), 13);
+
+    let numeric: Vec<_> = profile
+        .columns
+        .iter()
+        .filter(|c| c.histogram.is_some())
+        .collect();
+    assert!(!numeric.is_empty());
+
+    for c in numeric {
+        let h = c.histogram.as_ref().unwrap();
+
+        // O(bins)
+        assert_eq!(h.counts.len(), 20);
+        assert_eq!(h.edges().len(), 21);
+
+        // every non-null value falls in exactly one bucket
+        assert_eq!(h.counts.iter().sum::<u64>(), c.count);
+        assert_eq!(c.count + c.null_count, 1081);
+
+        // range matches the first pass
+        assert_eq!(Some(h.min), c.min);
+        assert_eq!(Some(h.max), c.max);
+    }
+}
+
+#[test]
+fn profile_histogram_with_missing_values() {
+    let rsp = common::setup_path("hasmissing.sas7bdat").unwrap();
+
+    let profile = ReadStatProfile::new(&rsp, Some(5), 10000).unwrap();
+
+    for c in profile.columns.iter().filter(|c| c.histogram.is_some()) {
+        let h = c.histogram.as_ref().unwrap();
+        assert_eq!(h.counts.iter().sum::<u64>(), c.count);
+    }
+}
+
+#[test]
+fn profile_without_histogram() {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+
+    let profile = ReadStatProfile::new(&rsp, None, 10000).unwrap();
+
+    assert!(profile.columns.iter().all(|c| c.histogram.is_none()));
+
+    // _int
+    assert_eq!(profile.columns[0].count, 2);
+    assert_eq!(profile.columns[0].null_count, 1);
+}
@@ -0,0 +1,99 @@
+use arrow2::io::ipc::read::{read_stream_metadata, StreamReader, StreamState};
+use assert_fs::TempDir;
+use readstat::prelude::{
+    OutFormat, ReadStatCancelled, ReadStatData, ReadStatMetadata, ReadStatPath, ReadStatWriter,
+};
+use std::{
+    io::Cursor,
+    ops::ControlFlow,
+    sync::{Arc, Mutex},
+};
+
+mod common;
+
+fn data(rsp: &ReadStatPath) -> ReadStatData {
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(rsp, false).unwrap();
+    let rows = md.row_count as u32;
+
+    ReadStatData::new().set_no_progress(true).init(md, 0, rows)
+}
+
+#[test]
+fn progress_is_reported_while_reading() {
+    let rsp = common::setup_path("rand_ds_largepage_ok.sas7bdat").unwrap();
+
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let seen = calls.clone();
+    let mut d = data(&rsp).set_progress_callback(Box::new(move |progress, rows| {
+        seen.lock().unwrap().push((progress, rows));
+        ControlFlow::Continue(())
+    }));
+    d.read_data(&rsp).unwrap();
+
+    let calls = calls.lock().unwrap();
+    assert!(!calls.is_empty());
+    assert!(calls.iter().all(|(p, _)| (0.0..=1.0).contains(p)));
+    assert!(calls.windows(2).all(|w| w[0].0 <= w[1].0 && w[0].1 <= w[1].1));
+    assert!(calls.iter().all(|(_, r)| *r <= d.chunk_rows_processed() as u64));
+}
+
+#[test]
+fn cancelling_is_a_distinct_error() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+
+    let mut d = data(&rsp).set_progress_callback(Box::new(|_, _| ControlFlow::Break(())));
+    let e = d.read_data(&rsp).unwrap_err();
+
+    let cancelled = e.downcast_ref::<ReadStatCancelled>().unwrap();
+    assert_eq!(cancelled.rows, d.chunk_rows_processed() as u64);
+    assert!(e.to_string().starts_with("Reading was cancelled after"));
+    assert_eq!(d.chunk().map_or(0, |c| c.len()), d.chunk_rows_processed());
+}
+
+#[test]
+fn rows_read_ahead_of_cancelling_may_still_be_written() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("rand_ds_largepage_ok.arrow");
+    let rsp = ReadStatPath::new(
+        common::setup_path("rand_ds_largepage_ok.sas7bdat").unwrap().path,
+        Some(out_path.clone()),
+        Some(OutFormat::arrow),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    // cancel once some rows have been read
+    let mut d = data(&rsp).set_progress_callback(Box::new(|_, rows| {
+        if rows == 0 {
+            ControlFlow::Continue(())
+        } else {
+            ControlFlow::Break(())
+        }
+    }));
+    let e = d.read_data(&rsp).unwrap_err();
+    let rows = e.downcast_ref::<ReadStatCancelled>().unwrap().rows as usize;
+    assert!(rows > 0);
+    assert!(rows < d.chunk_rows_to_process());
+    assert_eq!(d.chunk().unwrap().len(), rows);
+
+    let mut wtr = ReadStatWriter::new().set_quiet(true);
+    wtr.write(&d, &rsp).unwrap();
+    wtr.finish(&d, &rsp).unwrap();
+
+    // a complete stream holding the rows read
+    let mut reader = Cursor::new(std::fs::read(&out_path).unwrap());
+    let metadata = read_stream_metadata(&mut reader).unwrap();
+    let written: usize = StreamReader::new(reader, metadata, None)
+        .map_while(|s| match s.unwrap() {
+            StreamState::Some(c) => Some(c.len()),
+            StreamState::Waiting => None,
+        })
+        .sum();
+    assert_eq!(written, rows);
+
+    out_dir.close().unwrap();
+}
@@ -0,0 +1,97 @@
+use readstat::prelude::{convert_collect, read_batches, OutputMode, ReadStatConvertOptions, Reader};
+
+mod common;
+
+fn options() -> ReadStatConvertOptions {
+    ReadStatConvertOptions {
+        stream_rows: 100,
+        ..ReadStatConvertOptions::default()
+    }
+}
+
+#[test]
+fn batches_match_collected_chunks() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let collected = convert_collect(
+        &rsp,
+        &ReadStatConvertOptions {
+            output_mode: OutputMode::Collect,
+            ..options()
+        },
+    )
+    .unwrap();
+
+    // schema is known ahead of the first chunk
+    let mut batches = read_batches(&rsp, &options()).unwrap();
+    assert_eq!(batches.schema(), &collected.schema);
+    assert!(batches.collected().is_none());
+
+    let chunks: Vec<_> = (&mut batches).map(|c| c.unwrap()).collect();
+    assert_eq!(chunks.len(), 11);
+    assert!(chunks[..10].iter().all(|c| c.len() == 100));
+    assert_eq!(chunks[10].len(), 81);
+    assert_eq!(chunks, collected.chunks);
+
+    // once exhausted, the read is summarized without keeping any chunk
+    let summary = batches.collected().unwrap();
+    assert_eq!(summary.rows, 1081);
+    assert!(summary.chunks.is_empty());
+    assert!(batches.next().is_none());
+}
+
+#[test]
+fn dropping_early_stops_reading() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let options = ReadStatConvertOptions {
+        stream_rows: 10,
+        channel_depth: 1,
+        ..options()
+    };
+
+    let batches = read_batches(&rsp, &options).unwrap();
+    let rows: usize = batches.take(2).map(|c| c.unwrap().len()).sum();
+    assert_eq!(rows, 20);
+}
+
+#[test]
+fn errors_while_reading_end_the_iterator() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let options = ReadStatConvertOptions {
+        reader: Reader::mem,
+        mem_limit: Some(1024),
+        ..options()
+    };
+
+    let mut batches = read_batches(&rsp, &options).unwrap();
+    let e = batches.next().unwrap().unwrap_err().to_string();
+    assert!(e.contains("above the limit"));
+    assert!(batches.next().is_none());
+}
+
+#[test]
+fn small_batches_end_with_a_partial_batch() {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    let options = ReadStatConvertOptions {
+        stream_rows: 2,
+        ..options()
+    };
+
+    let chunks: Vec<_> = read_batches(&rsp, &options)
+        .unwrap()
+        .map(|c| c.unwrap().len())
+        .collect();
+    assert_eq!(chunks, vec![2, 1]);
+}
+
+#[test]
+fn zero_stream_rows_is_rejected() {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        stream_rows: 0,
+        ..options()
+    };
+
+    let e = convert_collect(&rsp, &options).unwrap_err().to_string();
+    assert!(e.contains("at least 1"));
+}
@@ -0,0 +1,197 @@
+use arrow2::{array::Utf8Array, datatypes::DataType};
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert, convert_collect, OutFormat, OutputMode, ReadStatCollected, ReadStatConvertOptions,
+    ReadStatHashColumn, ReadStatMapping, ReadStatPath, ReadStatRedact,
+};
+use serde_json::Value;
+
+mod common;
+
+fn collect(rsp: &ReadStatPath, redact: ReadStatRedact) -> ReadStatCollected {
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        redact,
+        ..ReadStatConvertOptions::default()
+    };
+    convert_collect(rsp, &options).unwrap()
+}
+
+fn hash(spec: &str) -> ReadStatRedact {
+    ReadStatRedact {
+        hash: vec![spec.parse().unwrap()],
+        ..ReadStatRedact::default()
+    }
+}
+
+// every value of the named string column, in file order
+fn strings(c: &ReadStatCollected, name: &str) -> Vec<Option<String>> {
+    let i = c.schema.fields.iter().position(|f| f.name == name).unwrap();
+    c.chunks
+        .iter()
+        .flat_map(|chunk| {
+            chunk.arrays()[i]
+                .as_any()
+                .downcast_ref::<Utf8Array<i32>>()
+                .unwrap()
+                .iter()
+                .map(|v| v.map(String::from))
+                .collect::<Vec<Option<String>>>()
+        })
+        .collect()
+}
+
+#[test]
+fn hash_column_parsing() {
+    // salt may itself contain colons
+    let h: ReadStatHashColumn = "USUBJID:sha256:pepper:2024".parse().unwrap();
+    assert_eq!(h.name, "USUBJID");
+    assert_eq!(h.salt, "pepper:2024");
+
+    for spec in [
+        "USUBJID",
+        "USUBJID:sha256",
+        "USUBJID:md5:salt",
+        "USUBJID:sha256:",
+        ":sha256:salt",
+    ] {
+        assert!(
+            spec.parse::<ReadStatHashColumn>().is_err(),
+            "{} was parsed",
+            spec
+        );
+    }
+}
+
+#[test]
+fn hash_depends_only_upon_salt_and_value() {
+    let a: ReadStatHashColumn = "a:sha256:salt".parse().unwrap();
+    let b: ReadStatHashColumn = "b:sha256:salt".parse().unwrap();
+    let c: ReadStatHashColumn = "a:sha256:other".parse().unwrap();
+
+    assert_eq!(a.hash("value"), b.hash("value"));
+    assert_ne!(a.hash("value"), c.hash("value"));
+    assert_ne!(a.hash("value"), a.hash("values"));
+
+    assert_eq!(a.hash("value").len(), 64);
+    assert!(a.hash("value").chars().all(|c| c.is_ascii_hexdigit()));
+}
+
+#[test]
+fn identical_inputs_hash_identically_across_files() {
+    let dir = TempDir::new().unwrap();
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let copy = dir.path().join("cars_copy.sas7bdat");
+    std::fs::copy(&rsp.path, &copy).unwrap();
+    let copy_rsp = ReadStatPath::new(copy, None, None, false, false, None, None).unwrap();
+
+    let plain = strings(&collect(&rsp, ReadStatRedact::default()), "Brand");
+    let hashed = strings(&collect(&rsp, hash("Brand:sha256:salt")), "Brand");
+    let hashed_copy = strings(&collect(&copy_rsp, hash("Brand:sha256:salt")), "Brand");
+    let resalted = strings(&collect(&rsp, hash("Brand:sha256:other")), "Brand");
+
+    assert_eq!(hashed, hashed_copy);
+    assert_ne!(hashed, resalted);
+
+    // each hash is that of the original value, so equal brands hash alike
+    let h: ReadStatHashColumn = "Brand:sha256:salt".parse().unwrap();
+    for (p, v) in plain.iter().zip(hashed.iter()) {
+        assert_eq!(p.as_deref().map(|p| h.hash(p)), v.clone());
+    }
+
+    dir.close().unwrap();
+}
+
+#[test]
+fn hashed_numeric_column_becomes_string() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let c = collect(&rsp, hash("EngineSize:sha256:salt"));
+
+    let f = c.schema.fields.iter().find(|f| f.name == "EngineSize").unwrap();
+    assert_eq!(f.data_type(), &DataType::Utf8);
+    assert!(strings(&c, "EngineSize")
+        .iter()
+        .flatten()
+        .all(|h| h.len() == 64));
+}
+
+#[test]
+fn redacted_columns_are_all_null() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let redact = ReadStatRedact {
+        redact: vec![String::from("Model"), String::from("Cylinders")],
+        ..ReadStatRedact::default()
+    };
+    let c = collect(&rsp, redact);
+
+    for name in ["Model", "Cylinders"] {
+        let i = c.schema.fields.iter().position(|f| f.name == name).unwrap();
+        for chunk in &c.chunks {
+            let a = &chunk.arrays()[i];
+            assert_eq!(a.null_count(), a.len(), "{} is not all null", name);
+        }
+    }
+
+    // types are unchanged
+    assert_eq!(c.schema.fields[1].data_type(), &DataType::Utf8);
+    assert_eq!(c.rows, 1081);
+}
+
+#[test]
+fn unknown_or_repeated_column_is_an_error() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+
+    for redact in [
+        ReadStatRedact {
+            redact: vec![String::from("nope")],
+            ..ReadStatRedact::default()
+        },
+        ReadStatRedact {
+            redact: vec![String::from("Brand")],
+            hash: vec!["Brand:sha256:salt".parse().unwrap()],
+        },
+    ] {
+        let options = ReadStatConvertOptions {
+            output_mode: OutputMode::Collect,
+            redact,
+            ..ReadStatConvertOptions::default()
+        };
+        assert!(convert_collect(&rsp, &options).is_err());
+    }
+}
+
+#[test]
+fn mapping_sidecar_records_redactions_but_not_salts() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("cars.csv");
+    let rsp = ReadStatPath::new(
+        common::setup_path("cars.sas7bdat").unwrap().path,
+        Some(out_path.clone()),
+        Some(OutFormat::csv),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let options = ReadStatConvertOptions {
+        redact: ReadStatRedact {
+            redact: vec![String::from("Model")],
+            hash: vec!["Brand:sha256:pepper".parse().unwrap()],
+        },
+        ..ReadStatConvertOptions::default()
+    };
+    convert(&rsp, &options).unwrap();
+
+    let text = std::fs::read_to_string(ReadStatMapping::sidecar_path(&out_path)).unwrap();
+    assert!(!text.contains("pepper"));
+
+    let mapping: Value = serde_json::from_str(&text).unwrap();
+    let variables = mapping["variables"].as_array().unwrap();
+    assert_eq!(variables[0]["redacted_by"], "sha256");
+    assert_eq!(variables[1]["redacted_by"], "null");
+    assert!(variables[2].get("redacted_by").is_none());
+
+    out_dir.close().unwrap();
+}
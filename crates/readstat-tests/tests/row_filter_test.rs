@@ -0,0 +1,196 @@
+use arrow2::array::{PrimitiveArray, Utf8Array};
+use assert_cmd::Command;
+use assert_fs::TempDir;
+use chrono::NaiveDate;
+use predicates::prelude::*;
+use readstat::prelude::{
+    convert_collect, FilterPredicate, FilterValue, OutFormat, OutputMode, ReadStatConvertOptions,
+    ReadStatData, ReadStatMetadata, ReadStatPath, ReadStatRowFilter,
+};
+
+mod common;
+
+fn filtered(column: &str, predicate: FilterPredicate) -> ReadStatData {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+
+    let rows = md.row_count as u32;
+    let mut d = ReadStatData::new()
+        .set_no_progress(true)
+        .set_row_filter(column, predicate)
+        .init(md, 0, rows);
+    d.read_data(&rsp).unwrap();
+    d
+}
+
+fn strings(d: &ReadStatData, i: usize) -> Vec<Option<String>> {
+    d.chunk().unwrap().arrays()[i]
+        .as_any()
+        .downcast_ref::<Utf8Array<i32>>()
+        .unwrap()
+        .iter()
+        .map(|v| v.map(String::from))
+        .collect()
+}
+
+fn string(s: &str) -> FilterValue {
+    FilterValue::String(String::from(s))
+}
+
+#[test]
+fn rows_are_kept_when_the_char_column_matches() {
+    // _char is the third column, so the values of _int and _float are staged
+    let d = filtered("_char", FilterPredicate::Eq(string("c")));
+
+    assert_eq!(d.chunk_rows_read(), 3);
+    assert_eq!(d.chunk_rows_processed(), 1);
+    assert_eq!(d.chunk().unwrap().len(), 1);
+    assert_eq!(strings(&d, 2), vec![Some(String::from("c"))]);
+    assert_eq!(strings(&d, 3), vec![Some(String::from("another string"))]);
+
+    let int = d.chunk().unwrap().arrays()[0]
+        .as_any()
+        .downcast_ref::<PrimitiveArray<f64>>()
+        .unwrap();
+    assert_eq!(int.value(0), 4567.0);
+}
+
+#[test]
+fn rows_are_kept_from_a_comparison() {
+    // the blank value of the third row is empty, so passes too
+    let d = filtered("_char", FilterPredicate::Ne(string("s")));
+    assert_eq!(
+        strings(&d, 3),
+        vec![
+            Some(String::from("another string")),
+            Some(String::from("stringy string"))
+        ]
+    );
+
+    let d = filtered("_char", FilterPredicate::Gt(string("d")));
+    assert_eq!(strings(&d, 2), vec![Some(String::from("s"))]);
+
+    // the missing value of the third row never passes
+    let d = filtered("_int", FilterPredicate::Le(FilterValue::Number(5000.0)));
+    assert_eq!(d.chunk_rows_processed(), 2);
+
+    let date = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+    let d = filtered("_date", FilterPredicate::Ge(FilterValue::Date(date)));
+    assert!(d.chunk_rows_processed() > 0 && d.chunk_rows_processed() < 3);
+}
+
+#[test]
+fn no_row_passing_is_an_empty_chunk() {
+    let d = filtered("_char", FilterPredicate::Eq(string("z")));
+
+    assert_eq!(d.chunk_rows_processed(), 0);
+    assert_eq!(d.chunk().unwrap().len(), 0);
+    assert_eq!(d.chunk().unwrap().arrays().len(), 8);
+}
+
+#[test]
+fn filters_that_cannot_be_compared_are_errors() {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    for (column, predicate, error) in [
+        ("_char", FilterPredicate::Eq(FilterValue::Number(1.0)), "with the number 1"),
+        ("_missing", FilterPredicate::Eq(string("c")), "not among the variables read"),
+    ] {
+        let mut md = ReadStatMetadata::new();
+        md.read_metadata(&rsp, false).unwrap();
+        let e = ReadStatData::new()
+            .set_no_progress(true)
+            .set_row_filter(column, predicate)
+            .init(md, 0, 3)
+            .read_data(&rsp)
+            .unwrap_err()
+            .to_string();
+        assert!(e.contains(error), "{}", e);
+    }
+}
+
+#[test]
+fn filters_parse_from_comparisons() {
+    let parse = |s: &str| s.parse::<ReadStatRowFilter>().unwrap().predicate;
+
+    assert_eq!(parse("_char=c"), FilterPredicate::Eq(string("c")));
+    assert_eq!(parse("_char == 'c'"), FilterPredicate::Eq(string("c")));
+    assert_eq!(parse("_int>=1234"), FilterPredicate::Ge(FilterValue::Number(1234.0)));
+    assert_eq!(parse("_int!=\"1234\""), FilterPredicate::Ne(string("1234")));
+    assert_eq!(
+        parse("_date<2020-01-01"),
+        FilterPredicate::Lt(FilterValue::Date(NaiveDate::from_ymd_opt(2020, 1, 1).unwrap()))
+    );
+    assert!("_char".parse::<ReadStatRowFilter>().is_err());
+    assert!("=c".parse::<ReadStatRowFilter>().is_err());
+}
+
+#[test]
+fn converted_rows_count_the_rows_kept() {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        stream_rows: 1,
+        row_filter: Some("_char=c".parse().unwrap()),
+        ..ReadStatConvertOptions::default()
+    };
+
+    let c = convert_collect(&rsp, &options).unwrap();
+    assert_eq!(c.rows, 1);
+    assert_eq!(c.chunks.iter().map(|c| c.len()).sum::<usize>(), 1);
+}
+
+#[test]
+fn sav_output_cannot_be_filtered() {
+    let out_dir = TempDir::new().unwrap();
+    let rsp = ReadStatPath::new(
+        common::setup_path("all_types.sas7bdat").unwrap().path,
+        Some(out_dir.path().join("all_types.sav")),
+        Some(OutFormat::sav),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+    let options = ReadStatConvertOptions {
+        row_filter: Some("_char=c".parse().unwrap()),
+        ..ReadStatConvertOptions::default()
+    };
+
+    let e = convert_collect(&rsp, &options).unwrap_err().to_string();
+    assert!(e.contains("not supported for sav files"));
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn cli_filters_rows() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("all_types.csv");
+
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("data")
+        .arg("tests/data/all_types.sas7bdat")
+        .arg("--output")
+        .arg(&out_path)
+        .args(["--filter", "_char=c"])
+        .arg("--no-progress");
+    cmd.assert().success();
+
+    let csv = std::fs::read_to_string(&out_path).unwrap();
+    assert_eq!(csv.lines().count(), 2);
+    assert!(csv.contains("another string"));
+
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("data")
+        .arg("tests/data/all_types.sas7bdat")
+        .arg("--output")
+        .arg(&out_path)
+        .args(["--filter", "_char"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("expected COLUMN=VALUE"));
+
+    out_dir.close().unwrap();
+}
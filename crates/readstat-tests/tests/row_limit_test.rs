@@ -0,0 +1,94 @@
+use arrow2::{array::Array, chunk::Chunk};
+use readstat::prelude::{
+    convert_collect, OutputMode, ReadStatConvertOptions, ReadStatData, ReadStatMetadata,
+    ReadStatPath,
+};
+
+mod common;
+
+fn init() -> (ReadStatPath, ReadStatMetadata) {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    (rsp, md)
+}
+
+fn read(rsp: &ReadStatPath, d: ReadStatData) -> Chunk<Box<dyn Array>> {
+    let mut d = d;
+    d.read_data(rsp).unwrap();
+    d.into_chunk().unwrap()
+}
+
+fn sliced(chunk: &Chunk<Box<dyn Array>>, offset: usize, length: usize) -> Vec<Box<dyn Array>> {
+    chunk
+        .arrays()
+        .iter()
+        .map(|a| a.sliced(offset, length))
+        .collect()
+}
+
+#[test]
+fn row_limit_reads_a_single_row() {
+    let (rsp, md) = init();
+    assert_eq!(md.row_count, 3);
+
+    let d = ReadStatData::new()
+        .set_no_progress(true)
+        .init(md.clone(), 0, 3)
+        .set_row_limit(1);
+    assert_eq!(d.chunk_rows_to_process(), 1);
+
+    let limited = read(&rsp, d);
+    assert_eq!(limited.len(), 1);
+
+    let full = read(&rsp, ReadStatData::new().set_no_progress(true).init(md, 0, 3));
+    assert_eq!(limited.arrays(), sliced(&full, 0, 1).as_slice());
+}
+
+#[test]
+fn row_offset_skips_leading_rows() {
+    let (rsp, md) = init();
+
+    let full = read(&rsp, ReadStatData::new().set_no_progress(true).init(md.clone(), 0, 3));
+
+    // two rows, beginning with the second
+    let d = ReadStatData::new()
+        .set_no_progress(true)
+        .init(md.clone(), 0, 2)
+        .set_row_offset(1);
+    let offset = read(&rsp, d);
+    assert_eq!(offset.len(), 2);
+    assert_eq!(offset.arrays(), sliced(&full, 1, 2).as_slice());
+
+    // rows past the end of the file are not read
+    let d = ReadStatData::new()
+        .set_no_progress(true)
+        .init(md, 0, 3)
+        .set_row_offset(2);
+    assert_eq!(read(&rsp, d).len(), 1);
+}
+
+#[test]
+fn conversion_resumes_from_row_offset() {
+    let (rsp, _) = init();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        stream_rows: 1,
+        row_offset: 1,
+        ..ReadStatConvertOptions::default()
+    };
+
+    let c = convert_collect(&rsp, &options).unwrap();
+    assert_eq!(c.rows, 2);
+    assert_eq!(c.chunks.len(), 2);
+
+    let full = convert_collect(
+        &rsp,
+        &ReadStatConvertOptions {
+            row_offset: 0,
+            ..options
+        },
+    )
+    .unwrap();
+    assert_eq!(c.chunks, full.chunks[1..]);
+}
@@ -4,7 +4,10 @@ use arrow2::{
     temporal_conversions::date32_to_date,
 };
 use chrono::NaiveDate;
-use readstat::{ReadStatData, ReadStatMetadata, ReadStatPath};
+use readstat::prelude::{
+    ReadStatCompress, ReadStatData, ReadStatEndian, ReadStatMetadata, ReadStatPath,
+    ReadStatVarFormatClass, ReadStatVarType, ReadStatVarTypeClass,
+};
 
 mod common;
 
@@ -18,7 +21,7 @@ fn init() -> (ReadStatPath, ReadStatMetadata, ReadStatData) {
 
     // parse sas7bdat
     // read starting at row 2
-    let d = readstat::ReadStatData::new()
+    let d = ReadStatData::new()
         .set_no_progress(true)
         .init(md.clone(), 2, 3);
 
@@ -43,13 +46,10 @@ fn row_offset_int() {
     let m = common::get_metadata(&d, var_index);
 
     // variable type class
-    assert!(matches!(
-        m.var_type_class,
-        readstat::ReadStatVarTypeClass::Numeric
-    ));
+    assert!(matches!(m.var_type_class, ReadStatVarTypeClass::Numeric));
 
     // variable type
-    assert!(matches!(m.var_type, readstat::ReadStatVarType::Double));
+    assert!(matches!(m.var_type, ReadStatVarType::Double));
 
     // variable format class
     assert!(m.var_format_class.is_none());
@@ -59,12 +59,12 @@ fn row_offset_int() {
 
     // arrow data type
     assert!(matches!(
-        d.schema.fields[var_index as usize].data_type(),
+        d.schema().fields[var_index as usize].data_type(),
         DataType::Float64
     ));
 
     // arrays
-    let arrays = d.chunk.unwrap().into_arrays();
+    let arrays = d.into_chunk().unwrap().into_arrays();
 
     // int column
     let col = arrays
@@ -96,13 +96,10 @@ fn row_offset_string() {
     let m = common::get_metadata(&d, var_index);
 
     // variable type class
-    assert!(matches!(
-        m.var_type_class,
-        readstat::ReadStatVarTypeClass::String
-    ));
+    assert!(matches!(m.var_type_class, ReadStatVarTypeClass::String));
 
     // variable type
-    assert!(matches!(m.var_type, readstat::ReadStatVarType::String));
+    assert!(matches!(m.var_type, ReadStatVarType::String));
 
     // variable format class
     assert!(m.var_format_class.is_none());
@@ -112,12 +109,12 @@ fn row_offset_string() {
 
     // arrow data type
     assert!(matches!(
-        d.schema.fields[var_index as usize].data_type(),
+        d.schema().fields[var_index as usize].data_type(),
         DataType::Utf8
     ));
 
     // arrays
-    let arrays = d.chunk.unwrap().into_arrays();
+    let arrays = d.into_chunk().unwrap().into_arrays();
 
     // string column
     let col = arrays
@@ -149,18 +146,15 @@ fn row_offset_date() {
     let m = common::get_metadata(&d, var_index);
 
     // variable type class
-    assert!(matches!(
-        m.var_type_class,
-        readstat::ReadStatVarTypeClass::Numeric
-    ));
+    assert!(matches!(m.var_type_class, ReadStatVarTypeClass::Numeric));
 
     // variable type
-    assert!(matches!(m.var_type, readstat::ReadStatVarType::Double));
+    assert!(matches!(m.var_type, ReadStatVarType::Double));
 
     // variable format class
     assert!(matches!(
         m.var_format_class,
-        Some(readstat::ReadStatVarFormatClass::Date)
+        Some(ReadStatVarFormatClass::Date)
     ));
 
     // variable format
@@ -168,12 +162,12 @@ fn row_offset_date() {
 
     // arrow data type
     assert!(matches!(
-        d.schema.fields[var_index as usize].data_type(),
+        d.schema().fields[var_index as usize].data_type(),
         DataType::Date32
     ));
 
     // arrays
-    let arrays = d.chunk.unwrap().into_arrays();
+    let arrays = d.into_chunk().unwrap().into_arrays();
 
     // non-missing value
     let col = arrays
@@ -199,7 +193,7 @@ fn row_offset_metadata() {
     assert!(error.is_ok());
 
     // row count = 1 due to offset
-    assert_eq!(d.chunk_rows_to_process, 1);
+    assert_eq!(d.chunk_rows_to_process(), 1);
 
     // variable count
     assert_eq!(md.var_count, 8);
@@ -226,10 +220,10 @@ fn row_offset_metadata() {
     assert_eq!(md.modified_time, "2022-01-08 19:40:48");
 
     // compression
-    assert!(matches!(md.compression, readstat::ReadStatCompress::None));
+    assert!(matches!(md.compression, ReadStatCompress::None));
 
     // endianness
-    assert!(matches!(md.endianness, readstat::ReadStatEndian::Little));
+    assert!(matches!(md.endianness, ReadStatEndian::Little));
 
     // variables - contains variable
     assert!(common::contains_var(&d, 0));
@@ -241,71 +235,71 @@ fn row_offset_metadata() {
 
     // 0 - _int
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 0);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from("BEST12"));
     assert!(matches!(adt, DataType::Float64));
 
     // 1 - _float
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 1);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from("BEST12"));
     assert!(matches!(adt, DataType::Float64));
 
     // 2 - _char
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 2);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::String));
-    assert!(matches!(vt, readstat::ReadStatVarType::String));
+    assert!(matches!(vtc, ReadStatVarTypeClass::String));
+    assert!(matches!(vt, ReadStatVarType::String));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from("$1"));
     assert!(matches!(adt, DataType::Utf8));
 
     // 3 - _string
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 3);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::String));
-    assert!(matches!(vt, readstat::ReadStatVarType::String));
+    assert!(matches!(vtc, ReadStatVarTypeClass::String));
+    assert!(matches!(vt, ReadStatVarType::String));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from("$30"));
     assert!(matches!(adt, DataType::Utf8));
 
     // 4 - _date
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 4);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
-    assert_eq!(vfc, Some(readstat::ReadStatVarFormatClass::Date));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
+    assert_eq!(vfc, Some(ReadStatVarFormatClass::Date));
     assert_eq!(vf, String::from("YYMMDD10"));
     assert!(matches!(adt, DataType::Date32));
 
     // 5 - _datetime
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 5);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
-    assert_eq!(vfc, Some(readstat::ReadStatVarFormatClass::DateTime));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
+    assert_eq!(vfc, Some(ReadStatVarFormatClass::DateTime));
     assert_eq!(vf, String::from("DATETIME22"));
     assert!(matches!(
         adt,
-        DataType::Timestamp(arrow2::datatypes::TimeUnit::Second, None)
+        DataType::Timestamp(arrow2::datatypes::TimeUnit::Millisecond, None)
     ));
 
     // 6 - _datetime_with_ms
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 6);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
-    assert_eq!(vfc, Some(readstat::ReadStatVarFormatClass::DateTime));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
+    assert_eq!(vfc, Some(ReadStatVarFormatClass::DateTime));
     assert_eq!(vf, String::from("DATETIME22"));
     assert!(matches!(
         adt,
-        DataType::Timestamp(arrow2::datatypes::TimeUnit::Second, None)
+        DataType::Timestamp(arrow2::datatypes::TimeUnit::Millisecond, None)
     ));
 
     // 7 - _time
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 7);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
-    assert_eq!(vfc, Some(readstat::ReadStatVarFormatClass::Time));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
+    assert_eq!(vfc, Some(ReadStatVarFormatClass::Time));
     assert_eq!(vf, String::from("TIME"));
     assert!(matches!(
         adt,
@@ -0,0 +1,74 @@
+use readstat::prelude::{sample_rows, ReadStatMetadata, ReadStatSampleRow};
+
+mod common;
+
+fn sampled_rows(
+    ds: &str,
+    n: usize,
+    seed: u64,
+    stream_rows: u32,
+) -> Vec<ReadStatSampleRow> {
+    let rsp = common::setup_path(ds).unwrap();
+
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+
+    sample_rows(&rsp, &md, n, seed, stream_rows).unwrap()
+}
+
+#[test]
+fn sample_rows_same_seed_same_rows() {
+    let first: Vec<u64> = sampled_rows("cars.sas7bdat", 10, 42, 10000)
+        .iter()
+        .map(|r| r.row)
+        .collect();
+    let second: Vec<u64> = sampled_rows("cars.sas7bdat", 10, 42, 10000)
+        .iter()
+        .map(|r| r.row)
+        .collect();
+
+    assert_eq!(first.len(), 10);
+    assert_eq!(first, second);
+
+    // rows are unique, in file order, and within the file
+    assert!(first.windows(2).all(|w| w[0] < w[1]));
+    assert!(first.iter().all(|r| *r < 1081));
+}
+
+#[test]
+fn sample_rows_independent_of_stream_rows() {
+    let streamed: Vec<u64> = sampled_rows("cars.sas7bdat", 10, 7, 100)
+        .iter()
+        .map(|r| r.row)
+        .collect();
+    let whole: Vec<u64> = sampled_rows("cars.sas7bdat", 10, 7, 10000)
+        .iter()
+        .map(|r| r.row)
+        .collect();
+
+    assert_eq!(streamed, whole);
+}
+
+#[test]
+fn sample_rows_not_only_the_head() {
+    let rows: Vec<u64> = sampled_rows("cars.sas7bdat", 10, 42, 10000)
+        .iter()
+        .map(|r| r.row)
+        .collect();
+
+    assert_ne!(rows, (0..10).collect::<Vec<u64>>());
+}
+
+#[test]
+fn sample_rows_more_than_row_count() {
+    let rows = sampled_rows("all_types.sas7bdat", 10, 42, 10000);
+
+    // every row is returned
+    assert_eq!(rows.iter().map(|r| r.row).collect::<Vec<u64>>(), vec![0, 1, 2]);
+
+    // values are keyed by name with missing values as nulls
+    assert_eq!(rows[0].values.len(), 8);
+    assert_eq!(rows[0].values["_int"], 1234.0);
+    assert!(rows[2].values["_int"].is_null());
+    assert_eq!(rows[0].values["_string"], "string");
+}
@@ -0,0 +1,202 @@
+use arrow2::{
+    array::{Array, PrimitiveArray, Utf8Array},
+    chunk::Chunk,
+    datatypes::{DataType, TimeUnit},
+};
+use assert_cmd::Command;
+use assert_fs::TempDir;
+use predicates::prelude::*;
+use readstat::prelude::{
+    convert_collect, OutFormat, OutputMode, ReadStatConvertOptions, ReadStatData,
+    ReadStatMetadata, ReadStatPath, ReadStatWriter,
+};
+use std::path::{Path, PathBuf};
+
+mod common;
+
+fn read(rsp: &ReadStatPath) -> (ReadStatMetadata, Chunk<Box<dyn Array>>) {
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(rsp, false).unwrap();
+
+    let rows = md.row_count as u32;
+    let mut d = ReadStatData::new()
+        .set_no_progress(true)
+        .init(md.clone(), 0, rows);
+    d.read_data(rsp).unwrap();
+    (md, d.chunk().unwrap().clone())
+}
+
+// all_types written as `format`, returning the warnings of the conversion
+fn write_all_types(out_path: &Path, format: OutFormat) -> Vec<String> {
+    let rsp = ReadStatPath::new(
+        common::setup_path("all_types.sas7bdat").unwrap().path,
+        Some(out_path.to_path_buf()),
+        Some(format),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::WriteAndCollect,
+        ..ReadStatConvertOptions::default()
+    };
+    convert_collect(&rsp, &options).unwrap().warnings
+}
+
+fn read_back(out_path: &Path) -> (ReadStatMetadata, Chunk<Box<dyn Array>>) {
+    let rsp = ReadStatPath::new(out_path.to_path_buf(), None, None, false, false, None, None)
+        .unwrap();
+    read(&rsp)
+}
+
+// Strings with missing values as empty, as SPSS and Stata store them
+fn strings(a: &dyn Array) -> Vec<String> {
+    let a = a.as_any().downcast_ref::<Utf8Array<i32>>().unwrap();
+    a.iter().map(|v| v.unwrap_or_default().to_owned()).collect()
+}
+
+fn timestamps(a: &dyn Array) -> Vec<Option<i64>> {
+    let a = a.as_any().downcast_ref::<PrimitiveArray<i64>>().unwrap();
+    a.iter().map(|v| v.copied()).collect()
+}
+
+#[test]
+fn sav_round_trip() {
+    let (md, original) = read(&common::setup_path("all_types.sas7bdat").unwrap());
+
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("all_types.sav");
+    let warnings = write_all_types(&out_path, OutFormat::sav);
+    let (md_sav, sav) = read_back(&out_path);
+
+    // SPSS names begin with a letter
+    let names: Vec<&str> = md_sav.schema.fields.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "v_int",
+            "v_float",
+            "v_char",
+            "v_string",
+            "v_date",
+            "v_datetime",
+            "v_datetime_with_ms",
+            "v_time"
+        ]
+    );
+    assert_eq!(warnings.iter().filter(|w| w.contains("is named")).count(), 8);
+
+    // types, labels, and values survive
+    assert_eq!(md_sav.row_count, md.row_count);
+    for ((f, f_sav), (vm, vm_sav)) in md
+        .schema
+        .fields
+        .iter()
+        .zip(md_sav.schema.fields.iter())
+        .zip(md.vars.values().zip(md_sav.vars.values()))
+    {
+        assert_eq!(f.data_type(), f_sav.data_type(), "{}", f.name);
+        assert_eq!(vm.var_label, vm_sav.var_label, "{}", f.name);
+    }
+    for i in [0, 1, 4, 5, 6, 7] {
+        assert_eq!(original.arrays()[i], sav.arrays()[i], "{}", names[i]);
+    }
+    for i in [2, 3] {
+        assert_eq!(
+            strings(original.arrays()[i].as_ref()),
+            strings(sav.arrays()[i].as_ref())
+        );
+    }
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn dta_round_trip() {
+    let (_, original) = read(&common::setup_path("all_types.sas7bdat").unwrap());
+
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("all_types.dta");
+    let warnings = write_all_types(&out_path, OutFormat::dta);
+    let (md_dta, dta) = read_back(&out_path);
+
+    // Stata names may begin with an underscore
+    assert!(warnings.iter().all(|w| !w.contains("is named")));
+    assert_eq!(md_dta.schema.fields[0].name, "_int");
+
+    for i in [0, 1, 4, 6, 7] {
+        assert_eq!(original.arrays()[i], dta.arrays()[i], "{}", i);
+    }
+    for i in [2, 3] {
+        assert_eq!(
+            strings(original.arrays()[i].as_ref()),
+            strings(dta.arrays()[i].as_ref())
+        );
+    }
+
+    // Stata datetimes are of milliseconds
+    assert_eq!(
+        md_dta.schema.fields[5].data_type(),
+        &DataType::Timestamp(TimeUnit::Millisecond, None)
+    );
+    let seconds: Vec<Option<i64>> = timestamps(original.arrays()[5].as_ref())
+        .into_iter()
+        .map(|v| v.map(|v| v * 1000))
+        .collect();
+    assert_eq!(seconds, timestamps(dta.arrays()[5].as_ref()));
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn sav_output_cannot_be_split() {
+    let out_dir = TempDir::new().unwrap();
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    let out_rsp = ReadStatPath {
+        out_path: Some(out_dir.path().join("all_types.sav")),
+        format: OutFormat::sav,
+        ..rsp.clone()
+    };
+
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    let mut d = ReadStatData::new().set_no_progress(true).init(md, 0, 3);
+    d.read_data(&rsp).unwrap();
+
+    let e = ReadStatWriter::new()
+        .set_quiet(true)
+        .set_split_rows(Some(1))
+        .write(&d, &out_rsp)
+        .unwrap_err()
+        .to_string();
+    assert!(e.contains("not supported for sav files"));
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn cli_writes_sav_and_reads_it_back() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path: PathBuf = out_dir.path().join("all_types.sav");
+
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("data")
+        .arg("tests/data/all_types.sas7bdat")
+        .arg("--output")
+        .arg(&out_path)
+        .args(["--format", "sav"])
+        .arg("--no-progress");
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("preview").arg(&out_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("v_int,v_float"))
+        .stdout(predicate::str::contains("stringy string"));
+
+    out_dir.close().unwrap();
+}
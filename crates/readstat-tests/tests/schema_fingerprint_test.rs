@@ -0,0 +1,129 @@
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert, NameTransform, OutFormat, ReadStatConvertOptions, ReadStatMapping, ReadStatMetadata,
+    ReadStatPath, ReadStatSchema, ReadStatSchemaColumn,
+};
+use serde_json::Value;
+
+mod common;
+
+// sha256 of the canonical form of the cars schema, computed independently
+const CARS_FINGERPRINT: &str = "3d74cd0786f62d92b28983298a24a357bbc9e37ecf3823d303736be27c0ed882";
+
+fn cars_metadata() -> ReadStatMetadata {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    md
+}
+
+fn column(name: &str, arrow_type: &str) -> ReadStatSchemaColumn {
+    ReadStatSchemaColumn {
+        name: String::from(name),
+        arrow_type: String::from(arrow_type),
+        sas_format: String::new(),
+        sas_format_parts: None,
+        label: String::new(),
+        nullable: true,
+    }
+}
+
+#[test]
+fn fingerprint_of_canonical_form() {
+    // sha256 of "readstat-schema-fingerprint-v1\n2:id:Float64\n4:name:Utf8\n"
+    assert_eq!(
+        ReadStatSchema::fingerprint_columns(&[column("id", "Float64"), column("name", "Utf8")]),
+        "59cfd00c8043926f37b5681da4681f15f69251415813190c42c08443b530ae7d"
+    );
+}
+
+#[test]
+fn fingerprint_excludes_metadata_but_not_order() {
+    let columns = vec![column("id", "Float64"), column("name", "Utf8")];
+    let fingerprint = ReadStatSchema::fingerprint_columns(&columns);
+
+    // labels, formats, and nullability are excluded
+    let mut described = columns.clone();
+    described[0].label = String::from("Identifier");
+    described[0].sas_format = String::from("BEST12");
+    described[1].nullable = false;
+    assert_eq!(
+        ReadStatSchema::fingerprint_columns(&described),
+        fingerprint
+    );
+
+    // order, names, and types are included
+    let reordered = vec![columns[1].clone(), columns[0].clone()];
+    let renamed = vec![column("ID", "Float64"), column("name", "Utf8")];
+    let retyped = vec![column("id", "Int32"), column("name", "Utf8")];
+    for other in [reordered, renamed, retyped] {
+        assert_ne!(ReadStatSchema::fingerprint_columns(&other), fingerprint);
+    }
+
+    // the name length keeps the boundary between name and type unambiguous
+    assert_ne!(
+        ReadStatSchema::fingerprint_columns(&[column("a:b", "Utf8")]),
+        ReadStatSchema::fingerprint_columns(&[column("a", "b:Utf8")])
+    );
+}
+
+#[test]
+fn cars_fingerprint_is_stable() {
+    // computed afresh each time
+    for _ in 0..2 {
+        assert_eq!(
+            ReadStatSchema::new(&cars_metadata()).fingerprint,
+            CARS_FINGERPRINT
+        );
+    }
+}
+
+#[test]
+fn fingerprint_changes_with_rename() {
+    let mut md = cars_metadata();
+    md.rename_vars(&[(String::from("Brand"), String::from("Make"))])
+        .unwrap();
+    let renamed = ReadStatSchema::new(&md).fingerprint;
+
+    assert_ne!(renamed, CARS_FINGERPRINT);
+    assert_eq!(
+        renamed,
+        "9d9f76da0f6059dbecf1805c7050073884ff54fae3b3c7abb7a99f58ea9bdb0f"
+    );
+}
+
+#[test]
+fn fingerprint_recorded_within_sidecars() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("cars.csv");
+    let rsp = ReadStatPath::new(
+        common::setup_path("cars.sas7bdat").unwrap().path,
+        Some(out_path.clone()),
+        Some(OutFormat::csv),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let options = ReadStatConvertOptions {
+        name_transform: NameTransform::Lowercase,
+        schema_sidecar: true,
+        ..ReadStatConvertOptions::default()
+    };
+    convert(&rsp, &options).unwrap();
+
+    let schema = ReadStatSchema::read(&ReadStatSchema::sidecar_path(&out_path)).unwrap();
+    assert_eq!(
+        schema.fingerprint,
+        ReadStatSchema::fingerprint_columns(&schema.columns)
+    );
+    assert_ne!(schema.fingerprint, CARS_FINGERPRINT);
+
+    let text = std::fs::read_to_string(ReadStatMapping::sidecar_path(&out_path)).unwrap();
+    let mapping: Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(mapping["schema_fingerprint"], schema.fingerprint.as_str());
+
+    out_dir.close().unwrap();
+}
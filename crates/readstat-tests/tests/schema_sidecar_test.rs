@@ -0,0 +1,106 @@
+use arrow2::io::csv::read;
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert_collect, OutFormat, OutputMode, ReadStatCollected, ReadStatConvertOptions, ReadStatPath,
+    ReadStatSchema, SCHEMA_VERSION,
+};
+use std::path::Path;
+
+mod common;
+
+fn convert_all_types(out_path: &Path, schema_sidecar: bool) -> ReadStatCollected {
+    let rsp = ReadStatPath::new(
+        common::setup_path("all_types.sas7bdat").unwrap().path,
+        Some(out_path.to_path_buf()),
+        Some(OutFormat::csv),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::WriteAndCollect,
+        schema_sidecar,
+        ..ReadStatConvertOptions::default()
+    };
+    convert_collect(&rsp, &options).unwrap()
+}
+
+#[test]
+fn schema_sidecar_describes_every_column() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("all_types.csv");
+    convert_all_types(&out_path, true);
+
+    let sidecar = ReadStatSchema::sidecar_path(&out_path);
+    assert_eq!(sidecar.file_name().unwrap(), "all_types.schema.json");
+
+    let schema = ReadStatSchema::read(&sidecar).unwrap();
+    assert_eq!(schema.version, SCHEMA_VERSION);
+    assert_eq!(schema.columns.len(), 8);
+
+    let string = &schema.columns[3];
+    assert_eq!(string.name, "_string");
+    assert_eq!(string.arrow_type, "Utf8");
+    assert_eq!(string.sas_format, "$30");
+    assert!(string.nullable);
+
+    let date = &schema.columns[4];
+    assert_eq!(date.arrow_type, "Date32");
+    assert_eq!(date.sas_format, "YYMMDD10");
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn schema_sidecar_round_trips_csv() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("all_types.csv");
+    let collected = convert_all_types(&out_path, true);
+
+    // types reconstructed from the sidecar match those written
+    let schema = ReadStatSchema::read(&ReadStatSchema::sidecar_path(&out_path))
+        .unwrap()
+        .to_arrow_schema()
+        .unwrap();
+    assert_eq!(schema, collected.schema);
+
+    // read the csv back using the reconstructed types
+    let mut reader = read::ReaderBuilder::new().from_path(&out_path).unwrap();
+    let mut rows = vec![read::ByteRecord::default(); 10];
+    let rows_read = read::read_rows(&mut reader, 0, &mut rows).unwrap();
+    assert_eq!(rows_read, 3);
+
+    // numeric, string, and date columns
+    let projection = [0, 1, 2, 3, 4];
+    let chunk = read::deserialize_batch(
+        &rows[..rows_read],
+        &schema.fields,
+        Some(&projection),
+        0,
+        read::deserialize_column,
+    )
+    .unwrap();
+
+    let original = &collected.chunks[0];
+    for (i, array) in projection.iter().zip(chunk.arrays()) {
+        assert_eq!(array.data_type(), original.arrays()[*i].data_type());
+        assert_eq!(array, &original.arrays()[*i], "column {} differs", i);
+    }
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn schema_sidecar_not_written_by_default() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("all_types.csv");
+    convert_all_types(&out_path, false);
+
+    assert!(out_path.exists());
+    assert!(!ReadStatSchema::sidecar_path(&out_path).exists());
+
+    out_dir.close().unwrap();
+}
@@ -0,0 +1,166 @@
+use arrow2::datatypes::DataType;
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert_collect, OutFormat, OutputMode, ReadStatCollected, ReadStatConvertOptions,
+    ReadStatData, ReadStatMetadata, ReadStatPath,
+};
+
+mod common;
+
+fn columns(names: &[&str]) -> Vec<String> {
+    names.iter().map(|n| n.to_string()).collect()
+}
+
+// every row of the file, of only the variables named
+fn read(rsp: &ReadStatPath, names: Option<&[&str]>) -> ReadStatData {
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(rsp, false).unwrap();
+
+    let rows = md.row_count as u32;
+    let d = ReadStatData::new().set_no_progress(true);
+    let d = match names {
+        Some(names) => d.set_columns(columns(names)),
+        None => d,
+    };
+    let mut d = d.init(md, 0, rows);
+    d.read_data(rsp).unwrap();
+    d
+}
+
+// Skips the variables for which skipped, given the position of each and the number of variables,
+//   holds; their values must not shift those that follow, nor rows end early or late
+fn assert_skipping_keeps_values(rsp: &ReadStatPath, skipped: fn(usize, usize) -> bool) {
+    let full = read(rsp, None);
+    let names: Vec<&str> = full.schema().fields.iter().map(|f| f.name.as_str()).collect();
+    let kept: Vec<&str> = names
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !skipped(*i, names.len()))
+        .map(|(_, n)| *n)
+        .collect();
+    let d = read(rsp, Some(&kept));
+
+    assert!(kept.len() < names.len());
+    assert_eq!(d.var_count() as usize, kept.len());
+    assert_eq!(d.chunk().unwrap().len(), full.chunk().unwrap().len());
+    for (i, (k, vm)) in d.vars().iter().enumerate() {
+        assert_eq!(vm.var_name, kept[i]);
+        assert_eq!(vm.var_index_after_skipping, Some(i as i32));
+        assert_eq!(full.vars()[k].var_index_after_skipping, Some(*k));
+
+        let f = names.iter().position(|n| *n == kept[i]).unwrap();
+        assert_eq!(
+            d.chunk().unwrap().arrays()[i],
+            full.chunk().unwrap().arrays()[f],
+            "{}",
+            kept[i]
+        );
+    }
+}
+
+fn collect_cars(options: ReadStatConvertOptions) -> ReadStatCollected {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        ..options
+    };
+    convert_collect(&rsp, &options).unwrap()
+}
+
+#[test]
+fn only_selected_columns_are_read() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+
+    let mut d = ReadStatData::new()
+        .set_no_progress(true)
+        .set_columns(columns(&["EngineSize", "Brand"]))
+        .init(md, 0, 10);
+    d.read_data(&rsp).unwrap();
+
+    // file order rather than the order given, with variables keeping their indices
+    assert_eq!(d.var_count(), 2);
+    assert_eq!(d.vars().keys().copied().collect::<Vec<i32>>(), vec![0, 6]);
+    let names: Vec<&str> = d.schema().fields.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, vec!["Brand", "EngineSize"]);
+
+    let chunk = d.chunk().unwrap();
+    assert_eq!(chunk.len(), 10);
+    assert_eq!(chunk.arrays().len(), 2);
+    assert_eq!(chunk.arrays()[0].data_type(), &DataType::Utf8);
+    assert_eq!(chunk.arrays()[1].data_type(), &DataType::Float64);
+}
+
+#[test]
+fn selected_columns_match_a_full_read() {
+    let full = collect_cars(ReadStatConvertOptions::default());
+    let selected = collect_cars(ReadStatConvertOptions {
+        columns: columns(&["Hybrid", "Model"]),
+        ..ReadStatConvertOptions::default()
+    });
+
+    assert_eq!(selected.rows, full.rows);
+    for (s, f) in selected.chunks.iter().zip(full.chunks.iter()) {
+        assert_eq!(s.arrays()[0], f.arrays()[1]);
+        assert_eq!(s.arrays()[1], f.arrays()[12]);
+    }
+}
+
+#[test]
+fn unknown_column_lists_available_names() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        columns: columns(&["Brand", "Colour"]),
+        ..ReadStatConvertOptions::default()
+    };
+
+    let e = convert_collect(&rsp, &options).unwrap_err().to_string();
+    assert!(e.contains("Colour"));
+    assert!(e.contains("Brand, Model, Minivan"));
+}
+
+// The first and a middle variable
+fn first_and_middle(i: usize, n: usize) -> bool {
+    i == 0 || i == n / 2
+}
+
+#[test]
+fn skipping_the_first_and_a_middle_variable_keeps_values_in_place() {
+    for name in ["all_types.sas7bdat", "cars.sas7bdat"] {
+        assert_skipping_keeps_values(&common::setup_path(name).unwrap(), first_and_middle);
+    }
+}
+
+#[test]
+fn skipping_the_last_variable_still_ends_every_row() {
+    for name in ["all_types.sas7bdat", "cars.sas7bdat"] {
+        assert_skipping_keeps_values(&common::setup_path(name).unwrap(), |i, n| i == n - 1);
+    }
+}
+
+#[test]
+fn skipping_variables_of_sav_and_dta_files_keeps_values_in_place() {
+    let out_dir = TempDir::new().unwrap();
+
+    for (format, name) in [(OutFormat::sav, "all_types.sav"), (OutFormat::dta, "all_types.dta")] {
+        let out_path = out_dir.path().join(name);
+        let rsp = ReadStatPath::new(
+            common::setup_path("all_types.sas7bdat").unwrap().path,
+            Some(out_path.clone()),
+            Some(format),
+            true,
+            false,
+            None,
+            None,
+        )
+        .unwrap();
+        convert_collect(&rsp, &ReadStatConvertOptions::default()).unwrap();
+
+        let rsp = ReadStatPath::new(out_path, None, None, false, false, None, None).unwrap();
+        assert_skipping_keeps_values(&rsp, first_and_middle);
+    }
+
+    out_dir.close().unwrap();
+}
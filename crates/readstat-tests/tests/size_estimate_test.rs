@@ -0,0 +1,123 @@
+use arrow2::compute::aggregate::estimated_bytes_size;
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert, convert_collect, OutFormat, OutputMode, ReadStatConvertOptions, ReadStatMetadata,
+    ReadStatPath, ReadStatSizeEstimate,
+};
+
+mod common;
+
+fn estimate(ds: &str) -> ReadStatSizeEstimate {
+    let rsp = common::setup_path(ds).unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    md.estimate_size(md.row_count as u32)
+}
+
+// bytes of the Arrow arrays actually read
+fn measured_arrow_bytes(ds: &str) -> u64 {
+    let rsp = common::setup_path(ds).unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        ..ReadStatConvertOptions::default()
+    };
+
+    convert_collect(&rsp, &options)
+        .unwrap()
+        .chunks
+        .iter()
+        .flat_map(|c| c.arrays().iter().map(|a| estimated_bytes_size(a.as_ref()) as u64))
+        .sum()
+}
+
+// bytes of the file actually written
+fn measured_output_bytes(ds: &str, format: OutFormat) -> u64 {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join(format!("out.{}", format));
+
+    let rsp = ReadStatPath::new(
+        common::setup_path(ds).unwrap().path,
+        Some(out_path.clone()),
+        Some(format),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+    convert(&rsp, &ReadStatConvertOptions::default()).unwrap();
+
+    let bytes = std::fs::metadata(&out_path).unwrap().len();
+    out_dir.close().unwrap();
+    bytes
+}
+
+fn assert_within(estimated: u64, measured: u64, factor: f64, what: &str) {
+    let ratio = estimated as f64 / measured as f64;
+    assert!(
+        ratio >= 1.0 / factor && ratio <= factor,
+        "{} estimated at {} bytes but measured at {} bytes",
+        what,
+        estimated,
+        measured
+    );
+}
+
+fn assert_estimates(ds: &str) {
+    let e = estimate(ds);
+
+    assert_within(e.arrow_bytes, measured_arrow_bytes(ds), 2.0, "Arrow");
+
+    // numbers and strings vary widely in length as text
+    for format in [OutFormat::csv, OutFormat::ndjson] {
+        let measured = measured_output_bytes(ds, format);
+        assert_within(e.output_bytes(format), measured, 3.0, &format.to_string());
+    }
+
+    // compression depends entirely upon the data
+    for format in [OutFormat::feather, OutFormat::parquet] {
+        let measured = measured_output_bytes(ds, format);
+        assert_within(e.output_bytes(format), measured, 5.0, &format.to_string());
+    }
+}
+
+#[test]
+fn storage_widths_are_read() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+
+    // Brand and Model are $13 and $36; every numeric is stored in 8 bytes
+    let widths: Vec<usize> = md.vars.values().map(|vm| vm.var_storage_width).collect();
+    assert_eq!(&widths[..2], &[13, 36]);
+    assert!(widths[2..].iter().all(|w| *w == 8));
+
+    let e = md.estimate_size(md.row_count as u32);
+    assert_eq!(e.rows, 1081);
+    assert_eq!(e.row_storage_bytes, 137);
+}
+
+#[test]
+fn estimate_scales_with_rows() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+
+    let half = md.estimate_size(500);
+    let full = md.estimate_size(1000);
+    assert_eq!(full.rows, 2 * half.rows);
+    assert!(full.arrow_bytes.abs_diff(2 * half.arrow_bytes) <= 1);
+    assert_eq!(md.estimate_size(0).arrow_bytes, 0);
+}
+
+#[test]
+fn estimate_near_measured_narrow() {
+    // cars - short strings and small numbers
+    assert_estimates("cars.sas7bdat");
+}
+
+#[test]
+fn estimate_near_measured_wide() {
+    // rand_ds_largepage_ok - full-width strings and numbers at full precision
+    assert_estimates("rand_ds_largepage_ok.sas7bdat");
+}
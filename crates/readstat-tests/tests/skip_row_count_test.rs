@@ -1,10 +1,13 @@
 use arrow2::{
     array::{Float64Array, Int64Array, Utf8Array},
     datatypes::{DataType, TimeUnit},
-    temporal_conversions::timestamp_s_to_datetime,
+    temporal_conversions::timestamp_ms_to_datetime,
 };
 use chrono::NaiveDate;
-use readstat::{ReadStatData, ReadStatMetadata, ReadStatPath};
+use readstat::prelude::{
+    ReadStatCompress, ReadStatData, ReadStatEndian, ReadStatMetadata, ReadStatPath,
+    ReadStatVarFormatClass, ReadStatVarType, ReadStatVarTypeClass,
+};
 
 mod common;
 
@@ -18,7 +21,7 @@ fn init() -> (ReadStatPath, ReadStatMetadata, ReadStatData) {
 
     // parse sas7bdat
     // read only up to the 5th row
-    let d = readstat::ReadStatData::new().set_no_progress(true).init(
+    let d = ReadStatData::new().set_no_progress(true).init(
         md.clone(),
         0,
         md.row_count as u32,
@@ -37,7 +40,7 @@ fn init_skip_row_count() -> (ReadStatPath, ReadStatMetadata, ReadStatData) {
 
     // parse sas7bdat
     // read only up to the 5th row
-    let d = readstat::ReadStatData::new().set_no_progress(true).init(
+    let d = ReadStatData::new().set_no_progress(true).init(
         md.clone(),
         0,
         md.row_count as u32,
@@ -64,13 +67,10 @@ fn skip_row_count_int() {
     let m = common::get_metadata(&d, var_index);
 
     // variable type class
-    assert!(matches!(
-        m.var_type_class,
-        readstat::ReadStatVarTypeClass::Numeric
-    ));
+    assert!(matches!(m.var_type_class, ReadStatVarTypeClass::Numeric));
 
     // variable type
-    assert!(matches!(m.var_type, readstat::ReadStatVarType::Double));
+    assert!(matches!(m.var_type, ReadStatVarType::Double));
 
     // variable format class
     assert!(m.var_format_class.is_none());
@@ -80,12 +80,12 @@ fn skip_row_count_int() {
 
     // arrow data type
     assert!(matches!(
-        d.schema.fields[var_index as usize].data_type(),
+        d.schema().fields[var_index as usize].data_type(),
         DataType::Float64
     ));
 
     // arrays
-    let arrays = d.chunk.unwrap().into_arrays();
+    let arrays = d.into_chunk().unwrap().into_arrays();
 
     // int column
     let col = arrays
@@ -122,13 +122,10 @@ fn skip_row_count_string() {
     let m = common::get_metadata(&d, var_index);
 
     // variable type class
-    assert!(matches!(
-        m.var_type_class,
-        readstat::ReadStatVarTypeClass::String
-    ));
+    assert!(matches!(m.var_type_class, ReadStatVarTypeClass::String));
 
     // variable type
-    assert!(matches!(m.var_type, readstat::ReadStatVarType::String));
+    assert!(matches!(m.var_type, ReadStatVarType::String));
 
     // variable format class
     assert!(m.var_format_class.is_none());
@@ -138,12 +135,12 @@ fn skip_row_count_string() {
 
     // arrow data type
     assert!(matches!(
-        d.schema.fields[var_index as usize].data_type(),
+        d.schema().fields[var_index as usize].data_type(),
         DataType::Utf8
     ));
 
     // arrays
-    let arrays = d.chunk.unwrap().into_arrays();
+    let arrays = d.into_chunk().unwrap().into_arrays();
 
     // string column
     let col = arrays
@@ -178,18 +175,15 @@ fn skip_row_count_datetime() {
     let m = common::get_metadata(&d, var_index);
 
     // variable type class
-    assert!(matches!(
-        m.var_type_class,
-        readstat::ReadStatVarTypeClass::Numeric
-    ));
+    assert!(matches!(m.var_type_class, ReadStatVarTypeClass::Numeric));
 
     // variable type
-    assert!(matches!(m.var_type, readstat::ReadStatVarType::Double));
+    assert!(matches!(m.var_type, ReadStatVarType::Double));
 
     // variable format class
     assert!(matches!(
         m.var_format_class,
-        Some(readstat::ReadStatVarFormatClass::DateTime)
+        Some(ReadStatVarFormatClass::DateTime)
     ));
 
     // variable format
@@ -197,12 +191,12 @@ fn skip_row_count_datetime() {
 
     // arrow data type
     assert!(matches!(
-        d.schema.fields[var_index as usize].data_type(),
-        DataType::Timestamp(TimeUnit::Second, None)
+        d.schema().fields[var_index as usize].data_type(),
+        DataType::Timestamp(TimeUnit::Millisecond, None)
     ));
 
     // arrays
-    let arrays = d.chunk.unwrap().into_arrays();
+    let arrays = d.into_chunk().unwrap().into_arrays();
 
     // datetime column
     let col = arrays
@@ -212,10 +206,10 @@ fn skip_row_count_datetime() {
         .downcast_ref::<Int64Array>()
         .unwrap()
         .to_owned()
-        .to(DataType::Timestamp(TimeUnit::Second, None));
+        .to(DataType::Timestamp(TimeUnit::Millisecond, None));
 
     // non-missing value
-    let dt = timestamp_s_to_datetime(col.value(1));
+    let dt = timestamp_ms_to_datetime(col.value(1));
     let dt_literal = NaiveDate::from_ymd_opt(2021, 6, 1)
         .unwrap()
         .and_hms_milli_opt(13, 42, 25, 0)
@@ -259,10 +253,10 @@ fn parse_all_types_metadata() {
     assert_eq!(md.modified_time, "2022-01-08 19:40:48");
 
     // compression
-    assert!(matches!(md.compression, readstat::ReadStatCompress::None));
+    assert!(matches!(md.compression, ReadStatCompress::None));
 
     // endianness
-    assert!(matches!(md.endianness, readstat::ReadStatEndian::Little));
+    assert!(matches!(md.endianness, ReadStatEndian::Little));
 
     // variables - contains variable
     assert!(common::contains_var(&d, 0));
@@ -274,71 +268,71 @@ fn parse_all_types_metadata() {
 
     // 0 - _int
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 0);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from("BEST12"));
     assert!(matches!(adt, DataType::Float64));
 
     // 1 - _float
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 1);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from("BEST12"));
     assert!(matches!(adt, DataType::Float64));
 
     // 2 - _char
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 2);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::String));
-    assert!(matches!(vt, readstat::ReadStatVarType::String));
+    assert!(matches!(vtc, ReadStatVarTypeClass::String));
+    assert!(matches!(vt, ReadStatVarType::String));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from("$1"));
     assert!(matches!(adt, DataType::Utf8));
 
     // 3 - _string
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 3);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::String));
-    assert!(matches!(vt, readstat::ReadStatVarType::String));
+    assert!(matches!(vtc, ReadStatVarTypeClass::String));
+    assert!(matches!(vt, ReadStatVarType::String));
     assert!(vfc.is_none());
     assert_eq!(vf, String::from("$30"));
     assert!(matches!(adt, DataType::Utf8));
 
     // 4 - _date
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 4);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
-    assert_eq!(vfc, Some(readstat::ReadStatVarFormatClass::Date));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
+    assert_eq!(vfc, Some(ReadStatVarFormatClass::Date));
     assert_eq!(vf, String::from("YYMMDD10"));
     assert!(matches!(adt, DataType::Date32));
 
     // 5 - _datetime
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 5);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
-    assert_eq!(vfc, Some(readstat::ReadStatVarFormatClass::DateTime));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
+    assert_eq!(vfc, Some(ReadStatVarFormatClass::DateTime));
     assert_eq!(vf, String::from("DATETIME22"));
     assert!(matches!(
         adt,
-        DataType::Timestamp(arrow2::datatypes::TimeUnit::Second, None)
+        DataType::Timestamp(arrow2::datatypes::TimeUnit::Millisecond, None)
     ));
 
     // 6 - _datetime_with_ms
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 6);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
-    assert_eq!(vfc, Some(readstat::ReadStatVarFormatClass::DateTime));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
+    assert_eq!(vfc, Some(ReadStatVarFormatClass::DateTime));
     assert_eq!(vf, String::from("DATETIME22"));
     assert!(matches!(
         adt,
-        DataType::Timestamp(arrow2::datatypes::TimeUnit::Second, None)
+        DataType::Timestamp(arrow2::datatypes::TimeUnit::Millisecond, None)
     ));
 
     // 7 - _time
     let (vtc, vt, vfc, vf, adt) = common::get_var_attrs(&d, 7);
-    assert!(matches!(vtc, readstat::ReadStatVarTypeClass::Numeric));
-    assert!(matches!(vt, readstat::ReadStatVarType::Double));
-    assert_eq!(vfc, Some(readstat::ReadStatVarFormatClass::Time));
+    assert!(matches!(vtc, ReadStatVarTypeClass::Numeric));
+    assert!(matches!(vt, ReadStatVarType::Double));
+    assert_eq!(vfc, Some(ReadStatVarFormatClass::Time));
     assert_eq!(vf, String::from("TIME"));
     assert!(matches!(
         adt,
@@ -0,0 +1,208 @@
+use arrow2::io::{
+    ipc::read::{read_file_metadata, FileReader},
+    parquet::read::read_metadata,
+};
+use assert_cmd::Command;
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert_collect, split_path, OutFormat, ReadStatConvertOptions, ReadStatPath,
+};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+mod common;
+
+fn split(out_path: &Path, format: OutFormat) -> Vec<PathBuf> {
+    let rsp = ReadStatPath::new(
+        common::setup_path("cars.sas7bdat").unwrap().path,
+        Some(out_path.to_path_buf()),
+        Some(format),
+        false,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    // 1,081 rows in batches of 100, split once 250 rows are written, i.e. every third batch
+    let options = ReadStatConvertOptions {
+        stream_rows: 100,
+        split_rows: Some(250),
+        ..ReadStatConvertOptions::default()
+    };
+    let c = convert_collect(&rsp, &options).unwrap();
+    assert_eq!(c.rows, 1081);
+    c.written_paths
+}
+
+fn parts(out_path: &Path) -> Vec<PathBuf> {
+    (0..4).map(|i| split_path(out_path, i)).collect()
+}
+
+#[test]
+fn parts_are_numbered_after_the_output() {
+    assert_eq!(
+        split_path(Path::new("/tmp/out.parquet"), 0),
+        PathBuf::from("/tmp/out_000.parquet")
+    );
+    assert_eq!(
+        split_path(Path::new("/tmp/out.parquet"), 12),
+        PathBuf::from("/tmp/out_012.parquet")
+    );
+    assert_eq!(split_path(Path::new("out"), 1000), PathBuf::from("out_1000"));
+}
+
+#[test]
+fn csv_parts_each_have_a_header() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("cars.csv");
+
+    let written = split(&out_path, OutFormat::csv);
+    assert_eq!(written, parts(&out_path));
+    assert!(!out_path.exists());
+
+    let lines: Vec<usize> = written
+        .iter()
+        .map(|p| std::fs::read_to_string(p).unwrap().lines().count())
+        .collect();
+    assert_eq!(lines, vec![301, 301, 301, 182]);
+    for p in &written {
+        let csv = std::fs::read_to_string(p).unwrap();
+        assert!(csv.starts_with("Brand,Model"));
+    }
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn ndjson_parts_hold_every_row() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("cars.ndjson");
+
+    let written = split(&out_path, OutFormat::ndjson);
+    let lines: Vec<usize> = written
+        .iter()
+        .map(|p| std::fs::read_to_string(p).unwrap().lines().count())
+        .collect();
+    assert_eq!(lines, vec![300, 300, 300, 181]);
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn parquet_parts_are_valid_files() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("cars.parquet");
+
+    let written = split(&out_path, OutFormat::parquet);
+    assert_eq!(written, parts(&out_path));
+
+    let rows: Vec<usize> = written
+        .iter()
+        .map(|p| read_metadata(&mut File::open(p).unwrap()).unwrap().num_rows)
+        .collect();
+    assert_eq!(rows, vec![300, 300, 300, 181]);
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn feather_parts_are_valid_files() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("cars.feather");
+
+    let written = split(&out_path, OutFormat::feather);
+    let rows: Vec<usize> = written
+        .iter()
+        .map(|p| {
+            let mut f = File::open(p).unwrap();
+            let metadata = read_file_metadata(&mut f).unwrap();
+            FileReader::new(f, metadata, None, None)
+                .map(|c| c.unwrap().len())
+                .sum()
+        })
+        .collect();
+    assert_eq!(rows, vec![300, 300, 300, 181]);
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn existing_parts_are_not_overwritten() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("cars.csv");
+    std::fs::write(split_path(&out_path, 1), "kept").unwrap();
+
+    let rsp = ReadStatPath::new(
+        common::setup_path("cars.sas7bdat").unwrap().path,
+        Some(out_path.clone()),
+        Some(OutFormat::csv),
+        false,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+    let options = ReadStatConvertOptions {
+        stream_rows: 100,
+        split_rows: Some(250),
+        ..ReadStatConvertOptions::default()
+    };
+    let e = convert_collect(&rsp, &options).unwrap_err();
+    assert!(e.to_string().contains("already exists"));
+    assert_eq!(std::fs::read_to_string(split_path(&out_path, 1)).unwrap(), "kept");
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn standard_out_cannot_be_split() {
+    let rsp = ReadStatPath::new(
+        common::setup_path("cars.sas7bdat").unwrap().path,
+        None,
+        Some(OutFormat::csv),
+        false,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+    let options = ReadStatConvertOptions {
+        split_rows: Some(250),
+        ..ReadStatConvertOptions::default()
+    };
+    assert!(convert_collect(&rsp, &options).is_err());
+}
+
+#[test]
+fn cli_splits_output() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("cars.parquet");
+
+    let output = Command::cargo_bin("readstat")
+        .unwrap()
+        .arg("data")
+        .arg("tests/data/cars.sas7bdat")
+        .arg("--output")
+        .arg(&out_path)
+        .arg("--format")
+        .arg("parquet")
+        .arg("--stream-rows")
+        .arg("500")
+        .arg("--split-rows")
+        .arg("500")
+        .arg("--no-progress")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Split output into 3 files"));
+
+    for (i, rows) in [500, 500, 81].into_iter().enumerate() {
+        let mut f = File::open(split_path(&out_path, i)).unwrap();
+        assert_eq!(read_metadata(&mut f).unwrap().num_rows, rows);
+    }
+
+    out_dir.close().unwrap();
+}
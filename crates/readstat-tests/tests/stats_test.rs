@@ -0,0 +1,116 @@
+use assert_cmd::Command;
+use assert_fs::TempDir;
+use predicates::prelude::*;
+use readstat::prelude::{
+    convert_collect, OutFormat, OutputMode, ReadStatCollected, ReadStatConvertOptions, ReadStatPath,
+};
+use std::path::Path;
+
+mod common;
+
+fn convert_cars(out_path: &Path, options: ReadStatConvertOptions) -> ReadStatCollected {
+    let rsp = ReadStatPath::new(
+        common::setup_path("cars.sas7bdat").unwrap().path,
+        Some(out_path.to_path_buf()),
+        Some(OutFormat::csv),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    convert_collect(&rsp, &options).unwrap()
+}
+
+#[test]
+fn stats_count_the_rows_batches_and_bytes_written() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("cars.csv");
+    let options = ReadStatConvertOptions {
+        stream_rows: 500,
+        ..ReadStatConvertOptions::default()
+    };
+
+    let c = convert_cars(&out_path, options);
+    let stats = &c.stats;
+    assert_eq!(stats.rows_read, 1081);
+    assert_eq!(stats.rows_written, 1081);
+    assert_eq!(stats.batches, 1081_usize.div_ceil(500));
+
+    let size = std::fs::metadata(&out_path).unwrap().len();
+    assert_eq!(stats.bytes_written, vec![(out_path.clone(), size)]);
+    assert_eq!(stats.total_bytes_written(), size);
+    assert!(stats.wall >= stats.write);
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn stats_count_each_file_of_split_output() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("cars.csv");
+    let options = ReadStatConvertOptions {
+        stream_rows: 400,
+        split_rows: Some(400),
+        ..ReadStatConvertOptions::default()
+    };
+
+    let c = convert_cars(&out_path, options);
+    assert_eq!(c.stats.rows_written, 1081);
+    assert_eq!(c.stats.batches, 3);
+    assert_eq!(c.stats.bytes_written.len(), 3);
+    for (p, bytes) in &c.stats.bytes_written {
+        assert_eq!(std::fs::metadata(p).unwrap().len(), *bytes);
+    }
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn stats_read_without_writing() {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        stream_rows: 1,
+        row_filter: Some("_char=c".parse().unwrap()),
+        ..ReadStatConvertOptions::default()
+    };
+
+    let c = convert_collect(&rsp, &options).unwrap();
+    assert_eq!(c.stats.rows_read, 3);
+    assert_eq!(c.stats.rows_written, 0);
+    assert_eq!(c.stats.batches, 3);
+    assert!(c.stats.bytes_written.is_empty());
+}
+
+#[test]
+fn cli_prints_a_summary_unless_quiet() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("cars.csv");
+
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("data")
+        .arg("tests/data/cars.sas7bdat")
+        .arg("--output")
+        .arg(&out_path)
+        .arg("--no-progress");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Read 1,081 rows in 1 batches"));
+
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("data")
+        .arg("tests/data/cars.sas7bdat")
+        .arg("--output")
+        .arg(&out_path)
+        .arg("--overwrite")
+        .arg("--no-progress")
+        .arg("--quiet");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Read 1,081 rows").not())
+        .stdout(predicate::str::contains("In total, wrote").not());
+
+    out_dir.close().unwrap();
+}
@@ -0,0 +1,100 @@
+use arrow2::{
+    array::{Array, Utf8Array},
+    chunk::Chunk,
+    datatypes::DataType,
+};
+use readstat::prelude::{
+    convert_collect, OutputMode, ReadStatConvertOptions, ReadStatData, ReadStatMetadata,
+};
+
+mod common;
+
+fn cars_metadata() -> ReadStatMetadata {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    md
+}
+
+fn read(row_start: u32, row_end: u32, capacities: Option<Vec<usize>>) -> ReadStatData {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let mut d = ReadStatData::new()
+        .set_no_progress(true)
+        .set_string_capacities(capacities)
+        .init(cars_metadata(), row_start, row_end);
+    d.read_data(&rsp).unwrap();
+    d
+}
+
+fn collect(stream_rows: u32, parallel: bool) -> Vec<Chunk<Box<dyn Array>>> {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        stream_rows,
+        parallel,
+        parallel_min_rows: 0,
+        ..ReadStatConvertOptions::default()
+    };
+    convert_collect(&rsp, &options).unwrap().chunks
+}
+
+// Every chunk compared against the same rows of a single chunk
+fn assert_same_rows(chunks: &[Chunk<Box<dyn Array>>], whole: &Chunk<Box<dyn Array>>) {
+    let mut offset = 0;
+    for c in chunks {
+        for (a, w) in c.arrays().iter().zip(whole.arrays()) {
+            assert_eq!(a.as_ref(), w.sliced(offset, c.len()).as_ref());
+        }
+        offset += c.len();
+    }
+    assert_eq!(offset, whole.len());
+}
+
+#[test]
+fn estimates_scale_the_bytes_read() {
+    let d = read(0, 100, None);
+    let strings = d.chunk().unwrap().arrays()[0]
+        .as_any()
+        .downcast_ref::<Utf8Array<i32>>()
+        .unwrap();
+    let bytes = strings.values().len();
+
+    let estimate = d.estimate_string_capacities(200).unwrap();
+    assert_eq!(estimate[0], bytes * 2);
+    // numeric columns hold no strings
+    let numeric = d
+        .schema()
+        .fields
+        .iter()
+        .position(|f| f.data_type() != &DataType::Utf8)
+        .unwrap();
+    assert_eq!(estimate[numeric], 0);
+
+    assert_eq!(read(0, 0, None).estimate_string_capacities(200), None);
+}
+
+#[test]
+fn estimates_too_small_or_too_large_read_alike() {
+    let expected = read(100, 300, None);
+    let estimate = read(0, 100, None).estimate_string_capacities(200);
+
+    let n = expected.schema().fields.len();
+    for capacities in [estimate, Some(vec![0; n]), Some(vec![1 << 20; n])] {
+        let d = read(100, 300, capacities);
+        assert_eq!(d.chunk(), expected.chunk());
+    }
+}
+
+#[test]
+fn batches_sized_from_those_before_match_a_single_batch() {
+    let whole = collect(10_000, false);
+    assert_eq!(whole.len(), 1);
+
+    // the first batch is sized from nothing and every batch after from the one before
+    let serial = collect(100, false);
+    assert_eq!(serial.len(), 11);
+    assert_same_rows(&serial, &whole[0]);
+
+    let parallel = collect(100, true);
+    assert_same_rows(&parallel, &whole[0]);
+}
@@ -0,0 +1,64 @@
+use arrow2::array::Utf8Array;
+use readstat::prelude::{
+    convert_collect, OutputMode, ReadStatConvertOptions, ReadStatData, ReadStatMetadata, TrimMode,
+};
+
+mod common;
+
+fn strings(string_trim: TrimMode, var_index: usize) -> Vec<Option<String>> {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    let rows = md.row_count as u32;
+
+    let mut d = ReadStatData::new()
+        .set_no_progress(true)
+        .set_string_trim(string_trim)
+        .init(md, 0, rows);
+    d.read_data(&rsp).unwrap();
+
+    d.chunk().unwrap().arrays()[var_index]
+        .as_any()
+        .downcast_ref::<Utf8Array<i32>>()
+        .unwrap()
+        .iter()
+        .map(|v| v.map(String::from))
+        .collect()
+}
+
+#[test]
+fn trailing_whitespace_is_trimmed_by_default() {
+    assert_eq!(ReadStatConvertOptions::default().string_trim, TrimMode::TrailingWhitespace);
+
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        ..ReadStatConvertOptions::default()
+    };
+    let c = convert_collect(&rsp, &options).unwrap();
+    let col = c.chunks[0].arrays()[3]
+        .as_any()
+        .downcast_ref::<Utf8Array<i32>>()
+        .unwrap();
+
+    // a $30 column holds its values unpadded, with internal spaces kept
+    assert_eq!(col.value(0), "string");
+    assert_eq!(col.value(1), "another string");
+    assert_eq!(col.value(2), "stringy string");
+}
+
+#[test]
+fn blank_values_are_empty_rather_than_missing() {
+    // _char is blank within the third row
+    for string_trim in [TrimMode::TrailingWhitespace, TrimMode::None] {
+        let chars = strings(string_trim, 2);
+        assert_eq!(chars[0].as_deref(), Some("s"));
+        assert_eq!(chars[2].as_deref(), Some(""));
+    }
+}
+
+#[test]
+fn trimming_may_be_turned_off() {
+    // ReadStat itself drops trailing spaces, so values read from the file are alike either way
+    assert_eq!(strings(TrimMode::None, 3), strings(TrimMode::TrailingWhitespace, 3));
+}
@@ -0,0 +1,130 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use readstat::prelude::{ReadStatColumnSummary, ReadStatSummary, ReadStatSummaryKind, DISTINCT_CAP};
+use serde_json::Value;
+
+mod common;
+
+fn summarize(distinct_cap: usize) -> ReadStatSummary {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    ReadStatSummary::new(&rsp, distinct_cap).unwrap()
+}
+
+fn column<'a>(summary: &'a ReadStatSummary, name: &str) -> &'a ReadStatColumnSummary {
+    summary.columns.iter().find(|c| c.var_name == name).unwrap()
+}
+
+#[test]
+fn summary_of_numerics() {
+    let summary = summarize(DISTINCT_CAP);
+    assert_eq!(summary.table_name, "ALL_TYPES");
+    assert_eq!(summary.row_count, 3);
+    assert_eq!(summary.columns.len(), 8);
+
+    let int = column(&summary, "_int");
+    assert_eq!(int.kind, ReadStatSummaryKind::Numeric);
+    assert_eq!((int.count, int.missing_count), (2, 1));
+    assert_eq!(int.min, Some(1234.0));
+    assert_eq!(int.max, Some(4567.0));
+    assert_eq!(int.mean, Some(2900.5));
+
+    let float = column(&summary, "_float");
+    assert_eq!((float.count, float.missing_count), (3, 0));
+    assert_eq!(float.min, Some(910.11));
+    assert_eq!(float.max, Some(4567.8));
+    assert!(float.distinct_count.is_none());
+}
+
+#[test]
+fn summary_of_strings() {
+    let summary = summarize(DISTINCT_CAP);
+
+    let string = column(&summary, "_string");
+    assert_eq!(string.kind, ReadStatSummaryKind::String);
+    assert_eq!((string.count, string.missing_count), (3, 0));
+    assert_eq!(string.distinct_count, Some(3));
+    assert_eq!(string.distinct_capped, Some(false));
+    // another string and stringy string
+    assert_eq!(string.max_length, Some(14));
+    assert!(string.min.is_none());
+
+    let char = column(&summary, "_char");
+    assert_eq!(char.count + char.missing_count, 3);
+    assert_eq!(char.max_length, Some(1));
+}
+
+#[test]
+fn distinct_strings_are_counted_up_to_the_cap() {
+    let summary = summarize(2);
+
+    let string = column(&summary, "_string");
+    assert_eq!(string.distinct_count, Some(2));
+    assert_eq!(string.distinct_capped, Some(true));
+    // every value is still counted
+    assert_eq!(string.count, 3);
+}
+
+#[test]
+fn summary_of_dates_times_and_datetimes() {
+    let summary = summarize(DISTINCT_CAP);
+
+    let date = column(&summary, "_date");
+    assert_eq!(date.kind, ReadStatSummaryKind::Date);
+    assert_eq!(date.earliest.as_deref(), Some("2014-05-22"));
+    assert_eq!(date.latest.as_deref(), Some("2021-06-01"));
+    assert!(date.min.is_none());
+
+    let datetime = column(&summary, "_datetime");
+    assert_eq!(datetime.kind, ReadStatSummaryKind::Datetime);
+    assert_eq!((datetime.count, datetime.missing_count), (2, 1));
+    assert_eq!(datetime.earliest.as_deref(), Some("2021-01-01 10:49:39"));
+    assert_eq!(datetime.latest.as_deref(), Some("2021-06-01 13:42:25"));
+
+    let with_ms = column(&summary, "_datetime_with_ms");
+    assert_eq!(with_ms.earliest.as_deref(), Some("2021-01-01 10:49:39.333"));
+    assert_eq!(with_ms.latest.as_deref(), Some("2021-06-01 13:42:25.943"));
+
+    let time = column(&summary, "_time");
+    assert_eq!(time.kind, ReadStatSummaryKind::Time);
+    assert_eq!(time.earliest.as_deref(), Some("02:14:13"));
+    assert_eq!(time.latest.as_deref(), Some("19:54:42"));
+}
+
+#[test]
+fn summary_as_table() {
+    let table = summarize(DISTINCT_CAP).to_table();
+    let lines: Vec<&str> = table.lines().collect();
+
+    // header, dashes, and a line for each column
+    assert_eq!(lines.len(), 10);
+    assert!(lines[0].starts_with("Variable"));
+    assert!(lines[2].starts_with("_int"));
+    assert!(lines[2].contains("1234"));
+    assert!(lines[2].contains("2900.5"));
+    assert!(lines.iter().any(|l| l.contains("2014-05-22")));
+}
+
+#[test]
+fn cli_summary() {
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("summary").arg("tests/data/all_types.sas7bdat");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Max length"))
+        .stdout(predicate::str::contains("19:54:42"));
+
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("summary")
+        .arg("tests/data/all_types.sas7bdat")
+        .arg("--as-json");
+    let out = cmd.assert().success().get_output().stdout.clone();
+
+    let summary: Value = serde_json::from_slice(&out).unwrap();
+    let int = &summary["columns"][0];
+    assert_eq!(int["var_name"], "_int");
+    assert_eq!(int["kind"], "numeric");
+    assert_eq!(int["missing_count"], 1);
+    assert_eq!(int["max"], 4567.0);
+    // statistics of other kinds are left out
+    assert!(int.get("earliest").is_none());
+}
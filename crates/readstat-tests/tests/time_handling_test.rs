@@ -0,0 +1,135 @@
+use arrow2::{
+    array::{Int32Array, Int64Array, PrimitiveArray},
+    datatypes::{DataType, TimeUnit},
+    types::NativeType,
+};
+use assert_cmd::Command;
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert_collect, sas_seconds_to_duration, OutputMode, ReadStatCollected,
+    ReadStatConvertOptions, ReadStatMetadata, ReadStatTypeReason, TimeHandling,
+};
+
+mod common;
+
+// durations.xpt holds id, elapsed (TIME8.), and lap (TIME12.3)
+const ELAPSED: usize = 1;
+const LAP: usize = 2;
+
+fn collect(time_handling: TimeHandling) -> ReadStatCollected {
+    let rsp = common::setup_path("durations.xpt").unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        time_handling,
+        ..ReadStatConvertOptions::default()
+    };
+    convert_collect(&rsp, &options).unwrap()
+}
+
+fn column<T: NativeType>(c: &ReadStatCollected, i: usize) -> Vec<Option<T>> {
+    let col = c.chunks[0].arrays()[i]
+        .as_any()
+        .downcast_ref::<PrimitiveArray<T>>()
+        .unwrap();
+    col.iter().map(|v| v.copied()).collect()
+}
+
+#[test]
+fn times_beyond_a_day_are_null_with_a_warning_by_default() {
+    let c = collect(TimeHandling::Time32);
+    assert_eq!(c.rows, 4);
+    assert_eq!(c.schema.fields[ELAPSED].data_type(), &DataType::Time32(TimeUnit::Second));
+    assert_eq!(c.schema.fields[LAP].data_type(), &DataType::Time64(TimeUnit::Microsecond));
+
+    // 09:30:00 is kept; 26:30:00 and -01:00:00 are not wrapped into a day
+    assert!(c.chunks[0].arrays()[ELAPSED].as_any().is::<Int32Array>());
+    assert_eq!(column::<i32>(&c, ELAPSED), vec![Some(34_200), None, None, None]);
+    assert_eq!(column::<i64>(&c, LAP), vec![Some(34_200_250_000), None, None, None]);
+
+    let warned: Vec<(usize, &str, f64)> = c
+        .date_range_warnings
+        .iter()
+        .map(|w| (w.row, w.column.as_str(), w.value))
+        .collect();
+    assert_eq!(
+        warned,
+        vec![
+            (1, "elapsed", 95_400.0),
+            (1, "lap", 95_400.5),
+            (2, "elapsed", -3_600.0),
+            (2, "lap", -3_600.125),
+        ]
+    );
+}
+
+#[test]
+fn times_are_kept_exactly_as_durations() {
+    let c = collect(TimeHandling::DurationSeconds);
+    assert!(c.date_range_warnings.is_empty());
+
+    let elapsed = &c.schema.fields[ELAPSED];
+    assert_eq!(elapsed.data_type(), &DataType::Int64);
+    assert_eq!(elapsed.metadata.get("duration_unit").unwrap(), "second");
+    let lap = &c.schema.fields[LAP];
+    assert_eq!(lap.data_type(), &DataType::Int64);
+    assert_eq!(lap.metadata.get("duration_unit").unwrap(), "microsecond");
+    // other numerics are untouched
+    assert!(c.schema.fields[0].metadata.is_empty());
+
+    assert!(c.chunks[0].arrays()[ELAPSED].as_any().is::<Int64Array>());
+    assert_eq!(column::<i64>(&c, ELAPSED), vec![Some(34_200), Some(95_400), Some(-3_600), None]);
+    assert_eq!(
+        column::<i64>(&c, LAP),
+        vec![Some(34_200_250_000), Some(95_400_500_000), Some(-3_600_125_000), None]
+    );
+}
+
+#[test]
+fn durations_are_recorded_as_a_type_decision() {
+    let rsp = common::setup_path("durations.xpt").unwrap();
+    let mut md = ReadStatMetadata::new().set_time_handling(TimeHandling::DurationSeconds);
+    md.read_metadata(&rsp, false).unwrap();
+
+    assert_eq!(md.type_decisions[&1].data_type, DataType::Int64);
+    assert_eq!(md.type_decisions[&1].reason, ReadStatTypeReason::UserOverride);
+    assert_eq!(md.type_decisions[&2].data_type, DataType::Int64);
+
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    assert_eq!(md.type_decisions[&1].reason, ReadStatTypeReason::FormatClassMapping);
+}
+
+#[test]
+fn durations_exceed_a_day_and_may_be_negative() {
+    assert_eq!(sas_seconds_to_duration(95_400.9, TimeUnit::Second), Some(95_400));
+    assert_eq!(sas_seconds_to_duration(-0.5, TimeUnit::Second), Some(-1));
+    assert_eq!(sas_seconds_to_duration(-3_600.125, TimeUnit::Millisecond), Some(-3_600_125));
+    assert_eq!(
+        sas_seconds_to_duration(3_661.123_456_7, TimeUnit::Microsecond),
+        Some(3_661_123_457)
+    );
+    for v in [f64::NAN, f64::INFINITY, 1e300] {
+        assert_eq!(sas_seconds_to_duration(v, TimeUnit::Second), None, "{}", v);
+    }
+}
+
+#[test]
+fn cli_writes_durations_as_seconds() {
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("durations.csv");
+
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("data")
+        .arg("tests/data/durations.xpt")
+        .arg("--output")
+        .arg(&out_path)
+        .args(["--time-handling", "duration-seconds"])
+        .arg("--no-progress");
+    cmd.assert().success();
+
+    let csv = std::fs::read_to_string(&out_path).unwrap();
+    let rows: Vec<&str> = csv.lines().skip(1).map(|l| l.split(',').nth(1).unwrap()).collect();
+    assert_eq!(rows, vec!["34200", "95400", "-3600", ""]);
+
+    out_dir.close().unwrap();
+}
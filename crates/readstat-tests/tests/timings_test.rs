@@ -0,0 +1,71 @@
+use readstat::prelude::{
+    convert_collect, convert_for_each, OutputMode, ReadStatBatchTiming, ReadStatConvertOptions,
+};
+use std::{thread, time::Duration};
+
+mod common;
+
+fn options() -> ReadStatConvertOptions {
+    ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        stream_rows: 100,
+        channel_depth: 1,
+        ..ReadStatConvertOptions::default()
+    }
+}
+
+#[test]
+fn every_batch_is_timed() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let c = convert_collect(&rsp, &options()).unwrap();
+
+    // 1081 rows in batches of 100, in file order
+    assert_eq!(c.timings.len(), 11);
+    for (i, t) in c.timings.iter().enumerate() {
+        assert_eq!(t.row_start, i * 100);
+        assert_eq!(t.rows, if i < 10 { 100 } else { 81 });
+    }
+    assert_eq!(ReadStatBatchTiming::total(&c.timings).rows, 1081);
+}
+
+#[test]
+fn slow_consumer_blocks_the_reader() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let delay = Duration::from_millis(50);
+
+    let mut seen = 0;
+    let c = convert_for_each(&rsp, &options(), |chunk| {
+        seen += chunk.len();
+        thread::sleep(delay);
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(seen, 1081);
+
+    // each batch spent at least the delay within the closure
+    assert!(c.timings.iter().all(|t| t.consume >= delay));
+
+    // the reader spent longer waiting on the closure than parsing
+    let total = ReadStatBatchTiming::total(&c.timings);
+    assert!(
+        total.blocked > total.parse,
+        "blocked for {:?} yet parsed in {:?}",
+        total.blocked,
+        total.parse
+    );
+    assert!(total.blocked >= delay * 5);
+}
+
+#[test]
+fn error_within_closure_stops_conversion() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+
+    let mut calls = 0;
+    let result = convert_for_each(&rsp, &options(), |_| {
+        calls += 1;
+        Err(From::from("consumer failed"))
+    });
+
+    assert_eq!(result.unwrap_err().to_string(), "consumer failed");
+    assert_eq!(calls, 1);
+}
@@ -0,0 +1,141 @@
+use arrow2::array::Utf8Array;
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert, convert_collect, MemLimitAction, OutFormat, OutputMode, ReadStatConvertOptions,
+    ReadStatMeasure, ReadStatMetadata, ReadStatPath, ReadStatStringNormalize, Reader,
+};
+use std::{collections::BTreeSet, path::Path};
+
+mod common;
+
+fn cars_metadata() -> ReadStatMetadata {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    md
+}
+
+fn measure_cars(stream_rows: u32) -> ReadStatMeasure {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let md = cars_metadata();
+    ReadStatMeasure::scan(
+        &rsp,
+        &md,
+        &ReadStatStringNormalize::default(),
+        &BTreeSet::new(),
+        md.row_count as u32,
+        stream_rows,
+    )
+    .unwrap()
+}
+
+fn convert_cars(out_path: &Path, format: OutFormat, reader: Reader, two_pass: bool) -> Vec<u8> {
+    let rsp = ReadStatPath::new(
+        common::setup_path("cars.sas7bdat").unwrap().path,
+        Some(out_path.to_path_buf()),
+        Some(format),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let options = ReadStatConvertOptions {
+        reader,
+        stream_rows: 100,
+        two_pass,
+        ..ReadStatConvertOptions::default()
+    };
+    convert(&rsp, &options).unwrap();
+
+    std::fs::read(out_path).unwrap()
+}
+
+#[test]
+fn output_is_identical_to_a_single_pass() {
+    let out_dir = TempDir::new().unwrap();
+
+    for (name, format) in [("cars.csv", OutFormat::csv), ("cars.ndjson", OutFormat::ndjson)] {
+        let out_path = out_dir.path().join(name);
+        let single = convert_cars(&out_path, format, Reader::stream, false);
+
+        for reader in [Reader::stream, Reader::mem] {
+            assert_eq!(convert_cars(&out_path, format, reader, true), single);
+        }
+    }
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn measured_string_bytes_match_the_arrays() {
+    let measure = measure_cars(100);
+    assert_eq!(measure.rows(), 1081);
+    assert_eq!(measure.batches.len(), 11);
+
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        ..ReadStatConvertOptions::default()
+    };
+    let c = convert_collect(&rsp, &options).unwrap();
+
+    // bytes of each string column, within the single chunk of 1081 rows
+    let chunk = &c.chunks[0];
+    let bytes = measure.string_bytes_between(0, 1081);
+    let mut strings = 0;
+    for (i, array) in chunk.arrays().iter().enumerate() {
+        match array.as_any().downcast_ref::<Utf8Array<i32>>() {
+            Some(a) => {
+                assert_eq!(bytes[i], a.values().len());
+                strings += 1;
+            }
+            None => assert_eq!(bytes[i], 0),
+        }
+    }
+    assert!(strings > 0);
+
+    // batches sum to the whole, whatever the rows measured at a time
+    assert_eq!(measure_cars(1000).string_bytes_between(0, 1081), bytes);
+    let halves: Vec<usize> = measure
+        .string_bytes_between(0, 500)
+        .iter()
+        .zip(measure.string_bytes_between(500, 1081).iter())
+        .map(|(a, b)| a + b)
+        .collect();
+    assert_eq!(halves, bytes);
+}
+
+#[test]
+fn mem_limit_checked_against_measured_bytes() {
+    let measure = measure_cars(100);
+    let md = cars_metadata();
+
+    // strings are measured rather than taken from their widths
+    let measured = measure.memory_bytes(&md, 0, 1081);
+    assert!(measured > 0);
+    assert_ne!(measured, md.estimate_memory_bytes(1081));
+    assert!(measure.memory_bytes(&md, 0, 500) < measured);
+
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        reader: Reader::mem,
+        mem_limit: Some(1024),
+        two_pass: true,
+        ..ReadStatConvertOptions::default()
+    };
+
+    let e = convert_collect(&rsp, &options).unwrap_err().to_string();
+    assert!(e.contains("is measured to use"));
+
+    // streamed instead, with the warning returned
+    let options = ReadStatConvertOptions {
+        mem_limit_action: MemLimitAction::Stream,
+        ..options
+    };
+    let c = convert_collect(&rsp, &options).unwrap();
+    assert_eq!(c.rows, 1081);
+    assert!(c.warnings.iter().any(|w| w.contains("streaming instead")));
+}
@@ -0,0 +1,47 @@
+use readstat::prelude::{DateTimeType, ReadStatMetadata, ReadStatTypeReason};
+
+mod common;
+
+const GOLDEN: &str = "\
+Variable           SAS type  Format      Format class  Arrow type                    Reason
+-----------------  --------  ----------  ------------  ----------------------------  --------------------
+_int               Double    BEST12      -             Float64                       default mapping
+_float             Double    BEST12      -             Float64                       default mapping
+_char              String    $1          -             Utf8                          default mapping
+_string            String    $30         -             Utf8                          default mapping
+_date              Double    YYMMDD10    Date          Date32                        format-class mapping
+_datetime          Double    DATETIME22  DateTime      Timestamp(Millisecond, None)  format-class mapping
+_datetime_with_ms  Double    DATETIME22  DateTime      Timestamp(Millisecond, None)  format-class mapping
+_time              Double    TIME        Time          Time32(Second)                format-class mapping
+";
+
+#[test]
+fn type_report_golden() {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+
+    assert_eq!(md.type_report(), GOLDEN);
+}
+
+#[test]
+fn type_report_user_override() {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+
+    let mut md =
+        ReadStatMetadata::new().set_datetime_type(Some(DateTimeType::TimestampMs));
+    md.read_metadata(&rsp, false).unwrap();
+
+    // decisions drive the schema
+    for (k, td) in md.type_decisions.iter() {
+        assert_eq!(md.schema.fields[*k as usize].data_type(), &td.data_type);
+    }
+
+    // datetimes are overridden; dates and times are not
+    assert_eq!(md.type_decisions[&4].reason, ReadStatTypeReason::FormatClassMapping);
+    assert_eq!(md.type_decisions[&5].reason, ReadStatTypeReason::UserOverride);
+    assert_eq!(md.type_decisions[&6].reason, ReadStatTypeReason::UserOverride);
+    assert_eq!(md.type_decisions[&7].reason, ReadStatTypeReason::FormatClassMapping);
+    assert!(md.type_report().contains("Timestamp(Millisecond, None)  user override"));
+}
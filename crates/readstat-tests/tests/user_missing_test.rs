@@ -0,0 +1,186 @@
+use arrow2::array::{Float64Array, Utf8Array};
+use assert_cmd::Command;
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert_collect, OutputMode, ReadStatConvertOptions, ReadStatData, ReadStatMetadata,
+    ReadStatMissingRange, UserMissing,
+};
+use serde_json::Value;
+
+mod common;
+
+// user_missing.sav holds id, q1, age, income, and answer
+const Q1: usize = 1;
+const AGE: usize = 2;
+const INCOME: usize = 3;
+const ANSWER: usize = 4;
+
+fn metadata() -> ReadStatMetadata {
+    let rsp = common::setup_path("user_missing.sav").unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    md
+}
+
+fn read(user_missing: UserMissing) -> ReadStatData {
+    let rsp = common::setup_path("user_missing.sav").unwrap();
+    let md = metadata();
+    let rows = md.row_count as u32;
+    let mut d = ReadStatData::new()
+        .set_no_progress(true)
+        .set_user_missing(user_missing)
+        .init(md, 0, rows);
+    d.read_data(&rsp).unwrap();
+    d
+}
+
+fn numbers(d: &ReadStatData, i: usize) -> Vec<Option<f64>> {
+    let col = d.chunk().unwrap().arrays()[i]
+        .as_any()
+        .downcast_ref::<Float64Array>()
+        .unwrap();
+    col.iter().map(|v| v.copied()).collect()
+}
+
+fn strings(d: &ReadStatData, i: usize) -> Vec<Option<&str>> {
+    let col = d.chunk().unwrap().arrays()[i]
+        .as_any()
+        .downcast_ref::<Utf8Array<i32>>()
+        .unwrap();
+    col.iter().collect()
+}
+
+fn discrete(v: f64) -> ReadStatMissingRange {
+    ReadStatMissingRange::Numeric {
+        lo: Some(v),
+        hi: Some(v),
+    }
+}
+
+fn string(value: &str) -> ReadStatMissingRange {
+    ReadStatMissingRange::String {
+        value: String::from(value),
+    }
+}
+
+#[test]
+fn missing_values_are_recorded_for_each_variable() {
+    let md = metadata();
+    let ranges = |k: i32| &md.vars[&k].var_missing_ranges;
+
+    assert!(ranges(0).is_empty());
+    // the maximum of three discrete values
+    assert_eq!(ranges(1), &vec![discrete(7.0), discrete(8.0), discrete(9.0)]);
+    // open-ended ranges, the second along with a discrete value
+    assert_eq!(
+        ranges(2),
+        &vec![ReadStatMissingRange::Numeric {
+            lo: Some(120.0),
+            hi: None,
+        }]
+    );
+    assert_eq!(
+        ranges(3),
+        &vec![
+            ReadStatMissingRange::Numeric {
+                lo: None,
+                hi: Some(-1.0),
+            },
+            discrete(99_999.0),
+        ]
+    );
+    assert_eq!(ranges(4), &vec![string("NA"), string("REFUSED")]);
+
+    let shown: Vec<String> = ranges(3).iter().map(|r| r.to_string()).collect();
+    assert_eq!(shown, vec!["LO THRU -1", "99999"]);
+    assert_eq!(ranges(2)[0].to_string(), "120 THRU HI");
+    assert_eq!(ranges(4)[1].to_string(), "\"REFUSED\"");
+}
+
+#[test]
+fn missing_values_are_within_the_metadata_json() {
+    let v: Value = serde_json::from_str(&metadata().to_json().unwrap()).unwrap();
+
+    assert!(v["vars"]["0"].get("var_missing_ranges").is_none());
+    assert_eq!(v["vars"]["1"]["var_missing_ranges"][2]["lo"], 9.0);
+    assert_eq!(v["vars"]["2"]["var_missing_ranges"][0]["hi"], Value::Null);
+    assert_eq!(v["vars"]["3"]["var_missing_ranges"][0]["lo"], Value::Null);
+    assert_eq!(v["vars"]["3"]["var_missing_ranges"][0]["hi"], -1.0);
+    assert_eq!(v["vars"]["4"]["var_missing_ranges"][0]["value"], "NA");
+}
+
+#[test]
+fn missing_values_are_kept_as_values_by_default() {
+    let d = read(UserMissing::AsValue);
+
+    assert_eq!(numbers(&d, Q1), vec![Some(1.0), Some(9.0), Some(7.0), None, Some(3.0)]);
+    assert_eq!(
+        numbers(&d, AGE),
+        vec![Some(34.0), Some(51.0), Some(150.0), Some(120.0), Some(29.0)]
+    );
+    assert_eq!(
+        numbers(&d, INCOME),
+        vec![Some(52_000.0), Some(-1.0), Some(99_999.0), Some(-5.5), Some(0.0)]
+    );
+    assert_eq!(
+        strings(&d, ANSWER),
+        vec![Some("yes"), Some("NA"), Some("no"), Some("REFUSED"), Some("maybe")]
+    );
+}
+
+#[test]
+fn missing_values_are_read_as_null_when_asked() {
+    let d = read(UserMissing::AsNull);
+
+    assert_eq!(numbers(&d, Q1), vec![Some(1.0), None, None, None, Some(3.0)]);
+    assert_eq!(numbers(&d, AGE), vec![Some(34.0), Some(51.0), None, None, Some(29.0)]);
+    assert_eq!(numbers(&d, INCOME), vec![Some(52_000.0), None, None, None, Some(0.0)]);
+    assert_eq!(strings(&d, ANSWER), vec![Some("yes"), None, Some("no"), None, Some("maybe")]);
+    // values outside of the missing values are untouched
+    assert_eq!(numbers(&d, 0), vec![Some(1.0), Some(2.0), Some(3.0), Some(4.0), Some(5.0)]);
+}
+
+#[test]
+fn conversions_read_missing_values_as_null_when_asked() {
+    let rsp = common::setup_path("user_missing.sav").unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        user_missing: UserMissing::AsNull,
+        ..ReadStatConvertOptions::default()
+    };
+    let c = convert_collect(&rsp, &options).unwrap();
+
+    let q1 = c.chunks[0].arrays()[Q1]
+        .as_any()
+        .downcast_ref::<Float64Array>()
+        .unwrap();
+    assert_eq!(q1.null_count(), 3);
+}
+
+#[test]
+fn cli_displays_and_applies_missing_values() {
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("metadata").arg("tests/data/user_missing.sav");
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let stdout = String::from_utf8(output).unwrap();
+    assert!(stdout.contains("LO THRU -1, 99999"), "{}", stdout);
+    assert!(stdout.contains("120 THRU HI"), "{}", stdout);
+
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("user_missing.csv");
+    let mut cmd = Command::cargo_bin("readstat").unwrap();
+    cmd.arg("data")
+        .arg("tests/data/user_missing.sav")
+        .arg("--output")
+        .arg(&out_path)
+        .args(["--user-missing", "as-null"])
+        .arg("--no-progress");
+    cmd.assert().success();
+
+    let csv = std::fs::read_to_string(&out_path).unwrap();
+    let answers: Vec<&str> =
+        csv.lines().skip(1).map(|l| l.split(',').nth(ANSWER).unwrap()).collect();
+    assert_eq!(answers, vec!["yes", "", "no", "", "maybe"]);
+
+    out_dir.close().unwrap();
+}
@@ -0,0 +1,122 @@
+use arrow2::{
+    array::{Array, Float64Array, Utf8Array},
+    chunk::Chunk,
+    datatypes::DataType,
+};
+use readstat::prelude::{
+    ReadStatData, ReadStatFormatSpec, ReadStatLabelSet, ReadStatLabelValue, ReadStatMetadata,
+    ReadStatPath, ReadStatTypeReason,
+};
+use std::collections::BTreeMap;
+
+mod common;
+
+// value labels for Brand (a string) and Hybrid (a numeric); 0 is left unlabelled for Hybrid
+fn sets() -> BTreeMap<String, ReadStatLabelSet> {
+    let mut brands = ReadStatLabelSet::default();
+    brands.insert(
+        ReadStatLabelValue::String(String::from("TOYOTA")),
+        String::from("Toyota"),
+    );
+    let mut yes = ReadStatLabelSet::default();
+    yes.insert(ReadStatLabelValue::Numeric(1.0), String::from("Yes"));
+
+    BTreeMap::from([(String::from("BRANDS"), brands), (String::from("YES"), yes)])
+}
+
+// cars, with label sets named for Brand (0) and Hybrid (12) as a format catalog would hold them
+fn init(apply: bool) -> (ReadStatPath, ReadStatMetadata) {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let mut md = ReadStatMetadata::new().set_apply_value_labels(apply);
+    md.read_metadata(&rsp, false).unwrap();
+    md.vars.get_mut(&0).unwrap().var_label_set = Some(String::from("BRANDS"));
+    md.vars.get_mut(&12).unwrap().var_label_set = Some(String::from("yes"));
+    md.set_value_labels(sets());
+    (rsp, md)
+}
+
+fn read(rsp: &ReadStatPath, md: ReadStatMetadata) -> Chunk<Box<dyn Array>> {
+    let rows = md.row_count as u32;
+    let mut d = ReadStatData::new().set_no_progress(true).init(md, 0, rows);
+    d.read_data(rsp).unwrap();
+    d.into_chunk().unwrap()
+}
+
+fn strings(chunk: &Chunk<Box<dyn Array>>, i: usize) -> Vec<Option<String>> {
+    chunk.arrays()[i]
+        .as_any()
+        .downcast_ref::<Utf8Array<i32>>()
+        .unwrap()
+        .iter()
+        .map(|v| v.map(String::from))
+        .collect()
+}
+
+#[test]
+fn label_sets_are_matched_to_variables() {
+    let (_, md) = init(false);
+
+    // names ignore case
+    assert_eq!(md.label_set(0).map(|(n, _)| n), Some("BRANDS"));
+    assert_eq!(md.label_set(12).map(|(n, _)| n), Some("YES"));
+    assert!(md.label_set(1).is_none());
+
+    // without applying labels, types are unchanged
+    assert_eq!(md.schema.fields[12].data_type(), &DataType::Float64);
+    assert!(md.labelled.is_empty());
+}
+
+#[test]
+fn label_sets_missing_from_the_catalog() {
+    let (_, mut md) = init(true);
+    assert!(md.missing_label_sets().is_empty());
+
+    // a label set, a user-defined format, and a format supplied with SAS
+    md.vars.get_mut(&1).unwrap().var_label_set = Some(String::from("MODELS"));
+    md.vars.get_mut(&2).unwrap().var_format_parts = ReadStatFormatSpec::parse("MINIVANF.");
+    md.vars.get_mut(&3).unwrap().var_format_parts = ReadStatFormatSpec::parse("BEST12.");
+    assert_eq!(md.missing_label_sets(), vec![(1, "MODELS"), (2, "MINIVANF")]);
+
+    // only selected variables are expected to be labelled
+    md.set_columns(&[String::from("Brand"), String::from("Minivan")]).unwrap();
+    assert_eq!(md.missing_label_sets(), vec![(2, "MINIVANF")]);
+}
+
+#[test]
+fn applied_labels_replace_values() {
+    let (rsp, md) = init(true);
+    assert_eq!(md.schema.fields[0].data_type(), &DataType::Utf8);
+    assert_eq!(md.schema.fields[12].data_type(), &DataType::Utf8);
+    assert!(matches!(
+        md.type_decisions.get(&12).unwrap().reason,
+        ReadStatTypeReason::ValueLabels
+    ));
+
+    let (plain_rsp, plain_md) = init(false);
+    let plain = read(&plain_rsp, plain_md);
+    let labelled = read(&rsp, md);
+
+    // strings are compared without their padding; unlabelled strings are unchanged
+    for (p, l) in strings(&plain, 0).iter().zip(strings(&labelled, 0).iter()) {
+        match p.as_deref().map(str::trim_end) {
+            Some("TOYOTA") => assert_eq!(l.as_deref(), Some("Toyota")),
+            _ => assert_eq!(l, p),
+        }
+    }
+
+    // numbers without a label are written as their text rather than as null
+    let hybrid = plain.arrays()[12]
+        .as_any()
+        .downcast_ref::<Float64Array>()
+        .unwrap();
+    let labels = strings(&labelled, 12);
+    assert!(labels.iter().any(|l| l.as_deref() == Some("Yes")));
+    for (p, l) in hybrid.iter().zip(labels.iter()) {
+        match p {
+            Some(v) if *v == 1.0 => assert_eq!(l.as_deref(), Some("Yes")),
+            Some(v) if *v == 0.0 => assert_eq!(l.as_deref(), Some("0")),
+            Some(_) => assert!(l.is_some()),
+            None => assert!(l.is_none()),
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use readstat::prelude::{NameTransform, ReadStatMetadata};
+
+mod common;
+
+const ALL_TYPES_ORDER: [&str; 8] = [
+    "_int",
+    "_float",
+    "_char",
+    "_string",
+    "_date",
+    "_datetime",
+    "_datetime_with_ms",
+    "_time",
+];
+
+fn all_types_metadata() -> ReadStatMetadata {
+    let rsp = common::setup_path("all_types.sas7bdat").unwrap();
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false).unwrap();
+    md
+}
+
+fn expected() -> Vec<(usize, String)> {
+    ALL_TYPES_ORDER
+        .iter()
+        .enumerate()
+        .map(|(i, n)| (i, String::from(*n)))
+        .collect()
+}
+
+#[test]
+fn variable_order_matches_file() {
+    let md = all_types_metadata();
+
+    assert_eq!(md.variable_order(), expected());
+}
+
+#[test]
+fn variable_order_ignores_renames() {
+    let mut md = all_types_metadata();
+    md.transform_names(NameTransform::Uppercase).unwrap();
+    md.rename_vars(&[(String::from("_int"), String::from("id"))]).unwrap();
+
+    assert_eq!(md.schema.fields[0].name, "id");
+    assert_eq!(md.variable_order(), expected());
+}
+
+#[test]
+fn variable_order_within_json() {
+    let md = all_types_metadata();
+    let json: serde_json::Value = serde_json::to_value(&md).unwrap();
+
+    let order = json["variable_order"].as_array().unwrap();
+    assert_eq!(order.len(), 8);
+    assert_eq!(order[0], serde_json::json!([0, "_int"]));
+    assert_eq!(order[7], serde_json::json!([7, "_time"]));
+}
@@ -0,0 +1,40 @@
+use readstat::prelude::{readstat_version, ReadStatVersionInfo};
+
+mod common;
+
+#[test]
+fn readstat_version_is_not_empty() {
+    assert!(!readstat_version().is_empty());
+}
+
+#[test]
+fn version_info_reflects_build() {
+    let info = ReadStatVersionInfo::new();
+
+    // versions
+    assert!(!info.crate_version.is_empty());
+    assert!(!info.readstat_version.is_empty());
+    assert!(!info.arrow2_version.is_empty());
+
+    // formats
+    assert_eq!(
+        info.input_formats,
+        vec!["sas7bdat", "sas7bcat", "xpt", "sav", "dta"]
+    );
+    assert_eq!(
+        info.output_formats,
+        vec!["arrow", "csv", "feather", "ndjson", "parquet", "sav", "dta"]
+    );
+    assert!(info.parquet_compression.contains(&String::from("zstd")));
+
+    // features
+    assert!(info.features.contains(&String::from("arrow2/io_parquet")));
+    assert!(info.features.contains(&String::from("arrow2/io_csv")));
+
+    // linkage
+    let system = info.features.contains(&String::from("system-readstat"));
+    assert_eq!(
+        info.readstat_source,
+        if system { "system" } else { "vendored" }
+    );
+}
@@ -0,0 +1,98 @@
+use readstat::prelude::{
+    convert_collect, OutputMode, ReadStatConvertOptions, ReadStatWarningCategory,
+    ReadStatWarnings, WARNINGS_RETAINED,
+};
+
+mod common;
+
+fn lossy(warnings: &mut ReadStatWarnings, column: &str, rows: std::ops::Range<usize>) {
+    for row in rows {
+        warnings.record(ReadStatWarningCategory::LossyUtf8, Some(column), Some(row), || {
+            format!("The value of {} in row {} is not valid UTF-8", column, row)
+        });
+    }
+}
+
+#[test]
+fn lossy_events_are_counted_within_a_single_group() {
+    let mut warnings = ReadStatWarnings::new();
+    lossy(&mut warnings, "name", 0..10000);
+
+    assert_eq!(warnings.count(), 10000);
+    assert_eq!(warnings.groups().len(), 1);
+
+    let g = &warnings.groups()[0];
+    assert_eq!(g.category, ReadStatWarningCategory::LossyUtf8);
+    assert_eq!(g.column.as_deref(), Some("name"));
+    assert_eq!(g.count, 10000);
+    assert_eq!((g.first_row, g.last_row), (Some(0), Some(9999)));
+    assert!(g.example.contains("row 0 "));
+    assert!(g.line().contains("10,000"));
+
+    // only the first messages are kept
+    assert_eq!(warnings.retained.len(), WARNINGS_RETAINED);
+    assert_eq!(warnings.dropped, 10000 - WARNINGS_RETAINED);
+
+    // header, dashes, and a single line
+    let summary = warnings.summary();
+    let lines: Vec<&str> = summary.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[2].starts_with("lossy utf-8"));
+    assert!(lines[2].contains("10000"));
+}
+
+#[test]
+fn merged_batches_keep_groups_in_order() {
+    // batches read in parallel arrive out of order
+    let mut later = ReadStatWarnings::new().set_retain(5);
+    lossy(&mut later, "name", 500..1000);
+    lossy(&mut later, "city", 500..510);
+
+    let mut earlier = ReadStatWarnings::new().set_retain(5);
+    lossy(&mut earlier, "name", 0..500);
+    earlier.record_all(
+        ReadStatWarningCategory::Truncation,
+        &[String::from("The label of x was truncated")],
+    );
+
+    let mut warnings = ReadStatWarnings::new().set_retain(8);
+    warnings.merge(later);
+    warnings.merge(earlier);
+
+    let groups: Vec<(ReadStatWarningCategory, Option<&str>, usize)> = warnings
+        .groups()
+        .iter()
+        .map(|g| (g.category, g.column.as_deref(), g.count))
+        .collect();
+    assert_eq!(
+        groups,
+        vec![
+            (ReadStatWarningCategory::LossyUtf8, Some("city"), 10),
+            (ReadStatWarningCategory::LossyUtf8, Some("name"), 1000),
+            (ReadStatWarningCategory::Truncation, None, 1),
+        ]
+    );
+    assert_eq!(warnings.groups()[1].first_row, Some(0));
+    assert_eq!(warnings.groups()[1].last_row, Some(999));
+
+    assert_eq!(warnings.count(), 1011);
+    assert_eq!(warnings.retained.len(), 8);
+    assert_eq!(warnings.dropped, 1011 - 8);
+}
+
+#[test]
+fn clean_file_has_no_value_warnings() {
+    let rsp = common::setup_path("cars.sas7bdat").unwrap();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        ..ReadStatConvertOptions::default()
+    };
+    let c = convert_collect(&rsp, &options).unwrap();
+
+    assert!(c
+        .warning_summary
+        .groups()
+        .iter()
+        .all(|g| g.category != ReadStatWarningCategory::LossyUtf8));
+    assert_eq!(c.warning_summary.count(), c.warnings.len());
+}
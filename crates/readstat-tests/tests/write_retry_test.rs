@@ -0,0 +1,300 @@
+use arrow2::{
+    array::{Float64Array, Utf8Array},
+    chunk::Chunk,
+    datatypes::{DataType, Field, Schema},
+    io::parquet::read::read_metadata,
+};
+use assert_fs::TempDir;
+use readstat::prelude::{
+    convert, OutFormat, OutputWriter, ReadStatConvertOptions, ReadStatData, ReadStatPath,
+    ReadStatWriter,
+};
+use std::{
+    io::{self, Cursor, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+mod common;
+
+// Output held in memory, whose chosen writes fail after writing half of their bytes
+#[derive(Clone)]
+struct FlakyOutput {
+    bytes: Arc<Mutex<Vec<u8>>>,
+    writes: Arc<AtomicUsize>,
+    fail: Arc<Mutex<Vec<usize>>>,
+    error: fn() -> io::Error,
+    reopens: Arc<Mutex<Vec<u64>>>,
+}
+
+impl FlakyOutput {
+    fn new(error: fn() -> io::Error) -> Self {
+        Self {
+            bytes: Arc::new(Mutex::new(Vec::new())),
+            writes: Arc::new(AtomicUsize::new(0)),
+            fail: Arc::new(Mutex::new(Vec::new())),
+            error,
+            reopens: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    // counting from 1
+    fn fail_at(&self, writes: impl IntoIterator<Item = usize>) {
+        self.fail.lock().unwrap().extend(writes);
+    }
+
+    fn writes(&self) -> usize {
+        self.writes.load(Ordering::SeqCst)
+    }
+
+    fn bytes(&self) -> Vec<u8> {
+        self.bytes.lock().unwrap().clone()
+    }
+
+    fn reopens(&self) -> Vec<u64> {
+        self.reopens.lock().unwrap().clone()
+    }
+}
+
+impl Write for FlakyOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut bytes = self.bytes.lock().unwrap();
+        let n = self.writes.fetch_add(1, Ordering::SeqCst) + 1;
+        if self.fail.lock().unwrap().contains(&n) {
+            bytes.extend_from_slice(&buf[..buf.len() / 2]);
+            Err((self.error)())
+        } else {
+            bytes.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl OutputWriter for FlakyOutput {
+    fn reopen(&mut self, len: u64) -> io::Result<()> {
+        self.bytes.lock().unwrap().truncate(len as usize);
+        self.reopens.lock().unwrap().push(len);
+        Ok(())
+    }
+}
+
+// EIO
+fn eio() -> io::Error {
+    io::Error::from_raw_os_error(5)
+}
+
+fn permission_denied() -> io::Error {
+    io::Error::from(io::ErrorKind::PermissionDenied)
+}
+
+// input is only used to satisfy path validation; data is supplied directly
+fn rsp(out_path: &Path, format: OutFormat) -> ReadStatPath {
+    ReadStatPath::new(
+        common::setup_path("all_types.sas7bdat").unwrap().path,
+        Some(out_path.to_path_buf()),
+        Some(format),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap()
+}
+
+// three batches of two rows
+fn batches() -> Vec<ReadStatData> {
+    let schema = Schema::from(vec![
+        Field::new("name", DataType::Utf8, true),
+        Field::new("value", DataType::Float64, true),
+    ]);
+
+    (0..3)
+        .map(|b| {
+            let names: Vec<String> = (0..2).map(|i| format!("row {}", b * 2 + i)).collect();
+            let values: Vec<f64> = (0..2).map(|i| (b * 2 + i) as f64 * 1.5).collect();
+            let chunk = Chunk::new(vec![
+                Utf8Array::<i32>::from_slice(names).boxed(),
+                Float64Array::from_slice(values).boxed(),
+            ]);
+            ReadStatData::new().set_chunk(schema.clone(), chunk)
+        })
+        .collect()
+}
+
+fn writer(output: &FlakyOutput, retries: u32) -> ReadStatWriter {
+    ReadStatWriter::new()
+        .set_quiet(true)
+        .set_write_retries(retries, Duration::from_millis(1))
+        .set_output(Box::new(output.clone()))
+}
+
+// csv written without any failures
+fn clean_csv(rsp: &ReadStatPath) -> Vec<u8> {
+    let output = FlakyOutput::new(eio);
+    let mut wtr = writer(&output, 0);
+    let batches = batches();
+    for d in &batches {
+        wtr.write(d, rsp).unwrap();
+    }
+    wtr.finish(&batches[2], rsp).unwrap();
+    output.bytes()
+}
+
+#[test]
+fn csv_batch_is_rewritten_after_transient_failure() {
+    let out_dir = TempDir::new().unwrap();
+    let rsp = rsp(&out_dir.path().join("out.csv"), OutFormat::csv);
+
+    let output = FlakyOutput::new(eio);
+    let mut wtr = writer(&output, 3);
+    let batches = batches();
+
+    wtr.write(&batches[0], &rsp).unwrap();
+    let committed = output.bytes().len() as u64;
+
+    // the second batch is half written before failing
+    output.fail_at([output.writes() + 1]);
+    wtr.write(&batches[1], &rsp).unwrap();
+    wtr.write(&batches[2], &rsp).unwrap();
+    wtr.finish(&batches[2], &rsp).unwrap();
+
+    // re-opened where the first batch ended, so the partial write is not duplicated
+    assert_eq!(output.reopens(), vec![committed]);
+    assert_eq!(output.bytes(), clean_csv(&rsp));
+    assert_eq!(wtr.warnings.len(), 1);
+    assert!(wtr.warnings[0].contains("retry 1 of 3"));
+    assert!(!out_dir.path().join("out.csv").exists());
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn csv_first_batch_restarts_with_header() {
+    let out_dir = TempDir::new().unwrap();
+    let rsp = rsp(&out_dir.path().join("out.csv"), OutFormat::csv);
+
+    let output = FlakyOutput::new(eio);
+    let mut wtr = writer(&output, 1);
+    let batches = batches();
+
+    // the header is written, then the rows following it fail
+    output.fail_at([2]);
+    for d in &batches {
+        wtr.write(d, &rsp).unwrap();
+    }
+    wtr.finish(&batches[2], &rsp).unwrap();
+
+    assert_eq!(output.reopens(), vec![0]);
+    assert_eq!(output.bytes(), clean_csv(&rsp));
+    assert!(output.bytes().starts_with(b"name,value\n"));
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn parquet_restarts_when_no_batch_was_written() {
+    let out_dir = TempDir::new().unwrap();
+    let rsp = rsp(&out_dir.path().join("out.parquet"), OutFormat::parquet);
+
+    let output = FlakyOutput::new(eio);
+    let mut wtr = writer(&output, 2);
+    let batches = batches();
+
+    output.fail_at([1]);
+    for d in &batches {
+        wtr.write(d, &rsp).unwrap();
+    }
+    wtr.finish(&batches[2], &rsp).unwrap();
+
+    assert_eq!(output.reopens(), vec![0]);
+    assert_eq!(wtr.warnings.len(), 1);
+
+    // a whole file, holding every row once
+    let md = read_metadata(&mut Cursor::new(output.bytes())).unwrap();
+    assert_eq!(md.num_rows, 6);
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn parquet_fails_once_a_batch_was_written() {
+    let out_dir = TempDir::new().unwrap();
+    let rsp = rsp(&out_dir.path().join("out.parquet"), OutFormat::parquet);
+
+    let output = FlakyOutput::new(eio);
+    let mut wtr = writer(&output, 2);
+    let batches = batches();
+
+    wtr.write(&batches[0], &rsp).unwrap();
+    output.fail_at([output.writes() + 1]);
+    let e = wtr.write(&batches[1], &rsp).unwrap_err().to_string();
+
+    assert!(e.contains("may be transient"));
+    assert!(e.contains("parquet output cannot be resumed"));
+    assert!(output.reopens().is_empty());
+    assert!(wtr.warnings.is_empty());
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn retries_are_bounded_and_fatal_errors_not_retried() {
+    let out_dir = TempDir::new().unwrap();
+    let rsp = rsp(&out_dir.path().join("out.csv"), OutFormat::csv);
+    let batches = batches();
+
+    // transient, yet failing every time
+    let output = FlakyOutput::new(eio);
+    let mut wtr = writer(&output, 2);
+    wtr.write(&batches[0], &rsp).unwrap();
+    output.fail_at(output.writes() + 1..output.writes() + 100);
+    let e = wtr.write(&batches[1], &rsp).unwrap_err().to_string();
+    assert!(e.contains("failed after 2 retries"));
+    assert_eq!(wtr.warnings.len(), 2);
+    assert_eq!(output.reopens().len(), 2);
+
+    // fatal, so returned as is
+    let output = FlakyOutput::new(permission_denied);
+    let mut wtr = writer(&output, 2);
+    wtr.write(&batches[0], &rsp).unwrap();
+    output.fail_at([output.writes() + 1]);
+    let e = wtr.write(&batches[1], &rsp).unwrap_err().to_string();
+    assert_eq!(e, permission_denied().to_string());
+    assert!(wtr.warnings.is_empty());
+    assert!(output.reopens().is_empty());
+
+    out_dir.close().unwrap();
+}
+
+#[test]
+fn unwritable_output_fails_conversion() {
+    // a directory stands where the output would be written
+    let out_dir = TempDir::new().unwrap();
+    let out_path = out_dir.path().join("out.csv");
+    std::fs::create_dir(&out_path).unwrap();
+
+    let rsp = ReadStatPath::new(
+        common::setup_path("cars.sas7bdat").unwrap().path,
+        Some(out_path.clone()),
+        Some(OutFormat::csv),
+        true,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let result = convert(&rsp, &ReadStatConvertOptions::default());
+    assert!(result.is_err());
+    assert!(out_path.is_dir());
+
+    out_dir.close().unwrap();
+}
@@ -0,0 +1,64 @@
+"""Write absurd_dates.xpt, a SAS transport (version 5) file whose date and datetime columns hold
+values no real date has, as a damaged file or faulty writer leaves them
+
+SAS itself stores whatever number it is given, so the following SAS program writes the same file
+
+    libname out xport "absurd_dates.xpt";
+
+    data out.events(label="Events");
+      length id 8 happened 8 day 8;
+      format happened DATETIME22. day DATE9.;
+      input id happened day;
+      datalines;
+    1 1938174145 22432
+    2 1e70 -168011
+    3 316831824000 1e70
+    ;
+    run;
+
+Row 1 is 2021-06-01 13:42:25 and 2021-06-01.  Row 2 holds a datetime beyond the range of any
+timestamp and 1500-01-01, a date ahead of the default window.  Row 3 holds 12000-01-01 00:00:00,
+a datetime beyond the default window though within the range of a timestamp, and a date beyond
+the range of a date.
+"""
+
+import sys
+from datetime import date, datetime
+
+from create_scores_xpt import write
+
+SAS_EPOCH = date(1960, 1, 1)
+
+
+def sas_date(d):
+    return (d - SAS_EPOCH).days
+
+
+def sas_datetime(dt):
+    return int((dt - datetime(1960, 1, 1)).total_seconds())
+
+
+# (name, type (1 = numeric, 2 = character), length, label, (format, width, decimals))
+VARS = [
+    (b"id", 1, 8, b""),
+    (b"happened", 1, 8, b"", (b"DATETIME", 22, 0)),
+    (b"day", 1, 8, b"", (b"DATE", 9, 0)),
+]
+
+# Python dates end with the year 9999, yet every 400 years of the Gregorian calendar hold the same
+#   146,097 days, so 12000-01-01 is 25 such spans beyond 2000-01-01
+YEAR_12000 = sas_datetime(datetime(2000, 1, 1)) + 25 * 146_097 * 86_400
+
+ROWS = [
+    (1, sas_datetime(datetime(2021, 6, 1, 13, 42, 25)), sas_date(date(2021, 6, 1))),
+    (2, 1e70, sas_date(date(1500, 1, 1))),
+    (3, YEAR_12000, 1e70),
+]
+
+
+def main(path):
+    write(path, b"EVENTS", b"Events", VARS, ROWS)
+
+
+if __name__ == "__main__":
+    main(sys.argv[1] if len(sys.argv) > 1 else "absurd_dates.xpt")
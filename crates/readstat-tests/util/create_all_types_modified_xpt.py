@@ -0,0 +1,52 @@
+"""Write all_types_modified.xpt, a SAS transport (version 5) file holding the variables of
+all_types.sas7bdat with some removed, added, and altered, to test comparing schemas
+
+Equivalent to the following SAS program
+
+    libname out xport "all_types_modified.xpt";
+
+    data out.all_type;
+      length _int 8 _float 8 _string $40 _date 8 _time $8 _flag 8;
+      format _int best12. _float best12. _string $40. _date date9. _time $8. _flag best12.;
+      label _int = "Integer";
+      input _int _float _string $ _date _time $ _flag;
+      datalines;
+    1234 1234.5 string 22281 02:14:13 1
+    4567 4567.8 another 22432 19:54:42 0
+    ;
+    run;
+
+Relative to all_types.sas7bdat
+- _char is dropped, and _datetime and _datetime_with_ms are too long to name within a version 5
+  transport file
+- _flag is added
+- _int gains a label, _string is formatted $40, and _date is formatted DATE9
+- _time is stored as a character variable
+"""
+
+import sys
+
+from create_scores_xpt import write
+
+# (name, type (1 = numeric, 2 = character), length, label, (format, width, decimals))
+VARS = [
+    (b"_int", 1, 8, b"Integer", (b"BEST", 12, 0)),
+    (b"_float", 1, 8, b"", (b"BEST", 12, 0)),
+    (b"_string", 2, 40, b"", (b"$", 40, 0)),
+    (b"_date", 1, 8, b"", (b"DATE", 9, 0)),
+    (b"_time", 2, 8, b"", (b"$", 8, 0)),
+    (b"_flag", 1, 8, b"", (b"BEST", 12, 0)),
+]
+
+ROWS = [
+    (1234, 1234.5, b"string", 22281, b"02:14:13", 1),
+    (4567, 4567.8, b"another", 22432, b"19:54:42", 0),
+]
+
+
+def main(path):
+    write(path, b"ALL_TYPE", b"", VARS, ROWS)
+
+
+if __name__ == "__main__":
+    main(sys.argv[1] if len(sys.argv) > 1 else "all_types_modified.xpt")
@@ -0,0 +1,29 @@
+"""Write duplicate_names.xpt, a SAS transport (version 5) file in which two variables share a name
+
+SAS itself never writes such a file, though third-party tools may, e.g. once they truncate longer
+names to 8 characters.  Both amounts are named amount; the first is in USD and the second in EUR.
+"""
+
+import sys
+
+from create_scores_xpt import write
+
+# (name, type (1 = numeric, 2 = character), length, label)
+VARS = [
+    (b"id", 1, 8, b""),
+    (b"amount", 1, 8, b"Amount (USD)"),
+    (b"amount", 1, 8, b"Amount (EUR)"),
+]
+
+ROWS = [
+    (1, 10.5, 9.75),
+    (2, 4, 3.5),
+]
+
+
+def main(path):
+    write(path, b"AMOUNTS", b"Amounts", VARS, ROWS)
+
+
+if __name__ == "__main__":
+    main(sys.argv[1] if len(sys.argv) > 1 else "duplicate_names.xpt")
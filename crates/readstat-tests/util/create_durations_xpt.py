@@ -0,0 +1,48 @@
+"""Write durations.xpt, a SAS transport (version 5) file whose time columns hold elapsed durations
+of 24 hours or more and negative durations rather than times of day
+
+Equivalent to the following SAS program
+
+    libname out xport "durations.xpt";
+
+    data out.laps(label="Lap times");
+      length id 8 elapsed 8 lap 8;
+      format elapsed TIME8. lap TIME12.3;
+      input id elapsed lap;
+      datalines;
+    1 34200 34200.25
+    2 95400 95400.5
+    3 -3600 -3600.125
+    4 . .
+    ;
+    run;
+
+Row 1 is 09:30:00, a time of day.  Row 2 is 26:30:00 and row 3 is -01:00:00, which no time of
+day is.  Row 4 is missing.
+"""
+
+import sys
+
+from create_scores_xpt import write
+
+# (name, type (1 = numeric, 2 = character), length, label, (format, width, decimals))
+VARS = [
+    (b"id", 1, 8, b""),
+    (b"elapsed", 1, 8, b"Elapsed time", (b"TIME", 8, 0)),
+    (b"lap", 1, 8, b"Lap time", (b"TIME", 12, 3)),
+]
+
+ROWS = [
+    (1, 34_200, 34_200.25),
+    (2, 95_400, 95_400.5),
+    (3, -3_600, -3_600.125),
+    (4, None, None),
+]
+
+
+def main(path):
+    write(path, b"LAPS", b"Lap times", VARS, ROWS)
+
+
+if __name__ == "__main__":
+    main(sys.argv[1] if len(sys.argv) > 1 else "durations.xpt")
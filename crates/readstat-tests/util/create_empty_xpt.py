@@ -0,0 +1,28 @@
+"""Write no_rows.xpt and no_variables.xpt, SAS transport (version 5) files without observations
+and without variables respectively
+
+no_rows.xpt is equivalent to the following SAS program
+
+    libname out xport "no_rows.xpt";
+
+    data out.norows(label="No rows");
+      length id 8 name $8 score 8;
+      label name = "Student name" score = "Test score";
+      stop;
+    run;
+
+SAS itself never writes a dataset without variables, yet a transport file may describe one.
+"""
+
+import sys
+
+from create_scores_xpt import VARS, write
+
+
+def main(directory):
+    write("%s/no_rows.xpt" % directory, b"NOROWS", b"No rows", VARS, [])
+    write("%s/no_variables.xpt" % directory, b"NOVARS", b"No variables", [], [])
+
+
+if __name__ == "__main__":
+    main(sys.argv[1] if len(sys.argv) > 1 else ".")
@@ -0,0 +1,30 @@
+"""Write mixed_case_names.xpt, a SAS transport (version 5) file whose variable names mix case
+
+SAS ignores the case of names, so VarA and vara would be the same variable within SAS, yet a
+transport file may hold both; lowercasing or uppercasing every name makes them collide.
+"""
+
+import sys
+
+from create_scores_xpt import write
+
+# (name, type (1 = numeric, 2 = character), length, label)
+VARS = [
+    (b"VarA", 1, 8, b"First A"),
+    (b"vara", 1, 8, b"Second a"),
+    (b"TotalAmt", 1, 8, b"Total amount"),
+    (b"UNIT_PRC", 1, 8, b"Unit price"),
+]
+
+ROWS = [
+    (1, 2, 30.5, 7.25),
+    (3, 4, 12, 1.5),
+]
+
+
+def main(path):
+    write(path, b"MIXED", b"Mixed case names", VARS, ROWS)
+
+
+if __name__ == "__main__":
+    main(sys.argv[1] if len(sys.argv) > 1 else "mixed_case_names.xpt")
@@ -0,0 +1,53 @@
+"""Write sales_2023_01.xpt, sales_2023_02.xpt, and sales_2023_03.xpt, monthly extracts of the same
+dataset held as SAS transport (version 5) files
+
+Equivalent to the following SAS program, with the month of each extract in place of 01
+
+    libname out xport "sales_2023_01.xpt";
+
+    data out.sales(label="Sales for 2023-01");
+      length id 8 region $8 amount 8;
+      label region = "Sales region" amount = "Sale amount";
+      input id region $ amount;
+      datalines;
+    1 north 10.5
+    2 south .
+    ;
+    run;
+
+The February extract labels amount "Sale amount (USD)", so differs only in its labels.  The March
+extract stores amount as a character variable, so differs in the type of a column.
+"""
+
+import sys
+
+from create_scores_xpt import write
+
+# (name, type (1 = numeric, 2 = character), length, label)
+VARS = [
+    (b"id", 1, 8, b""),
+    (b"region", 2, 8, b"Sales region"),
+    (b"amount", 1, 8, b"Sale amount"),
+]
+
+EXTRACTS = {
+    b"01": (VARS, [(1, b"north", 10.5), (2, b"south", None)]),
+    b"02": (
+        VARS[:2] + [(b"amount", 1, 8, b"Sale amount (USD)")],
+        [(3, b"east", 7.25), (4, b"west", 12), (5, b"north", -3.5)],
+    ),
+    b"03": (
+        VARS[:2] + [(b"amount", 2, 8, b"Sale amount")],
+        [(6, b"south", b"8.75")],
+    ),
+}
+
+
+def main(directory):
+    for month, (variables, rows) in EXTRACTS.items():
+        path = "%s/sales_2023_%s.xpt" % (directory, month.decode())
+        write(path, b"SALES", b"Sales for 2023-" + month, variables, rows)
+
+
+if __name__ == "__main__":
+    main(sys.argv[1] if len(sys.argv) > 1 else ".")
@@ -0,0 +1,56 @@
+"""Write orders.xpt and orders_fractional.xpt, SAS transport (version 5) files whose numerics
+carry formats
+
+Equivalent to the following SAS program
+
+    libname out xport "orders.xpt";
+
+    data out.orders(label="Orders");
+      length id 8 quantity 8 price 8 weight 8;
+      format id F8. quantity COMMA6. price 8.2 weight BEST12.;
+      input id quantity price weight;
+      datalines;
+    1 3 19.99 0.5
+    2 1 5.25 1.75
+    3 . 7.5 2
+    4 12000 100 3.25
+    ;
+    run;
+
+Every id and quantity is a whole number, as their formats display; orders_fractional.xpt holds a
+single order whose quantity, 2.5, is not.
+"""
+
+import sys
+
+from create_scores_xpt import write
+
+# (name, type (1 = numeric, 2 = character), length, label, (format, width, decimals))
+VARS = [
+    (b"id", 1, 8, b"", (b"F", 8, 0)),
+    (b"quantity", 1, 8, b"", (b"COMMA", 6, 0)),
+    (b"price", 1, 8, b"", (b"", 8, 2)),
+    (b"weight", 1, 8, b"", (b"BEST", 12, 0)),
+]
+
+ROWS = [
+    (1, 3, 19.99, 0.5),
+    (2, 1, 5.25, 1.75),
+    (3, None, 7.5, 2),
+    (4, 12000, 100, 3.25),
+]
+
+
+def main(directory):
+    write("%s/orders.xpt" % directory, b"ORDERS", b"Orders", VARS, ROWS)
+    write(
+        "%s/orders_fractional.xpt" % directory,
+        b"ORDERS",
+        b"Orders",
+        VARS,
+        [(5, 2.5, 1, 1)],
+    )
+
+
+if __name__ == "__main__":
+    main(sys.argv[1] if len(sys.argv) > 1 else ".")
@@ -0,0 +1,47 @@
+"""Write rates.xpt, a SAS transport (version 5) file whose numerics carry formats that change how
+they are displayed
+
+Equivalent to the following SAS program
+
+    libname out xport "rates.xpt";
+
+    data out.rates(label="Regional rates");
+      length region $8 amount 8 rate 8 total 8 score 8;
+      format amount 8.2 rate PERCENT8.1 total DOLLAR14.2 score E10.;
+      input region $ amount rate total score;
+      datalines;
+    North 1234.5 0.125 1234567.891 0.5
+    South 0.1 -0.032 42 1.25
+    East . 1 -1500.5 .
+    ;
+    run;
+
+E10. displays numbers in scientific notation, which is not one of the formats applied.
+"""
+
+import sys
+
+from create_scores_xpt import write
+
+# (name, type (1 = numeric, 2 = character), length, label, (format, width, decimals))
+VARS = [
+    (b"region", 2, 8, b""),
+    (b"amount", 1, 8, b"", (b"", 8, 2)),
+    (b"rate", 1, 8, b"", (b"PERCENT", 8, 1)),
+    (b"total", 1, 8, b"", (b"DOLLAR", 14, 2)),
+    (b"score", 1, 8, b"", (b"E", 10, 0)),
+]
+
+ROWS = [
+    (b"North", 1234.5, 0.125, 1234567.891, 0.5),
+    (b"South", 0.1, -0.032, 42, 1.25),
+    (b"East", None, 1, -1500.5, None),
+]
+
+
+def main(path):
+    write(path, b"RATES", b"Regional rates", VARS, ROWS)
+
+
+if __name__ == "__main__":
+    main(sys.argv[1] if len(sys.argv) > 1 else "rates.xpt")
@@ -0,0 +1,141 @@
+"""Write scores.xpt, a SAS transport (version 5) file, without needing SAS
+
+Equivalent to the following SAS program
+
+    libname out xport "scores.xpt";
+
+    data out.scores(label="Test scores");
+      length id 8 name $8 score 8;
+      label name = "Student name" score = "Test score";
+      input id name $ score;
+      datalines;
+    1 alpha 9.5
+    2 beta .
+    3 gamma -12.25
+    ;
+    run;
+"""
+
+import struct
+import sys
+
+CREATED = b"01JAN24:00:00:00"
+
+# (name, type (1 = numeric, 2 = character), length, label)
+VARS = [
+    (b"id", 1, 8, b""),
+    (b"name", 2, 8, b"Student name"),
+    (b"score", 1, 8, b"Test score"),
+]
+
+ROWS = [
+    (1, b"alpha", 9.5),
+    (2, b"beta", None),
+    (3, b"gamma", -12.25),
+]
+
+
+def pad(b, n):
+    return b.ljust(n, b" ")
+
+
+def header(name, counts=b"0" * 30):
+    return b"HEADER RECORD*******" + pad(name, 8) + b"HEADER RECORD!!!!!!!" + counts + b"  "
+
+
+def ibm(v):
+    """IBM hexadecimal floating point, as held by transport files"""
+    if v is None:
+        # a SAS missing value
+        return b"." + b"\x00" * 7
+    if isinstance(v, str):
+        # a special missing value, .A through .Z or ._, held as its letter
+        return v.encode() + b"\x00" * 7
+    if v == 0:
+        return b"\x00" * 8
+
+    sign = 0x80 if v < 0 else 0
+    v = abs(v)
+    exponent = 0
+    while v >= 1:
+        v /= 16
+        exponent += 1
+    while v < 1 / 16:
+        v *= 16
+        exponent -= 1
+
+    mantissa = round(v * 2**56)
+    if mantissa >= 2**56:
+        mantissa >>= 4
+        exponent += 1
+    return bytes([sign | (exponent + 64)]) + mantissa.to_bytes(7, "big")
+
+
+def namestr(i, name, ntype, length, label, position, fmt=(b"", 0, 0)):
+    form, width, decimals = fmt
+    return struct.pack(
+        ">hhhh8s40s8shhh2s8shhl52s",
+        ntype,
+        0,
+        length,
+        i + 1,
+        pad(name, 8),
+        pad(label, 40),
+        pad(form, 8),
+        width,
+        decimals,
+        0,
+        b"\x00\x00",
+        pad(b"", 8),
+        0,
+        0,
+        position,
+        b"\x00" * 52,
+    )
+
+
+def records(b):
+    """Pad to a whole number of 80 byte records"""
+    return b + b" " * (-len(b) % 80)
+
+
+def value(v, ntype, length):
+    return ibm(v) if ntype == 1 else pad(v, length)
+
+
+def write(path, member, label, variables, rows):
+    out = header(b"LIBRARY")
+    out += pad(b"SAS     SAS     SASLIB  9.4     X64_10PR", 64) + CREATED
+    out += pad(CREATED, 80)
+
+    out += header(b"MEMBER", b"000000000000000001600000000140")
+    out += header(b"DSCRPTR")
+    out += pad(b"SAS     " + pad(member, 8) + b"SASDATA 9.4     X64_10PR", 64) + CREATED
+    out += pad(CREATED, 16) + b" " * 16 + pad(label, 40) + pad(b"", 8)
+
+    out += header(b"NAMESTR", b"000000" + b"%04d" % len(variables) + b"0" * 20)
+    names = b""
+    position = 0
+    # a variable may be followed by its format as (name, width, decimals)
+    for i, (name, ntype, length, var_label, *fmt) in enumerate(variables):
+        names += namestr(i, name, ntype, length, var_label, position, *fmt)
+        position += length
+    out += records(names)
+
+    out += header(b"OBS")
+    data = b""
+    for row in rows:
+        for v, (_, ntype, length, *_) in zip(row, variables):
+            data += value(v, ntype, length)
+    out += records(data)
+
+    with open(path, "wb") as f:
+        f.write(out)
+
+
+def main(path):
+    write(path, b"SCORES", b"Test scores", VARS, ROWS)
+
+
+if __name__ == "__main__":
+    main(sys.argv[1] if len(sys.argv) > 1 else "scores.xpt")
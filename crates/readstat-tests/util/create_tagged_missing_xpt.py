@@ -0,0 +1,51 @@
+"""Write tagged_missing.xpt, a SAS transport (version 5) file holding special missing values
+
+Equivalent to the following SAS program
+
+    libname out xport "tagged_missing.xpt";
+
+    missing A B;
+    data out.survey(label="Survey responses");
+      length id 8 answer 8 age 8 name $8;
+      label answer = "Response" age = "Age";
+      input id answer age name $;
+      datalines;
+    1 1 34 alpha
+    2 A 51 beta
+    3 B . gamma
+    4 . B delta
+    5 0 A epsilon
+    ;
+    run;
+
+Within the survey, .A means "refused" and .B means "don't know".
+"""
+
+import sys
+
+from create_scores_xpt import write
+
+# (name, type (1 = numeric, 2 = character), length, label)
+VARS = [
+    (b"id", 1, 8, b""),
+    (b"answer", 1, 8, b"Response"),
+    (b"age", 1, 8, b"Age"),
+    (b"name", 2, 8, b""),
+]
+
+# None is a missing value, a letter is the special missing value of that letter
+ROWS = [
+    (1, 1, 34, b"alpha"),
+    (2, "A", 51, b"beta"),
+    (3, "B", None, b"gamma"),
+    (4, None, "B", b"delta"),
+    (5, 0, "A", b"epsilon"),
+]
+
+
+def main(path):
+    write(path, b"SURVEY", b"Survey responses", VARS, ROWS)
+
+
+if __name__ == "__main__":
+    main(sys.argv[1] if len(sys.argv) > 1 else "tagged_missing.xpt")
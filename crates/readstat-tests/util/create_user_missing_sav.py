@@ -0,0 +1,116 @@
+"""Write user_missing.sav, an SPSS system file whose variables declare user-defined missing values
+
+Equivalent to the following SPSS syntax
+
+    DATA LIST LIST / id (F8.0) q1 (F8.0) age (F8.0) income (F8.2) answer (A8).
+    BEGIN DATA
+    1 1 34 52000 yes
+    2 9 51 -1 NA
+    3 7 150 99999 no
+    4 . 120 -5.5 REFUSED
+    5 3 29 0 maybe
+    END DATA.
+    VARIABLE LABELS q1 "Satisfaction" income "Yearly income".
+    MISSING VALUES q1 (7, 8, 9) age (120 THRU HI) income (LO THRU -1, 99999)
+      answer ("NA", "REFUSED").
+    SAVE OUTFILE="user_missing.sav".
+
+q1 declares the maximum of three discrete missing values, age and income open-ended ranges, the
+latter along with a discrete value, and answer two strings.  Row 4 of q1 is system missing.
+"""
+
+import struct
+import sys
+
+SYSMIS = struct.unpack("<d", struct.pack("<Q", 0xFFEFFFFFFFFFFFFF))[0]
+HIGHEST = struct.unpack("<d", struct.pack("<Q", 0x7FEFFFFFFFFFFFFF))[0]
+LOWEST = struct.unpack("<d", struct.pack("<Q", 0xFFEFFFFFFFFFFFFE))[0]
+
+# format types
+A = 1
+F = 5
+
+# (name, width (0 = numeric), label, (format, width, decimals), missing values)
+# A negative count of missing values declares a range, followed by a discrete value when -3
+VARS = [
+    (b"id", 0, b"", (F, 8, 0), (0, [])),
+    (b"q1", 0, b"Satisfaction", (F, 8, 0), (3, [7.0, 8.0, 9.0])),
+    (b"age", 0, b"", (F, 8, 0), (-2, [120.0, HIGHEST])),
+    (b"income", 0, b"Yearly income", (F, 8, 2), (-3, [LOWEST, -1.0, 99999.0])),
+    (b"answer", 8, b"", (A, 8, 0), (2, [b"NA", b"REFUSED"])),
+]
+
+# None is system missing
+ROWS = [
+    (1, 1, 34, 52000, b"yes"),
+    (2, 9, 51, -1, b"NA"),
+    (3, 7, 150, 99999, b"no"),
+    (4, None, 120, -5.5, b"REFUSED"),
+    (5, 3, 29, 0, b"maybe"),
+]
+
+
+def pad(b, n):
+    return b.ljust(n, b" ")
+
+
+def int32(*values):
+    return b"".join(struct.pack("<i", v) for v in values)
+
+
+def header(cases):
+    return (
+        b"$FL2"
+        + pad(b"@(#) SPSS DATA FILE readstat-rs test fixture", 60)
+        + int32(2, len(VARS), 0, 0, cases)
+        + struct.pack("<d", 100.0)
+        + b"01 Jan 24"
+        + b"00:00:00"
+        + pad(b"User-defined missing values", 64)
+        + b"\x00" * 3
+    )
+
+
+def variable(name, width, label, fmt, missing):
+    (fmt_type, fmt_width, fmt_decimals) = fmt
+    (n_missing, values) = missing
+    packed = (fmt_type << 16) | (fmt_width << 8) | fmt_decimals
+
+    record = int32(2, width, 1 if label else 0, n_missing, packed, packed) + pad(name, 8)
+    if label:
+        record += int32(len(label)) + label.ljust((len(label) + 3) // 4 * 4, b" ")
+    for v in values:
+        record += pad(v, 8) if width else struct.pack("<d", v)
+    return record
+
+
+def extension(subtype, size, data):
+    return int32(7, subtype, size, len(data) // size) + data
+
+
+def case(row):
+    return b"".join(
+        pad(v, 8) if width else struct.pack("<d", SYSMIS if v is None else v)
+        for v, (_, width, _, _, _) in zip(row, VARS)
+    )
+
+
+def main(path):
+    # machine integer info: version, machine code, IEEE 754 doubles, no compression, little
+    #   endian, UTF-8
+    integers = extension(3, 4, int32(20, 0, 0, -1, 1, 1, 2, 65001))
+    floats = extension(4, 8, struct.pack("<3d", SYSMIS, HIGHEST, LOWEST))
+    encoding = extension(20, 1, b"UTF-8")
+
+    with open(path, "wb") as f:
+        f.write(header(len(ROWS)))
+        for v in VARS:
+            f.write(variable(*v))
+        f.write(integers + floats + encoding)
+        f.write(int32(999, 0))
+        for row in ROWS:
+            f.write(case(row))
+
+
+if __name__ == "__main__":
+    main(sys.argv[1] if len(sys.argv) > 1 else "user_missing.sav")
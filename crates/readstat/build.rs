@@ -0,0 +1,15 @@
+use std::env;
+
+// Accessors only newer versions of ReadStat provide, as detected by readstat-sys
+const OPTIONAL_FUNCTIONS: &[&str] = &["get_file_encoding"];
+
+fn main() {
+    for name in OPTIONAL_FUNCTIONS {
+        let var = format!("DEP_READSTAT_HAS_{}", name.to_uppercase());
+        println!("cargo:rerun-if-env-changed={}", var);
+        println!("cargo:rustc-check-cfg=cfg(readstat_has_{})", name);
+        if env::var_os(&var).is_some() {
+            println!("cargo:rustc-cfg=readstat_has_{}", name);
+        }
+    }
+}
@@ -1,91 +1,114 @@
+use arrow2::datatypes::DataType;
 use chrono::DateTime;
+use colored::Colorize;
 use log::debug;
 use num_traits::FromPrimitive;
-use std::os::raw::{c_char, c_int, c_void};
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    ffi::CStr,
+    ops::ControlFlow,
+    os::raw::{c_char, c_int, c_void},
+    panic::{self, AssertUnwindSafe},
+    sync::atomic::Ordering,
+};
 
 use crate::{
     common::ptr_to_string,
+    conversions::OutOfRange,
     formats,
+    rs_cell_errors::{ErrorMode, ReadStatCellError},
     rs_data::ReadStatData,
-    rs_metadata::{ReadStatCompress, ReadStatEndian, ReadStatMetadata, ReadStatVarMetadata},
+    rs_date_window::ReadStatDateRangeWarning,
+    rs_metadata::{
+        ReadStatCompress, ReadStatEndian, ReadStatMetadata, ReadStatTypeDecision,
+        ReadStatTypeReason, ReadStatVarMetadata,
+    },
+    rs_redact::ReadStatRedaction,
+    rs_user_missing::ReadStatMissingRange,
+    rs_value_labels::{ReadStatLabelSet, ReadStatLabelValue},
     rs_var::{ReadStatVar, ReadStatVarType, ReadStatVarTypeClass},
+    rs_warnings::ReadStatWarningCategory,
+    TrimMode, UserMissing,
 };
 
 // C types
 #[allow(dead_code)]
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 #[repr(C)]
-enum ReadStatHandler {
+pub(crate) enum ReadStatHandler {
     READSTAT_HANDLER_OK,
     READSTAT_HANDLER_ABORT,
     READSTAT_HANDLER_SKIP_VARIABLE,
 }
 
-// C callback functions
+// The C callback functions only convert pointers into plain Rust values before handing off to
+//   the safe functions on_metadata, on_variable, and on_value; all of the logic lives within the
+//   latter so that it may be tested without a file
 
-// TODO: May need a version of handle_metadata that only gets metadata
-//       and a version that does very little and instead metadata handling occurs
-//       in handle_value function
-//       As an example see the below from the readstat binary
-//         https://github.com/WizardMac/ReadStat/blob/master/src/bin/readstat.c#L98
-pub extern "C" fn handle_metadata(
-    metadata: *mut readstat_sys::readstat_metadata_t,
-    ctx: *mut c_void,
-) -> c_int {
-    // dereference ctx pointer
-    let m = unsafe { &mut *(ctx as *mut ReadStatMetadata) };
+// File metadata as plain Rust values
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MetadataInfo {
+    pub row_count: c_int,
+    pub var_count: c_int,
+    pub table_name: String,
+    pub file_label: String,
+    pub file_encoding: String,
+    pub version: c_int,
+    pub is64bit: c_int,
+    pub creation_time: i64,
+    pub modified_time: i64,
+    pub compression: c_int,
+    pub endianness: c_int,
+}
 
-    // get metadata
-    let rc: c_int = unsafe { readstat_sys::readstat_get_row_count(metadata) };
-    let vc: c_int = unsafe { readstat_sys::readstat_get_var_count(metadata) };
-    let table_name = unsafe { ptr_to_string(readstat_sys::readstat_get_table_name(metadata)) };
-    let file_label = unsafe { ptr_to_string(readstat_sys::readstat_get_file_label(metadata)) };
-    let file_encoding =
-        unsafe { ptr_to_string(readstat_sys::readstat_get_file_encoding(metadata)) };
-    let version: c_int = unsafe { readstat_sys::readstat_get_file_format_version(metadata) };
-    let is64bit = unsafe { readstat_sys::readstat_get_file_format_is_64bit(metadata) };
-    let ct = DateTime::from_timestamp(
-        unsafe { readstat_sys::readstat_get_creation_time(metadata) },
-        0,
-    )
-    .expect("Panics (returns None) on the out-of-range number of seconds (more than 262 000 years away from common era) and/or invalid nanosecond (2 seconds or more")
-    .format("%Y-%m-%d %H:%M:%S")
-    .to_string();
-    let mt = DateTime::from_timestamp   (
-        unsafe { readstat_sys::readstat_get_modified_time(metadata) },
-        0,
-    )
-    .expect("Panics (returns None) on the out-of-range number of seconds (more than 262 000 years away from common era) and/or invalid nanosecond (2 seconds or more")
-    .format("%Y-%m-%d %H:%M:%S")
-    .to_string();
-
-    #[allow(clippy::useless_conversion)]
-    let compression = match FromPrimitive::from_i32(unsafe {
-        readstat_sys::readstat_get_compression(metadata)
-            .try_into()
-            .unwrap()
-    }) {
+// Variable metadata as plain Rust values
+#[derive(Clone, Debug, Default)]
+pub(crate) struct VarInfo {
+    pub var_type: c_int,
+    pub var_type_class: c_int,
+    pub name: String,
+    pub label: String,
+    pub format: String,
+    pub storage_width: usize,
+    pub display_width: c_int,
+    pub alignment: c_int,
+    pub measure: c_int,
+    pub label_set: String,
+    pub missing_ranges: Vec<ReadStatMissingRange>,
+}
+
+// An out-of-range timestamp is recorded as an empty string rather than panicking
+fn format_timestamp(t: i64) -> String {
+    DateTime::from_timestamp(t, 0).map_or(String::new(), |dt| {
+        dt.format("%Y-%m-%d %H:%M:%S").to_string()
+    })
+}
+
+pub(crate) fn on_metadata(m: &mut ReadStatMetadata, info: MetadataInfo) -> ReadStatHandler {
+    let ct = format_timestamp(info.creation_time);
+    let mt = format_timestamp(info.modified_time);
+
+    let compression = match FromPrimitive::from_i32(info.compression) {
         Some(t) => t,
         None => ReadStatCompress::None,
     };
 
-    #[allow(clippy::useless_conversion)]
-    let endianness = match FromPrimitive::from_i32(unsafe {
-        readstat_sys::readstat_get_endianness(metadata)
-            .try_into()
-            .unwrap()
-    }) {
+    let endianness = match FromPrimitive::from_i32(info.endianness) {
         Some(t) => t,
         None => ReadStatEndian::None,
     };
 
+    // ReadStat reports -1 when the row count is unknown; any negative count is treated as unknown
+    let rc = if info.row_count < 0 { -1 } else { info.row_count };
+
     debug!("row_count is {}", rc);
-    debug!("var_count is {}", vc);
-    debug!("table_name is {}", &table_name);
-    debug!("file_label is {}", &file_label);
-    debug!("file_encoding is {}", &file_encoding);
-    debug!("version is {}", version);
-    debug!("is64bit is {}", is64bit);
+    debug!("var_count is {}", info.var_count);
+    debug!("table_name is {}", &info.table_name);
+    debug!("file_label is {}", &info.file_label);
+    debug!("file_encoding is {}", &info.file_encoding);
+    debug!("version is {}", info.version);
+    debug!("is64bit is {}", info.is64bit);
     debug!("creation_time is {}", &ct);
     debug!("modified_time is {}", &mt);
     debug!("compression is {:#?}", &compression);
@@ -93,12 +116,14 @@ pub extern "C" fn handle_metadata(
 
     // insert into ReadStatMetadata struct
     m.row_count = rc;
-    m.var_count = vc;
-    m.table_name = table_name;
-    m.file_label = file_label;
-    m.file_encoding = file_encoding;
-    m.version = version;
-    m.is64bit = is64bit;
+    m.var_count = info.var_count;
+    m.table_name = String::from(info.table_name.trim_end());
+    m.file_label = String::from(info.file_label.trim_end());
+    m.raw_table_name = info.table_name;
+    m.raw_file_label = info.file_label;
+    m.file_encoding = info.file_encoding;
+    m.version = info.version;
+    m.is64bit = info.is64bit;
     m.creation_time = ct;
     m.modified_time = mt;
     m.compression = compression;
@@ -106,11 +131,483 @@ pub extern "C" fn handle_metadata(
 
     debug!("metadata struct is {:#?}", &m);
 
-    ReadStatHandler::READSTAT_HANDLER_OK as c_int
+    ReadStatHandler::READSTAT_HANDLER_OK
+}
+
+pub(crate) fn on_variable(
+    m: &mut ReadStatMetadata,
+    index: c_int,
+    info: VarInfo,
+) -> ReadStatHandler {
+    if index < 0 {
+        debug!("variable index {} is negative", index);
+        return ReadStatHandler::READSTAT_HANDLER_ABORT;
+    }
+
+    let var_type = match FromPrimitive::from_i32(info.var_type) {
+        Some(t) => t,
+        None => ReadStatVarType::Unknown,
+    };
+
+    let var_type_class = match FromPrimitive::from_i32(info.var_type_class) {
+        Some(t) => t,
+        None => ReadStatVarTypeClass::Numeric,
+    };
+
+    let var_format_class = formats::match_var_format(&info.format);
+
+    debug!("var_type is {:#?}", &var_type);
+    debug!("var_type_class is {:#?}", &var_type_class);
+    debug!("var_name is {}", &info.name);
+    debug!("var_label is {}", &info.label);
+    debug!("var_format is {}", &info.format);
+    debug!("var_format_class is {:#?}", &var_format_class);
+    debug!("var_storage_width is {}", info.storage_width);
+    debug!("var_display_width is {}", info.display_width);
+    debug!("var_alignment is {}", info.alignment);
+    debug!("var_measure is {}", info.measure);
+    debug!("var_label_set is {}", &info.label_set);
+    debug!("var_missing_ranges is {:?}", &info.missing_ranges);
+
+    // variables are keyed by index so a repeated name does not replace an earlier variable
+    if m.vars.values().any(|vm| vm.var_name == info.name) {
+        debug!("var_name {} is repeated at index {}", &info.name, index);
+    }
+
+    // raw ordering, kept sorted by index should ReadStat report variables out of order
+    let pos = m.variable_order.partition_point(|(i, _)| *i < index as usize);
+    m.variable_order.insert(pos, (index as usize, info.name.clone()));
+
+    let mut vm = ReadStatVarMetadata::new(
+        info.name,
+        var_type,
+        var_type_class,
+        info.label,
+        info.format,
+        var_format_class,
+        info.storage_width,
+    );
+    if !info.label_set.is_empty() {
+        vm.var_label_set = Some(info.label_set);
+    }
+    // zero, as ReadStat reports for unknown alignments and measures, is no variant
+    vm.var_display_width = u32::try_from(info.display_width).ok().filter(|w| *w > 0);
+    vm.var_alignment = FromPrimitive::from_i32(info.alignment);
+    vm.var_measure = FromPrimitive::from_i32(info.measure);
+    vm.var_missing_ranges = info.missing_ranges;
+
+    // decide upon the arrow type, recording why it was chosen
+    let mut td = ReadStatTypeDecision::new(&vm, m.datetime_type, m.time_handling);
+    // decided from the format rather than the values, so every batch agrees
+    if m.infer_integers
+        && td.data_type == DataType::Float64
+        && vm.var_format_parts.as_ref().is_some_and(|p| p.is_integral())
+    {
+        td = ReadStatTypeDecision {
+            data_type: DataType::Int64,
+            reason: ReadStatTypeReason::IntegerInference,
+        };
+    }
+    debug!("type decision is {:#?}", &td);
+
+    // insert into BTreeMaps within ReadStatMetadata struct
+    m.type_decisions.insert(index, td);
+    m.vars.insert(index, vm);
+
+    ReadStatHandler::READSTAT_HANDLER_OK
+}
+
+// A labelled value from a format catalog; values that are missing are never labelled
+pub(crate) fn on_value_label(
+    sets: &mut BTreeMap<String, ReadStatLabelSet>,
+    set_name: String,
+    value: Option<ReadStatLabelValue>,
+    label: String,
+) -> ReadStatHandler {
+    debug!("value label set is {}", &set_name);
+    debug!("value is {:?} and label is {}", &value, &label);
+
+    if let Some(v) = value {
+        sets.entry(set_name).or_default().insert(v, label);
+    }
+
+    ReadStatHandler::READSTAT_HANDLER_OK
+}
+
+pub(crate) fn on_variable_skip(d: &ReadStatData, index: c_int) -> ReadStatHandler {
+    match usize::try_from(index).ok().and_then(|i| d.positions.get(i)) {
+        Some(None) => ReadStatHandler::READSTAT_HANDLER_SKIP_VARIABLE,
+        _ => ReadStatHandler::READSTAT_HANDLER_OK,
+    }
+}
+
+// The tag of a special missing value is recorded upon the metadata of its variable and, with a
+//   companion column, held alongside the value; None, for any other value, only the latter
+pub(crate) fn on_missing_tag(d: &mut ReadStatData, var_index: c_int, tag: Option<char>) {
+    if let (Some(t), Some(vm)) = (tag, d.vars.get_mut(&var_index)) {
+        vm.var_missing_tags.insert(t);
+    }
+
+    // when filtering, the tag is kept along with the value; see on_value
+    if !d.measure_only && !d.summarize {
+        match (d.filter_index, d.row_kept) {
+            (Some(_), None) if d.missing_tag_cols.contains_key(&var_index) => {
+                d.staged_tags.push((var_index, tag));
+            }
+            (Some(_), Some(false)) => (),
+            _ => {
+                if let Some(col) = d.missing_tag_cols.get_mut(&var_index) {
+                    col.push(tag);
+                }
+            }
+        }
+    }
+}
+
+// Push a value into its column, when summarizing update the statistics of the column, or when
+//   measuring only count the bytes of strings
+fn append(d: &mut ReadStatData, i: usize, value: ReadStatVar) {
+    if d.summarize {
+        d.summaries[i].push(&value);
+    } else if d.measure_only {
+        if let ReadStatVar::ReadStat_String(Some(s)) = &value {
+            d.string_bytes[i] += s.len();
+        }
+    } else {
+        d.cols[i].push(value);
+    }
+}
+
+// Once a row is complete, append the values staged should it have passed the filter, or else
+//   discard them
+fn end_filtered_row(d: &mut ReadStatData) -> bool {
+    let kept = d.row_kept.take().unwrap_or(false);
+    let mut staged = std::mem::take(&mut d.staged);
+    let mut staged_tags = std::mem::take(&mut d.staged_tags);
+
+    if kept {
+        for (i, value) in staged.drain(..) {
+            append(d, i, value);
+        }
+        for (k, tag) in staged_tags.drain(..) {
+            if let Some(col) = d.missing_tag_cols.get_mut(&k) {
+                col.push(tag);
+            }
+        }
+    } else {
+        staged.clear();
+        staged_tags.clear();
+    }
+
+    // the buffers are handed back so that the next row reuses their room
+    d.staged = staged;
+    d.staged_tags = staged_tags;
+    kept
+}
+
+pub(crate) fn on_value<F>(
+    d: &mut ReadStatData,
+    var_index: c_int,
+    index_after_skipping: c_int,
+    convert: F,
+) -> ReadStatHandler
+where
+    F: FnOnce(
+        &BTreeMap<i32, ReadStatVarMetadata>,
+        &DataType,
+    ) -> Result<ReadStatVar, Box<dyn Error + Send + Sync>>,
+{
+    debug!("chunk_rows_to_process is {}", d.chunk_rows_to_process);
+    debug!("chunk_row_start is {}", d.chunk_row_start);
+    debug!("chunk_row_end is {}", d.chunk_row_end);
+    debug!("chunk_rows_processed is {}", d.chunk_rows_processed);
+    debug!("var_count is {}", d.var_count);
+    debug!("var_index is {}", var_index);
+    debug!("index_after_skipping is {}", index_after_skipping);
+
+    // stop promptly once nothing is left to receive the data
+    if let Some(c) = &d.cancel {
+        if c.load(Ordering::Relaxed) {
+            d.errors.push(format!(
+                "Reading was cancelled in row {}",
+                d.chunk_row_start + d.chunk_rows_read
+            ));
+            return ReadStatHandler::READSTAT_HANDLER_ABORT;
+        }
+    }
+
+    // a value for a variable that was never described cannot be placed, whereas one for a
+    //   variable that is not read is ignored.  Columns are indexed by the position ReadStat
+    //   reports after skipping, which must be where the variable was expected; otherwise each
+    //   variable skipped would shift the values of those following into the wrong columns
+    let position = usize::try_from(var_index).ok().and_then(|i| d.positions.get(i));
+    let i = match (position, usize::try_from(index_after_skipping)) {
+        (Some(None), _) => return ReadStatHandler::READSTAT_HANDLER_OK,
+        (Some(Some(p)), Ok(i)) if *p == i && i < d.cols.len() && i < d.schema.fields.len() => i,
+        (Some(Some(p)), Ok(i)) if *p != i => {
+            let name = d.vars.get(&var_index).map_or("", |m| m.var_name.as_str());
+            d.errors.push(format!(
+                "Received the value of {} (variable index {}) in row {} as variable {} after skipping, yet it is read into column {}",
+                name.bright_yellow(),
+                var_index,
+                d.chunk_row_start + d.chunk_rows_read,
+                i,
+                p
+            ));
+            return ReadStatHandler::READSTAT_HANDLER_ABORT;
+        }
+        _ => {
+            d.errors.push(format!(
+                "Received a value for the unknown variable index {} in row {}",
+                var_index,
+                d.chunk_row_start + d.chunk_rows_read
+            ));
+            return ReadStatHandler::READSTAT_HANDLER_ABORT;
+        }
+    };
+
+    // once the row has failed the filter, the rest of its values need not be converted
+    if d.row_kept == Some(false) {
+        return complete_row(d, var_index);
+    }
+
+    // get value
+    // the arrow data type chosen for the column determines how the value is converted, except
+    //   for labelled and hashed columns whose values are read as before being replaced by strings
+    let data_type = match (d.labelled.get(&var_index), d.redactions.get(&var_index)) {
+        (Some(l), _) => &l.source_type,
+        (None, Some(ReadStatRedaction::Hash { source_type, .. })) => source_type,
+        _ => d.schema.fields[i].data_type(),
+    };
+    // when collecting errors a value that cannot be converted is recorded and read as missing,
+    //   until more than the maximum have been recorded
+    let value = match convert(&d.vars, data_type) {
+        Ok(v) => v,
+        // a date, datetime, or time beyond the range of its type or the date window is missing,
+        //   with a warning, unless strict
+        Err(e) if e.is::<OutOfRange>() => {
+            let missing = ReadStatVar::missing(data_type);
+            let row = d.chunk_row_start + d.chunk_rows_read;
+            let name = d.vars.get(&var_index).map(|m| m.var_name.as_str());
+            if d.strict_dates {
+                d.errors.push(format!(
+                    "Error converting the value of variable {} in row {}: {}",
+                    name.unwrap_or_default(),
+                    row,
+                    e
+                ));
+                return ReadStatHandler::READSTAT_HANDLER_ABORT;
+            }
+            d.date_range_warnings.push(ReadStatDateRangeWarning {
+                row,
+                column: String::from(name.unwrap_or_default()),
+                value: e.downcast_ref::<OutOfRange>().map_or(f64::NAN, |o| o.value),
+                var_index,
+            });
+            d.warnings.record(ReadStatWarningCategory::DateRange, name, Some(row), || {
+                format!(
+                    "{}: The value of {} in row {} was read as missing as {}",
+                    "Warning".bright_yellow(),
+                    name.unwrap_or_default().bright_yellow(),
+                    row,
+                    e
+                )
+            });
+            missing.unwrap_or(ReadStatVar::ReadStat_f64(None))
+        }
+        Err(e) => {
+            let row = d.chunk_row_start + d.chunk_rows_read;
+            let name = d.vars.get(&var_index).map_or("", |m| m.var_name.as_str());
+            match (d.error_mode, ReadStatVar::missing(data_type)) {
+                (ErrorMode::Collect { max }, Some(missing)) if d.cell_errors.len() < max => {
+                    d.cell_errors.push(ReadStatCellError {
+                        row,
+                        column: String::from(name),
+                        description: e.to_string(),
+                        var_index,
+                    });
+                    missing
+                }
+                (ErrorMode::Collect { max }, _) => {
+                    d.errors.push(format!(
+                        "Stopped reading as more than {} values could not be read; the last, of variable {} in row {}: {}",
+                        max, name, row, e
+                    ));
+                    return ReadStatHandler::READSTAT_HANDLER_ABORT;
+                }
+                (ErrorMode::FailFast, _) => {
+                    d.errors.push(format!(
+                        "Error converting the value of variable {} in row {}: {}",
+                        name, row, e
+                    ));
+                    return ReadStatHandler::READSTAT_HANDLER_ABORT;
+                }
+            }
+        }
+    };
+
+    // trailing whitespace is trimmed ahead of labelling and normalizing; a value left blank is
+    //   empty rather than missing
+    let value = match value {
+        ReadStatVar::ReadStat_String(Some(mut s))
+            if d.string_trim == TrimMode::TrailingWhitespace =>
+        {
+            s.truncate(s.trim_end().len());
+            ReadStatVar::ReadStat_String(Some(s))
+        }
+        v => v,
+    };
+
+    // the filter compares the value as read, once trimmed
+    if d.filter_index == Some(var_index) {
+        d.row_kept = d.row_filter.as_ref().map(|f| f.predicate.matches(&value));
+    }
+
+    // labels replace values ahead of normalizing, so that labels are normalized too
+    let value = match d.labelled.get(&var_index) {
+        Some(l) => l.labels.apply(value),
+        None => value,
+    };
+
+    // normalize strings ahead of appending, counting the cells modified
+    let value = match value {
+        ReadStatVar::ReadStat_String(Some(s)) if d.normalize_vars.contains(&var_index) => {
+            match d.normalize.apply(&s) {
+                Some(n) => {
+                    *d.strings_normalized.entry(var_index).or_insert(0) += 1;
+                    ReadStatVar::ReadStat_String(Some(n))
+                }
+                None => ReadStatVar::ReadStat_String(Some(s)),
+            }
+        }
+        v => v,
+    };
+
+    // redact after normalizing so that strings differing only in case or spacing hash alike
+    let value = match d.redactions.get(&var_index) {
+        Some(r) => r.redact(value),
+        None => value,
+    };
+
+    // when filtering, values arriving ahead of the value compared are staged until the row is
+    //   kept or discarded, whereas once it is known they are appended or dropped directly
+    match (d.filter_index, d.row_kept) {
+        (Some(_), None) => d.staged.push((i, value)),
+        (Some(_), Some(false)) => (),
+        _ => append(d, i, value),
+    }
+
+    complete_row(d, var_index)
+}
+
+// A row is complete once the value of the last variable read arrives; only rows kept are counted
+//   as processed
+fn complete_row(d: &mut ReadStatData, var_index: c_int) -> ReadStatHandler {
+    if var_index == d.last_index {
+        d.chunk_rows_read += 1;
+        if d.filter_index.is_none() || end_filtered_row(d) {
+            d.chunk_rows_processed += 1;
+            if let Some(trp) = &d.total_rows_processed {
+                trp.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            }
+        }
+    };
+
+    ReadStatHandler::READSTAT_HANDLER_OK
+}
+
+// progress is the fraction of the file parsed, handed along with the rows of the chunk read so far
+pub(crate) fn on_progress(d: &mut ReadStatData, progress: f64) -> ReadStatHandler {
+    let rows = d.chunk_rows_processed as u64;
+    match d.progress_callback.as_mut().map(|f| f(progress, rows)) {
+        Some(ControlFlow::Break(())) => {
+            d.progress_cancelled = true;
+            ReadStatHandler::READSTAT_HANDLER_ABORT
+        }
+        _ => ReadStatHandler::READSTAT_HANDLER_OK,
+    }
+}
+
+// C callback functions
+
+// A panic must never unwind into ReadStat, which is undefined behaviour; each callback runs within
+//   catch_panic so that a panic instead aborts parsing, with its message returned
+fn catch_panic<F: FnOnce() -> c_int>(f: F) -> Result<c_int, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|e| {
+        e.downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| e.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| String::from("unknown panic"))
+    })
+}
+
+fn abort_on_panic<F: FnOnce() -> c_int>(f: F) -> c_int {
+    catch_panic(f).unwrap_or_else(|e| {
+        debug!("callback panicked with {}", e);
+        ReadStatHandler::READSTAT_HANDLER_ABORT as c_int
+    })
+}
+
+// TODO: May need a version of handle_metadata that only gets metadata
+//       and a version that does very little and instead metadata handling occurs
+//       in handle_value function
+//       As an example see the below from the readstat binary
+//         https://github.com/WizardMac/ReadStat/blob/master/src/bin/readstat.c#L98
+pub(crate) extern "C" fn handle_metadata(
+    metadata: *mut readstat_sys::readstat_metadata_t,
+    ctx: *mut c_void,
+) -> c_int {
+    abort_on_panic(|| {
+        // dereference ctx pointer
+        let m = unsafe { &mut *(ctx as *mut ReadStatMetadata) };
+
+        // get metadata
+        #[allow(clippy::useless_conversion)]
+        let info = unsafe {
+            MetadataInfo {
+                row_count: readstat_sys::readstat_get_row_count(metadata),
+                var_count: readstat_sys::readstat_get_var_count(metadata),
+                table_name: ptr_to_string(readstat_sys::readstat_get_table_name(metadata)),
+                file_label: ptr_to_string(readstat_sys::readstat_get_file_label(metadata)),
+                #[cfg(readstat_has_get_file_encoding)]
+                file_encoding: ptr_to_string(readstat_sys::readstat_get_file_encoding(metadata)),
+                // older versions of ReadStat do not report the encoding
+                #[cfg(not(readstat_has_get_file_encoding))]
+                file_encoding: String::new(),
+                version: readstat_sys::readstat_get_file_format_version(metadata),
+                is64bit: readstat_sys::readstat_get_file_format_is_64bit(metadata),
+                creation_time: readstat_sys::readstat_get_creation_time(metadata).into(),
+                modified_time: readstat_sys::readstat_get_modified_time(metadata).into(),
+                compression: readstat_sys::readstat_get_compression(metadata)
+                    .try_into()
+                    .unwrap_or(-1),
+                endianness: readstat_sys::readstat_get_endianness(metadata)
+                    .try_into()
+                    .unwrap_or(-1),
+            }
+        };
+
+        on_metadata(m, info) as c_int
+    })
+}
+
+// Rows of a file that does not record its row count, counted as values arrive; ctx is the count
+pub(crate) extern "C" fn handle_value_row_count(
+    obs_index: c_int,
+    _variable: *mut readstat_sys::readstat_variable_t,
+    _value: readstat_sys::readstat_value_t,
+    ctx: *mut c_void,
+) -> c_int {
+    abort_on_panic(|| {
+        let rows = unsafe { &mut *(ctx as *mut c_int) };
+        *rows = std::cmp::max(*rows, obs_index + 1);
+
+        ReadStatHandler::READSTAT_HANDLER_OK as c_int
+    })
 }
 
 /*
-pub extern "C" fn handle_metadata_row_count_only(
+pub(crate) extern "C" fn handle_metadata_row_count_only(
     metadata: *mut readstat_sys::readstat_metadata_t,
     ctx: *mut c_void,
 ) -> c_int {
@@ -129,102 +626,1018 @@ pub extern "C" fn handle_metadata_row_count_only(
 }
 */
 
-pub extern "C" fn handle_variable(
+pub(crate) extern "C" fn handle_variable(
     index: c_int,
     variable: *mut readstat_sys::readstat_variable_t,
-    #[allow(unused_variables)] val_labels: *const c_char,
+    val_labels: *const c_char,
     ctx: *mut c_void,
 ) -> c_int {
-    // dereference ctx pointer
-    let m = unsafe { &mut *(ctx as *mut ReadStatMetadata) };
-
-    // get variable metadata
-    #[allow(clippy::useless_conversion)]
-    let var_type = match FromPrimitive::from_i32(unsafe {
-        readstat_sys::readstat_variable_get_type(variable)
-            .try_into()
-            .unwrap()
-    }) {
-        Some(t) => t,
-        None => ReadStatVarType::Unknown,
-    };
+    abort_on_panic(|| {
+        // dereference ctx pointer
+        let m = unsafe { &mut *(ctx as *mut ReadStatMetadata) };
 
-    #[allow(clippy::useless_conversion)]
-    let var_type_class = match FromPrimitive::from_i32(unsafe {
-        readstat_sys::readstat_variable_get_type_class(variable)
-            .try_into()
-            .unwrap()
-    }) {
-        Some(t) => t,
-        None => ReadStatVarTypeClass::Numeric,
-    };
+        // get variable metadata
+        #[allow(clippy::useless_conversion)]
+        let info = unsafe {
+            VarInfo {
+                var_type: readstat_sys::readstat_variable_get_type(variable)
+                    .try_into()
+                    .unwrap_or(-1),
+                var_type_class: readstat_sys::readstat_variable_get_type_class(variable)
+                    .try_into()
+                    .unwrap_or(-1),
+                name: ptr_to_string(readstat_sys::readstat_variable_get_name(variable)),
+                label: ptr_to_string(readstat_sys::readstat_variable_get_label(variable)),
+                format: ptr_to_string(readstat_sys::readstat_variable_get_format(variable)),
+                storage_width: readstat_sys::readstat_variable_get_storage_width(variable),
+                display_width: readstat_sys::readstat_variable_get_display_width(variable),
+                alignment: readstat_sys::readstat_variable_get_alignment(variable)
+                    .try_into()
+                    .unwrap_or(-1),
+                measure: readstat_sys::readstat_variable_get_measure(variable)
+                    .try_into()
+                    .unwrap_or(-1),
+                label_set: ptr_to_string(val_labels),
+                missing_ranges: missing_ranges(variable),
+            }
+        };
 
-    let var_name = unsafe { ptr_to_string(readstat_sys::readstat_variable_get_name(variable)) };
-    let var_label = unsafe { ptr_to_string(readstat_sys::readstat_variable_get_label(variable)) };
-    let var_format = unsafe { ptr_to_string(readstat_sys::readstat_variable_get_format(variable)) };
-    let var_format_class = formats::match_var_format(&var_format);
+        on_variable(m, index, info) as c_int
+    })
+}
 
-    debug!("var_type is {:#?}", &var_type);
-    debug!("var_type_class is {:#?}", &var_type_class);
-    debug!("var_name is {}", &var_name);
-    debug!("var_label is {}", &var_label);
-    debug!("var_format is {}", &var_format);
-    debug!("var_format_class is {:#?}", &var_format_class);
+// Missing values declared by a variable; ReadStat reports a discrete value as a range whose ends
+//   are equal, and the ends of string ranges are always equal
+unsafe fn missing_ranges(
+    variable: *mut readstat_sys::readstat_variable_t,
+) -> Vec<ReadStatMissingRange> {
+    let count = readstat_sys::readstat_variable_get_missing_ranges_count(variable);
 
-    // insert into BTreeMap within ReadStatMetadata struct
-    m.vars.insert(
-        index,
-        ReadStatVarMetadata::new(
-            var_name,
-            var_type,
-            var_type_class,
-            var_label,
-            var_format,
-            var_format_class,
-        ),
-    );
+    (0..count)
+        .map(|i| {
+            let lo = readstat_sys::readstat_variable_get_missing_range_lo(variable, i);
+            let hi = readstat_sys::readstat_variable_get_missing_range_hi(variable, i);
+            match readstat_sys::readstat_value_type_class(lo) {
+                readstat_sys::readstat_type_class_e_READSTAT_TYPE_CLASS_STRING => {
+                    ReadStatMissingRange::String {
+                        value: ptr_to_string(readstat_sys::readstat_string_value(lo)),
+                    }
+                }
+                _ => ReadStatMissingRange::numeric(
+                    readstat_sys::readstat_double_value(lo),
+                    readstat_sys::readstat_double_value(hi),
+                ),
+            }
+        })
+        .collect()
+}
 
-    ReadStatHandler::READSTAT_HANDLER_OK as c_int
+// Value labels of a format catalog; ctx is the label sets keyed by name
+pub(crate) extern "C" fn handle_value_label(
+    val_labels: *const c_char,
+    value: readstat_sys::readstat_value_t,
+    label: *const c_char,
+    ctx: *mut c_void,
+) -> c_int {
+    abort_on_panic(|| {
+        // dereference ctx pointer
+        let sets = unsafe { &mut *(ctx as *mut BTreeMap<String, ReadStatLabelSet>) };
+
+        let value_type: readstat_sys::readstat_type_t =
+            unsafe { readstat_sys::readstat_value_type(value) };
+        let is_missing: c_int = unsafe { readstat_sys::readstat_value_is_system_missing(value) };
+
+        let value = match value_type {
+            _ if is_missing != 0 => None,
+            readstat_sys::readstat_type_e_READSTAT_TYPE_STRING
+            | readstat_sys::readstat_type_e_READSTAT_TYPE_STRING_REF => Some(
+                ReadStatLabelValue::String(unsafe {
+                    ptr_to_string(readstat_sys::readstat_string_value(value))
+                }),
+            ),
+            _ => Some(ReadStatLabelValue::Numeric(unsafe {
+                readstat_sys::readstat_double_value(value)
+            })),
+        };
+
+        let (set_name, label) = unsafe { (ptr_to_string(val_labels), ptr_to_string(label)) };
+
+        on_value_label(sets, set_name, value, label) as c_int
+    })
 }
 
-pub extern "C" fn handle_value(
+// Variables that are not read are skipped so that ReadStat never hands over their values; ctx is
+//   ReadStatData
+pub(crate) extern "C" fn handle_variable_skip(
+    index: c_int,
+    #[allow(unused_variables)] variable: *mut readstat_sys::readstat_variable_t,
+    #[allow(unused_variables)] val_labels: *const c_char,
+    ctx: *mut c_void,
+) -> c_int {
+    abort_on_panic(|| {
+        // dereference ctx pointer
+        let d = unsafe { &mut *(ctx as *mut ReadStatData) };
+
+        on_variable_skip(d, index) as c_int
+    })
+}
+
+pub(crate) extern "C" fn handle_progress(progress: f64, ctx: *mut c_void) -> c_int {
+    abort_on_panic(|| {
+        // dereference ctx pointer
+        let d = unsafe { &mut *(ctx as *mut ReadStatData) };
+
+        on_progress(d, progress) as c_int
+    })
+}
+
+pub(crate) extern "C" fn handle_value(
     obs_index: c_int,
     variable: *mut readstat_sys::readstat_variable_t,
     value: readstat_sys::readstat_value_t,
     ctx: *mut c_void,
 ) -> c_int {
-    // dereference ctx pointer
-    let d = unsafe { &mut *(ctx as *mut ReadStatData) };
+    let result = catch_panic(|| {
+        // dereference ctx pointer
+        let d = unsafe { &mut *(ctx as *mut ReadStatData) };
 
-    // get index, type, and missingness
-    let var_index: c_int = unsafe { readstat_sys::readstat_variable_get_index(variable) };
-    let value_type: readstat_sys::readstat_type_t =
-        unsafe { readstat_sys::readstat_value_type(value) };
-    let is_missing: c_int = unsafe { readstat_sys::readstat_value_is_system_missing(value) };
+        // get index, type, and missingness
+        let var_index: c_int = unsafe { readstat_sys::readstat_variable_get_index(variable) };
 
-    debug!("chunk_rows_to_process is {}", d.chunk_rows_to_process);
-    debug!("chunk_row_start is {}", d.chunk_row_start);
-    debug!("chunk_row_end is {}", d.chunk_row_end);
-    debug!("chunk_rows_processed is {}", d.chunk_rows_processed);
-    debug!("var_count is {}", d.var_count);
-    debug!("obs_index is {}", obs_index);
-    debug!("var_index is {}", var_index);
-    debug!("value_type is {:#?}", &value_type);
-    debug!("is_missing is {}", is_missing);
+        // a variable that is not read is ignored ahead of querying or converting its value
+        if let Some(None) = usize::try_from(var_index).ok().and_then(|i| d.positions.get(i)) {
+            return ReadStatHandler::READSTAT_HANDLER_OK as c_int;
+        }
+
+        let index_after_skipping: c_int =
+            unsafe { readstat_sys::readstat_variable_get_index_after_skipping(variable) };
+        let value_type: readstat_sys::readstat_type_t =
+            unsafe { readstat_sys::readstat_value_type(value) };
+        let is_system_missing: c_int =
+            unsafe { readstat_sys::readstat_value_is_system_missing(value) };
+        let is_tagged_missing: c_int =
+            unsafe { readstat_sys::readstat_value_is_tagged_missing(value) };
+        // values among the missing values the variable declares are read as missing when asked
+        let is_defined_missing: c_int = match d.user_missing {
+            UserMissing::AsNull => unsafe {
+                readstat_sys::readstat_value_is_defined_missing(value, variable)
+            },
+            UserMissing::AsValue => 0,
+        };
+        let is_missing = is_system_missing | is_tagged_missing | is_defined_missing;
 
-    // get value and push into arrays
-    let value = ReadStatVar::get_readstat_value(value, value_type, is_missing, &d.vars, var_index);
+        debug!("obs_index is {}", obs_index);
+        debug!("value_type is {:#?}", &value_type);
+        debug!("is_missing is {}", is_missing);
 
-    // push into cols
-    d.cols[var_index as usize].push(value);
+        // special missing values (.A through .Z and ._) are read as missing, their tag kept aside
+        let tag = if is_tagged_missing == 1 {
+            Some(unsafe { readstat_sys::readstat_value_tag(value) } as u8 as char)
+        } else {
+            None
+        };
+        on_missing_tag(d, var_index, tag);
 
-    // if row is complete
-    if var_index == (d.var_count - 1) {
-        d.chunk_rows_processed += 1;
-        if let Some(trp) = &d.total_rows_processed {
-            trp.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        // watch the raw bytes of strings, ahead of their lossy conversion to UTF-8
+        if is_missing == 0
+            && matches!(
+                value_type,
+                readstat_sys::readstat_type_e_READSTAT_TYPE_STRING
+                    | readstat_sys::readstat_type_e_READSTAT_TYPE_STRING_REF
+            )
+        {
+            let ptr = unsafe { readstat_sys::readstat_string_value(value) };
+            if !ptr.is_null() {
+                let bytes = unsafe { CStr::from_ptr(ptr) }.to_bytes();
+                d.encoding_detector.push(bytes);
+                if std::str::from_utf8(bytes).is_err() {
+                    let row = d.chunk_row_start + d.chunk_rows_read;
+                    let name = d.vars.get(&var_index).map(|m| m.var_name.as_str());
+                    d.warnings.record(ReadStatWarningCategory::LossyUtf8, name, Some(row), || {
+                        format!(
+                            "{}: The value of {} in row {} is not valid UTF-8 and was converted lossily",
+                            "Warning".bright_yellow(),
+                            name.unwrap_or_default().bright_yellow(),
+                            row
+                        )
+                    });
+                }
+            }
         }
-    };
 
-    ReadStatHandler::READSTAT_HANDLER_OK as c_int
+        // get value and push into arrays
+        let float_handling = d.float_handling;
+        let epoch = d.epoch;
+        let date_window = d.date_window;
+        on_value(d, var_index, index_after_skipping, |vars, data_type| {
+            ReadStatVar::get_readstat_value(
+                value,
+                value_type,
+                is_missing,
+                vars,
+                var_index,
+                data_type,
+                float_handling,
+                epoch,
+                date_window,
+            )
+        }) as c_int
+    });
+
+    // the panic is reported once parsing stops
+    result.unwrap_or_else(|e| {
+        let d = unsafe { &mut *(ctx as *mut ReadStatData) };
+        let row = d.chunk_row_start + d.chunk_rows_read;
+        d.errors.push(format!("Reading the value in row {} panicked with {}", row, e));
+        ReadStatHandler::READSTAT_HANDLER_ABORT as c_int
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow2::datatypes::TimeUnit;
+
+    use crate::{rs_date_window::ReadStatDateWindow, rs_metadata::ReadStatRenameReason};
+
+    const DOUBLE: c_int = readstat_sys::readstat_type_e_READSTAT_TYPE_DOUBLE as c_int;
+    const STRING: c_int = readstat_sys::readstat_type_e_READSTAT_TYPE_STRING as c_int;
+    const NUMERIC: c_int = readstat_sys::readstat_type_class_e_READSTAT_TYPE_CLASS_NUMERIC as c_int;
+    const CHARACTER: c_int = readstat_sys::readstat_type_class_e_READSTAT_TYPE_CLASS_STRING as c_int;
+
+    fn var(name: &str, var_type: c_int, var_type_class: c_int, format: &str) -> VarInfo {
+        VarInfo {
+            var_type,
+            var_type_class,
+            name: String::from(name),
+            label: String::new(),
+            format: String::from(format),
+            storage_width: 8,
+            display_width: 0,
+            alignment: 0,
+            measure: 0,
+            label_set: String::new(),
+            missing_ranges: Vec::new(),
+        }
+    }
+
+    // metadata for a numeric and a string variable over two rows
+    fn metadata() -> ReadStatMetadata {
+        let mut m = ReadStatMetadata::new();
+        let info = MetadataInfo {
+            row_count: 2,
+            var_count: 2,
+            ..MetadataInfo::default()
+        };
+        assert_eq!(on_metadata(&mut m, info), ReadStatHandler::READSTAT_HANDLER_OK);
+        on_variable(&mut m, 0, var("x", DOUBLE, NUMERIC, "BEST12"));
+        on_variable(&mut m, 1, var("s", STRING, CHARACTER, "$8"));
+        m.schema = m.initialize_schema();
+        m
+    }
+
+    fn data(m: ReadStatMetadata) -> ReadStatData {
+        let rows = m.row_count as u32;
+        ReadStatData::new().set_no_progress(true).init(m, 0, rows)
+    }
+
+    fn ok(v: ReadStatVar) -> impl FnOnce(
+        &BTreeMap<i32, ReadStatVarMetadata>,
+        &DataType,
+    ) -> Result<ReadStatVar, Box<dyn Error + Send + Sync>> {
+        move |_, _| Ok(v)
+    }
+
+    #[test]
+    fn panics_are_caught_rather_than_unwinding_into_readstat() {
+        assert_eq!(catch_panic(|| 0), Ok(0));
+        assert_eq!(catch_panic(|| panic!("boom")), Err(String::from("boom")));
+        assert_eq!(
+            abort_on_panic(|| panic!("{} went wrong", "something")),
+            ReadStatHandler::READSTAT_HANDLER_ABORT as c_int
+        );
+    }
+
+    #[test]
+    fn strings_that_are_not_utf8_are_converted_lossily() {
+        // café within WINDOWS-1252
+        let bytes: [u8; 5] = [b'c', b'a', b'f', 0xE9, 0];
+        let s = unsafe { ptr_to_string(bytes.as_ptr() as *const c_char) };
+        assert_eq!(s, "caf\u{FFFD}");
+        assert_eq!(unsafe { ptr_to_string(std::ptr::null()) }, "");
+    }
+
+    #[test]
+    fn unknown_row_count_is_recorded_as_negative_one() {
+        for row_count in [-1, -42] {
+            let mut m = ReadStatMetadata::new();
+            let info = MetadataInfo {
+                row_count,
+                ..MetadataInfo::default()
+            };
+            assert_eq!(on_metadata(&mut m, info), ReadStatHandler::READSTAT_HANDLER_OK);
+            assert_eq!(m.row_count, -1);
+        }
+    }
+
+    #[test]
+    fn padded_table_name_and_file_label_are_trimmed() {
+        let mut m = ReadStatMetadata::new();
+        let info = MetadataInfo {
+            table_name: String::from("  CARS    "),
+            file_label: String::from("Written by SAS\t  "),
+            ..MetadataInfo::default()
+        };
+        assert_eq!(on_metadata(&mut m, info), ReadStatHandler::READSTAT_HANDLER_OK);
+
+        // only trailing whitespace is removed
+        assert_eq!(m.table_name, "  CARS");
+        assert_eq!(m.file_label, "Written by SAS");
+        assert_eq!(m.raw_table_name(), "  CARS    ");
+        assert_eq!(m.raw_file_label(), "Written by SAS\t  ");
+    }
+
+    #[test]
+    fn out_of_range_timestamps_do_not_panic() {
+        let mut m = ReadStatMetadata::new();
+        let info = MetadataInfo {
+            creation_time: i64::MAX,
+            modified_time: 0,
+            ..MetadataInfo::default()
+        };
+        assert_eq!(on_metadata(&mut m, info), ReadStatHandler::READSTAT_HANDLER_OK);
+        assert_eq!(m.creation_time, "");
+        assert_eq!(m.modified_time, "1970-01-01 00:00:00");
+    }
+
+    #[test]
+    fn unknown_compression_and_endianness_default_to_none() {
+        let mut m = ReadStatMetadata::new();
+        let info = MetadataInfo {
+            compression: 99,
+            endianness: -7,
+            ..MetadataInfo::default()
+        };
+        on_metadata(&mut m, info);
+        assert!(matches!(m.compression, ReadStatCompress::None));
+        assert!(matches!(m.endianness, ReadStatEndian::None));
+    }
+
+    #[test]
+    fn unknown_type_codes_are_read_as_strings() {
+        let mut m = ReadStatMetadata::new();
+        assert_eq!(
+            on_variable(&mut m, 0, var("weird", 42, 42, "")),
+            ReadStatHandler::READSTAT_HANDLER_OK
+        );
+        let vm = m.vars.get(&0).unwrap();
+        assert!(matches!(vm.var_type, ReadStatVarType::Unknown));
+        assert!(matches!(vm.var_type_class, ReadStatVarTypeClass::Numeric));
+        assert_eq!(m.type_decisions.get(&0).unwrap().data_type, DataType::Utf8);
+    }
+
+    #[test]
+    fn date_format_decides_the_arrow_type() {
+        let mut m = ReadStatMetadata::new();
+        on_variable(&mut m, 0, var("d", DOUBLE, NUMERIC, "YYMMDD10"));
+        assert_eq!(m.type_decisions.get(&0).unwrap().data_type, DataType::Date32);
+    }
+
+    #[test]
+    fn datetimes_keep_milliseconds_and_times_with_decimals_microseconds() {
+        let mut m = ReadStatMetadata::new();
+        for (i, format) in ["DATETIME22", "DATETIME22.2", "DATETIME26.6", "TIME", "TIME12.3"]
+            .into_iter()
+            .enumerate()
+        {
+            on_variable(&mut m, i as c_int, var("t", DOUBLE, NUMERIC, format));
+        }
+
+        let types: Vec<DataType> = m
+            .type_decisions
+            .values()
+            .map(|td| td.data_type.clone())
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                DataType::Timestamp(TimeUnit::Millisecond, None),
+                DataType::Timestamp(TimeUnit::Millisecond, None),
+                DataType::Timestamp(TimeUnit::Microsecond, None),
+                DataType::Time32(TimeUnit::Second),
+                DataType::Time64(TimeUnit::Microsecond),
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicate_names_keep_both_variables() {
+        let mut m = ReadStatMetadata::new();
+        on_variable(&mut m, 0, var("x", DOUBLE, NUMERIC, "BEST12"));
+        on_variable(&mut m, 1, var("x", STRING, CHARACTER, "$8"));
+
+        assert_eq!(m.vars.len(), 2);
+        assert!(matches!(m.vars.get(&0).unwrap().var_type, ReadStatVarType::Double));
+        assert!(matches!(m.vars.get(&1).unwrap().var_type, ReadStatVarType::String));
+        assert_eq!(m.initialize_schema().fields.len(), 2);
+    }
+
+    // x at indices 0, 2, and 3, and x_1 at index 1
+    fn duplicated(dedup_columns: bool) -> ReadStatMetadata {
+        let mut m = ReadStatMetadata::new().set_dedup_columns(dedup_columns);
+        on_variable(&mut m, 0, var("x", DOUBLE, NUMERIC, ""));
+        on_variable(&mut m, 1, var("x_1", DOUBLE, NUMERIC, ""));
+        on_variable(&mut m, 2, var("x", STRING, CHARACTER, "$8"));
+        on_variable(&mut m, 3, var("x", DOUBLE, NUMERIC, ""));
+        m
+    }
+
+    #[test]
+    fn duplicate_names_are_an_error_naming_their_indices() {
+        let mut m = duplicated(false);
+        let e = m.finish_variables().unwrap_err().to_string();
+        assert!(e.contains("(indices 0, 2, 3)"));
+        assert!(e.contains("--dedup-columns"));
+    }
+
+    #[test]
+    fn duplicate_names_are_suffixed_past_names_taken() {
+        let mut m = duplicated(true);
+        m.finish_variables().unwrap();
+
+        let names: Vec<&str> = m.schema.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["x", "x_1", "x_2", "x_3"]);
+        assert!(!m.renames.contains_key(&0));
+        assert_eq!(m.renames[&2].reason, ReadStatRenameReason::Deduplication);
+        assert_eq!(m.final_name(3), "x_3");
+    }
+
+    #[test]
+    fn variable_order_sorted_by_index() {
+        let mut m = ReadStatMetadata::new();
+        on_variable(&mut m, 1, var("b", DOUBLE, NUMERIC, ""));
+        on_variable(&mut m, 0, var("a", DOUBLE, NUMERIC, ""));
+
+        assert_eq!(
+            m.variable_order(),
+            vec![(0, String::from("a")), (1, String::from("b"))]
+        );
+    }
+
+    #[test]
+    fn negative_variable_index_aborts() {
+        let mut m = ReadStatMetadata::new();
+        assert_eq!(
+            on_variable(&mut m, -1, var("x", DOUBLE, NUMERIC, "")),
+            ReadStatHandler::READSTAT_HANDLER_ABORT
+        );
+        assert!(m.vars.is_empty());
+    }
+
+    #[test]
+    fn metadata_after_variables_keeps_variables() {
+        let mut m = ReadStatMetadata::new();
+        on_variable(&mut m, 0, var("x", DOUBLE, NUMERIC, ""));
+        on_metadata(&mut m, MetadataInfo::default());
+        assert_eq!(m.vars.len(), 1);
+    }
+
+    #[test]
+    fn values_complete_rows() {
+        let mut d = data(metadata());
+
+        for r in 0..2 {
+            let x = ReadStatVar::ReadStat_f64(Some(r as f64));
+            let s = ReadStatVar::ReadStat_String(None);
+            assert_eq!(on_value(&mut d, 0, 0, ok(x)), ReadStatHandler::READSTAT_HANDLER_OK);
+            assert_eq!(d.chunk_rows_processed, r);
+            assert_eq!(on_value(&mut d, 1, 1, ok(s)), ReadStatHandler::READSTAT_HANDLER_OK);
+            assert_eq!(d.chunk_rows_processed, r + 1);
+        }
+
+        assert_eq!(d.cols[0].len(), 2);
+        assert_eq!(d.cols[1].len(), 2);
+        assert!(d.errors.is_empty());
+    }
+
+    #[test]
+    fn values_out_of_range_are_missing_with_a_warning() {
+        let mut d = data(metadata());
+
+        let handled = on_value(&mut d, 0, 0, |_, _| {
+            Err(Box::new(OutOfRange {
+                value: 1e300,
+                data_type: DataType::Date32,
+                window: None,
+            }))
+        });
+        assert_eq!(handled, ReadStatHandler::READSTAT_HANDLER_OK);
+        assert!(matches!(d.cols[0][..], [ReadStatVar::ReadStat_f64(None)]));
+        assert!(d.errors.is_empty() && d.cell_errors.is_empty());
+
+        let g = &d.warnings.groups()[0];
+        assert_eq!(g.category, ReadStatWarningCategory::DateRange);
+        assert!(g.example.contains("beyond the range of Date32"), "{}", g.example);
+
+        let w = &d.date_range_warnings()[0];
+        assert_eq!((w.row, w.column.as_str(), w.value), (0, "x", 1e300));
+    }
+
+    #[test]
+    fn values_out_of_range_fail_when_strict() {
+        let mut d = data(metadata()).set_strict_dates(true);
+
+        let handled = on_value(&mut d, 0, 0, |_, _| {
+            Err(Box::new(OutOfRange {
+                value: 1e70,
+                data_type: DataType::Timestamp(TimeUnit::Millisecond, None),
+                window: Some(ReadStatDateWindow::default()),
+            }))
+        });
+        assert_eq!(handled, ReadStatHandler::READSTAT_HANDLER_ABORT);
+        assert!(d.errors[0].contains("beyond the date window 1582-01-01..9999-12-31"));
+        assert!(d.date_range_warnings().is_empty() && d.warnings.is_empty());
+    }
+
+    #[test]
+    fn filtered_rows_are_staged_until_complete() {
+        use crate::rs_filter::{FilterPredicate, FilterValue};
+
+        // s arrives after x, so x is staged until s decides the row
+        let mut d = ReadStatData::new()
+            .set_no_progress(true)
+            .set_row_filter("s", FilterPredicate::Eq(FilterValue::String(String::from("a"))))
+            .init(metadata(), 0, 2);
+
+        for (r, s) in ["a", "b"].iter().enumerate() {
+            let x = ReadStatVar::ReadStat_f64(Some(r as f64));
+            let s = ReadStatVar::ReadStat_String(Some(String::from(*s)));
+            assert_eq!(on_value(&mut d, 0, 0, ok(x)), ReadStatHandler::READSTAT_HANDLER_OK);
+            assert_eq!(d.cols[0].len(), r);
+            assert_eq!(on_value(&mut d, 1, 1, ok(s)), ReadStatHandler::READSTAT_HANDLER_OK);
+        }
+
+        assert_eq!((d.chunk_rows_read, d.chunk_rows_processed), (2, 1));
+        assert!(matches!(d.cols[0][..], [ReadStatVar::ReadStat_f64(Some(x))] if x == 0.0));
+        assert_eq!(d.cols[1].len(), 1);
+        assert!(d.staged.is_empty());
+
+        // once x fails, s is never converted
+        let mut d = ReadStatData::new()
+            .set_no_progress(true)
+            .set_row_filter("x", FilterPredicate::Gt(FilterValue::Number(0.0)))
+            .init(metadata(), 0, 2);
+
+        let x = ReadStatVar::ReadStat_f64(Some(0.0));
+        assert_eq!(on_value(&mut d, 0, 0, ok(x)), ReadStatHandler::READSTAT_HANDLER_OK);
+        let handled = on_value(&mut d, 1, 1, |_, _| unreachable!());
+        assert_eq!(handled, ReadStatHandler::READSTAT_HANDLER_OK);
+        assert_eq!((d.chunk_rows_read, d.chunk_rows_processed), (1, 0));
+        assert!(d.cols.iter().all(|c| c.is_empty()));
+    }
+
+    #[test]
+    fn columns_not_read_are_skipped() {
+        let mut d = ReadStatData::new()
+            .set_no_progress(true)
+            .set_columns(vec![String::from("x")])
+            .init(metadata(), 0, 2);
+
+        assert_eq!(on_variable_skip(&d, 0), ReadStatHandler::READSTAT_HANDLER_OK);
+        assert_eq!(on_variable_skip(&d, 1), ReadStatHandler::READSTAT_HANDLER_SKIP_VARIABLE);
+        assert_eq!(d.cols.len(), 1);
+        assert_eq!(d.schema.fields.len(), 1);
+
+        // x is the last variable read, so completes each row
+        for r in 0..2 {
+            let x = ReadStatVar::ReadStat_f64(Some(r as f64));
+            assert_eq!(on_value(&mut d, 0, 0, ok(x)), ReadStatHandler::READSTAT_HANDLER_OK);
+            assert_eq!(d.chunk_rows_processed, r + 1);
+        }
+
+        // a value for s is ignored should ReadStat hand one over regardless
+        let s = ReadStatVar::ReadStat_String(None);
+        assert_eq!(on_value(&mut d, 1, 1, ok(s)), ReadStatHandler::READSTAT_HANDLER_OK);
+        assert_eq!(d.cols[0].len(), 2);
+        assert!(d.errors.is_empty());
+    }
+
+    #[test]
+    fn values_are_placed_by_their_index_after_skipping() {
+        let mut d = ReadStatData::new()
+            .set_no_progress(true)
+            .set_columns(vec![String::from("s")])
+            .init(metadata(), 0, 2);
+        assert_eq!(d.vars[&1].var_index_after_skipping, Some(0));
+
+        // with x skipped, s is the first variable ReadStat hands over
+        let s = ReadStatVar::ReadStat_String(Some(String::from("a")));
+        assert_eq!(on_value(&mut d, 1, 0, ok(s)), ReadStatHandler::READSTAT_HANDLER_OK);
+        assert_eq!(d.cols[0].len(), 1);
+        assert_eq!(d.chunk_rows_processed, 1);
+
+        // a value arriving where the variable was not expected is never placed
+        let s = ReadStatVar::ReadStat_String(Some(String::from("b")));
+        assert_eq!(on_value(&mut d, 1, 1, ok(s)), ReadStatHandler::READSTAT_HANDLER_ABORT);
+        assert_eq!(d.cols[0].len(), 1);
+        assert!(d.errors[0].contains("as variable 1 after skipping"));
+    }
+
+    #[test]
+    fn measuring_counts_string_bytes_without_keeping_values() {
+        let m = metadata();
+        let rows = m.row_count as u32;
+        let mut d = ReadStatData::new()
+            .set_no_progress(true)
+            .set_measure_only(true)
+            .init(m, 0, rows);
+
+        for s in ["abc", "défg"] {
+            let x = ReadStatVar::ReadStat_f64(Some(1.0));
+            let s = ReadStatVar::ReadStat_String(Some(String::from(s)));
+            assert_eq!(on_value(&mut d, 0, 0, ok(x)), ReadStatHandler::READSTAT_HANDLER_OK);
+            assert_eq!(on_value(&mut d, 1, 1, ok(s)), ReadStatHandler::READSTAT_HANDLER_OK);
+        }
+
+        // bytes rather than characters
+        assert_eq!(d.chunk_rows_processed, 2);
+        assert_eq!(d.string_bytes, vec![0, 8]);
+        assert!(d.cols.iter().all(|c| c.is_empty()));
+    }
+
+    #[test]
+    fn summarizing_accumulates_statistics_without_keeping_values() {
+        let mut m = metadata();
+        m.row_count = 4;
+        let mut d = ReadStatData::new()
+            .set_no_progress(true)
+            .set_summarize(true)
+            .set_distinct_cap(2)
+            .init(m, 0, 4);
+
+        let rows = [(Some(3.0), "abc"), (None, "défg"), (Some(-1.0), "abc"), (Some(4.0), "z")];
+        for (x, s) in rows {
+            let x = ReadStatVar::ReadStat_f64(x);
+            let s = ReadStatVar::ReadStat_String(Some(String::from(s)));
+            assert_eq!(on_value(&mut d, 0, 0, ok(x)), ReadStatHandler::READSTAT_HANDLER_OK);
+            assert_eq!(on_value(&mut d, 1, 1, ok(s)), ReadStatHandler::READSTAT_HANDLER_OK);
+        }
+        assert_eq!(d.chunk_rows_processed, 4);
+        assert!(d.cols.iter().all(|c| c.is_empty()));
+
+        let mut summaries = d.into_summaries();
+        summaries.iter_mut().for_each(|c| c.finish());
+
+        let x = &summaries[0];
+        assert_eq!((x.count, x.missing_count), (3, 1));
+        assert_eq!((x.min, x.max, x.mean), (Some(-1.0), Some(4.0), Some(2.0)));
+
+        // characters rather than bytes; z is a third distinct value, beyond the cap
+        let s = &summaries[1];
+        assert_eq!(s.max_length, Some(4));
+        assert_eq!(s.distinct_count, Some(2));
+        assert_eq!(s.distinct_capped, Some(true));
+    }
+
+    #[test]
+    fn value_for_unknown_variable_aborts() {
+        let mut d = data(metadata());
+
+        for index in [-1, 2] {
+            let v = ReadStatVar::ReadStat_f64(None);
+            assert_eq!(on_value(&mut d, index, index, ok(v)), ReadStatHandler::READSTAT_HANDLER_ABORT);
+        }
+
+        assert_eq!(d.errors.len(), 2);
+        assert!(d.errors[1].contains("unknown variable index 2"));
+        assert!(d.cols.iter().all(|c| c.is_empty()));
+    }
+
+    #[test]
+    fn conversion_errors_name_variable_and_row() {
+        let mut d = data(metadata());
+
+        let x = ReadStatVar::ReadStat_f64(Some(1.0));
+        let s = ReadStatVar::ReadStat_String(Some(String::from("a")));
+        on_value(&mut d, 0, 0, ok(x));
+        on_value(&mut d, 1, 1, ok(s));
+
+        // the schema's type is passed along for conversion
+        let handler = on_value(&mut d, 0, 0, |_, data_type| {
+            assert_eq!(data_type, &DataType::Float64);
+            Err(From::from("bad value"))
+        });
+
+        assert_eq!(handler, ReadStatHandler::READSTAT_HANDLER_ABORT);
+        assert_eq!(
+            d.errors,
+            vec![String::from(
+                "Error converting the value of variable x in row 1: bad value"
+            )]
+        );
+        assert_eq!(d.cols[0].len(), 1);
+    }
+
+    #[test]
+    fn collected_conversion_errors_are_read_as_missing() {
+        fn bad(
+            _: &BTreeMap<i32, ReadStatVarMetadata>,
+            _: &DataType,
+        ) -> Result<ReadStatVar, Box<dyn Error + Send + Sync>> {
+            Err(From::from("bad value"))
+        }
+        let mut d = data(metadata()).set_error_mode(ErrorMode::Collect { max: 1 });
+
+        // recorded, with the row read on
+        let s = ReadStatVar::ReadStat_String(Some(String::from("a")));
+        assert_eq!(on_value(&mut d, 0, 0, bad), ReadStatHandler::READSTAT_HANDLER_OK);
+        assert_eq!(on_value(&mut d, 1, 1, ok(s)), ReadStatHandler::READSTAT_HANDLER_OK);
+        assert!(matches!(d.cols[0][..], [ReadStatVar::ReadStat_f64(None)]));
+        assert_eq!(d.chunk_rows_processed, 1);
+        assert_eq!(d.cell_errors().len(), 1);
+        assert_eq!(d.cell_errors()[0].row, 0);
+        assert_eq!(d.cell_errors()[0].column, "x");
+        assert_eq!(d.cell_errors()[0].description, "bad value");
+        assert!(d.errors.is_empty());
+
+        // more than the maximum aborts
+        assert_eq!(on_value(&mut d, 0, 0, bad), ReadStatHandler::READSTAT_HANDLER_ABORT);
+        assert_eq!(d.cell_errors().len(), 1);
+        assert!(d.errors[0].contains("more than 1 values could not be read"));
+        assert!(d.errors[0].contains("variable x in row 1"));
+    }
+
+    #[test]
+    fn strings_normalized_before_appending() {
+        let mut d = data(metadata()).set_string_normalize(
+            crate::rs_normalize::ReadStatStringNormalize {
+                case: Some(crate::StringCase::Upper),
+                collapse_whitespace: true,
+                ..Default::default()
+            },
+            [1].into_iter().collect(),
+        );
+
+        for s in ["Mixed   case\tvalue", "ALREADY NORMAL"] {
+            on_value(&mut d, 0, 0, ok(ReadStatVar::ReadStat_f64(None)));
+            on_value(&mut d, 1, 1, ok(string(s)));
+        }
+
+        assert!(matches!(
+            &d.cols[1][0],
+            ReadStatVar::ReadStat_String(Some(s)) if s == "MIXED CASE VALUE"
+        ));
+        assert!(matches!(
+            &d.cols[1][1],
+            ReadStatVar::ReadStat_String(Some(s)) if s == "ALREADY NORMAL"
+        ));
+        assert_eq!(d.strings_normalized.get(&1), Some(&1));
+    }
+
+    #[test]
+    fn redacted_columns_null_and_hashed_columns_read_as_before() {
+        let mut m = metadata();
+        crate::rs_redact::ReadStatRedact {
+            redact: vec![String::from("s")],
+            hash: vec!["x:sha256:pepper".parse().unwrap()],
+        }
+        .apply(&mut m)
+        .unwrap();
+        assert_eq!(m.schema.fields[0].data_type(), &DataType::Utf8);
+        let mut d = data(m);
+
+        for x in [Some(1.5), None] {
+            on_value(&mut d, 0, 0, |_, dt| {
+                assert_eq!(dt, &DataType::Float64);
+                Ok(ReadStatVar::ReadStat_f64(x))
+            });
+            on_value(&mut d, 1, 1, ok(string("secret")));
+        }
+
+        assert!(matches!(
+            &d.cols[0][0],
+            ReadStatVar::ReadStat_String(Some(h)) if h.len() == 64 && h != "1.5"
+        ));
+        assert!(matches!(&d.cols[0][1], ReadStatVar::ReadStat_String(None)));
+        assert!(d.cols[1]
+            .iter()
+            .all(|v| matches!(v, ReadStatVar::ReadStat_String(None))));
+    }
+
+    #[test]
+    fn value_labels_are_collected_by_set() {
+        let mut sets = BTreeMap::new();
+        for (value, label) in [
+            (Some(ReadStatLabelValue::Numeric(1.0)), "Male"),
+            (Some(ReadStatLabelValue::Numeric(2.0)), "Female"),
+            (None, "Missing"),
+        ] {
+            let r = on_value_label(&mut sets, String::from("SEXFMT"), value, String::from(label));
+            assert_eq!(r, ReadStatHandler::READSTAT_HANDLER_OK);
+        }
+
+        // missing values are never labelled
+        let labels = sets.get("SEXFMT").unwrap();
+        assert_eq!(labels.labels.len(), 2);
+        assert_eq!(labels.label(&ReadStatVar::ReadStat_f64(Some(2.0))), Some("Female"));
+        assert_eq!(labels.label(&ReadStatVar::ReadStat_f64(None)), None);
+    }
+
+    #[test]
+    fn labelled_values_are_read_as_before_and_written_as_labels() {
+        let mut m = ReadStatMetadata::new().set_apply_value_labels(true);
+        on_metadata(
+            &mut m,
+            MetadataInfo {
+                row_count: 3,
+                var_count: 1,
+                ..MetadataInfo::default()
+            },
+        );
+        on_variable(
+            &mut m,
+            0,
+            VarInfo {
+                label_set: String::from("SEXFMT"),
+                ..var("x", DOUBLE, NUMERIC, "SEXFMT")
+            },
+        );
+        assert_eq!(m.vars.get(&0).unwrap().var_label_set.as_deref(), Some("SEXFMT"));
+        m.schema = m.initialize_schema();
+
+        let mut labels = ReadStatLabelSet::default();
+        labels.insert(ReadStatLabelValue::Numeric(1.0), String::from("Male"));
+        m.set_value_labels(BTreeMap::from([(String::from("SEXFMT"), labels)]));
+        assert_eq!(m.schema.fields[0].data_type(), &DataType::Utf8);
+        let mut d = data(m);
+
+        for x in [Some(1.0), Some(3.0), None] {
+            on_value(&mut d, 0, 0, |_, dt| {
+                assert_eq!(dt, &DataType::Float64);
+                Ok(ReadStatVar::ReadStat_f64(x))
+            });
+        }
+
+        // unlabelled values are written as their text and missing values stay missing
+        assert!(matches!(
+            &d.cols[0][0],
+            ReadStatVar::ReadStat_String(Some(l)) if l == "Male"
+        ));
+        assert!(matches!(
+            &d.cols[0][1],
+            ReadStatVar::ReadStat_String(Some(l)) if l == "3"
+        ));
+        assert!(matches!(&d.cols[0][2], ReadStatVar::ReadStat_String(None)));
+    }
+
+    // a batch of values for x and s, where rows are (x, s) pairs
+    fn batch(
+        m: &ReadStatMetadata,
+        row_start: u32,
+        values: Vec<(ReadStatVar, ReadStatVar)>,
+    ) -> ReadStatData {
+        let row_end = row_start + values.len() as u32;
+        let mut d = ReadStatData::new()
+            .set_no_progress(true)
+            .init(m.clone(), row_start, row_end);
+        for (x, s) in values {
+            on_value(&mut d, 0, 0, ok(x));
+            on_value(&mut d, 1, 1, ok(s));
+        }
+        d.cols_to_chunk().unwrap();
+        d
+    }
+
+    // metadata whose x column has fallen back to strings partway through the file
+    fn fallen_back(m: &ReadStatMetadata) -> ReadStatMetadata {
+        let mut m = m.clone();
+        m.vars.get_mut(&0).unwrap().var_type = ReadStatVarType::String;
+        m.type_decisions.clear();
+        m.schema = m.initialize_schema();
+        m
+    }
+
+    fn string(s: &str) -> ReadStatVar {
+        ReadStatVar::ReadStat_String(Some(String::from(s)))
+    }
+
+    #[test]
+    fn schema_lock_rejects_a_mid_file_fallback() {
+        let m = metadata();
+        let mut lock = crate::rs_data::ReadStatSchemaLock::new(false);
+
+        let mut first = batch(&m, 0, vec![(ReadStatVar::ReadStat_f64(Some(1.0)), string("a"))]);
+        assert!(lock.check(&mut first).is_ok());
+
+        let mut second = batch(&fallen_back(&m), 1, vec![(string("2.5"), string("b"))]);
+        let e = lock.check(&mut second).unwrap_err().to_string();
+        assert!(e.contains("Float64 to Utf8"));
+        assert!(e.contains("starting at row 1"));
+    }
+
+    #[test]
+    fn schema_lock_coerces_a_mid_file_fallback() {
+        let m = metadata();
+        let mut lock = crate::rs_data::ReadStatSchemaLock::new(true);
+
+        let mut first = batch(&m, 0, vec![(ReadStatVar::ReadStat_f64(Some(1.0)), string("a"))]);
+        lock.check(&mut first).unwrap();
+
+        let mut second = batch(&fallen_back(&m), 1, vec![(string("2.5"), string("b"))]);
+        lock.check(&mut second).unwrap();
+
+        // cast back to the locked type
+        assert_eq!(second.schema, m.schema);
+        let chunk = second.chunk.unwrap();
+        let x = chunk.arrays()[0]
+            .as_any()
+            .downcast_ref::<arrow2::array::PrimitiveArray<f64>>()
+            .unwrap();
+        assert_eq!(x.value(0), 2.5);
+    }
+
+    #[test]
+    fn progress_callback_may_cancel_reading() {
+        let calls = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = calls.clone();
+        let mut d = data(metadata()).set_progress_callback(Box::new(move |progress, rows| {
+            seen.lock().unwrap().push((progress, rows));
+            if progress < 0.5 {
+                ControlFlow::Continue(())
+            } else {
+                ControlFlow::Break(())
+            }
+        }));
+
+        assert_eq!(on_progress(&mut d, 0.25), ReadStatHandler::READSTAT_HANDLER_OK);
+        assert!(!d.progress_cancelled);
+        assert_eq!(on_progress(&mut d, 0.5), ReadStatHandler::READSTAT_HANDLER_ABORT);
+        assert!(d.progress_cancelled);
+        assert_eq!(*calls.lock().unwrap(), vec![(0.25, 0), (0.5, 0)]);
+    }
+
+    #[test]
+    fn tags_of_special_missing_values_are_kept_aside() {
+        let mut d = ReadStatData::new()
+            .set_no_progress(true)
+            .set_missing_tags(true)
+            .init(metadata(), 0, 2);
+
+        // a companion column for the numeric variable only
+        let names: Vec<&str> = d.schema.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["x", "s", "x__missing_tag"]);
+
+        let s = ReadStatVar::ReadStat_String(Some(String::from("a")));
+        on_missing_tag(&mut d, 0, Some('R'));
+        on_value(&mut d, 0, 0, ok(ReadStatVar::ReadStat_f64(None)));
+        on_missing_tag(&mut d, 1, None);
+        on_value(&mut d, 1, 1, ok(s.clone()));
+        on_missing_tag(&mut d, 0, None);
+        on_value(&mut d, 0, 0, ok(ReadStatVar::ReadStat_f64(Some(1.0))));
+        on_missing_tag(&mut d, 1, None);
+        on_value(&mut d, 1, 1, ok(s));
+
+        assert_eq!(d.missing_tag_cols[&0], vec![Some('R'), None]);
+        assert!(d.vars[&0].var_missing_tags.contains(&'R'));
+        assert!(d.vars[&1].var_missing_tags.is_empty());
+
+        d.cols_to_chunk().unwrap();
+        let chunk = d.chunk.unwrap();
+        let tags = chunk.arrays()[2]
+            .as_any()
+            .downcast_ref::<arrow2::array::Utf8Array<i32>>()
+            .unwrap();
+        assert_eq!(tags.iter().collect::<Vec<_>>(), vec![Some("R"), None]);
+    }
+
+    #[test]
+    fn trailing_whitespace_is_trimmed_from_strings() {
+        // padded, with an internal space, and blank
+        let values = ["  padded\t ", "an internal  space", " \t"];
+
+        for (string_trim, expected) in [
+            (TrimMode::TrailingWhitespace, ["  padded", "an internal  space", ""]),
+            (TrimMode::None, values),
+        ] {
+            let mut d = ReadStatData::new()
+                .set_no_progress(true)
+                .set_string_trim(string_trim)
+                .init(metadata(), 0, 2);
+
+            for v in values {
+                let s = ReadStatVar::ReadStat_String(Some(String::from(v)));
+                assert_eq!(on_value(&mut d, 1, 1, ok(s)), ReadStatHandler::READSTAT_HANDLER_OK);
+            }
+
+            // a blank value is empty rather than missing
+            let read: Vec<Option<&str>> = d.cols[1]
+                .iter()
+                .map(|v| match v {
+                    ReadStatVar::ReadStat_String(s) => s.as_deref(),
+                    _ => unreachable!(),
+                })
+                .collect();
+            assert_eq!(read, expected.map(Some).to_vec());
+        }
+    }
 }
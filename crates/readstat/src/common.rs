@@ -1,3 +1,4 @@
+use sha2::{Digest, Sha256};
 use std::error::Error;
 use std::ffi::CStr;
 
@@ -5,6 +6,10 @@ pub fn build_offsets(
     row_count: u32,
     stream_rows: u32,
 ) -> Result<Vec<u32>, Box<dyn Error + Send + Sync>> {
+    if stream_rows == 0 {
+        return Err(From::from("The number of rows to stream at a time must be at least 1"));
+    }
+
     // Get number of chunks
     let chunks = if stream_rows < row_count {
         if row_count % stream_rows == 0 {
@@ -33,7 +38,7 @@ pub fn build_offsets(
 }
 
 // String out from C pointer
-pub unsafe fn ptr_to_string(x: *const i8) -> String {
+pub(crate) unsafe fn ptr_to_string(x: *const i8) -> String {
     if x.is_null() {
         String::new()
     } else {
@@ -43,3 +48,42 @@ pub unsafe fn ptr_to_string(x: *const i8) -> String {
         String::from_utf8_lossy(cstr.to_bytes()).to_string()
     }
 }
+
+// Lowercase hex of the sha256 of bytes
+pub(crate) fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+// Float formatting for text output
+// Relies solely upon Rust's own float formatting so output is identical across platforms and
+//   independent of any locale
+//   - the shortest representation that round-trips is written; whole numbers drop the trailing .0
+//   - magnitudes below 1e-5 or at and above 1e15 are written in scientific notation (e.g. 1e-7)
+//   - negative zero is written as 0
+//   - NaN, infinity, and negative infinity are written as NaN, inf, and -inf
+const SCI_LOWER: f64 = 1e-5;
+const SCI_UPPER: f64 = 1e15;
+
+pub(crate) fn format_f64(v: f64) -> String {
+    format_float(v, v.abs())
+}
+
+pub(crate) fn format_f32(v: f32) -> String {
+    format_float(v, v.abs() as f64)
+}
+
+fn format_float<T>(v: T, abs: f64) -> String
+where
+    T: std::fmt::Display + std::fmt::LowerExp,
+{
+    if abs == 0.0 {
+        String::from("0")
+    } else if abs.is_finite() && !(SCI_LOWER..SCI_UPPER).contains(&abs) {
+        format!("{:e}", v)
+    } else {
+        format!("{}", v)
+    }
+}
@@ -0,0 +1,155 @@
+use arrow2::datatypes::{DataType, TimeUnit};
+use chrono::{DateTime, NaiveDate};
+use std::{error::Error, fmt};
+
+use crate::{rs_date_window::ReadStatDateWindow, rs_schema::arrow_type_name};
+
+// Days from the SAS epoch, 1960-01-01, to the Unix epoch, 1970-01-01
+const SAS_EPOCH_DAYS: i64 = 3653;
+const SECONDS_PER_DAY: i64 = 86_400;
+const SAS_EPOCH_SECONDS: i64 = SAS_EPOCH_DAYS * SECONDS_PER_DAY;
+// Days from 0001-01-01, the first day of the common era, to the Unix epoch
+const UNIX_EPOCH_DAYS_FROM_CE: i64 = 719_163;
+
+/// Days since 1970-01-01, as held by Arrow's `Date32`, of a SAS date of days since 1960-01-01
+///
+/// A fractional day is truncated to the start of its day, as SAS displays it, so that `-0.5` is
+/// 1959-12-31.  Days are counted upon the proleptic Gregorian calendar, as SAS counts them, so
+/// dates ahead of the calendar reform of 1582 are not shifted by the days it dropped.  `None` for
+/// NaN, infinity, and any date beyond those chrono represents, some 262,000 years either side of
+/// the epoch.
+pub fn sas_days_to_date32(days: f64) -> Option<i32> {
+    if !days.is_finite() {
+        return None;
+    }
+
+    // i64::MAX as f64 rounds up to 2^63 so the upper bound is exclusive
+    let days = days.floor();
+    if days < i64::MIN as f64 || days >= i64::MAX as f64 {
+        return None;
+    }
+
+    let days = (days as i64).checked_sub(SAS_EPOCH_DAYS)?;
+    let from_ce = i32::try_from(days.checked_add(UNIX_EPOCH_DAYS_FROM_CE)?).ok()?;
+    NaiveDate::from_num_days_from_ce_opt(from_ce)?;
+
+    i32::try_from(days).ok()
+}
+
+/// Seconds since 1970-01-01 00:00:00, as held by Arrow's `Timestamp(Second)`, of a SAS datetime
+/// of seconds since 1960-01-01 00:00:00; see [`sas_seconds_to_timestamp_in`]
+pub fn sas_seconds_to_timestamp(seconds: f64) -> Option<i64> {
+    sas_seconds_to_timestamp_in(seconds, TimeUnit::Second)
+}
+
+/// `unit`s since 1970-01-01 00:00:00 of a SAS datetime of seconds since 1960-01-01 00:00:00
+///
+/// For whole seconds a fractional second is truncated to the start of its second, as for
+/// [`sas_days_to_date32`]; finer units are rounded to the nearest unit.  `None` for NaN,
+/// infinity, and any datetime beyond the range of `unit` (for nanoseconds, the years 1677 through
+/// 2262) or of chrono.
+pub fn sas_seconds_to_timestamp_in(seconds: f64, unit: TimeUnit) -> Option<i64> {
+    if !seconds.is_finite() {
+        return None;
+    }
+
+    let unix = seconds - SAS_EPOCH_SECONDS as f64;
+    let scaled = match unit {
+        TimeUnit::Second => unix.floor(),
+        TimeUnit::Millisecond => (unix * 1e3).round(),
+        TimeUnit::Microsecond => (unix * 1e6).round(),
+        TimeUnit::Nanosecond => (unix * 1e9).round(),
+    };
+
+    // i64::MAX as f64 rounds up to 2^63 so the upper bound is exclusive
+    let whole = unix.floor();
+    if !in_i64(scaled) || !in_i64(whole) {
+        return None;
+    }
+    DateTime::from_timestamp(whole as i64, 0)?;
+
+    Some(scaled as i64)
+}
+
+/// Seconds since midnight, as held by Arrow's `Time32(Second)`, of a SAS time of seconds
+///
+/// A fractional second is truncated to the start of its second.  `None` for NaN, infinity, and
+/// any time beyond the range of `i32`.
+pub fn sas_seconds_to_time32(seconds: f64) -> Option<i32> {
+    let seconds = seconds.floor();
+    if seconds.is_finite() && seconds >= i32::MIN as f64 && seconds <= i32::MAX as f64 {
+        Some(seconds as i32)
+    } else {
+        None
+    }
+}
+
+/// Microseconds since midnight, as held by Arrow's `Time64(Microsecond)`, of a SAS time of
+/// seconds
+///
+/// Fractional seconds are rounded to the nearest microsecond.  `None` for NaN, infinity, and any
+/// time beyond the range of `i64`.
+pub fn sas_seconds_to_time64(seconds: f64) -> Option<i64> {
+    let micros = (seconds * 1e6).round();
+    if micros.is_finite() && in_i64(micros) {
+        Some(micros as i64)
+    } else {
+        None
+    }
+}
+
+/// `unit`s of a SAS time of seconds read as an elapsed duration, which, unlike a time of day, may
+/// exceed 24 hours or be negative
+///
+/// For whole seconds a fractional second is truncated to the start of its second, as for
+/// [`sas_seconds_to_time32`]; finer units are rounded to the nearest unit.  `None` for NaN,
+/// infinity, and any duration beyond the range of `i64`.
+pub fn sas_seconds_to_duration(seconds: f64, unit: TimeUnit) -> Option<i64> {
+    let scaled = match unit {
+        TimeUnit::Second => seconds.floor(),
+        TimeUnit::Millisecond => (seconds * 1e3).round(),
+        TimeUnit::Microsecond => (seconds * 1e6).round(),
+        TimeUnit::Nanosecond => (seconds * 1e9).round(),
+    };
+
+    if scaled.is_finite() && in_i64(scaled) {
+        Some(scaled as i64)
+    } else {
+        None
+    }
+}
+
+// Whether a time of day, counted in units of which a second holds units_per_second, lies within
+//   00:00:00 through 23:59:59, as Arrow's Time32 and Time64 require
+pub(crate) fn within_day(time: i64, units_per_second: i64) -> bool {
+    (0..SECONDS_PER_DAY * units_per_second).contains(&time)
+}
+
+fn in_i64(value: f64) -> bool {
+    value >= i64::MIN as f64 && value < i64::MAX as f64
+}
+
+// A date, datetime, or time the Arrow type of its column cannot hold, or a date or datetime
+//   beyond the date window when set; read as missing with a warning rather than failing
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct OutOfRange {
+    pub value: f64,
+    pub data_type: DataType,
+    pub window: Option<ReadStatDateWindow>,
+}
+
+impl fmt::Display for OutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.window {
+            Some(w) => write!(f, "the value {} is beyond the date window {}", self.value, w),
+            None => write!(
+                f,
+                "the value {} is beyond the range of {}",
+                self.value,
+                arrow_type_name(&self.data_type)
+            ),
+        }
+    }
+}
+
+impl Error for OutOfRange {}
@@ -0,0 +1,214 @@
+//! Reading and converting a file in a single call
+//!
+//! Each function is a thin wrapper over [`ReadStatPath`], [`ReadStatMetadata`], and
+//! [`convert_collect`], with the defaults of [`ReadOptions`] in place of the many options of
+//! [`ReadStatConvertOptions`].  Files are always streamed, a batch at a time, so there is no
+//! reader to choose, and nothing is printed.
+//!
+//! ```no_run
+//! use readstat::{easy, OutFormat};
+//!
+//! let md = easy::read_metadata("cars.sas7bdat")?;
+//! println!("{} rows of {} variables", md.row_count, md.var_count);
+//!
+//! let options = easy::ReadOptions {
+//!     columns: vec![String::from("Brand"), String::from("EngineSize")],
+//!     ..easy::ReadOptions::default()
+//! };
+//! let stats = easy::convert("cars.sas7bdat", "cars.parquet", OutFormat::parquet, &options)?;
+//! println!("wrote {} rows", stats.rows_written);
+//! # Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
+//! ```
+
+use arrow2::{array::Array, chunk::Chunk, datatypes::Schema};
+use std::{error::Error, path::Path};
+
+use crate::{
+    rs_convert::{convert_collect, ReadStatConvertOptions, ReadStatStats},
+    rs_metadata::ReadStatMetadata,
+    rs_path::ReadStatPath,
+    DateTimeType, FloatHandling, OutFormat, OutputMode, TimeHandling, STREAM_ROWS,
+};
+
+/// What to read, and how, for each function of [`easy`](self)
+#[derive(Clone, Debug)]
+pub struct ReadOptions {
+    /// Rows to read, from the start of the file; defaults to every row
+    pub rows: Option<u32>,
+    /// Variables to read, named as within the file; defaults to every variable
+    pub columns: Vec<String>,
+    /// Rows of each batch; defaults to 10,000
+    pub batch_rows: u32,
+    /// Rounding of floating point values; defaults to [`FloatHandling::ReadStatDigits`]
+    pub float_handling: FloatHandling,
+    /// Arrow type of datetime columns; defaults to a timestamp whose unit matches the precision
+    /// of the SAS format
+    pub datetime_type: Option<DateTimeType>,
+    /// Arrow type of time columns; defaults to [`TimeHandling::Time32`], reading times beyond a
+    /// day as missing
+    pub time_handling: TimeHandling,
+    /// Store numerics whose format displays no decimals as Int64; see
+    /// [`ReadStatMetadata::set_infer_integers`]
+    pub infer_integers: bool,
+    /// Replace an existing output rather than fail
+    pub overwrite: bool,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        Self {
+            rows: None,
+            columns: Vec::new(),
+            batch_rows: STREAM_ROWS,
+            float_handling: FloatHandling::ReadStatDigits,
+            datetime_type: None,
+            time_handling: TimeHandling::Time32,
+            infer_integers: false,
+            overwrite: false,
+        }
+    }
+}
+
+impl ReadOptions {
+    /// The options of a conversion that reads as these do
+    pub fn convert_options(&self) -> ReadStatConvertOptions {
+        ReadStatConvertOptions {
+            rows: self.rows,
+            columns: self.columns.clone(),
+            stream_rows: self.batch_rows,
+            float_handling: self.float_handling,
+            datetime_type: self.datetime_type,
+            time_handling: self.time_handling,
+            infer_integers: self.infer_integers,
+            overwrite: self.overwrite,
+            ..ReadStatConvertOptions::default()
+        }
+    }
+}
+
+/// Metadata of the file at `path`, including its row count
+///
+/// ```no_run
+/// let md = readstat::easy::read_metadata("cars.sas7bdat")?;
+/// for vm in md.vars.values() {
+///     println!("{}: {}", vm.var_name, vm.var_format);
+/// }
+/// # Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
+/// ```
+pub fn read_metadata<P>(path: P) -> Result<ReadStatMetadata, Box<dyn Error + Send + Sync>>
+where
+    P: AsRef<Path>,
+{
+    let rsp = input(path.as_ref())?;
+    let mut md = ReadStatMetadata::new();
+    md.read_metadata(&rsp, false)?;
+    Ok(md)
+}
+
+/// Every batch of the file at `path`, in file order, along with the schema they share
+///
+/// Every batch is held in memory; see [`read_batches`](crate::read_batches) to read one batch at
+/// a time instead.
+///
+/// ```no_run
+/// use readstat::easy::{read_to_batches, ReadOptions};
+///
+/// let options = ReadOptions {
+///     rows: Some(100),
+///     ..ReadOptions::default()
+/// };
+/// let (schema, batches) = read_to_batches("cars.sas7bdat", &options)?;
+/// assert_eq!(batches.iter().map(|b| b.len()).sum::<usize>(), 100);
+/// println!("{:?}", schema.fields);
+/// # Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
+/// ```
+pub fn read_to_batches<P>(
+    path: P,
+    options: &ReadOptions,
+) -> Result<(Schema, Vec<Chunk<Box<dyn Array>>>), Box<dyn Error + Send + Sync>>
+where
+    P: AsRef<Path>,
+{
+    let rsp = input(path.as_ref())?;
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Collect,
+        ..options.convert_options()
+    };
+    let collected = convert_collect(&rsp, &options)?;
+    Ok((collected.schema, collected.chunks))
+}
+
+/// Convert the file at `path` into `out_path`, written as `format`
+///
+/// Returns the rows, batches, and bytes written and the time spent.  The output is written to a
+/// temporary file that replaces `out_path` only once complete, and an existing output is an
+/// error unless `options.overwrite` is set.
+///
+/// ```no_run
+/// use readstat::{easy, OutFormat};
+///
+/// let stats = easy::convert(
+///     "cars.sas7bdat",
+///     "cars.csv",
+///     OutFormat::csv,
+///     &easy::ReadOptions::default(),
+/// )?;
+/// println!("wrote {} bytes", stats.total_bytes_written());
+/// # Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
+/// ```
+pub fn convert<P, Q>(
+    path: P,
+    out_path: Q,
+    format: OutFormat,
+    options: &ReadOptions,
+) -> Result<ReadStatStats, Box<dyn Error + Send + Sync>>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let rsp = ReadStatPath::new(
+        path.as_ref().to_path_buf(),
+        Some(out_path.as_ref().to_path_buf()),
+        Some(format),
+        options.overwrite,
+        false,
+        None,
+        None,
+    )?;
+    let collected = convert_collect(&rsp, &options.convert_options())?;
+    Ok(collected.stats)
+}
+
+/// Convert the file at `path` into parquet at `out_path`, with the default [`ReadOptions`]
+///
+/// ```no_run
+/// readstat::easy::to_parquet("cars.sas7bdat", "cars.parquet")?;
+/// # Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
+/// ```
+pub fn to_parquet<P, Q>(path: P, out_path: Q) -> Result<ReadStatStats, Box<dyn Error + Send + Sync>>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    convert(path, out_path, OutFormat::parquet, &ReadOptions::default())
+}
+
+/// Convert the file at `path` into feather (Arrow IPC file) at `out_path`, with the default
+/// [`ReadOptions`]
+///
+/// ```no_run
+/// readstat::easy::to_feather("cars.sas7bdat", "cars.feather")?;
+/// # Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
+/// ```
+pub fn to_feather<P, Q>(path: P, out_path: Q) -> Result<ReadStatStats, Box<dyn Error + Send + Sync>>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    convert(path, out_path, OutFormat::feather, &ReadOptions::default())
+}
+
+// An input read without being written
+fn input(path: &Path) -> Result<ReadStatPath, Box<dyn Error + Send + Sync>> {
+    ReadStatPath::new(path.to_path_buf(), None, None, false, false, None, None)
+}
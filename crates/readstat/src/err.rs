@@ -1,6 +1,9 @@
 use num_derive::FromPrimitive;
+use std::{fmt, path::PathBuf};
 
-#[derive(Debug, FromPrimitive)]
+use crate::common::ptr_to_string;
+
+#[derive(Clone, Copy, Debug, Eq, FromPrimitive, PartialEq)]
 pub enum ReadStatError {
     READSTAT_OK = 0,
     READSTAT_ERROR_OPEN = 1,
@@ -43,3 +46,134 @@ pub enum ReadStatError {
     READSTAT_ERROR_NAME_IS_ZERO_LENGTH = 38,
     READSTAT_ERROR_BAD_TIMESTAMP_VALUE = 39,
 }
+
+// Errors of character conversion name the remedy, as the encoding a file declares is often wrong
+impl fmt::Display for ReadStatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::READSTAT_ERROR_UNSUPPORTED_CHARSET => write!(
+                f,
+                "{:?}; the character encoding is not one iconv supports, so name another with --encoding",
+                self
+            ),
+            Self::READSTAT_ERROR_CONVERT
+            | Self::READSTAT_ERROR_CONVERT_BAD_STRING
+            | Self::READSTAT_ERROR_CONVERT_SHORT_STRING
+            | Self::READSTAT_ERROR_CONVERT_LONG_STRING => write!(
+                f,
+                "{:?}; a string could not be converted from the character encoding of the file, which may be declared wrongly, so name the actual encoding with --encoding (e.g. WINDOWS-1252)",
+                self
+            ),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
+/// Parsing stopped with an error from ReadStat
+///
+/// Returned boxed like any other error, so tell it apart with
+/// `e.downcast_ref::<ReadStatParseError>()`; `code as i32` is the numeric `readstat_error_t`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReadStatParseError {
+    pub code: ReadStatError,
+    /// ReadStat's description of the code, e.g. `Invalid file, or file has unsupported features`
+    pub message: String,
+}
+
+impl ReadStatParseError {
+    pub fn new(code: ReadStatError) -> Self {
+        let message = unsafe {
+            ptr_to_string(readstat_sys::readstat_error_message(
+                code as readstat_sys::readstat_error_t,
+            ))
+        };
+
+        Self { code, message }
+    }
+}
+
+impl fmt::Display for ReadStatParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Error when attempting to parse sas7bdat: {} ({})",
+            self.message, self.code
+        )
+    }
+}
+
+impl std::error::Error for ReadStatParseError {}
+
+/// Reading was cancelled by the callback given to [`crate::ReadStatData::set_progress_callback`]
+///
+/// Returned boxed like any other error, so tell it apart with
+/// `e.downcast_ref::<ReadStatCancelled>()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReadStatCancelled {
+    /// Rows read in full ahead of cancelling
+    pub rows: u64,
+}
+
+impl fmt::Display for ReadStatCancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Reading was cancelled after {} rows", self.rows)
+    }
+}
+
+impl std::error::Error for ReadStatCancelled {}
+
+/// Inputs to be concatenated do not all have the schema of the first; see
+/// [`crate::ReadStatConcat`]
+///
+/// Returned boxed like any other error, so tell it apart with
+/// `e.downcast_ref::<ReadStatSchemaMismatch>()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReadStatSchemaMismatch {
+    /// Input whose schema every other must match, i.e. the first
+    pub expected: PathBuf,
+    /// First input whose schema differs
+    pub found: PathBuf,
+    /// A line for each column that differs by position, beginning with `-` as within `expected`
+    /// and `+` as within `found`, e.g. `+ 3: amount Utf8`
+    pub diff: Vec<String>,
+}
+
+impl fmt::Display for ReadStatSchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The schema of {} differs from that of {}\n{}",
+            self.found.to_string_lossy(),
+            self.expected.to_string_lossy(),
+            self.diff.join("\n")
+        )
+    }
+}
+
+impl std::error::Error for ReadStatSchemaMismatch {}
+
+/// A checkpointed conversion cannot be resumed as its input or options have changed since it was
+/// interrupted; see [`crate::ReadStatCheckpoint`]
+///
+/// Returned boxed like any other error, so tell it apart with
+/// `e.downcast_ref::<ReadStatResumeMismatch>()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReadStatResumeMismatch {
+    /// Checkpoint of the interrupted conversion
+    pub checkpoint: PathBuf,
+    /// What differs, e.g. `the size of the input differs (1,024 bytes rather than 2,048)`
+    pub reason: String,
+}
+
+impl fmt::Display for ReadStatResumeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The conversion checkpointed within {} cannot be resumed as {}; use --overwrite to start afresh",
+            self.checkpoint.to_string_lossy(),
+            self.reason
+        )
+    }
+}
+
+impl std::error::Error for ReadStatResumeMismatch {}
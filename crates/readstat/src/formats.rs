@@ -10,7 +10,8 @@ pub fn match_var_format(v: &str) -> Option<ReadStatVarFormatClass> {
             (^DATE[0-9]{1,2}$) |
             (^DDMMYY[BCDNPS]?[0-9]*$) |
             (^MMDDYY[BCDNPS]?[0-9]*$) |
-            (^YYMMDD[BCDNPS]?[0-9]*$)
+            (^YYMMDD[BCDNPS]?[0-9]*$) |
+            (^%td)
             "#
         )
         .unwrap();
@@ -26,7 +27,8 @@ pub fn match_var_format(v: &str) -> Option<ReadStatVarFormatClass> {
     lazy_static! {
         static ref RE_DATETIME_WITH_MILLI: Regex = Regex::new(
             r#"(?xi)
-            (^DATETIME[0-9]{1,2}\.\[0-9]{3}$)
+            (^DATETIME[0-9]{1,2}\.[0-9]{1,3}$) |
+            (^%tc)
             "#
         )
         .unwrap();
@@ -34,7 +36,7 @@ pub fn match_var_format(v: &str) -> Option<ReadStatVarFormatClass> {
     lazy_static! {
         static ref RE_DATETIME_WITH_MICRO: Regex = Regex::new(
             r#"(?xi)
-            (^DATETIME[0-9]{1,2}\.[0-9]{6}$)
+            (^DATETIME[0-9]{1,2}\.[0-9]{4,6}$)
             "#
         )
         .unwrap();
@@ -42,7 +44,7 @@ pub fn match_var_format(v: &str) -> Option<ReadStatVarFormatClass> {
     lazy_static! {
         static ref RE_DATETIME_WITH_NANO: Regex = Regex::new(
             r#"(?xi)
-            (^DATETIME[0-9]{1,2}\.[0-9]{9}$)
+            (^DATETIME[0-9]{1,2}\.[0-9]{7,9}$)
             "#
         )
         .unwrap();
@@ -55,8 +57,32 @@ pub fn match_var_format(v: &str) -> Option<ReadStatVarFormatClass> {
         )
         .unwrap();
     };
+    lazy_static! {
+        static ref RE_TIME_WITH_MICRO: Regex = Regex::new(
+            r#"(?xi)
+            (^TIME[0-9]{1,2}\.[0-9]{1,6}$)
+            "#
+        )
+        .unwrap();
+    };
+
+    // Stata stores times of day as datetimes, of milliseconds, displayed without their date
+    lazy_static! {
+        static ref RE_STATA_TIME: Regex = Regex::new(
+            r#"(?x)
+            (^%tcHH:MM:SS(\.s{1,6})?$)
+            "#
+        )
+        .unwrap();
+    };
 
-    if RE_DATE.is_match(v) {
+    if RE_STATA_TIME.is_match(v) {
+        if v.contains('.') {
+            Some(ReadStatVarFormatClass::TimeWithMicroseconds)
+        } else {
+            Some(ReadStatVarFormatClass::Time)
+        }
+    } else if RE_DATE.is_match(v) {
         Some(ReadStatVarFormatClass::Date)
     } else if RE_DATETIME.is_match(v) {
         Some(ReadStatVarFormatClass::DateTime)
@@ -68,6 +94,8 @@ pub fn match_var_format(v: &str) -> Option<ReadStatVarFormatClass> {
         Some(ReadStatVarFormatClass::DateTimeWithNanoseconds)
     } else if RE_TIME.is_match(v) {
         Some(ReadStatVarFormatClass::Time)
+    } else if RE_TIME_WITH_MICRO.is_match(v) {
+        Some(ReadStatVarFormatClass::TimeWithMicroseconds)
     } else {
         None
     }
@@ -1,45 +1,176 @@
 #![allow(non_camel_case_types)]
 use clap::{Parser, Subcommand, ValueEnum, ValueHint};
 use colored::Colorize;
-use crossbeam::channel::bounded;
+use indicatif::{ProgressBar, ProgressStyle};
 use log::debug;
+use num_format::{Locale, ToFormattedString};
 use path_abs::{PathAbs, PathInfo};
-use rayon::prelude::*;
-use std::{error::Error, fmt, path::PathBuf, sync::Arc, thread};
+use std::{
+    error::Error, fmt, num::NonZeroU32, ops::ControlFlow, path::PathBuf, sync::Arc, time::Duration,
+};
+
+use rs_write::print_message;
 
 pub use common::build_offsets;
-pub use err::ReadStatError;
-pub use rs_data::ReadStatData;
-pub use rs_metadata::{ReadStatCompress, ReadStatEndian, ReadStatMetadata, ReadStatVarMetadata};
-pub use rs_path::ReadStatPath;
-pub use rs_var::{ReadStatVar, ReadStatVarFormatClass, ReadStatVarType, ReadStatVarTypeClass};
-pub use rs_write::ReadStatWriter;
+pub use conversions::{
+    sas_days_to_date32, sas_seconds_to_duration, sas_seconds_to_time32, sas_seconds_to_time64,
+    sas_seconds_to_timestamp, sas_seconds_to_timestamp_in,
+};
+pub use err::{
+    ReadStatCancelled, ReadStatError, ReadStatParseError, ReadStatResumeMismatch,
+    ReadStatSchemaMismatch,
+};
+#[cfg(feature = "async")]
+pub use rs_async::{read_async, ReadStatBatchStream};
+#[cfg(feature = "cache")]
+pub use rs_cache::{ReadStatCache, ReadStatCacheStats};
+pub use rs_cell_errors::{ErrorMode, ReadStatCellError, ReadStatErrorReport};
+pub use rs_checkpoint::{
+    ReadStatCheckpoint, ReadStatInputFingerprint, CHECKPOINT_ROWS, CHECKPOINT_VERSION,
+};
+pub use rs_compare::{compare_metadata, PropertyDiff, SchemaDiff, VariableDiff};
+pub use rs_concat::{ReadStatConcat, ReadStatConcatenated};
+pub use rs_convert::{
+    convert, convert_collect, convert_for_each, convert_many, read_batches, resolve_reader,
+    ReadStatBatchTiming, ReadStatBatches, ReadStatCollected, ReadStatConversionResult,
+    ReadStatConvertOptions, ReadStatSalvage, ReadStatStats,
+};
+pub use rs_data::{ReadStatData, ReadStatProgressCallback, ReadStatSchemaLock};
+pub use rs_date_window::{ReadStatDateRangeWarning, ReadStatDateWindow};
+pub use rs_dates::{
+    apply_dates, detect_date_columns, ReadStatDateColumn, ReadStatDateDetection,
+    DETECT_DATES_DAYS, DETECT_DATES_MIN_VALUES, DETECT_DATES_SAMPLE_ROWS, DETECT_DATES_SECONDS,
+    DETECT_DATES_THRESHOLD,
+};
+pub use rs_dictionary::{apply_string_encoding, StringEncoding};
+pub use rs_encoding::{guess_encoding, ReadStatEncodingDetector, ReadStatEncodingGuess};
+pub use rs_estimate::ReadStatSizeEstimate;
+pub use rs_filter::{FilterPredicate, FilterValue, ReadStatRowFilter};
+pub use rs_info::{readstat_version, ReadStatVersionInfo};
+pub use rs_io::{
+    without_compression_extension, InputCompression, ReadStatInput, ReadStatSource,
+    DEFAULT_MAX_DECOMPRESSED_BYTES,
+};
+pub use rs_mapping::{ReadStatMapping, ReadStatMappingEntry};
+pub use rs_measure::{ReadStatMeasure, ReadStatMeasuredBatch};
+pub use rs_metadata::{
+    ReadStatCompress, ReadStatEndian, ReadStatFormatSpec, ReadStatMetadata, ReadStatRename,
+    ReadStatRenameReason, ReadStatTruncation, ReadStatTypeDecision, ReadStatTypeReason,
+    ReadStatVarMetadata,
+};
+pub use rs_missing_tags::{missing_tag_name, MISSING_TAG_SUFFIX};
+pub use rs_normalize::ReadStatStringNormalize;
+pub use rs_path::{is_special_file, ReadStatPath, STDOUT_PATH};
+#[cfg(feature = "polars")]
+pub use rs_polars::chunk_to_polars;
+pub use rs_redact::{ReadStatHashAlgorithm, ReadStatHashColumn, ReadStatRedact, ReadStatRedaction};
+pub use rs_sample::{sample_rows, ReadStatSampleRow};
+pub use rs_schema::{
+    ReadStatSchema, ReadStatSchemaColumn, FINGERPRINT_VERSION, SCHEMA_VERSION,
+};
+pub use rs_spec::{ReadStatColumnSpec, ReadStatColumnSpecRow};
+pub use rs_stats::{
+    apply_booleans, detect_boolean_columns, ReadStatBooleanDetector, ReadStatColumnProfile,
+    ReadStatHistogram, ReadStatProfile,
+};
+pub use rs_summary::{ReadStatColumnSummary, ReadStatSummary, ReadStatSummaryKind, DISTINCT_CAP};
+pub use rs_template::ReadStatOutputTemplate;
+pub use rs_user_missing::ReadStatMissingRange;
+pub use rs_value_labels::{
+    ReadStatLabelSet, ReadStatLabelValue, ReadStatLabelling, ReadStatValueLabel,
+};
+pub use rs_var::{
+    ReadStatVar, ReadStatVarAlignment, ReadStatVarFormatClass, ReadStatVarMeasure, ReadStatVarType,
+    ReadStatVarTypeClass,
+};
+pub use rs_verify::{
+    check_numeric_sanity, ReadStatNumericSanity, ReadStatNumericSanityReport, ReadStatRawValue,
+    NUMERIC_SANITY_HUGE, NUMERIC_SANITY_THRESHOLD,
+};
+pub use rs_warnings::{
+    ReadStatWarningCategory, ReadStatWarningGroup, ReadStatWarnings, WARNINGS_RETAINED,
+};
+pub use rs_write::{
+    segment_path, split_path, OutputWriter, ReadStatCsvOptions, ReadStatFileOutput,
+    ReadStatOutput, ReadStatStdoutOutput, ReadStatWriter,
+};
+
+pub mod easy;
+pub mod prelude;
 
 mod cb;
 mod common;
+mod conversions;
 mod err;
 mod formats;
+#[cfg(feature = "async")]
+mod rs_async;
+#[cfg(feature = "cache")]
+mod rs_cache;
+mod rs_cell_errors;
+mod rs_checkpoint;
+mod rs_compare;
+mod rs_concat;
+mod rs_convert;
 mod rs_data;
+mod rs_date_window;
+mod rs_dates;
+mod rs_dictionary;
+mod rs_encoding;
+mod rs_estimate;
+mod rs_filter;
+mod rs_info;
+mod rs_io;
+mod rs_mapping;
+mod rs_measure;
 mod rs_metadata;
+mod rs_missing_tags;
+mod rs_normalize;
 mod rs_parser;
 mod rs_path;
+#[cfg(feature = "polars")]
+mod rs_polars;
+mod rs_redact;
+mod rs_sample;
+mod rs_schema;
+mod rs_spec;
+mod rs_stats;
+mod rs_summary;
+mod rs_template;
+mod rs_user_missing;
+mod rs_value_labels;
 mod rs_var;
+mod rs_verify;
+mod rs_warnings;
 mod rs_write;
+mod rs_write_readstat;
 
 // GLOBALS
 // Default rows to stream
 const STREAM_ROWS: u32 = 10000;
+// Most rows of a batch sized to fit within a memory limit
+const MAX_BATCH_ROWS: u32 = 1_000_000;
+// Default batches buffered between reading and writing
+const CHANNEL_DEPTH: usize = 2;
+// Default wait ahead of retrying a failed write
+const WRITE_RETRY_DELAY: Duration = Duration::from_secs(5);
+// Default rows below which reading stays upon a single thread with --parallel
+const PARALLEL_MIN_ROWS: u32 = 100_000;
 
 // CLI
 #[derive(Parser, Debug)]
 #[command(version)]
 #[command(propagate_version = true)]
+#[command(arg_required_else_help = true)]
 /// 💾 Command-line tool for working with SAS binary files
 ///
 /// 🦀 Rust wrapper of ReadStat C library
 pub struct ReadStatCli {
     #[command(subcommand)]
-    command: ReadStatCliCommands,
+    command: Option<ReadStatCliCommands>,
+    /// Display versions of readstat, ReadStat, and arrow2 along with the formats and features compiled in
+    #[arg(action, long)]
+    version_info: bool,
 }
 
 #[derive(Debug, Subcommand)]
@@ -48,8 +179,8 @@ pub enum ReadStatCliCommands {
     Metadata {
         #[arg(value_hint = ValueHint::FilePath, value_parser)]
         input: PathBuf,
-        /// Display sas7bdat metadata as json
-        #[arg(action, long)]
+        /// Display sas7bdat metadata as json{n}Variables are ordered by index and include the Arrow type each is written as
+        #[arg(action, long, visible_alias = "json")]
         as_json: bool,
         /// Do not display progress bar
         #[arg(action, long)]
@@ -57,33 +188,132 @@ pub enum ReadStatCliCommands {
         /// Skip calculating row count{n}If only interested in variable metadata speeds up parsing
         #[arg(action, long)]
         skip_row_count: bool,
+        /// Display a report of the Arrow type chosen for each variable and why
+        #[arg(action, long)]
+        type_report: bool,
+        /// Recover what can be read from a damaged file rather than stopping at the first error{n}Metadata is read so long as every variable is described; data is written up to the row at which reading fails
+        #[arg(action, long)]
+        best_effort: bool,
+        /// Rename each later variable sharing the name of an earlier one to <name>_1, <name>_2, and so on{n}Otherwise variables sharing a name are an error
+        #[arg(action, long)]
+        dedup_columns: bool,
+        /// Number of rows to sample uniformly from the whole file and include within the metadata
+        #[arg(long, value_parser, conflicts_with = "skip_row_count")]
+        sample_rows_in_metadata: Option<u32>,
+        /// Seed for sampling rows{n}The same seed always selects the same rows{n}Defaults to a seed derived from the current time
+        #[arg(long, value_parser)]
+        seed: Option<u64>,
+        /// Character encoding of the strings within the file (e.g. WINDOWS-1252){n}Overrides the encoding the file declares{n}Defaults to the declared encoding
+        #[arg(long, value_parser)]
+        encoding: Option<String>,
+        /// Include estimates of the size of the data once read into memory and once written in each output format
+        #[arg(action, long, conflicts_with = "skip_row_count")]
+        estimate_size: bool,
+        /// Characters each variable label and SAS format may hold{n}Longer ones are truncated with a warning
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        max_label_length: Option<u32>,
+        /// Fail rather than truncate when a label or format exceeds --max-label-length
+        #[arg(action, long, requires = "max_label_length")]
+        strict: bool,
+        /// Path to the format catalog (sas7bcat) holding the value labels of the variables{n}Each variable is matched to the label set named as its format
+        #[arg(long, value_hint = ValueHint::FilePath, value_parser)]
+        catalog: Option<PathBuf>,
     },
     /// Preview sas7bdat data
     Preview {
-        /// Path to sas7bdat file
+        /// Path to sas7bdat or xpt file
         #[arg(value_parser)]
         input: PathBuf,
         /// Number of rows to write
         #[arg(default_value = "10", long, value_parser)]
         rows: u32,
+        /// Number of rows to skip from the start of the file ahead of the rows written{n}Defaults to 0
+        #[arg(long, value_parser)]
+        row_offset: Option<u32>,
+        /// Display the rows as a table rather than writing csv{n}Dates, times, and datetimes are displayed as such; the rows of transport files are not counted beforehand
+        #[arg(action, long, conflicts_with = "row_offset")]
+        table: bool,
         /// Type of reader{n}    mem = read all data into memory{n}    stream = read at most stream-rows into memory{n}Defaults to stream
         #[arg(value_enum, ignore_case = true, long, value_parser)]
         reader: Option<Reader>,
         /// Number of rows to stream (read into memory) at a time{n}↑ rows = ↑ memory usage{n}Ignored if reader is set to mem{n}Defaults to 10,000 rows
-        #[arg(long, value_parser)]
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
         stream_rows: Option<u32>,
         /// Do not display progress bar
         #[arg(action, long)]
         no_progress: bool,
+        /// Arrow type for datetime columns{n}Defaults to a timestamp whose unit matches the precision of the SAS format
+        #[arg(ignore_case = true, long, value_enum, value_parser)]
+        datetime_type: Option<DateTimeType>,
+        /// Arrow type for columns with a SAS time format{n}    time32 = times of day; times of 24 hours or more and negative times are written as null with a warning{n}    duration-seconds = Int64 seconds (microseconds for formats with decimals), keeping every time{n}Defaults to time32
+        #[arg(ignore_case = true, long, value_enum, value_parser)]
+        time_handling: Option<TimeHandling>,
+        /// Store numeric columns whose SAS format displays no decimals (e.g. 8., F8.0, or COMMA6.) as Int64 rather than Float64{n}Decided from the format alone, so every batch holds the same types; a value that is not a whole number is an error
+        #[arg(action, long)]
+        infer_integers: bool,
+        /// Rename each later variable sharing the name of an earlier one to <name>_1, <name>_2, and so on{n}Otherwise variables sharing a name are an error
+        #[arg(action, long)]
+        dedup_columns: bool,
+        /// Rounding of floating point values{n}    readstat-digits = round to 14 decimal places, as the ReadStat binary does{n}    raw = keep every value exactly as stored{n}Defaults to readstat-digits
+        #[arg(ignore_case = true, long, value_enum, value_parser)]
+        float_handling: Option<FloatHandling>,
+        /// Follow the columns with a column named <name>__missing_tag for each numeric column{n}Holds the letter of each special missing value (.A through .Z and ._), e.g. R for .R, and is empty otherwise{n}Special missing values are missing within the numeric column either way
+        #[arg(action, long)]
+        missing_tags: bool,
+        /// Whitespace trimmed from string values{n}    trailing-whitespace = trailing whitespace; leading whitespace is kept, and a blank value becomes an empty string rather than missing{n}    none = none, beyond the trailing spaces ReadStat itself drops{n}Defaults to trailing-whitespace
+        #[arg(ignore_case = true, long, value_enum, value_parser)]
+        string_trim: Option<TrimMode>,
+        /// How values among the missing values an SPSS variable declares are read{n}    as-value = kept as stored{n}    as-null = read as missing{n}Defaults to as-value
+        #[arg(ignore_case = true, long, value_enum, value_parser)]
+        user_missing: Option<UserMissing>,
+        /// Store 0/1 coded numerics as booleans{n}    auto = columns whose every non-missing value is 0 or 1 (reads the data twice){n}    a comma-separated list of variable names = the named columns; other values are an error
+        #[arg(long, value_parser)]
+        booleans: Option<Booleans>,
+        /// Character encoding of the strings within the file (e.g. WINDOWS-1252){n}Overrides the encoding the file declares{n}Defaults to the declared encoding
+        #[arg(long, value_parser)]
+        encoding: Option<String>,
     },
-    /// Convert sas7bdat data to csv, feather (or the Arrow IPC format), ndjson, or parquet format
-    Data {
-        /// Path to sas7bdat file
+    /// Profile sas7bdat data as json
+    Profile {
+        /// Path to sas7bdat or xpt file
+        #[arg(value_hint = ValueHint::FilePath, value_parser)]
+        input: PathBuf,
+        /// Number of fixed-width histogram bins to compute for each numeric column{n}If unset, histograms are not computed
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        histogram_bins: Option<u32>,
+        /// Number of rows to stream (read into memory) at a time{n}↑ rows = ↑ memory usage{n}Defaults to 10,000 rows
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        stream_rows: Option<u32>,
+        /// Number of rows to sample uniformly from the whole file and include within the profile
+        #[arg(long, value_parser)]
+        sample_rows_in_metadata: Option<u32>,
+        /// Seed for sampling rows{n}The same seed always selects the same rows{n}Defaults to a seed derived from the current time
+        #[arg(long, value_parser)]
+        seed: Option<u64>,
+    },
+    /// Summarize each column of sas7bdat data as a table{n}Counts, missing counts, min, max, and mean of numerics; distinct values and the longest of strings; the earliest and latest of dates, times, and datetimes{n}Reads every row once without holding any in memory
+    Summary {
+        /// Path to sas7bdat or xpt file
         #[arg(value_hint = ValueHint::FilePath, value_parser)]
         input: PathBuf,
-        /// Output file path
+        /// Display the summary as json
+        #[arg(action, long, visible_alias = "json")]
+        as_json: bool,
+        /// Distinct string values counted exactly for each column{n}Beyond it the count is a lower bound, displayed as e.g. >=10000{n}Defaults to 10,000
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        distinct_cap: Option<u32>,
+    },
+    /// Convert sas7bdat data to csv, feather (or the Arrow IPC format), an Arrow IPC stream, ndjson, parquet, SPSS (sav), or Stata (dta) format
+    Data {
+        /// Path to sas7bdat or xpt file{n}Several may be given, e.g. data_2023_*.sas7bdat, to stack their rows in order into a single output; each must have the columns, types, and column order of the first
+        #[arg(value_hint = ValueHint::FilePath, value_parser, required = true)]
+        input: Vec<PathBuf>,
+        /// Output file path{n}A value of - writes to standard out, with messages written to standard error
         #[arg(long, short = 'o', value_parser)]
         output: Option<PathBuf>,
+        /// Name the output file from a template rather than --output, e.g. {stem}_{date}.parquet{n}Placeholders are {stem}, {table_name}, {date} (creation date), {subdir} (directory of the input), and {index}{n}Written next to the input
+        #[arg(long, value_parser, conflicts_with = "output")]
+        output_template: Option<String>,
         /// Output file format{n}Defaults to csv
         #[arg(ignore_case = true, long, short = 'f', value_enum, value_parser)]
         format: Option<OutFormat>,
@@ -93,34 +323,276 @@ pub enum ReadStatCliCommands {
         /// Number of rows to write
         #[arg(long, value_parser)]
         rows: Option<u32>,
+        /// Number of rows to skip from the start of the file ahead of the rows written{n}Useful to resume a conversion that stopped part of the way through{n}Defaults to 0
+        #[arg(long, value_parser)]
+        row_offset: Option<u32>,
         /// Type of reader{n}    mem = read all data into memory{n}    stream = read at most stream-rows into memory{n}Defaults to stream
         #[arg(ignore_case = true, long, value_enum, value_parser)]
         reader: Option<Reader>,
         /// Number of rows to stream (read into memory) at a time{n}↑ rows = ↑ memory usage{n}Ignored if reader is set to mem{n}Defaults to 10,000 rows
-        #[arg(long, value_parser)]
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
         stream_rows: Option<u32>,
+        /// Number of rows of every batch, whatever the reader and mem-limit{n}Takes the place of stream-rows, and of the whole file that the mem reader would otherwise read at once{n}Defaults to the rows chosen from reader, stream-rows, and mem-limit
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        batch_rows: Option<u32>,
+        /// Limit, in megabytes, on the memory the mem reader may use{n}Checked against an estimate made from the metadata before any data is read, or with --two-pass against the measured size{n}Ignored if reader is set to stream
+        #[arg(alias = "max-memory", long, value_parser = clap::value_parser!(u64).range(1..))]
+        mem_limit: Option<u64>,
+        /// Limit, in megabytes, on the memory a gzip or zstd compressed input (e.g. example.sas7bdat.gz) is decompressed into{n}Compressed inputs are decompressed into memory once, ahead of parsing, as ReadStat seeks through the file{n}Defaults to 4,096
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        max_decompressed_size: Option<u64>,
+        /// What to do when the mem reader would exceed mem-limit{n}    error = stop with an error{n}    stream = switch to the stream reader with a warning, reading batches of at most stream-rows rows small enough to stay within mem-limit{n}Defaults to error
+        #[arg(ignore_case = true, long, value_enum, value_parser)]
+        mem_limit_action: Option<MemLimitAction>,
+        /// Read the file twice: first measuring the bytes of every string, then allocating each string column exactly{n}Lowers peak memory for string-heavy files, at the cost of parsing twice{n}--mem-limit is checked against the measured size
+        #[arg(action, long)]
+        two_pass: bool,
         /// Do not display progress bar
         #[arg(action, long)]
         no_progress: bool,
-        /// Convert sas7bdat data in parallel
+        /// Do not print the rows written, nor the summary of rows, batches, bytes, and time once finished{n}Warnings are printed regardless
+        #[arg(action, long)]
+        quiet: bool,
+        /// Convert sas7bdat data in parallel{n}Batches are read upon several threads and written in the order of the file{n}Files of fewer than 100,000 rows are read upon a single thread
         #[arg(action, long)]
         parallel: bool,
+        /// Number of threads reading with --parallel{n}Defaults to one for each processor
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..), requires = "parallel")]
+        parallelism: Option<u32>,
+        /// Display the time spent parsing each batch, blocked handing it to the writer, and writing it{n}A reader mostly blocked is waiting on the writer, so --parallel will not help
+        #[arg(action, long)]
+        timings: bool,
+        /// Cast a batch whose column types differ from those of the first batch{n}Otherwise such a batch is an error naming the column and row
+        #[arg(action, long)]
+        coerce_across_batches: bool,
+        /// Recover what can be read from a damaged file rather than stopping at the first error{n}Metadata is read so long as every variable is described; data is written up to the row at which reading fails
+        #[arg(action, long)]
+        best_effort: bool,
+        /// Rename each later variable sharing the name of an earlier one to <name>_1, <name>_2, and so on{n}Otherwise variables sharing a name are an error
+        #[arg(action, long)]
+        dedup_columns: bool,
+        /// Write values that cannot be read as null rather than stopping, until more than this many have been{n}Each is reported with its row and column
+        #[arg(long, value_parser)]
+        max_errors: Option<usize>,
+        /// Also write <output>.errors.json listing the row, column, and description of each value that could not be read
+        #[arg(action, long, requires = "max_errors")]
+        error_report: bool,
         /// Parquet compression algorithm
         #[arg(long, value_enum, value_parser)]
         compression: Option<ParquetCompression>,
         /// Parquet compression level (if applicable)
         #[arg(long, value_parser = clap::value_parser!(u32).range(0..=22))]
         compression_level: Option<u32>,
+        /// Feather (Arrow IPC) compression algorithm{n}Defaults to zstd
+        #[arg(ignore_case = true, long, value_enum, value_parser)]
+        ipc_compression: Option<IpcCompression>,
+        /// Target size, in megabytes, of each parquet row group{n}Rows are accumulated until their uncompressed size reaches the target{n}Defaults to a row group for every stream-rows chunk
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        row_group_mb: Option<u32>,
+        /// Begin csv and ndjson output with a UTF-8 byte order mark{n}Needed by Excel on Windows to display accented characters correctly
+        #[arg(action, long)]
+        bom: bool,
+        /// Line ending of each record of csv and ndjson output{n}Newlines within quoted csv values are left as is{n}Defaults to lf
+        #[arg(ignore_case = true, long, value_enum, value_parser)]
+        line_ending: Option<LineEnding>,
+        /// Character separating the fields of csv output, e.g. ; or tab{n}Defaults to ,
+        #[arg(long, value_parser = parse_delimiter)]
+        delimiter: Option<u8>,
+        /// Fields of csv output that are quoted{n}    necessary = only fields holding the delimiter, a quote, or a line break{n}    always = every string field{n}    never = no field{n}Defaults to necessary
+        #[arg(ignore_case = true, long, value_enum, value_parser)]
+        quote_style: Option<CsvQuoteStyle>,
+        /// Do not begin csv output with a header of the column names
+        #[arg(action, long)]
+        no_header: bool,
+        /// Written in place of each missing value within csv output, e.g. NA{n}Defaults to an empty field
+        #[arg(long, value_parser)]
+        null_value: Option<String>,
+        /// Format of datetimes within csv output, using chrono's strftime specifiers, e.g. %Y-%m-%dT%H:%M:%S{n}Defaults to e.g. 2021-01-01 10:49:39
+        #[arg(long, value_parser)]
+        datetime_format: Option<String>,
+        /// Write numerics within csv output as their SAS formats display them, e.g. 12.5% for 0.125 formatted as PERCENT8.1{n}Applies w.d, BEST, COMMA, DOLLAR, PERCENT, and Z formats; numerics with other formats are written as without the option
+        #[arg(action, long)]
+        apply_formats: bool,
+        /// Times to retry writing a batch when the output fails with an error that may be transient, such as EIO or ESTALE from a network filesystem{n}Csv and ndjson output is re-opened and the batch written again; feather and parquet output is restarted only if no batch has yet been written{n}Defaults to 0
+        #[arg(long, value_parser)]
+        write_retries: Option<u32>,
+        /// Time to wait ahead of each retry, e.g. 500ms, 5s, or 2m{n}Defaults to 5s
+        #[arg(long, value_parser = parse_duration, requires = "write_retries")]
+        write_retry_delay: Option<Duration>,
+        /// Split output into numbered files of about this many rows each, e.g. out_000.parquet and out_001.parquet for --output out.parquet{n}Files are closed between batches, so each holds this many rows rounded up to a whole number of stream-rows batches
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        split_rows: Option<u64>,
+        /// Record the progress of the conversion beside the output, as <output>.checkpoint.json, so that should the conversion be interrupted it may be resumed with --resume{n}csv and ndjson output is appended to a single hidden segment, synced at every checkpoint and moved into place once every row is written{n}Other output is written as hidden segments of about checkpoint-rows rows, merged into the output once every row is written; the merge rewrites every segment, so about twice the output is written{n}Not supported for sav and dta output, alongside --split-rows, or when concatenating several inputs
+        #[arg(action, long, conflicts_with = "split_rows")]
+        checkpoint: bool,
+        /// Rows written between checkpoints, rounded up to a whole number of stream-rows batches{n}Implies --checkpoint{n}Defaults to 1,000,000
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..), conflicts_with = "split_rows")]
+        checkpoint_rows: Option<u64>,
+        /// Resume a checkpointed conversion that was interrupted, skipping the rows already written{n}Refused should the input or options differ from those the conversion was begun with; begins a checkpointed conversion should there be no checkpoint{n}Implies --checkpoint
+        #[arg(action, long, conflicts_with = "split_rows")]
+        resume: bool,
+        /// Arrow type for datetime columns{n}Defaults to a timestamp whose unit matches the precision of the SAS format
+        #[arg(ignore_case = true, long, value_enum, value_parser)]
+        datetime_type: Option<DateTimeType>,
+        /// Arrow type for columns with a SAS time format{n}    time32 = times of day; times of 24 hours or more and negative times are written as null with a warning{n}    duration-seconds = Int64 seconds (microseconds for formats with decimals), keeping every time{n}Defaults to time32
+        #[arg(ignore_case = true, long, value_enum, value_parser)]
+        time_handling: Option<TimeHandling>,
+        /// Store numeric columns whose SAS format displays no decimals (e.g. 8., F8.0, or COMMA6.) as Int64 rather than Float64{n}Decided from the format alone, so every batch holds the same types; a value that is not a whole number is an error
+        #[arg(action, long)]
+        infer_integers: bool,
+        /// Rounding of floating point values{n}    readstat-digits = round to 14 decimal places, as the ReadStat binary does{n}    raw = keep every value exactly as stored{n}Defaults to readstat-digits
+        #[arg(ignore_case = true, long, value_enum, value_parser)]
+        float_handling: Option<FloatHandling>,
+        /// Dates, and the dates of datetimes, read as valid, given as MIN..MAX, e.g. 1900-01-01..2100-12-31{n}Any other is written as null with a warning naming the variable and row, as is any date, datetime, or time beyond the range of its Arrow type{n}Defaults to 1582-01-01..9999-12-31
+        #[arg(long, value_parser)]
+        date_window: Option<ReadStatDateWindow>,
+        /// Read every date and datetime the Arrow type of its column holds, however far from the present
+        #[arg(action, long, conflicts_with = "date_window")]
+        no_date_window: bool,
+        /// Fail rather than write a date beyond the date window, or any date, datetime, or time beyond the range of its Arrow type, as null
+        #[arg(action, long)]
+        strict_dates: bool,
+        /// Display each date, datetime, or time written as null as it was out of range, with its row, variable, and value as stored
+        #[arg(action, long)]
+        print_date_warnings: bool,
+        /// Follow the columns with a column named <name>__missing_tag for each numeric column{n}Holds the letter of each special missing value (.A through .Z and ._), e.g. R for .R, and is empty otherwise{n}Special missing values are missing within the numeric column either way
+        #[arg(action, long)]
+        missing_tags: bool,
+        /// Whitespace trimmed from string values{n}    trailing-whitespace = trailing whitespace; leading whitespace is kept, and a blank value becomes an empty string rather than missing{n}    none = none, beyond the trailing spaces ReadStat itself drops{n}Defaults to trailing-whitespace
+        #[arg(ignore_case = true, long, value_enum, value_parser)]
+        string_trim: Option<TrimMode>,
+        /// How values among the missing values an SPSS variable declares are read{n}    as-value = kept as stored{n}    as-null = read as missing{n}Defaults to as-value
+        #[arg(ignore_case = true, long, value_enum, value_parser)]
+        user_missing: Option<UserMissing>,
+        /// Store string columns as Arrow dictionaries, each distinct value held once{n}Shrinks feather, arrow, and parquet output of columns repeating few values; csv and ndjson are written with the values
+        #[arg(action, long)]
+        dictionary_strings: bool,
+        /// Only store as dictionaries the string columns holding at most this many distinct values within the first stream-rows rows{n}Decided once, ahead of reading, so every batch has the same types{n}Defaults to every string column
+        #[arg(long, value_parser, requires = "dictionary_strings")]
+        dictionary_max_cardinality: Option<usize>,
+        /// Store 0/1 coded numerics as booleans{n}    auto = columns whose every non-missing value is 0 or 1 (reads the data twice){n}    a comma-separated list of variable names = the named columns; other values are an error
+        #[arg(long, value_parser)]
+        booleans: Option<Booleans>,
+        /// Sample plain numeric columns for values that look like dates or datetimes despite having no date format{n}    report = display the candidates{n}    apply = also read each candidate as a date or datetime, with a warning; columns whose values increase by one from row to row, like row IDs, are only ever reported{n}Defaults to report
+        #[arg(
+            ignore_case = true,
+            long,
+            value_enum,
+            value_parser,
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "report"
+        )]
+        detect_dates: Option<DetectDates>,
+        /// Fraction of the sampled values of a column that must look like dates for it to be a candidate{n}Defaults to 0.99
+        #[arg(long, value_parser, requires = "detect_dates")]
+        detect_dates_threshold: Option<f64>,
+        /// Number of rows, from the start of the file, sampled by --detect-dates{n}Defaults to 10,000 rows
+        #[arg(long, value_parser, requires = "detect_dates")]
+        detect_dates_sample: Option<u32>,
+        /// Path to the format catalog (sas7bcat) holding the value labels of the variables{n}Each variable is matched to the label set named as its format
+        #[arg(long, value_hint = ValueHint::FilePath, value_parser)]
+        catalog: Option<PathBuf>,
+        /// Write each variable having a label set within --catalog as the labels of its values{n}Values without a label are written as their text, e.g. 3 for 3.0; missing values stay missing{n}Dates, times, and datetimes are never labelled
+        #[arg(action, long, requires = "catalog")]
+        apply_value_labels: bool,
+        /// Character encoding of the strings within the file (e.g. WINDOWS-1252){n}Overrides the encoding the file declares{n}Defaults to the declared encoding
+        #[arg(long, value_parser)]
+        encoding: Option<String>,
+        /// Convert the case of string values{n}Applies to every string column unless limited by --normalize-columns
+        #[arg(ignore_case = true, long, value_enum, value_parser)]
+        normalize_strings: Option<StringCase>,
+        /// Replace every run of whitespace between words within string values with a single space
+        #[arg(action, long)]
+        collapse_whitespace: bool,
+        /// Comma-separated list of the string variables to normalize{n}Defaults to every string variable
+        #[arg(long, value_parser, value_delimiter = ',')]
+        normalize_columns: Vec<String>,
+        /// Regular expression; string variables whose names match are never normalized
+        #[arg(long, value_parser)]
+        normalize_exclude: Option<String>,
+        /// Comma-separated list of the variables to read{n}Named as within the sas7bdat file; every other variable is skipped while parsing{n}Columns are written in file order, whatever the order listed
+        #[arg(long, value_parser, value_delimiter = ',')]
+        columns: Vec<String>,
+        /// Only write the rows whose value of a variable passes a comparison, given as NAME=VALUE or with any of !=, <, <=, >, and >={n}NAME is the name within the sas7bdat file; VALUE is a date when given as YYYY-MM-DD, a number when it parses as one, and otherwise a string, as is any value in quotes{n}Rows are filtered while parsing, so rows that fail are never held; missing values never pass
+        #[arg(long, value_parser)]
+        filter: Option<ReadStatRowFilter>,
+        /// Comma-separated list of variables whose values are all written as null{n}Named as within the sas7bdat file
+        #[arg(long, value_parser, value_delimiter = ',')]
+        redact: Vec<String>,
+        /// Comma-separated list of variables whose values are written as salted hashes, each given as NAME:sha256:SALT{n}Hashes are hex strings, so each column is written as a string{n}The same value and salt always give the same hash, so hashed columns may still be joined across files
+        #[arg(long, value_parser, value_delimiter = ',')]
+        hash_columns: Vec<ReadStatHashColumn>,
+        /// Characters each variable label and SAS format may hold{n}Longer ones are truncated with a warning, their originals recorded within <output>.mapping.json
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        max_label_length: Option<u32>,
+        /// Fail rather than truncate when a label or format exceeds --max-label-length
+        #[arg(action, long, requires = "max_label_length")]
+        strict: bool,
+        /// Display a report of the Arrow type chosen for each variable and why
+        #[arg(action, long)]
+        type_report: bool,
+        /// Rename a variable, given as OLD=NEW{n}May be repeated{n}OLD is the name within the sas7bdat file
+        #[arg(long, value_parser = parse_rename)]
+        rename: Vec<(String, String)>,
+        /// Transform every variable name{n}snake-case joins lowercase words with underscores, replacing any other character with one{n}Variables the transform gives the same name are an error unless --dedup-columns is set{n}Explicit renames via --rename take precedence
+        #[arg(ignore_case = true, long, value_enum, value_parser)]
+        name_transform: Option<NameTransform>,
+        /// Csv spec of the variables to keep, rename, and cast, with the header variable,keep,new_name,type{n}Variables not listed are kept as is{n}Every row is checked against the file before any data is read; --rename and a --booleans list take precedence over the spec
+        #[arg(alias = "columns-file", long, value_hint = ValueHint::FilePath, value_parser)]
+        spec: Option<PathBuf>,
+        /// Do not write <output>.mapping.json when variable names are altered, values redacted, or labels truncated
+        #[arg(action, long)]
+        no_mapping_sidecar: bool,
+        /// Also write <stem>.schema.json containing the name, Arrow type, SAS format, label, and nullability of each column{n}Useful for restoring types when reading csv
+        #[arg(action, long)]
+        schema_sidecar: bool,
+    },
+    /// Check sas7bdat data for signs of being decoded incorrectly{n}Exits with an error when any column is flagged
+    Verify {
+        /// Path to sas7bdat or xpt file
+        #[arg(value_hint = ValueHint::FilePath, value_parser)]
+        input: PathBuf,
+        /// Flag floating point columns whose values are mostly subnormal, beyond 1e300 in magnitude, or NaN{n}These usually point to the wrong byte order or floating point representation{n}Run when no check is selected
+        #[arg(action, long)]
+        numeric_sanity: bool,
+        /// Fraction of the non-missing values of a column that must be suspect for it to be flagged{n}Defaults to 0.5
+        #[arg(long, value_parser)]
+        sanity_threshold: Option<f64>,
+        /// Number of rows to stream (read into memory) at a time{n}↑ rows = ↑ memory usage{n}Defaults to 10,000 rows
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        stream_rows: Option<u32>,
+        /// Display the findings as json
+        #[arg(action, long)]
+        as_json: bool,
+    },
+    /// Compare the variables of two sas7bdat or xpt files{n}Reports variables added and removed, differences in the type, format, label, and Arrow type of each variable, and differences in the encoding and version of the files{n}Exits with an error when any differ
+    Compare {
+        /// Path to the first sas7bdat or xpt file
+        #[arg(value_hint = ValueHint::FilePath, value_parser)]
+        left: PathBuf,
+        /// Path to the second sas7bdat or xpt file
+        #[arg(value_hint = ValueHint::FilePath, value_parser)]
+        right: PathBuf,
+        /// Display the differences as json
+        #[arg(action, long)]
+        as_json: bool,
     },
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
 #[allow(non_camel_case_types)]
 pub enum OutFormat {
+    /// Arrow IPC stream, written batch by batch
+    arrow,
     csv,
     feather,
     ndjson,
     parquet,
+    /// SPSS file, written by ReadStat
+    sav,
+    /// Stata file, written by ReadStat
+    dta,
 }
 
 impl fmt::Display for OutFormat {
@@ -129,10 +601,11 @@ impl fmt::Display for OutFormat {
     }
 }
 
-#[derive(Debug, Clone, Copy, ValueEnum)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
 #[allow(non_camel_case_types)]
 pub enum Reader {
     mem,
+    #[default]
     stream,
 }
 
@@ -142,6 +615,79 @@ impl fmt::Display for Reader {
     }
 }
 
+/// Compression of the buffers within feather (Arrow IPC) output
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum IpcCompression {
+    None,
+    Lz4,
+    #[default]
+    Zstd,
+}
+
+impl fmt::Display for IpcCompression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", &self)
+    }
+}
+
+/// What a conversion does with the chunks it reads
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum OutputMode {
+    /// Write to the output path
+    #[default]
+    Write,
+    /// Keep every chunk in memory without writing
+    Collect,
+    /// Write to the output path and keep every chunk in memory
+    WriteAndCollect,
+    /// Neither write nor keep any chunk; each is only passed on, e.g. to the closure given to
+    /// [`convert_for_each`] or by [`read_batches`]
+    Discard,
+}
+
+/// Terminator of each record of csv and ndjson output
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+impl fmt::Display for LineEnding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", &self)
+    }
+}
+
+/// Fields of csv output that are quoted
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum CsvQuoteStyle {
+    /// Only fields holding the delimiter, a quote, or a line break
+    #[default]
+    Necessary,
+    Always,
+    Never,
+}
+
+impl fmt::Display for CsvQuoteStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", &self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum MemLimitAction {
+    #[default]
+    Error,
+    Stream,
+}
+
+impl fmt::Display for MemLimitAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", &self)
+    }
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum ParquetCompression {
     Uncompressed,
@@ -158,15 +704,287 @@ impl fmt::Display for ParquetCompression {
     }
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum DateTimeType {
+    TimestampS,
+    TimestampMs,
+    TimestampUs,
+    TimestampNs,
+    Date64,
+}
+
+impl fmt::Display for DateTimeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", &self)
+    }
+}
+
+/// How floating point values are read
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum FloatHandling {
+    /// Round to 14 decimal places, as the ReadStat binary does when writing csv
+    #[default]
+    #[value(name = "readstat-digits")]
+    ReadStatDigits,
+    /// Keep every value exactly as stored
+    Raw,
+}
+
+impl fmt::Display for FloatHandling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", &self)
+    }
+}
+
+/// How variables with a SAS time format (e.g. `TIME8.`) are read
+///
+/// SAS times are seconds, which may exceed 24 hours or be negative when they hold elapsed
+/// durations rather than times of day.
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum TimeHandling {
+    /// Arrow `Time32(Second)`, or `Time64(Microsecond)` for formats displaying fractions of a
+    /// second; times outside 00:00:00 through 23:59:59 are read as missing with a warning
+    #[default]
+    Time32,
+    /// Arrow `Int64` seconds, or microseconds for formats displaying fractions of a second, the
+    /// unit recorded within the metadata of the field as `duration_unit`; every time is kept
+    DurationSeconds,
+}
+
+impl fmt::Display for TimeHandling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", &self)
+    }
+}
+
+/// Whitespace trimmed from string values as they are read
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum TrimMode {
+    /// Trailing whitespace, as pandas and haven do; leading whitespace is kept
+    #[default]
+    TrailingWhitespace,
+    /// None, beyond the trailing spaces ReadStat itself drops
+    None,
+}
+
+impl fmt::Display for TrimMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", &self)
+    }
+}
+
+/// How values among the missing values a variable declares are read
+///
+/// SPSS variables may declare up to three discrete missing values, or a range along with an
+/// optional discrete value, recorded as [`ReadStatVarMetadata::var_missing_ranges`].  SAS and
+/// Stata files declare none.
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum UserMissing {
+    /// Kept as the values stored
+    #[default]
+    AsValue,
+    /// Read as missing, as SPSS itself treats them
+    AsNull,
+}
+
+impl fmt::Display for UserMissing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", &self)
+    }
+}
+
+/// What is done with plain numeric columns that look like dates
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum DetectDates {
+    /// Display the candidates only
+    #[default]
+    Report,
+    /// Read the candidates as dates or datetimes
+    Apply,
+}
+
+impl fmt::Display for DetectDates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", &self)
+    }
+}
+
+/// Transformation applied to every variable name
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum NameTransform {
+    #[default]
+    None,
+    Lowercase,
+    Uppercase,
+    /// Lowercase words joined by underscores, e.g. `TotalHTTPCount` to `total_http_count`, and
+    /// every other character replaced by an underscore
+    SnakeCase,
+}
+
+impl NameTransform {
+    pub fn apply(&self, name: &str) -> String {
+        match self {
+            NameTransform::None => String::from(name),
+            NameTransform::Lowercase => name.to_lowercase(),
+            NameTransform::Uppercase => name.to_uppercase(),
+            NameTransform::SnakeCase => snake_case(name),
+        }
+    }
+}
+
+// Words begin at an uppercase letter following a lowercase letter or digit, or at the last of a
+//   run of uppercase letters followed by a lowercase one; runs of other characters become a
+//   single underscore and a leading digit is prefixed with one
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut s = String::with_capacity(name.len());
+
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_alphanumeric() {
+            let prev = i.checked_sub(1).map(|p| chars[p]);
+            let next = chars.get(i + 1);
+            let boundary = c.is_uppercase()
+                && prev.map_or(false, |p| {
+                    p.is_lowercase()
+                        || p.is_numeric()
+                        || (p.is_uppercase() && next.map_or(false, |n| n.is_lowercase()))
+                });
+            if boundary && !s.ends_with('_') {
+                s.push('_');
+            }
+            s.extend(c.to_lowercase());
+        } else if !s.ends_with('_') && (!s.is_empty() || *c == '_') {
+            // a leading underscore, as in _N_, is kept
+            s.push('_');
+        }
+    }
+
+    let s = match s.trim_end_matches('_') {
+        "" => "_",
+        trimmed => trimmed,
+    };
+    if s.starts_with(|c: char| c.is_numeric()) {
+        format!("_{}", s)
+    } else {
+        String::from(s)
+    }
+}
+
+impl fmt::Display for NameTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", &self)
+    }
+}
+
+/// Case to which string values are converted
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum StringCase {
+    Upper,
+    Lower,
+}
+
+impl fmt::Display for StringCase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", &self)
+    }
+}
+
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (n, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+    let n: u64 = n
+        .parse()
+        .map_err(|_| String::from("expected a duration such as 500ms, 5s, or 2m"))?;
+    match unit {
+        "ms" => Ok(Duration::from_millis(n)),
+        "" | "s" => Ok(Duration::from_secs(n)),
+        "m" => Ok(Duration::from_secs(n * 60)),
+        _ => Err(String::from("expected a duration such as 500ms, 5s, or 2m")),
+    }
+}
+
+fn parse_delimiter(s: &str) -> Result<u8, String> {
+    match s {
+        "tab" | "\\t" | "\t" => Ok(b'\t'),
+        _ => match s.as_bytes() {
+            [b] if b.is_ascii() && !matches!(*b, b'"' | b'\n' | b'\r') => Ok(*b),
+            _ => Err(String::from(
+                "expected a single ASCII character other than a quote or line break, or tab",
+            )),
+        },
+    }
+}
+
+fn parse_rename(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((old, new)) if !old.trim().is_empty() && !new.trim().is_empty() => {
+            Ok((String::from(old.trim()), String::from(new.trim())))
+        }
+        _ => Err(String::from("expected OLD=NEW")),
+    }
+}
+
+/// Numeric columns to store as Arrow booleans
+#[derive(Debug, Clone, PartialEq)]
+pub enum Booleans {
+    /// Columns whose every non-missing value is 0 or 1, found by first reading the data
+    Auto,
+    /// The named columns; any value other than 0 or 1 is an error
+    Explicit(Vec<String>),
+}
+
+impl std::str::FromStr for Booleans {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("auto") {
+            return Ok(Booleans::Auto);
+        }
+
+        let names: Vec<String> = s
+            .split(',')
+            .map(|n| n.trim())
+            .filter(|n| !n.is_empty())
+            .map(String::from)
+            .collect();
+
+        if names.is_empty() {
+            Err(String::from("expected auto or a comma-separated list of variable names"))
+        } else {
+            Ok(Booleans::Explicit(names))
+        }
+    }
+}
+
 pub fn run(rs: ReadStatCli) -> Result<(), Box<dyn Error + Send + Sync>> {
     env_logger::init();
 
-    match rs.command {
+    if rs.version_info {
+        ReadStatVersionInfo::new().write_to_stdout();
+        return Ok(());
+    }
+
+    let command = match rs.command {
+        Some(c) => c,
+        None => return Err(From::from("A subcommand is required; see readstat --help")),
+    };
+
+    match command {
         ReadStatCliCommands::Metadata {
             input: in_path,
             as_json,
             no_progress: _,
             skip_row_count,
+            type_report,
+            best_effort,
+            dedup_columns,
+            sample_rows_in_metadata,
+            seed,
+            encoding,
+            estimate_size,
+            max_label_length,
+            strict,
+            catalog,
         } => {
             // Validate and create path to sas7bdat/sas7bcat
             let sas_path = PathAbs::new(in_path)?.as_path().to_path_buf();
@@ -177,25 +995,86 @@ pub fn run(rs: ReadStatCli) -> Result<(), Box<dyn Error + Send + Sync>> {
 
             // out_path and format determine the type of writing performed
             let rsp = ReadStatPath::new(sas_path, None, None, false, false, None, None)?;
+            print_warnings(&rsp);
 
             // Instantiate ReadStatMetadata
-            let mut md = ReadStatMetadata::new();
+            let mut md = ReadStatMetadata::new()
+                .set_encoding(encoding)
+                .set_best_effort(best_effort)
+                .set_dedup_columns(dedup_columns);
 
             // Read metadata
             md.read_metadata(&rsp, skip_row_count)?;
+            if let Some(e) = &md.salvage_error {
+                eprintln!(
+                    "{}: metadata was read in full though parsing stopped with the error {}",
+                    "Warning".bright_yellow(),
+                    e.bright_red()
+                );
+            }
+
+            // Labels and formats
+            if let Some(max) = max_label_length {
+                for w in md.limit_label_lengths(max as usize, strict)? {
+                    eprintln!("{}", w);
+                }
+            }
+
+            // Value labels
+            if let Some(c) = &catalog {
+                for w in md.read_value_labels(c)? {
+                    eprintln!("{}", w);
+                }
+            }
+
+            // Sample rows
+            if let Some(n) = sample_rows_in_metadata {
+                md.sample_rows = Some(sample_rows(
+                    &rsp,
+                    &md,
+                    n as usize,
+                    seed.unwrap_or_else(default_seed),
+                    STREAM_ROWS,
+                )?);
+            }
+
+            // Schema fingerprint
+            md.schema_fingerprint = Some(ReadStatSchema::new(&md).fingerprint);
+
+            // Size estimate
+            if estimate_size {
+                md.size_estimate = Some(md.estimate_size(md.row_count.max(0) as u32));
+            }
 
             // Write metadata
             ReadStatWriter::new().write_metadata(&md, &rsp, as_json)?;
 
+            // Write type report
+            if type_report {
+                println!("\n{}", md.type_report());
+            }
+
             // Return
             Ok(())
         }
         ReadStatCliCommands::Preview {
             input,
             rows,
+            row_offset,
+            table,
             reader,
             stream_rows,
             no_progress,
+            datetime_type,
+            time_handling,
+            infer_integers,
+            dedup_columns,
+            float_handling,
+            missing_tags,
+            string_trim,
+            user_missing,
+            booleans,
+            encoding,
         } => {
             // Validate and create path to sas7bdat/sas7bcat
             let sas_path = PathAbs::new(input)?.as_path().to_path_buf();
@@ -214,15 +1093,54 @@ pub fn run(rs: ReadStatCli) -> Result<(), Box<dyn Error + Send + Sync>> {
                 None,
                 None,
             )?;
+            print_warnings(&rsp);
 
             // instantiate ReadStatMetadata
-            let mut md = ReadStatMetadata::new();
+            let mut md = ReadStatMetadata::new()
+                .set_datetime_type(datetime_type)
+                .set_time_handling(time_handling.unwrap_or_default())
+                .set_infer_integers(infer_integers)
+                .set_dedup_columns(dedup_columns)
+                .set_encoding(encoding.clone());
+
+            // Read metadata; a table reads only the rows it displays, so needs no row count,
+            //   unless booleans are detected by reading every row
+            md.read_metadata(&rsp, table && booleans.is_none())?;
+            md.require_variables(&rsp)?;
+
+            // Booleans
+            if let Some(b) = &booleans {
+                apply_booleans(&rsp, &mut md, b, stream_rows.unwrap_or(STREAM_ROWS))?;
+            }
 
-            // Read metadata
-            md.read_metadata(&rsp, false)?;
+            // Display as a table
+            if table {
+                let d = ReadStatData::new()
+                    .set_no_progress(true)
+                    .set_float_handling(float_handling.unwrap_or_default())
+                    .set_missing_tags(missing_tags)
+                    .set_string_trim(string_trim.unwrap_or_default())
+                    .set_user_missing(user_missing.unwrap_or_default())
+                    .get_preview(md.clone(), &rsp, rows as usize)?;
+
+                if let Some(c) = d.chunk() {
+                    let names: Vec<&str> =
+                        d.schema().fields.iter().map(|f| f.name.as_str()).collect();
+                    println!("{}", arrow2::io::print::write(&[c.clone()], &names));
+                }
+
+                let declared = encoding.unwrap_or(md.file_encoding);
+                if let Some(w) = d.encoding_detector().warning(&declared) {
+                    eprintln!("{}", w);
+                }
+
+                return Ok(());
+            }
 
-            // Determine row count
-            let total_rows_to_process = std::cmp::min(rows, md.row_count as u32);
+            // Determine row count, from the offset on
+            let row_offset = row_offset.unwrap_or(0);
+            let total_rows_to_process =
+                std::cmp::min(rows, (md.row_count as u32).saturating_sub(row_offset));
 
             // Determine stream row count
             // 📝 Default stream rows set to 10,000
@@ -245,6 +1163,9 @@ pub fn run(rs: ReadStatCli) -> Result<(), Box<dyn Error + Send + Sync>> {
             // Initialize writing
             let mut wtr = ReadStatWriter::new();
 
+            // String bytes observed across every batch
+            let mut detector = ReadStatEncodingDetector::new();
+
             // Process data in batches (i.e. stream chunks of rows)
             // Read data - for each iteration create a new instance of ReadStatData
             for (i, w) in offsets_pairs.enumerate() {
@@ -256,10 +1177,16 @@ pub fn run(rs: ReadStatCli) -> Result<(), Box<dyn Error + Send + Sync>> {
                     .set_no_progress(no_progress)
                     .set_total_rows_to_process(total_rows_to_process as usize)
                     .set_total_rows_processed(total_rows_processed.clone())
-                    .init(md.clone(), row_start, row_end);
+                    .set_float_handling(float_handling.unwrap_or_default())
+                    .set_missing_tags(missing_tags)
+                    .set_string_trim(string_trim.unwrap_or_default())
+                    .set_user_missing(user_missing.unwrap_or_default())
+                    .init(md.clone(), row_start, row_end)
+                    .set_row_offset(row_offset);
 
                 // Read
                 d.read_data(&rsp)?;
+                detector.merge(&d.encoding_detector);
 
                 // Write
                 wtr.write(&d, &rsp)?;
@@ -273,6 +1200,12 @@ pub fn run(rs: ReadStatCli) -> Result<(), Box<dyn Error + Send + Sync>> {
             // Finish writer
             //wtr.finish(&d, &rsp)?;
 
+            // Preview data is written to standard out, so warn on standard error
+            let declared = encoding.unwrap_or(md.file_encoding);
+            if let Some(w) = detector.warning(&declared) {
+                eprintln!("{}", w);
+            }
+
             // Return
             Ok(())
 
@@ -283,27 +1216,192 @@ pub fn run(rs: ReadStatCli) -> Result<(), Box<dyn Error + Send + Sync>> {
             };
             */
         }
+        ReadStatCliCommands::Profile {
+            input,
+            histogram_bins,
+            stream_rows,
+            sample_rows_in_metadata,
+            seed,
+        } => {
+            // Validate and create path to sas7bdat/sas7bcat
+            let sas_path = PathAbs::new(input)?.as_path().to_path_buf();
+            debug!(
+                "Profiling data from the file {}",
+                &sas_path.to_string_lossy()
+            );
+
+            let rsp = ReadStatPath::new(sas_path, None, None, false, false, None, None)?;
+            print_warnings(&rsp);
+
+            // Profile
+            let mut profile = ReadStatProfile::new(
+                &rsp,
+                histogram_bins.map(|b| b as usize),
+                stream_rows.unwrap_or(STREAM_ROWS),
+            )?;
+
+            // Sample rows
+            if let Some(n) = sample_rows_in_metadata {
+                let mut md = ReadStatMetadata::new().set_dedup_columns(true);
+                md.read_metadata(&rsp, false)?;
+                profile.sample_rows = Some(sample_rows(
+                    &rsp,
+                    &md,
+                    n as usize,
+                    seed.unwrap_or_else(default_seed),
+                    stream_rows.unwrap_or(STREAM_ROWS),
+                )?);
+            }
+
+            // Write profile
+            println!("{}", serde_json::to_string_pretty(&profile)?);
+
+            // Return
+            Ok(())
+        }
+        ReadStatCliCommands::Summary {
+            input,
+            as_json,
+            distinct_cap,
+        } => {
+            // Validate and create path to sas7bdat/sas7bcat
+            let sas_path = PathAbs::new(input)?.as_path().to_path_buf();
+            debug!(
+                "Summarizing data from the file {}",
+                &sas_path.to_string_lossy()
+            );
+
+            let rsp = ReadStatPath::new(sas_path, None, None, false, false, None, None)?;
+            print_warnings(&rsp);
+
+            // Summarize
+            let summary = ReadStatSummary::new(
+                &rsp,
+                distinct_cap.map_or(DISTINCT_CAP, |c| c as usize),
+            )?;
+
+            // Write summary
+            if as_json {
+                println!("{}", serde_json::to_string_pretty(&summary)?);
+            } else {
+                print!("{}", summary.to_table());
+            }
+
+            // Return
+            Ok(())
+        }
         ReadStatCliCommands::Data {
             input,
             output,
+            output_template,
             format,
             rows,
+            row_offset,
             reader,
             stream_rows,
+            batch_rows,
+            mem_limit,
+            max_decompressed_size,
+            mem_limit_action,
+            two_pass,
             no_progress,
+            quiet,
             overwrite,
             parallel,
+            parallelism,
+            timings,
+            coerce_across_batches,
+            best_effort,
+            dedup_columns,
+            max_errors,
+            error_report,
             compression,
             compression_level,
+            ipc_compression,
+            row_group_mb,
+            bom,
+            line_ending,
+            delimiter,
+            quote_style,
+            no_header,
+            null_value,
+            datetime_format,
+            apply_formats,
+            write_retries,
+            write_retry_delay,
+            split_rows,
+            checkpoint,
+            checkpoint_rows,
+            resume,
+            datetime_type,
+            time_handling,
+            infer_integers,
+            float_handling,
+            date_window,
+            no_date_window,
+            strict_dates,
+            print_date_warnings,
+            missing_tags,
+            string_trim,
+            user_missing,
+            dictionary_strings,
+            dictionary_max_cardinality,
+            booleans,
+            detect_dates,
+            detect_dates_threshold,
+            detect_dates_sample,
+            catalog,
+            apply_value_labels,
+            encoding,
+            normalize_strings,
+            collapse_whitespace,
+            normalize_columns,
+            normalize_exclude,
+            columns,
+            filter,
+            redact,
+            hash_columns,
+            max_label_length,
+            strict,
+            type_report,
+            rename,
+            name_transform,
+            spec,
+            no_mapping_sidecar,
+            schema_sidecar,
         } => {
-            // Validate and create path to sas7bdat/sas7bcat
-            let sas_path = PathAbs::new(input)?.as_path().to_path_buf();
+            // Validate and create path to sas7bdat/sas7bcat; any further inputs are concatenated
+            let mut sas_paths = Vec::with_capacity(input.len());
+            for p in input {
+                sas_paths.push(PathAbs::new(p)?.as_path().to_path_buf());
+            }
+            let sas_path = sas_paths.remove(0);
             debug!(
                 "Generating data from the file {}",
                 &sas_path.to_string_lossy()
             );
 
+            // Parse the column spec ahead of reading anything
+            let column_spec = spec
+                .map(|p| ReadStatColumnSpec::from_path(&p))
+                .transpose()?;
+
+            // Name the output from a template
+            let output = match &output_template {
+                Some(t) => {
+                    let mut outputs = ReadStatOutputTemplate::parse(t)?.resolve(
+                        &[sas_path.clone()],
+                        None,
+                        format.unwrap_or(OutFormat::csv),
+                    )?;
+                    Some(outputs.remove(0)?)
+                }
+                None => output,
+            };
+
             // output and format determine the type of writing to be performed
+            let max_decompressed_bytes = max_decompressed_size
+                .map_or(DEFAULT_MAX_DECOMPRESSED_BYTES, |m| m.saturating_mul(1024 * 1024));
             let rsp = ReadStatPath::new(
                 sas_path,
                 output,
@@ -312,11 +1410,76 @@ pub fn run(rs: ReadStatCli) -> Result<(), Box<dyn Error + Send + Sync>> {
                 false,
                 compression,
                 compression_level,
-            )?;
+            )?
+            .set_max_decompressed_bytes(max_decompressed_bytes);
+            print_warnings(&rsp);
+
+            // Further inputs, whose rows follow those of the first
+            let mut concat = Vec::with_capacity(sas_paths.len());
+            for p in sas_paths {
+                concat.push(
+                    ReadStatPath::new(p, None, None, false, false, None, None)?
+                        .set_max_decompressed_bytes(max_decompressed_bytes),
+                );
+            }
+            if !concat.is_empty() && rsp.out_path.is_none() {
+                return Err(From::from(format!(
+                    "A value for {} is needed to concatenate several inputs",
+                    "--output".bright_cyan()
+                )));
+            }
+            if !concat.is_empty() && (checkpoint || checkpoint_rows.is_some() || resume) {
+                return Err(From::from(
+                    "Checkpointing is not supported when concatenating several inputs",
+                ));
+            }
 
-            // Instantiate ReadStatMetadata
-            let mut md = ReadStatMetadata::new();
-            md.read_metadata(&rsp, false)?;
+            // Columns to redact
+            let redact = ReadStatRedact {
+                redact,
+                hash: hash_columns,
+            };
+
+            // Dates stored as plain numerics
+            let detect_dates_threshold = detect_dates_threshold.unwrap_or(DETECT_DATES_THRESHOLD);
+            if !(0.0..=1.0).contains(&detect_dates_threshold) {
+                return Err(From::from(
+                    "The value of --detect-dates-threshold must be between 0 and 1",
+                ));
+            }
+            let detect_dates_sample = detect_dates_sample.unwrap_or(DETECT_DATES_SAMPLE_ROWS);
+
+            // Write type report
+            if type_report {
+                let mut md = ReadStatMetadata::new()
+                    .set_datetime_type(datetime_type)
+                    .set_time_handling(time_handling.unwrap_or_default())
+                    .set_infer_integers(infer_integers)
+                    .set_best_effort(best_effort)
+                    .set_dedup_columns(dedup_columns)
+                    .set_apply_value_labels(apply_value_labels);
+                md.read_metadata(&rsp, booleans.is_none())?;
+                if let Some(b) = &booleans {
+                    apply_booleans(&rsp, &mut md, b, stream_rows.unwrap_or(STREAM_ROWS))?;
+                }
+                if let Some(DetectDates::Apply) = detect_dates {
+                    apply_dates(
+                        &rsp,
+                        &mut md,
+                        DetectDates::Apply,
+                        detect_dates_threshold,
+                        detect_dates_sample,
+                        stream_rows.unwrap_or(STREAM_ROWS),
+                    )?;
+                }
+                if let Some(c) = &catalog {
+                    for w in md.read_value_labels(c)? {
+                        eprintln!("{}", w);
+                    }
+                }
+                redact.apply(&mut md)?;
+                print_message(&rsp, &md.type_report());
+            }
 
             // If no output path then only read metadata; otherwise read data
             match &rsp.out_path {
@@ -324,7 +1487,10 @@ pub fn run(rs: ReadStatCli) -> Result<(), Box<dyn Error + Send + Sync>> {
                     println!("{}: a value was not provided for the parameter {}, thus displaying metadata only\n", "Warning".bright_yellow(), "--output".bright_cyan());
 
                     // Instantiate ReadStatMetadata
-                    let mut md = ReadStatMetadata::new();
+                    let mut md = ReadStatMetadata::new()
+                        .set_encoding(encoding)
+                        .set_best_effort(best_effort)
+                        .set_dedup_columns(dedup_columns);
                     md.read_metadata(&rsp, false)?;
 
                     // Write metadata
@@ -334,146 +1500,522 @@ pub fn run(rs: ReadStatCli) -> Result<(), Box<dyn Error + Send + Sync>> {
                     Ok(())
                 }
                 Some(p) => {
-                    println!(
-                        "Writing parsed data to file {}",
-                        p.to_string_lossy().bright_yellow()
-                    );
-
-                    // Determine row count
-                    let total_rows_to_process = if let Some(r) = rows {
-                        std::cmp::min(r, md.row_count as u32)
+                    if rsp.writes_to_stdout() {
+                        eprintln!("Writing parsed data to standard out");
+                    } else if rsp.writes_to_special_file() {
+                        eprintln!(
+                            "Writing parsed data to {}",
+                            p.to_string_lossy().bright_yellow()
+                        );
                     } else {
-                        md.row_count as u32
-                    };
-
-                    // Determine stream row count
-                    // 📝 Default stream rows set to 10,000
-                    let total_rows_to_stream = match reader {
-                        Some(Reader::stream) | None => match stream_rows {
-                            Some(s) => s,
-                            None => STREAM_ROWS,
-                        },
-                        Some(Reader::mem) => total_rows_to_process,
-                    };
+                        println!(
+                            "Writing parsed data to file {}",
+                            p.to_string_lossy().bright_yellow()
+                        );
+                    }
 
-                    // Initialize AtomicUsize to contain total rows processed
-                    let total_rows_processed = Arc::new(std::sync::atomic::AtomicUsize::new(0));
-
-                    // Build up offsets
-                    let offsets = build_offsets(total_rows_to_process, total_rows_to_stream)?;
-
-                    // Create channels with a capacity of 10
-                    // Unbounded channels can result in extreme memory usage if files are large and
-                    //   the reader significantly outpaces the writer
-                    let (s, r) = bounded(10);
-
-                    // Process data in batches (i.e. stream chunks of rows)
-                    thread::spawn(move || -> Result<(), Box<dyn Error + Send + Sync>> {
-                        // Create windows
-                        let offsets_pairs = offsets.par_windows(2);
-                        let pairs_cnt = offsets_pairs.len();
-
-                        // Run in parallel or not?
-                        // Controlled via number of threads in the rayon threadpool
-                        if !parallel {
-                            rayon::ThreadPoolBuilder::new()
-                                .num_threads(1)
-                                .build_global()?;
+                    let reader = reader.unwrap_or(Reader::stream);
+                    let mem_limit = mem_limit.map(|mb| mb.saturating_mul(1024 * 1024));
+                    let mem_limit_action = mem_limit_action.unwrap_or_default();
+
+                    // Check whether the file is too large to read into memory; with two passes the
+                    //   check is made once measured
+                    if let (Reader::mem, Some(_), false) = (reader, mem_limit, two_pass) {
+                        let mut md = ReadStatMetadata::new()
+                            .set_datetime_type(datetime_type)
+                            .set_time_handling(time_handling.unwrap_or_default())
+                            .set_infer_integers(infer_integers)
+                            .set_best_effort(best_effort)
+                            .set_dedup_columns(dedup_columns);
+                        md.read_metadata(&rsp, false)?;
+                        if !columns.is_empty() {
+                            md.set_columns(&columns)?;
+                        }
+                        let rows_after_offset =
+                            (md.row_count as u32).saturating_sub(row_offset.unwrap_or(0));
+                        let rows_to_read = match rows {
+                            Some(r) => std::cmp::min(r, rows_after_offset),
+                            None => rows_after_offset,
                         };
 
-                        // Iterate over offset pairs, reading data for each iteration and then
-                        //   sending the results over a channel to the writer
-                        // 📝 For each iteration a new instance of ReadStatData is created
-                        // for w in offsets_pairs {
-                        let errors: Vec<_> = offsets_pairs
-                            .map(|w| -> Result<(), Box<dyn Error + Send + Sync>> {
-                                let row_start = w[0];
-                                let row_end = w[1];
-
-                                // Initialize ReadStatData struct
-                                let mut d = ReadStatData::new()
-                                    .set_no_progress(no_progress)
-                                    .set_total_rows_to_process(total_rows_to_process as usize)
-                                    .set_total_rows_processed(total_rows_processed.clone())
-                                    .init(md.clone(), row_start, row_end);
-
-                                // Read
-                                d.read_data(&rsp)?;
-
-                                // Send
-                                let sent = s.send((d, rsp.clone(), pairs_cnt));
-
-                                // Early return if an error
-                                if sent.is_err() {
-                                    Err(From::from(
-                                        "Error when attempting to send read data for writing",
-                                    ))
-                                } else {
-                                    Ok(())
-                                }
-                            })
-                            .filter_map(|r| -> Option<Box<dyn Error + Send + Sync>> {
-                                match r {
-                                    Ok(()) => None,
-                                    Err(e) => Some(e),
-                                }
-                            })
-                            .collect();
-
-                        // Drop sender so that receive iterator will eventually exit
-                        drop(s);
-
-                        if !errors.is_empty() {
-                            println!("The following errors occured when processing data:");
-                            for e in errors {
-                                println!("    Error: {:#?}", e);
-                            }
+                        let (_, warning) = resolve_reader(
+                            &md,
+                            rows_to_read,
+                            reader,
+                            mem_limit,
+                            mem_limit_action,
+                        )?;
+                        if let Some(w) = warning {
+                            print_message(&rsp, &w);
                         }
+                    }
 
-                        // Return
-                        Ok(())
-                    });
-
-                    // Write
-
-                    // Initialize writing
-                    let mut wtr = ReadStatWriter::new();
+                    // 📝 Default stream rows set to 10,000
+                    let options = ReadStatConvertOptions {
+                        ipc_compression: ipc_compression.unwrap_or_default(),
+                        row_group_bytes: row_group_mb.map(|mb| mb as usize * 1024 * 1024),
+                        bom,
+                        line_ending: line_ending.unwrap_or_default(),
+                        csv: ReadStatCsvOptions {
+                            delimiter: delimiter.unwrap_or(b','),
+                            quote_style: quote_style.unwrap_or_default(),
+                            write_header: !no_header,
+                            null_value: null_value.unwrap_or_default(),
+                            datetime_format,
+                        },
+                        apply_formats,
+                        write_retries: write_retries.unwrap_or(0),
+                        write_retry_delay: write_retry_delay.unwrap_or(WRITE_RETRY_DELAY),
+                        split_rows: split_rows.map(|r| r as usize),
+                        checkpoint: checkpoint || checkpoint_rows.is_some(),
+                        checkpoint_rows: checkpoint_rows.map_or(CHECKPOINT_ROWS, |r| r as usize),
+                        resume,
+                        rows,
+                        row_offset: row_offset.unwrap_or(0),
+                        reader,
+                        stream_rows: stream_rows.unwrap_or(STREAM_ROWS),
+                        batch_rows: batch_rows.and_then(NonZeroU32::new),
+                        mem_limit,
+                        mem_limit_action,
+                        two_pass,
+                        no_progress,
+                        parallel,
+                        parallelism: parallelism.map(|p| p as usize),
+                        coerce_across_batches,
+                        best_effort,
+                        dedup_columns,
+                        error_mode: max_errors
+                            .map_or(ErrorMode::FailFast, |max| ErrorMode::Collect { max }),
+                        datetime_type,
+                        time_handling: time_handling.unwrap_or_default(),
+                        infer_integers,
+                        float_handling: float_handling.unwrap_or_default(),
+                        date_window: if no_date_window {
+                            None
+                        } else {
+                            Some(date_window.unwrap_or_default())
+                        },
+                        strict_dates,
+                        missing_tags,
+                        string_trim: string_trim.unwrap_or_default(),
+                        user_missing: user_missing.unwrap_or_default(),
+                        string_encoding: if dictionary_strings {
+                            StringEncoding::Dictionary {
+                                max_cardinality: dictionary_max_cardinality,
+                            }
+                        } else {
+                            StringEncoding::Plain
+                        },
+                        booleans,
+                        detect_dates,
+                        detect_dates_threshold,
+                        detect_dates_sample_rows: detect_dates_sample,
+                        catalog,
+                        apply_value_labels,
+                        encoding,
+                        normalize_strings: ReadStatStringNormalize {
+                            case: normalize_strings,
+                            collapse_whitespace,
+                            columns: normalize_columns,
+                            exclude: normalize_exclude,
+                        },
+                        redact,
+                        max_label_length: max_label_length.map(|m| m as usize),
+                        strict,
+                        renames: rename,
+                        name_transform: name_transform.unwrap_or_default(),
+                        column_spec,
+                        columns,
+                        row_filter: filter,
+                        mapping_sidecar: !no_mapping_sidecar,
+                        schema_sidecar,
+                        error_report,
+                        ..ReadStatConvertOptions::default()
+                    };
 
-                    for (i, (d, rsp, pairs_cnt)) in r.iter().enumerate() {
-                        wtr.write(&d, &rsp)?;
+                    // Stack the rows of every input into the single output
+                    if !concat.is_empty() {
+                        let mut inputs = vec![rsp.clone()];
+                        inputs.extend(concat);
+                        return convert_concat(&rsp, inputs, &options, no_progress);
+                    }
 
-                        if i == (pairs_cnt - 1) {
-                            wtr.finish(&d, &rsp)?;
+                    // Convert, then render the outcome
+                    let collected = convert_collect(&rsp, &options)?;
+                    for w in &collected.warnings {
+                        print_message(&rsp, w);
+                    }
+                    if collected.stats.rows_resumed > 0 {
+                        print_message(
+                            &rsp,
+                            &format!(
+                                "Resumed after {} rows already read by an interrupted conversion",
+                                collected.stats.rows_resumed.to_formatted_string(&Locale::en)
+                            ),
+                        );
+                    }
+                    if reader == Reader::mem && collected.stats.reader == Reader::stream {
+                        print_message(
+                            &rsp,
+                            &format!(
+                                "Streamed {} rows at a time to stay within the memory limit",
+                                collected.stats.batch_rows.to_formatted_string(&Locale::en)
+                            ),
+                        );
+                    }
+                    if let Some(d) = &collected.date_detection {
+                        if d.candidates().is_empty() {
+                            print_message(
+                                &rsp,
+                                &format!(
+                                    "No column of the {} rows sampled looks like an unformatted date",
+                                    d.sampled_rows.to_formatted_string(&Locale::en)
+                                ),
+                            );
+                        } else {
+                            print_message(&rsp, &format!("\nDate candidates\n{}", d.report()));
+                        }
+                    }
+                    if !quiet {
+                        print_total_rows_written(&rsp, collected.rows);
+                        print_split_paths(&rsp, &collected.written_paths);
+                    }
+                    for (name, n) in &collected.strings_normalized {
+                        print_message(
+                            &rsp,
+                            &format!(
+                                "Normalized {} values of column {}",
+                                n.to_formatted_string(&Locale::en),
+                                name.bright_yellow()
+                            ),
+                        );
+                    }
+                    if !collected.cell_errors.is_empty() {
+                        print_message(
+                            &rsp,
+                            &format!(
+                                "{}: {} values could not be read and were written as null",
+                                "Warning".bright_yellow(),
+                                collected.cell_errors.len().to_formatted_string(&Locale::en)
+                            ),
+                        );
+                    }
+                    if print_date_warnings {
+                        for w in &collected.date_range_warnings {
+                            print_message(
+                                &rsp,
+                                &format!(
+                                    "Row {} of column {} was written as null; its value as stored is {}",
+                                    w.row,
+                                    w.column.bright_yellow(),
+                                    w.value
+                                ),
+                            );
                         }
-
-                        // Explicitly drop to save on memory
-                        drop(d);
+                    }
+                    if timings {
+                        print_timings(&rsp, &collected.timings);
+                    }
+                    if !collected.warning_summary.is_empty() {
+                        let summary = collected.warning_summary.summary();
+                        print_message(&rsp, &format!("\nWarnings\n{}", summary.trim_end()));
+                    }
+                    if !quiet {
+                        print_stats(&rsp, &collected.stats);
                     }
 
                     // Return
                     Ok(())
+                }
+            }
+        }
+        ReadStatCliCommands::Verify {
+            input,
+            // Currently the only check, so it runs whether or not it is selected
+            numeric_sanity: _,
+            sanity_threshold,
+            stream_rows,
+            as_json,
+        } => {
+            // Validate and create path to sas7bdat/sas7bcat
+            let sas_path = PathAbs::new(input)?.as_path().to_path_buf();
+            debug!(
+                "Verifying data from the file {}",
+                &sas_path.to_string_lossy()
+            );
 
-                    // progress bar
-                    /*
-                    if let Some(pb) = &d.pb {
-                        pb.finish_at_current_pos()
-                    };
-                    */
-
-                    // get and optionally write data - single or multi-threaded
-                    /*
-                    if let Some(_p) = parallel {
-                        let cpu_logical_count: usize = num_cpus::get();
-                        let cpu_physical_count: usize = num_cpus::get_physical();
-                        println!("Logical count {:?}", cpu_logical_count);
-                        println!("Physical count {:?}", cpu_physical_count);
-
-                        d.get_row_count()?;
-                        println!("Row count {:?}", d.metadata.row_count);
-                        Ok(())
-                    */
+            let rsp = ReadStatPath::new(sas_path, None, None, false, false, None, None)?;
+            print_warnings(&rsp);
+
+            // Numeric sanity
+            let threshold = sanity_threshold.unwrap_or(NUMERIC_SANITY_THRESHOLD);
+            if !(0.0..=1.0).contains(&threshold) {
+                return Err(From::from(
+                    "The value of --sanity-threshold must be between 0 and 1",
+                ));
+            }
+            let report =
+                check_numeric_sanity(&rsp, threshold, stream_rows.unwrap_or(STREAM_ROWS))?;
+            let flagged = report.flagged();
+
+            // Report
+            if as_json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                for c in &flagged {
+                    let samples: Vec<String> = c
+                        .samples
+                        .iter()
+                        .map(|s| format!("{:e} ({})", s.value, s.bits))
+                        .collect();
+                    println!(
+                        "{}: column {} has {} suspect values of {} ({} subnormal, {} huge, {} NaN); e.g. {}",
+                        "Warning".bright_yellow(),
+                        c.var_name.bright_yellow(),
+                        c.suspect().to_formatted_string(&Locale::en),
+                        c.values.to_formatted_string(&Locale::en),
+                        c.subnormal.to_formatted_string(&Locale::en),
+                        c.huge.to_formatted_string(&Locale::en),
+                        c.nan.to_formatted_string(&Locale::en),
+                        samples.join(", ")
+                    );
+                }
+                if flagged.is_empty() {
+                    println!(
+                        "Checked {} floating point columns; none look misread",
+                        report.columns.len()
+                    );
+                }
+            }
+
+            // Return
+            if flagged.is_empty() {
+                Ok(())
+            } else {
+                Err(From::from(format!(
+                    "{} columns failed the numeric sanity check; the file may have been written with a different byte order or floating point representation",
+                    flagged.len()
+                )))
+            }
+        }
+        ReadStatCliCommands::Compare {
+            left,
+            right,
+            as_json,
+        } => {
+            // Metadata only; row counts are not compared
+            let mut mds = Vec::new();
+            for input in [&left, &right] {
+                let sas_path = PathAbs::new(input)?.as_path().to_path_buf();
+                debug!(
+                    "Comparing metadata from the file {}",
+                    &sas_path.to_string_lossy()
+                );
+
+                let rsp = ReadStatPath::new(sas_path, None, None, false, false, None, None)?;
+                print_warnings(&rsp);
+
+                let mut md = ReadStatMetadata::new();
+                md.read_metadata(&rsp, true)?;
+                mds.push(md);
+            }
+            let diff = compare_metadata(&mds[0], &mds[1]);
+
+            // Report
+            if as_json {
+                println!("{}", serde_json::to_string_pretty(&diff)?);
+            } else if diff.is_empty() {
+                println!(
+                    "The variables of {} and {} do not differ",
+                    left.to_string_lossy(),
+                    right.to_string_lossy()
+                );
+            } else {
+                for line in diff.lines() {
+                    match line.chars().next() {
+                        Some('-') => println!("{}", line.bright_red()),
+                        Some('+') => println!("{}", line.bright_green()),
+                        _ => println!("{}", line.bright_yellow()),
+                    }
                 }
             }
+
+            // Return
+            if diff.is_empty() {
+                Ok(())
+            } else {
+                Err(From::from(format!(
+                    "{} differences between {} and {}",
+                    diff.len(),
+                    left.to_string_lossy().bright_red(),
+                    right.to_string_lossy().bright_red()
+                )))
+            }
         }
     }
 }
+
+fn default_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64)
+}
+
+fn print_warnings(rsp: &ReadStatPath) {
+    for w in &rsp.warnings {
+        print_message(rsp, w);
+    }
+}
+
+// Convert every input into the output of rsp, then render the outcome of each
+fn convert_concat(
+    rsp: &ReadStatPath,
+    inputs: Vec<ReadStatPath>,
+    options: &ReadStatConvertOptions,
+    no_progress: bool,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut concat = ReadStatConcat::new(inputs.clone());
+
+    // Progress across the rows of every input
+    let pb = (!no_progress).then(|| ProgressBar::new(1000));
+    if let Some(pb) = pb.clone() {
+        pb.set_style(ProgressStyle::default_bar().template("[{elapsed_precise}] {bar:40} {msg}")?);
+        concat = concat.set_progress_callback(Box::new(move |fraction, rows| {
+            pb.set_position((fraction * 1000.0) as u64);
+            pb.set_message(format!("{} rows", rows.to_formatted_string(&Locale::en)));
+            ControlFlow::Continue(())
+        }));
+    }
+
+    let concatenated = concat.convert(rsp, options);
+    if let Some(pb) = pb {
+        pb.finish_and_clear();
+    }
+    let concatenated = concatenated?;
+
+    for (input, c) in inputs.iter().zip(&concatenated.inputs) {
+        for w in &c.warnings {
+            print_message(rsp, w);
+        }
+
+        let in_f = match input.path.file_name() {
+            Some(f) => f.to_string_lossy().bright_red(),
+            None => String::from("___").bright_red(),
+        };
+        print_message(
+            rsp,
+            &format!(
+                "Wrote {} rows from file {}",
+                c.rows.to_formatted_string(&Locale::en).truecolor(255, 132, 0),
+                in_f
+            ),
+        );
+    }
+
+    let out_f = match rsp.out_path.as_ref().and_then(|p| p.file_name()) {
+        Some(f) => f.to_string_lossy().bright_green(),
+        None => String::from("___").bright_green(),
+    };
+    print_message(
+        rsp,
+        &format!(
+            "In total, wrote {} rows from {} files into {}",
+            concatenated
+                .rows
+                .to_formatted_string(&Locale::en)
+                .truecolor(255, 132, 0),
+            inputs.len(),
+            out_f
+        ),
+    );
+    print_split_paths(rsp, &concatenated.written_paths);
+
+    Ok(())
+}
+
+// Each file written when output is split by rows
+fn print_split_paths(rsp: &ReadStatPath, written_paths: &[PathBuf]) {
+    if written_paths.len() < 2 {
+        return;
+    }
+
+    print_message(rsp, &format!("Split output into {} files", written_paths.len()));
+    for p in written_paths {
+        print_message(rsp, &format!("  {}", p.to_string_lossy().bright_green()));
+    }
+}
+
+fn print_timings(rsp: &ReadStatPath, timings: &[ReadStatBatchTiming]) {
+    let ms = |d: std::time::Duration| format!("{:.1} ms", d.as_secs_f64() * 1000.0);
+
+    for t in timings {
+        print_message(
+            rsp,
+            &format!(
+                "Rows {} to {}: parsed in {}, blocked for {}, written in {}",
+                t.row_start.to_formatted_string(&Locale::en),
+                (t.row_start + t.rows).to_formatted_string(&Locale::en),
+                ms(t.parse),
+                ms(t.blocked),
+                ms(t.consume)
+            ),
+        );
+    }
+
+    let total = ReadStatBatchTiming::total(timings);
+    print_message(
+        rsp,
+        &format!(
+            "In total, parsed in {}, blocked for {}, written in {}",
+            ms(total.parse).bright_cyan(),
+            ms(total.blocked).bright_cyan(),
+            ms(total.consume).bright_cyan()
+        ),
+    );
+}
+
+fn print_stats(rsp: &ReadStatPath, stats: &ReadStatStats) {
+    let ms = |d: std::time::Duration| format!("{:.1} ms", d.as_secs_f64() * 1000.0);
+
+    print_message(
+        rsp,
+        &format!(
+            "Read {} rows in {} batches and wrote {} rows ({} bytes) in {}; parsing took {} and writing {}",
+            stats.rows_read.to_formatted_string(&Locale::en),
+            stats.batches.to_formatted_string(&Locale::en),
+            stats.rows_written.to_formatted_string(&Locale::en),
+            stats.total_bytes_written().to_formatted_string(&Locale::en),
+            ms(stats.wall).bright_cyan(),
+            ms(stats.parse),
+            ms(stats.write)
+        ),
+    );
+}
+
+fn print_total_rows_written(rsp: &ReadStatPath, rows: usize) {
+    let in_f = if let Some(f) = rsp.path.file_name() {
+        f.to_string_lossy().bright_red()
+    } else {
+        String::from("___").bright_red()
+    };
+
+    let out_f = if let Some(p) = &rsp.out_path {
+        if let Some(f) = p.file_name() {
+            f.to_string_lossy().bright_green()
+        } else {
+            String::from("___").bright_green()
+        }
+    } else {
+        String::from("___").bright_green()
+    };
+
+    let rows = rows.to_formatted_string(&Locale::en).truecolor(255, 132, 0);
+
+    print_message(
+        rsp,
+        &format!(
+            "In total, wrote {} rows from file {} into {}",
+            rows, in_f, out_f
+        ),
+    );
+}
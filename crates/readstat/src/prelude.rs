@@ -0,0 +1,101 @@
+//! The intended public API of readstat
+//!
+//! ```ignore
+//! use readstat::prelude::*;
+//! ```
+//!
+//! Everything exported here is covered by semantic versioning; anything else, including the
+//! callbacks handed to ReadStat, is internal and may change within any release.
+
+pub use crate::conversions::{
+    sas_days_to_date32, sas_seconds_to_duration, sas_seconds_to_time32, sas_seconds_to_time64,
+    sas_seconds_to_timestamp, sas_seconds_to_timestamp_in,
+};
+pub use crate::easy;
+pub use crate::err::{
+    ReadStatCancelled, ReadStatError, ReadStatParseError, ReadStatResumeMismatch,
+    ReadStatSchemaMismatch,
+};
+#[cfg(feature = "async")]
+pub use crate::rs_async::{read_async, ReadStatBatchStream};
+#[cfg(feature = "cache")]
+pub use crate::rs_cache::{ReadStatCache, ReadStatCacheStats};
+pub use crate::rs_cell_errors::{ErrorMode, ReadStatCellError, ReadStatErrorReport};
+pub use crate::rs_checkpoint::{
+    ReadStatCheckpoint, ReadStatInputFingerprint, CHECKPOINT_ROWS, CHECKPOINT_VERSION,
+};
+pub use crate::rs_compare::{compare_metadata, PropertyDiff, SchemaDiff, VariableDiff};
+pub use crate::rs_concat::{ReadStatConcat, ReadStatConcatenated};
+pub use crate::rs_convert::{
+    convert, convert_collect, convert_for_each, convert_many, read_batches, resolve_reader,
+    ReadStatBatchTiming, ReadStatBatches, ReadStatCollected, ReadStatConversionResult,
+    ReadStatConvertOptions, ReadStatSalvage, ReadStatStats,
+};
+pub use crate::rs_data::{ReadStatData, ReadStatProgressCallback, ReadStatSchemaLock};
+pub use crate::rs_date_window::{ReadStatDateRangeWarning, ReadStatDateWindow};
+pub use crate::rs_dates::{
+    apply_dates, detect_date_columns, ReadStatDateColumn, ReadStatDateDetection,
+    DETECT_DATES_DAYS, DETECT_DATES_MIN_VALUES, DETECT_DATES_SAMPLE_ROWS, DETECT_DATES_SECONDS,
+    DETECT_DATES_THRESHOLD,
+};
+pub use crate::rs_dictionary::{apply_string_encoding, StringEncoding};
+pub use crate::rs_encoding::{guess_encoding, ReadStatEncodingDetector, ReadStatEncodingGuess};
+pub use crate::rs_estimate::ReadStatSizeEstimate;
+pub use crate::rs_filter::{FilterPredicate, FilterValue, ReadStatRowFilter};
+pub use crate::rs_info::{readstat_version, ReadStatVersionInfo};
+pub use crate::rs_io::{
+    without_compression_extension, InputCompression, ReadStatInput, ReadStatSource,
+    DEFAULT_MAX_DECOMPRESSED_BYTES,
+};
+pub use crate::rs_mapping::{ReadStatMapping, ReadStatMappingEntry};
+pub use crate::rs_measure::{ReadStatMeasure, ReadStatMeasuredBatch};
+pub use crate::rs_metadata::{
+    ReadStatCompress, ReadStatEndian, ReadStatFormatSpec, ReadStatMetadata, ReadStatRename,
+    ReadStatRenameReason, ReadStatTruncation, ReadStatTypeDecision, ReadStatTypeReason,
+    ReadStatVarMetadata,
+};
+pub use crate::rs_missing_tags::{missing_tag_name, MISSING_TAG_SUFFIX};
+pub use crate::rs_normalize::ReadStatStringNormalize;
+pub use crate::rs_path::{is_special_file, ReadStatPath, STDOUT_PATH};
+#[cfg(feature = "polars")]
+pub use crate::rs_polars::chunk_to_polars;
+pub use crate::rs_redact::{
+    ReadStatHashAlgorithm, ReadStatHashColumn, ReadStatRedact, ReadStatRedaction,
+};
+pub use crate::rs_sample::{sample_rows, ReadStatSampleRow};
+pub use crate::rs_schema::{
+    ReadStatSchema, ReadStatSchemaColumn, FINGERPRINT_VERSION, SCHEMA_VERSION,
+};
+pub use crate::rs_spec::{ReadStatColumnSpec, ReadStatColumnSpecRow};
+pub use crate::rs_stats::{
+    apply_booleans, detect_boolean_columns, ReadStatBooleanDetector, ReadStatColumnProfile,
+    ReadStatHistogram, ReadStatProfile,
+};
+pub use crate::rs_summary::{
+    ReadStatColumnSummary, ReadStatSummary, ReadStatSummaryKind, DISTINCT_CAP,
+};
+pub use crate::rs_template::ReadStatOutputTemplate;
+pub use crate::rs_user_missing::ReadStatMissingRange;
+pub use crate::rs_value_labels::{
+    ReadStatLabelSet, ReadStatLabelValue, ReadStatLabelling, ReadStatValueLabel,
+};
+pub use crate::rs_var::{
+    ReadStatVar, ReadStatVarAlignment, ReadStatVarFormatClass, ReadStatVarMeasure, ReadStatVarType,
+    ReadStatVarTypeClass,
+};
+pub use crate::rs_verify::{
+    check_numeric_sanity, ReadStatNumericSanity, ReadStatNumericSanityReport, ReadStatRawValue,
+    NUMERIC_SANITY_HUGE, NUMERIC_SANITY_THRESHOLD,
+};
+pub use crate::rs_warnings::{
+    ReadStatWarningCategory, ReadStatWarningGroup, ReadStatWarnings, WARNINGS_RETAINED,
+};
+pub use crate::rs_write::{
+    segment_path, split_path, OutputWriter, ReadStatCsvOptions, ReadStatFileOutput,
+    ReadStatOutput, ReadStatStdoutOutput, ReadStatWriter,
+};
+pub use crate::{
+    Booleans, CsvQuoteStyle, DateTimeType, DetectDates, FloatHandling, IpcCompression,
+    LineEnding, MemLimitAction, NameTransform, OutFormat, OutputMode, ParquetCompression, Reader,
+    StringCase, TimeHandling, TrimMode, UserMissing,
+};
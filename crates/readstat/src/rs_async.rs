@@ -0,0 +1,116 @@
+use arrow2::{array::Array, chunk::Chunk, datatypes::Schema};
+use futures_core::Stream;
+use std::{
+    error::Error,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+use tokio::{
+    sync::{mpsc, oneshot},
+    task,
+};
+
+use crate::rs_convert::{read_batches, ReadStatCollected, ReadStatConvertOptions};
+use crate::rs_path::ReadStatPath;
+
+type ChunkResult = Result<Chunk<Box<dyn Array>>, Box<dyn Error + Send + Sync>>;
+
+/// Chunks of an input, streamed to async code as they are read; see [`read_async`]
+pub struct ReadStatBatchStream {
+    schema: Schema,
+    r: mpsc::Receiver<ChunkResult>,
+    collected: oneshot::Receiver<ReadStatCollected>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl ReadStatBatchStream {
+    /// Schema every chunk shares, known before the first is read
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Rows, warnings, and timings of the read, once every chunk has been streamed
+    ///
+    /// `None` should the stream not have been read to its end, in which case reading stops, or
+    /// reading have failed.  No chunks are kept, so `chunks` is always empty.
+    pub async fn collected(mut self) -> Option<ReadStatCollected> {
+        // chunks not yet streamed are never sent, rather than waited upon
+        self.r.close();
+        (&mut self.collected).await.ok()
+    }
+}
+
+// Reading stops at the next value rather than once the next chunk is read
+impl Drop for ReadStatBatchStream {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Stream for ReadStatBatchStream {
+    type Item = ChunkResult;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.r.poll_recv(cx)
+    }
+}
+
+/// Read the input described by `rsp` as [`read_batches`] does, streaming each chunk to async
+/// code
+///
+/// The blocking parse runs upon a thread of tokio's blocking pool, at most
+/// `options.channel_depth` chunks ahead of the stream, so memory stays bounded however slowly
+/// the stream is consumed.  The schema is known once this resolves; an error while reading is
+/// yielded by the stream, which then ends.  Dropping the stream stops reading at the next value.
+/// Must be called within a tokio runtime.
+pub async fn read_async(
+    rsp: &ReadStatPath,
+    options: &ReadStatConvertOptions,
+) -> Result<ReadStatBatchStream, Box<dyn Error + Send + Sync>> {
+    let (s, r) = mpsc::channel(std::cmp::max(options.channel_depth, 1));
+    let (schema_s, schema_r) = oneshot::channel();
+    let (collected_s, collected_r) = oneshot::channel();
+    let rsp = rsp.clone();
+    let options = options.clone();
+
+    task::spawn_blocking(move || {
+        let mut batches = match read_batches(&rsp, &options) {
+            Ok(batches) => batches,
+            Err(e) => {
+                let _ = schema_s.send(Err(e));
+                return;
+            }
+        };
+        if schema_s
+            .send(Ok((batches.schema().clone(), batches.cancel())))
+            .is_err()
+        {
+            return;
+        }
+
+        // Dropping batches, should the stream have been dropped, stops reading
+        for chunk in &mut batches {
+            if s.blocking_send(chunk).is_err() {
+                return;
+            }
+        }
+        if let Some(c) = batches.into_collected() {
+            let _ = collected_s.send(c);
+        }
+    });
+
+    let (schema, cancel) = schema_r.await.map_err(|_| -> Box<dyn Error + Send + Sync> {
+        From::from("Thread reading data panicked")
+    })??;
+
+    Ok(ReadStatBatchStream {
+        schema,
+        r,
+        collected: collected_r,
+        cancel,
+    })
+}
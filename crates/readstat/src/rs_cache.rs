@@ -0,0 +1,203 @@
+use arrow2::compute::aggregate::estimated_bytes_size;
+use std::{
+    collections::HashMap,
+    error::Error,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    sync::{Arc, Condvar, Mutex, PoisonError},
+};
+
+use crate::rs_convert::{convert_collect, ReadStatCollected, ReadStatConvertOptions};
+use crate::rs_path::ReadStatPath;
+use crate::OutputMode;
+
+// input path along with the options it is read with
+type CacheKey = (PathBuf, String);
+
+enum CacheEntry {
+    // being parsed by the first caller to request it
+    Pending,
+    Ready {
+        collected: Arc<ReadStatCollected>,
+        bytes: usize,
+        last_used: u64,
+    },
+}
+
+/// Counts of the work done by a [`ReadStatCache`]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ReadStatCacheStats {
+    /// Files parsed
+    pub parses: usize,
+    /// Requests answered by a parse that had already finished
+    pub hits: usize,
+    /// Requests that waited upon a parse already underway
+    pub waits: usize,
+    /// Entries removed to stay within the limit of cached bytes
+    pub evictions: usize,
+    /// Estimated bytes of the Arrow arrays currently cached
+    pub bytes: usize,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<CacheKey, CacheEntry>,
+    stats: ReadStatCacheStats,
+    clock: u64,
+}
+
+/// In-process cache of the chunks collected from files, keyed by path and conversion options
+///
+/// Identical requests made at the same time are parsed once; the first caller parses while the
+/// others wait for it to finish and then share its chunks.  Should that parse fail, the error is
+/// not cached and a waiting caller parses in its place.  Once the bytes cached exceed `max_bytes`
+/// the least recently used entries are evicted; a result larger than `max_bytes` is returned
+/// without being kept.
+///
+/// Parsing shares no state between calls, so the same file may be parsed from many threads at
+/// once with or without the cache; the cache only avoids the duplicate work.
+pub struct ReadStatCache {
+    max_bytes: usize,
+    state: Mutex<CacheState>,
+    ready: Condvar,
+}
+
+impl ReadStatCache {
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            state: Mutex::new(CacheState::default()),
+            ready: Condvar::new(),
+        }
+    }
+
+    /// Chunks of the input described by `rsp`, as collected by [`convert_collect`]
+    ///
+    /// Nothing is written; `options.output_mode` is always `OutputMode::Collect`.
+    pub fn get_or_collect(
+        &self,
+        rsp: &ReadStatPath,
+        options: &ReadStatConvertOptions,
+    ) -> Result<Arc<ReadStatCollected>, Box<dyn Error + Send + Sync>> {
+        let options = ReadStatConvertOptions {
+            output_mode: OutputMode::Collect,
+            ..options.clone()
+        };
+        let key = (rsp.path.clone(), format!("{:?}", options));
+
+        // Wait for any parse already underway
+        let mut state = self.state.lock().map_err(poisoned)?;
+        let mut waited = false;
+        loop {
+            let s = &mut *state;
+            s.clock += 1;
+            match s.entries.get_mut(&key) {
+                Some(CacheEntry::Ready {
+                    collected,
+                    last_used,
+                    ..
+                }) => {
+                    *last_used = s.clock;
+                    if !waited {
+                        s.stats.hits += 1;
+                    }
+                    return Ok(collected.clone());
+                }
+                Some(CacheEntry::Pending) => {
+                    if !waited {
+                        s.stats.waits += 1;
+                        waited = true;
+                    }
+                }
+                None => break,
+            }
+            state = self.ready.wait(state).map_err(poisoned)?;
+        }
+
+        // Parse without holding the lock
+        state.entries.insert(key.clone(), CacheEntry::Pending);
+        state.stats.parses += 1;
+        drop(state);
+
+        // A panic must not leave the entry pending, or those waiting would wait forever
+        let result = panic::catch_unwind(AssertUnwindSafe(|| convert_collect(rsp, &options)))
+            .unwrap_or_else(|_| Err(From::from("Parsing panicked")));
+
+        let mut state = self.state.lock().map_err(poisoned)?;
+        state.entries.remove(&key);
+        let result = result.map(|collected| {
+            let bytes = collected_bytes(&collected);
+            let collected = Arc::new(collected);
+
+            if bytes <= self.max_bytes {
+                state.clock += 1;
+                let last_used = state.clock;
+                state.entries.insert(
+                    key,
+                    CacheEntry::Ready {
+                        collected: collected.clone(),
+                        bytes,
+                        last_used,
+                    },
+                );
+                state.stats.bytes += bytes;
+                self.evict(&mut state);
+            }
+
+            collected
+        });
+        drop(state);
+        self.ready.notify_all();
+
+        result
+    }
+
+    pub fn stats(&self) -> ReadStatCacheStats {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .stats
+    }
+
+    /// Remove every finished entry; parses underway are unaffected
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.entries.retain(|_, e| matches!(e, CacheEntry::Pending));
+        state.stats.bytes = 0;
+    }
+
+    // Least recently used first
+    fn evict(&self, state: &mut CacheState) {
+        while state.stats.bytes > self.max_bytes {
+            let oldest = state
+                .entries
+                .iter()
+                .filter_map(|(k, e)| match e {
+                    CacheEntry::Ready { last_used, .. } => Some((*last_used, k.clone())),
+                    CacheEntry::Pending => None,
+                })
+                .min();
+
+            match oldest.and_then(|(_, k)| state.entries.remove(&k)) {
+                Some(CacheEntry::Ready { bytes, .. }) => {
+                    state.stats.bytes -= bytes;
+                    state.stats.evictions += 1;
+                }
+                _ => break,
+            }
+        }
+    }
+}
+
+// Estimated bytes of the Arrow arrays collected
+fn collected_bytes(collected: &ReadStatCollected) -> usize {
+    collected
+        .chunks
+        .iter()
+        .flat_map(|c| c.arrays().iter().map(|a| estimated_bytes_size(a.as_ref())))
+        .sum()
+}
+
+fn poisoned<T>(_: PoisonError<T>) -> Box<dyn Error + Send + Sync> {
+    From::from("The cache was poisoned by a panic while it was locked")
+}
@@ -0,0 +1,56 @@
+use serde::Serialize;
+use std::{
+    error::Error,
+    ffi::OsString,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// How values that cannot be read are handled
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ErrorMode {
+    /// Stop reading at the first value that cannot be read
+    #[default]
+    FailFast,
+    /// Record each value that cannot be read and write it as null, stopping only once more than
+    /// `max` have been recorded
+    Collect { max: usize },
+}
+
+/// A value that could not be read, written as null in its place
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ReadStatCellError {
+    pub row: usize,
+    /// Column as written
+    pub column: String,
+    pub description: String,
+    #[serde(skip)]
+    pub(crate) var_index: i32,
+}
+
+/// Values that could not be read while converting with [`ErrorMode::Collect`]
+///
+/// Written alongside the output as `<output>.errors.json` when requested, in file order.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ReadStatErrorReport {
+    pub table_name: String,
+    pub errors: Vec<ReadStatCellError>,
+}
+
+impl ReadStatErrorReport {
+    /// `<output>.errors.json`, e.g. `cars.parquet.errors.json`
+    pub fn sidecar_path(out_path: &Path) -> PathBuf {
+        let mut p = OsString::from(out_path.as_os_str());
+        p.push(".errors.json");
+        PathBuf::from(p)
+    }
+
+    /// Write as pretty json to `path`, replacing any existing file
+    pub fn write(&self, path: &Path) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut f = File::create(path)?;
+        f.write_all(serde_json::to_string_pretty(self)?.as_bytes())?;
+        f.write_all(b"\n")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,358 @@
+use colored::Colorize;
+use num_format::{Locale, ToFormattedString};
+use serde::{Deserialize, Serialize};
+use std::{
+    error::Error,
+    ffi::OsString,
+    fs::File,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use crate::common::sha256_hex;
+use crate::err::ReadStatResumeMismatch;
+use crate::rs_convert::ReadStatConvertOptions;
+use crate::rs_path::ReadStatPath;
+use crate::rs_write::{appends_to_segment, segment_path, OutputWriter, ReadStatFileOutput};
+use crate::OutFormat;
+
+/// Version of the checkpoint file, increased should its layout change
+pub const CHECKPOINT_VERSION: u32 = 2;
+
+/// Rows written between checkpoints, by default
+pub const CHECKPOINT_ROWS: usize = 1_000_000;
+
+// Bytes from the start of the input that are hashed; the header of every format supported lies
+//   within them
+const HEADER_BYTES: u64 = 64 * 1024;
+
+/// The input of a checkpointed conversion, as found when the conversion began
+///
+/// Recognised by its size, modification time, and the sha256 of its first 64 KiB, so that an
+/// input replaced or rewritten since is never resumed without hashing every byte.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ReadStatInputFingerprint {
+    pub path: PathBuf,
+    pub size: u64,
+    /// Modification time as seconds, and nanoseconds within the second, since the Unix epoch
+    pub modified_secs: u64,
+    pub modified_nanos: u32,
+    /// Lowercase hex sha256 of the first 64 KiB
+    pub header_sha256: String,
+}
+
+impl ReadStatInputFingerprint {
+    pub fn new(path: &Path) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let m = std::fs::metadata(path)?;
+        let modified = m.modified()?.duration_since(UNIX_EPOCH)?;
+
+        let mut header = Vec::new();
+        File::open(path)?.take(HEADER_BYTES).read_to_end(&mut header)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            size: m.len(),
+            modified_secs: modified.as_secs(),
+            modified_nanos: modified.subsec_nanos(),
+            header_sha256: sha256_hex(&header),
+        })
+    }
+
+    // Why other is not the same input, if it is not; the path alone may differ
+    fn differs_from(&self, other: &Self) -> Option<String> {
+        if self.size != other.size {
+            Some(format!(
+                "the size of the input differs ({} bytes rather than {})",
+                self.size.to_formatted_string(&Locale::en),
+                other.size.to_formatted_string(&Locale::en)
+            ))
+        } else if (self.modified_secs, self.modified_nanos)
+            != (other.modified_secs, other.modified_nanos)
+        {
+            Some(String::from("the input has been modified since"))
+        } else if self.header_sha256 != other.header_sha256 {
+            Some(String::from("the header of the input differs"))
+        } else {
+            None
+        }
+    }
+}
+
+/// Progress of a checkpointed conversion, written beside its output as
+/// `<output>.checkpoint.json`
+///
+/// A checkpointed conversion of csv or ndjson appends its output to a single hidden segment (see
+/// [`segment_path`]), synced to disk every [`ReadStatConvertOptions::checkpoint_rows`] rows,
+/// after which the checkpoint is updated with the bytes of the segment.  Should the conversion
+/// be interrupted, a conversion of the same input with the same options and
+/// [`ReadStatConvertOptions::resume`] skips the rows already written, truncates the segment to
+/// those bytes, and appends to it.  Once every row is written the segment is moved into place as
+/// the output, and the checkpoint is removed.
+///
+/// Parquet and feather files end with a footer that cannot be reopened to add row groups or
+/// batches, so arrow, feather, and parquet output is instead written as hidden numbered
+/// segments, each moved into place once it holds `checkpoint_rows` rows, and a resumed
+/// conversion carries on from the next segment.  Once every row is written the segments are
+/// merged into the output and removed, which rewrites every row of them once more.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ReadStatCheckpoint {
+    pub version: u32,
+    /// Version of readstat that wrote the checkpoint
+    pub readstat_version: String,
+    pub input: ReadStatInputFingerprint,
+    /// Output format, e.g. `parquet`
+    pub format: String,
+    /// Lowercase hex sha256 of the options that shape the output; see
+    /// [`ReadStatCheckpoint::options_fingerprint`]
+    pub options: String,
+    /// Rows of the input read into the finished segments, counted from `options.row_offset`
+    pub rows_read: u64,
+    /// Rows written to the finished segments; fewer than `rows_read` when rows are filtered
+    pub rows_written: u64,
+    /// Segments finished and moved into place
+    pub segments: usize,
+    /// Bytes of the segment appended to by csv or ndjson output, holding `rows_written` rows;
+    /// 0 otherwise
+    #[serde(default)]
+    pub bytes: u64,
+}
+
+impl ReadStatCheckpoint {
+    /// Checkpoint of a conversion of `rsp` with `options` that has yet to write any rows
+    pub fn new(
+        rsp: &ReadStatPath,
+        options: &ReadStatConvertOptions,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(Self {
+            version: CHECKPOINT_VERSION,
+            readstat_version: String::from(env!("CARGO_PKG_VERSION")),
+            input: ReadStatInputFingerprint::new(&rsp.path)?,
+            format: rsp.format.to_string(),
+            options: Self::options_fingerprint(rsp, options),
+            rows_read: 0,
+            rows_written: 0,
+            segments: 0,
+            bytes: 0,
+        })
+    }
+
+    /// Fingerprint of the options that shape the output
+    ///
+    /// Options that only change how the conversion runs (e.g. the reader, batch size,
+    /// parallelism, retries, and progress) or what is written beside the output are left out,
+    /// so they may change upon resuming.
+    pub fn options_fingerprint(rsp: &ReadStatPath, options: &ReadStatConvertOptions) -> String {
+        let defaults = ReadStatConvertOptions::default();
+        let shaping = ReadStatConvertOptions {
+            out_dir: None,
+            output_template: None,
+            format: defaults.format,
+            overwrite: false,
+            compression: None,
+            compression_level: None,
+            write_retries: 0,
+            write_retry_delay: defaults.write_retry_delay,
+            output_mode: defaults.output_mode,
+            checkpoint: false,
+            checkpoint_rows: defaults.checkpoint_rows,
+            resume: false,
+            reader: defaults.reader,
+            stream_rows: defaults.stream_rows,
+            batch_rows: None,
+            channel_depth: defaults.channel_depth,
+            mem_limit: None,
+            mem_limit_action: defaults.mem_limit_action,
+            two_pass: false,
+            no_progress: true,
+            parallel: false,
+            parallelism: None,
+            parallel_min_rows: defaults.parallel_min_rows,
+            mapping_sidecar: defaults.mapping_sidecar,
+            schema_sidecar: false,
+            error_report: false,
+            ..options.clone()
+        };
+
+        sha256_hex(
+            format!(
+                "{}\n{:?}\n{:?}\n{:?}",
+                rsp.format, rsp.compression, rsp.compression_level, shaping
+            )
+            .as_bytes(),
+        )
+    }
+
+    /// `<output>.checkpoint.json` next to the output, e.g. `cars.parquet.checkpoint.json`
+    pub fn sidecar_path(out_path: &Path) -> PathBuf {
+        let mut p = OsString::from(out_path.as_os_str());
+        p.push(".checkpoint.json");
+        PathBuf::from(p)
+    }
+
+    /// Checkpoint to begin converting `rsp` with `options` from
+    ///
+    /// With `options.resume`, the checkpoint of an interrupted conversion is returned once its
+    /// input, options, and segments are found to match, and a [`ReadStatResumeMismatch`] is
+    /// returned otherwise; without one, a fresh checkpoint.  Without `options.resume`, an
+    /// existing checkpoint is an error unless `rsp.overwrite`, whereupon it is removed along with
+    /// its segments.
+    pub fn begin(
+        rsp: &ReadStatPath,
+        options: &ReadStatConvertOptions,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let out_path = match &rsp.out_path {
+            Some(p) if !rsp.writes_to_stdout() => p,
+            _ => {
+                return Err(From::from(
+                    "Checkpointing requires an output file rather than standard out",
+                ))
+            }
+        };
+        if options.split_rows.is_some() {
+            return Err(From::from(
+                "Checkpointing is not supported alongside splitting output",
+            ));
+        }
+        // ReadStat records the rows of a file ahead of writing any
+        if matches!(rsp.format, OutFormat::sav | OutFormat::dta) {
+            return Err(From::from(format!(
+                "Checkpointing is not supported for {} files",
+                rsp.format
+            )));
+        }
+
+        let fresh = Self::new(rsp, options)?;
+        let path = Self::sidecar_path(out_path);
+        if !path.exists() {
+            return Ok(fresh);
+        }
+
+        if !options.resume {
+            if rsp.overwrite {
+                Self::remove(out_path)?;
+                return Ok(fresh);
+            }
+            return Err(From::from(format!(
+                "A checkpoint of an interrupted conversion - {} - already exists!  To resume the conversion, utilize the {} parameter, or to start afresh, the {} parameter",
+                path.to_string_lossy().bright_yellow(),
+                String::from("--resume").bright_cyan(),
+                String::from("--overwrite").bright_cyan()
+            )));
+        }
+
+        let found = Self::read(&path)?;
+        let mismatch = |reason: String| -> Box<dyn Error + Send + Sync> {
+            Box::new(ReadStatResumeMismatch {
+                checkpoint: path.clone(),
+                reason,
+            })
+        };
+
+        if found.version != fresh.version {
+            return Err(mismatch(format!(
+                "it is version {} rather than {}",
+                found.version, fresh.version
+            )));
+        }
+        if found.readstat_version != fresh.readstat_version {
+            return Err(mismatch(format!(
+                "it was written by readstat {} rather than {}",
+                found.readstat_version, fresh.readstat_version
+            )));
+        }
+        if let Some(reason) = fresh.input.differs_from(&found.input) {
+            return Err(mismatch(reason));
+        }
+        if found.format != fresh.format {
+            return Err(mismatch(format!(
+                "its output is {} rather than {}",
+                found.format, fresh.format
+            )));
+        }
+        if found.options != fresh.options {
+            return Err(mismatch(String::from(
+                "the options differ from those it was begun with",
+            )));
+        }
+        if appends_to_segment(rsp.format) {
+            let s = segment_path(out_path, 0);
+            let len = std::fs::metadata(&s).map(|m| m.len()).unwrap_or(0);
+            if !s.exists() || len < found.bytes {
+                return Err(mismatch(format!(
+                    "its segment {} is missing or holds fewer than {} bytes",
+                    s.to_string_lossy(),
+                    found.bytes.to_formatted_string(&Locale::en)
+                )));
+            }
+        } else if let Some(missing) = (0..found.segments)
+            .map(|i| segment_path(out_path, i))
+            .find(|s| !s.exists())
+        {
+            return Err(mismatch(format!(
+                "its segment {} is missing",
+                missing.to_string_lossy()
+            )));
+        }
+
+        Ok(found)
+    }
+
+    /// Read a checkpoint previously written by [`ReadStatCheckpoint::write`]
+    pub fn read(path: &Path) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let checkpoint: Self = serde_json::from_reader(File::open(path)?)?;
+
+        if checkpoint.version > CHECKPOINT_VERSION {
+            return Err(From::from(format!(
+                "The checkpoint {} is version {} but at most version {} is supported",
+                path.to_string_lossy().bright_red(),
+                checkpoint.version,
+                CHECKPOINT_VERSION
+            )));
+        }
+
+        Ok(checkpoint)
+    }
+
+    /// Write as pretty json to `path`, replacing any existing file only once completely written
+    pub fn write(&self, path: &Path) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut f = ReadStatFileOutput::create(path, true)?;
+        f.write_all(serde_json::to_string_pretty(self)?.as_bytes())?;
+        f.write_all(b"\n")?;
+        f.commit()?;
+        Ok(())
+    }
+
+    /// Record that `segments` segments, or for csv and ndjson the first `bytes` bytes of the
+    /// segment appended to, holding the rows of the input up to `rows_read` of which
+    /// `rows_written` were written, are finished
+    pub fn record(
+        &mut self,
+        out_path: &Path,
+        rows_read: u64,
+        rows_written: u64,
+        segments: usize,
+        bytes: u64,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.rows_read = rows_read;
+        self.rows_written = rows_written;
+        self.segments = segments;
+        self.bytes = bytes;
+        self.write(&Self::sidecar_path(out_path))
+    }
+
+    /// Remove the checkpoint of the output at `out_path` along with any of its segments
+    pub fn remove(out_path: &Path) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut i = 0;
+        while segment_path(out_path, i).exists() {
+            std::fs::remove_file(segment_path(out_path, i))?;
+            i += 1;
+        }
+
+        let path = Self::sidecar_path(out_path);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use serde::Serialize;
+
+use crate::rs_metadata::{ReadStatMetadata, ReadStatVarMetadata};
+use crate::rs_schema::arrow_type_name;
+
+/// A property whose value differs between the two files compared
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PropertyDiff {
+    /// One of `type`, `format`, `label`, and `arrow_type` for a variable, or `encoding` and
+    /// `version` for the file
+    pub property: String,
+    /// Value within the first file
+    pub left: String,
+    /// Value within the second file
+    pub right: String,
+}
+
+/// A variable of both files whose properties differ
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct VariableDiff {
+    pub name: String,
+    pub changes: Vec<PropertyDiff>,
+}
+
+/// Differences between the metadata of two files, as found by [`compare_metadata`]
+///
+/// Variables are matched by name, so a variable that merely moves is not a difference.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct SchemaDiff {
+    /// Variables of the second file only, in its order
+    pub added: Vec<String>,
+    /// Variables of the first file only, in its order
+    pub removed: Vec<String>,
+    /// Variables of both files that differ, in the order of the first
+    pub changed: Vec<VariableDiff>,
+    /// Properties of the files themselves that differ
+    pub file: Vec<PropertyDiff>,
+}
+
+impl SchemaDiff {
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of differences, counting each property of a variable that differs
+    pub fn len(&self) -> usize {
+        self.added.len()
+            + self.removed.len()
+            + self.changed.iter().map(|v| v.changes.len()).sum::<usize>()
+            + self.file.len()
+    }
+
+    /// A line for each difference, beginning with `~` for one of the files themselves, `-` for
+    /// a removed variable, `+` for an added one, and `~` for a property of a variable, e.g.
+    ///
+    /// ```text
+    /// ~ version: "9" -> "5"
+    /// - _char
+    /// + _flag
+    /// ~ _time format: "TIME" -> "$8"
+    /// ```
+    pub fn lines(&self) -> Vec<String> {
+        let change = |subject: &str, c: &PropertyDiff| {
+            format!("~ {}{}: {:?} -> {:?}", subject, c.property, c.left, c.right)
+        };
+
+        self.file
+            .iter()
+            .map(|c| change("", c))
+            .chain(self.removed.iter().map(|name| format!("- {}", name)))
+            .chain(self.added.iter().map(|name| format!("+ {}", name)))
+            .chain(self.changed.iter().flat_map(|v| {
+                let subject = format!("{} ", v.name);
+                v.changes.iter().map(move |c| change(&subject, c))
+            }))
+            .collect()
+    }
+}
+
+/// Compare the metadata of two files, read via [`ReadStatMetadata::read_metadata`]
+///
+/// Reports the variables added to and removed from `right`, the differences in type, SAS format,
+/// label, and Arrow type of the variables `left` and `right` share, and the differences in the
+/// encoding and version of the files.  Row counts, timestamps, and the order of variables are
+/// not compared.
+pub fn compare_metadata(left: &ReadStatMetadata, right: &ReadStatMetadata) -> SchemaDiff {
+    let left_vars = variables(left);
+    let right_vars = variables(right);
+    let find = |vars: &[(&ReadStatVarMetadata, String)], name: &str| {
+        vars.iter().position(|(vm, _)| vm.var_name == name)
+    };
+
+    let added = right_vars
+        .iter()
+        .filter(|(vm, _)| find(&left_vars, &vm.var_name).is_none())
+        .map(|(vm, _)| vm.var_name.clone())
+        .collect();
+
+    let mut removed = Vec::new();
+    let mut changed = Vec::new();
+    for (l, l_arrow) in &left_vars {
+        let Some(i) = find(&right_vars, &l.var_name) else {
+            removed.push(l.var_name.clone());
+            continue;
+        };
+
+        let (r, r_arrow) = &right_vars[i];
+        let changes: Vec<PropertyDiff> = [
+            ("type", format!("{:?}", l.var_type), format!("{:?}", r.var_type)),
+            ("format", l.var_format_raw.clone(), r.var_format_raw.clone()),
+            ("label", l.var_label.clone(), r.var_label.clone()),
+            ("arrow_type", l_arrow.clone(), r_arrow.clone()),
+        ]
+        .into_iter()
+        .filter_map(|(property, l, r)| property_diff(property, l, r))
+        .collect();
+
+        if !changes.is_empty() {
+            changed.push(VariableDiff {
+                name: l.var_name.clone(),
+                changes,
+            });
+        }
+    }
+
+    let file = [
+        ("encoding", left.file_encoding.clone(), right.file_encoding.clone()),
+        ("version", left.version.to_string(), right.version.to_string()),
+    ]
+    .into_iter()
+    .filter_map(|(property, l, r)| property_diff(property, l, r))
+    .collect();
+
+    SchemaDiff {
+        added,
+        removed,
+        changed,
+        file,
+    }
+}
+
+// Each variable in order, along with the name of its Arrow type
+fn variables(md: &ReadStatMetadata) -> Vec<(&ReadStatVarMetadata, String)> {
+    md.vars
+        .values()
+        .zip(md.schema.fields.iter())
+        .map(|(vm, f)| (vm, arrow_type_name(f.data_type())))
+        .collect()
+}
+
+fn property_diff(property: &str, left: String, right: String) -> Option<PropertyDiff> {
+    (left != right).then(|| PropertyDiff {
+        property: String::from(property),
+        left,
+        right,
+    })
+}
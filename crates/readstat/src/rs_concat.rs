@@ -0,0 +1,245 @@
+use arrow2::{
+    array::{new_empty_array, Array},
+    chunk::Chunk,
+    datatypes::{Field, Schema},
+};
+use std::{error::Error, ops::ControlFlow, path::PathBuf};
+
+use crate::err::{ReadStatCancelled, ReadStatSchemaMismatch};
+use crate::rs_convert::{
+    check_row_filter, read_batches, writer, ReadStatCollected, ReadStatConvertOptions,
+};
+use crate::rs_data::{ReadStatData, ReadStatProgressCallback};
+use crate::rs_mapping::ReadStatMapping;
+use crate::rs_metadata::ReadStatMetadata;
+use crate::rs_path::ReadStatPath;
+use crate::rs_schema::{arrow_type_name, ReadStatSchema};
+use crate::OutputMode;
+
+/// Rows of several inputs stacked into a single output, e.g. monthly extracts of one dataset
+///
+/// Every input must have the same Arrow schema as the first, with the same column names, types,
+/// and order, once read with the options given.  Schemas are compared before anything is written,
+/// and the first input that differs is a [`ReadStatSchemaMismatch`].  Labels and formats may
+/// differ; those of the first input are the ones written to any sidecar.
+pub struct ReadStatConcat {
+    inputs: Vec<ReadStatPath>,
+    progress_callback: Option<ReadStatProgressCallback>,
+}
+
+/// Outcome of [`ReadStatConcat::convert`]
+#[derive(Debug)]
+pub struct ReadStatConcatenated {
+    /// Rows written across every input
+    pub rows: usize,
+    pub schema: Schema,
+    /// Chunks of every input in order, when collected as directed by `options.output_mode`
+    pub chunks: Vec<Chunk<Box<dyn Array>>>,
+    /// Outcome of reading each input, in order, with its warnings; chunks are never kept here
+    pub inputs: Vec<ReadStatCollected>,
+    /// Every file written, in order; more than one when split by `options.split_rows`
+    pub written_paths: Vec<PathBuf>,
+}
+
+impl ReadStatConcat {
+    pub fn new(inputs: Vec<ReadStatPath>) -> Self {
+        Self {
+            inputs,
+            progress_callback: None,
+        }
+    }
+
+    /// Report progress across every input as each batch is written
+    ///
+    /// The closure is given the fraction of the rows of every input written and the rows written
+    /// so far, and returns `ControlFlow::Break(())` to cancel, whereupon converting fails with
+    /// [`ReadStatCancelled`].
+    pub fn set_progress_callback(self, progress_callback: ReadStatProgressCallback) -> Self {
+        Self {
+            progress_callback: Some(progress_callback),
+            ..self
+        }
+    }
+
+    /// Write the rows of every input, in order, to the output of `rsp`
+    ///
+    /// Only the output path, format, and compression of `rsp` are used; the input it describes is
+    /// not read unless it is also one of the inputs.  Each input is read as by
+    /// [`crate::convert`], so that `options.rows` and `options.row_offset` apply to each.
+    pub fn convert(
+        &mut self,
+        rsp: &ReadStatPath,
+        options: &ReadStatConvertOptions,
+    ) -> Result<ReadStatConcatenated, Box<dyn Error + Send + Sync>> {
+        if self.inputs.is_empty() {
+            return Err(From::from("At least one input is needed to concatenate"));
+        }
+
+        let write = matches!(
+            options.output_mode,
+            OutputMode::Write | OutputMode::WriteAndCollect
+        );
+        let collect = matches!(
+            options.output_mode,
+            OutputMode::Collect | OutputMode::WriteAndCollect
+        );
+        if write {
+            check_row_filter(rsp, options)?;
+        }
+
+        // Rows to write across every input, for progress
+        let mut total_rows: u64 = 0;
+        for input in &self.inputs {
+            let mut md = ReadStatMetadata::new()
+                .set_encoding(options.encoding.clone())
+                .set_best_effort(options.best_effort)
+                .set_dedup_columns(options.dedup_columns);
+            md.read_metadata(input, false)?;
+
+            let rows_after_offset = (md.row_count as u32).saturating_sub(options.row_offset);
+            let rows_to_write = match options.rows {
+                Some(r) => std::cmp::min(r, rows_after_offset),
+                None => rows_after_offset,
+            };
+            total_rows += rows_to_write as u64;
+        }
+
+        // Every input must share the schema of the first, checked ahead of writing anything
+        let mut schema: Option<Schema> = None;
+        for input in &self.inputs {
+            let found = read_batches(input, options)?.schema().clone();
+            match &schema {
+                None => schema = Some(found),
+                Some(expected) if same_columns(expected, &found) => (),
+                Some(expected) => {
+                    return Err(Box::new(ReadStatSchemaMismatch {
+                        expected: self.inputs[0].path.clone(),
+                        found: input.path.clone(),
+                        diff: schema_diff(expected, &found),
+                    }))
+                }
+            }
+        }
+        let schema = schema.unwrap_or_default();
+
+        // Stream the batches of every input, in order, into a single writer
+        let mut wtr = writer(options);
+        let mut rows: usize = 0;
+        let mut chunks: Vec<Chunk<Box<dyn Array>>> = Vec::new();
+        let mut inputs: Vec<ReadStatCollected> = Vec::with_capacity(self.inputs.len());
+
+        for input in &self.inputs {
+            let mut batches = read_batches(input, options)?;
+
+            for chunk in &mut batches {
+                let d = ReadStatData::new()
+                    .set_total_rows_to_process(total_rows as usize)
+                    .set_chunk(schema.clone(), chunk?);
+                rows += d.chunk_rows_processed;
+
+                if write {
+                    wtr.write(&d, rsp)?;
+                }
+                if collect {
+                    chunks.extend(d.into_chunk());
+                }
+
+                if let Some(progress_callback) = self.progress_callback.as_mut() {
+                    let fraction = if total_rows == 0 {
+                        1.0
+                    } else {
+                        (rows as f64 / total_rows as f64).min(1.0)
+                    };
+                    if let ControlFlow::Break(()) = progress_callback(fraction, rows as u64) {
+                        return Err(Box::new(ReadStatCancelled { rows: rows as u64 }));
+                    }
+                }
+            }
+
+            if let Some(mut c) = batches.into_collected() {
+                c.warnings.extend(std::mem::take(&mut wtr.warnings));
+                c.warnings.extend(std::mem::take(&mut wtr.name_warnings));
+                c.warnings.extend(std::mem::take(&mut wtr.direct_warnings));
+                inputs.push(c);
+            }
+        }
+
+        if write {
+            // An output is written even should every input be empty, holding only the schema
+            let empty = Chunk::new(
+                schema
+                    .fields
+                    .iter()
+                    .map(|f| new_empty_array(f.data_type().clone()))
+                    .collect(),
+            );
+            let d = ReadStatData::new().set_chunk(schema.clone(), empty);
+            if rows == 0 {
+                wtr.write(&d, rsp)?;
+            }
+            wtr.finish(&d, rsp)?;
+
+            // Sidecars, with the names, labels, and formats of the first input
+            let out_path = rsp.sidecar_base();
+            if let (Some(p), Some(first)) = (out_path, inputs.first()) {
+                if options.mapping_sidecar
+                    && (first.mapping.has_renames()
+                        || first.mapping.has_redactions()
+                        || first.mapping.has_truncations())
+                {
+                    first.mapping.write(&ReadStatMapping::sidecar_path(p))?;
+                }
+                if options.schema_sidecar {
+                    first.schema_doc.write(&ReadStatSchema::sidecar_path(p))?;
+                }
+            }
+        }
+
+        Ok(ReadStatConcatenated {
+            rows,
+            schema,
+            chunks,
+            inputs,
+            written_paths: wtr.written_paths,
+        })
+    }
+}
+
+// A line for each column of `found` that differs from the column of `expected` at the same
+//   position, or that either lacks
+// Columns match by name, type, and nullability; the metadata of their fields, such as the original
+//   name of a transformed variable, may differ
+fn same_column(expected: Option<&Field>, found: Option<&Field>) -> bool {
+    match (expected, found) {
+        (Some(e), Some(f)) => {
+            e.name == f.name && e.data_type == f.data_type && e.is_nullable == f.is_nullable
+        }
+        (e, f) => e.is_none() && f.is_none(),
+    }
+}
+
+fn same_columns(expected: &Schema, found: &Schema) -> bool {
+    let columns = std::cmp::max(expected.fields.len(), found.fields.len());
+    (0..columns).all(|i| same_column(expected.fields.get(i), found.fields.get(i)))
+}
+
+fn schema_diff(expected: &Schema, found: &Schema) -> Vec<String> {
+    let columns = std::cmp::max(expected.fields.len(), found.fields.len());
+    let line = |sign: char, i: usize, schema: &Schema| {
+        schema.fields.get(i).map(|f| {
+            format!(
+                "{} {}: {} {}",
+                sign,
+                i + 1,
+                f.name,
+                arrow_type_name(f.data_type())
+            )
+        })
+    };
+
+    (0..columns)
+        .filter(|i| !same_column(expected.fields.get(*i), found.fields.get(*i)))
+        .flat_map(|i| [line('-', i, expected), line('+', i, found)])
+        .flatten()
+        .collect()
+}
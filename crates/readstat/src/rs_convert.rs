@@ -0,0 +1,1383 @@
+use arrow2::{array::Array, chunk::Chunk, datatypes::Schema};
+use colored::Colorize;
+use crossbeam::channel::{bounded, Receiver};
+use log::debug;
+use num_format::{Locale, ToFormattedString};
+use rayon::prelude::*;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    num::NonZeroU32,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::common::build_offsets;
+use crate::rs_checkpoint::{ReadStatCheckpoint, CHECKPOINT_ROWS};
+use crate::rs_data::{ReadStatData, ReadStatSchemaLock};
+use crate::rs_date_window::{ReadStatDateRangeWarning, ReadStatDateWindow};
+use crate::rs_dates::{
+    apply_dates, ReadStatDateDetection, DETECT_DATES_SAMPLE_ROWS, DETECT_DATES_THRESHOLD,
+};
+use crate::rs_dictionary::{apply_string_encoding, StringEncoding};
+use crate::rs_encoding::ReadStatEncodingDetector;
+use crate::rs_filter::ReadStatRowFilter;
+use crate::rs_io::without_compression_extension;
+use crate::rs_mapping::ReadStatMapping;
+use crate::rs_measure::ReadStatMeasure;
+use crate::rs_metadata::ReadStatMetadata;
+use crate::rs_missing_tags::missing_tag_fields;
+use crate::rs_normalize::ReadStatStringNormalize;
+use crate::rs_path::ReadStatPath;
+use crate::rs_redact::ReadStatRedact;
+use crate::rs_cell_errors::{ErrorMode, ReadStatCellError, ReadStatErrorReport};
+use crate::rs_schema::ReadStatSchema;
+use crate::rs_spec::ReadStatColumnSpec;
+use crate::rs_stats::apply_booleans;
+use crate::rs_template::ReadStatOutputTemplate;
+use crate::rs_warnings::{ReadStatWarningCategory, ReadStatWarnings};
+use crate::rs_write::{ReadStatCsvOptions, ReadStatWriter};
+use crate::{
+    Booleans, DateTimeType, DetectDates, FloatHandling, IpcCompression, LineEnding, MemLimitAction,
+    NameTransform, OutFormat, OutputMode, ParquetCompression, Reader, TimeHandling, TrimMode,
+    UserMissing, CHANNEL_DEPTH, MAX_BATCH_ROWS, PARALLEL_MIN_ROWS, STREAM_ROWS, WRITE_RETRY_DELAY,
+};
+
+/// Options for converting one or more files
+///
+/// The output fields `out_dir`, `output_template`, `format`, `overwrite`, `compression`, and
+/// `compression_level` are only used by [`convert_many`]; [`convert`] takes them from the
+/// [`ReadStatPath`] it is given.
+#[derive(Clone, Debug)]
+pub struct ReadStatConvertOptions {
+    // output
+    pub out_dir: Option<PathBuf>,
+    /// Names outputs using a [`ReadStatOutputTemplate`] rather than `<stem>.<format>`
+    pub output_template: Option<String>,
+    pub format: OutFormat,
+    pub overwrite: bool,
+    pub compression: Option<ParquetCompression>,
+    pub compression_level: Option<u32>,
+    pub ipc_compression: IpcCompression,
+    pub row_group_bytes: Option<usize>,
+    pub bom: bool,
+    pub line_ending: LineEnding,
+    /// Delimiter, quoting, header, missing values, and datetime format of csv output; see
+    /// [`ReadStatWriter::set_csv_options`]
+    pub csv: ReadStatCsvOptions,
+    /// Write numerics within csv output as their SAS formats display them; see
+    /// [`ReadStatWriter::set_apply_formats`]
+    pub apply_formats: bool,
+    /// Times a failed write of a batch is retried; see [`ReadStatWriter::set_write_retries`]
+    pub write_retries: u32,
+    pub write_retry_delay: Duration,
+    /// Split output into numbered files of about this many rows each; see
+    /// [`ReadStatWriter::set_split_rows`]
+    pub split_rows: Option<usize>,
+    /// Record the progress of the conversion every `checkpoint_rows` rows so that an interrupted
+    /// conversion may be resumed; csv and ndjson output is appended to a single segment synced
+    /// at each checkpoint, whereas other output is written as segments that are merged into the
+    /// output once complete, rewriting them.  See [`ReadStatCheckpoint`]
+    pub checkpoint: bool,
+    pub checkpoint_rows: usize,
+    /// Resume the interrupted conversion checkpointed beside the output, skipping the rows it
+    /// wrote, or begin a checkpointed conversion should there be none; implies `checkpoint`
+    pub resume: bool,
+    pub output_mode: OutputMode,
+    // reading
+    pub rows: Option<u32>,
+    /// Rows skipped from the start of the file, e.g. to resume a conversion; `rows` are read
+    /// from there on
+    pub row_offset: u32,
+    pub reader: Reader,
+    pub stream_rows: u32,
+    /// Rows of every batch, in place of those chosen from `reader`, `stream_rows`, and
+    /// `mem_limit`
+    pub batch_rows: Option<NonZeroU32>,
+    pub channel_depth: usize,
+    pub mem_limit: Option<u64>,
+    pub mem_limit_action: MemLimitAction,
+    /// First measure every value, then read with string arrays allocated exactly and check
+    /// `mem_limit` against the measured rather than estimated bytes; see [`ReadStatMeasure`]
+    pub two_pass: bool,
+    pub no_progress: bool,
+    /// Read batches upon several threads, delivering them in row order nonetheless
+    pub parallel: bool,
+    /// Threads reading when `parallel`; defaults to one for each processor
+    pub parallelism: Option<usize>,
+    /// Rows to read below which reading stays upon a single thread even when `parallel`
+    pub parallel_min_rows: u32,
+    pub coerce_across_batches: bool,
+    pub best_effort: bool,
+    /// Rename variables sharing a name rather than fail; see
+    /// [`ReadStatMetadata::set_dedup_columns`]
+    pub dedup_columns: bool,
+    /// Read values that cannot be read as null rather than failing; see
+    /// [`ReadStatData::set_error_mode`].  The maximum applies to the conversion as a whole.
+    pub error_mode: ErrorMode,
+    /// Round floating point values to 14 decimal places or keep them as stored; see
+    /// [`ReadStatData::set_float_handling`]
+    pub float_handling: FloatHandling,
+    /// Dates, and the dates of datetimes, read as valid, the rest read as null with a warning;
+    /// see [`ReadStatData::set_date_window`]
+    pub date_window: Option<ReadStatDateWindow>,
+    /// Fail rather than read a date beyond `date_window`, or any date, datetime, or time beyond
+    /// the range of its Arrow type, as null
+    pub strict_dates: bool,
+    /// Follow the columns with a column of the tags of the special missing values of each
+    /// numeric column; see [`ReadStatData::set_missing_tags`]
+    pub missing_tags: bool,
+    /// Whitespace trimmed from string values; see [`ReadStatData::set_string_trim`]
+    pub string_trim: TrimMode,
+    /// Values among the missing values a variable declares, read as values or as missing; see
+    /// [`ReadStatData::set_user_missing`]
+    pub user_missing: UserMissing,
+    /// Store string columns as Arrow dictionaries; see [`apply_string_encoding`]
+    pub string_encoding: StringEncoding,
+    pub datetime_type: Option<DateTimeType>,
+    /// Read SAS times as times of day or as durations; see [`ReadStatMetadata::set_time_handling`]
+    pub time_handling: TimeHandling,
+    /// Store plain numerics whose format displays no decimals as Int64; see
+    /// [`ReadStatMetadata::set_infer_integers`]
+    pub infer_integers: bool,
+    pub booleans: Option<Booleans>,
+    /// Sample plain numeric columns for values that look like dates; see
+    /// [`ReadStatDateDetection`]
+    pub detect_dates: Option<DetectDates>,
+    pub detect_dates_threshold: f64,
+    pub detect_dates_sample_rows: u32,
+    /// Format catalog (`sas7bcat`) holding the value labels of the variables; see
+    /// [`ReadStatMetadata::read_value_labels`]
+    pub catalog: Option<PathBuf>,
+    /// Write labelled variables as the labels of their values; requires `catalog`
+    pub apply_value_labels: bool,
+    pub encoding: Option<String>,
+    pub normalize_strings: ReadStatStringNormalize,
+    pub redact: ReadStatRedact,
+    /// Characters each variable label and SAS format may hold; see
+    /// [`ReadStatMetadata::limit_label_lengths`]
+    pub max_label_length: Option<usize>,
+    /// Fail rather than truncate when a label or format exceeds `max_label_length`
+    pub strict: bool,
+    // naming
+    pub renames: Vec<(String, String)>,
+    /// See [`ReadStatMetadata::set_name_transform`]
+    pub name_transform: NameTransform,
+    /// Columns to drop, rename, and cast; see [`ReadStatColumnSpec::apply`]
+    pub column_spec: Option<ReadStatColumnSpec>,
+    /// Variables to read, named as within the file, or every variable when empty; see
+    /// [`ReadStatMetadata::set_columns`]
+    pub columns: Vec<String>,
+    /// Keep only the rows passing a comparison of the value of a variable; see
+    /// [`ReadStatData::set_row_filter`]
+    pub row_filter: Option<ReadStatRowFilter>,
+    pub mapping_sidecar: bool,
+    pub schema_sidecar: bool,
+    /// Write the values that could not be read to `<output>.errors.json`; see
+    /// [`ReadStatErrorReport`]
+    pub error_report: bool,
+}
+
+impl Default for ReadStatConvertOptions {
+    fn default() -> Self {
+        Self {
+            // output
+            out_dir: None,
+            output_template: None,
+            format: OutFormat::csv,
+            overwrite: false,
+            compression: None,
+            compression_level: None,
+            ipc_compression: IpcCompression::Zstd,
+            row_group_bytes: None,
+            bom: false,
+            line_ending: LineEnding::Lf,
+            csv: ReadStatCsvOptions::default(),
+            apply_formats: false,
+            write_retries: 0,
+            write_retry_delay: WRITE_RETRY_DELAY,
+            split_rows: None,
+            checkpoint: false,
+            checkpoint_rows: CHECKPOINT_ROWS,
+            resume: false,
+            output_mode: OutputMode::Write,
+            // reading
+            rows: None,
+            row_offset: 0,
+            reader: Reader::stream,
+            stream_rows: STREAM_ROWS,
+            batch_rows: None,
+            channel_depth: CHANNEL_DEPTH,
+            mem_limit: None,
+            mem_limit_action: MemLimitAction::Error,
+            two_pass: false,
+            no_progress: true,
+            parallel: false,
+            parallelism: None,
+            parallel_min_rows: PARALLEL_MIN_ROWS,
+            coerce_across_batches: false,
+            best_effort: false,
+            dedup_columns: false,
+            error_mode: ErrorMode::FailFast,
+            float_handling: FloatHandling::ReadStatDigits,
+            date_window: Some(ReadStatDateWindow::default()),
+            strict_dates: false,
+            missing_tags: false,
+            string_trim: TrimMode::TrailingWhitespace,
+            user_missing: UserMissing::AsValue,
+            string_encoding: StringEncoding::Plain,
+            datetime_type: None,
+            time_handling: TimeHandling::Time32,
+            infer_integers: false,
+            booleans: None,
+            detect_dates: None,
+            detect_dates_threshold: DETECT_DATES_THRESHOLD,
+            detect_dates_sample_rows: DETECT_DATES_SAMPLE_ROWS,
+            catalog: None,
+            apply_value_labels: false,
+            encoding: None,
+            normalize_strings: ReadStatStringNormalize::default(),
+            redact: ReadStatRedact::default(),
+            max_label_length: None,
+            strict: false,
+            // naming
+            renames: Vec::new(),
+            name_transform: NameTransform::None,
+            column_spec: None,
+            columns: Vec::new(),
+            row_filter: None,
+            mapping_sidecar: true,
+            schema_sidecar: false,
+            error_report: false,
+        }
+    }
+}
+
+/// Chunks collected in memory by [`convert_collect`], in file order
+#[derive(Debug)]
+pub struct ReadStatCollected {
+    pub rows: usize,
+    pub schema: Schema,
+    pub chunks: Vec<Chunk<Box<dyn Array>>>,
+    pub warnings: Vec<String>,
+    /// Every warning grouped by category and column; warnings about values are counted here
+    ///   and appear within `warnings` only as a line for each column
+    pub warning_summary: ReadStatWarnings,
+    /// Set when `options.detect_dates` is, with the columns that look like dates
+    pub date_detection: Option<ReadStatDateDetection>,
+    /// Most batches ever waiting between reading and writing; at most `options.channel_depth`
+    pub peak_buffered: usize,
+    /// Set when, with `options.best_effort`, the data could not be read in full
+    pub salvage: Option<ReadStatSalvage>,
+    /// Cells modified by `options.normalize_strings`, keyed by column name as written
+    pub strings_normalized: BTreeMap<String, usize>,
+    /// Values read as null as they could not be read, with `options.error_mode`, in file order
+    pub cell_errors: Vec<ReadStatCellError>,
+    /// Dates, datetimes, and times read as null as they were beyond `options.date_window` or the
+    /// range of their Arrow type, in file order
+    pub date_range_warnings: Vec<ReadStatDateRangeWarning>,
+    /// Tags of the special missing values observed (e.g. `R` for `.R`), keyed by column name as
+    /// written; recorded whether or not `options.missing_tags` is set
+    pub missing_tags: BTreeMap<String, BTreeSet<char>>,
+    /// Time spent upon each batch by the reader and by the consumer, in file order
+    pub timings: Vec<ReadStatBatchTiming>,
+    /// Variables renamed, redacted, or with truncated labels, as within `<output>.mapping.json`
+    pub mapping: ReadStatMapping,
+    /// Name, Arrow type, SAS format, and label of every column, as within `<stem>.schema.json`
+    pub schema_doc: ReadStatSchema,
+    /// Every file written, in order; more than one when split by `options.split_rows`
+    pub written_paths: Vec<PathBuf>,
+    /// Rows, batches, and bytes read and written, and the time spent reading and writing them
+    pub stats: ReadStatStats,
+}
+
+/// Counts and times of a conversion, gathered while parsing and writing
+///
+/// Parsing and writing overlap, and batches read in parallel are parsed at once, so `parse` and
+/// `write` may each approach `wall` and together exceed it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReadStatStats {
+    /// Rows parsed, including any dropped by `options.row_filter`
+    pub rows_read: usize,
+    /// Rows written to the output, `0` when nothing is written
+    pub rows_written: usize,
+    /// Batches read, and written or collected
+    pub batches: usize,
+    /// Size of each file written, in the order of `written_paths`; empty when writing to
+    /// standard out
+    pub bytes_written: Vec<(PathBuf, u64)>,
+    /// Time spent parsing every batch
+    pub parse: Duration,
+    /// Time spent writing or collecting every batch, including within the closure given to
+    /// [`convert_for_each`]
+    pub write: Duration,
+    /// Time from reading metadata to the finished output
+    pub wall: Duration,
+    /// Bytes reading every row into memory at once is estimated, or with `options.two_pass`
+    /// measured, to need; see [`ReadStatMetadata::estimate_memory_bytes`]
+    pub memory_estimate: u64,
+    /// Reader used, `Reader::stream` in place of `Reader::mem` when the memory estimate exceeds
+    /// `options.mem_limit` and `options.mem_limit_action` is [`MemLimitAction::Stream`]
+    pub reader: Reader,
+    /// Rows of each batch read, fewer than `options.stream_rows` when streaming in place of
+    /// `Reader::mem` would otherwise hold more than `options.mem_limit` in a single batch, or
+    /// `options.batch_rows` when set
+    pub batch_rows: usize,
+    /// Rows of the input already read by the interrupted conversion resumed with
+    /// `options.resume`, and skipped; they are not counted by `rows_read`
+    pub rows_resumed: usize,
+}
+
+impl ReadStatStats {
+    /// Bytes of every file written
+    pub fn total_bytes_written(&self) -> u64 {
+        self.bytes_written.iter().map(|(_, b)| b).sum()
+    }
+}
+
+/// Time spent upon a single batch by each stage of a conversion
+///
+/// Batches are parsed and then handed across a channel to the consumer, which writes, collects,
+/// or passes them to the closure given to [`convert_for_each`].  A reader that is mostly
+/// `blocked` is waiting on a slow consumer, so reading in parallel will not help; one that is
+/// mostly parsing is the bottleneck.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ReadStatBatchTiming {
+    pub row_start: usize,
+    pub rows: usize,
+    /// Parsing, i.e. within ReadStat and the callbacks it invokes
+    pub parse: Duration,
+    /// Handing the batch to the consumer, blocked while the channel is full
+    pub blocked: Duration,
+    /// Consuming the batch once received
+    pub consume: Duration,
+}
+
+impl ReadStatBatchTiming {
+    /// Sum of the time spent upon every batch
+    pub fn total(timings: &[ReadStatBatchTiming]) -> ReadStatBatchTiming {
+        timings
+            .iter()
+            .fold(ReadStatBatchTiming::default(), |acc, t| ReadStatBatchTiming {
+                row_start: 0,
+                rows: acc.rows + t.rows,
+                parse: acc.parse + t.parse,
+                blocked: acc.blocked + t.blocked,
+                consume: acc.consume + t.consume,
+            })
+    }
+}
+
+/// Rows recovered from a file whose data could not be read in full
+#[derive(Clone, Debug)]
+pub struct ReadStatSalvage {
+    pub rows_recovered: usize,
+    pub rows_declared: usize,
+    pub error: String,
+}
+
+impl ReadStatSalvage {
+    pub fn warning(&self) -> String {
+        format!(
+            "{}: recovered {} of {} rows as reading stopped with the error {}",
+            "Warning".bright_yellow(),
+            self.rows_recovered.to_formatted_string(&Locale::en),
+            self.rows_declared.to_formatted_string(&Locale::en),
+            self.error.bright_red()
+        )
+    }
+}
+
+/// Outcome of converting a single input file
+#[derive(Debug)]
+pub struct ReadStatConversionResult {
+    pub input: PathBuf,
+    pub output: Option<PathBuf>,
+    pub rows: usize,
+    pub duration: Duration,
+    pub warnings: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl ReadStatConversionResult {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+
+    fn failed(input: &std::path::Path, start: Instant, error: String) -> Self {
+        Self {
+            input: input.to_path_buf(),
+            output: None,
+            rows: 0,
+            duration: start.elapsed(),
+            warnings: Vec::new(),
+            error: Some(error),
+        }
+    }
+}
+
+/// Convert each input file, writing `<stem>.<format>` into `options.out_dir` (or next to the input)
+///
+/// Nothing is printed; every outcome, including failures, is reported in the returned results
+/// which are in the same order as `inputs`.  With `options.output_template`, every output name is
+/// resolved ahead of converting; an invalid template or two inputs resolving to the same output
+/// fails every input without converting any.
+pub fn convert_many(
+    inputs: &[PathBuf],
+    options: &ReadStatConvertOptions,
+) -> Vec<ReadStatConversionResult> {
+    let start = Instant::now();
+    let outputs: Vec<Result<PathBuf, String>> = match &options.output_template {
+        None => inputs.iter().map(|i| Ok(output_path(i, options))).collect(),
+        Some(t) => match ReadStatOutputTemplate::parse(t)
+            .and_then(|t| t.resolve(inputs, options.out_dir.as_deref(), options.format))
+        {
+            Ok(outputs) => outputs,
+            Err(e) => {
+                return inputs
+                    .iter()
+                    .map(|i| ReadStatConversionResult::failed(i, start, e.to_string()))
+                    .collect()
+            }
+        },
+    };
+
+    inputs
+        .iter()
+        .zip(outputs)
+        .map(|(input, output)| {
+            let start = Instant::now();
+            let output = match output {
+                Ok(o) => o,
+                Err(e) => return ReadStatConversionResult::failed(input, start, e),
+            };
+
+            let rsp = ReadStatPath::new(
+                input.clone(),
+                Some(output),
+                Some(options.format),
+                options.overwrite,
+                false,
+                options.compression,
+                options.compression_level,
+            );
+
+            match rsp {
+                Err(e) => ReadStatConversionResult::failed(input, start, e.to_string()),
+                Ok(rsp) => {
+                    let mut warnings = rsp.warnings.clone();
+                    let converted = convert_collect(&rsp, options);
+                    let (rows, error) = match converted {
+                        Ok(c) => {
+                            warnings.extend(c.warnings);
+                            (c.rows, None)
+                        }
+                        Err(e) => (0, Some(e.to_string())),
+                    };
+
+                    ReadStatConversionResult {
+                        input: input.clone(),
+                        output: rsp.out_path.clone(),
+                        rows,
+                        duration: start.elapsed(),
+                        warnings,
+                        error,
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+fn output_path(input: &std::path::Path, options: &ReadStatConvertOptions) -> PathBuf {
+    // cars.sas7bdat.gz is written as cars.parquet
+    let input = without_compression_extension(input);
+    let file_name = input
+        .with_extension(options.format.to_string())
+        .file_name()
+        .map(|f| f.to_owned())
+        .unwrap_or_default();
+
+    match &options.out_dir {
+        Some(dir) => dir.join(file_name),
+        None => input.with_extension(options.format.to_string()),
+    }
+}
+
+/// Reader to use given a limit, in bytes, on the memory the mem reader may use
+///
+/// Only `Reader::mem` is affected.  If reading `rows` rows into memory is estimated (see
+/// [`ReadStatMetadata::estimate_memory_bytes`]) to exceed `mem_limit`, then either an error is
+/// returned or, with `MemLimitAction::Stream`, `Reader::stream` is returned along with a warning.
+pub fn resolve_reader(
+    md: &ReadStatMetadata,
+    rows: u32,
+    reader: Reader,
+    mem_limit: Option<u64>,
+    action: MemLimitAction,
+) -> Result<(Reader, Option<String>), Box<dyn Error + Send + Sync>> {
+    let estimate = md.estimate_memory_bytes(rows);
+    resolve_reader_with(estimate, "estimated", rows, reader, mem_limit, action)
+}
+
+// As resolve_reader, given the bytes reading `rows` rows into memory is `how` to need
+fn resolve_reader_with(
+    estimate: u64,
+    how: &str,
+    rows: u32,
+    reader: Reader,
+    mem_limit: Option<u64>,
+    action: MemLimitAction,
+) -> Result<(Reader, Option<String>), Box<dyn Error + Send + Sync>> {
+    let limit = match (reader, mem_limit) {
+        (Reader::mem, Some(limit)) => limit,
+        _ => return Ok((reader, None)),
+    };
+
+    if estimate <= limit {
+        return Ok((reader, None));
+    }
+
+    let mb = |bytes: u64| (bytes / (1024 * 1024)).to_formatted_string(&Locale::en);
+
+    match action {
+        MemLimitAction::Error => Err(From::from(format!(
+            "Reading {} rows into memory is {} to use {} MB, above the limit of {} MB; use {} instead",
+            rows.to_formatted_string(&Locale::en),
+            how,
+            mb(estimate),
+            mb(limit),
+            "--reader stream".bright_cyan()
+        ))),
+        MemLimitAction::Stream => Ok((
+            Reader::stream,
+            Some(format!(
+                "{}: reading {} rows into memory is {} to use {} MB, above the limit of {} MB; streaming instead",
+                "Warning".bright_yellow(),
+                rows.to_formatted_string(&Locale::en),
+                how,
+                mb(estimate),
+                mb(limit)
+            )),
+        )),
+    }
+}
+
+// Rows of a batch, at most `stream_rows` and `MAX_BATCH_ROWS`, that fit within `limit` given
+//   that `rows` rows need `bytes`; always at least one
+fn rows_within_limit(bytes: u64, rows: u32, limit: u64, stream_rows: u32) -> u32 {
+    let row_bytes = bytes.div_ceil(u64::from(rows.max(1))).max(1);
+    let fit = u32::try_from(limit / row_bytes).unwrap_or(u32::MAX);
+    fit.clamp(1, stream_rows.clamp(1, MAX_BATCH_ROWS))
+}
+
+/// Convert the input described by `rsp` into its output path and format
+///
+/// Returns the number of rows written.  Nothing is printed to standard out or standard error.
+/// If any variable is renamed, redacted, or has its label or format truncated,
+/// `<output>.mapping.json` is also written unless `options.mapping_sidecar` is false.  With
+/// `options.schema_sidecar`, `<stem>.schema.json` is also written.
+pub fn convert(
+    rsp: &ReadStatPath,
+    options: &ReadStatConvertOptions,
+) -> Result<usize, Box<dyn Error + Send + Sync>> {
+    convert_collect(rsp, options).map(|c| c.rows)
+}
+
+/// Convert the input described by `rsp`, collecting each chunk in memory as directed by
+/// `options.output_mode`
+///
+/// With `OutputMode::Collect` nothing is written, so `rsp` needs no output path.  With
+/// `OutputMode::Write` no chunks are collected and this behaves as [`convert`].  Chunks are
+/// returned in file order, even when read in parallel.  Should the bytes of string values
+/// suggest the file's declared encoding is wrong, a warning is returned rather than printed.
+pub fn convert_collect(
+    rsp: &ReadStatPath,
+    options: &ReadStatConvertOptions,
+) -> Result<ReadStatCollected, Box<dyn Error + Send + Sync>> {
+    convert_with(rsp, options, Arc::default(), |_| Ok(()), |_| Ok(()))
+}
+
+/// Convert the input described by `rsp` as [`convert_collect`] does, passing each chunk to `f`
+/// once it has been written
+///
+/// Chunks are passed in file order, even when reading in parallel.  An error returned by `f`
+/// stops the conversion.  The time spent within `f` is included in the `consume` time of each
+/// batch.
+pub fn convert_for_each<F>(
+    rsp: &ReadStatPath,
+    options: &ReadStatConvertOptions,
+    f: F,
+) -> Result<ReadStatCollected, Box<dyn Error + Send + Sync>>
+where
+    F: FnMut(&Chunk<Box<dyn Array>>) -> Result<(), Box<dyn Error + Send + Sync>>,
+{
+    convert_with(rsp, options, Arc::default(), |_| Ok(()), f)
+}
+
+// Sent from the thread reading for a ReadStatBatches; the schema is sent once, ahead of any chunk
+enum Batch {
+    Schema(Schema),
+    Chunk(Chunk<Box<dyn Array>>),
+}
+
+/// Chunks of an input, yielded as they are read; see [`read_batches`]
+pub struct ReadStatBatches {
+    schema: Schema,
+    r: Receiver<Batch>,
+    reading: Option<JoinHandle<Result<ReadStatCollected, Box<dyn Error + Send + Sync>>>>,
+    collected: Option<ReadStatCollected>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl ReadStatBatches {
+    /// Schema every chunk shares, known before the first is read
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Rows, warnings, and timings of the read, once every chunk has been yielded
+    ///
+    /// No chunks are kept, so `chunks` is always empty.
+    pub fn collected(&self) -> Option<&ReadStatCollected> {
+        self.collected.as_ref()
+    }
+
+    pub(crate) fn into_collected(mut self) -> Option<ReadStatCollected> {
+        self.collected.take()
+    }
+
+    // Set to stop reading at the next value
+    #[cfg(feature = "async")]
+    pub(crate) fn cancel(&self) -> Arc<AtomicBool> {
+        self.cancel.clone()
+    }
+
+    // Wait for reading to stop, keeping what it returns
+    fn join(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if let Some(reading) = self.reading.take() {
+            let collected = reading
+                .join()
+                .map_err(|_| -> Box<dyn Error + Send + Sync> {
+                    From::from("Thread reading data panicked")
+                })??;
+            self.collected = Some(collected);
+        }
+        Ok(())
+    }
+}
+
+// Reading stops at the next value rather than once the next chunk is read
+impl Drop for ReadStatBatches {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Iterator for ReadStatBatches {
+    type Item = Result<Chunk<Box<dyn Array>>, Box<dyn Error + Send + Sync>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.r.recv() {
+            Ok(Batch::Chunk(c)) => Some(Ok(c)),
+            Ok(Batch::Schema(_)) => unreachable!(),
+            // every chunk has been sent, or reading failed
+            Err(_) => self.join().err().map(Err),
+        }
+    }
+}
+
+/// Read the input described by `rsp` as [`convert_for_each`] does, yielding each chunk rather
+/// than writing it
+///
+/// Nothing is written and no chunks are kept; `options.output_mode` is ignored.  Chunks hold
+/// `options.stream_rows` rows but for the last, which holds whatever rows remain, and are yielded
+/// in file order, even when reading in parallel.  Reading runs on its own thread, at most
+/// `options.channel_depth` chunks ahead of the consumer, so files larger than memory may be read.
+/// The schema is known once this returns; an error while reading is yielded by the iterator,
+/// which then ends.  Dropping the iterator early stops reading.
+pub fn read_batches(
+    rsp: &ReadStatPath,
+    options: &ReadStatConvertOptions,
+) -> Result<ReadStatBatches, Box<dyn Error + Send + Sync>> {
+    let (s, r) = bounded(options.channel_depth);
+    let rsp = rsp.clone();
+    let options = ReadStatConvertOptions {
+        output_mode: OutputMode::Discard,
+        ..options.clone()
+    };
+    let cancel = Arc::new(AtomicBool::new(false));
+    let reading_cancel = cancel.clone();
+
+    let reading = thread::spawn(move || {
+        let stopped = || -> Box<dyn Error + Send + Sync> {
+            From::from("Reading stopped as the batches are no longer wanted")
+        };
+        let schema_s = s.clone();
+        convert_with(
+            &rsp,
+            &options,
+            reading_cancel,
+            move |schema| {
+                schema_s.send(Batch::Schema(schema.clone())).map_err(|_| stopped())
+            },
+            move |chunk| s.send(Batch::Chunk(chunk.clone())).map_err(|_| stopped()),
+        )
+    });
+
+    let mut batches = ReadStatBatches {
+        schema: Schema::default(),
+        r,
+        reading: Some(reading),
+        collected: None,
+        cancel,
+    };
+
+    match batches.r.recv() {
+        Ok(Batch::Schema(schema)) => {
+            batches.schema = schema;
+            Ok(batches)
+        }
+        // reading failed before the schema was known
+        _ => Err(batches
+            .join()
+            .err()
+            .unwrap_or_else(|| From::from("Reading stopped before the schema was known"))),
+    }
+}
+
+// Writer of the output of a conversion, printing nothing
+pub(crate) fn writer(options: &ReadStatConvertOptions) -> ReadStatWriter {
+    ReadStatWriter::new()
+        .set_quiet(true)
+        .set_ipc_compression(options.ipc_compression)
+        .set_row_group_bytes(options.row_group_bytes)
+        .set_bom(options.bom)
+        .set_line_ending(options.line_ending)
+        .set_csv_options(options.csv.clone())
+        .set_apply_formats(options.apply_formats)
+        .set_write_retries(options.write_retries, options.write_retry_delay)
+        .set_split_rows(options.split_rows)
+}
+
+// ReadStat records the rows of a file ahead of writing any, which are not known until filtered
+pub(crate) fn check_row_filter(
+    rsp: &ReadStatPath,
+    options: &ReadStatConvertOptions,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    match (&options.row_filter, rsp.format) {
+        (Some(_), OutFormat::sav | OutFormat::dta) => Err(From::from(format!(
+            "Filtering rows is not supported for {} files",
+            rsp.format
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn convert_with<S, F>(
+    rsp: &ReadStatPath,
+    options: &ReadStatConvertOptions,
+    cancel: Arc<AtomicBool>,
+    on_schema: S,
+    mut f: F,
+) -> Result<ReadStatCollected, Box<dyn Error + Send + Sync>>
+where
+    S: FnOnce(&Schema) -> Result<(), Box<dyn Error + Send + Sync>>,
+    F: FnMut(&Chunk<Box<dyn Array>>) -> Result<(), Box<dyn Error + Send + Sync>>,
+{
+    let start = Instant::now();
+    let write = matches!(options.output_mode, OutputMode::Write | OutputMode::WriteAndCollect);
+    let collect = matches!(options.output_mode, OutputMode::Collect | OutputMode::WriteAndCollect);
+    if write {
+        check_row_filter(rsp, options)?;
+    }
+
+    // Progress of an interrupted conversion, whose rows are skipped
+    let mut checkpoint = if write && (options.checkpoint || options.resume) {
+        Some(ReadStatCheckpoint::begin(rsp, options)?)
+    } else {
+        None
+    };
+    let rows_resumed = checkpoint.as_ref().map_or(0, |c| c.rows_read as u32);
+    let row_offset = options.row_offset.saturating_add(rows_resumed);
+    let rows_wanted = options.rows.map(|r| r.saturating_sub(rows_resumed));
+
+    // Read metadata
+    let mut md = ReadStatMetadata::new()
+        .set_datetime_type(options.datetime_type)
+        .set_time_handling(options.time_handling)
+        .set_infer_integers(options.infer_integers)
+        .set_string_encoding(options.string_encoding)
+        .set_encoding(options.encoding.clone())
+        .set_best_effort(options.best_effort)
+        .set_dedup_columns(options.dedup_columns)
+        .set_name_transform(options.name_transform)
+        .set_apply_value_labels(options.apply_value_labels);
+    md.read_metadata(rsp, false)?;
+    md.require_variables(rsp)?;
+
+    // Booleans
+    if let Some(b) = &options.booleans {
+        apply_booleans(rsp, &mut md, b, options.stream_rows)?;
+    }
+
+    // Dates stored as plain numerics
+    let date_detection = options
+        .detect_dates
+        .map(|detect| {
+            apply_dates(
+                rsp,
+                &mut md,
+                detect,
+                options.detect_dates_threshold,
+                options.detect_dates_sample_rows,
+                options.stream_rows,
+            )
+        })
+        .transpose()?;
+    let date_warnings = match (&date_detection, options.detect_dates) {
+        (Some(d), Some(DetectDates::Apply)) => d.warnings(),
+        _ => Vec::new(),
+    };
+
+    // Value labels
+    let label_warnings = match &options.catalog {
+        Some(catalog) => md.read_value_labels(catalog)?,
+        None => Vec::new(),
+    };
+
+    // Strings to normalize, named as within the file
+    let normalize_vars = options.normalize_strings.resolve(&md)?;
+
+    // Columns to redact, named as within the file
+    options.redact.apply(&mut md)?;
+
+    // Strings stored as dictionaries, including labels and hashes
+    apply_string_encoding(rsp, &mut md, options.stream_rows)?;
+
+    // Labels and formats
+    let truncated = match options.max_label_length {
+        Some(max) => md.limit_label_lengths(max, options.strict)?,
+        None => Vec::new(),
+    };
+
+    // Names, already transformed while reading metadata
+    let spec_warnings = match &options.column_spec {
+        Some(spec) => spec.apply(&mut md, &options.renames)?,
+        None => Vec::new(),
+    };
+    if !options.columns.is_empty() {
+        md.set_columns(&options.columns)?;
+    }
+    md.rename_vars(&options.renames)?;
+
+    // Columns as written
+    let selected = md.selected();
+    let mapping = ReadStatMapping::new(&selected);
+    let mut schema = selected.schema.clone();
+    if options.missing_tags {
+        let fields = missing_tag_fields(&selected.vars, &selected.schema.fields);
+        schema.fields.extend(fields.into_iter().map(|(_, f)| f));
+    }
+    let schema_doc = ReadStatSchema::new(&selected);
+    let final_names: BTreeMap<i32, String> = md
+        .vars
+        .keys()
+        .map(|k| (*k, String::from(md.final_name(*k))))
+        .collect();
+    on_schema(&schema)?;
+
+    // Encoding strings are decoded from, checked against the bytes observed once read
+    let declared = options
+        .encoding
+        .clone()
+        .unwrap_or_else(|| md.file_encoding.clone());
+
+    // Determine row count, from the offset on
+    let rows_after_offset = (md.row_count as u32).saturating_sub(row_offset);
+    let total_rows_to_process = match rows_wanted {
+        Some(r) => std::cmp::min(r, rows_after_offset),
+        None => rows_after_offset,
+    };
+
+    // Exact sizes, measured by a first pass that discards every value
+    let measure = if options.two_pass {
+        Some(ReadStatMeasure::scan_from(
+            rsp,
+            &md,
+            &options.normalize_strings,
+            &normalize_vars,
+            row_offset,
+            total_rows_to_process,
+            options.stream_rows,
+        )?)
+    } else {
+        None
+    };
+
+    // Guard against reading a huge file into memory; nothing is printed so an estimated warning
+    //   is dropped, whereas a measured one is returned as only known here
+    let (memory_estimate, how) = match &measure {
+        Some(m) => (m.memory_bytes(&md, 0, total_rows_to_process as usize), "measured"),
+        None => (md.estimate_memory_bytes(total_rows_to_process), "estimated"),
+    };
+    let (reader_used, reader_warning) = resolve_reader_with(
+        memory_estimate,
+        how,
+        total_rows_to_process,
+        options.reader,
+        options.mem_limit,
+        options.mem_limit_action,
+    )?;
+    let reader_warning = reader_warning.filter(|_| measure.is_some());
+
+    // Determine stream row count; streaming in place of the mem reader, each batch is kept
+    //   within the limit, unless the caller chose the rows of each batch
+    let total_rows_to_stream =
+        match (options.batch_rows, options.reader, reader_used, options.mem_limit) {
+            (Some(rows), ..) => rows.get(),
+            (None, Reader::mem, Reader::stream, Some(limit)) => {
+                let rows = rows_within_limit(
+                    memory_estimate,
+                    total_rows_to_process,
+                    limit,
+                    options.stream_rows,
+                );
+                debug!(
+                    "mem reader {} to use {} bytes, above the limit of {} bytes; streaming {} rows at a time",
+                    how, memory_estimate, limit, rows
+                );
+                rows
+            }
+            (None, _, Reader::stream, _) => options.stream_rows,
+            (None, _, Reader::mem, _) => total_rows_to_process,
+        };
+
+    // Initialize AtomicUsize to contain total rows processed
+    let total_rows_processed = Arc::new(AtomicUsize::new(0));
+
+    // Build up offsets
+    let offsets = build_offsets(total_rows_to_process, total_rows_to_stream)?;
+
+    // Create channels with a capacity of channel_depth batches
+    // Unbounded channels can result in extreme memory usage if files are large and
+    //   the reader significantly outpaces the writer; a full channel blocks the reader
+    let (s, r) = bounded(std::cmp::max(options.channel_depth, 1));
+
+    // cancel is set once writing stops so that reading stops too rather than parsing to no end,
+    //   or by the caller to stop early
+    let peak_buffered = Arc::new(AtomicUsize::new(0));
+
+    // Time spent parsing and then blocked sending each batch, keyed by its starting row
+    let reader_timings: Arc<Mutex<Vec<(usize, Duration, Duration)>>> =
+        Arc::new(Mutex::new(Vec::new()));
+
+    // Process data in batches (i.e. stream chunks of rows)
+    // Only files with enough rows to share among threads are read in parallel
+    let threads = if options.parallel && total_rows_to_process >= options.parallel_min_rows {
+        options.parallelism.unwrap_or(0)
+    } else {
+        1
+    };
+    let no_progress = options.no_progress;
+    let best_effort = options.best_effort;
+    let error_mode = options.error_mode;
+    let float_handling = options.float_handling;
+    let date_window = options.date_window;
+    let strict_dates = options.strict_dates;
+    let missing_tags = options.missing_tags;
+    let string_trim = options.string_trim;
+    let user_missing = options.user_missing;
+    let normalize = options.normalize_strings.clone();
+    let row_filter = options.row_filter.clone();
+    let reader_rsp = rsp.clone();
+    let reader_trp = total_rows_processed.clone();
+    let reader_cancel = cancel.clone();
+    let reader_peak = peak_buffered.clone();
+    let reader_times = reader_timings.clone();
+
+    let reader = thread::spawn(move || -> Result<(), Box<dyn Error + Send + Sync>> {
+        // Run in parallel or not?
+        // Controlled via number of threads in a rayon threadpool local to this conversion
+        //   so that repeated conversions within the same process do not collide
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build()?;
+
+        // Iterate over offset pairs a wave at a time, reading a batch upon each thread and then
+        //   sending the wave over a channel to the writer in row order, so that batches arrive
+        //   in the order of the file whether or not they are read in parallel
+        // 📝 For each iteration a new instance of ReadStatData is created
+        let pairs: Vec<(u32, u32)> = offsets.windows(2).map(|w| (w[0], w[1])).collect();
+        let pairs_cnt = pairs.len();
+
+        // Without a first pass to measure them, the strings of each wave are sized from those of
+        //   the wave before, rather than grown from nothing batch after batch
+        let mut capacities: Option<Vec<usize>> = None;
+
+        for wave in pairs.chunks(pool.current_num_threads()) {
+            let read: Vec<Result<(ReadStatData, Duration), Box<dyn Error + Send + Sync>>> =
+                pool.install(|| {
+                    wave.par_iter()
+                        .map(|(row_start, row_end)| {
+                            let (row_start, row_end) = (*row_start, *row_end);
+
+                            // Nothing is left to receive, so do not begin reading
+                            if reader_cancel.load(Ordering::Relaxed) {
+                                return Err(From::from("Reading was cancelled"));
+                            }
+
+                            // Initialize ReadStatData struct
+                            let d = match &row_filter {
+                                Some(f) => ReadStatData::new()
+                                    .set_row_filter(&f.column, f.predicate.clone()),
+                                None => ReadStatData::new(),
+                            };
+                            let mut d = d
+                                .set_no_progress(no_progress)
+                                .set_total_rows_to_process(total_rows_to_process as usize)
+                                .set_total_rows_processed(reader_trp.clone())
+                                .set_cancel(reader_cancel.clone())
+                                .set_best_effort(best_effort)
+                                .set_error_mode(error_mode)
+                                .set_float_handling(float_handling)
+                                .set_date_window(date_window)
+                                .set_strict_dates(strict_dates)
+                                .set_missing_tags(missing_tags)
+                                .set_string_trim(string_trim)
+                                .set_user_missing(user_missing)
+                                .set_string_normalize(normalize.clone(), normalize_vars.clone())
+                                .set_string_capacities(match &measure {
+                                    Some(m) => Some(m.string_bytes_between(
+                                        row_start as usize,
+                                        row_end as usize,
+                                    )),
+                                    None => capacities.clone(),
+                                })
+                                .init(md.clone(), row_start, row_end)
+                                .set_row_offset(row_offset);
+
+                            // Read
+                            let parsing = Instant::now();
+                            d.read_data(&reader_rsp)?;
+                            Ok((d, parsing.elapsed()))
+                        })
+                        .collect()
+                });
+
+            // The error of the earliest batch to fail stops reading
+            for r in read {
+                let (d, parse) = r?;
+                let row_start = d.chunk_row_start;
+                if let Some(c) = d.estimate_string_capacities(total_rows_to_stream as usize) {
+                    capacities = Some(c);
+                }
+
+                // Send, blocking while the channel is full
+                let sending = Instant::now();
+                s.send((d, pairs_cnt)).map_err(|_| -> Box<dyn Error + Send + Sync> {
+                    reader_cancel.store(true, Ordering::Relaxed);
+                    From::from("Error when attempting to send read data for writing")
+                })?;
+                let blocked = sending.elapsed();
+                reader_peak.fetch_max(s.len(), Ordering::Relaxed);
+                if let Ok(mut t) = reader_times.lock() {
+                    t.push((row_start, parse, blocked));
+                }
+            }
+        }
+
+        // Drop sender so that receive iterator will eventually exit
+        drop(s);
+
+        Ok(())
+    });
+
+    // Write, continuing from the segments of an interrupted conversion
+    let mut wtr = writer(options);
+    if let Some(c) = &checkpoint {
+        wtr = wtr
+            .set_checkpoint_rows(Some(options.checkpoint_rows))
+            .set_resume_from(c.segments, c.rows_written as usize, c.bytes);
+    }
+
+    // Collected chunks are keyed by their starting row, within which order they arrive
+    let mut collected: Vec<(usize, Chunk<Box<dyn Array>>)> = Vec::new();
+
+    // Every batch must agree with the schema of the first
+    let mut lock = ReadStatSchemaLock::new(options.coerce_across_batches);
+
+    // String bytes observed across every batch
+    let mut detector = ReadStatEncodingDetector::new();
+
+    // Cells modified by string normalization across every batch
+    let mut strings_normalized: BTreeMap<String, usize> = BTreeMap::new();
+
+    // Warnings about individual values across every batch
+    let mut warning_summary = ReadStatWarnings::new();
+
+    // Values that could not be read across every batch
+    let mut cell_errors: Vec<ReadStatCellError> = Vec::new();
+
+    // Dates read as null across every batch
+    let mut date_range_warnings: Vec<ReadStatDateRangeWarning> = Vec::new();
+
+    // Tags of the special missing values observed across every batch
+    let mut observed_tags: BTreeMap<String, BTreeSet<char>> = BTreeMap::new();
+
+    // Row at which reading first failed, and why, along with the rows read beyond it and dropped
+    let mut failed: Option<(usize, String)> = None;
+    let mut rows_dropped = 0;
+
+    // Time spent consuming each batch, keyed by its starting row
+    let mut consumed: BTreeMap<usize, (usize, Duration)> = BTreeMap::new();
+
+    // Rows parsed, filtered or not, across every batch
+    let mut rows_read = 0;
+
+    let written = (|| -> Result<(), Box<dyn Error + Send + Sync>> {
+        for (i, (mut d, pairs_cnt)) in r.iter().enumerate() {
+            let consuming = Instant::now();
+            rows_read += d.chunk_rows_read;
+
+            // Only rows ahead of the first failure are kept
+            let beyond_failure = matches!(&failed, Some((row, _)) if d.chunk_row_start >= *row);
+            if beyond_failure {
+                rows_dropped += d.chunk_rows_processed;
+                d.chunk = None;
+            }
+            if let Some(e) = d.salvage_error.take() {
+                let row = d.chunk_row_start + d.chunk_rows_read;
+                if !matches!(&failed, Some((f, _)) if *f <= row) {
+                    failed = Some((row, e));
+                }
+            }
+
+            lock.check(&mut d)?;
+            detector.merge(&d.encoding_detector);
+            if !beyond_failure {
+                for (k, n) in &d.strings_normalized {
+                    let name = final_names.get(k).cloned().unwrap_or_default();
+                    *strings_normalized.entry(name).or_insert(0) += n;
+                }
+                for (k, vm) in d.vars.iter().filter(|(_, vm)| !vm.var_missing_tags.is_empty()) {
+                    let name = final_names.get(k).cloned().unwrap_or_default();
+                    observed_tags
+                        .entry(name)
+                        .or_default()
+                        .extend(&vm.var_missing_tags);
+                }
+                warning_summary.merge(std::mem::take(&mut d.warnings));
+                cell_errors.extend(d.cell_errors.drain(..).map(|e| ReadStatCellError {
+                    column: final_names.get(&e.var_index).cloned().unwrap_or_default(),
+                    ..e
+                }));
+                date_range_warnings.extend(d.date_range_warnings.drain(..).map(|w| {
+                    ReadStatDateRangeWarning {
+                        column: final_names.get(&w.var_index).cloned().unwrap_or_default(),
+                        ..w
+                    }
+                }));
+            }
+            if let ErrorMode::Collect { max } = error_mode {
+                if cell_errors.len() > max {
+                    return Err(From::from(format!(
+                        "Stopped reading as more than {} values could not be read",
+                        max
+                    )));
+                }
+            }
+
+            if write {
+                let checkpoints = wtr.checkpoints();
+                wtr.write(&d, rsp)?;
+
+                // A segment just finished or synced holds every row up to the end of this batch,
+                //   unless rows beyond a failure are being dropped
+                if let (Some(c), Some(p), None) = (checkpoint.as_mut(), &rsp.out_path, &failed) {
+                    if wtr.checkpoints() > checkpoints {
+                        c.record(
+                            p,
+                            u64::from(rows_resumed) + d.chunk_row_end as u64,
+                            wtr.rows_written as u64,
+                            wtr.segments_finished(),
+                            wtr.segment_bytes,
+                        )?;
+                    }
+                }
+
+                if i == (pairs_cnt - 1) {
+                    wtr.finish(&d, rsp)?;
+                }
+            }
+
+            if let Some(c) = &d.chunk {
+                f(c)?;
+            }
+
+            if collect {
+                if let Some(c) = d.chunk.take() {
+                    collected.push((d.chunk_row_start, c));
+                }
+            }
+
+            consumed.insert(
+                d.chunk_row_start,
+                (d.chunk_rows_processed, consuming.elapsed()),
+            );
+
+            // Explicitly drop to save on memory
+            drop(d);
+        }
+        Ok(())
+    })();
+
+    // Dropping the receiver unblocks the reader should writing have stopped early
+    cancel.store(true, Ordering::Relaxed);
+    drop(r);
+
+    let read = reader
+        .join()
+        .map_err(|_| -> Box<dyn Error + Send + Sync> {
+            From::from("Thread reading data panicked")
+        })?;
+
+    // A writing error is reported ahead of the send error it causes within the reader
+    written?;
+    read?;
+
+    // Every segment is merged into the output, so nothing is left to resume
+    if let (Some(_), Some(p)) = (&checkpoint, &rsp.out_path) {
+        ReadStatCheckpoint::remove(p)?;
+    }
+
+    // Mapping sidecar; there is nothing to write it next to when writing to standard out, a pipe,
+    //   or a device
+    if let Some(p) = rsp.sidecar_base() {
+        if write
+            && options.mapping_sidecar
+            && (mapping.has_renames() || mapping.has_redactions() || mapping.has_truncations())
+        {
+            mapping.write(&ReadStatMapping::sidecar_path(p))?;
+        }
+    }
+
+    // Schema sidecar
+    if let Some(p) = rsp.sidecar_base() {
+        if write && options.schema_sidecar {
+            schema_doc.write(&ReadStatSchema::sidecar_path(p))?;
+        }
+    }
+
+    // Error report
+    cell_errors.sort_by_key(|e| e.row);
+    if let Some(p) = rsp.sidecar_base() {
+        if write && options.error_report {
+            let report = ReadStatErrorReport {
+                table_name: mapping.table_name.clone(),
+                errors: cell_errors.clone(),
+            };
+            report.write(&ReadStatErrorReport::sidecar_path(p))?;
+        }
+    }
+
+    collected.sort_by_key(|(row_start, _)| *row_start);
+    date_range_warnings.sort_by_key(|w| w.row);
+
+    // Timings
+    let mut timings: Vec<ReadStatBatchTiming> = reader_timings
+        .lock()
+        .map(|t| t.clone())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(row_start, parse, blocked)| {
+            let (rows, consume) = consumed.get(&row_start).copied().unwrap_or_default();
+            ReadStatBatchTiming {
+                row_start,
+                rows,
+                parse,
+                blocked,
+                consume,
+            }
+        })
+        .collect();
+    timings.sort_by_key(|t| t.row_start);
+
+    // Statistics
+    let total = ReadStatBatchTiming::total(&timings);
+    let stats = ReadStatStats {
+        rows_read,
+        rows_written: wtr.rows_written,
+        batches: consumed.len(),
+        bytes_written: wtr
+            .written_paths
+            .iter()
+            .cloned()
+            .zip(wtr.bytes_written.iter().copied())
+            .collect(),
+        parse: total.parse,
+        write: total.consume,
+        wall: start.elapsed(),
+        memory_estimate,
+        reader: reader_used,
+        batch_rows: total_rows_to_stream as usize,
+        rows_resumed: rows_resumed as usize,
+    };
+
+    // Warnings about values, a line for each column rather than for each value
+    let mut warnings: Vec<String> = warning_summary.groups().iter().map(|g| g.line()).collect();
+
+    // Encoding
+    let encoding: Vec<String> = detector.warning(&declared).into_iter().collect();
+    let reader_warning: Vec<String> = reader_warning.into_iter().collect();
+    for (category, w) in [
+        (ReadStatWarningCategory::Encoding, encoding),
+        (ReadStatWarningCategory::DateDetection, date_warnings),
+        (ReadStatWarningCategory::ValueLabels, label_warnings),
+        (ReadStatWarningCategory::MemLimit, reader_warning),
+        (ReadStatWarningCategory::Truncation, truncated),
+        (ReadStatWarningCategory::ColumnSpec, spec_warnings),
+        (ReadStatWarningCategory::WriteRetry, std::mem::take(&mut wtr.warnings)),
+        (ReadStatWarningCategory::OutputName, std::mem::take(&mut wtr.name_warnings)),
+        (ReadStatWarningCategory::DirectOutput, std::mem::take(&mut wtr.direct_warnings)),
+    ] {
+        warning_summary.record_all(category, &w);
+        warnings.extend(w);
+    }
+
+    // Salvage
+    let rows = total_rows_processed.load(Ordering::SeqCst) - rows_dropped;
+    let salvage = failed.map(|(_, error)| ReadStatSalvage {
+        rows_recovered: rows,
+        rows_declared: total_rows_to_process as usize,
+        error,
+    });
+    if let Some(s) = &salvage {
+        let w = s.warning();
+        warning_summary.record_all(ReadStatWarningCategory::Salvage, std::slice::from_ref(&w));
+        warnings.push(w);
+    }
+
+    Ok(ReadStatCollected {
+        rows,
+        schema,
+        chunks: collected.into_iter().map(|(_, c)| c).collect(),
+        warnings,
+        warning_summary,
+        date_detection,
+        peak_buffered: peak_buffered.load(Ordering::Relaxed),
+        salvage,
+        strings_normalized,
+        cell_errors,
+        date_range_warnings,
+        missing_tags: observed_tags,
+        timings,
+        mapping,
+        schema_doc,
+        written_paths: wtr.written_paths,
+        stats,
+    })
+}
@@ -1,51 +1,145 @@
 use arrow2::{
-    array::{Array, PrimitiveArray, Utf8Array},
+    array::{new_empty_array, Array, BooleanArray, MutableUtf8Array, PrimitiveArray, Utf8Array},
     chunk::Chunk,
-    datatypes::{DataType, Schema, TimeUnit},
+    compute::cast::{cast, CastOptions},
+    datatypes::{DataType, Field, Schema, TimeUnit},
 };
 use colored::Colorize;
 use indicatif::{ProgressBar, ProgressStyle};
 use log::debug;
+use num_format::{Locale, ToFormattedString};
 use num_traits::FromPrimitive;
 use path_abs::PathInfo;
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     error::Error,
+    ops::ControlFlow,
     os::raw::c_void,
-    sync::{atomic::AtomicUsize, Arc},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize},
+        Arc,
+    },
 };
 
 use crate::{
     cb,
-    err::ReadStatError,
+    err::{ReadStatCancelled, ReadStatError, ReadStatParseError},
+    rs_cell_errors::{ErrorMode, ReadStatCellError},
+    rs_date_window::{ReadStatDateRangeWarning, ReadStatDateWindow},
+    rs_encoding::ReadStatEncodingDetector,
+    rs_filter::{FilterPredicate, ReadStatRowFilter},
     rs_metadata::{ReadStatMetadata, ReadStatVarMetadata},
+    rs_missing_tags::missing_tag_fields,
+    rs_normalize::ReadStatStringNormalize,
     rs_parser::ReadStatParser,
     rs_path::ReadStatPath,
-    rs_var::ReadStatVar,
+    rs_redact::ReadStatRedaction,
+    rs_summary::{ReadStatColumnSummary, DISTINCT_CAP},
+    rs_value_labels::ReadStatLabelling,
+    rs_var::{ReadStatEpoch, ReadStatVar},
+    rs_warnings::ReadStatWarnings,
+    FloatHandling, TrimMode, UserMissing,
 };
 
+/// Called with the fraction of the file parsed and the rows of the chunk read so far; returning
+/// [`ControlFlow::Break`] cancels reading
+pub type ReadStatProgressCallback = Box<dyn FnMut(f64, u64) -> ControlFlow<()> + Send>;
+
 #[derive(Default)]
 pub struct ReadStatData {
     // metadata
-    pub var_count: i32,
-    pub vars: BTreeMap<i32, ReadStatVarMetadata>,
+    pub(crate) var_count: i32,
+    pub(crate) vars: BTreeMap<i32, ReadStatVarMetadata>,
+    pub(crate) encoding: Option<String>,
     // data
-    pub cols: Vec<Vec<ReadStatVar>>,
-    pub schema: Schema,
+    pub(crate) cols: Vec<Vec<ReadStatVar>>,
+    pub(crate) schema: Schema,
     // chunk
-    pub chunk: Option<Chunk<Box<dyn Array>>>,
-    pub chunk_rows_to_process: usize, // min(stream_rows, row_limit, row_count)
-    pub chunk_row_start: usize,
-    pub chunk_row_end: usize,
-    pub chunk_rows_processed: usize,
+    pub(crate) chunk: Option<Chunk<Box<dyn Array>>>,
+    pub(crate) chunk_rows_to_process: usize, // min(stream_rows, row_limit, row_count)
+    pub(crate) chunk_row_start: usize,
+    pub(crate) chunk_row_end: usize,
+    pub(crate) chunk_rows_processed: usize, // rows kept, so fewer than read when filtering
+    pub(crate) chunk_rows_read: usize,
     // total rows
-    pub total_rows_to_process: usize,
-    pub total_rows_processed: Option<Arc<AtomicUsize>>,
+    pub(crate) total_rows_to_process: usize,
+    pub(crate) total_rows_processed: Option<Arc<AtomicUsize>>,
     // progress
-    pub pb: Option<ProgressBar>,
-    pub no_progress: bool,
+    pub(crate) pb: Option<ProgressBar>,
+    pub(crate) no_progress: bool,
+    // progress reported to, and possibly cancelled by, the caller
+    pub(crate) progress_callback: Option<ReadStatProgressCallback>,
+    pub(crate) progress_cancelled: bool,
     // errors
-    pub errors: Vec<String>,
+    pub(crate) errors: Vec<String>,
+    // values that could not be read, recorded rather than failing when collecting errors
+    pub(crate) error_mode: ErrorMode,
+    pub(crate) cell_errors: Vec<ReadStatCellError>,
+    // cancellation
+    pub(crate) cancel: Option<Arc<AtomicBool>>,
+    // salvage
+    pub(crate) best_effort: bool,
+    pub(crate) salvage_error: Option<String>,
+    // string bytes observed while parsing
+    pub(crate) encoding_detector: ReadStatEncodingDetector,
+    // string normalization, along with the variables normalized and their cells modified
+    pub(crate) normalize: ReadStatStringNormalize,
+    pub(crate) normalize_vars: BTreeSet<i32>,
+    pub(crate) strings_normalized: BTreeMap<i32, usize>,
+    // redaction of the values of variables, keyed by index
+    pub(crate) redactions: BTreeMap<i32, ReadStatRedaction>,
+    // value labels written in place of the values of variables, keyed by index
+    pub(crate) labelled: BTreeMap<i32, ReadStatLabelling>,
+    // variables named by set_columns, resolved against the metadata on init
+    pub(crate) columns: Option<Vec<String>>,
+    // column of each variable read, by variable index, or None for a variable skipped while
+    //   parsing; the column is the index ReadStat reports after skipping.  A row is complete once
+    //   the value of the last variable read arrives
+    pub(crate) positions: Vec<Option<usize>>,
+    pub(crate) last_index: i32,
+    // casts of the variables read, applied once read
+    pub(crate) casts: BTreeMap<i32, DataType>,
+    // rounding of floating point values as read
+    pub(crate) float_handling: FloatHandling,
+    // whitespace trimmed from string values as read
+    pub(crate) string_trim: TrimMode,
+    // values among the missing values a variable declares, read as values or as missing
+    pub(crate) user_missing: UserMissing,
+    // tags of the special missing values of each numeric variable read, by variable index, each
+    //   written to a companion column following every column read
+    pub(crate) missing_tags: bool,
+    pub(crate) missing_tag_cols: BTreeMap<i32, Vec<Option<char>>>,
+    // measuring only, values are discarded once the bytes of strings are counted; otherwise the
+    //   bytes of the strings of each column are those of the arrays built
+    pub(crate) measure_only: bool,
+    pub(crate) string_bytes: Vec<usize>,
+    // bytes of the strings of each column, measured exactly by a first pass or estimated from a
+    //   previous batch
+    pub(crate) string_capacities: Option<Vec<usize>>,
+    // limit upon the bytes the columns are estimated to need, checked ahead of allocating them
+    pub(crate) memory_limit: Option<u64>,
+    pub(crate) memory_estimate: Option<u64>,
+    // summarizing only, values update the statistics of their column and are then discarded
+    pub(crate) summarize: bool,
+    pub(crate) distinct_cap: usize,
+    pub(crate) summaries: Vec<ReadStatColumnSummary>,
+    // warnings about individual values
+    pub(crate) warnings: ReadStatWarnings,
+    // how the file read stores dates, datetimes, and times
+    pub(crate) epoch: ReadStatEpoch,
+    // dates and datetimes beyond the window, or beyond the range of their type, are read as
+    //   missing and recorded, or fail reading when strict
+    pub(crate) date_window: Option<ReadStatDateWindow>,
+    pub(crate) strict_dates: bool,
+    pub(crate) date_range_warnings: Vec<ReadStatDateRangeWarning>,
+    // rows kept only when the value of a variable passes the filter, resolved to its index on
+    //   init; as that value may arrive after the others of its row, the values of each row are
+    //   staged until the row is complete and then either kept or discarded
+    pub(crate) row_filter: Option<ReadStatRowFilter>,
+    pub(crate) filter_index: Option<i32>,
+    pub(crate) staged: Vec<(usize, ReadStatVar)>,
+    pub(crate) staged_tags: Vec<(i32, Option<char>)>,
+    pub(crate) row_kept: Option<bool>,
 }
 
 impl ReadStatData {
@@ -54,6 +148,7 @@ impl ReadStatData {
             // metadata
             var_count: 0,
             vars: BTreeMap::new(),
+            encoding: None,
             // data
             cols: Vec::new(),
             schema: Schema::default(),
@@ -61,6 +156,7 @@ impl ReadStatData {
             chunk: None,
             chunk_rows_to_process: 0,
             chunk_rows_processed: 0,
+            chunk_rows_read: 0,
             chunk_row_start: 0,
             chunk_row_end: 0,
             // total rows
@@ -69,29 +165,152 @@ impl ReadStatData {
             // progress
             pb: None,
             no_progress: false,
+            progress_callback: None,
+            progress_cancelled: false,
             // errors
             errors: Vec::new(),
+            error_mode: ErrorMode::FailFast,
+            cell_errors: Vec::new(),
+            // cancellation
+            cancel: None,
+            // salvage
+            best_effort: false,
+            salvage_error: None,
+            encoding_detector: ReadStatEncodingDetector::new(),
+            // string normalization
+            normalize: ReadStatStringNormalize::default(),
+            normalize_vars: BTreeSet::new(),
+            strings_normalized: BTreeMap::new(),
+            // redaction
+            redactions: BTreeMap::new(),
+            // value labels
+            labelled: BTreeMap::new(),
+            // selection
+            columns: None,
+            positions: Vec::new(),
+            last_index: -1,
+            casts: BTreeMap::new(),
+            float_handling: FloatHandling::ReadStatDigits,
+            string_trim: TrimMode::TrailingWhitespace,
+            user_missing: UserMissing::AsValue,
+            missing_tags: false,
+            missing_tag_cols: BTreeMap::new(),
+            // measurement
+            measure_only: false,
+            string_bytes: Vec::new(),
+            string_capacities: None,
+            memory_limit: None,
+            memory_estimate: None,
+            // summary
+            summarize: false,
+            distinct_cap: DISTINCT_CAP,
+            summaries: Vec::new(),
+            // warnings
+            warnings: ReadStatWarnings::new(),
+            epoch: ReadStatEpoch::Sas,
+            date_window: Some(ReadStatDateWindow::default()),
+            strict_dates: false,
+            date_range_warnings: Vec::new(),
+            // filtering
+            row_filter: None,
+            filter_index: None,
+            staged: Vec::new(),
+            staged_tags: Vec::new(),
+            row_kept: None,
         }
     }
 
-    fn allocate_cols(self) -> Self {
+    fn allocate_cols(mut self) -> Self {
+        // columns estimated to exceed the memory limit are never allocated; the error is reported
+        //   once reading begins
+        let over_limit = match (self.memory_estimate, self.memory_limit) {
+            (Some(estimate), Some(limit)) if estimate > limit => {
+                self.errors.push(format!(
+                    "Reading {} rows into memory is estimated to use {} bytes, above the memory limit of {} bytes; read fewer rows at once, or convert with {} to stream them",
+                    self.chunk_rows_to_process.to_formatted_string(&Locale::en),
+                    estimate.to_formatted_string(&Locale::en),
+                    limit.to_formatted_string(&Locale::en),
+                    "Reader::stream".bright_cyan()
+                ));
+                true
+            }
+            _ => false,
+        };
+
+        // when measuring or summarizing, values are never held so columns need no room
+        let rows = if self.measure_only || self.summarize || over_limit {
+            0
+        } else {
+            self.chunk_rows_to_process
+        };
+
         let mut cols = Vec::with_capacity(self.var_count as usize);
         for _ in 0..self.var_count {
-            cols.push(Vec::with_capacity(self.chunk_rows_to_process))
+            cols.push(Vec::with_capacity(rows))
+        }
+        let string_bytes = vec![0; self.var_count as usize];
+        let summaries = if self.summarize {
+            self.schema
+                .fields
+                .iter()
+                .map(|f| ReadStatColumnSummary::new(&f.name, f.data_type(), self.distinct_cap))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let missing_tag_cols = self
+            .missing_tag_cols
+            .keys()
+            .map(|k| (*k, Vec::with_capacity(rows)))
+            .collect();
+        Self {
+            cols,
+            string_bytes,
+            summaries,
+            missing_tag_cols,
+            ..self
         }
-        Self { cols, ..self }
     }
 
-    fn cols_to_chunk(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+    pub(crate) fn cols_to_chunk(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        // without a value to tell the type of each column, the columns are those of the schema
+        if self.cols.iter().any(|col| col.is_empty()) {
+            let arrays = self
+                .schema
+                .fields
+                .iter()
+                .map(|f| new_empty_array(f.data_type().clone()))
+                .collect();
+            self.chunk = Some(Chunk::try_new(arrays)?);
+            return Ok(());
+        }
+
         // for each column in cols
-        let arrays: Vec<Box<dyn Array>> = self
+        let mut arrays: Vec<Box<dyn Array>> = self
             .cols
             .iter()
-            .map(|col| {
+            .enumerate()
+            .map(|(i, col)| {
                 // what kind of column is this?
                 // grab the first element to determine the column type
                 let col_type = &col[0];
 
+                // with the bytes measured, build strings without ever reallocating
+                let capacity = self.string_capacities.as_ref().and_then(|c| c.get(i));
+                if let (ReadStatVar::ReadStat_String(_), Some(bytes)) = (col_type, capacity) {
+                    let mut array = MutableUtf8Array::<i32>::with_capacities(col.len(), *bytes);
+                    for s in col {
+                        if let ReadStatVar::ReadStat_String(v) = s {
+                            array.push(v.as_deref());
+                        } else {
+                            unreachable!()
+                        }
+                    }
+
+                    let array: Box<dyn Array> = Box::new(<Utf8Array<i32>>::from(array));
+                    return array;
+                }
+
                 // convert from a Vec<ReadStatVar> into a Box<dyn Array>
                 let array: Box<dyn Array> = match col_type {
                     ReadStatVar::ReadStat_String(_) => {
@@ -152,6 +371,20 @@ impl ReadStatData {
 
                         Box::new(<PrimitiveArray<i32>>::from(vec))
                     }
+                    ReadStatVar::ReadStat_i64(_) => {
+                        let vec = col
+                            .iter()
+                            .map(|i| {
+                                if let ReadStatVar::ReadStat_i64(v) = i {
+                                    *v
+                                } else {
+                                    unreachable!()
+                                }
+                            })
+                            .collect::<Vec<Option<i64>>>();
+
+                        Box::new(<PrimitiveArray<i64>>::from(vec))
+                    }
                     ReadStatVar::ReadStat_f32(_) => {
                         let vec = col
                             .iter()
@@ -262,6 +495,34 @@ impl ReadStatData {
                                 .to(DataType::Timestamp(TimeUnit::Nanosecond, None)),
                         )
                     }
+                    ReadStatVar::ReadStat_Date64(_) => {
+                        let vec = col
+                            .iter()
+                            .map(|dt| {
+                                if let ReadStatVar::ReadStat_Date64(v) = dt {
+                                    *v
+                                } else {
+                                    unreachable!()
+                                }
+                            })
+                            .collect::<Vec<Option<i64>>>();
+
+                        Box::new(<PrimitiveArray<i64>>::from(vec).to(DataType::Date64))
+                    }
+                    ReadStatVar::ReadStat_Bool(_) => {
+                        let vec = col
+                            .iter()
+                            .map(|b| {
+                                if let ReadStatVar::ReadStat_Bool(v) = b {
+                                    *v
+                                } else {
+                                    unreachable!()
+                                }
+                            })
+                            .collect::<Vec<Option<bool>>>();
+
+                        Box::new(BooleanArray::from(vec))
+                    }
                     ReadStatVar::ReadStat_Time(_) => {
                         let vec = col
                             .iter()
@@ -278,6 +539,23 @@ impl ReadStatData {
                             <PrimitiveArray<i32>>::from(vec).to(DataType::Time32(TimeUnit::Second)),
                         )
                     }
+                    ReadStatVar::ReadStat_TimeWithMicroseconds(_) => {
+                        let vec = col
+                            .iter()
+                            .map(|t| {
+                                if let ReadStatVar::ReadStat_TimeWithMicroseconds(v) = t {
+                                    *v
+                                } else {
+                                    unreachable!()
+                                }
+                            })
+                            .collect::<Vec<Option<i64>>>();
+
+                        Box::new(
+                            <PrimitiveArray<i64>>::from(vec)
+                                .to(DataType::Time64(TimeUnit::Microsecond)),
+                        )
+                    }
                 };
 
                 // return
@@ -285,6 +563,22 @@ impl ReadStatData {
             })
             .collect();
 
+        // bytes of the strings of each column, from which to size those of a following batch
+        for (i, a) in arrays.iter().enumerate() {
+            if let (Some(s), Some(b)) = (
+                a.as_any().downcast_ref::<Utf8Array<i32>>(),
+                self.string_bytes.get_mut(i),
+            ) {
+                *b = s.values().len();
+            }
+        }
+
+        // companion columns of the tags of special missing values follow every column read
+        for col in self.missing_tag_cols.values() {
+            let tags: Utf8Array<i32> = col.iter().map(|t| t.map(String::from)).collect();
+            arrays.push(Box::new(tags));
+        }
+
         // convert into a chunk
         self.chunk = Some(Chunk::try_new(arrays)?);
 
@@ -292,9 +586,126 @@ impl ReadStatData {
     }
 
     pub fn read_data(&mut self, rsp: &ReadStatPath) -> Result<(), Box<dyn Error + Send + Sync>> {
+        // variables named by set_columns that are not within the file
+        if !self.errors.is_empty() {
+            return Err(From::from(self.errors.join("\n")));
+        }
+
+        // a row limit of 0 would read every row, so a batch without rows, e.g. of a file without
+        //   observations, is not parsed; its chunk holds the columns of the schema, empty
+        if self.chunk_rows_to_process == 0 {
+            if !self.measure_only && !self.summarize {
+                self.cols_to_chunk()?;
+                self.cast_columns()?;
+            }
+            return Ok(());
+        }
+
         // parse data and if successful then convert cols into a chunk
-        self.parse_data(rsp)?;
+        match self.parse_data(rsp) {
+            Ok(()) => (),
+            // keep the rows handled in full ahead of cancelling so that they may still be written
+            Err(e) if self.progress_cancelled => {
+                self.truncate_cols();
+                if !self.measure_only && !self.summarize && self.chunk_rows_processed > 0 {
+                    self.cols_to_chunk()?;
+                    self.cast_columns()?;
+                }
+                return Err(e);
+            }
+            Err(e) if self.best_effort => {
+                // keep only the rows handled in full ahead of the failure
+                self.truncate_cols();
+                self.salvage_error = Some(e.to_string());
+
+                if self.chunk_rows_processed == 0 {
+                    return Ok(());
+                }
+            }
+            Err(e) => return Err(e),
+        }
+
+        // nothing was kept, only measured or summarized
+        if self.measure_only || self.summarize {
+            return Ok(());
+        }
+
         self.cols_to_chunk()?;
+        self.cast_columns()
+    }
+
+    /// Read the first `n` rows of the file as a single chunk without configuring a writer
+    ///
+    /// Set any options ahead of calling; the rows are then available from
+    /// [`ReadStatData::chunk`], with their columns described by [`ReadStatData::schema`].  The
+    /// row count of the metadata is never relied upon, so it may be read with `skip_row_count`;
+    /// fewer rows are read should the file end first.
+    pub fn get_preview(
+        self,
+        md: ReadStatMetadata,
+        rsp: &ReadStatPath,
+        n: usize,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        md.require_variables(rsp)?;
+        let rows = u32::try_from(n).unwrap_or(u32::MAX);
+
+        let mut d = self.set_total_rows_to_process(rows as usize).init(md, 0, rows);
+        d.read_data(rsp)?;
+
+        Ok(d)
+    }
+
+    // Drop the values of a row not handled in full
+    fn truncate_cols(&mut self) {
+        let rows = self.chunk_rows_processed;
+        for col in self.cols.iter_mut() {
+            col.truncate(rows);
+        }
+        for col in self.missing_tag_cols.values_mut() {
+            col.truncate(rows);
+        }
+    }
+
+    // Values are parsed into a column for each variable read with the types of the file, then
+    //   cast
+    fn cast_columns(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.casts.is_empty() {
+            return Ok(());
+        }
+
+        let casts = std::mem::take(&mut self.casts);
+
+        // the cast of each column by position; companion columns of missing value tags, which
+        //   follow the columns of the variables, are never cast
+        let keys: Vec<&i32> = self.vars.keys().collect();
+        let cast_of = |i: usize| keys.get(i).and_then(|k| casts.get(*k));
+
+        let fields: Vec<Field> = self
+            .schema
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| match cast_of(i) {
+                Some(dt) => {
+                    Field::new(&f.name, dt.clone(), f.is_nullable).with_metadata(f.metadata.clone())
+                }
+                None => f.clone(),
+            })
+            .collect();
+
+        if let Some(chunk) = self.chunk.take() {
+            let mut arrays = Vec::with_capacity(fields.len());
+            for (i, array) in chunk.into_arrays().into_iter().enumerate() {
+                arrays.push(match cast_of(i) {
+                    Some(dt) => cast(array.as_ref(), dt, CastOptions::default())?,
+                    None => array,
+                });
+            }
+            self.chunk = Some(Chunk::try_new(arrays)?);
+        }
+
+        self.schema = Schema::from(fields);
+
         Ok(())
     }
 
@@ -324,6 +735,9 @@ impl ReadStatData {
             pb.enable_steady_tick(std::time::Duration::new(120, 0));
         }
 
+        // dates, datetimes, and times are converted from the convention of the file
+        self.epoch = ReadStatEpoch::of_extension(&rsp.extension);
+
         // initialize context
         let ctx = self as *mut ReadStatData as *mut c_void;
 
@@ -332,21 +746,41 @@ impl ReadStatData {
         debug!("Initially, error ==> {:#?}", &error);
 
         // setup parser
-        // once call parse_sas7bdat, iteration begins
-        let error = ReadStatParser::new()
-            // do not set metadata handler nor variable handler as already processed
+        let mut parser = ReadStatParser::new()
+            // do not set metadata handler as already processed
             .set_value_handler(Some(cb::handle_value))?
+            .set_file_character_encoding(self.encoding.as_deref())?
             .set_row_limit(Some(self.chunk_rows_to_process.try_into().unwrap()))?
             .set_row_offset(Some(self.chunk_row_start.try_into().unwrap()))?
-            .parse_sas7bdat(ppath, ctx);
+            .set_input(rsp.source()?.as_ref())?;
+
+        // the variable handler is only needed to skip variables that are not read
+        if self.positions.iter().any(|p| p.is_none()) {
+            parser = parser.set_variable_handler(Some(cb::handle_variable_skip))?;
+        }
+
+        if self.progress_callback.is_some() {
+            parser = parser.set_progress_handler(Some(cb::handle_progress))?;
+        }
+
+        // once call parse, iteration begins
+        let error = parser.parse(&rsp.extension, ppath, ctx);
+
+        if self.progress_cancelled {
+            return Err(Box::new(ReadStatCancelled {
+                rows: self.chunk_rows_processed as u64,
+            }));
+        }
+
+        // values that could not be converted abort parsing; report them ahead of the abort itself
+        if !self.errors.is_empty() {
+            return Err(From::from(self.errors.join("\n")));
+        }
 
         #[allow(clippy::useless_conversion)]
         match FromPrimitive::from_i32(error.try_into().unwrap()) {
             Some(ReadStatError::READSTAT_OK) => Ok(()),
-            Some(e) => Err(From::from(format!(
-                "Error when attempting to parse sas7bdat: {:#?}",
-                e
-            ))),
+            Some(e) => Err(Box::new(ReadStatParseError::new(e))),
             None => Err(From::from(
                 "Error when attempting to parse sas7bdat: Unknown return value",
             )),
@@ -372,8 +806,9 @@ impl ReadStatData {
     */
 
     pub fn init(self, md: ReadStatMetadata, row_start: u32, row_end: u32) -> Self {
-        self.set_metadata(md)
-            .set_chunk_counts(row_start, row_end)
+        // rows are counted first so that the memory they need may be estimated from the metadata
+        self.set_chunk_counts(row_start, row_end)
+            .set_metadata(md)
             .allocate_cols()
     }
 
@@ -388,22 +823,147 @@ impl ReadStatData {
             chunk_row_start,
             chunk_row_end,
             chunk_rows_processed,
+            chunk_rows_read: 0,
             ..self
         }
     }
 
-    fn set_metadata(self, md: ReadStatMetadata) -> Self {
-        let var_count = md.var_count;
+    fn set_metadata(mut self, mut md: ReadStatMetadata) -> Self {
+        // an unknown name is reported once reading begins
+        if let Some(columns) = &self.columns {
+            if let Err(e) = md.set_columns(columns) {
+                self.errors.push(e.to_string());
+            }
+        }
+
+        // estimated from the variables selected, ahead of allocating their columns
+        let memory_estimate = match self.memory_limit {
+            Some(_) if !self.measure_only && !self.summarize => {
+                Some(md.estimate_memory_bytes(self.chunk_rows_to_process as u32))
+            }
+            _ => None,
+        };
+
+        // only the variables selected are read, each into the next column
+        let selection = md.selection.take();
+        let selected = |k: &i32| selection.as_ref().map_or(true, |s| s.contains(k));
+
+        let mut positions = vec![None; md.vars.keys().last().map_or(0, |k| *k as usize + 1)];
+        let mut fields = Vec::with_capacity(md.vars.len());
+        let mut last_index = -1;
+        for (k, f) in md.vars.keys().zip(md.schema.fields.iter()) {
+            if selected(k) {
+                positions[*k as usize] = Some(fields.len());
+                fields.push(f.clone());
+                last_index = *k;
+            }
+        }
+        md.vars.retain(|k, _| selected(k));
+        for (k, vm) in md.vars.iter_mut() {
+            vm.var_index_after_skipping = positions[*k as usize].map(|p| p as i32);
+        }
+
+        // the filter compares the values of a variable as read, ahead of labelling or redaction
+        let mut filter_index = None;
+        if let Some(f) = &self.row_filter {
+            match md.vars.iter().find(|(_, vm)| vm.var_name == f.column) {
+                Some((k, _)) => {
+                    let data_type = match (md.labelled.get(k), md.redactions.get(k)) {
+                        (Some(l), _) => &l.source_type,
+                        (None, Some(ReadStatRedaction::Hash { source_type, .. })) => source_type,
+                        _ => fields[positions[*k as usize].unwrap_or_default()].data_type(),
+                    };
+                    match f.check(data_type) {
+                        Ok(()) => filter_index = Some(*k),
+                        Err(e) => self.errors.push(e),
+                    }
+                }
+                None => self.errors.push(format!(
+                    "The row filter compares the variable {}, which is not among the variables read",
+                    f.column.bright_yellow()
+                )),
+            }
+        }
+
+        let var_count = fields.len() as i32;
+
+        // a companion column for each numeric variable read, following every column read
+        let mut missing_tag_cols = BTreeMap::new();
+        if self.missing_tags {
+            for (k, f) in missing_tag_fields(&md.vars, &fields) {
+                missing_tag_cols.insert(k, Vec::new());
+                fields.push(f);
+            }
+        }
+
         let vars = md.vars;
-        let schema = md.schema;
+        // an encoding set upon the data overrides the one set upon the metadata
+        let encoding = self.encoding.take().or(md.encoding);
+        let schema = Schema::from(fields);
+        let redactions = md.redactions;
+        let labelled = md.labelled;
+        let casts = md.casts;
         Self {
             var_count,
             vars,
+            encoding,
             schema,
+            redactions,
+            labelled,
+            positions,
+            last_index,
+            casts,
+            missing_tag_cols,
+            filter_index,
+            memory_estimate,
+            ..self
+        }
+    }
+
+    /// Read only the named variables, named as within the file; see
+    /// [`ReadStatMetadata::set_columns`]
+    ///
+    /// Must be set ahead of [`ReadStatData::init`].  A name not within the file is an error once
+    /// [`ReadStatData::read_data`] is called.
+    pub fn set_columns(self, columns: Vec<String>) -> Self {
+        Self {
+            columns: Some(columns),
             ..self
         }
     }
 
+    /// Keep only the rows whose value of `column`, named as within the file, passes `predicate`
+    ///
+    /// Must be set ahead of [`ReadStatData::init`]; the column must be read, and a column that
+    /// is not, or whose values cannot be compared with the value of the predicate, is an error
+    /// once [`ReadStatData::read_data`] is called.  Rows are filtered as they are parsed, so
+    /// [`ReadStatData::chunk_rows_processed`] counts the rows kept and those discarded are never
+    /// held.
+    pub fn set_row_filter(self, column: &str, predicate: FilterPredicate) -> Self {
+        Self {
+            row_filter: Some(ReadStatRowFilter {
+                column: String::from(column),
+                predicate,
+            }),
+            ..self
+        }
+    }
+
+    /// Character encoding of the strings within the file (e.g. `WINDOWS-1252`), overriding the
+    /// one the file declares; ReadStat converts every string from it into UTF-8
+    ///
+    /// May be set ahead of or after [`ReadStatData::init`]; when `None`, the encoding set upon
+    /// the metadata, if any, is used.
+    pub fn set_encoding(self, encoding: Option<String>) -> Self {
+        match encoding {
+            Some(e) => Self {
+                encoding: Some(e),
+                ..self
+            },
+            None => self,
+        }
+    }
+
     pub fn set_no_progress(self, no_progress: bool) -> Self {
         Self {
             no_progress,
@@ -418,10 +978,401 @@ impl ReadStatData {
         }
     }
 
+    /// Begin reading `row_offset` rows further into the file than the rows given to
+    /// [`ReadStatData::init`], reading as many rows
+    ///
+    /// Used to resume a conversion part of the way through a file; set once initialized.
+    pub fn set_row_offset(self, row_offset: u32) -> Self {
+        let row_offset = row_offset as usize;
+        Self {
+            chunk_row_start: self.chunk_row_start + row_offset,
+            chunk_row_end: self.chunk_row_end + row_offset,
+            ..self
+        }
+    }
+
+    /// Read at most `row_limit` of the rows given to [`ReadStatData::init`]; set once
+    /// initialized
+    ///
+    /// Fewer rows are read should the file end first.
+    pub fn set_row_limit(self, row_limit: u32) -> Self {
+        let chunk_rows_to_process = std::cmp::min(self.chunk_rows_to_process, row_limit as usize);
+        Self {
+            chunk_rows_to_process,
+            chunk_row_end: self.chunk_row_start + chunk_rows_to_process,
+            ..self
+        }
+    }
+
+    /// Keep the rows read ahead of a parsing error rather than failing
+    ///
+    /// The error is available from [`ReadStatData::salvage_error`] and the chunk holds the rows
+    /// handled in full, or is `None` should none have been.
+    pub fn set_best_effort(self, best_effort: bool) -> Self {
+        Self {
+            best_effort,
+            ..self
+        }
+    }
+
+    /// How values that cannot be read are handled; defaults to [`ErrorMode::FailFast`]
+    ///
+    /// With [`ErrorMode::Collect`] each such value is read as missing and recorded, available
+    /// from [`ReadStatData::cell_errors`], until more than `max` have been.
+    pub fn set_error_mode(self, error_mode: ErrorMode) -> Self {
+        Self { error_mode, ..self }
+    }
+
+    /// Report progress while parsing to `progress_callback`, which may cancel reading
+    ///
+    /// Once cancelled, [`ReadStatData::read_data`] fails with [`ReadStatCancelled`] yet the chunk
+    /// holds the rows read in full ahead of cancelling, so that they may still be written, or is
+    /// `None` should none have been.
+    pub fn set_progress_callback(self, progress_callback: ReadStatProgressCallback) -> Self {
+        Self {
+            progress_callback: Some(progress_callback),
+            ..self
+        }
+    }
+
+    /// Dates, and the dates of datetimes, read as valid; defaults to
+    /// [`ReadStatDateWindow::default`], 1582-01-01 through 9999-12-31
+    ///
+    /// Any other is read as missing and recorded, available from
+    /// [`ReadStatData::date_range_warnings`], as is any date, datetime, or time beyond the range
+    /// of its Arrow type.  `None` reads every date its type holds.
+    pub fn set_date_window(self, date_window: Option<ReadStatDateWindow>) -> Self {
+        Self {
+            date_window,
+            ..self
+        }
+    }
+
+    /// Fail reading at the first date, datetime, or time beyond the date window or the range of
+    /// its Arrow type rather than reading it as missing
+    pub fn set_strict_dates(self, strict_dates: bool) -> Self {
+        Self {
+            strict_dates,
+            ..self
+        }
+    }
+
+    /// How floating point values are read; defaults to [`FloatHandling::ReadStatDigits`], rounding
+    /// to 14 decimal places
+    pub fn set_float_handling(self, float_handling: FloatHandling) -> Self {
+        Self {
+            float_handling,
+            ..self
+        }
+    }
+
+    /// Whitespace trimmed from string values as read; defaults to
+    /// [`TrimMode::TrailingWhitespace`]
+    ///
+    /// Leading whitespace is always kept, and a value left blank is an empty string rather than
+    /// missing.
+    pub fn set_string_trim(self, string_trim: TrimMode) -> Self {
+        Self {
+            string_trim,
+            ..self
+        }
+    }
+
+    /// Whether values among the missing values a variable declares, as SPSS variables may, are
+    /// read as missing; defaults to [`UserMissing::AsValue`], keeping them as stored
+    ///
+    /// The missing values declared are recorded as
+    /// [`ReadStatVarMetadata::var_missing_ranges`].
+    pub fn set_user_missing(self, user_missing: UserMissing) -> Self {
+        Self {
+            user_missing,
+            ..self
+        }
+    }
+
+    /// Follow the columns read with a Utf8 column for each numeric variable, named by
+    /// [`missing_tag_name`](crate::missing_tag_name), holding the tag of each special missing
+    /// value (e.g. `R` for `.R`) and null for any other value
+    ///
+    /// Must be set ahead of [`ReadStatData::init`].  Special missing values are null within the
+    /// column of their variable either way, and the tags observed are recorded by
+    /// [`ReadStatVarMetadata::var_missing_tags`].
+    pub fn set_missing_tags(self, missing_tags: bool) -> Self {
+        Self {
+            missing_tags,
+            ..self
+        }
+    }
+
+    /// Abort parsing at the next value once `cancel` is set
+    pub fn set_cancel(self, cancel: Arc<AtomicBool>) -> Self {
+        Self {
+            cancel: Some(cancel),
+            ..self
+        }
+    }
+
+    /// Read every value, counting the bytes of strings, yet discard them rather than building a
+    /// chunk
+    ///
+    /// Used by [`ReadStatMeasure::scan`](crate::ReadStatMeasure::scan) for the first pass of
+    /// `--two-pass`.
+    pub fn set_measure_only(self, measure_only: bool) -> Self {
+        Self {
+            measure_only,
+            ..self
+        }
+    }
+
+    /// Read every value into the running statistics of its column rather than building a chunk
+    ///
+    /// Memory is proportional to the columns read whatever the rows; once read, the statistics
+    /// are taken with [`ReadStatData::into_summaries`].  Used by
+    /// [`ReadStatSummary::new`](crate::ReadStatSummary::new).
+    pub fn set_summarize(self, summarize: bool) -> Self {
+        Self { summarize, ..self }
+    }
+
+    /// Distinct string values counted exactly for each column when summarizing; see
+    /// [`ReadStatColumnSummary`]
+    ///
+    /// Must be set ahead of [`ReadStatData::init`].  Defaults to [`DISTINCT_CAP`].
+    pub fn set_distinct_cap(self, distinct_cap: usize) -> Self {
+        Self {
+            distinct_cap,
+            ..self
+        }
+    }
+
+    /// Bytes of the string values of each column, by position, with which to allocate the
+    /// string arrays
+    ///
+    /// Exact when measured by a first pass (see [`ReadStatMeasure`](crate::ReadStatMeasure));
+    /// should they be estimates, e.g. from [`ReadStatData::estimate_string_capacities`], arrays
+    /// grow beyond them as needed.
+    pub fn set_string_capacities(self, string_capacities: Option<Vec<usize>>) -> Self {
+        Self {
+            string_capacities,
+            ..self
+        }
+    }
+
+    /// Fail reading, ahead of allocating any column, when the rows to read are estimated to need
+    /// more than `bytes` of memory; see [`ReadStatMetadata::estimate_memory_bytes`]
+    ///
+    /// Must be set ahead of [`ReadStatData::init`].  The rows read are held in memory at once, so
+    /// there is no streaming to fall back upon; [`convert`](crate::convert) streams instead, given
+    /// `ReadStatConvertOptions::mem_limit` and [`MemLimitAction::Stream`](crate::MemLimitAction).
+    pub fn set_memory_limit(self, bytes: u64) -> Self {
+        Self {
+            memory_limit: Some(bytes),
+            ..self
+        }
+    }
+
+    /// Normalize the string values of the variables at the indices `vars`, as found by
+    /// [`ReadStatStringNormalize::resolve`]
+    pub fn set_string_normalize(
+        self,
+        normalize: ReadStatStringNormalize,
+        vars: BTreeSet<i32>,
+    ) -> Self {
+        Self {
+            normalize,
+            normalize_vars: vars,
+            ..self
+        }
+    }
+
     pub fn set_total_rows_processed(self, total_rows_processed: Arc<AtomicUsize>) -> Self {
         Self {
             total_rows_processed: Some(total_rows_processed),
             ..self
         }
     }
+
+    /// Data supplied directly rather than read, e.g. to write arrays built elsewhere
+    pub fn set_chunk(self, schema: Schema, chunk: Chunk<Box<dyn Array>>) -> Self {
+        let rows = chunk.len();
+        Self {
+            var_count: schema.fields.len() as i32,
+            schema,
+            chunk: Some(chunk),
+            chunk_rows_to_process: rows,
+            chunk_row_end: rows,
+            chunk_rows_processed: rows,
+            chunk_rows_read: rows,
+            ..self
+        }
+    }
+
+    pub fn var_count(&self) -> i32 {
+        self.var_count
+    }
+
+    /// Metadata of each variable, keyed by variable index
+    pub fn vars(&self) -> &BTreeMap<i32, ReadStatVarMetadata> {
+        &self.vars
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Data read, once [`ReadStatData::read_data`] has succeeded
+    pub fn chunk(&self) -> Option<&Chunk<Box<dyn Array>>> {
+        self.chunk.as_ref()
+    }
+
+    pub fn into_chunk(self) -> Option<Chunk<Box<dyn Array>>> {
+        self.chunk
+    }
+
+    /// Bytes of the string values of each column, by position, estimated for a batch of `rows`
+    /// rows from those read into this one, to pass to a following batch via
+    /// [`ReadStatData::set_string_capacities`]
+    ///
+    /// Scaled by the rows read rather than kept, so batches read with a row filter estimate
+    /// alike.  `None` once no rows, or no strings, were read.
+    pub fn estimate_string_capacities(&self, rows: usize) -> Option<Vec<usize>> {
+        if self.chunk_rows_read == 0 || self.string_bytes.iter().all(|b| *b == 0) {
+            return None;
+        }
+
+        let read = self.chunk_rows_read as u64;
+        Some(
+            self.string_bytes
+                .iter()
+                .map(|b| (*b as u64 * rows as u64).div_ceil(read) as usize)
+                .collect(),
+        )
+    }
+
+    /// Row of the file at which this chunk starts
+    pub fn chunk_row_start(&self) -> usize {
+        self.chunk_row_start
+    }
+
+    pub fn chunk_rows_to_process(&self) -> usize {
+        self.chunk_rows_to_process
+    }
+
+    /// Rows kept, i.e. read and, when filtering, passing the filter
+    pub fn chunk_rows_processed(&self) -> usize {
+        self.chunk_rows_processed
+    }
+
+    /// Rows read, including any discarded by the filter
+    pub fn chunk_rows_read(&self) -> usize {
+        self.chunk_rows_read
+    }
+
+    /// Bytes of memory the rows to read were estimated to need, once initialized with a memory
+    /// limit; see [`ReadStatData::set_memory_limit`]
+    pub fn memory_estimate(&self) -> Option<u64> {
+        self.memory_estimate
+    }
+
+    /// Values that could not be read, in the order read, when reading with
+    /// [`ErrorMode::Collect`]
+    pub fn cell_errors(&self) -> &[ReadStatCellError] {
+        &self.cell_errors
+    }
+
+    /// Dates, datetimes, and times read as missing as they were beyond the date window or the
+    /// range of their Arrow type, in the order read; see [`ReadStatData::set_date_window`]
+    pub fn date_range_warnings(&self) -> &[ReadStatDateRangeWarning] {
+        &self.date_range_warnings
+    }
+
+    /// Error reading stopped with, when reading with [`ReadStatData::set_best_effort`]
+    pub fn salvage_error(&self) -> Option<&str> {
+        self.salvage_error.as_deref()
+    }
+
+    pub fn encoding_detector(&self) -> &ReadStatEncodingDetector {
+        &self.encoding_detector
+    }
+
+    /// Cells modified by string normalization, keyed by variable index
+    pub fn strings_normalized(&self) -> &BTreeMap<i32, usize> {
+        &self.strings_normalized
+    }
+
+    /// Statistics of each column read, by position, when summarizing; see
+    /// [`ReadStatData::set_summarize`]
+    pub fn into_summaries(self) -> Vec<ReadStatColumnSummary> {
+        self.summaries
+    }
+}
+
+/// Guards against the Arrow type of a column changing from one batch to the next
+///
+/// The schema of the first batch checked is locked.  A later batch whose arrays disagree with it
+/// is an error naming the column and the row at which the batch starts or, when coercing, has its
+/// arrays cast to the locked types.
+#[derive(Clone, Debug, Default)]
+pub struct ReadStatSchemaLock {
+    schema: Option<Schema>,
+    coerce: bool,
+}
+
+impl ReadStatSchemaLock {
+    pub fn new(coerce: bool) -> Self {
+        Self {
+            schema: None,
+            coerce,
+        }
+    }
+
+    /// Locked schema, once a batch has been checked
+    pub fn schema(&self) -> Option<&Schema> {
+        self.schema.as_ref()
+    }
+
+    pub fn check(&mut self, d: &mut ReadStatData) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let locked = match &self.schema {
+            Some(s) => s,
+            None => {
+                self.schema = Some(d.schema.clone());
+                return Ok(());
+            }
+        };
+
+        if locked.fields.len() != d.schema.fields.len() {
+            return Err(From::from(format!(
+                "The batch starting at row {} has {} columns rather than {}",
+                d.chunk_row_start,
+                d.schema.fields.len(),
+                locked.fields.len()
+            )));
+        }
+
+        if let Some(chunk) = &d.chunk {
+            let mut arrays: Vec<Box<dyn Array>> = Vec::with_capacity(chunk.arrays().len());
+
+            for (f, a) in locked.fields.iter().zip(chunk.arrays()) {
+                if a.data_type() == f.data_type() {
+                    arrays.push(a.clone());
+                } else if self.coerce {
+                    arrays.push(cast(a.as_ref(), f.data_type(), CastOptions::default())?);
+                } else {
+                    return Err(From::from(format!(
+                        "The type of column {} changed from {:?} to {:?} in the batch starting at row {}; consider {}",
+                        f.name.bright_yellow(),
+                        f.data_type(),
+                        a.data_type(),
+                        d.chunk_row_start,
+                        String::from("--coerce-across-batches").bright_cyan()
+                    )));
+                }
+            }
+
+            d.chunk = Some(Chunk::try_new(arrays)?);
+        }
+
+        d.schema = locked.clone();
+
+        Ok(())
+    }
 }
@@ -0,0 +1,105 @@
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use std::{error::Error, fmt, str::FromStr};
+
+// Days from 0001-01-01, the first day of the common era, to the Unix epoch
+const UNIX_EPOCH_DAYS_FROM_CE: i64 = 719_163;
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Dates read as valid, whether of a date or of a datetime; the rest are read as missing
+///
+/// Damaged files and faulty writers leave garbage within date and datetime columns (e.g. `1e70`)
+/// which, read as dates thousands of years away, poison whatever sorts or partitions by them.
+/// Defaults to 1582-01-01 through 9999-12-31, the years of the Gregorian calendar that four
+/// digits display.  Both bounds are inclusive.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ReadStatDateWindow {
+    pub min: NaiveDate,
+    pub max: NaiveDate,
+}
+
+impl Default for ReadStatDateWindow {
+    fn default() -> Self {
+        Self {
+            min: NaiveDate::from_ymd_opt(1582, 1, 1).unwrap(),
+            max: NaiveDate::from_ymd_opt(9999, 12, 31).unwrap(),
+        }
+    }
+}
+
+impl ReadStatDateWindow {
+    pub fn new(min: NaiveDate, max: NaiveDate) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        if min > max {
+            return Err(From::from(format!(
+                "The date window begins on {} which is after it ends on {}",
+                min, max
+            )));
+        }
+
+        Ok(Self { min, max })
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.min <= date && date <= self.max
+    }
+
+    /// Whether the window holds the date of days since 1970-01-01, as held by Arrow's `Date32`
+    pub fn contains_date32(&self, days: i32) -> bool {
+        self.contains_unix_days(days as i64)
+    }
+
+    /// Whether the window holds the date of seconds since 1970-01-01 00:00:00, as held by
+    /// Arrow's `Timestamp(Second)`
+    pub fn contains_timestamp(&self, seconds: i64) -> bool {
+        self.contains_unix_days(seconds.div_euclid(SECONDS_PER_DAY))
+    }
+
+    fn contains_unix_days(&self, days: i64) -> bool {
+        let unix_days = |d: NaiveDate| i64::from(d.num_days_from_ce()) - UNIX_EPOCH_DAYS_FROM_CE;
+        unix_days(self.min) <= days && days <= unix_days(self.max)
+    }
+}
+
+impl fmt::Display for ReadStatDateWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.min, self.max)
+    }
+}
+
+impl FromStr for ReadStatDateWindow {
+    type Err = String;
+
+    // given as MIN..MAX, each as YYYY-MM-DD
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (min, max) = s.split_once("..").ok_or_else(|| {
+            format!(
+                "The date window {} is not given as MIN..MAX, e.g. 1900-01-01..2100-12-31",
+                s
+            )
+        })?;
+
+        let parse = |d: &str| {
+            NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d").map_err(|e| {
+                format!(
+                    "The date {} of the date window is not a date given as YYYY-MM-DD: {}",
+                    d, e
+                )
+            })
+        };
+
+        Self::new(parse(min)?, parse(max)?).map_err(|e| e.to_string())
+    }
+}
+
+/// A date, datetime, or time read as missing as it was beyond the date window or the range of
+/// its Arrow type
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ReadStatDateRangeWarning {
+    pub row: usize,
+    /// Column as written
+    pub column: String,
+    /// Value as stored within the file, e.g. seconds since 1960-01-01 for a SAS datetime
+    pub value: f64,
+    #[serde(skip)]
+    pub(crate) var_index: i32,
+}
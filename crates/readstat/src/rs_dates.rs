@@ -0,0 +1,292 @@
+use arrow2::{
+    array::{Array, PrimitiveArray},
+    chunk::Chunk,
+    datatypes::{DataType, Schema},
+};
+use colored::Colorize;
+use serde::Serialize;
+use std::error::Error;
+
+use crate::common::build_offsets;
+use crate::rs_metadata::ReadStatMetadata;
+use crate::rs_path::ReadStatPath;
+use crate::rs_stats::for_each_chunk;
+use crate::rs_var::ReadStatVarFormatClass;
+use crate::DetectDates;
+
+/// Fraction of the sampled values that must look like dates before a column is a candidate, by
+/// default
+pub const DETECT_DATES_THRESHOLD: f64 = 0.99;
+
+/// Rows sampled from the start of the file, by default
+pub const DETECT_DATES_SAMPLE_ROWS: u32 = 10_000;
+
+/// Non-missing values a column needs before it may be a candidate
+pub const DETECT_DATES_MIN_VALUES: u64 = 20;
+
+/// Plausible dates as days since 1960-01-01, from 1980-01-01 up to 2050-01-01
+pub const DETECT_DATES_DAYS: (f64, f64) = (7_305.0, 32_873.0);
+
+/// Plausible datetimes as seconds since 1960-01-01, from 1980-01-01 up to 2050-01-01
+pub const DETECT_DATES_SECONDS: (f64, f64) = (631_152_000.0, 2_840_227_200.0);
+
+// Fraction of successive values that increase by exactly one for a column to look like row IDs
+const SEQUENTIAL: f64 = 0.5;
+
+/// Counts of the sampled values of a single plain numeric column that look like dates
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadStatDateColumn {
+    pub var_name: String,
+    /// Non-missing values sampled
+    pub values: u64,
+    /// Whole numbers within [`DETECT_DATES_DAYS`]
+    pub days: u64,
+    /// Values within [`DETECT_DATES_SECONDS`]
+    pub seconds: u64,
+    /// Values exactly one more than the value before them
+    pub steps: u64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    #[serde(skip_serializing)]
+    previous: Option<f64>,
+    #[serde(skip_serializing)]
+    index: usize,
+}
+
+impl ReadStatDateColumn {
+    /// Date when values look like days, DateTime when seconds
+    pub fn format_class(&self) -> ReadStatVarFormatClass {
+        if self.seconds > self.days {
+            ReadStatVarFormatClass::DateTime
+        } else {
+            ReadStatVarFormatClass::Date
+        }
+    }
+
+    /// Fraction of the non-missing values that look like dates; 0 without any
+    pub fn fraction(&self) -> f64 {
+        if self.values == 0 {
+            0.0
+        } else {
+            std::cmp::max(self.days, self.seconds) as f64 / self.values as f64
+        }
+    }
+
+    /// Whether values mostly increase by one from row to row, as row IDs do
+    ///
+    /// Row IDs of a file with around 20,000 rows fall squarely within the plausible dates, so
+    /// such columns are only ever reported.
+    pub fn sequential(&self) -> bool {
+        self.values > 1 && self.steps as f64 / (self.values - 1) as f64 >= SEQUENTIAL
+    }
+
+    fn push(&mut self, value: f64) {
+        self.values += 1;
+        if value.fract() == 0.0 && value >= DETECT_DATES_DAYS.0 && value < DETECT_DATES_DAYS.1 {
+            self.days += 1;
+        }
+        if value >= DETECT_DATES_SECONDS.0 && value < DETECT_DATES_SECONDS.1 {
+            self.seconds += 1;
+        }
+        if self.previous == Some(value - 1.0) {
+            self.steps += 1;
+        }
+        self.previous = Some(value);
+        self.min = Some(self.min.map_or(value, |m| m.min(value)));
+        self.max = Some(self.max.map_or(value, |m| m.max(value)));
+    }
+}
+
+/// Plain numeric columns whose values look like dates or datetimes despite the file giving them
+/// no date format, counted as a sample of the data streams past
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadStatDateDetection {
+    pub threshold: f64,
+    pub sampled_rows: usize,
+    pub columns: Vec<ReadStatDateColumn>,
+}
+
+impl ReadStatDateDetection {
+    pub fn new(schema: &Schema, threshold: f64) -> Self {
+        let columns = schema
+            .fields
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| matches!(f.data_type(), DataType::Float64))
+            .map(|(index, f)| ReadStatDateColumn {
+                var_name: f.name.clone(),
+                values: 0,
+                days: 0,
+                seconds: 0,
+                steps: 0,
+                min: None,
+                max: None,
+                previous: None,
+                index,
+            })
+            .collect();
+
+        Self {
+            threshold,
+            sampled_rows: 0,
+            columns,
+        }
+    }
+
+    pub fn push_chunk(&mut self, chunk: &Chunk<Box<dyn Array>>) {
+        self.sampled_rows += chunk.len();
+        for c in self.columns.iter_mut() {
+            if let Some(a) = chunk.arrays()[c.index]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f64>>()
+            {
+                for v in a.iter().flatten() {
+                    c.push(*v);
+                }
+            }
+        }
+    }
+
+    /// Columns with enough values, at least `threshold` of which look like dates
+    pub fn candidates(&self) -> Vec<&ReadStatDateColumn> {
+        self.columns
+            .iter()
+            .filter(|c| c.values >= DETECT_DATES_MIN_VALUES && c.fraction() >= self.threshold)
+            .collect()
+    }
+
+    /// Candidates that may be read as dates, i.e. all but those that look like row IDs
+    pub fn applicable(&self) -> Vec<&ReadStatDateColumn> {
+        self.candidates()
+            .into_iter()
+            .filter(|c| !c.sequential())
+            .collect()
+    }
+
+    /// Warning for each column read as a date
+    pub fn warnings(&self) -> Vec<String> {
+        self.applicable()
+            .iter()
+            .map(|c| {
+                let kind = match c.format_class() {
+                    ReadStatVarFormatClass::Date => "date",
+                    _ => "datetime",
+                };
+                format!(
+                    "{}: The column {} has no date format yet {:.1}% of its sampled values look like {}s, so it is read as a {}; check its values before relying on them",
+                    "Warning".bright_yellow(),
+                    c.var_name.bright_yellow(),
+                    100.0 * c.fraction(),
+                    kind,
+                    kind
+                )
+            })
+            .collect()
+    }
+
+    /// Plain text table with a line for each candidate
+    pub fn report(&self) -> String {
+        let header: Vec<String> = [
+            "Column",
+            "Looks like",
+            "Values",
+            "In range",
+            "Min",
+            "Max",
+            "Applies",
+        ]
+        .iter()
+        .map(|h| h.to_string())
+        .collect();
+
+        let value = |v: Option<f64>| v.map_or(String::from("-"), |v| v.to_string());
+        let rows: Vec<Vec<String>> = self
+            .candidates()
+            .iter()
+            .map(|c| {
+                vec![
+                    c.var_name.clone(),
+                    format!("{:?}", c.format_class()),
+                    c.values.to_string(),
+                    format!("{:.1}%", 100.0 * c.fraction()),
+                    value(c.min),
+                    value(c.max),
+                    if c.sequential() {
+                        String::from("no (row IDs?)")
+                    } else {
+                        String::from("yes")
+                    },
+                ]
+            })
+            .collect();
+
+        // width of each column
+        let mut widths: Vec<usize> = header.iter().map(|h| h.len()).collect();
+        for r in &rows {
+            for (w, c) in widths.iter_mut().zip(r.iter()) {
+                *w = (*w).max(c.chars().count());
+            }
+        }
+
+        let line = |cells: &[String]| -> String {
+            cells
+                .iter()
+                .zip(widths.iter())
+                .map(|(c, w)| format!("{:<1$}", c, w))
+                .collect::<Vec<String>>()
+                .join("  ")
+                .trim_end()
+                .to_owned()
+        };
+
+        let dashes: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+
+        let mut report = String::new();
+        for r in [&header, &dashes].into_iter().chain(rows.iter()) {
+            report.push_str(&line(r));
+            report.push('\n');
+        }
+        report
+    }
+}
+
+/// Sample the first `sample_rows` rows of the file described by `rsp` for plain numeric columns
+/// that look like dates, reading `stream_rows` rows at a time
+pub fn detect_date_columns(
+    rsp: &ReadStatPath,
+    md: &ReadStatMetadata,
+    threshold: f64,
+    sample_rows: u32,
+    stream_rows: u32,
+) -> Result<ReadStatDateDetection, Box<dyn Error + Send + Sync>> {
+    let mut detection = ReadStatDateDetection::new(&md.schema, threshold);
+    let rows = std::cmp::min(md.row_count as u32, sample_rows);
+    let offsets = build_offsets(rows, stream_rows)?;
+    for_each_chunk(rsp, md, &offsets, |chunk| detection.push_chunk(chunk))?;
+
+    Ok(detection)
+}
+
+/// Detect the plain numeric columns that look like dates and, with [`DetectDates::Apply`], read
+/// the candidates that do not look like row IDs as dates or datetimes
+pub fn apply_dates(
+    rsp: &ReadStatPath,
+    md: &mut ReadStatMetadata,
+    detect: DetectDates,
+    threshold: f64,
+    sample_rows: u32,
+    stream_rows: u32,
+) -> Result<ReadStatDateDetection, Box<dyn Error + Send + Sync>> {
+    let detection = detect_date_columns(rsp, md, threshold, sample_rows, stream_rows)?;
+
+    if detect == DetectDates::Apply {
+        let columns: Vec<(String, ReadStatVarFormatClass)> = detection
+            .applicable()
+            .iter()
+            .map(|c| (c.var_name.clone(), c.format_class()))
+            .collect();
+        md.set_date_columns(&columns)?;
+    }
+
+    Ok(detection)
+}
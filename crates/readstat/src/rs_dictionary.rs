@@ -0,0 +1,93 @@
+use arrow2::{
+    array::Utf8Array,
+    datatypes::{DataType, IntegerType},
+};
+use std::{collections::HashSet, error::Error};
+
+use crate::common::build_offsets;
+use crate::rs_metadata::ReadStatMetadata;
+use crate::rs_path::ReadStatPath;
+use crate::rs_stats::for_each_chunk;
+
+/// How string columns are stored within Arrow; see [`ReadStatMetadata::set_string_encoding`]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum StringEncoding {
+    /// As `Utf8`, every value held in full
+    #[default]
+    Plain,
+    /// As `Dictionary(Int32, Utf8)`, each distinct value held once and every row as a key into
+    /// them; with `max_cardinality`, only the columns holding at most that many distinct values
+    /// within the first batch
+    Dictionary { max_cardinality: Option<usize> },
+}
+
+// Arrow type of dictionary-encoded strings
+pub(crate) fn dictionary_type() -> DataType {
+    DataType::Dictionary(IntegerType::Int32, Box::new(DataType::Utf8), false)
+}
+
+/// Store string columns as dictionaries as directed by `md.string_encoding`, recording each
+/// within `md.casts`
+///
+/// Strings are still parsed as `Utf8`, then cast once each batch is read.  With a
+/// `max_cardinality`, the first `stream_rows` rows of the file are read to count the distinct
+/// values of each string column, and a column holding more is left as `Utf8`.  The choice is
+/// made once, ahead of reading, so every batch has the same types whatever later batches hold.
+/// Columns already cast, e.g. by a column spec, are left as they are.
+pub fn apply_string_encoding(
+    rsp: &ReadStatPath,
+    md: &mut ReadStatMetadata,
+    stream_rows: u32,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let max_cardinality = match md.string_encoding {
+        StringEncoding::Plain => return Ok(()),
+        StringEncoding::Dictionary { max_cardinality } => max_cardinality,
+    };
+
+    // string columns with their positions within the schema
+    let strings: Vec<(usize, i32)> = md
+        .vars
+        .keys()
+        .zip(md.schema.fields.iter())
+        .enumerate()
+        .filter(|(_, (k, f))| f.data_type() == &DataType::Utf8 && !md.casts.contains_key(k))
+        .map(|(i, (k, _))| (i, *k))
+        .collect();
+
+    let encoded: Vec<i32> = match max_cardinality {
+        None => strings.iter().map(|(_, k)| *k).collect(),
+        Some(max) => {
+            let rows = std::cmp::min(md.row_count.max(0) as u32, stream_rows);
+            let offsets = build_offsets(rows, stream_rows)?;
+
+            // counting stops once a column is beyond the cap
+            let mut distinct: Vec<HashSet<String>> = vec![HashSet::new(); strings.len()];
+            for_each_chunk(rsp, md, &offsets, |chunk| {
+                for ((i, _), seen) in strings.iter().zip(distinct.iter_mut()) {
+                    let a = chunk.arrays()[*i].as_any().downcast_ref::<Utf8Array<i32>>();
+                    for v in a.into_iter().flat_map(|a| a.iter().flatten()) {
+                        if seen.len() > max {
+                            break;
+                        }
+                        if !seen.contains(v) {
+                            seen.insert(String::from(v));
+                        }
+                    }
+                }
+            })?;
+
+            strings
+                .iter()
+                .zip(distinct.iter())
+                .filter(|(_, seen)| seen.len() <= max)
+                .map(|((_, k), _)| *k)
+                .collect()
+        }
+    };
+
+    for k in encoded {
+        md.casts.insert(k, dictionary_type());
+    }
+
+    Ok(())
+}
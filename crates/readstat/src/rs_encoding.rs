@@ -0,0 +1,161 @@
+use colored::Colorize;
+
+// Number of suspicious string values retained for guessing the actual encoding
+const SAMPLE_LIMIT: usize = 16;
+
+// Bytes left undefined by WINDOWS-1252
+const CP1252_UNDEFINED: [u8; 5] = [0x81, 0x8D, 0x8F, 0x90, 0x9D];
+
+/// What the bytes of a string value look like
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReadStatEncodingGuess {
+    Ascii,
+    Utf8,
+    /// Valid UTF-8 containing the telltale pairs (e.g. `Ã©` for `é`) left by decoding UTF-8 as
+    /// a single-byte encoding
+    DoubleEncodedUtf8,
+    Windows1252,
+    Unknown,
+}
+
+impl ReadStatEncodingGuess {
+    /// Value to suggest for `--encoding`
+    pub fn encoding(&self) -> Option<&'static str> {
+        match self {
+            Self::Utf8 | Self::DoubleEncodedUtf8 => Some("UTF-8"),
+            Self::Windows1252 => Some("WINDOWS-1252"),
+            Self::Ascii | Self::Unknown => None,
+        }
+    }
+}
+
+/// Guess the encoding of the bytes of a single string value using byte patterns
+pub fn guess_encoding(bytes: &[u8]) -> ReadStatEncodingGuess {
+    if bytes.is_ascii() {
+        return ReadStatEncodingGuess::Ascii;
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(s) if looks_double_encoded(s) => ReadStatEncodingGuess::DoubleEncodedUtf8,
+        Ok(_) => ReadStatEncodingGuess::Utf8,
+        Err(_) if bytes.iter().any(|b| CP1252_UNDEFINED.contains(b)) => {
+            ReadStatEncodingGuess::Unknown
+        }
+        Err(_) => ReadStatEncodingGuess::Windows1252,
+    }
+}
+
+fn looks_double_encoded(s: &str) -> bool {
+    let chars: Vec<char> = s.chars().collect();
+    chars.windows(2).any(|w| {
+        matches!((w[0], w[1]), ('Ã' | 'Â', '\u{80}'..='\u{BF}') | ('â', '€'))
+    })
+}
+
+// Encoding names as written within sas7bdat files and as accepted by iconv
+fn normalize(encoding: &str) -> String {
+    match encoding.to_ascii_uppercase().replace(['_', ' '], "-").as_str() {
+        "UTF8" => String::from("UTF-8"),
+        "WLATIN1" | "CP1252" | "WINDOWS1252" => String::from("WINDOWS-1252"),
+        e => String::from(e),
+    }
+}
+
+/// Watches the bytes of string values for signs that the declared file encoding is wrong
+///
+/// Every string value is pushed before lossy conversion to UTF-8; only values that are not plain
+/// ASCII are examined further, so the cost for typical data is a single ASCII check.
+#[derive(Clone, Debug, Default)]
+pub struct ReadStatEncodingDetector {
+    /// Values that were not valid UTF-8 and so were converted lossily
+    pub lossy: usize,
+    /// Values containing any byte outside of ASCII
+    pub non_ascii: usize,
+    samples: Vec<ReadStatEncodingGuess>,
+}
+
+impl ReadStatEncodingDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, bytes: &[u8]) {
+        if bytes.is_ascii() {
+            return;
+        }
+        self.non_ascii += 1;
+
+        let guess = guess_encoding(bytes);
+        if std::str::from_utf8(bytes).is_err() {
+            self.lossy += 1;
+        }
+
+        if guess != ReadStatEncodingGuess::Utf8 && self.samples.len() < SAMPLE_LIMIT {
+            self.samples.push(guess);
+        }
+    }
+
+    /// Combine with the observations of another detector, e.g. from another chunk
+    pub fn merge(&mut self, other: &Self) {
+        self.lossy += other.lossy;
+        self.non_ascii += other.non_ascii;
+        for s in &other.samples {
+            if self.samples.len() < SAMPLE_LIMIT {
+                self.samples.push(*s);
+            }
+        }
+    }
+
+    /// Most frequent suspicious guess among the sampled values
+    pub fn guess(&self) -> Option<ReadStatEncodingGuess> {
+        [
+            ReadStatEncodingGuess::Windows1252,
+            ReadStatEncodingGuess::DoubleEncodedUtf8,
+            ReadStatEncodingGuess::Unknown,
+        ]
+        .into_iter()
+        .map(|g| (g, self.samples.iter().filter(|s| **s == g).count()))
+        .filter(|(_, n)| *n > 0)
+        .max_by_key(|(_, n)| *n)
+        .map(|(g, _)| g)
+    }
+
+    /// An actionable warning should the data disagree with the `declared` encoding
+    pub fn warning(&self, declared: &str) -> Option<String> {
+        let guess = self.guess()?;
+        let declared = if declared.is_empty() {
+            String::from("(none)")
+        } else {
+            String::from(declared)
+        };
+
+        let evidence = match guess {
+            ReadStatEncodingGuess::DoubleEncodedUtf8 => String::from(
+                "its text contains UTF-8 that was decoded as a single-byte encoding (e.g. Ã© for é)",
+            ),
+            _ => format!(
+                "{} of {} non-ASCII values were not valid UTF-8 and were converted lossily",
+                self.lossy, self.non_ascii
+            ),
+        };
+
+        match guess.encoding() {
+            Some(e) if normalize(e) == normalize(&declared) => None,
+            Some(e) => Some(format!(
+                "{}: the file declares encoding {} but its data looks like {}; {}; consider {}",
+                "Warning".bright_yellow(),
+                declared.bright_magenta(),
+                e.bright_magenta(),
+                evidence,
+                format!("--encoding {}", e).bright_cyan()
+            )),
+            None => Some(format!(
+                "{}: the file declares encoding {} but {}; consider setting {}",
+                "Warning".bright_yellow(),
+                declared.bright_magenta(),
+                evidence,
+                String::from("--encoding").bright_cyan()
+            )),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use arrow2::datatypes::{DataType, TimeUnit};
+use serde::Serialize;
+
+use crate::rs_metadata::ReadStatMetadata;
+use crate::OutFormat;
+
+// SAS pads strings to their storage width; values are assumed to fill half of it
+const STRING_FILL: f64 = 0.5;
+
+// Characters assumed for a number written as text; small integers need 1 or 2 whereas numbers at
+//   full precision need upwards of 17
+const NUMBER_CHARS: f64 = 6.0;
+
+// Compressed bytes per uncompressed byte assumed for parquet (snappy) and feather (zstd)
+const COMPRESSION_RATIO: f64 = 0.5;
+
+/// Rough sizes, in bytes, of converting a file, estimated from its metadata alone
+///
+/// Strings are assumed to fill half of their storage width and numbers written as text to take
+/// 6 characters, so expect an estimate to be within a factor of 2 or 3 of the actual size rather
+/// than exact.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ReadStatSizeEstimate {
+    pub rows: u64,
+    /// Bytes of a row within the file, the sum of the storage widths
+    pub row_storage_bytes: u64,
+    /// Bytes of the Arrow arrays holding every row
+    pub arrow_bytes: u64,
+    pub csv_bytes: u64,
+    /// With the default zstd compression, as is an Arrow IPC stream
+    pub feather_bytes: u64,
+    pub ndjson_bytes: u64,
+    /// With the default snappy compression
+    pub parquet_bytes: u64,
+}
+
+impl ReadStatSizeEstimate {
+    pub fn new(md: &ReadStatMetadata, rows: u32) -> Self {
+        let rows_f = rows as f64;
+
+        let mut row_storage_bytes = 0;
+        let mut arrow_row = 0.0;
+        let mut csv_row = 0.0;
+        let mut ndjson_row = 2.0; // braces
+        let mut csv_header = 0.0;
+
+        for (vm, f) in md.vars.values().zip(md.schema.fields.iter()) {
+            let width = vm.var_storage_width as f64;
+            row_storage_bytes += vm.var_storage_width as u64;
+
+            // validity is a bit per value
+            arrow_row += arrow_width(f.data_type(), width) + 0.125;
+
+            let text = text_width(f.data_type(), width);
+            csv_row += text + 1.0; // delimiter or newline
+            csv_header += f.name.len() as f64 + 1.0;
+
+            // "name":value, with strings quoted
+            let quotes = if f.data_type() == &DataType::Utf8 { 2.0 } else { 0.0 };
+            ndjson_row += f.name.len() as f64 + 4.0 + text + quotes;
+        }
+
+        let arrow_bytes = arrow_row * rows_f;
+
+        Self {
+            rows: rows as u64,
+            row_storage_bytes,
+            arrow_bytes: arrow_bytes as u64,
+            csv_bytes: (csv_header + csv_row * rows_f) as u64,
+            feather_bytes: (arrow_bytes * COMPRESSION_RATIO) as u64,
+            ndjson_bytes: (ndjson_row * rows_f) as u64,
+            parquet_bytes: (arrow_bytes * COMPRESSION_RATIO) as u64,
+        }
+    }
+
+    /// Estimated bytes of the output written in `format`
+    pub fn output_bytes(&self, format: OutFormat) -> u64 {
+        match format {
+            OutFormat::arrow | OutFormat::feather => self.feather_bytes,
+            OutFormat::csv => self.csv_bytes,
+            OutFormat::ndjson => self.ndjson_bytes,
+            OutFormat::parquet => self.parquet_bytes,
+            // uncompressed, each row is stored much as within the file
+            OutFormat::sav | OutFormat::dta => self.row_storage_bytes * self.rows,
+        }
+    }
+}
+
+// Bytes of a single value within an Arrow array
+fn arrow_width(data_type: &DataType, storage_width: f64) -> f64 {
+    match data_type {
+        // values plus an offset
+        DataType::Utf8 => storage_width * STRING_FILL + 4.0,
+        DataType::Boolean => 0.125,
+        DataType::Int8 => 1.0,
+        DataType::Int16 => 2.0,
+        DataType::Int32 | DataType::Float32 | DataType::Date32 | DataType::Time32(_) => 4.0,
+        _ => 8.0,
+    }
+}
+
+// Characters of a single value written as text
+fn text_width(data_type: &DataType, storage_width: f64) -> f64 {
+    match data_type {
+        DataType::Utf8 => storage_width * STRING_FILL,
+        DataType::Boolean => 5.0,
+        DataType::Date32 | DataType::Date64 => 10.0,
+        DataType::Time32(_) | DataType::Time64(_) => 8.0,
+        DataType::Timestamp(unit, _) => match unit {
+            TimeUnit::Second => 19.0,
+            TimeUnit::Millisecond => 23.0,
+            TimeUnit::Microsecond => 26.0,
+            TimeUnit::Nanosecond => 29.0,
+        },
+        _ => NUMBER_CHARS,
+    }
+}
@@ -0,0 +1,222 @@
+use arrow2::datatypes::DataType;
+use chrono::NaiveDate;
+use colored::Colorize;
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use crate::rs_schema::arrow_type_name;
+use crate::rs_var::ReadStatVar;
+
+/// Value a [`FilterPredicate`] compares the values of a column with
+#[derive(Clone, Debug, PartialEq)]
+pub enum FilterValue {
+    /// Compared with the values of numeric and boolean columns, booleans being 0 or 1
+    Number(f64),
+    /// Compared byte by byte with the values of string columns, as trimmed
+    String(String),
+    /// Compared with the values of date columns
+    Date(NaiveDate),
+}
+
+impl FilterValue {
+    // Whether the values of a column read as `data_type` may be compared with this value
+    fn compares_with(&self, data_type: &DataType) -> bool {
+        match self {
+            Self::Number(_) => matches!(
+                data_type,
+                DataType::Boolean
+                    | DataType::Int8
+                    | DataType::Int16
+                    | DataType::Int32
+                    | DataType::Int64
+                    | DataType::Float32
+                    | DataType::Float64
+            ),
+            Self::String(_) => matches!(data_type, DataType::Utf8 | DataType::LargeUtf8),
+            Self::Date(_) => matches!(data_type, DataType::Date32),
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Number(_) => "number",
+            Self::String(_) => "string",
+            Self::Date(_) => "date",
+        }
+    }
+}
+
+impl fmt::Display for FilterValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(n) => write!(f, "{}", n),
+            Self::String(s) => write!(f, "'{}'", s),
+            Self::Date(d) => write!(f, "{}", d.format("%Y-%m-%d")),
+        }
+    }
+}
+
+/// Comparison the value of a column must pass for its row to be kept; see
+/// [`ReadStatData::set_row_filter`](crate::ReadStatData::set_row_filter)
+///
+/// A missing value never passes, whatever the comparison.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FilterPredicate {
+    Eq(FilterValue),
+    Ne(FilterValue),
+    Lt(FilterValue),
+    Le(FilterValue),
+    Gt(FilterValue),
+    Ge(FilterValue),
+}
+
+impl FilterPredicate {
+    pub fn value(&self) -> &FilterValue {
+        match self {
+            Self::Eq(v) | Self::Ne(v) | Self::Lt(v) | Self::Le(v) | Self::Gt(v) | Self::Ge(v) => v,
+        }
+    }
+
+    fn operator(&self) -> &'static str {
+        match self {
+            Self::Eq(_) => "=",
+            Self::Ne(_) => "!=",
+            Self::Lt(_) => "<",
+            Self::Le(_) => "<=",
+            Self::Gt(_) => ">",
+            Self::Ge(_) => ">=",
+        }
+    }
+
+    /// Whether `value`, as read, passes
+    pub fn matches(&self, value: &ReadStatVar) -> bool {
+        let ordering = match compare(value, self.value()) {
+            Some(o) => o,
+            None => return false,
+        };
+
+        match self {
+            Self::Eq(_) => ordering == Ordering::Equal,
+            Self::Ne(_) => ordering != Ordering::Equal,
+            Self::Lt(_) => ordering == Ordering::Less,
+            Self::Le(_) => ordering != Ordering::Greater,
+            Self::Gt(_) => ordering == Ordering::Greater,
+            Self::Ge(_) => ordering != Ordering::Less,
+        }
+    }
+}
+
+// Ordering of a value as read relative to the value compared with, or None for a missing value
+//   or one that cannot be compared
+fn compare(value: &ReadStatVar, with: &FilterValue) -> Option<Ordering> {
+    match (value, with) {
+        (ReadStatVar::ReadStat_String(Some(s)), FilterValue::String(w)) => Some(s.as_str().cmp(w)),
+        (ReadStatVar::ReadStat_Date(Some(d)), FilterValue::Date(w)) => {
+            let epoch = NaiveDate::from_ymd_opt(1970, 1, 1)?;
+            Some(i64::from(*d).cmp(&w.signed_duration_since(epoch).num_days()))
+        }
+        (v, FilterValue::Number(w)) => number(v)?.partial_cmp(w),
+        _ => None,
+    }
+}
+
+fn number(value: &ReadStatVar) -> Option<f64> {
+    match value {
+        ReadStatVar::ReadStat_i8(v) => v.map(f64::from),
+        ReadStatVar::ReadStat_i16(v) => v.map(f64::from),
+        ReadStatVar::ReadStat_i32(v) => v.map(f64::from),
+        ReadStatVar::ReadStat_i64(v) => v.map(|v| v as f64),
+        ReadStatVar::ReadStat_f32(v) => v.map(f64::from),
+        ReadStatVar::ReadStat_f64(v) => *v,
+        ReadStatVar::ReadStat_Bool(v) => v.map(|b| if b { 1.0 } else { 0.0 }),
+        _ => None,
+    }
+}
+
+/// Rows kept only when the value of `column`, named as within the file, passes `predicate`,
+/// given as `COLUMN=VALUE` or with any of `!=`, `<`, `<=`, `>`, and `>=`
+///
+/// A value in quotes is a string; otherwise a value given as `YYYY-MM-DD` is a date, then one
+/// that parses as a number is a number, and anything else is a string.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadStatRowFilter {
+    pub column: String,
+    pub predicate: FilterPredicate,
+}
+
+impl ReadStatRowFilter {
+    /// Check that the values of the column, read as `data_type`, may be compared
+    pub(crate) fn check(&self, data_type: &DataType) -> Result<(), String> {
+        let value = self.predicate.value();
+        if value.compares_with(data_type) {
+            Ok(())
+        } else {
+            Err(format!(
+                "The row filter compares the column {}, of the Arrow type {}, with the {} {}",
+                self.column.bright_yellow(),
+                arrow_type_name(data_type),
+                value.kind(),
+                value
+            ))
+        }
+    }
+}
+
+impl fmt::Display for ReadStatRowFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            self.column,
+            self.predicate.operator(),
+            self.predicate.value()
+        )
+    }
+}
+
+impl FromStr for ReadStatRowFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let expected = "expected COLUMN=VALUE, or a comparison such as COLUMN>=VALUE";
+        let at = match s.find(|c| matches!(c, '=' | '!' | '<' | '>')) {
+            Some(at) => at,
+            None => return Err(String::from(expected)),
+        };
+
+        let column = s[..at].trim();
+        let rest = &s[at..];
+        let (operator, value) = ["==", "!=", "<=", ">=", "=", "<", ">"]
+            .iter()
+            .find_map(|op| rest.strip_prefix(op).map(|v| (*op, v.trim())))
+            .ok_or_else(|| String::from(expected))?;
+        if column.is_empty() {
+            return Err(String::from(expected));
+        }
+
+        let quoted = ['\'', '"']
+            .iter()
+            .find_map(|q| value.strip_prefix(*q).and_then(|v| v.strip_suffix(*q)));
+        let value = match quoted {
+            Some(v) => FilterValue::String(String::from(v)),
+            None => match (NaiveDate::parse_from_str(value, "%Y-%m-%d"), value.parse::<f64>()) {
+                (Ok(d), _) => FilterValue::Date(d),
+                (_, Ok(n)) => FilterValue::Number(n),
+                _ => FilterValue::String(String::from(value)),
+            },
+        };
+
+        let predicate = match operator {
+            "==" | "=" => FilterPredicate::Eq(value),
+            "!=" => FilterPredicate::Ne(value),
+            "<=" => FilterPredicate::Le(value),
+            ">=" => FilterPredicate::Ge(value),
+            "<" => FilterPredicate::Lt(value),
+            _ => FilterPredicate::Gt(value),
+        };
+
+        Ok(Self {
+            column: String::from(column),
+            predicate,
+        })
+    }
+}
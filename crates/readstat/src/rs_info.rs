@@ -0,0 +1,122 @@
+use clap::ValueEnum;
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::rs_path::IN_EXTENSIONS;
+use crate::{IpcCompression, OutFormat, ParquetCompression};
+
+// arrow2 does not expose its version at compile time; keep in step with Cargo.toml
+const ARROW2_VERSION: &str = "0.18";
+// arrow2 features enabled within Cargo.toml
+const ARROW2_FEATURES: &[&str] = &[
+    "compute_aggregate",
+    "compute_cast",
+    "compute_concatenate",
+    "io_csv",
+    "io_ipc",
+    "io_ipc_compression",
+    "io_json",
+    "io_parquet",
+    "io_parquet_compression",
+    "io_print",
+];
+
+// features of this crate enabled when compiled
+const CRATE_FEATURES: &[&str] = &[
+    #[cfg(feature = "async")]
+    "async",
+    #[cfg(feature = "cache")]
+    "cache",
+    #[cfg(feature = "polars")]
+    "polars",
+    #[cfg(feature = "system-readstat")]
+    "system-readstat",
+];
+
+pub(crate) fn has_arrow2_feature(feature: &str) -> bool {
+    ARROW2_FEATURES.contains(&feature)
+}
+
+/// Version of the ReadStat C library compiled into this crate, or linked against with the
+/// `system-readstat` feature
+pub fn readstat_version() -> String {
+    String::from(readstat_sys::READSTAT_VERSION)
+}
+
+/// Versions and capabilities of this build
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadStatVersionInfo {
+    pub crate_version: String,
+    pub readstat_version: String,
+    /// `vendored`, or `system` when linked against the ReadStat located via pkg-config
+    pub readstat_source: String,
+    pub arrow2_version: String,
+    pub input_formats: Vec<String>,
+    pub output_formats: Vec<String>,
+    pub parquet_compression: Vec<String>,
+    pub ipc_compression: Vec<String>,
+    pub features: Vec<String>,
+}
+
+impl ReadStatVersionInfo {
+    pub fn new() -> Self {
+        Self {
+            crate_version: String::from(env!("CARGO_PKG_VERSION")),
+            readstat_version: readstat_version(),
+            readstat_source: String::from(if readstat_sys::READSTAT_SYSTEM {
+                "system"
+            } else {
+                "vendored"
+            }),
+            arrow2_version: String::from(ARROW2_VERSION),
+            input_formats: IN_EXTENSIONS.iter().map(|e| String::from(*e)).collect(),
+            output_formats: value_names(OutFormat::value_variants()),
+            parquet_compression: value_names(ParquetCompression::value_variants()),
+            ipc_compression: value_names(IpcCompression::value_variants()),
+            features: ARROW2_FEATURES
+                .iter()
+                .map(|f| format!("arrow2/{}", f))
+                .chain(CRATE_FEATURES.iter().map(|f| String::from(*f)))
+                .collect(),
+        }
+    }
+
+    pub fn write_to_stdout(&self) {
+        println!("{}: {}", "readstat".purple(), self.crate_version);
+        println!(
+            "{}: {} ({})",
+            "ReadStat".blue(),
+            self.readstat_version,
+            self.readstat_source
+        );
+        println!("{}: {}", "arrow2".cyan(), self.arrow2_version);
+        println!("{}: {}", "Input formats".yellow(), self.input_formats.join(", "));
+        println!("{}: {}", "Output formats".green(), self.output_formats.join(", "));
+        println!(
+            "{}: {}",
+            "Parquet compression".red(),
+            self.parquet_compression.join(", ")
+        );
+        println!(
+            "{}: {}",
+            "Feather compression".blue(),
+            self.ipc_compression.join(", ")
+        );
+        println!("{}: {}", "Features".purple(), self.features.join(", "));
+    }
+}
+
+impl Default for ReadStatVersionInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Names as accepted on the command line
+fn value_names<T: ValueEnum>(variants: &[T]) -> Vec<String> {
+    variants
+        .iter()
+        .filter_map(|v| v.to_possible_value())
+        .map(|p| String::from(p.get_name()))
+        .collect()
+}
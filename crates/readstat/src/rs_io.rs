@@ -0,0 +1,287 @@
+use colored::Colorize;
+use flate2::read::MultiGzDecoder;
+use log::debug;
+use num_format::{Locale, ToFormattedString};
+use std::{
+    error::Error,
+    fmt,
+    fs::File,
+    io::{BufReader, Cursor, ErrorKind, Read, Seek, SeekFrom},
+    os::raw::{c_char, c_int, c_long, c_void},
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+/// A source of the bytes of a file, such as a [`Cursor`] over a buffer held in memory
+pub trait ReadStatSource: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ReadStatSource for T {}
+
+/// Input read from memory, or from any other source, rather than from the filesystem
+///
+/// Clones share the source; every parse keeps its own position within it, so that the same input
+/// may be parsed for its metadata and then for its data, or from several threads at once.
+#[derive(Clone)]
+pub struct ReadStatInput {
+    source: Arc<Mutex<Box<dyn ReadStatSource>>>,
+}
+
+impl ReadStatInput {
+    /// Input of the bytes of a file
+    pub fn from_bytes(bytes: impl Into<Arc<[u8]>>) -> Self {
+        Self::from_reader(Cursor::new(bytes.into()))
+    }
+
+    /// Input of the bytes of a file read from `reader`
+    pub fn from_reader<R: Read + Seek + Send + 'static>(reader: R) -> Self {
+        Self {
+            source: Arc::new(Mutex::new(Box::new(reader))),
+        }
+    }
+}
+
+impl fmt::Debug for ReadStatInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ReadStatInput")
+    }
+}
+
+/// Limit upon the bytes a compressed input is decompressed into, unless set by
+/// [`ReadStatPath::set_max_decompressed_bytes`](crate::ReadStatPath::set_max_decompressed_bytes)
+pub const DEFAULT_MAX_DECOMPRESSED_BYTES: u64 = 4 * 1024 * 1024 * 1024;
+
+// Extensions and leading bytes of the compressed inputs read
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// Compression of an input file, e.g. `cars.sas7bdat.gz`
+///
+/// ReadStat seeks back and forth through a file whereas neither gzip nor zstd streams may be
+/// seeked, so a compressed input is decompressed into memory once, ahead of parsing, and read
+/// from there; nothing is written to disk.  Decompression stops with an error once it passes the
+/// limit of [`ReadStatPath::max_decompressed_bytes`](crate::ReadStatPath::max_decompressed_bytes).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputCompression {
+    Gzip,
+    Zstd,
+}
+
+impl InputCompression {
+    /// Compression of the file at `path`, by its extension (`gz` or `zst`) or else by the magic
+    /// bytes it begins with; `None` for a file that is not compressed
+    pub fn detect(path: &Path) -> Option<Self> {
+        Self::of_extension(path).or_else(|| {
+            let mut magic = [0_u8; 4];
+            let n = File::open(path).and_then(|mut f| f.read(&mut magic)).ok()?;
+            Self::of_magic(&magic[..n])
+        })
+    }
+
+    fn of_extension(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("gz") => Some(Self::Gzip),
+            Some("zst") => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    fn of_magic(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(GZIP_MAGIC) {
+            Some(Self::Gzip)
+        } else if bytes.starts_with(ZSTD_MAGIC) {
+            Some(Self::Zstd)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for InputCompression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Gzip => f.write_str("gzip"),
+            Self::Zstd => f.write_str("zstd"),
+        }
+    }
+}
+
+/// `path` without the extension of its compression, e.g. `cars.sas7bdat` for
+/// `cars.sas7bdat.gz`; any other path is returned as is
+pub fn without_compression_extension(path: &Path) -> PathBuf {
+    match InputCompression::of_extension(path) {
+        Some(_) => path.with_extension(""),
+        None => path.to_path_buf(),
+    }
+}
+
+// The decompressed bytes of the file at path, of at most max_bytes
+pub(crate) fn decompress(
+    path: &Path,
+    compression: InputCompression,
+    max_bytes: u64,
+) -> Result<ReadStatInput, Box<dyn Error + Send + Sync>> {
+    let f = BufReader::new(File::open(path)?);
+    // gzip files may hold several members, as written by e.g. pigz
+    let reader: Box<dyn Read> = match compression {
+        InputCompression::Gzip => Box::new(MultiGzDecoder::new(f)),
+        InputCompression::Zstd => Box::new(zstd::stream::read::Decoder::with_buffer(f)?),
+    };
+
+    // one byte beyond the limit tells a file of exactly max_bytes from a larger one
+    let mut bytes = Vec::new();
+    reader
+        .take(max_bytes.saturating_add(1))
+        .read_to_end(&mut bytes)
+        .map_err(|e| {
+            format!(
+                "Error when attempting to decompress {} as {}: {}",
+                path.to_string_lossy().bright_yellow(),
+                compression,
+                e
+            )
+        })?;
+
+    if bytes.len() as u64 > max_bytes {
+        return Err(From::from(format!(
+            "The file {} decompresses to more than {} bytes, the most read into memory.  To read it, raise the limit with the {} parameter of the data subcommand or decompress the file ahead of reading",
+            path.to_string_lossy().bright_yellow(),
+            max_bytes.to_formatted_string(&Locale::en),
+            String::from("--max-decompressed-size").bright_cyan()
+        )));
+    }
+
+    debug!("Decompressed {} bytes of {}", bytes.len(), path.display());
+    Ok(ReadStatInput::from_bytes(bytes))
+}
+
+// I/O context of a single parse, within which ReadStat opens, seeks through, and reads the input
+//   in place of a file
+// Held apart from the context of the handlers of metadata and values, which remains the
+//   ReadStatMetadata or ReadStatData being read into
+pub(crate) struct ReadStatIo {
+    source: Arc<Mutex<Box<dyn ReadStatSource>>>,
+    position: u64,
+}
+
+impl ReadStatIo {
+    pub(crate) fn new(input: &ReadStatInput) -> Self {
+        Self {
+            source: Arc::clone(&input.source),
+            position: 0,
+        }
+    }
+
+    fn seek(&mut self, offset: i64, whence: readstat_sys::readstat_io_flags_t) -> Option<u64> {
+        let position = match whence {
+            readstat_sys::readstat_io_flags_e_READSTAT_SEEK_SET => Some(offset),
+            readstat_sys::readstat_io_flags_e_READSTAT_SEEK_CUR => {
+                (self.position as i64).checked_add(offset)
+            }
+            readstat_sys::readstat_io_flags_e_READSTAT_SEEK_END => {
+                let mut source = self.source.lock().ok()?;
+                let len = source.seek(SeekFrom::End(0)).ok()?;
+                (len as i64).checked_add(offset)
+            }
+            _ => None,
+        };
+
+        self.position = position.filter(|p| *p >= 0)? as u64;
+        Some(self.position)
+    }
+
+    // Reads until buf is full or the input ends, as ReadStat takes a short read for an error
+    fn read(&mut self, buf: &mut [u8]) -> Option<usize> {
+        let mut source = self.source.lock().ok()?;
+        source.seek(SeekFrom::Start(self.position)).ok()?;
+
+        let mut filled = 0;
+        while filled < buf.len() {
+            match source.read(&mut buf[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => (),
+                Err(_) => return None,
+            }
+        }
+
+        self.position += filled as u64;
+        Some(filled)
+    }
+}
+
+// C callback functions
+// As with the handlers of metadata and values, a panic must never unwind into ReadStat
+
+fn io_ctx<'a>(io_ctx: *mut c_void) -> &'a mut ReadStatIo {
+    unsafe { &mut *(io_ctx as *mut ReadStatIo) }
+}
+
+fn or_on_panic<T, F: FnOnce() -> T>(failure: T, f: F) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|_| {
+        debug!("I/O callback panicked");
+        failure
+    })
+}
+
+pub(crate) extern "C" fn handle_open(_path: *const c_char, ctx: *mut c_void) -> c_int {
+    // the path only names the input; every parse begins from its start
+    io_ctx(ctx).position = 0;
+    0
+}
+
+pub(crate) extern "C" fn handle_close(_ctx: *mut c_void) -> c_int {
+    0
+}
+
+#[allow(clippy::unnecessary_cast)]
+pub(crate) extern "C" fn handle_seek(
+    offset: readstat_sys::readstat_off_t,
+    whence: readstat_sys::readstat_io_flags_t,
+    ctx: *mut c_void,
+) -> readstat_sys::readstat_off_t {
+    or_on_panic(-1, || match io_ctx(ctx).seek(offset as i64, whence) {
+        Some(p) => p as readstat_sys::readstat_off_t,
+        None => -1,
+    })
+}
+
+pub(crate) extern "C" fn handle_read(
+    buf: *mut c_void,
+    nbyte: usize,
+    ctx: *mut c_void,
+) -> readstat_sys::ssize_t {
+    if nbyte == 0 {
+        return 0;
+    }
+
+    or_on_panic(-1, || {
+        let buf = unsafe { std::slice::from_raw_parts_mut(buf as *mut u8, nbyte) };
+
+        match io_ctx(ctx).read(buf) {
+            Some(n) => n as readstat_sys::ssize_t,
+            None => -1,
+        }
+    })
+}
+
+// Reports progress through the input, as ReadStat does for files
+pub(crate) extern "C" fn handle_update(
+    file_size: c_long,
+    progress_handler: readstat_sys::readstat_progress_handler,
+    user_ctx: *mut c_void,
+    ctx: *mut c_void,
+) -> readstat_sys::readstat_error_t {
+    let Some(progress_handler) = progress_handler else {
+        return readstat_sys::readstat_error_e_READSTAT_OK;
+    };
+    if file_size <= 0 {
+        return readstat_sys::readstat_error_e_READSTAT_OK;
+    }
+
+    let progress = io_ctx(ctx).position as f64 / file_size as f64;
+    match unsafe { progress_handler(progress, user_ctx) } {
+        0 => readstat_sys::readstat_error_e_READSTAT_OK,
+        _ => readstat_sys::readstat_error_e_READSTAT_ERROR_USER_ABORT,
+    }
+}
@@ -0,0 +1,104 @@
+use serde::Serialize;
+use std::{
+    error::Error,
+    ffi::OsString,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::rs_metadata::ReadStatMetadata;
+use crate::rs_schema::ReadStatSchema;
+
+/// Original and final name of a variable along with its label, SAS format, and Arrow type
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadStatMappingEntry {
+    pub original_name: String,
+    pub final_name: String,
+    pub label: String,
+    pub format: String,
+    pub arrow_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renamed_by: Option<String>,
+    /// `null` or the hash the values were replaced by
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redacted_by: Option<String>,
+    /// Label within the file, where `label` was truncated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_label: Option<String>,
+    /// SAS format within the file, where `format` was truncated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_format: Option<String>,
+}
+
+/// Mapping from the variables of a file to the columns written
+///
+/// Written alongside the output as `<output>.mapping.json` whenever any variable is renamed, so
+/// that the original names can always be recovered, redacted, so that it is known which
+/// columns no longer hold their original values, or when a label or format is truncated.
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadStatMapping {
+    pub table_name: String,
+    /// Fingerprint of the columns written; see [`ReadStatSchema::fingerprint_columns`]
+    pub schema_fingerprint: String,
+    pub variables: Vec<ReadStatMappingEntry>,
+}
+
+impl ReadStatMapping {
+    pub fn new(md: &ReadStatMetadata) -> Self {
+        let variables = md
+            .vars
+            .iter()
+            .zip(md.schema.fields.iter())
+            .map(|((k, vm), f)| ReadStatMappingEntry {
+                original_name: vm.var_name.clone(),
+                final_name: f.name.clone(),
+                label: vm.var_label.clone(),
+                format: vm.var_format.clone(),
+                arrow_type: format!("{:?}", f.data_type()),
+                renamed_by: md.renames.get(k).map(|r| r.reason.to_string()),
+                redacted_by: md.redactions.get(k).map(|r| r.to_string()),
+                original_label: md.truncations.get(k).and_then(|t| t.label.clone()),
+                original_format: md.truncations.get(k).and_then(|t| t.format.clone()),
+            })
+            .collect();
+
+        Self {
+            table_name: md.table_name.clone(),
+            schema_fingerprint: ReadStatSchema::new(md).fingerprint,
+            variables,
+        }
+    }
+
+    /// Whether any variable was renamed
+    pub fn has_renames(&self) -> bool {
+        self.variables.iter().any(|v| v.renamed_by.is_some())
+    }
+
+    /// Whether any variable was redacted
+    pub fn has_redactions(&self) -> bool {
+        self.variables.iter().any(|v| v.redacted_by.is_some())
+    }
+
+    /// Whether any label or format was truncated
+    pub fn has_truncations(&self) -> bool {
+        self.variables
+            .iter()
+            .any(|v| v.original_label.is_some() || v.original_format.is_some())
+    }
+
+    /// `<output>.mapping.json`, e.g. `cars.parquet.mapping.json`
+    pub fn sidecar_path(out_path: &Path) -> PathBuf {
+        let mut p = OsString::from(out_path.as_os_str());
+        p.push(".mapping.json");
+        PathBuf::from(p)
+    }
+
+    /// Write as pretty json to `path`, replacing any existing file
+    pub fn write(&self, path: &Path) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut f = File::create(path)?;
+        f.write_all(serde_json::to_string_pretty(self)?.as_bytes())?;
+        f.write_all(b"\n")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,144 @@
+use arrow2::datatypes::DataType;
+use serde::Serialize;
+use std::{collections::BTreeSet, error::Error};
+
+use crate::common::build_offsets;
+use crate::rs_data::ReadStatData;
+use crate::rs_metadata::ReadStatMetadata;
+use crate::rs_normalize::ReadStatStringNormalize;
+use crate::rs_path::ReadStatPath;
+use crate::rs_var::ReadStatVar;
+
+/// Rows and string bytes of one batch of a [`ReadStatMeasure`]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ReadStatMeasuredBatch {
+    pub row_start: usize,
+    pub rows: usize,
+    /// Bytes of the string values of each column, by position; 0 for columns not strings
+    pub string_bytes: Vec<usize>,
+}
+
+/// Exact sizes of the values of a file, found by a first pass that reads every value and then
+/// discards it
+///
+/// Strings are measured once normalized and redacted, i.e. as they are held in memory.  Batches
+/// are measured `stream_rows` at a time, so any batch of a second pass over the same rows, or the
+/// single batch of the mem reader, is the sum of consecutive measured batches.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ReadStatMeasure {
+    pub batches: Vec<ReadStatMeasuredBatch>,
+}
+
+impl ReadStatMeasure {
+    /// Measure the first `rows` rows of the input described by `rsp`, reading `stream_rows`
+    /// rows at a time
+    pub fn scan(
+        rsp: &ReadStatPath,
+        md: &ReadStatMetadata,
+        normalize: &ReadStatStringNormalize,
+        normalize_vars: &BTreeSet<i32>,
+        rows: u32,
+        stream_rows: u32,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Self::scan_from(rsp, md, normalize, normalize_vars, 0, rows, stream_rows)
+    }
+
+    /// As [`ReadStatMeasure::scan`], measuring the `rows` rows after the first `row_offset`
+    ///
+    /// Batches are keyed by their starting row counted from `row_offset`.
+    pub fn scan_from(
+        rsp: &ReadStatPath,
+        md: &ReadStatMetadata,
+        normalize: &ReadStatStringNormalize,
+        normalize_vars: &BTreeSet<i32>,
+        row_offset: u32,
+        rows: u32,
+        stream_rows: u32,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let offsets = build_offsets(rows, stream_rows)?;
+
+        let mut batches = Vec::new();
+        for w in offsets.windows(2).filter(|w| w[0] < w[1]) {
+            let mut d = ReadStatData::new()
+                .set_no_progress(true)
+                .set_measure_only(true)
+                .set_string_normalize(normalize.clone(), normalize_vars.clone())
+                .init(md.clone(), w[0], w[1])
+                .set_row_offset(row_offset);
+            d.read_data(rsp)?;
+
+            batches.push(ReadStatMeasuredBatch {
+                row_start: w[0] as usize,
+                rows: d.chunk_rows_processed,
+                string_bytes: d.string_bytes,
+            });
+        }
+
+        Ok(Self { batches })
+    }
+
+    /// Rows measured
+    pub fn rows(&self) -> usize {
+        self.batches.iter().map(|b| b.rows).sum()
+    }
+
+    /// Bytes of the string values of each column, by position, over the batches that begin
+    /// from `row_start` up to `row_end`
+    pub fn string_bytes_between(&self, row_start: usize, row_end: usize) -> Vec<usize> {
+        let mut bytes: Vec<usize> = Vec::new();
+        for b in self.between(row_start, row_end) {
+            bytes.resize(std::cmp::max(bytes.len(), b.string_bytes.len()), 0);
+            for (total, n) in bytes.iter_mut().zip(b.string_bytes.iter()) {
+                *total += n;
+            }
+        }
+        bytes
+    }
+
+    /// Bytes of memory needed to read the rows from `row_start` to `row_end` at once
+    ///
+    /// Counted as [`ReadStatMetadata::estimate_memory_bytes`] does, though with the measured bytes
+    /// of strings rather than their widths, so exact but for the overhead of the allocator.
+    pub fn memory_bytes(&self, md: &ReadStatMetadata, row_start: usize, row_end: usize) -> u64 {
+        let var_size = std::mem::size_of::<ReadStatVar>() as u64;
+        let rows: u64 = self
+            .between(row_start, row_end)
+            .map(|b| b.rows as u64)
+            .sum();
+        let string_bytes = self.string_bytes_between(row_start, row_end);
+
+        // strings are measured by position among the variables read
+        md.vars
+            .keys()
+            .zip(md.schema.fields.iter())
+            .filter(|(k, _)| md.is_selected(**k))
+            .enumerate()
+            .map(|(i, (_, f))| {
+                let values = match f.data_type() {
+                    DataType::Utf8 => {
+                        // owned Strings plus values and offsets of the Arrow array
+                        let bytes = string_bytes.get(i).copied().unwrap_or(0) as u64;
+                        2 * bytes + 4 * rows
+                    }
+                    DataType::Boolean => rows,
+                    DataType::Int16 => 2 * rows,
+                    DataType::Int32 | DataType::Float32 | DataType::Date32 | DataType::Time32(_) => {
+                        4 * rows
+                    }
+                    _ => 8 * rows,
+                };
+                var_size * rows + values
+            })
+            .sum()
+    }
+
+    fn between(
+        &self,
+        row_start: usize,
+        row_end: usize,
+    ) -> impl Iterator<Item = &ReadStatMeasuredBatch> {
+        self.batches
+            .iter()
+            .filter(move |b| b.row_start >= row_start && b.row_start < row_end)
+    }
+}
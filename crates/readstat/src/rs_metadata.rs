@@ -3,21 +3,45 @@ use colored::Colorize;
 use log::debug;
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
-use serde::Serialize;
-use std::{collections::BTreeMap, error::Error, ffi::c_void, os::raw::c_int};
+use serde::{Deserialize, Serialize, Serializer};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    ffi::{c_void, CString},
+    fmt,
+    os::raw::c_int,
+    path::Path,
+};
 
-use crate::cb::{handle_metadata, handle_variable};
-use crate::err::ReadStatError;
+use crate::cb::{handle_metadata, handle_value_label, handle_value_row_count, handle_variable};
+use crate::err::{ReadStatError, ReadStatParseError};
+use crate::rs_dictionary::{dictionary_type, StringEncoding};
+use crate::rs_estimate::ReadStatSizeEstimate;
 use crate::rs_parser::ReadStatParser;
 use crate::rs_path::ReadStatPath;
-use crate::rs_var::{ReadStatVarFormatClass, ReadStatVarType, ReadStatVarTypeClass};
+use crate::rs_redact::ReadStatRedaction;
+use crate::rs_sample::ReadStatSampleRow;
+use crate::rs_schema::arrow_type_name;
+use crate::rs_user_missing::ReadStatMissingRange;
+use crate::rs_value_labels::{ReadStatLabelSet, ReadStatLabelling};
+use crate::rs_var::{
+    ReadStatVar, ReadStatVarAlignment, ReadStatVarFormatClass, ReadStatVarMeasure, ReadStatVarType,
+    ReadStatVarTypeClass,
+};
+use crate::{DateTimeType, NameTransform, TimeHandling};
 
-#[derive(Clone, Debug, Default, Serialize)]
+/// Serialized with each variable's index and Arrow type, and with timestamps as ISO-8601; see
+/// [`ReadStatMetadata::to_json`]
+#[derive(Clone, Debug, Default)]
 pub struct ReadStatMetadata {
     pub row_count: c_int,
     pub var_count: c_int,
+    /// Without trailing whitespace; see [`ReadStatMetadata::raw_table_name`]
     pub table_name: String,
+    /// Without trailing whitespace; see [`ReadStatMetadata::raw_file_label`]
     pub file_label: String,
+    pub(crate) raw_table_name: String,
+    pub(crate) raw_file_label: String,
     pub file_encoding: String,
     pub version: c_int,
     pub is64bit: c_int,
@@ -26,8 +50,135 @@ pub struct ReadStatMetadata {
     pub compression: ReadStatCompress,
     pub endianness: ReadStatEndian,
     pub vars: BTreeMap<i32, ReadStatVarMetadata>,
-    #[serde(skip_serializing)]
+    pub(crate) variable_order: Vec<(usize, String)>,
     pub schema: Schema,
+    pub datetime_type: Option<DateTimeType>,
+    /// Arrow type of variables with a SAS time format; see [`ReadStatMetadata::set_time_handling`]
+    pub time_handling: TimeHandling,
+    /// Store plain numerics whose SAS format displays no decimals as Arrow Int64; see
+    /// [`ReadStatMetadata::set_infer_integers`]
+    pub infer_integers: bool,
+    /// Store string columns as Arrow dictionaries; see [`ReadStatMetadata::set_string_encoding`]
+    pub string_encoding: StringEncoding,
+    pub type_decisions: BTreeMap<i32, ReadStatTypeDecision>,
+    pub renames: BTreeMap<i32, ReadStatRename>,
+    pub redactions: BTreeMap<i32, ReadStatRedaction>,
+    pub truncations: BTreeMap<i32, ReadStatTruncation>,
+    /// Indices of the variables written, or `None` for every variable; see
+    /// [`ReadStatMetadata::selected`]
+    pub selection: Option<BTreeSet<i32>>,
+    /// Arrow types variables are cast to once read, keyed by index
+    pub casts: BTreeMap<i32, DataType>,
+    /// Value label sets keyed by name, as read from a format catalog; see
+    /// [`ReadStatMetadata::read_value_labels`]
+    pub value_labels: BTreeMap<String, ReadStatLabelSet>,
+    /// Write variables with a label set as the text of their labels rather than their values
+    pub apply_value_labels: bool,
+    /// Labels applied to the values of each variable, keyed by index
+    pub labelled: BTreeMap<i32, ReadStatLabelling>,
+    pub encoding: Option<String>,
+    pub best_effort: bool,
+    /// Rename variables sharing a name rather than fail; see
+    /// [`ReadStatMetadata::set_dedup_columns`]
+    pub dedup_columns: bool,
+    /// Applied to every name once variables are read; see [`ReadStatMetadata::set_name_transform`]
+    pub name_transform: NameTransform,
+    pub salvage_error: Option<String>,
+    pub sample_rows: Option<Vec<ReadStatSampleRow>>,
+    pub size_estimate: Option<ReadStatSizeEstimate>,
+    /// See [`ReadStatSchema::fingerprint_columns`](crate::ReadStatSchema::fingerprint_columns)
+    pub schema_fingerprint: Option<String>,
+}
+
+// Borrows the fields of ReadStatMetadata that are serialized, in the order serialized
+#[derive(Serialize)]
+struct ReadStatMetadataJson<'a> {
+    row_count: c_int,
+    var_count: c_int,
+    table_name: &'a str,
+    file_label: &'a str,
+    file_encoding: &'a str,
+    version: c_int,
+    is64bit: c_int,
+    #[serde(serialize_with = "iso_8601")]
+    creation_time: &'a str,
+    #[serde(serialize_with = "iso_8601")]
+    modified_time: &'a str,
+    compression: &'a ReadStatCompress,
+    endianness: &'a ReadStatEndian,
+    vars: BTreeMap<i32, ReadStatVarMetadataJson<'a>>,
+    variable_order: &'a [(usize, String)],
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    value_labels: &'a BTreeMap<String, ReadStatLabelSet>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    salvage_error: &'a Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sample_rows: &'a Option<Vec<ReadStatSampleRow>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size_estimate: &'a Option<ReadStatSizeEstimate>,
+    /// See [`ReadStatSchema::fingerprint_columns`](crate::ReadStatSchema::fingerprint_columns)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schema_fingerprint: &'a Option<String>,
+}
+
+// A variable along with its index and the Arrow type it is written as, named as within
+//   ReadStatSchemaColumn::arrow_type; the type is absent until the schema is initialized
+#[derive(Serialize)]
+struct ReadStatVarMetadataJson<'a> {
+    var_index: i32,
+    #[serde(flatten)]
+    vm: &'a ReadStatVarMetadata,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    var_arrow_type: Option<String>,
+}
+
+// Timestamps are held as e.g. 2008-09-30 14:55:01; an empty string is left as is
+fn iso_8601<S: Serializer>(t: &&str, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&t.replacen(' ', "T", 1))
+}
+
+impl Serialize for ReadStatMetadata {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let vars = self
+            .vars
+            .iter()
+            .enumerate()
+            .map(|(i, (k, vm))| {
+                let vm = ReadStatVarMetadataJson {
+                    var_index: *k,
+                    vm,
+                    var_arrow_type: self
+                        .schema
+                        .fields
+                        .get(i)
+                        .map(|f| arrow_type_name(f.data_type())),
+                };
+                (*k, vm)
+            })
+            .collect();
+
+        ReadStatMetadataJson {
+            row_count: self.row_count,
+            var_count: self.var_count,
+            table_name: &self.table_name,
+            file_label: &self.file_label,
+            file_encoding: &self.file_encoding,
+            version: self.version,
+            is64bit: self.is64bit,
+            creation_time: &self.creation_time,
+            modified_time: &self.modified_time,
+            compression: &self.compression,
+            endianness: &self.endianness,
+            vars,
+            variable_order: &self.variable_order,
+            value_labels: &self.value_labels,
+            salvage_error: &self.salvage_error,
+            sample_rows: &self.sample_rows,
+            size_estimate: &self.size_estimate,
+            schema_fingerprint: &self.schema_fingerprint,
+        }
+        .serialize(serializer)
+    }
 }
 
 impl ReadStatMetadata {
@@ -37,6 +188,8 @@ impl ReadStatMetadata {
             var_count: 0,
             table_name: String::new(),
             file_label: String::new(),
+            raw_table_name: String::new(),
+            raw_file_label: String::new(),
             file_encoding: String::new(),
             version: 0,
             is64bit: 0,
@@ -45,45 +198,189 @@ impl ReadStatMetadata {
             compression: ReadStatCompress::None,
             endianness: ReadStatEndian::None,
             vars: BTreeMap::new(),
+            variable_order: Vec::new(),
             schema: Schema::default(),
+            datetime_type: None,
+            time_handling: TimeHandling::Time32,
+            infer_integers: false,
+            string_encoding: StringEncoding::Plain,
+            type_decisions: BTreeMap::new(),
+            renames: BTreeMap::new(),
+            redactions: BTreeMap::new(),
+            truncations: BTreeMap::new(),
+            selection: None,
+            casts: BTreeMap::new(),
+            value_labels: BTreeMap::new(),
+            apply_value_labels: false,
+            labelled: BTreeMap::new(),
+            encoding: None,
+            best_effort: false,
+            dedup_columns: false,
+            name_transform: NameTransform::None,
+            salvage_error: None,
+            sample_rows: None,
+            size_estimate: None,
+            schema_fingerprint: None,
+        }
+    }
+
+    pub fn set_datetime_type(self, datetime_type: Option<DateTimeType>) -> Self {
+        Self {
+            datetime_type,
+            ..self
         }
     }
 
-    fn initialize_schema(&self) -> Schema {
-        // build up Schema
+    /// Store variables with a SAS time format as times of day or as elapsed durations
+    ///
+    /// With [`TimeHandling::Time32`] a time outside 00:00:00 through 23:59:59, which Arrow's
+    /// time types cannot hold, is read as missing with a warning.  With
+    /// [`TimeHandling::DurationSeconds`] every time is kept exactly, as Int64 seconds (or
+    /// microseconds for formats displaying fractions of a second) with the unit recorded within
+    /// the metadata of the field as `duration_unit`.
+    pub fn set_time_handling(self, time_handling: TimeHandling) -> Self {
+        Self {
+            time_handling,
+            ..self
+        }
+    }
+
+    /// Store plain numerics whose SAS format displays no decimals (e.g. `8.`, `F8.0`, or
+    /// `COMMA6.`) as Arrow Int64 rather than Float64; see [`ReadStatFormatSpec::is_integral`]
+    ///
+    /// Decided from the format as each variable is read, before any data, so every batch holds
+    /// the same types.  When the data is read, a value that is not a whole number within the
+    /// range of Int64 is an error.
+    pub fn set_infer_integers(self, infer_integers: bool) -> Self {
+        Self {
+            infer_integers,
+            ..self
+        }
+    }
+
+    /// Store string columns as `Dictionary(Int32, Utf8)` rather than `Utf8`, each distinct value
+    /// held once
+    ///
+    /// Takes effect once [`apply_string_encoding`](crate::apply_string_encoding) decides which
+    /// columns to encode, ahead of reading the data.  Every writer handles dictionaries; csv and
+    /// ndjson are written with the values rather than their keys.
+    pub fn set_string_encoding(self, string_encoding: StringEncoding) -> Self {
+        Self {
+            string_encoding,
+            ..self
+        }
+    }
+
+    /// Metadata as pretty json, with variables keyed and ordered by index
+    ///
+    /// Every variable includes its index and the Arrow type it is written as; timestamps are
+    /// ISO-8601, e.g. `2008-09-30T14:55:01`, and enums are written as the names of their
+    /// variants, e.g. `Little`.
+    pub fn to_json(&self) -> Result<String, Box<dyn Error + Send + Sync>> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Index and name of every variable in the order stored within the file
+    ///
+    /// Recorded as each variable is parsed, ahead of any renaming, so it is unaffected by changes
+    /// made to the schema.
+    pub fn variable_order(&self) -> Vec<(usize, String)> {
+        self.variable_order.clone()
+    }
+
+    /// Table name exactly as within the file
+    ///
+    /// Some writers pad the table name with spaces; `table_name` has any trailing whitespace
+    /// removed so that it may be compared directly.
+    pub fn raw_table_name(&self) -> &str {
+        &self.raw_table_name
+    }
+
+    /// File label exactly as within the file, before trailing whitespace is removed
+    pub fn raw_file_label(&self) -> &str {
+        &self.raw_file_label
+    }
+
+    /// Succeed despite a parsing error so long as every variable was described
+    ///
+    /// For files whose header is intact but whose data is not; the error is recorded within
+    /// `salvage_error`.
+    pub fn set_best_effort(self, best_effort: bool) -> Self {
+        Self {
+            best_effort,
+            ..self
+        }
+    }
+
+    /// Rename each later variable sharing the name of an earlier one to `<name>_1`, `<name>_2`,
+    /// and so on, rather than fail reading metadata
+    ///
+    /// Renames are recorded within `renames`, so appear within the mapping sidecar.  A suffix
+    /// already taken by another variable is skipped.
+    pub fn set_dedup_columns(self, dedup_columns: bool) -> Self {
+        Self {
+            dedup_columns,
+            ..self
+        }
+    }
+
+    /// Transform the name of every variable as its field is added to the schema
+    ///
+    /// The name within the file stays the variable's `var_name`, and is kept within the metadata
+    /// of its field as `original_name`.  Variables the transform gives the same name are an error
+    /// unless [`ReadStatMetadata::set_dedup_columns`] is set, when every one after the first is
+    /// suffixed.  Columns are still selected by their names within the file.
+    pub fn set_name_transform(self, name_transform: NameTransform) -> Self {
+        Self {
+            name_transform,
+            ..self
+        }
+    }
+
+    /// Override the character encoding declared by the file when reading strings
+    pub fn set_encoding(self, encoding: Option<String>) -> Self {
+        Self { encoding, ..self }
+    }
+
+    /// Once value labels are read, write each variable with a label set as Arrow strings holding
+    /// the labels of its values; values without a label are written as their text
+    pub fn set_apply_value_labels(self, apply_value_labels: bool) -> Self {
+        Self {
+            apply_value_labels,
+            ..self
+        }
+    }
+
+    pub(crate) fn initialize_schema(&self) -> Schema {
+        // build up Schema from the type decisions made while reading variable metadata
         let fields: Vec<Field> = self
             .vars
-            .values()
-            .map(|vm| {
-                let var_dt = match &vm.var_type {
-                    ReadStatVarType::String
-                    | ReadStatVarType::StringRef
-                    | ReadStatVarType::Unknown => DataType::Utf8,
-                    ReadStatVarType::Int8 | ReadStatVarType::Int16 => DataType::Int16,
-                    ReadStatVarType::Int32 => DataType::Int32,
-                    ReadStatVarType::Float => DataType::Float32,
-                    ReadStatVarType::Double => match &vm.var_format_class {
-                        Some(ReadStatVarFormatClass::Date) => DataType::Date32,
-                        Some(ReadStatVarFormatClass::DateTime) => {
-                            DataType::Timestamp(TimeUnit::Second, None)
-                        }
-                        Some(ReadStatVarFormatClass::DateTimeWithMilliseconds) => {
-                            // DataType::Timestamp(arrow::datatypes::TimeUnit::Second, None)
-                            DataType::Timestamp(TimeUnit::Millisecond, None)
-                        }
-                        Some(ReadStatVarFormatClass::DateTimeWithMicroseconds) => {
-                            // DataType::Timestamp(arrow::datatypes::TimeUnit::Second, None)
-                            DataType::Timestamp(TimeUnit::Microsecond, None)
-                        }
-                        Some(ReadStatVarFormatClass::DateTimeWithNanoseconds) => {
-                            // DataType::Timestamp(arrow::datatypes::TimeUnit::Second, None)
-                            DataType::Timestamp(TimeUnit::Nanosecond, None)
-                        }
-                        Some(ReadStatVarFormatClass::Time) => DataType::Time32(TimeUnit::Second),
-                        None => DataType::Float64,
-                    },
+            .iter()
+            .map(|(k, vm)| {
+                let var_dt = match self.type_decisions.get(k) {
+                    Some(td) => td.data_type.clone(),
+                    None => {
+                        ReadStatTypeDecision::new(vm, self.datetime_type, self.time_handling)
+                            .data_type
+                    }
                 };
-                Field::new(&vm.var_name, var_dt, true)
+                // times read as durations record their unit
+                let duration_unit = match vm.var_format_class {
+                    Some(ReadStatVarFormatClass::Time) => Some("second"),
+                    Some(ReadStatVarFormatClass::TimeWithMicroseconds) => Some("microsecond"),
+                    _ => None,
+                }
+                .filter(|_| var_dt == DataType::Int64);
+                let field = Field::new(self.final_name(*k), var_dt, true);
+
+                let mut metadata = BTreeMap::new();
+                if self.renames.contains_key(k) {
+                    metadata.insert(String::from("original_name"), vm.var_name.clone());
+                }
+                if let Some(unit) = duration_unit {
+                    metadata.insert(String::from("duration_unit"), String::from(unit));
+                }
+                field.with_metadata(metadata)
             })
             .collect();
 
@@ -91,6 +388,675 @@ impl ReadStatMetadata {
         // Schema::new(fields)
     }
 
+    /// Store the named numeric variables as Arrow booleans
+    ///
+    /// Only plain numerics (doubles without a date, time, or datetime format) may be stored as
+    /// booleans.  When the data is read, any value other than 0 or 1 is an error.
+    pub fn set_boolean_columns(
+        &mut self,
+        names: &[String],
+        reason: ReadStatTypeReason,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        for name in names {
+            let (k, vm) = self
+                .vars
+                .iter()
+                .find(|(_, vm)| &vm.var_name == name)
+                .ok_or_else(|| format!("The variable {} does not exist", name.bright_yellow()))?;
+
+            if !matches!(vm.var_type, ReadStatVarType::Double) || vm.var_format_class.is_some() {
+                return Err(From::from(format!(
+                    "The variable {} is not a plain numeric and cannot be stored as a boolean",
+                    name.bright_yellow()
+                )));
+            }
+
+            self.type_decisions.insert(
+                *k,
+                ReadStatTypeDecision {
+                    data_type: DataType::Boolean,
+                    reason,
+                },
+            );
+        }
+
+        // decisions changed, so rebuild the schema
+        self.schema = self.initialize_schema();
+
+        Ok(())
+    }
+
+    /// Read the named plain numeric variables as dates or datetimes, as if the file gave them
+    /// such a format
+    pub fn set_date_columns(
+        &mut self,
+        columns: &[(String, ReadStatVarFormatClass)],
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        for (name, format_class) in columns {
+            let (k, vm) = self
+                .vars
+                .iter_mut()
+                .find(|(_, vm)| &vm.var_name == name)
+                .ok_or_else(|| format!("The variable {} does not exist", name.bright_yellow()))?;
+
+            if !matches!(vm.var_type, ReadStatVarType::Double) || vm.var_format_class.is_some() {
+                return Err(From::from(format!(
+                    "The variable {} is not a plain numeric and cannot be read as a date",
+                    name.bright_yellow()
+                )));
+            }
+
+            vm.var_format_class = Some(*format_class);
+            let data_type =
+                ReadStatTypeDecision::new(vm, self.datetime_type, self.time_handling).data_type;
+            self.type_decisions.insert(
+                *k,
+                ReadStatTypeDecision {
+                    data_type,
+                    reason: ReadStatTypeReason::DateDetection,
+                },
+            );
+        }
+
+        // decisions changed, so rebuild the schema
+        self.schema = self.initialize_schema();
+
+        Ok(())
+    }
+
+    /// Read only the named variables, named as within the file
+    ///
+    /// Narrows `selection`, so variables already dropped stay dropped.  Variables are written in
+    /// file order, whatever the order of `columns`.  Unlike variables dropped by a spec, the
+    /// other variables are skipped by ReadStat while parsing so their values are never read.
+    pub fn set_columns(&mut self, columns: &[String]) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut keep = BTreeSet::new();
+        for name in columns {
+            match self.vars.iter().find(|(_, vm)| &vm.var_name == name) {
+                Some((k, _)) => {
+                    keep.insert(*k);
+                }
+                None => {
+                    let available: Vec<&str> =
+                        self.vars.values().map(|vm| vm.var_name.as_str()).collect();
+                    return Err(From::from(format!(
+                        "The variable {} does not exist; the variables within the file are {}",
+                        name.bright_yellow(),
+                        available.join(", ")
+                    )));
+                }
+            }
+        }
+
+        self.selection = Some(match self.selection.take() {
+            Some(s) => s.intersection(&keep).copied().collect(),
+            None => keep,
+        });
+
+        Ok(())
+    }
+
+    /// Read the value label sets of the format catalog (`sas7bcat`) at `catalog`
+    ///
+    /// Each variable is matched to the label set named as its SAS format; see
+    /// [`ReadStatMetadata::set_value_labels`].  A warning is returned for each variable whose
+    /// format is user-defined yet missing from the catalog, as its values cannot be labelled.
+    pub fn read_value_labels(
+        &mut self,
+        catalog: &Path,
+    ) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+        let cstring_path = CString::new(catalog.to_string_lossy().as_bytes())?;
+        let mut sets: BTreeMap<String, ReadStatLabelSet> = BTreeMap::new();
+        let ctx = &mut sets as *mut BTreeMap<String, ReadStatLabelSet> as *mut c_void;
+
+        let error = ReadStatParser::new()
+            .set_value_label_handler(Some(handle_value_label))?
+            .parse_sas7bcat(cstring_path.as_ptr(), ctx);
+
+        #[allow(clippy::useless_conversion)]
+        match FromPrimitive::from_i32(error.try_into().unwrap()) {
+            Some(ReadStatError::READSTAT_OK) => {
+                self.set_value_labels(sets);
+                let warnings = self
+                    .missing_label_sets()
+                    .into_iter()
+                    .map(|(k, name)| {
+                        format!(
+                            "{}: the format {} of variable {} is missing from the catalog {}",
+                            "Warning".bright_yellow(),
+                            name.bright_yellow(),
+                            self.vars[&k].var_name.bright_yellow(),
+                            catalog.to_string_lossy().bright_yellow()
+                        )
+                    })
+                    .collect();
+                Ok(warnings)
+            }
+            Some(e) => Err(From::from(format!(
+                "Error when attempting to parse the format catalog {}: {} ({})",
+                catalog.to_string_lossy().bright_yellow(),
+                ReadStatParseError::new(e).message,
+                e
+            ))),
+            None => Err(From::from(format!(
+                "Error when attempting to parse the format catalog {}: Unknown return value",
+                catalog.to_string_lossy().bright_yellow()
+            ))),
+        }
+    }
+
+    /// Index and label set name of each selected variable whose label set, or user-defined
+    /// format, is not among [`ReadStatMetadata::value_labels`]
+    pub fn missing_label_sets(&self) -> Vec<(i32, &str)> {
+        self.vars
+            .iter()
+            .filter(|(k, vm)| {
+                self.is_selected(**k)
+                    && vm.var_format_class.is_none()
+                    && self.label_set(**k).is_none()
+            })
+            .filter_map(|(k, vm)| {
+                let label_set = vm.var_label_set.as_deref().filter(|n| !n.is_empty());
+                let user_defined = vm
+                    .var_format_parts
+                    .as_ref()
+                    .map(|p| p.name.as_str())
+                    .filter(|n| !SAS_FORMATS.contains(&n.to_uppercase().as_str()));
+                label_set.or(user_defined).map(|name| (*k, name))
+            })
+            .collect()
+    }
+
+    /// Record the value label sets `sets`, keyed by name
+    ///
+    /// With [`ReadStatMetadata::set_apply_value_labels`], every plain numeric or string variable
+    /// whose label set is among `sets` is then written as Arrow strings holding the labels of
+    /// its values.
+    pub fn set_value_labels(&mut self, sets: BTreeMap<String, ReadStatLabelSet>) {
+        self.value_labels = sets;
+        self.labelled.clear();
+
+        if self.apply_value_labels {
+            let labelled: Vec<(i32, ReadStatLabelling)> = self
+                .vars
+                .iter()
+                .zip(self.schema.fields.iter())
+                .filter(|((_, vm), _)| vm.var_format_class.is_none())
+                .filter_map(|((k, _), f)| {
+                    self.label_set(*k).map(|(name, labels)| {
+                        let labelling = ReadStatLabelling {
+                            set_name: String::from(name),
+                            labels: labels.clone(),
+                            source_type: f.data_type().clone(),
+                        };
+                        (*k, labelling)
+                    })
+                })
+                .collect();
+
+            for (k, labelling) in labelled {
+                self.type_decisions.insert(
+                    k,
+                    ReadStatTypeDecision {
+                        data_type: DataType::Utf8,
+                        reason: ReadStatTypeReason::ValueLabels,
+                    },
+                );
+                self.labelled.insert(k, labelling);
+            }
+
+            // decisions changed, so rebuild the schema
+            self.schema = self.initialize_schema();
+        }
+    }
+
+    /// Name and labels of the label set of the variable at `index`
+    ///
+    /// The set is the one named as the label set ReadStat reports for the variable or, failing
+    /// that, as the name of its SAS format (without its width); SAS names ignore case.
+    pub fn label_set(&self, index: i32) -> Option<(&str, &ReadStatLabelSet)> {
+        let vm = self.vars.get(&index)?;
+        let names = [
+            vm.var_label_set.as_deref(),
+            vm.var_format_parts.as_ref().map(|p| p.name.as_str()),
+        ];
+
+        names
+            .into_iter()
+            .flatten()
+            .filter(|n| !n.is_empty() && *n != "$")
+            .find_map(|n| {
+                self.value_labels
+                    .iter()
+                    .find(|(set, _)| set.eq_ignore_ascii_case(n))
+                    .map(|(set, labels)| (set.as_str(), labels))
+            })
+    }
+
+    /// Error unless the file at `rsp` holds a variable, as an Arrow schema without a field holds
+    /// no data to read or write
+    pub(crate) fn require_variables(
+        &self,
+        rsp: &ReadStatPath,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.var_count > 0 {
+            return Ok(());
+        }
+        Err(From::from(format!(
+            "The file {} holds no variables, so there is no data to read",
+            rsp.path.to_string_lossy().bright_yellow()
+        )))
+    }
+
+    /// Whether the variable at `index` is within `selection`
+    pub(crate) fn is_selected(&self, index: i32) -> bool {
+        self.selection.as_ref().map_or(true, |s| s.contains(&index))
+    }
+
+    /// Name the variable at `index` is given within the output
+    ///
+    /// This is the name from the file unless the variable has been renamed.
+    pub fn final_name(&self, index: i32) -> &str {
+        match self.renames.get(&index) {
+            Some(r) => &r.final_name,
+            None => self.vars.get(&index).map_or("", |vm| vm.var_name.as_str()),
+        }
+    }
+
+    /// Transform the name of every variable
+    pub fn transform_names(
+        &mut self,
+        transform: NameTransform,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let renames: Vec<(i32, String)> = self
+            .vars
+            .keys()
+            .map(|k| (*k, transform.apply(self.final_name(*k))))
+            .collect();
+
+        for (k, name) in renames {
+            self.rename_var(k, name, ReadStatRenameReason::NameTransform);
+        }
+
+        self.finish_renames()
+    }
+
+    /// Rename variables, given as pairs of the name within the file and the new name
+    ///
+    /// A rename replaces any earlier name transform for the same variable.
+    pub fn rename_vars(
+        &mut self,
+        renames: &[(String, String)],
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        for (old, new) in renames {
+            let k = self
+                .vars
+                .iter()
+                .find(|(_, vm)| &vm.var_name == old)
+                .map(|(k, _)| *k)
+                .ok_or_else(|| format!("The variable {} does not exist", old.bright_yellow()))?;
+
+            self.rename_var(k, new.clone(), ReadStatRenameReason::UserRename);
+        }
+
+        self.finish_renames()
+    }
+
+    /// Hold every variable label and SAS format to at most `max` characters
+    ///
+    /// A label or format that is too long is truncated, its original recorded so that it appears
+    /// within the mapping sidecar, and a warning naming the variable returned.  With `strict`,
+    /// nothing is truncated; instead an error names every variable that is over the budget.
+    pub fn limit_label_lengths(
+        &mut self,
+        max: usize,
+        strict: bool,
+    ) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+        let too_long = |s: &str| s.chars().count() > max;
+        let over: Vec<i32> = self
+            .vars
+            .iter()
+            .filter(|(_, vm)| too_long(&vm.var_label) || too_long(&vm.var_format))
+            .map(|(k, _)| *k)
+            .collect();
+
+        if strict && !over.is_empty() {
+            let names: Vec<String> = over
+                .iter()
+                .map(|k| self.vars[k].var_name.bright_yellow().to_string())
+                .collect();
+            return Err(From::from(format!(
+                "The label or format of the variables {} exceeds {} characters",
+                names.join(", "),
+                max
+            )));
+        }
+
+        let mut warnings = Vec::new();
+        for k in over {
+            let vm = self.vars.get_mut(&k).unwrap();
+            let mut truncation = ReadStatTruncation::default();
+            for (value, original, what) in [
+                (&mut vm.var_label, &mut truncation.label, "label"),
+                (&mut vm.var_format, &mut truncation.format, "format"),
+            ] {
+                if too_long(value) {
+                    warnings.push(format!(
+                        "{}: truncated the {} of variable {} from {} to {} characters",
+                        "Warning".bright_yellow(),
+                        what,
+                        vm.var_name.bright_yellow(),
+                        value.chars().count(),
+                        max
+                    ));
+                    let truncated: String = value.chars().take(max).collect();
+                    *original = Some(std::mem::replace(value, truncated));
+                }
+            }
+            self.truncations.insert(k, truncation);
+        }
+
+        Ok(warnings)
+    }
+
+    /// The variables as written, with the names and Arrow types they are written with
+    ///
+    /// Variables outside of `selection` are removed and those within `casts` given their new
+    /// types.  Variables keep their indices, so these may have gaps.  Variables outside of
+    /// `selection` are skipped while parsing; the rest are still read using `schema`, then cast
+    /// once read.
+    pub fn selected(&self) -> Self {
+        let mut md = self.clone();
+
+        if let Some(keep) = &self.selection {
+            md.vars.retain(|k, _| keep.contains(k));
+            md.type_decisions.retain(|k, _| keep.contains(k));
+            md.renames.retain(|k, _| keep.contains(k));
+            md.redactions.retain(|k, _| keep.contains(k));
+            md.truncations.retain(|k, _| keep.contains(k));
+            md.labelled.retain(|k, _| keep.contains(k));
+            md.var_count = md.vars.len() as c_int;
+        }
+
+        for (k, dt) in &self.casts {
+            if md.vars.contains_key(k) {
+                let reason = if *dt == dictionary_type() {
+                    ReadStatTypeReason::DictionaryEncoding
+                } else {
+                    ReadStatTypeReason::UserOverride
+                };
+                md.type_decisions.insert(
+                    *k,
+                    ReadStatTypeDecision {
+                        data_type: dt.clone(),
+                        reason,
+                    },
+                );
+            }
+        }
+
+        md.selection = None;
+        md.casts.clear();
+        md.schema = md.initialize_schema();
+        md
+    }
+
+    // Every renaming mechanism records its renames here so that the mapping stays complete
+    pub(crate) fn rename_var(&mut self, index: i32, final_name: String, reason: ReadStatRenameReason) {
+        let unchanged = self
+            .vars
+            .get(&index)
+            .map_or(true, |vm| vm.var_name == final_name);
+
+        if unchanged {
+            self.renames.remove(&index);
+        } else {
+            self.renames
+                .insert(index, ReadStatRename { final_name, reason });
+        }
+    }
+
+    // Variables sharing a name within the file, or once transformed by name_transform, are an
+    //   error unless renamed by dedup_columns; then initialize the schema
+    pub(crate) fn finish_variables(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.resolve_duplicate_names(false)?;
+
+        if self.name_transform != NameTransform::None {
+            let transformed: Vec<(i32, String)> = self
+                .vars
+                .keys()
+                .map(|k| (*k, self.name_transform.apply(self.final_name(*k))))
+                .filter(|(k, name)| name != self.final_name(*k))
+                .collect();
+            for (k, name) in transformed {
+                self.rename_var(k, name, ReadStatRenameReason::NameTransform);
+            }
+            self.resolve_duplicate_names(true)?;
+        }
+
+        self.schema = self.initialize_schema();
+
+        Ok(())
+    }
+
+    // Variables sharing a name are an error unless dedup_columns is set; then every one after the
+    //   first is given the lowest suffix no other variable is named with
+    fn resolve_duplicate_names(
+        &mut self,
+        transformed: bool,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut indices: BTreeMap<String, Vec<i32>> = BTreeMap::new();
+        for k in self.vars.keys() {
+            indices
+                .entry(String::from(self.final_name(*k)))
+                .or_default()
+                .push(*k);
+        }
+        let duplicates: Vec<(String, Vec<i32>)> =
+            indices.into_iter().filter(|(_, ks)| ks.len() > 1).collect();
+
+        if !duplicates.is_empty() && !self.dedup_columns {
+            let listed: Vec<String> = duplicates
+                .iter()
+                .map(|(name, ks)| {
+                    let ks: Vec<String> = if transformed {
+                        ks.iter()
+                            .map(|k| format!("{} at index {}", self.vars[k].var_name, k))
+                            .collect()
+                    } else {
+                        let ks: Vec<String> = ks.iter().map(|k| k.to_string()).collect();
+                        vec![format!("indices {}", ks.join(", "))]
+                    };
+                    format!("{} ({})", name.bright_yellow(), ks.join(", "))
+                })
+                .collect();
+            let what = if transformed {
+                "Transforming names results in more than one variable named"
+            } else {
+                "More than one variable is named"
+            };
+            return Err(From::from(format!(
+                "{} {}; set dedup_columns (--dedup-columns) to rename all but the first",
+                what,
+                listed.join(", ")
+            )));
+        }
+
+        let mut taken: BTreeSet<String> =
+            self.vars.keys().map(|k| String::from(self.final_name(*k))).collect();
+        for (name, ks) in duplicates {
+            let mut suffix = 0;
+            for k in &ks[1..] {
+                let new = loop {
+                    suffix += 1;
+                    let candidate = format!("{}_{}", name, suffix);
+                    if !taken.contains(&candidate) {
+                        break candidate;
+                    }
+                };
+                taken.insert(new.clone());
+                self.rename_var(*k, new, ReadStatRenameReason::Deduplication);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Names of the variables written must remain unique once renamed; if so rebuild the schema
+    pub(crate) fn finish_renames(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut seen: BTreeMap<&str, i32> = BTreeMap::new();
+        for k in self.vars.keys().filter(|k| self.is_selected(**k)) {
+            let name = self.final_name(*k);
+            if let Some(first) = seen.insert(name, *k) {
+                return Err(From::from(format!(
+                    "Renaming results in more than one variable named {} (variables at indices {} and {})",
+                    name.bright_yellow(),
+                    first,
+                    k
+                )));
+            }
+        }
+
+        self.schema = self.initialize_schema();
+
+        Ok(())
+    }
+
+    /// Estimated bytes of memory needed to read `rows` rows at once
+    ///
+    /// Each value is first held as a [`ReadStatVar`] and then copied into an Arrow array, so both
+    /// are counted.  String widths are taken from the SAS format (e.g. `$30`), or else from the
+    /// storage width of the variable, or else 8 bytes.
+    pub fn estimate_memory_bytes(&self, rows: u32) -> u64 {
+        let var_size = std::mem::size_of::<ReadStatVar>() as u64;
+
+        // variables outside of selection are skipped while parsing, so take no memory
+        let row_bytes: u64 = self
+            .vars
+            .iter()
+            .zip(self.schema.fields.iter())
+            .filter(|((k, _), _)| self.is_selected(**k))
+            .map(|((_, vm), f)| {
+                let (payload, arrow) = match f.data_type() {
+                    DataType::Utf8 => {
+                        let width = string_width(&vm.var_format, vm.var_storage_width);
+                        // owned String plus values and offsets of the Arrow array
+                        (width, width + 4)
+                    }
+                    DataType::Boolean => (0, 1),
+                    DataType::Int16 => (0, 2),
+                    DataType::Int32 | DataType::Float32 | DataType::Date32 | DataType::Time32(_) => {
+                        (0, 4)
+                    }
+                    _ => (0, 8),
+                };
+                var_size + payload + arrow
+            })
+            .sum();
+
+        row_bytes * rows as u64
+    }
+
+    /// Estimated sizes of reading `rows` rows into Arrow arrays and of writing them in each format
+    ///
+    /// Unlike [`ReadStatMetadata::estimate_memory_bytes`], only the Arrow arrays are counted.
+    pub fn estimate_size(&self, rows: u32) -> ReadStatSizeEstimate {
+        ReadStatSizeEstimate::new(self, rows)
+    }
+
+    /// Plain text table explaining the Arrow type chosen for each variable
+    pub fn type_report(&self) -> String {
+        let header: Vec<String> = [
+            "Variable",
+            "SAS type",
+            "Format",
+            "Format class",
+            "Arrow type",
+            "Reason",
+        ]
+        .iter()
+        .map(|h| h.to_string())
+        .collect();
+
+        let rows: Vec<Vec<String>> = self
+            .vars
+            .iter()
+            .map(|(k, vm)| {
+                let td = match self.type_decisions.get(k) {
+                    Some(td) => td.clone(),
+                    None => {
+                        ReadStatTypeDecision::new(vm, self.datetime_type, self.time_handling)
+                    }
+                };
+                vec![
+                    vm.var_name.clone(),
+                    format!("{:?}", vm.var_type),
+                    vm.var_format.clone(),
+                    vm.var_format_class
+                        .map_or(String::from("-"), |fc| format!("{:?}", fc)),
+                    format!("{:?}", td.data_type),
+                    td.reason.to_string(),
+                ]
+            })
+            .collect();
+
+        // width of each column
+        let mut widths: Vec<usize> = header.iter().map(|h| h.len()).collect();
+        for r in &rows {
+            for (w, c) in widths.iter_mut().zip(r.iter()) {
+                *w = (*w).max(c.chars().count());
+            }
+        }
+
+        let line = |cells: &[String]| -> String {
+            cells
+                .iter()
+                .zip(widths.iter())
+                .map(|(c, w)| format!("{:<1$}", c, w))
+                .collect::<Vec<String>>()
+                .join("  ")
+                .trim_end()
+                .to_owned()
+        };
+
+        let dashes: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+
+        let mut report = String::new();
+        for r in [&header, &dashes].into_iter().chain(rows.iter()) {
+            report.push_str(&line(r));
+            report.push('\n');
+        }
+        report
+    }
+
+    // Count the rows of a file that does not record how many it holds by reading every value; with
+    //   best_effort the rows reached ahead of an error are counted
+    fn count_rows(&self, rsp: &ReadStatPath) -> Result<c_int, Box<dyn Error + Send + Sync>> {
+        let mut rows: c_int = 0;
+        let ctx = &mut rows as *mut c_int as *mut c_void;
+
+        let error = ReadStatParser::new()
+            .set_value_handler(Some(handle_value_row_count))?
+            .set_file_character_encoding(self.encoding.as_deref())?
+            .set_input(rsp.source()?.as_ref())?
+            .parse(&rsp.extension, rsp.cstring_path.as_ptr(), ctx);
+
+        #[allow(clippy::useless_conversion)]
+        match FromPrimitive::from_i32(error.try_into().unwrap()) {
+            Some(ReadStatError::READSTAT_OK) => Ok(rows),
+            Some(_) if self.best_effort => Ok(rows),
+            Some(e) => Err(From::from(format!(
+                "Error when attempting to count the rows of {}: {} ({})",
+                rsp.path.to_string_lossy().bright_red(),
+                ReadStatParseError::new(e).message,
+                e
+            ))),
+            None => Err(From::from(
+                "Error when attempting to count rows: Unknown return value",
+            )),
+        }
+    }
+
     pub fn read_metadata(
         &mut self,
         rsp: &ReadStatPath,
@@ -132,8 +1098,10 @@ impl ReadStatMetadata {
         let error = ReadStatParser::new()
             .set_metadata_handler(Some(handle_metadata))?
             .set_variable_handler(Some(handle_variable))?
+            .set_file_character_encoding(self.encoding.as_deref())?
             .set_row_limit(row_limit)?
-            .parse_sas7bdat(ppath, ctx);
+            .set_input(rsp.source()?.as_ref())?
+            .parse(&rsp.extension, ppath, ctx);
 
         /*
         if let Some(pb) = &self.pb {
@@ -143,15 +1111,28 @@ impl ReadStatMetadata {
 
         #[allow(clippy::useless_conversion)]
         match FromPrimitive::from_i32(error.try_into().unwrap()) {
+            Some(ReadStatError::READSTAT_OK) if self.row_count < 0 && !skip_row_count => {
+                // transport files often do not record their row count
+                self.row_count = self.count_rows(rsp)?;
+                self.finish_variables()
+            }
             Some(ReadStatError::READSTAT_OK) => {
                 // if successful, initialize schema
-                self.schema = self.initialize_schema();
-                Ok(())
+                self.finish_variables()
             }
-            Some(e) => Err(From::from(format!(
-                "Error when attempting to parse sas7bdat: {:#?}",
-                e
-            ))),
+            Some(e)
+                if self.best_effort
+                    && self.var_count > 0
+                    && self.vars.len() == self.var_count as usize =>
+            {
+                // the header and every variable were read, which suffices to read what data can be
+                self.salvage_error = Some(format!("{:#?}", e));
+                if self.row_count < 0 && !skip_row_count {
+                    self.row_count = self.count_rows(rsp)?;
+                }
+                self.finish_variables()
+            }
+            Some(e) => Err(Box::new(ReadStatParseError::new(e))),
             None => Err(From::from(
                 "Error when attempting to parse sas7bdat: Unknown return value",
             )),
@@ -159,6 +1140,22 @@ impl ReadStatMetadata {
     }
 }
 
+// Width of a character format such as $30 or $CHAR12., or else of the storage of its values
+fn string_width(format: &str, storage_width: usize) -> u64 {
+    let digits: String = format
+        .trim_start_matches('$')
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+
+    match digits.parse() {
+        Ok(width) => width,
+        Err(_) if storage_width > 0 => storage_width as u64,
+        Err(_) => 8,
+    }
+}
+
 #[derive(Clone, Debug, Default, FromPrimitive, Serialize)]
 pub enum ReadStatCompress {
     #[default]
@@ -175,6 +1172,9 @@ pub enum ReadStatEndian {
     Big = readstat_sys::readstat_endian_e_READSTAT_ENDIAN_BIG as isize,
 }
 
+/// Variable metadata
+///
+/// Only the display format is available; ReadStat does not expose SAS informats.
 #[derive(Clone, Debug, Serialize)]
 pub struct ReadStatVarMetadata {
     pub var_name: String,
@@ -182,7 +1182,39 @@ pub struct ReadStatVarMetadata {
     pub var_type_class: ReadStatVarTypeClass,
     pub var_label: String,
     pub var_format: String,
+    /// The format exactly as stored, e.g. `DATETIME22` or `$30`; unlike `var_format` never
+    /// truncated by [`ReadStatMetadata::limit_label_lengths`]
+    pub var_format_raw: String,
+    /// The parts of `var_format_raw`; `None` without a format
+    pub var_format_parts: Option<ReadStatFormatSpec>,
     pub var_format_class: Option<ReadStatVarFormatClass>,
+    /// Bytes each value occupies within the file; zero when unknown, see
+    /// [`ReadStatVarMetadata::storage_width`]
+    pub var_storage_width: usize,
+    /// Characters the values are displayed within, as stored by the file; `None` when unset
+    pub var_display_width: Option<u32>,
+    /// `None` when the file stores no alignment
+    pub var_alignment: Option<ReadStatVarAlignment>,
+    /// `None` when the file stores no measure, as SAS files never do
+    pub var_measure: Option<ReadStatVarMeasure>,
+    /// Name of the value label set ReadStat reports for the variable, if any; for SAS files
+    /// this is usually the name of its format
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub var_label_set: Option<String>,
+    /// Tags of the special missing values (`.A` through `.Z` and `._`) observed among the values
+    /// of the variable; only known once its data has been read
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub var_missing_tags: BTreeSet<char>,
+    /// Missing values, and ranges of missing values, the variable declares, as SPSS variables
+    /// may; read as values unless [`UserMissing::AsNull`](crate::UserMissing::AsNull)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub var_missing_ranges: Vec<ReadStatMissingRange>,
+    /// Position of the variable among those read, which ReadStat reports as its index after
+    /// skipping every variable not read; unlike its index within the file, a variable is
+    /// shifted by each one skipped ahead of it.  `None` until the variables read are chosen, as
+    /// by [`ReadStatData::init`](crate::ReadStatData::init)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub var_index_after_skipping: Option<i32>,
 }
 
 impl ReadStatVarMetadata {
@@ -193,14 +1225,369 @@ impl ReadStatVarMetadata {
         var_label: String,
         var_format: String,
         var_format_class: Option<ReadStatVarFormatClass>,
+        var_storage_width: usize,
     ) -> Self {
         Self {
             var_name,
             var_type,
             var_type_class,
             var_label,
+            var_format_raw: var_format.clone(),
+            var_format_parts: ReadStatFormatSpec::parse(&var_format),
             var_format,
             var_format_class,
+            var_storage_width,
+            var_display_width: None,
+            var_alignment: None,
+            var_measure: None,
+            var_label_set: None,
+            var_missing_tags: BTreeSet::new(),
+            var_missing_ranges: Vec::new(),
+            var_index_after_skipping: None,
+        }
+    }
+
+    /// Bytes each value occupies within the file, or `None` when unknown
+    pub fn storage_width(&self) -> Option<usize> {
+        Some(self.var_storage_width).filter(|w| *w > 0)
+    }
+
+    /// Characters the values are displayed within, or `None` when unset
+    pub fn display_width(&self) -> Option<u32> {
+        self.var_display_width
+    }
+
+    pub fn alignment(&self) -> Option<ReadStatVarAlignment> {
+        self.var_alignment
+    }
+
+    pub fn measure(&self) -> Option<ReadStatVarMeasure> {
+        self.var_measure
+    }
+}
+
+// Names of the formats supplied with SAS, other than those of dates and times; any other format
+//   is user-defined, and so expected within a format catalog
+const SAS_FORMATS: &[&str] = &[
+    "", "$", "$ASCII", "$BINARY", "$CHAR", "$EBCDIC", "$HEX", "$OCTAL", "$QUOTE", "$REVERJ",
+    "$REVERS", "$UPCASE", "$VARYING", "BEST", "BESTD", "BINARY", "COMMA", "COMMAX", "D",
+    "DOLLAR", "DOLLARX", "E", "EURO", "EUROX", "F", "FLOAT", "FRACT", "HEX", "IB", "NEGPAREN",
+    "NLMNY", "NLNUM", "NLPCT", "NUMX", "OCTAL", "PD", "PERCENT", "PERCENTN", "PIB", "PVALUE",
+    "RB", "ROMAN", "SSN", "WORDF", "WORDS", "YEN", "Z", "ZD",
+];
+
+/// A SAS format split into its name, width, and decimals, e.g. `DATETIME22.3` into `DATETIME`,
+/// 22, and 3
+///
+/// Names of character formats begin with `$`, so `$30` is `$` of width 30; a format of only a
+/// width and decimals, e.g. `8.2`, has an empty name.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ReadStatFormatSpec {
+    pub name: String,
+    pub width: Option<u32>,
+    pub decimals: Option<u32>,
+}
+
+impl ReadStatFormatSpec {
+    /// Split `format`; `None` when it is empty
+    pub fn parse(format: &str) -> Option<Self> {
+        if format.is_empty() {
+            return None;
+        }
+
+        // SAS format names never end in a digit, so trailing digits are the width
+        let (rest, decimals) = match format.rsplit_once('.') {
+            Some((rest, d)) if d.chars().all(|c| c.is_ascii_digit()) => (rest, d.parse().ok()),
+            _ => (format, None),
+        };
+        let name = rest.trim_end_matches(|c: char| c.is_ascii_digit());
+
+        Some(Self {
+            name: String::from(name),
+            width: rest[name.len()..].parse().ok(),
+            decimals,
+        })
+    }
+
+    /// Whether the format displays numbers without decimals, e.g. `8.`, `F8.0`, or `COMMA6.`
+    ///
+    /// `BEST` is not, as it displays whatever decimals fit within its width.
+    pub fn is_integral(&self) -> bool {
+        matches!(
+            self.name.to_uppercase().as_str(),
+            "" | "F" | "COMMA" | "NLNUM" | "Z"
+        ) && self.decimals.unwrap_or(0) == 0
+    }
+
+    /// `v` as SAS displays it, e.g. `1,234.50` for `COMMA10.2` or `12.5%` for `PERCENT8.1`;
+    /// `None` when the format is not one of `w.d`, `F`, `BEST`, `COMMA`, `DOLLAR`, `PERCENT`,
+    /// or `Z`
+    ///
+    /// Besides `BEST`, which fits its digits within the width, widths are not enforced, so values
+    /// are neither padded nor replaced by asterisks when too wide.
+    pub fn render(&self, v: f64) -> Option<String> {
+        if !v.is_finite() {
+            return None;
+        }
+        let decimals = self.decimals.unwrap_or(0) as usize;
+
+        match self.name.to_uppercase().as_str() {
+            "" | "F" => Some(fixed(v, decimals)),
+            "BEST" => Some(best(v, self.width.unwrap_or(12).clamp(1, 32) as usize)),
+            "COMMA" => Some(grouped(v, decimals, "")),
+            "DOLLAR" => Some(grouped(v, decimals, "$")),
+            "PERCENT" => {
+                // negative percentages are shown within parentheses
+                let s = fixed((v * 100.0).abs(), decimals);
+                Some(if v < 0.0 && s.bytes().any(|b| (b'1'..=b'9').contains(&b)) {
+                    format!("({}%)", s)
+                } else {
+                    format!("{}%", s)
+                })
+            }
+            "Z" => {
+                let s = fixed(v, decimals);
+                let width = self.width.map_or(s.len(), |w| w as usize);
+                Some(match s.strip_prefix('-') {
+                    Some(digits) => format!("-{:0>1$}", digits, width.saturating_sub(1)),
+                    None => format!("{:0>1$}", s, width),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+// `v` with `decimals` decimals, rounding halves away from zero as SAS does, and without the sign
+//   of values that round to zero
+fn fixed(v: f64, decimals: usize) -> String {
+    let scale = 10f64.powi(decimals as i32);
+    let rounded = (v * scale).round() / scale;
+    let s = format!("{:.*}", decimals, if rounded.is_finite() { rounded } else { v });
+    match s.strip_prefix('-') {
+        Some(digits) if !digits.bytes().any(|b| (b'1'..=b'9').contains(&b)) => digits.to_string(),
+        _ => s,
+    }
+}
+
+// `v` with `decimals` decimals, `prefix` before the digits, and commas between thousands
+fn grouped(v: f64, decimals: usize, prefix: &str) -> String {
+    let s = fixed(v, decimals);
+    let (sign, digits) = match s.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", s.as_str()),
+    };
+    let (int, frac) = match digits.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (digits, None),
+    };
+
+    let mut out = format!("{}{}", sign, prefix);
+    for (i, c) in int.chars().enumerate() {
+        if i > 0 && (int.len() - i) % 3 == 0 {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    if let Some(frac) = frac {
+        out.push('.');
+        out.push_str(frac);
+    }
+    out
+}
+
+// `v` within `width` characters, with as many decimals as fit, or in scientific notation when
+//   even the integer digits do not fit
+fn best(v: f64, width: usize) -> String {
+    let int = fixed(v, 0);
+    if int.len() <= width {
+        // room for the integer digits and a decimal point
+        let decimals = width.saturating_sub(int.len() + 1);
+        let s = fixed(v, decimals);
+        return trim_decimals(&s).to_string();
+    }
+
+    let mut digits = width;
+    loop {
+        let s = format!("{:.*e}", digits, v);
+        let (mantissa, exponent) = s.split_once('e').unwrap();
+        let s = format!("{}E{}", trim_decimals(mantissa), exponent);
+        if s.len() <= width || digits == 0 {
+            return s;
+        }
+        digits -= 1;
+    }
+}
+
+fn trim_decimals(s: &str) -> &str {
+    if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        s
+    }
+}
+
+/// Why a variable was given its Arrow type
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub enum ReadStatTypeReason {
+    /// Mapping of the SAS storage type
+    DefaultMapping,
+    /// The SAS format marks a numeric as a date, time, or datetime
+    FormatClassMapping,
+    /// The user requested a type (e.g. via --datetime-type)
+    UserOverride,
+    /// Strings stored as an Arrow dictionary
+    DictionaryEncoding,
+    /// Every non-missing value of the numeric is 0 or 1
+    BooleanDetection,
+    /// Values of the numeric look like dates or datetimes (e.g. via --detect-dates=apply)
+    DateDetection,
+    /// Values are replaced by their salted hash (e.g. via --hash-columns)
+    Hashing,
+    /// Values are replaced by their labels (e.g. via --apply-value-labels)
+    ValueLabels,
+    /// The SAS format of the numeric displays no decimals (e.g. via --infer-integers)
+    IntegerInference,
+}
+
+impl fmt::Display for ReadStatTypeReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DefaultMapping => write!(f, "default mapping"),
+            Self::FormatClassMapping => write!(f, "format-class mapping"),
+            Self::UserOverride => write!(f, "user override"),
+            Self::DictionaryEncoding => write!(f, "dictionary encoding"),
+            Self::BooleanDetection => write!(f, "boolean detection"),
+            Self::DateDetection => write!(f, "date detection"),
+            Self::Hashing => write!(f, "hashing"),
+            Self::ValueLabels => write!(f, "value labels"),
+            Self::IntegerInference => write!(f, "integer inference"),
+        }
+    }
+}
+
+/// Why a variable was renamed
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub enum ReadStatRenameReason {
+    /// The user renamed the variable (e.g. via --rename)
+    UserRename,
+    /// The case of every name was transformed (e.g. via --name-transform)
+    NameTransform,
+    /// A new name was given within a column spec (e.g. via --spec)
+    ColumnSpec,
+    /// Another variable within the file has the same name (e.g. via --dedup-columns)
+    Deduplication,
+}
+
+impl fmt::Display for ReadStatRenameReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UserRename => write!(f, "user rename"),
+            Self::NameTransform => write!(f, "name transform"),
+            Self::ColumnSpec => write!(f, "column spec"),
+            Self::Deduplication => write!(f, "deduplication"),
         }
     }
 }
+
+/// Name a variable is given within the output, along with the reason it was renamed
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadStatRename {
+    pub final_name: String,
+    pub reason: ReadStatRenameReason,
+}
+
+/// Label and SAS format of a variable as within the file, for those truncated to a length budget
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReadStatTruncation {
+    pub label: Option<String>,
+    pub format: Option<String>,
+}
+
+/// Arrow type chosen for a variable, along with the reason it was chosen
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadStatTypeDecision {
+    pub data_type: DataType,
+    pub reason: ReadStatTypeReason,
+}
+
+impl ReadStatTypeDecision {
+    pub fn new(
+        vm: &ReadStatVarMetadata,
+        datetime_type: Option<DateTimeType>,
+        time_handling: TimeHandling,
+    ) -> Self {
+        let (data_type, reason) = match &vm.var_type {
+            ReadStatVarType::String | ReadStatVarType::StringRef | ReadStatVarType::Unknown => {
+                (DataType::Utf8, ReadStatTypeReason::DefaultMapping)
+            }
+            ReadStatVarType::Int8 | ReadStatVarType::Int16 => {
+                (DataType::Int16, ReadStatTypeReason::DefaultMapping)
+            }
+            ReadStatVarType::Int32 => (DataType::Int32, ReadStatTypeReason::DefaultMapping),
+            ReadStatVarType::Float => (DataType::Float32, ReadStatTypeReason::DefaultMapping),
+            ReadStatVarType::Double => match &vm.var_format_class {
+                Some(ReadStatVarFormatClass::Date) => {
+                    (DataType::Date32, ReadStatTypeReason::FormatClassMapping)
+                }
+                // a requested datetime type applies uniformly to every datetime
+                Some(
+                    ReadStatVarFormatClass::DateTime
+                    | ReadStatVarFormatClass::DateTimeWithMilliseconds
+                    | ReadStatVarFormatClass::DateTimeWithMicroseconds
+                    | ReadStatVarFormatClass::DateTimeWithNanoseconds,
+                ) if datetime_type.is_some() => {
+                    let dt = match datetime_type.unwrap() {
+                        DateTimeType::TimestampS => DataType::Timestamp(TimeUnit::Second, None),
+                        DateTimeType::TimestampMs => {
+                            DataType::Timestamp(TimeUnit::Millisecond, None)
+                        }
+                        DateTimeType::TimestampUs => {
+                            DataType::Timestamp(TimeUnit::Microsecond, None)
+                        }
+                        DateTimeType::TimestampNs => {
+                            DataType::Timestamp(TimeUnit::Nanosecond, None)
+                        }
+                        DateTimeType::Date64 => DataType::Date64,
+                    };
+                    (dt, ReadStatTypeReason::UserOverride)
+                }
+                // SAS datetimes hold fractional seconds whatever their format displays, so
+                //   milliseconds are kept
+                Some(ReadStatVarFormatClass::DateTime) => (
+                    DataType::Timestamp(TimeUnit::Millisecond, None),
+                    ReadStatTypeReason::FormatClassMapping,
+                ),
+                Some(ReadStatVarFormatClass::DateTimeWithMilliseconds) => (
+                    DataType::Timestamp(TimeUnit::Millisecond, None),
+                    ReadStatTypeReason::FormatClassMapping,
+                ),
+                Some(ReadStatVarFormatClass::DateTimeWithMicroseconds) => (
+                    DataType::Timestamp(TimeUnit::Microsecond, None),
+                    ReadStatTypeReason::FormatClassMapping,
+                ),
+                Some(ReadStatVarFormatClass::DateTimeWithNanoseconds) => (
+                    DataType::Timestamp(TimeUnit::Nanosecond, None),
+                    ReadStatTypeReason::FormatClassMapping,
+                ),
+                // durations that may exceed a day or be negative, which no Arrow time holds
+                Some(
+                    ReadStatVarFormatClass::Time | ReadStatVarFormatClass::TimeWithMicroseconds,
+                ) if time_handling == TimeHandling::DurationSeconds => {
+                    (DataType::Int64, ReadStatTypeReason::UserOverride)
+                }
+                Some(ReadStatVarFormatClass::Time) => (
+                    DataType::Time32(TimeUnit::Second),
+                    ReadStatTypeReason::FormatClassMapping,
+                ),
+                Some(ReadStatVarFormatClass::TimeWithMicroseconds) => (
+                    DataType::Time64(TimeUnit::Microsecond),
+                    ReadStatTypeReason::FormatClassMapping,
+                ),
+                None => (DataType::Float64, ReadStatTypeReason::DefaultMapping),
+            },
+        };
+
+        Self { data_type, reason }
+    }
+}
@@ -0,0 +1,28 @@
+use arrow2::datatypes::{DataType, Field};
+use std::collections::BTreeMap;
+
+use crate::{rs_metadata::ReadStatVarMetadata, rs_var::ReadStatVarTypeClass};
+
+/// Suffix of the name of the column holding the tags of the special missing values (`.A`
+/// through `.Z` and `._`) of a numeric column
+pub const MISSING_TAG_SUFFIX: &str = "__missing_tag";
+
+/// Name of the column holding the tags of the special missing values of the column `name`
+pub fn missing_tag_name(name: &str) -> String {
+    format!("{}{}", name, MISSING_TAG_SUFFIX)
+}
+
+// Variables given a companion column of the tags of their special missing values, along with
+//   the field of that column, for `fields` the fields of `vars` in order
+// Every numeric variable is given one, as whether a column holds special missing values is only
+//   known once it has been read whereas the schema is fixed ahead of writing the first batch
+pub(crate) fn missing_tag_fields<'a>(
+    vars: &BTreeMap<i32, ReadStatVarMetadata>,
+    fields: impl IntoIterator<Item = &'a Field>,
+) -> Vec<(i32, Field)> {
+    vars.iter()
+        .zip(fields)
+        .filter(|((_, vm), _)| matches!(vm.var_type_class, ReadStatVarTypeClass::Numeric))
+        .map(|((k, _), f)| (*k, Field::new(missing_tag_name(&f.name), DataType::Utf8, true)))
+        .collect()
+}
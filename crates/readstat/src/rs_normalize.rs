@@ -0,0 +1,111 @@
+use colored::Colorize;
+use regex::Regex;
+use std::{borrow::Cow, collections::BTreeSet, error::Error};
+
+use crate::rs_metadata::ReadStatMetadata;
+use crate::rs_var::ReadStatVarTypeClass;
+use crate::StringCase;
+
+/// How the values of string columns are normalized as they are read
+///
+/// Columns are named as within the file, before any rename.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReadStatStringNormalize {
+    pub case: Option<StringCase>,
+    /// Replace every run of whitespace between words with a single space
+    pub collapse_whitespace: bool,
+    /// Columns to normalize; every string column when empty
+    pub columns: Vec<String>,
+    /// Regular expression; columns whose names match are never normalized
+    pub exclude: Option<String>,
+}
+
+impl ReadStatStringNormalize {
+    pub fn is_active(&self) -> bool {
+        self.case.is_some() || self.collapse_whitespace
+    }
+
+    /// The normalized value, or `None` when normalizing leaves `s` unchanged
+    pub fn apply(&self, s: &str) -> Option<String> {
+        let collapsed = if self.collapse_whitespace {
+            collapse_whitespace(s)
+        } else {
+            Cow::Borrowed(s)
+        };
+
+        let normalized = match self.case {
+            Some(StringCase::Upper) => collapsed.to_uppercase(),
+            Some(StringCase::Lower) => collapsed.to_lowercase(),
+            None => collapsed.into_owned(),
+        };
+
+        if normalized == s {
+            None
+        } else {
+            Some(normalized)
+        }
+    }
+
+    /// Indices of the variables to normalize
+    ///
+    /// Naming a variable that does not exist or is not a string is an error, as is an invalid
+    /// `exclude` pattern.
+    pub fn resolve(
+        &self,
+        md: &ReadStatMetadata,
+    ) -> Result<BTreeSet<i32>, Box<dyn Error + Send + Sync>> {
+        if !self.is_active() {
+            return Ok(BTreeSet::new());
+        }
+
+        let exclude = match &self.exclude {
+            Some(p) => Some(Regex::new(p).map_err(|e| {
+                format!("The exclude pattern {} is invalid: {}", p.bright_yellow(), e)
+            })?),
+            None => None,
+        };
+
+        for name in &self.columns {
+            let vm = md
+                .vars
+                .values()
+                .find(|vm| &vm.var_name == name)
+                .ok_or_else(|| format!("The variable {} does not exist", name.bright_yellow()))?;
+
+            if !matches!(vm.var_type_class, ReadStatVarTypeClass::String) {
+                return Err(From::from(format!(
+                    "The variable {} is not a string and so cannot be normalized",
+                    name.bright_yellow()
+                )));
+            }
+        }
+
+        Ok(md
+            .vars
+            .iter()
+            .filter(|(_, vm)| matches!(vm.var_type_class, ReadStatVarTypeClass::String))
+            .filter(|(_, vm)| self.columns.is_empty() || self.columns.contains(&vm.var_name))
+            .filter(|(_, vm)| !exclude.as_ref().is_some_and(|re| re.is_match(&vm.var_name)))
+            .map(|(k, _)| *k)
+            .collect())
+    }
+}
+
+// Leading and trailing whitespace is kept as is
+fn collapse_whitespace(s: &str) -> Cow<'_, str> {
+    let inner = s.trim();
+    let has_run = inner
+        .chars()
+        .zip(inner.chars().skip(1))
+        .any(|(a, b)| a.is_whitespace() && (b.is_whitespace() || a != ' '));
+
+    if !has_run {
+        return Cow::Borrowed(s);
+    }
+
+    let start = s.len() - s.trim_start().len();
+    let end = start + inner.len();
+    let words: Vec<&str> = inner.split_whitespace().collect();
+
+    Cow::Owned(format!("{}{}{}", &s[..start], words.join(" "), &s[end..]))
+}
@@ -2,13 +2,19 @@ use log::debug;
 use num_traits::FromPrimitive;
 use std::{
     error::Error,
+    ffi::CString,
     os::raw::{c_char, c_long, c_void},
 };
 
 use crate::err::ReadStatError;
+use crate::rs_io::{self, ReadStatInput, ReadStatIo};
 
 pub struct ReadStatParser {
     parser: *mut readstat_sys::readstat_parser_t,
+    // ReadStat holds on to the pointer so the string must live as long as the parser
+    encoding: Option<CString>,
+    // likewise the I/O context of an input read other than from a file
+    io: Option<Box<ReadStatIo>>,
 }
 
 impl ReadStatParser {
@@ -16,7 +22,117 @@ impl ReadStatParser {
         let parser: *mut readstat_sys::readstat_parser_t =
             unsafe { readstat_sys::readstat_parser_init() };
 
-        Self { parser }
+        Self {
+            parser,
+            encoding: None,
+            io: None,
+        }
+    }
+
+    pub fn set_file_character_encoding(
+        mut self,
+        encoding: Option<&str>,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        match encoding {
+            Some(e) => {
+                let encoding = CString::new(e)?;
+
+                let set_encoding_error = unsafe {
+                    readstat_sys::readstat_set_file_character_encoding(
+                        self.parser,
+                        encoding.as_ptr(),
+                    )
+                };
+
+                debug!(
+                    "After setting file character encoding, error ==> {}",
+                    &set_encoding_error
+                );
+
+                #[allow(clippy::useless_conversion)]
+                match FromPrimitive::from_i32(set_encoding_error.try_into().unwrap()) {
+                    Some(ReadStatError::READSTAT_OK) => {
+                        self.encoding = Some(encoding);
+                        Ok(self)
+                    }
+                    Some(e) => Err(From::from(format!(
+                        "Unable to set file character encoding: {:#?}",
+                        e
+                    ))),
+                    None => Err(From::from(
+                        "Error when attempting to set file character encoding: Unknown return value",
+                    )),
+                }
+            }
+            None => Ok(self),
+        }
+    }
+
+    /// Read `input`, if any, in place of the file at the path parsed
+    ///
+    /// The I/O handlers are given a context of their own, leaving the context of every other
+    /// handler to be the one passed to parse.
+    pub fn set_input(
+        mut self,
+        input: Option<&ReadStatInput>,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        match input {
+            Some(i) => {
+                let mut io = Box::new(ReadStatIo::new(i));
+                let io_ctx = io.as_mut() as *mut ReadStatIo as *mut c_void;
+
+                let set_io_errors = unsafe {
+                    [
+                        readstat_sys::readstat_set_open_handler(
+                            self.parser,
+                            Some(rs_io::handle_open),
+                        ),
+                        readstat_sys::readstat_set_close_handler(
+                            self.parser,
+                            Some(rs_io::handle_close),
+                        ),
+                        readstat_sys::readstat_set_seek_handler(
+                            self.parser,
+                            Some(rs_io::handle_seek),
+                        ),
+                        readstat_sys::readstat_set_read_handler(
+                            self.parser,
+                            Some(rs_io::handle_read),
+                        ),
+                        readstat_sys::readstat_set_update_handler(
+                            self.parser,
+                            Some(rs_io::handle_update),
+                        ),
+                        readstat_sys::readstat_set_io_ctx(self.parser, io_ctx),
+                    ]
+                };
+                // ReadStat holds on to the context from here on
+                self.io = Some(io);
+
+                debug!("After setting I/O handlers, errors ==> {:?}", &set_io_errors);
+
+                for set_io_error in set_io_errors {
+                    #[allow(clippy::useless_conversion)]
+                    match FromPrimitive::from_i32(set_io_error.try_into().unwrap()) {
+                        Some(ReadStatError::READSTAT_OK) => (),
+                        Some(e) => {
+                            return Err(From::from(format!(
+                                "Unable to set I/O handlers: {:#?}",
+                                e
+                            )))
+                        }
+                        None => {
+                            return Err(From::from(
+                                "Error when attempting to set I/O handlers: Unknown return value",
+                            ))
+                        }
+                    }
+                }
+
+                Ok(self)
+            }
+            None => Ok(self),
+        }
     }
 
     pub fn set_metadata_handler(
@@ -44,6 +160,31 @@ impl ReadStatParser {
         }
     }
 
+    pub fn set_progress_handler(
+        self,
+        progress_handler: readstat_sys::readstat_progress_handler,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let set_progress_handler_error =
+            unsafe { readstat_sys::readstat_set_progress_handler(self.parser, progress_handler) };
+
+        debug!(
+            "After setting progress handler, error ==> {}",
+            &set_progress_handler_error
+        );
+
+        #[allow(clippy::useless_conversion)]
+        match FromPrimitive::from_i32(set_progress_handler_error.try_into().unwrap()) {
+            Some(ReadStatError::READSTAT_OK) => Ok(self),
+            Some(e) => Err(From::from(format!(
+                "Unable to set progress handler: {:#?}",
+                e
+            ))),
+            None => Err(From::from(
+                "Error when attempting to set progress handler: Unknown return value",
+            )),
+        }
+    }
+
     pub fn set_row_limit(
         self,
         row_limit: Option<u32>,
@@ -145,6 +286,32 @@ impl ReadStatParser {
         }
     }
 
+    pub fn set_value_label_handler(
+        self,
+        value_label_handler: readstat_sys::readstat_value_label_handler,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let set_value_label_handler_error = unsafe {
+            readstat_sys::readstat_set_value_label_handler(self.parser, value_label_handler)
+        };
+
+        debug!(
+            "After setting value label handler, error ==> {}",
+            &set_value_label_handler_error
+        );
+
+        #[allow(clippy::useless_conversion)]
+        match FromPrimitive::from_i32(set_value_label_handler_error.try_into().unwrap()) {
+            Some(ReadStatError::READSTAT_OK) => Ok(self),
+            Some(e) => Err(From::from(format!(
+                "Unable to set value label handler: {:#?}",
+                e
+            ))),
+            None => Err(From::from(
+                "Error when attempting to set value label handler: Unknown return value",
+            )),
+        }
+    }
+
     pub fn parse_sas7bdat(
         &mut self,
         path: *const c_char,
@@ -160,6 +327,81 @@ impl ReadStatParser {
 
         parse_sas7bdat_error
     }
+
+    pub fn parse_xport(
+        &mut self,
+        path: *const c_char,
+        user_ctx: *mut c_void,
+    ) -> readstat_sys::readstat_error_t {
+        let parse_xport_error: readstat_sys::readstat_error_t =
+            unsafe { readstat_sys::readstat_parse_xport(self.parser, path, user_ctx) };
+
+        debug!(
+            "After calling parse xport, error ==> {}",
+            &parse_xport_error
+        );
+
+        parse_xport_error
+    }
+
+    pub fn parse_sas7bcat(
+        &mut self,
+        path: *const c_char,
+        user_ctx: *mut c_void,
+    ) -> readstat_sys::readstat_error_t {
+        let parse_sas7bcat_error: readstat_sys::readstat_error_t =
+            unsafe { readstat_sys::readstat_parse_sas7bcat(self.parser, path, user_ctx) };
+
+        debug!(
+            "After calling parse sas7bcat, error ==> {}",
+            &parse_sas7bcat_error
+        );
+
+        parse_sas7bcat_error
+    }
+
+    pub fn parse_sav(
+        &mut self,
+        path: *const c_char,
+        user_ctx: *mut c_void,
+    ) -> readstat_sys::readstat_error_t {
+        let parse_sav_error: readstat_sys::readstat_error_t =
+            unsafe { readstat_sys::readstat_parse_sav(self.parser, path, user_ctx) };
+
+        debug!("After calling parse sav, error ==> {}", &parse_sav_error);
+
+        parse_sav_error
+    }
+
+    pub fn parse_dta(
+        &mut self,
+        path: *const c_char,
+        user_ctx: *mut c_void,
+    ) -> readstat_sys::readstat_error_t {
+        let parse_dta_error: readstat_sys::readstat_error_t =
+            unsafe { readstat_sys::readstat_parse_dta(self.parser, path, user_ctx) };
+
+        debug!("After calling parse dta, error ==> {}", &parse_dta_error);
+
+        parse_dta_error
+    }
+
+    /// Parse with the parser the extension of the file calls for, i.e. SAS transport (`xpt`),
+    /// SPSS (`sav`), and Stata (`dta`) files with their own parsers and all others with the
+    /// sas7bdat parser
+    pub fn parse(
+        &mut self,
+        extension: &str,
+        path: *const c_char,
+        user_ctx: *mut c_void,
+    ) -> readstat_sys::readstat_error_t {
+        match extension {
+            "xpt" => self.parse_xport(path, user_ctx),
+            "sav" => self.parse_sav(path, user_ctx),
+            "dta" => self.parse_dta(path, user_ctx),
+            _ => self.parse_sas7bdat(path, user_ctx),
+        }
+    }
 }
 
 impl Drop for ReadStatParser {
@@ -4,12 +4,29 @@ use std::{
     error::Error,
     ffi::CString,
     path::{Path, PathBuf},
+    sync::{Arc, Mutex},
 };
 
+use crate::rs_io::{
+    decompress, without_compression_extension, InputCompression, ReadStatInput,
+    DEFAULT_MAX_DECOMPRESSED_BYTES,
+};
 use crate::OutFormat;
 use crate::ParquetCompression;
 
-const IN_EXTENSIONS: &[&str] = &["sas7bdat", "sas7bcat"];
+pub(crate) const IN_EXTENSIONS: &[&str] = &["sas7bdat", "sas7bcat", "xpt", "sav", "dta"];
+
+/// Output path standing for standard out
+pub const STDOUT_PATH: &str = "-";
+
+/// Whether `path` is an existing file that is neither a regular file nor a directory, e.g. a named
+/// pipe or a device such as `/dev/stdout`
+///
+/// Output is written straight to such a file, as it can neither be replaced by renaming nor
+/// have a temporary file created beside it, and it is never refused as already existing.
+pub fn is_special_file(path: &Path) -> bool {
+    std::fs::metadata(path).is_ok_and(|m| !m.is_file() && !m.is_dir())
+}
 
 #[derive(Debug, Clone)]
 pub struct ReadStatPath {
@@ -22,6 +39,17 @@ pub struct ReadStatPath {
     pub no_write: bool,
     pub compression: Option<ParquetCompression>,
     pub compression_level: Option<u32>,
+    pub warnings: Vec<String>,
+    /// Input read in place of the file at `path`, from memory or from any other source
+    pub input: Option<ReadStatInput>,
+    /// Compression of the file at `path`, which is decompressed into memory once it is first
+    /// read; see [`InputCompression`]
+    pub input_compression: Option<InputCompression>,
+    /// Most bytes a compressed input is decompressed into; see
+    /// [`set_max_decompressed_bytes`](Self::set_max_decompressed_bytes)
+    pub max_decompressed_bytes: u64,
+    // the decompressed input, shared by every clone so that it is only decompressed once
+    decompressed: Arc<Mutex<Option<ReadStatInput>>>,
 }
 
 impl ReadStatPath {
@@ -34,28 +62,34 @@ impl ReadStatPath {
         compression: Option<ParquetCompression>,
         compression_level: Option<u32>,
     ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        // warnings are collected rather than printed so callers decide how to surface them
+        let mut warnings: Vec<String> = Vec::new();
+
         let p = Self::validate_path(path)?;
-        let ext = Self::validate_in_extension(&p)?;
+        // a compressed file is named for the file it holds, e.g. cars.sas7bdat.gz
+        let input_compression = InputCompression::detect(&p);
+        let ext = Self::validate_in_extension(&without_compression_extension(&p))?;
         let csp = Self::path_to_cstring(&p)?;
         let f = Self::validate_format(format)?;
-        let op: Option<PathBuf> = Self::validate_out_path(out_path, overwrite)?;
+        let op: Option<PathBuf> = Self::validate_out_path(out_path, overwrite, &mut warnings)?;
         let op = match op {
             None => op,
+            Some(op) if op.as_os_str() == STDOUT_PATH || is_special_file(&op) => Some(op),
             Some(op) => Self::validate_out_extension(&op, f)?,
         };
         let cl: Option<u32> = match compression {
             None => match compression_level {
                 None => None,
                 Some(_) => {
-                    println!(
+                    warnings.push(format!(
                         "Ignoring value of {} as {} was not set",
                         String::from("--compression-level").bright_cyan(),
                         String::from("--compression").bright_cyan()
-                    );
+                    ));
                     None
                 }
             },
-            Some(pc) => Self::validate_compression_level(pc, compression_level)?,
+            Some(pc) => Self::validate_compression_level(pc, compression_level, &mut warnings)?,
         };
 
         Ok(Self {
@@ -68,9 +102,124 @@ impl ReadStatPath {
             no_write,
             compression,
             compression_level: cl,
+            warnings,
+            input: None,
+            input_compression,
+            max_decompressed_bytes: DEFAULT_MAX_DECOMPRESSED_BYTES,
+            decompressed: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// Path standing for `bytes`, the bytes of a file held in memory
+    ///
+    /// Nothing is read from the filesystem. `name` is not looked for; its extension picks the
+    /// parser, as for a file, and it stands for the input within messages. No output is set.
+    pub fn from_bytes(
+        name: PathBuf,
+        bytes: impl Into<std::sync::Arc<[u8]>>,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Self::from_input(name, ReadStatInput::from_bytes(bytes))
+    }
+
+    /// Path standing for the bytes of a file read from `reader`, as with
+    /// [`from_bytes`](Self::from_bytes)
+    pub fn from_reader<R: std::io::Read + std::io::Seek + Send + 'static>(
+        name: PathBuf,
+        reader: R,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Self::from_input(name, ReadStatInput::from_reader(reader))
+    }
+
+    /// Whether the output may replace an existing file
+    ///
+    /// Without `overwrite`, an output file found to exist, whether by [`new`](Self::new) or once
+    /// writing is complete, is an error and nothing is written over it.
+    pub fn set_overwrite(self, overwrite: bool) -> Self {
+        Self { overwrite, ..self }
+    }
+
+    /// Most bytes a compressed input is decompressed into, as it is held in memory while read;
+    /// defaults to [`DEFAULT_MAX_DECOMPRESSED_BYTES`]
+    ///
+    /// A file decompressing to more is an error once read, rather than exhausting memory.
+    pub fn set_max_decompressed_bytes(self, max_decompressed_bytes: u64) -> Self {
+        Self {
+            max_decompressed_bytes,
+            decompressed: Arc::new(Mutex::new(None)),
+            ..self
+        }
+    }
+
+    /// Input ReadStat reads in place of the file at `path`; `None` to read the file itself
+    ///
+    /// Either `input`, or for a compressed file, its bytes decompressed into memory upon the
+    /// first call and shared by every clone thereafter.
+    pub fn source(&self) -> Result<Option<ReadStatInput>, Box<dyn Error + Send + Sync>> {
+        if let Some(i) = &self.input {
+            return Ok(Some(i.clone()));
+        }
+        let Some(compression) = self.input_compression else {
+            return Ok(None);
+        };
+
+        let mut decompressed = self
+            .decompressed
+            .lock()
+            .map_err(|_| "Decompressed input lock poisoned")?;
+        if decompressed.is_none() {
+            *decompressed = Some(decompress(&self.path, compression, self.max_decompressed_bytes)?);
+        }
+        Ok(decompressed.clone())
+    }
+
+    fn from_input(
+        name: PathBuf,
+        input: ReadStatInput,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let ext = Self::validate_in_extension(&name)?;
+        let csp = Self::path_to_cstring(&name)?;
+
+        Ok(Self {
+            path: name,
+            extension: ext,
+            cstring_path: csp,
+            out_path: None,
+            format: OutFormat::csv,
+            overwrite: false,
+            no_write: false,
+            compression: None,
+            compression_level: None,
+            warnings: Vec::new(),
+            input: Some(input),
+            input_compression: None,
+            max_decompressed_bytes: DEFAULT_MAX_DECOMPRESSED_BYTES,
+            decompressed: Arc::new(Mutex::new(None)),
         })
     }
 
+    /// Whether data is written to standard out rather than to a file
+    pub fn writes_to_stdout(&self) -> bool {
+        self.out_path
+            .as_ref()
+            .is_some_and(|p| p.as_os_str() == STDOUT_PATH)
+    }
+
+    /// Whether data is written to a named pipe or device, e.g. `/dev/stdout`, rather than to a
+    /// regular file; nothing is written beside it, such as a sidecar
+    pub fn writes_to_special_file(&self) -> bool {
+        self.out_path
+            .as_ref()
+            .is_some_and(|p| p.as_os_str() != STDOUT_PATH && is_special_file(p))
+    }
+
+    /// Output path beside which sidecars are written; `None` when writing to standard out, a
+    /// named pipe, or a device
+    pub fn sidecar_base(&self) -> Option<&PathBuf> {
+        self.out_path
+            .as_ref()
+            .filter(|_| !self.writes_to_stdout() && !self.writes_to_special_file())
+    }
+
     #[cfg(unix)]
     pub fn path_to_cstring(path: &PathBuf) -> Result<CString, Box<dyn Error + Send + Sync>> {
         use std::os::unix::ffi::OsStrExt;
@@ -106,7 +255,7 @@ impl ReadStatPath {
                     if IN_EXTENSIONS.iter().any(|&ext| ext == e) {
                         Ok(e)
                     } else {
-                        Err(From::from(format!("Expecting extension {} or {}.\nFile {} does not have expected extension!", String::from("sas7bdat").bright_green(), String::from("sas7bcat").bright_blue(), path.to_string_lossy().bright_yellow())))
+                        Err(From::from(format!("Expecting extension {}, {}, {}, {}, or {}.\nFile {} does not have expected extension!", String::from("sas7bdat").bright_green(), String::from("sas7bcat").bright_blue(), String::from("xpt").bright_magenta(), String::from("sav").bright_cyan(), String::from("dta").bright_cyan(), path.to_string_lossy().bright_yellow())))
                     }
             )
     }
@@ -125,10 +274,13 @@ impl ReadStatPath {
                     format.to_string().bright_green()
                 ))),
                 |e| match format {
-                    OutFormat::csv
+                    OutFormat::arrow
+                    | OutFormat::csv
                     | OutFormat::ndjson
                     | OutFormat::feather
-                    | OutFormat::parquet => {
+                    | OutFormat::parquet
+                    | OutFormat::sav
+                    | OutFormat::dta => {
                         if e == format.to_string() {
                             Ok(Some(path.to_owned()))
                         } else {
@@ -147,9 +299,11 @@ impl ReadStatPath {
     fn validate_out_path(
         path: Option<PathBuf>,
         overwrite: bool,
+        warnings: &mut Vec<String>,
     ) -> Result<Option<PathBuf>, Box<dyn Error + Send + Sync>> {
         match path {
             None => Ok(None),
+            Some(p) if p.as_os_str() == STDOUT_PATH || is_special_file(&p) => Ok(Some(p)),
             Some(p) => {
                 let abs_path = PathAbs::new(p)?;
 
@@ -160,7 +314,7 @@ impl ReadStatPath {
                             // Check to see if file already exists
                             if abs_path.exists() {
                                 if overwrite {
-                                    println!("The file {} will be {}!", abs_path.to_string_lossy().bright_yellow(), String::from("overwritten").truecolor(255, 105, 180));
+                                    warnings.push(format!("The file {} will be {}!", abs_path.to_string_lossy().bright_yellow(), String::from("overwritten").truecolor(255, 105, 180)));
                                     Ok(Some(abs_path.as_path().to_path_buf()))
                                 } else {
                                     Err(From::from(format!("The output file - {} - already exists!  To overwrite the file, utilize the {} parameter", abs_path.to_string_lossy().bright_yellow(), String::from("--overwrite").bright_cyan())))
@@ -193,26 +347,27 @@ impl ReadStatPath {
     fn validate_compression_level(
         compression: ParquetCompression,
         compression_level: Option<u32>,
+        warnings: &mut Vec<String>,
     ) -> Result<Option<u32>, Box<dyn Error + Send + Sync>> {
         match compression {
             ParquetCompression::Uncompressed => match compression_level {
                 None => Ok(compression_level),
                 Some(_) => {
-                    println!("Compression level is not required for compression={}, ignoring value of {}", String::from("uncompressed").bright_magenta(), String::from("--compression-level").bright_cyan());
+                    warnings.push(format!("Compression level is not required for compression={}, ignoring value of {}", String::from("uncompressed").bright_magenta(), String::from("--compression-level").bright_cyan()));
                     Ok(None)
                 }
             },
             ParquetCompression::Snappy => match compression_level {
                 None => Ok(compression_level),
                 Some(_) => {
-                    println!("Compression level is not required for compression={}, ignoring value of {}", String::from("snappy").bright_magenta(), String::from("--compression-level").bright_cyan());
+                    warnings.push(format!("Compression level is not required for compression={}, ignoring value of {}", String::from("snappy").bright_magenta(), String::from("--compression-level").bright_cyan()));
                     Ok(None)
                 }
             },
             ParquetCompression::Lz4Raw => match compression_level {
                 None => Ok(compression_level),
                 Some(_) => {
-                    println!("Compression level is not required for compression={}, ignoring value of {}", String::from("lz4-raw").bright_magenta(), String::from("--compression-level").bright_cyan());
+                    warnings.push(format!("Compression level is not required for compression={}, ignoring value of {}", String::from("lz4-raw").bright_magenta(), String::from("--compression-level").bright_cyan()));
                     Ok(None)
                 }
             },
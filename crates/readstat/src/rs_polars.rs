@@ -0,0 +1,130 @@
+use arrow2::{
+    array::{new_empty_array, Array, BooleanArray, PrimitiveArray, Utf8Array},
+    chunk::Chunk,
+    compute::cast::{cast, CastOptions},
+    datatypes::{DataType as ArrowDataType, Schema, TimeUnit as ArrowTimeUnit},
+    types::NativeType,
+};
+use colored::Colorize;
+use polars::prelude::{DataFrame, DataType, NamedFrom, Series, TimeUnit};
+use std::error::Error;
+
+use crate::rs_data::ReadStatData;
+use crate::rs_schema::arrow_type_name;
+
+impl ReadStatData {
+    /// The rows read as a Polars `DataFrame`, with a column for each field of
+    /// [`ReadStatData::schema`]; see [`chunk_to_polars`]
+    ///
+    /// Before any rows are read, the columns are empty.
+    pub fn to_polars(&self) -> Result<DataFrame, Box<dyn Error + Send + Sync>> {
+        match &self.chunk {
+            Some(c) => chunk_to_polars(&self.schema, c),
+            None => {
+                let arrays = self
+                    .schema
+                    .fields
+                    .iter()
+                    .map(|f| new_empty_array(f.data_type().clone()))
+                    .collect();
+                chunk_to_polars(&self.schema, &Chunk::new(arrays))
+            }
+        }
+    }
+}
+
+/// `chunk`, with its columns described by `schema`, as a Polars `DataFrame`, e.g. each batch of
+/// [`read_batches`](crate::read_batches)
+///
+/// Strings, booleans, integers, and floats keep their types, while `Date32` becomes `Date`,
+/// `Timestamp` becomes `Datetime` (of milliseconds for timestamps of seconds), and `Time32` and
+/// `Time64` become `Time`.  Dictionaries of strings become strings; missing values are null.
+pub fn chunk_to_polars(
+    schema: &Schema,
+    chunk: &Chunk<Box<dyn Array>>,
+) -> Result<DataFrame, Box<dyn Error + Send + Sync>> {
+    let columns = schema
+        .fields
+        .iter()
+        .zip(chunk.arrays())
+        .map(|(f, a)| to_series(&f.name, a.as_ref()))
+        .collect::<Result<Vec<Series>, _>>()?;
+
+    Ok(DataFrame::new(columns)?)
+}
+
+fn to_series(name: &str, a: &dyn Array) -> Result<Series, Box<dyn Error + Send + Sync>> {
+    let series = match a.data_type() {
+        ArrowDataType::Boolean => {
+            let a = a.as_any().downcast_ref::<BooleanArray>().unwrap();
+            Series::new(name.into(), a.iter().collect::<Vec<Option<bool>>>())
+        }
+        ArrowDataType::Int8 => Series::new(name.into(), values::<i8>(a)),
+        ArrowDataType::Int16 => Series::new(name.into(), values::<i16>(a)),
+        ArrowDataType::Int32 => Series::new(name.into(), values::<i32>(a)),
+        ArrowDataType::Int64 => Series::new(name.into(), values::<i64>(a)),
+        ArrowDataType::Float32 => Series::new(name.into(), values::<f32>(a)),
+        ArrowDataType::Float64 => Series::new(name.into(), values::<f64>(a)),
+        ArrowDataType::Utf8 => {
+            let a = a.as_any().downcast_ref::<Utf8Array<i32>>().unwrap();
+            Series::new(name.into(), a.iter().collect::<Vec<Option<&str>>>())
+        }
+        ArrowDataType::LargeUtf8 => {
+            let a = a.as_any().downcast_ref::<Utf8Array<i64>>().unwrap();
+            Series::new(name.into(), a.iter().collect::<Vec<Option<&str>>>())
+        }
+        ArrowDataType::Dictionary(..) => {
+            let strings = cast(a, &ArrowDataType::Utf8, CastOptions::default())?;
+            to_series(name, strings.as_ref())?
+        }
+        // days since 1970-01-01 in both
+        ArrowDataType::Date32 => Series::new(name.into(), values::<i32>(a)).cast(&DataType::Date)?,
+        ArrowDataType::Timestamp(unit, _) => {
+            // polars has no timestamps of seconds
+            let (scale, unit) = match unit {
+                ArrowTimeUnit::Second => (1_000, TimeUnit::Milliseconds),
+                ArrowTimeUnit::Millisecond => (1, TimeUnit::Milliseconds),
+                ArrowTimeUnit::Microsecond => (1, TimeUnit::Microseconds),
+                ArrowTimeUnit::Nanosecond => (1, TimeUnit::Nanoseconds),
+            };
+            Series::new(name.into(), scaled(values::<i64>(a), scale))
+                .cast(&DataType::Datetime(unit, None))?
+        }
+        // times of polars are nanoseconds since midnight
+        ArrowDataType::Time32(unit) => {
+            let v = values::<i32>(a).into_iter().map(|v| v.map(i64::from)).collect();
+            Series::new(name.into(), scaled(v, nanoseconds(unit))).cast(&DataType::Time)?
+        }
+        ArrowDataType::Time64(unit) => {
+            Series::new(name.into(), scaled(values::<i64>(a), nanoseconds(unit)))
+                .cast(&DataType::Time)?
+        }
+        dt => {
+            return Err(From::from(format!(
+                "The column {} is of the Arrow type {}, which cannot be converted to a Polars type",
+                name.bright_yellow(),
+                arrow_type_name(dt)
+            )))
+        }
+    };
+
+    Ok(series)
+}
+
+fn values<T: NativeType>(a: &dyn Array) -> Vec<Option<T>> {
+    let a = a.as_any().downcast_ref::<PrimitiveArray<T>>().unwrap();
+    a.iter().map(|v| v.copied()).collect()
+}
+
+fn scaled(values: Vec<Option<i64>>, scale: i64) -> Vec<Option<i64>> {
+    values.into_iter().map(|v| v.map(|v| v * scale)).collect()
+}
+
+fn nanoseconds(unit: &ArrowTimeUnit) -> i64 {
+    match unit {
+        ArrowTimeUnit::Second => 1_000_000_000,
+        ArrowTimeUnit::Millisecond => 1_000_000,
+        ArrowTimeUnit::Microsecond => 1_000,
+        ArrowTimeUnit::Nanosecond => 1,
+    }
+}
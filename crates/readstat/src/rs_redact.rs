@@ -0,0 +1,185 @@
+use arrow2::datatypes::DataType;
+use colored::Colorize;
+use std::{collections::BTreeMap, error::Error, fmt, str::FromStr};
+
+use crate::common::sha256_hex;
+use crate::rs_metadata::{ReadStatMetadata, ReadStatTypeDecision, ReadStatTypeReason};
+use crate::rs_var::ReadStatVar;
+
+/// Hash applied to the values of a column
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReadStatHashAlgorithm {
+    Sha256,
+}
+
+impl fmt::Display for ReadStatHashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sha256 => write!(f, "sha256"),
+        }
+    }
+}
+
+/// Column whose values are replaced by a salted hash, given as `NAME:sha256:SALT`
+///
+/// Hashes depend only upon the salt and the value, so the same value hashed with the same salt
+/// is identical across files and hashed columns may still be joined.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadStatHashColumn {
+    pub name: String,
+    pub algorithm: ReadStatHashAlgorithm,
+    pub salt: String,
+}
+
+impl ReadStatHashColumn {
+    /// Salted hash of `value` as lowercase hex
+    pub fn hash(&self, value: &str) -> String {
+        match self.algorithm {
+            ReadStatHashAlgorithm::Sha256 => {
+                sha256_hex(format!("{}{}", self.salt, value).as_bytes())
+            }
+        }
+    }
+}
+
+impl FromStr for ReadStatHashColumn {
+    type Err = String;
+
+    // the salt is everything following the second colon, so may itself contain colons
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(3, ':');
+        let (name, algorithm, salt) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(n), Some(a), Some(s)) if !n.trim().is_empty() => (n.trim(), a.trim(), s),
+            _ => return Err(String::from("expected NAME:sha256:SALT")),
+        };
+
+        let algorithm = if algorithm.eq_ignore_ascii_case("sha256") {
+            ReadStatHashAlgorithm::Sha256
+        } else {
+            return Err(format!("unknown hash {}; expecting sha256", algorithm));
+        };
+
+        if salt.is_empty() {
+            return Err(format!("the salt for {} is empty", name));
+        }
+
+        Ok(Self {
+            name: String::from(name),
+            algorithm,
+            salt: String::from(salt),
+        })
+    }
+}
+
+/// How the values of a column are redacted
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReadStatRedaction {
+    /// Every value is written as null
+    Null,
+    /// Every non-missing value is written as a salted hash; missing values stay null
+    Hash {
+        column: ReadStatHashColumn,
+        /// Arrow type of the column before hashing, which its values are read as
+        source_type: DataType,
+    },
+}
+
+impl ReadStatRedaction {
+    pub(crate) fn redact(&self, value: ReadStatVar) -> ReadStatVar {
+        match self {
+            Self::Null => value.into_missing(),
+            Self::Hash { column, .. } => {
+                ReadStatVar::ReadStat_String(value.to_text().map(|t| column.hash(&t)))
+            }
+        }
+    }
+}
+
+// Recorded within the mapping sidecar, so the salt is never included
+impl fmt::Display for ReadStatRedaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => write!(f, "null"),
+            Self::Hash { column, .. } => write!(f, "{}", column.algorithm),
+        }
+    }
+}
+
+/// Columns whose values are redacted as they are read
+///
+/// Columns are named as within the file, before any rename.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReadStatRedact {
+    /// Columns written as all null
+    pub redact: Vec<String>,
+    /// Columns whose values are written as salted hashes
+    pub hash: Vec<ReadStatHashColumn>,
+}
+
+impl ReadStatRedact {
+    pub fn is_active(&self) -> bool {
+        !self.redact.is_empty() || !self.hash.is_empty()
+    }
+
+    /// Record the redaction of each column within `md.redactions`
+    ///
+    /// Hashed columns are stored as Arrow strings.  Naming a variable that does not exist, or
+    /// naming the same variable more than once, is an error.
+    pub fn apply(&self, md: &mut ReadStatMetadata) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if !self.is_active() {
+            return Ok(());
+        }
+
+        let index_of = |name: &str| -> Result<i32, Box<dyn Error + Send + Sync>> {
+            md.vars
+                .iter()
+                .find(|(_, vm)| vm.var_name == name)
+                .map(|(k, _)| *k)
+                .ok_or_else(|| {
+                    From::from(format!("The variable {} does not exist", name.bright_yellow()))
+                })
+        };
+
+        let mut columns = Vec::new();
+        for name in &self.redact {
+            columns.push((name, index_of(name)?, ReadStatRedaction::Null));
+        }
+        for h in &self.hash {
+            let k = index_of(&h.name)?;
+            let redaction = ReadStatRedaction::Hash {
+                column: h.clone(),
+                source_type: md.schema.fields[k as usize].data_type().clone(),
+            };
+            columns.push((&h.name, k, redaction));
+        }
+
+        let mut redactions = BTreeMap::new();
+        for (name, k, redaction) in columns {
+            if redactions.insert(k, redaction).is_some() {
+                return Err(From::from(format!(
+                    "The variable {} is redacted more than once",
+                    name.bright_yellow()
+                )));
+            }
+        }
+
+        for (k, r) in &redactions {
+            if matches!(r, ReadStatRedaction::Hash { .. }) {
+                md.type_decisions.insert(
+                    *k,
+                    ReadStatTypeDecision {
+                        data_type: DataType::Utf8,
+                        reason: ReadStatTypeReason::Hashing,
+                    },
+                );
+            }
+        }
+
+        md.redactions = redactions;
+
+        // decisions changed, so rebuild the schema
+        md.schema = md.initialize_schema();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+use arrow2::{
+    array::{get_display, Array, PrimitiveArray, Utf8Array},
+    chunk::Chunk,
+    datatypes::DataType,
+};
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+use std::error::Error;
+
+use crate::common::build_offsets;
+use crate::rs_metadata::ReadStatMetadata;
+use crate::rs_path::ReadStatPath;
+use crate::rs_stats::for_each_chunk;
+
+/// A row sampled from a dataset, keyed by variable name with missing values as nulls
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadStatSampleRow {
+    pub row: u64,
+    pub values: Map<String, Value>,
+}
+
+// SplitMix64 - small, fast, and fully determined by its seed
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // uniform within [0, n)
+    fn below(&mut self, n: u64) -> u64 {
+        ((self.next_u64() as u128 * n as u128) >> 64) as u64
+    }
+}
+
+/// Sample `n` rows uniformly from the whole file using reservoir sampling
+///
+/// A single pass is made over the data; only rows entering the reservoir are buffered.  The
+/// same `seed` always selects the same rows.  Rows are returned in file order.
+pub fn sample_rows(
+    rsp: &ReadStatPath,
+    md: &ReadStatMetadata,
+    n: usize,
+    seed: u64,
+    stream_rows: u32,
+) -> Result<Vec<ReadStatSampleRow>, Box<dyn Error + Send + Sync>> {
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut rng = SplitMix64(seed);
+    let mut reservoir: Vec<ReadStatSampleRow> = Vec::with_capacity(n);
+    let mut seen: u64 = 0;
+
+    let names: Vec<String> = md.schema.fields.iter().map(|f| f.name.clone()).collect();
+    let offsets = build_offsets(md.row_count as u32, stream_rows)?;
+
+    for_each_chunk(rsp, md, &offsets, |chunk| {
+        for i in 0..chunk.len() {
+            // Algorithm R
+            let slot = if reservoir.len() < n {
+                Some(reservoir.len())
+            } else {
+                let j = rng.below(seen + 1) as usize;
+                if j < n {
+                    Some(j)
+                } else {
+                    None
+                }
+            };
+
+            if let Some(slot) = slot {
+                let row = ReadStatSampleRow {
+                    row: seen,
+                    values: row_to_json(chunk, &names, i),
+                };
+
+                if slot == reservoir.len() {
+                    reservoir.push(row);
+                } else {
+                    reservoir[slot] = row;
+                }
+            }
+
+            seen += 1;
+        }
+    })?;
+
+    reservoir.sort_by_key(|r| r.row);
+    Ok(reservoir)
+}
+
+fn row_to_json(chunk: &Chunk<Box<dyn Array>>, names: &[String], i: usize) -> Map<String, Value> {
+    names
+        .iter()
+        .zip(chunk.arrays())
+        .map(|(name, a)| (name.clone(), value_to_json(a.as_ref(), i)))
+        .collect()
+}
+
+fn value_to_json(array: &dyn Array, i: usize) -> Value {
+    if array.is_null(i) {
+        return Value::Null;
+    }
+
+    macro_rules! number {
+        ($t:ty) => {
+            Value::from(
+                array
+                    .as_any()
+                    .downcast_ref::<PrimitiveArray<$t>>()
+                    .unwrap()
+                    .value(i),
+            )
+        };
+    }
+
+    match array.data_type() {
+        DataType::Utf8 => Value::from(
+            array
+                .as_any()
+                .downcast_ref::<Utf8Array<i32>>()
+                .unwrap()
+                .value(i),
+        ),
+        DataType::Int8 => number!(i8),
+        DataType::Int16 => number!(i16),
+        DataType::Int32 => number!(i32),
+        DataType::Int64 => number!(i64),
+        DataType::Float32 => Number::from_f64(
+            array
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f32>>()
+                .unwrap()
+                .value(i) as f64,
+        )
+        .map_or(Value::Null, Value::Number),
+        DataType::Float64 => Number::from_f64(
+            array
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f64>>()
+                .unwrap()
+                .value(i),
+        )
+        .map_or(Value::Null, Value::Number),
+        // dates, times, and datetimes are rendered as text
+        _ => {
+            let mut s = String::new();
+            let display = get_display(array, "null");
+            match display(&mut s, i) {
+                Ok(()) => Value::from(s),
+                Err(_) => Value::Null,
+            }
+        }
+    }
+}
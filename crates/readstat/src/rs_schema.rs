@@ -0,0 +1,204 @@
+use arrow2::datatypes::{DataType, Field, Schema, TimeUnit};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::{
+    error::Error,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::common::sha256_hex;
+use crate::rs_dictionary::dictionary_type;
+use crate::rs_metadata::{ReadStatFormatSpec, ReadStatMetadata};
+
+/// Version of the structure below; bumped only should a field be removed or change meaning
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// First line of the canonical form hashed by [`ReadStatSchema::fingerprint_columns`]; changed
+/// only should the canonical form itself change
+pub const FINGERPRINT_VERSION: &str = "readstat-schema-fingerprint-v1";
+
+/// A single column of [`ReadStatSchema`]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ReadStatSchemaColumn {
+    /// Name of the column as written
+    pub name: String,
+    /// Arrow type, e.g. `Float64`, `Utf8`, `Date32`, `Timestamp(Millisecond)`
+    pub arrow_type: String,
+    /// SAS format, e.g. `BEST12`, `$30`, `YYMMDD10`; empty when the variable has none
+    pub sas_format: String,
+    /// The name, width, and decimals of the SAS format as stored; absent without a format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sas_format_parts: Option<ReadStatFormatSpec>,
+    pub label: String,
+    pub nullable: bool,
+}
+
+/// Name, Arrow type, SAS format, label, and nullability of every column written
+///
+/// Written alongside the output as `<stem>.schema.json` (e.g. `cars.schema.json`) when requested,
+/// chiefly for `csv` so that the types lost by plain text can be reconstructed via
+/// [`ReadStatSchema::to_arrow_schema`].
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ReadStatSchema {
+    pub version: u32,
+    pub table_name: String,
+    /// See [`ReadStatSchema::fingerprint_columns`]; empty within schemas written before it was
+    /// recorded
+    #[serde(default)]
+    pub fingerprint: String,
+    pub columns: Vec<ReadStatSchemaColumn>,
+}
+
+impl ReadStatSchema {
+    pub fn new(md: &ReadStatMetadata) -> Self {
+        let columns = md
+            .vars
+            .values()
+            .zip(md.schema.fields.iter())
+            .map(|(vm, f)| ReadStatSchemaColumn {
+                name: f.name.clone(),
+                arrow_type: arrow_type_name(f.data_type()),
+                sas_format: vm.var_format.clone(),
+                sas_format_parts: vm.var_format_parts.clone(),
+                label: vm.var_label.clone(),
+                nullable: f.is_nullable,
+            })
+            .collect();
+
+        Self {
+            version: SCHEMA_VERSION,
+            table_name: md.table_name.clone(),
+            fingerprint: Self::fingerprint_columns(&columns),
+            columns,
+        }
+    }
+
+    /// Fingerprint of the names, Arrow types, and order of `columns`, to detect schema drift with
+    /// a single comparison
+    ///
+    /// The lowercase hex sha256 of the canonical form
+    ///
+    /// ```text
+    /// readstat-schema-fingerprint-v1
+    /// <bytes within name>:<name>:<arrow type>
+    /// ```
+    ///
+    /// with a line for each column in order, every line ending in `\n`, and the Arrow type named as
+    /// within [`ReadStatSchemaColumn::arrow_type`].  The length prefix keeps names containing `:`
+    /// or newlines unambiguous.  Everything else (table name, labels, SAS formats, nullability)
+    /// is excluded, so the fingerprint changes only should a column be added, removed, renamed,
+    /// retyped, or moved.
+    pub fn fingerprint_columns(columns: &[ReadStatSchemaColumn]) -> String {
+        let mut canonical = format!("{}\n", FINGERPRINT_VERSION);
+        for c in columns {
+            canonical.push_str(&format!("{}:{}:{}\n", c.name.len(), c.name, c.arrow_type));
+        }
+
+        sha256_hex(canonical.as_bytes())
+    }
+
+    /// `<stem>.schema.json` next to the output, e.g. `cars.schema.json` for `cars.csv`
+    pub fn sidecar_path(out_path: &Path) -> PathBuf {
+        out_path.with_extension("schema.json")
+    }
+
+    /// Read a schema previously written by [`ReadStatSchema::write`]
+    pub fn read(path: &Path) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let schema: Self = serde_json::from_reader(File::open(path)?)?;
+
+        if schema.version > SCHEMA_VERSION {
+            return Err(From::from(format!(
+                "The schema {} is version {} but at most version {} is supported",
+                path.to_string_lossy().bright_red(),
+                schema.version,
+                SCHEMA_VERSION
+            )));
+        }
+
+        Ok(schema)
+    }
+
+    /// Write as pretty json to `path`, replacing any existing file
+    pub fn write(&self, path: &Path) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut f = File::create(path)?;
+        f.write_all(serde_json::to_string_pretty(self)?.as_bytes())?;
+        f.write_all(b"\n")?;
+        Ok(())
+    }
+
+    /// The Arrow schema the columns were written with
+    pub fn to_arrow_schema(&self) -> Result<Schema, Box<dyn Error + Send + Sync>> {
+        let fields = self
+            .columns
+            .iter()
+            .map(|c| Ok(Field::new(&c.name, parse_arrow_type(&c.arrow_type)?, c.nullable)))
+            .collect::<Result<Vec<Field>, Box<dyn Error + Send + Sync>>>()?;
+
+        Ok(Schema::from(fields))
+    }
+}
+
+fn time_unit_name(unit: &TimeUnit) -> &'static str {
+    match unit {
+        TimeUnit::Second => "Second",
+        TimeUnit::Millisecond => "Millisecond",
+        TimeUnit::Microsecond => "Microsecond",
+        TimeUnit::Nanosecond => "Nanosecond",
+    }
+}
+
+fn parse_time_unit(name: &str) -> Option<TimeUnit> {
+    match name {
+        "Second" => Some(TimeUnit::Second),
+        "Millisecond" => Some(TimeUnit::Millisecond),
+        "Microsecond" => Some(TimeUnit::Microsecond),
+        "Nanosecond" => Some(TimeUnit::Nanosecond),
+        _ => None,
+    }
+}
+
+// Timestamps are always written without a time zone, so the zone is left out of the name
+pub(crate) fn arrow_type_name(data_type: &DataType) -> String {
+    match data_type {
+        DataType::Time32(u) => format!("Time32({})", time_unit_name(u)),
+        DataType::Time64(u) => format!("Time64({})", time_unit_name(u)),
+        DataType::Timestamp(u, _) => format!("Timestamp({})", time_unit_name(u)),
+        DataType::Dictionary(k, v, _) => format!("Dictionary({:?}, {})", k, arrow_type_name(v)),
+        dt => format!("{:?}", dt),
+    }
+}
+
+pub(crate) fn parse_arrow_type(name: &str) -> Result<DataType, Box<dyn Error + Send + Sync>> {
+    let data_type = match name {
+        "Boolean" => Some(DataType::Boolean),
+        "Int8" => Some(DataType::Int8),
+        "Int16" => Some(DataType::Int16),
+        "Int32" => Some(DataType::Int32),
+        "Int64" => Some(DataType::Int64),
+        "Float32" => Some(DataType::Float32),
+        "Float64" => Some(DataType::Float64),
+        "Utf8" => Some(DataType::Utf8),
+        "Date32" => Some(DataType::Date32),
+        "Date64" => Some(DataType::Date64),
+        "Dictionary(Int32, Utf8)" => Some(dictionary_type()),
+        _ => name
+            .strip_suffix(')')
+            .and_then(|n| n.split_once('('))
+            .and_then(|(outer, unit)| parse_time_unit(unit).map(|u| (outer, u)))
+            .and_then(|(outer, unit)| match outer {
+                "Time32" => Some(DataType::Time32(unit)),
+                "Time64" => Some(DataType::Time64(unit)),
+                "Timestamp" => Some(DataType::Timestamp(unit, None)),
+                _ => None,
+            }),
+    };
+
+    data_type.ok_or_else(|| {
+        From::from(format!(
+            "The Arrow type {} is not supported",
+            name.bright_yellow()
+        ))
+    })
+}
@@ -0,0 +1,324 @@
+use arrow2::{compute::cast::can_cast_types, datatypes::DataType};
+use colored::Colorize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    io::Read,
+    path::Path,
+};
+
+use crate::rs_metadata::{ReadStatMetadata, ReadStatRenameReason, ReadStatTypeReason};
+use crate::rs_schema::parse_arrow_type;
+
+/// Row of a [`ReadStatColumnSpec`], naming a variable as within the file
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadStatColumnSpecRow {
+    /// Line of the spec the row was read from, the header being line 1
+    pub line: u64,
+    pub variable: String,
+    pub keep: bool,
+    pub new_name: Option<String>,
+    pub data_type: Option<DataType>,
+}
+
+/// Column selection, renames, and type overrides read from a csv spec
+///
+/// The spec has the header `variable,keep,new_name,type`; only `variable` is required and any
+/// other columns are ignored.  A `keep` of `no`, `false`, or `0` drops the variable, whereas an
+/// empty `keep` keeps it, as are variables the spec does not list.  Types are the Arrow names
+/// used within the schema sidecar, e.g. `Int32`, `Utf8`, or `Timestamp(Millisecond)`.
+///
+/// Rows are checked against the metadata by [`ReadStatColumnSpec::apply`], ahead of reading any
+/// data, with every problem reported along with its line.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReadStatColumnSpec {
+    /// Name of the spec within error messages, usually its path
+    pub source: String,
+    pub rows: Vec<ReadStatColumnSpecRow>,
+}
+
+impl ReadStatColumnSpec {
+    pub fn from_path(path: &Path) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let f = std::fs::File::open(path).map_err(|e| {
+            format!(
+                "Unable to open the column spec {}: {}",
+                path.to_string_lossy().bright_yellow(),
+                e
+            )
+        })?;
+        Self::from_reader(&path.to_string_lossy(), f)
+    }
+
+    /// Parse a spec, reporting every malformed row rather than only the first
+    pub fn from_reader<R: Read>(
+        source: &str,
+        rdr: R,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let mut rdr = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .flexible(true)
+            .from_reader(rdr);
+
+        let headers = rdr.headers()?.clone();
+        let column = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+        let variable = column("variable").ok_or_else(|| {
+            format!(
+                "The column spec {} has no {} column",
+                source.bright_yellow(),
+                "variable".bright_cyan()
+            )
+        })?;
+        let (keep, new_name, data_type) = (column("keep"), column("new_name"), column("type"));
+
+        let mut rows = Vec::new();
+        let mut errors = Vec::new();
+        for record in rdr.records() {
+            let record = record?;
+            let line = record.position().map_or(0, |p| p.line());
+            let field = |i: Option<usize>| i.and_then(|i| record.get(i)).filter(|v| !v.is_empty());
+
+            let name = match field(Some(variable)) {
+                Some(v) => String::from(v),
+                None => {
+                    // blank lines between rows are not an error
+                    if record.iter().all(|v| v.is_empty()) {
+                        continue;
+                    }
+                    errors.push((line, String::from("the variable is missing")));
+                    continue;
+                }
+            };
+
+            let keep = match field(keep).map(|v| v.to_ascii_lowercase()).as_deref() {
+                None | Some("yes") | Some("true") | Some("1") => true,
+                Some("no") | Some("false") | Some("0") => false,
+                Some(v) => {
+                    let e = format!("keep is {}; expecting yes or no", v.bright_yellow());
+                    errors.push((line, e));
+                    continue;
+                }
+            };
+
+            let data_type = match field(data_type).map(parse_arrow_type).transpose() {
+                Ok(dt) => dt,
+                Err(e) => {
+                    errors.push((line, e.to_string()));
+                    continue;
+                }
+            };
+
+            rows.push(ReadStatColumnSpecRow {
+                line,
+                variable: name,
+                keep,
+                new_name: field(new_name).map(String::from),
+                data_type,
+            });
+        }
+
+        if !errors.is_empty() {
+            return Err(invalid(source, errors));
+        }
+
+        Ok(Self {
+            source: String::from(source),
+            rows,
+        })
+    }
+
+    /// Select, rename, and cast the variables of `md` as the spec directs
+    ///
+    /// Called once any name transform is applied, so that the spec overrides it, yet ahead of
+    /// `renames` (from `--rename`); a rename or `--booleans` list naming the same variable as the
+    /// spec takes precedence over it, with a warning.  Every row is checked ahead of any change,
+    /// and the returned error lists each unknown variable, uncastable type, and duplicated name
+    /// along with its line.
+    pub fn apply(
+        &self,
+        md: &mut ReadStatMetadata,
+        renames: &[(String, String)],
+    ) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+        let mut errors = Vec::new();
+
+        // rows by the index of their variable
+        let mut rows: BTreeMap<i32, &ReadStatColumnSpecRow> = BTreeMap::new();
+        for row in &self.rows {
+            let k = match md.vars.iter().find(|(_, vm)| vm.var_name == row.variable) {
+                Some((k, _)) => *k,
+                None => {
+                    errors.push((
+                        row.line,
+                        format!("the variable {} does not exist", row.variable.bright_yellow()),
+                    ));
+                    continue;
+                }
+            };
+
+            if let Some(first) = rows.get(&k) {
+                errors.push((
+                    row.line,
+                    format!(
+                        "the variable {} is already listed on line {}",
+                        row.variable.bright_yellow(),
+                        first.line
+                    ),
+                ));
+                continue;
+            }
+
+            if let (true, Some(dt)) = (row.keep, &row.data_type) {
+                let from = md.schema.fields[k as usize].data_type();
+                if !can_cast_types(from, dt) {
+                    errors.push((
+                        row.line,
+                        format!(
+                            "the variable {} cannot be cast from {:?} to {:?}",
+                            row.variable.bright_yellow(),
+                            from,
+                            dt
+                        ),
+                    ));
+                }
+            }
+
+            rows.insert(k, row);
+        }
+
+        let user_renamed: BTreeMap<&str, &str> = renames
+            .iter()
+            .map(|(old, new)| (old.as_str(), new.as_str()))
+            .collect();
+
+        // kept variables and the names they are written with
+        let mut names: BTreeMap<String, i32> = BTreeMap::new();
+        for (k, vm) in &md.vars {
+            let row = rows.get(k);
+            if row.map_or(false, |r| !r.keep) {
+                continue;
+            }
+
+            let user_rename = user_renamed.get(vm.var_name.as_str());
+            let name = match (user_rename, row.and_then(|r| r.new_name.as_ref())) {
+                (Some(new), _) => String::from(*new),
+                (None, Some(new)) => new.clone(),
+                (None, None) => String::from(md.final_name(*k)),
+            };
+
+            if let Some(first) = names.get(&name) {
+                // name clashes not involving the spec are reported when renaming
+                let line = row.or_else(|| rows.get(first)).map(|r| r.line);
+                if let Some(line) = line {
+                    errors.push((
+                        line,
+                        format!(
+                            "{} would name both the variables {} and {}",
+                            name.bright_yellow(),
+                            md.vars[first].var_name.bright_yellow(),
+                            vm.var_name.bright_yellow()
+                        ),
+                    ));
+                }
+                continue;
+            }
+            names.insert(name, *k);
+        }
+
+        if names.is_empty() && errors.is_empty() {
+            errors.push((0, String::from("every variable is dropped")));
+        }
+
+        if !errors.is_empty() {
+            errors.sort();
+            return Err(invalid(&self.source, errors));
+        }
+
+        let mut warnings = Vec::new();
+        for (k, row) in &rows {
+            if !row.keep {
+                if user_renamed.contains_key(row.variable.as_str()) {
+                    warnings.push(format!(
+                        "{}: the variable {} is renamed by {} yet dropped by line {} of the column spec",
+                        "Warning".bright_yellow(),
+                        row.variable.bright_yellow(),
+                        "--rename".bright_cyan(),
+                        row.line
+                    ));
+                }
+                continue;
+            }
+
+            if let Some(new) = &row.new_name {
+                if user_renamed.contains_key(row.variable.as_str()) {
+                    warnings.push(precedence(row, "--rename", "new_name"));
+                } else {
+                    md.rename_var(*k, new.clone(), ReadStatRenameReason::ColumnSpec);
+                }
+            }
+
+            if let Some(dt) = &row.data_type {
+                match md.type_decisions.get(k) {
+                    Some(td)
+                        if td.data_type == DataType::Boolean
+                            && td.reason == ReadStatTypeReason::UserOverride =>
+                    {
+                        warnings.push(precedence(row, "--booleans", "type"))
+                    }
+                    Some(td) if td.reason == ReadStatTypeReason::Hashing => {
+                        warnings.push(precedence(row, "--hash-columns", "type"))
+                    }
+                    _ => {
+                        md.casts.insert(*k, dt.clone());
+                    }
+                }
+            }
+        }
+
+        let dropped: Vec<i32> = rows
+            .iter()
+            .filter(|(_, r)| !r.keep)
+            .map(|(k, _)| *k)
+            .collect();
+        if !dropped.is_empty() {
+            let kept: BTreeSet<i32> = md
+                .vars
+                .keys()
+                .filter(|k| !dropped.contains(k))
+                .copied()
+                .collect();
+            md.selection = Some(kept);
+        }
+
+        md.finish_renames()?;
+
+        Ok(warnings)
+    }
+}
+
+// Warning that a flag naming the same variable as a row of the spec wins over it
+fn precedence(row: &ReadStatColumnSpecRow, flag: &str, what: &str) -> String {
+    format!(
+        "{}: {} of variable {} takes precedence over line {} of the column spec, so its {} is ignored",
+        "Warning".bright_yellow(),
+        flag.bright_cyan(),
+        row.variable.bright_yellow(),
+        row.line,
+        what
+    )
+}
+
+// Every problem found within a spec, one per line
+fn invalid(source: &str, mut errors: Vec<(u64, String)>) -> Box<dyn Error + Send + Sync> {
+    errors.dedup();
+    let lines: Vec<String> = errors
+        .iter()
+        .map(|(line, e)| match line {
+            0 => format!("  {}", e),
+            l => format!("  line {}: {}", l, e),
+        })
+        .collect();
+    From::from(format!(
+        "The column spec {} is invalid:\n{}",
+        source.bright_yellow(),
+        lines.join("\n")
+    ))
+}
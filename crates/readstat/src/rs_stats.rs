@@ -0,0 +1,320 @@
+use arrow2::{
+    array::{Array, PrimitiveArray},
+    chunk::Chunk,
+    datatypes::{DataType, Schema},
+};
+use serde::Serialize;
+use std::error::Error;
+
+use crate::common::build_offsets;
+use crate::rs_data::ReadStatData;
+use crate::rs_metadata::{ReadStatMetadata, ReadStatTypeReason};
+use crate::rs_path::ReadStatPath;
+use crate::rs_sample::ReadStatSampleRow;
+use crate::Booleans;
+
+/// Fixed-bucket histogram over `[min, max]`
+///
+/// Memory is proportional to the number of bins; values are counted as they stream past.
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadStatHistogram {
+    pub min: f64,
+    pub max: f64,
+    pub counts: Vec<u64>,
+}
+
+impl ReadStatHistogram {
+    pub fn new(min: f64, max: f64, bins: usize) -> Self {
+        Self {
+            min,
+            max,
+            counts: vec![0; bins.max(1)],
+        }
+    }
+
+    pub fn push(&mut self, value: f64) {
+        let bins = self.counts.len();
+        let width = (self.max - self.min) / bins as f64;
+
+        // the last bin is closed so that max is counted within it
+        let index = if width > 0.0 {
+            (((value - self.min) / width) as usize).min(bins - 1)
+        } else {
+            0
+        };
+
+        self.counts[index] += 1;
+    }
+
+    /// Bin boundaries; bin `i` covers `[edges[i], edges[i + 1])`
+    pub fn edges(&self) -> Vec<f64> {
+        let bins = self.counts.len();
+        let width = (self.max - self.min) / bins as f64;
+        (0..=bins)
+            .map(|i| {
+                if i == bins {
+                    self.max
+                } else {
+                    self.min + width * i as f64
+                }
+            })
+            .collect()
+    }
+}
+
+/// Profile of a single column
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadStatColumnProfile {
+    pub var_name: String,
+    pub count: u64,
+    pub null_count: u64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+    pub histogram: Option<ReadStatHistogram>,
+    #[serde(skip_serializing)]
+    sum: f64,
+    #[serde(skip_serializing)]
+    numeric: bool,
+}
+
+/// Profile of a dataset
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadStatProfile {
+    pub table_name: String,
+    pub row_count: u64,
+    pub columns: Vec<ReadStatColumnProfile>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_rows: Option<Vec<ReadStatSampleRow>>,
+}
+
+impl ReadStatProfile {
+    /// Profile every column of the file described by `rsp`
+    ///
+    /// Numeric columns receive a min, max, and mean.  If `histogram_bins` is set, a second pass is
+    /// made over the data so that each numeric column's histogram spans its exact range.
+    pub fn new(
+        rsp: &ReadStatPath,
+        histogram_bins: Option<usize>,
+        stream_rows: u32,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        // Read metadata; variables sharing a name are profiled apart under their renamed names
+        let mut md = ReadStatMetadata::new().set_dedup_columns(true);
+        md.read_metadata(rsp, false)?;
+
+        let mut columns: Vec<ReadStatColumnProfile> = md
+            .schema
+            .fields
+            .iter()
+            .map(|f| ReadStatColumnProfile {
+                var_name: f.name.clone(),
+                count: 0,
+                null_count: 0,
+                min: None,
+                max: None,
+                mean: None,
+                histogram: None,
+                sum: 0.0,
+                numeric: is_numeric(f.data_type()),
+            })
+            .collect();
+
+        let row_count = md.row_count as u32;
+        let offsets = build_offsets(row_count, stream_rows)?;
+
+        // First pass - counts, min, max, and mean
+        for_each_chunk(rsp, &md, &offsets, |chunk| {
+            for (c, a) in columns.iter_mut().zip(chunk.arrays()) {
+                c.null_count += a.null_count() as u64;
+                c.count += (a.len() - a.null_count()) as u64;
+
+                if c.numeric {
+                    for_each_value(a.as_ref(), |v| {
+                        c.sum += v;
+                        c.min = Some(c.min.map_or(v, |m| m.min(v)));
+                        c.max = Some(c.max.map_or(v, |m| m.max(v)));
+                    });
+                }
+            }
+        })?;
+
+        for c in columns.iter_mut().filter(|c| c.numeric && c.count > 0) {
+            c.mean = Some(c.sum / c.count as f64);
+        }
+
+        // Second pass - histograms
+        if let Some(bins) = histogram_bins {
+            for c in columns.iter_mut() {
+                if let (Some(min), Some(max)) = (c.min, c.max) {
+                    c.histogram = Some(ReadStatHistogram::new(min, max, bins));
+                }
+            }
+
+            for_each_chunk(rsp, &md, &offsets, |chunk| {
+                for (c, a) in columns.iter_mut().zip(chunk.arrays()) {
+                    if let Some(h) = &mut c.histogram {
+                        for_each_value(a.as_ref(), |v| h.push(v));
+                    }
+                }
+            })?;
+        }
+
+        Ok(Self {
+            table_name: md.table_name.clone(),
+            row_count: row_count as u64,
+            columns,
+            sample_rows: None,
+        })
+    }
+}
+
+/// Detects plain numeric columns whose every non-missing value is exactly 0 or 1
+///
+/// Columns without any non-missing values do not qualify.
+#[derive(Clone, Debug)]
+pub struct ReadStatBooleanDetector {
+    // per column - whether still a candidate, and the number of non-missing values observed
+    candidates: Vec<(bool, u64)>,
+}
+
+impl ReadStatBooleanDetector {
+    pub fn new(schema: &Schema) -> Self {
+        Self {
+            candidates: schema
+                .fields
+                .iter()
+                .map(|f| (matches!(f.data_type(), DataType::Float64), 0))
+                .collect(),
+        }
+    }
+
+    pub fn push_chunk(&mut self, chunk: &Chunk<Box<dyn Array>>) {
+        for ((candidate, count), a) in self.candidates.iter_mut().zip(chunk.arrays()) {
+            if !*candidate {
+                continue;
+            }
+
+            if let Some(a) = a.as_any().downcast_ref::<PrimitiveArray<f64>>() {
+                for v in a.iter().flatten() {
+                    if *v != 0.0 && *v != 1.0 {
+                        *candidate = false;
+                        break;
+                    }
+                    *count += 1;
+                }
+            } else {
+                *candidate = false;
+            }
+        }
+    }
+
+    /// Indices of the columns that qualify
+    pub fn qualifying(&self) -> Vec<usize> {
+        self.candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, (candidate, count))| *candidate && *count > 0)
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+/// Names of the plain numeric columns whose every non-missing value is exactly 0 or 1
+pub fn detect_boolean_columns(
+    rsp: &ReadStatPath,
+    md: &ReadStatMetadata,
+    stream_rows: u32,
+) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+    let mut detector = ReadStatBooleanDetector::new(&md.schema);
+    let offsets = build_offsets(md.row_count as u32, stream_rows)?;
+
+    for_each_chunk(rsp, md, &offsets, |chunk| detector.push_chunk(chunk))?;
+
+    // names within the file, as columns may have been renamed
+    let names: Vec<&String> = md.vars.values().map(|vm| &vm.var_name).collect();
+
+    Ok(detector
+        .qualifying()
+        .into_iter()
+        .map(|i| names[i].clone())
+        .collect())
+}
+
+/// Store numeric columns as Arrow booleans, either those detected by reading the data or those named
+pub fn apply_booleans(
+    rsp: &ReadStatPath,
+    md: &mut ReadStatMetadata,
+    booleans: &Booleans,
+    stream_rows: u32,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    match booleans {
+        Booleans::Auto => {
+            let names = detect_boolean_columns(rsp, md, stream_rows)?;
+            md.set_boolean_columns(&names, ReadStatTypeReason::BooleanDetection)
+        }
+        Booleans::Explicit(names) => md.set_boolean_columns(names, ReadStatTypeReason::UserOverride),
+    }
+}
+
+pub(crate) fn for_each_chunk<F>(
+    rsp: &ReadStatPath,
+    md: &ReadStatMetadata,
+    offsets: &[u32],
+    mut f: F,
+) -> Result<(), Box<dyn Error + Send + Sync>>
+where
+    F: FnMut(&Chunk<Box<dyn Array>>),
+{
+    for w in offsets.windows(2).filter(|w| w[0] < w[1]) {
+        let mut d = ReadStatData::new()
+            .set_no_progress(true)
+            .init(md.clone(), w[0], w[1]);
+        d.read_data(rsp)?;
+
+        if let Some(chunk) = &d.chunk {
+            f(chunk);
+        }
+    }
+    Ok(())
+}
+
+// Dates, times, and datetimes are not profiled as numbers
+fn is_numeric(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::Float32
+            | DataType::Float64
+    )
+}
+
+// Non-null values of a numeric array
+fn for_each_value<F>(array: &dyn Array, mut f: F)
+where
+    F: FnMut(f64),
+{
+    macro_rules! visit {
+        ($t:ty) => {
+            array
+                .as_any()
+                .downcast_ref::<PrimitiveArray<$t>>()
+                .unwrap()
+                .iter()
+                .flatten()
+                .for_each(|v| f(*v as f64))
+        };
+    }
+
+    match array.data_type() {
+        DataType::Int8 => visit!(i8),
+        DataType::Int16 => visit!(i16),
+        DataType::Int32 => visit!(i32),
+        DataType::Int64 => visit!(i64),
+        DataType::Float32 => visit!(f32),
+        DataType::Float64 => visit!(f64),
+        _ => (),
+    }
+}
@@ -0,0 +1,341 @@
+use arrow2::datatypes::{DataType, TimeUnit};
+use chrono::{DateTime, NaiveTime};
+use serde::Serialize;
+use std::{collections::HashSet, error::Error};
+
+use crate::common::format_f64;
+use crate::rs_data::ReadStatData;
+use crate::rs_metadata::ReadStatMetadata;
+use crate::rs_path::ReadStatPath;
+use crate::rs_schema::arrow_type_name;
+use crate::rs_var::ReadStatVar;
+
+/// Distinct string values counted exactly for each column before the count becomes a lower bound
+pub const DISTINCT_CAP: usize = 10_000;
+
+/// Kind of statistics accumulated for a column, following its Arrow type
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReadStatSummaryKind {
+    Numeric,
+    String,
+    Boolean,
+    Date,
+    Time,
+    Datetime,
+}
+
+impl ReadStatSummaryKind {
+    fn new(data_type: &DataType) -> Self {
+        match data_type {
+            DataType::Utf8 | DataType::LargeUtf8 => Self::String,
+            DataType::Boolean => Self::Boolean,
+            DataType::Date32 | DataType::Date64 => Self::Date,
+            DataType::Time32(_) | DataType::Time64(_) => Self::Time,
+            DataType::Timestamp(_, _) => Self::Datetime,
+            _ => Self::Numeric,
+        }
+    }
+}
+
+/// Statistics of a single column, accumulated from its values as they are parsed
+///
+/// Numerics receive a min, max, and mean; strings a count of their distinct values and the
+/// characters of the longest; dates, times, and datetimes their earliest and latest, as text.
+/// Distinct strings are counted exactly up to a cap, beyond which `distinct_capped` is set and
+/// `distinct_count` is a lower bound, so memory stays bounded whatever the rows.
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadStatColumnSummary {
+    pub var_name: String,
+    pub kind: ReadStatSummaryKind,
+    pub arrow_type: String,
+    pub count: u64,
+    pub missing_count: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mean: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distinct_count: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distinct_capped: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub earliest: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest: Option<String>,
+    #[serde(skip_serializing)]
+    data_type: DataType,
+    #[serde(skip_serializing)]
+    sum: f64,
+    #[serde(skip_serializing)]
+    distinct: HashSet<String>,
+    #[serde(skip_serializing)]
+    distinct_cap: usize,
+    // earliest and latest in the units of the Arrow type
+    #[serde(skip_serializing)]
+    range: Option<(i64, i64)>,
+}
+
+impl ReadStatColumnSummary {
+    pub(crate) fn new(var_name: &str, data_type: &DataType, distinct_cap: usize) -> Self {
+        let kind = ReadStatSummaryKind::new(data_type);
+        let strings = kind == ReadStatSummaryKind::String;
+
+        Self {
+            var_name: String::from(var_name),
+            kind,
+            arrow_type: arrow_type_name(data_type),
+            count: 0,
+            missing_count: 0,
+            min: None,
+            max: None,
+            mean: None,
+            distinct_count: strings.then_some(0),
+            distinct_capped: strings.then_some(false),
+            max_length: strings.then_some(0),
+            earliest: None,
+            latest: None,
+            data_type: data_type.clone(),
+            sum: 0.0,
+            distinct: HashSet::new(),
+            distinct_cap,
+            range: None,
+        }
+    }
+
+    pub(crate) fn push(&mut self, value: &ReadStatVar) {
+        let number = match value {
+            ReadStatVar::ReadStat_i8(v) => v.map(|v| v as f64),
+            ReadStatVar::ReadStat_i16(v) => v.map(|v| v as f64),
+            ReadStatVar::ReadStat_i32(v) => v.map(|v| v as f64),
+            ReadStatVar::ReadStat_i64(v) => v.map(|v| v as f64),
+            ReadStatVar::ReadStat_f32(v) => v.map(|v| v as f64),
+            ReadStatVar::ReadStat_f64(v) => *v,
+            _ => None,
+        };
+        let instant = match value {
+            ReadStatVar::ReadStat_Date(v) | ReadStatVar::ReadStat_Time(v) => v.map(i64::from),
+            ReadStatVar::ReadStat_DateTime(v)
+            | ReadStatVar::ReadStat_DateTimeWithMilliseconds(v)
+            | ReadStatVar::ReadStat_DateTimeWithMicroseconds(v)
+            | ReadStatVar::ReadStat_DateTimeWithNanoseconds(v)
+            | ReadStatVar::ReadStat_Date64(v)
+            | ReadStatVar::ReadStat_TimeWithMicroseconds(v) => *v,
+            _ => None,
+        };
+
+        match (value, number, instant) {
+            (ReadStatVar::ReadStat_String(Some(s)), _, _) => {
+                self.count += 1;
+                let length = s.chars().count();
+                self.max_length = Some(self.max_length.map_or(length, |m| m.max(length)));
+                if !self.distinct.contains(s) {
+                    if self.distinct.len() < self.distinct_cap {
+                        self.distinct.insert(s.clone());
+                    } else {
+                        self.distinct_capped = Some(true);
+                    }
+                }
+            }
+            (ReadStatVar::ReadStat_Bool(Some(_)), _, _) => self.count += 1,
+            (_, Some(v), _) => {
+                self.count += 1;
+                self.sum += v;
+                self.min = Some(self.min.map_or(v, |m| m.min(v)));
+                self.max = Some(self.max.map_or(v, |m| m.max(v)));
+            }
+            (_, _, Some(v)) => {
+                self.count += 1;
+                self.range = Some(self.range.map_or((v, v), |(lo, hi)| (lo.min(v), hi.max(v))));
+            }
+            _ => self.missing_count += 1,
+        }
+    }
+
+    // Statistics derived once every value has been pushed
+    pub(crate) fn finish(&mut self) {
+        if self.kind == ReadStatSummaryKind::Numeric && self.count > 0 {
+            self.mean = Some(self.sum / self.count as f64);
+        }
+        if self.kind == ReadStatSummaryKind::String {
+            self.distinct_count = Some(self.distinct.len() as u64);
+        }
+        if let Some((lo, hi)) = self.range {
+            self.earliest = Some(format_instant(lo, &self.data_type));
+            self.latest = Some(format_instant(hi, &self.data_type));
+        }
+    }
+}
+
+/// Per-column statistics of a dataset, gathered by a single pass that keeps no values
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadStatSummary {
+    pub table_name: String,
+    pub row_count: u64,
+    pub columns: Vec<ReadStatColumnSummary>,
+}
+
+impl ReadStatSummary {
+    /// Summarize every column of the file described by `rsp`
+    ///
+    /// Values update running statistics as they are parsed rather than being held in Arrow
+    /// arrays, so memory is proportional to the number of columns rather than rows.  At most
+    /// `distinct_cap` distinct strings are counted exactly for each column.
+    pub fn new(
+        rsp: &ReadStatPath,
+        distinct_cap: usize,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        // Read metadata; variables sharing a name are summarized apart under their renamed names
+        let mut md = ReadStatMetadata::new().set_dedup_columns(true);
+        md.read_metadata(rsp, false)?;
+
+        let row_count = md.row_count.max(0) as u32;
+        let mut columns = if row_count > 0 {
+            let mut d = ReadStatData::new()
+                .set_no_progress(true)
+                .set_summarize(true)
+                .set_distinct_cap(distinct_cap)
+                .init(md.clone(), 0, row_count);
+            d.read_data(rsp)?;
+            d.into_summaries()
+        } else {
+            md.schema
+                .fields
+                .iter()
+                .map(|f| ReadStatColumnSummary::new(&f.name, f.data_type(), distinct_cap))
+                .collect()
+        };
+
+        for c in columns.iter_mut() {
+            c.finish();
+        }
+
+        Ok(Self {
+            table_name: md.table_name.clone(),
+            row_count: row_count as u64,
+            columns,
+        })
+    }
+
+    /// The statistics of each column as a table for display
+    ///
+    /// Min and max hold the earliest and latest of dates, times, and datetimes; a distinct count
+    /// that reached the cap is prefixed with `>=`.
+    pub fn to_table(&self) -> String {
+        let header: Vec<String> = [
+            "Variable",
+            "Kind",
+            "Count",
+            "Missing",
+            "Min",
+            "Max",
+            "Mean",
+            "Distinct",
+            "Max length",
+        ]
+        .iter()
+        .map(|h| h.to_string())
+        .collect();
+
+        let dash = || String::from("-");
+        let rows: Vec<Vec<String>> = self
+            .columns
+            .iter()
+            .map(|c| {
+                let (min, max) = match (c.min, c.max, &c.earliest, &c.latest) {
+                    (Some(lo), Some(hi), _, _) => (format_f64(lo), format_f64(hi)),
+                    (_, _, Some(lo), Some(hi)) => (lo.clone(), hi.clone()),
+                    _ => (dash(), dash()),
+                };
+                let distinct = match (c.distinct_count, c.distinct_capped) {
+                    (Some(n), Some(true)) => format!(">={}", n),
+                    (Some(n), _) => n.to_string(),
+                    (None, _) => dash(),
+                };
+                vec![
+                    c.var_name.clone(),
+                    format!("{:?}", c.kind),
+                    c.count.to_string(),
+                    c.missing_count.to_string(),
+                    min,
+                    max,
+                    c.mean.map_or_else(dash, format_f64),
+                    distinct,
+                    c.max_length.map_or_else(dash, |l| l.to_string()),
+                ]
+            })
+            .collect();
+
+        // width of each column
+        let mut widths: Vec<usize> = header.iter().map(|h| h.len()).collect();
+        for r in &rows {
+            for (w, c) in widths.iter_mut().zip(r.iter()) {
+                *w = (*w).max(c.chars().count());
+            }
+        }
+
+        let line = |cells: &[String]| -> String {
+            cells
+                .iter()
+                .zip(widths.iter())
+                .map(|(c, w)| format!("{:<1$}", c, w))
+                .collect::<Vec<String>>()
+                .join("  ")
+                .trim_end()
+                .to_owned()
+        };
+
+        let dashes: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+
+        let mut table = String::new();
+        for r in [&header, &dashes].into_iter().chain(rows.iter()) {
+            table.push_str(&line(r));
+            table.push('\n');
+        }
+        table
+    }
+}
+
+// Dates as e.g. 2021-01-01, times as e.g. 10:49:39, and datetimes as e.g. 2021-01-01 10:49:39,
+//   with fractions of a second only when held (e.g. 10:49:39.333); an out-of-range value is
+//   written as is
+fn format_instant(v: i64, data_type: &DataType) -> String {
+    let (units_per_second, format) = match data_type {
+        DataType::Date32 => (0, "%Y-%m-%d"),
+        DataType::Date64 => (1_000, "%Y-%m-%d"),
+        DataType::Time32(_) => (1, "%H:%M:%S"),
+        DataType::Time64(TimeUnit::Microsecond) => (1_000_000, "%H:%M:%S%.f"),
+        DataType::Time64(_) => (1_000_000_000, "%H:%M:%S%.f"),
+        DataType::Timestamp(unit, _) => (
+            match unit {
+                TimeUnit::Second => 1,
+                TimeUnit::Millisecond => 1_000,
+                TimeUnit::Microsecond => 1_000_000,
+                TimeUnit::Nanosecond => 1_000_000_000,
+            },
+            "%Y-%m-%d %H:%M:%S%.f",
+        ),
+        _ => return v.to_string(),
+    };
+
+    // days, for Date32, are counted in seconds
+    let (units, units_per_second) = match units_per_second {
+        0 => (v.saturating_mul(86_400), 1),
+        u => (v, u),
+    };
+    let secs = units.div_euclid(units_per_second);
+    let nanos = (units.rem_euclid(units_per_second) * (1_000_000_000 / units_per_second)) as u32;
+
+    let formatted = match data_type {
+        DataType::Time32(_) | DataType::Time64(_) => u32::try_from(secs)
+            .ok()
+            .and_then(|s| NaiveTime::from_num_seconds_from_midnight_opt(s, nanos))
+            .map(|t| t.format(format).to_string()),
+        _ => DateTime::from_timestamp(secs, nanos).map(|dt| dt.format(format).to_string()),
+    };
+    formatted.unwrap_or_else(|| v.to_string())
+}
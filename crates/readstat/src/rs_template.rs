@@ -0,0 +1,223 @@
+use colored::Colorize;
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use crate::rs_io::without_compression_extension;
+use crate::rs_metadata::ReadStatMetadata;
+use crate::rs_path::ReadStatPath;
+use crate::OutFormat;
+
+const PLACEHOLDERS: [&str; 5] = ["stem", "table_name", "date", "subdir", "index"];
+
+#[derive(Clone, Debug, PartialEq)]
+enum Part {
+    Literal(String),
+    Stem,
+    TableName,
+    Date,
+    Subdir,
+    Index,
+}
+
+/// Template from which the name of each output file is rendered, e.g. `{stem}_{date}.parquet`
+///
+/// Placeholders are
+/// - `{stem}` &rarr; file name of the input without its extension
+/// - `{table_name}` &rarr; table name within the metadata, without surrounding whitespace and with
+///   its case unchanged (SAS typically writes it in upper case, e.g. `CARS`)
+/// - `{date}` &rarr; creation date within the metadata, as `YYYY-MM-DD`
+/// - `{subdir}` &rarr; name of the directory containing the input
+/// - `{index}` &rarr; position of the input among those converted, starting from 1
+///
+/// Should the template not end with an extension, that of the output format is appended.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadStatOutputTemplate {
+    template: String,
+    parts: Vec<Part>,
+}
+
+impl ReadStatOutputTemplate {
+    pub fn parse(template: &str) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let invalid = |why: String| -> Box<dyn Error + Send + Sync> {
+            From::from(format!(
+                "The output template {} is invalid: {}",
+                template.bright_yellow(),
+                why
+            ))
+        };
+
+        let mut parts = Vec::new();
+        let mut rest = template;
+
+        while let Some(open) = rest.find(['{', '}']) {
+            if rest[open..].starts_with('}') {
+                return Err(invalid(String::from("} without a matching {")));
+            }
+            if open > 0 {
+                parts.push(Part::Literal(String::from(&rest[..open])));
+            }
+
+            let close = rest[open..]
+                .find('}')
+                .ok_or_else(|| invalid(String::from("{ without a matching }")))?;
+            let name = &rest[open + 1..open + close];
+
+            parts.push(match name {
+                "stem" => Part::Stem,
+                "table_name" => Part::TableName,
+                "date" => Part::Date,
+                "subdir" => Part::Subdir,
+                "index" => Part::Index,
+                _ => {
+                    return Err(invalid(format!(
+                        "unknown placeholder {{{}}}; expecting one of {}",
+                        name,
+                        PLACEHOLDERS.map(|p| format!("{{{}}}", p)).join(", ")
+                    )))
+                }
+            });
+
+            rest = &rest[open + close + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Literal(String::from(rest)));
+        }
+
+        if parts.is_empty() {
+            return Err(invalid(String::from("it is empty")));
+        }
+
+        Ok(Self {
+            template: String::from(template),
+            parts,
+        })
+    }
+
+    /// Whether rendering requires the metadata of each input
+    pub fn needs_metadata(&self) -> bool {
+        self.parts
+            .iter()
+            .any(|p| matches!(p, Part::TableName | Part::Date))
+    }
+
+    /// Extension given by the text following the last placeholder, if any
+    pub fn extension(&self) -> Option<&str> {
+        match self.parts.last() {
+            Some(Part::Literal(s)) => s.rsplit_once('.').map(|(_, e)| e),
+            _ => None,
+        }
+    }
+
+    /// File name for `input`, the `index`-th input (starting from 1), whose metadata is `md`
+    ///
+    /// Metadata is only needed should the template contain `{table_name}` or `{date}`; without it
+    /// both render as `unknown`.
+    pub fn render(&self, input: &Path, md: Option<&ReadStatMetadata>, index: usize) -> String {
+        let stem = without_compression_extension(input)
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned());
+        let subdir = input
+            .parent()
+            .and_then(|p| p.file_name())
+            .map(|s| s.to_string_lossy().into_owned());
+        let table_name = md.map(|m| m.table_name.trim().to_owned());
+        let date = md.and_then(|m| m.creation_time.get(..10).map(String::from));
+
+        self.parts
+            .iter()
+            .map(|p| match p {
+                Part::Literal(s) => s.clone(),
+                Part::Stem => sanitize(stem.as_deref()),
+                Part::TableName => sanitize(table_name.as_deref()),
+                Part::Date => sanitize(date.as_deref()),
+                Part::Subdir => sanitize(subdir.as_deref()),
+                Part::Index => index.to_string(),
+            })
+            .collect()
+    }
+
+    /// Output path of every input, in the same order as `inputs`, written to `out_dir` or else next
+    /// to each input
+    ///
+    /// An input whose metadata cannot be read has its error in place of a path.  Should the
+    /// extension disagree with `format`, or two inputs be given the same path, an error is returned
+    /// instead so that nothing is converted.  Paths that differ only in case are treated as the
+    /// same, as they would be on a case-insensitive file system.
+    pub fn resolve(
+        &self,
+        inputs: &[PathBuf],
+        out_dir: Option<&Path>,
+        format: OutFormat,
+    ) -> Result<Vec<Result<PathBuf, String>>, Box<dyn Error + Send + Sync>> {
+        let mut outputs = Vec::with_capacity(inputs.len());
+        let mut seen: BTreeMap<String, &Path> = BTreeMap::new();
+
+        for (i, input) in inputs.iter().enumerate() {
+            let md = if self.needs_metadata() {
+                match read_metadata(input) {
+                    Ok(md) => Some(md),
+                    Err(e) => {
+                        outputs.push(Err(e.to_string()));
+                        continue;
+                    }
+                }
+            } else {
+                None
+            };
+
+            let mut name = self.render(input, md.as_ref(), i + 1);
+            match self.extension() {
+                None => name = format!("{}.{}", name, format),
+                Some(e) if e == format.to_string() => (),
+                Some(e) => {
+                    return Err(From::from(format!(
+                        "The output template {} gives the extension {} but the format is {}",
+                        self.template.bright_yellow(),
+                        e.bright_red(),
+                        format.to_string().bright_green()
+                    )))
+                }
+            }
+
+            let output = match out_dir {
+                Some(dir) => dir.join(name),
+                None => input.with_file_name(name),
+            };
+
+            let folded = output.to_string_lossy().to_lowercase();
+            if let Some(first) = seen.insert(folded, input) {
+                return Err(From::from(format!(
+                    "The inputs {} and {} would both be written to {}; consider adding {} or {} to the output template",
+                    first.to_string_lossy().bright_yellow(),
+                    input.to_string_lossy().bright_yellow(),
+                    output.to_string_lossy().bright_red(),
+                    String::from("{stem}").bright_cyan(),
+                    String::from("{index}").bright_cyan()
+                )));
+            }
+
+            outputs.push(Ok(output));
+        }
+
+        Ok(outputs)
+    }
+}
+
+fn read_metadata(input: &Path) -> Result<ReadStatMetadata, Box<dyn Error + Send + Sync>> {
+    let rsp = ReadStatPath::new(input.to_path_buf(), None, None, false, false, None, None)?;
+    // only the table name and file label are rendered, so variables sharing a name do no harm
+    let mut md = ReadStatMetadata::new().set_dedup_columns(true);
+    md.read_metadata(&rsp, true)?;
+    Ok(md)
+}
+
+// Placeholders render as a single path component
+fn sanitize(value: Option<&str>) -> String {
+    match value {
+        Some(v) if !v.is_empty() => v.replace(['/', '\\'], "_"),
+        _ => String::from("unknown"),
+    }
+}
@@ -0,0 +1,54 @@
+use serde::Serialize;
+use std::fmt;
+
+/// A missing value, or range of missing values, declared by a variable
+///
+/// SPSS variables may declare up to three discrete missing values, or a range along with an
+/// optional discrete value; strings may only declare discrete values.  ReadStat reports each
+/// discrete value as a range whose ends are equal.  SAS and Stata files declare none, their
+/// missing values being tagged instead (see [`missing_tag_name`](crate::missing_tag_name)).
+///
+/// Within the metadata JSON a numeric range is `{"lo": 1.0, "hi": 3.0}`, with `null` for an open
+/// end, and a string is `{"value": "NA"}`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ReadStatMissingRange {
+    /// Values from `lo` through `hi`, inclusive; `None` for an open end, as `LO THRU 0` or
+    /// `99 THRU HI` declare
+    Numeric { lo: Option<f64>, hi: Option<f64> },
+    /// A single string value
+    String { value: String },
+}
+
+impl ReadStatMissingRange {
+    // ReadStat reports the open ends of a range as infinite
+    pub(crate) fn numeric(lo: f64, hi: f64) -> Self {
+        Self::Numeric {
+            lo: Some(lo).filter(|v| v.is_finite()),
+            hi: Some(hi).filter(|v| v.is_finite()),
+        }
+    }
+
+    /// Whether the range holds a single value rather than a span of values
+    pub fn is_discrete(&self) -> bool {
+        match self {
+            Self::Numeric { lo, hi } => lo.is_some() && lo == hi,
+            Self::String { .. } => true,
+        }
+    }
+}
+
+/// As SPSS displays it, e.g. `9`, `LO THRU -1`, `120 THRU HI`, or `"NA"`
+impl fmt::Display for ReadStatMissingRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Numeric { lo: Some(lo), .. } if self.is_discrete() => write!(f, "{}", lo),
+            Self::Numeric { lo, hi } => {
+                let end =
+                    |v: &Option<f64>, open: &str| v.map_or(String::from(open), |v| v.to_string());
+                write!(f, "{} THRU {}", end(lo, "LO"), end(hi, "HI"))
+            }
+            Self::String { value } => write!(f, "{:?}", value.trim_end()),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use arrow2::datatypes::DataType;
+use serde::Serialize;
+
+use crate::rs_var::ReadStatVar;
+
+/// A labelled value, numeric or string as the variables it labels
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ReadStatLabelValue {
+    Numeric(f64),
+    String(String),
+}
+
+/// A value and its label, e.g. 1 and `Male`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ReadStatValueLabel {
+    pub value: ReadStatLabelValue,
+    pub label: String,
+}
+
+/// Labels of the values of the variables sharing a user-defined SAS format, as held by a format
+/// catalog (`sas7bcat`)
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ReadStatLabelSet {
+    pub labels: Vec<ReadStatValueLabel>,
+}
+
+impl ReadStatLabelSet {
+    /// Label `value`, replacing any label it already has
+    pub fn insert(&mut self, value: ReadStatLabelValue, label: String) {
+        match self.labels.iter_mut().find(|l| l.value == value) {
+            Some(l) => l.label = label,
+            None => self.labels.push(ReadStatValueLabel { value, label }),
+        }
+    }
+
+    /// Label of `value`, or `None` when missing or unlabelled
+    ///
+    /// SAS pads strings with spaces, so strings are compared without trailing whitespace.
+    pub fn label(&self, value: &ReadStatVar) -> Option<&str> {
+        let number = match value {
+            ReadStatVar::ReadStat_String(Some(s)) => {
+                let s = s.trim_end();
+                return self
+                    .labels
+                    .iter()
+                    .find(|l| match &l.value {
+                        ReadStatLabelValue::String(v) => v.trim_end() == s,
+                        ReadStatLabelValue::Numeric(_) => false,
+                    })
+                    .map(|l| l.label.as_str());
+            }
+            ReadStatVar::ReadStat_i8(Some(v)) => *v as f64,
+            ReadStatVar::ReadStat_i16(Some(v)) => *v as f64,
+            ReadStatVar::ReadStat_i32(Some(v)) => *v as f64,
+            ReadStatVar::ReadStat_i64(Some(v)) => *v as f64,
+            ReadStatVar::ReadStat_f32(Some(v)) => *v as f64,
+            ReadStatVar::ReadStat_f64(Some(v)) => *v,
+            _ => return None,
+        };
+
+        self.labels
+            .iter()
+            .find(|l| l.value == ReadStatLabelValue::Numeric(number))
+            .map(|l| l.label.as_str())
+    }
+
+    /// `value` as its label, or when unlabelled as its text (e.g. `3` for 3.0); missing values
+    /// stay missing
+    pub(crate) fn apply(&self, value: ReadStatVar) -> ReadStatVar {
+        match self.label(&value) {
+            Some(l) => ReadStatVar::ReadStat_String(Some(String::from(l))),
+            None => ReadStatVar::ReadStat_String(value.to_text()),
+        }
+    }
+}
+
+/// Value labels applied to a variable, which is then written as the text of its labels
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadStatLabelling {
+    /// Name of the label set, i.e. of its SAS format
+    pub set_name: String,
+    pub labels: ReadStatLabelSet,
+    /// Arrow type of the variable without its labels, which its values are read as
+    pub source_type: DataType,
+}
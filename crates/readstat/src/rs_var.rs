@@ -1,14 +1,60 @@
+use arrow2::datatypes::{DataType, TimeUnit};
 use log::debug;
 use num_derive::FromPrimitive;
 use serde::Serialize;
-use std::{collections::BTreeMap, os::raw::c_int};
+use std::{collections::BTreeMap, error::Error, os::raw::c_int};
 
-use crate::{common::ptr_to_string, rs_metadata::ReadStatVarMetadata};
+use crate::{
+    common::{format_f32, format_f64, ptr_to_string},
+    conversions::{
+        sas_days_to_date32, sas_seconds_to_duration, sas_seconds_to_time32, sas_seconds_to_time64,
+        sas_seconds_to_timestamp, sas_seconds_to_timestamp_in, within_day, OutOfRange,
+    },
+    rs_date_window::ReadStatDateWindow,
+    rs_metadata::ReadStatVarMetadata,
+    FloatHandling,
+};
 
 // Constants
-const DIGITS: usize = 14;
-const DAY_SHIFT: i32 = 3653;
-const SEC_SHIFT: i64 = 315619200;
+const DIGITS: i32 = 14;
+// Days from the SPSS epoch, 1582-10-14, to the SAS epoch, 1960-01-01
+const SPSS_DAY_SHIFT: f64 = 137775.0;
+const SECONDS_PER_DAY: f64 = 86400.0;
+
+/// How the file read stores dates, datetimes, and times, each converted to the SAS convention
+/// of days or seconds since 1960-01-01 as read
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) enum ReadStatEpoch {
+    #[default]
+    Sas,
+    /// Seconds since 1582-10-14, dates included
+    Spss,
+    /// Days or milliseconds since 1960-01-01, with times of day as milliseconds
+    Stata,
+}
+
+impl ReadStatEpoch {
+    pub(crate) fn of_extension(extension: &str) -> Self {
+        match extension {
+            "sav" => Self::Spss,
+            "dta" => Self::Stata,
+            _ => Self::Sas,
+        }
+    }
+
+    fn to_sas(self, value: f64, fc: ReadStatVarFormatClass) -> f64 {
+        match (self, fc) {
+            (Self::Sas, _) | (Self::Stata, ReadStatVarFormatClass::Date) => value,
+            (Self::Spss, ReadStatVarFormatClass::Date) => {
+                value / SECONDS_PER_DAY - SPSS_DAY_SHIFT
+            }
+            (Self::Spss, ReadStatVarFormatClass::Time)
+            | (Self::Spss, ReadStatVarFormatClass::TimeWithMicroseconds) => value,
+            (Self::Spss, _) => value - SPSS_DAY_SHIFT * SECONDS_PER_DAY,
+            (Self::Stata, _) => value / 1000.0,
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum ReadStatVar {
@@ -16,6 +62,7 @@ pub enum ReadStatVar {
     ReadStat_i8(Option<i8>),
     ReadStat_i16(Option<i16>),
     ReadStat_i32(Option<i32>),
+    ReadStat_i64(Option<i64>),
     ReadStat_f32(Option<f32>),
     ReadStat_f64(Option<f64>),
     ReadStat_Date(Option<i32>),
@@ -23,22 +70,29 @@ pub enum ReadStatVar {
     ReadStat_DateTimeWithMilliseconds(Option<i64>),
     ReadStat_DateTimeWithMicroseconds(Option<i64>),
     ReadStat_DateTimeWithNanoseconds(Option<i64>),
+    ReadStat_Date64(Option<i64>),
+    ReadStat_Bool(Option<bool>),
     ReadStat_Time(Option<i32>),
+    ReadStat_TimeWithMicroseconds(Option<i64>),
     // TODO
     // ReadStat_TimeWithMilliseconds(Option<i32>),
-    // ReadStat_TimeWithMicroseconds(Option<i32>),
-    // ReadStat_TimeWithNanoseconds(Option<i32>),
+    // ReadStat_TimeWithNanoseconds(Option<i64>),
 }
 
 impl ReadStatVar {
-    pub fn get_readstat_value(
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn get_readstat_value(
         value: readstat_sys::readstat_value_t,
         value_type: readstat_sys::readstat_type_t,
         is_missing: c_int,
         vars: &BTreeMap<i32, ReadStatVarMetadata>,
         var_index: i32,
-    ) -> Self {
-        match value_type {
+        data_type: &DataType,
+        float_handling: FloatHandling,
+        epoch: ReadStatEpoch,
+        date_window: Option<ReadStatDateWindow>,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let value = match value_type {
             readstat_sys::readstat_type_e_READSTAT_TYPE_STRING
             | readstat_sys::readstat_type_e_READSTAT_TYPE_STRING_REF => {
                 if is_missing == 1 {
@@ -106,12 +160,15 @@ impl ReadStatVar {
                     let value = unsafe { readstat_sys::readstat_float_value(value) };
 
                     // debug
-                    debug!("value (before parsing) is {:#?}", value);
+                    debug!("value (before rounding) is {:#?}", value);
 
-                    let value: f32 = lexical::parse(format!("{1:.0$}", DIGITS, value)).unwrap();
+                    let value = match float_handling {
+                        FloatHandling::ReadStatDigits => round_to_digits(value as f64) as f32,
+                        FloatHandling::Raw => value,
+                    };
 
                     // debug
-                    debug!("value (after parsing) is {:#?}", value);
+                    debug!("value (after rounding) is {:#?}", value);
 
                     // return
                     Self::ReadStat_f32(Some(value))
@@ -122,20 +179,27 @@ impl ReadStatVar {
 
                 if is_missing == 1 {
                     match var_format_class {
+                        None if matches!(data_type, DataType::Boolean) => Self::ReadStat_Bool(None),
+                        None if matches!(data_type, DataType::Int64) => Self::ReadStat_i64(None),
                         None => Self::ReadStat_f64(None),
                         Some(fc) => match fc {
                             ReadStatVarFormatClass::Date => Self::ReadStat_Date(None),
-                            ReadStatVarFormatClass::DateTime => Self::ReadStat_DateTime(None),
-                            ReadStatVarFormatClass::DateTimeWithMilliseconds => {
-                                Self::ReadStat_DateTimeWithMilliseconds(None)
+                            ReadStatVarFormatClass::DateTime
+                            | ReadStatVarFormatClass::DateTimeWithMilliseconds
+                            | ReadStatVarFormatClass::DateTimeWithMicroseconds
+                            | ReadStatVarFormatClass::DateTimeWithNanoseconds => {
+                                Self::get_datetime_value(None, data_type)?
                             }
-                            ReadStatVarFormatClass::DateTimeWithMicroseconds => {
-                                Self::ReadStat_DateTimeWithMicroseconds(None)
-                            }
-                            ReadStatVarFormatClass::DateTimeWithNanoseconds => {
-                                Self::ReadStat_DateTimeWithNanoseconds(None)
+                            ReadStatVarFormatClass::Time
+                            | ReadStatVarFormatClass::TimeWithMicroseconds
+                                if matches!(data_type, DataType::Int64) =>
+                            {
+                                Self::ReadStat_i64(None)
                             }
                             ReadStatVarFormatClass::Time => Self::ReadStat_Time(None),
+                            ReadStatVarFormatClass::TimeWithMicroseconds => {
+                                Self::ReadStat_TimeWithMicroseconds(None)
+                            }
                         },
                     }
                 } else {
@@ -143,49 +207,293 @@ impl ReadStatVar {
                     let value = unsafe { readstat_sys::readstat_double_value(value) };
 
                     // debug
-                    debug!("value (before parsing) is {:#?}", value);
+                    debug!("value (before rounding) is {:#?}", value);
 
-                    let value: f64 = lexical::parse(format!("{1:.0$}", DIGITS, value)).unwrap();
+                    let value = match float_handling {
+                        FloatHandling::ReadStatDigits => round_to_digits(value),
+                        FloatHandling::Raw => value,
+                    };
 
                     // debug
-                    debug!("value (after parsing) is {:#?}", value);
+                    debug!("value (after rounding) is {:#?}", value);
+
+                    // reported as read should the date, time, or datetime be out of range
+                    let read = value;
+
+                    // dates, times, and datetimes as SAS stores them, whatever the file read
+                    let value = match var_format_class {
+                        Some(fc) => epoch.to_sas(value, fc),
+                        None => value,
+                    };
 
                     // is double a value or is it really a date, time, or datetime?
                     match var_format_class {
+                        None if matches!(data_type, DataType::Boolean) => {
+                            if value == 0.0 {
+                                Self::ReadStat_Bool(Some(false))
+                            } else if value == 1.0 {
+                                Self::ReadStat_Bool(Some(true))
+                            } else {
+                                return Err(From::from(format!(
+                                    "The value {} cannot be stored as a boolean as it is neither 0 nor 1",
+                                    value
+                                )));
+                            }
+                        }
+                        // i64::MAX as f64 rounds up to 2^63, which is itself out of range
+                        None if matches!(data_type, DataType::Int64) => {
+                            if value.fract() == 0.0
+                                && value >= i64::MIN as f64
+                                && value < i64::MAX as f64
+                            {
+                                Self::ReadStat_i64(Some(value as i64))
+                            } else {
+                                return Err(From::from(format!(
+                                    "The value {} cannot be stored as an integer as it is not a whole number within the range of Int64",
+                                    value
+                                )));
+                            }
+                        }
                         None => Self::ReadStat_f64(Some(value)),
                         Some(fc) => match fc {
-                            ReadStatVarFormatClass::Date => Self::ReadStat_Date(Some(
-                                (value as i32).checked_sub(DAY_SHIFT).unwrap(),
-                            )),
-                            ReadStatVarFormatClass::DateTime => Self::ReadStat_DateTime(Some(
-                                (value as i64).checked_sub(SEC_SHIFT).unwrap(),
-                            )),
-                            ReadStatVarFormatClass::DateTimeWithMilliseconds => {
-                                Self::ReadStat_DateTime(Some(
-                                    (value as i64).checked_sub(SEC_SHIFT).unwrap() * 1000,
+                            ReadStatVarFormatClass::Date => {
+                                let days = sas_days_to_date32(value)
+                                    .ok_or_else(|| out_of_range(read, data_type))?;
+                                if let Some(w) = date_window.filter(|w| !w.contains_date32(days)) {
+                                    return Err(beyond_window(read, data_type, w));
+                                }
+                                Self::ReadStat_Date(Some(days))
+                            }
+                            ReadStatVarFormatClass::DateTime
+                            | ReadStatVarFormatClass::DateTimeWithMilliseconds
+                            | ReadStatVarFormatClass::DateTimeWithMicroseconds
+                            | ReadStatVarFormatClass::DateTimeWithNanoseconds => {
+                                // a datetime chrono cannot hold is beyond the range of its type
+                                let seconds = sas_seconds_to_timestamp(value);
+                                if let Some(w) = date_window.filter(|w| {
+                                    seconds.is_some_and(|t| !w.contains_timestamp(t))
+                                }) {
+                                    return Err(beyond_window(read, data_type, w));
+                                }
+                                Self::get_datetime_value(Some(value), data_type).map_err(|e| {
+                                    if e.is::<OutOfRange>() {
+                                        out_of_range(read, data_type)
+                                    } else {
+                                        e
+                                    }
+                                })?
+                            }
+                            // durations are kept whatever their length or sign
+                            ReadStatVarFormatClass::Time
+                                if matches!(data_type, DataType::Int64) =>
+                            {
+                                Self::ReadStat_i64(Some(
+                                    sas_seconds_to_duration(value, TimeUnit::Second)
+                                        .ok_or_else(|| out_of_range(read, data_type))?,
                                 ))
                             }
-                            ReadStatVarFormatClass::DateTimeWithMicroseconds => {
-                                Self::ReadStat_DateTime(Some(
-                                    (value as i64).checked_sub(SEC_SHIFT).unwrap() * 1000000,
+                            ReadStatVarFormatClass::TimeWithMicroseconds
+                                if matches!(data_type, DataType::Int64) =>
+                            {
+                                Self::ReadStat_i64(Some(
+                                    sas_seconds_to_duration(value, TimeUnit::Microsecond)
+                                        .ok_or_else(|| out_of_range(read, data_type))?,
                                 ))
                             }
-                            ReadStatVarFormatClass::DateTimeWithNanoseconds => {
-                                Self::ReadStat_DateTime(Some(
-                                    (value as i64).checked_sub(SEC_SHIFT).unwrap() * 1000000000,
+                            // a time of day beyond 24 hours or negative is not wrapped
+                            ReadStatVarFormatClass::Time => Self::ReadStat_Time(Some(
+                                sas_seconds_to_time32(value)
+                                    .filter(|t| within_day(i64::from(*t), 1))
+                                    .ok_or_else(|| out_of_range(read, data_type))?,
+                            )),
+                            // fractional seconds are rounded to the nearest microsecond
+                            ReadStatVarFormatClass::TimeWithMicroseconds => {
+                                Self::ReadStat_TimeWithMicroseconds(Some(
+                                    sas_seconds_to_time64(value)
+                                        .filter(|t| within_day(*t, 1_000_000))
+                                        .ok_or_else(|| out_of_range(read, data_type))?,
                                 ))
                             }
-                            ReadStatVarFormatClass::Time => Self::ReadStat_Time(Some(value as i32)),
                         },
                     }
                 }
             }
             // exhaustive
             _ => unreachable!(),
+        };
+
+        Ok(value)
+    }
+
+    /// The missing value of the same kind
+    pub(crate) fn into_missing(self) -> Self {
+        match self {
+            Self::ReadStat_String(_) => Self::ReadStat_String(None),
+            Self::ReadStat_i8(_) => Self::ReadStat_i8(None),
+            Self::ReadStat_i16(_) => Self::ReadStat_i16(None),
+            Self::ReadStat_i32(_) => Self::ReadStat_i32(None),
+            Self::ReadStat_i64(_) => Self::ReadStat_i64(None),
+            Self::ReadStat_f32(_) => Self::ReadStat_f32(None),
+            Self::ReadStat_f64(_) => Self::ReadStat_f64(None),
+            Self::ReadStat_Date(_) => Self::ReadStat_Date(None),
+            Self::ReadStat_DateTime(_) => Self::ReadStat_DateTime(None),
+            Self::ReadStat_DateTimeWithMilliseconds(_) => {
+                Self::ReadStat_DateTimeWithMilliseconds(None)
+            }
+            Self::ReadStat_DateTimeWithMicroseconds(_) => {
+                Self::ReadStat_DateTimeWithMicroseconds(None)
+            }
+            Self::ReadStat_DateTimeWithNanoseconds(_) => {
+                Self::ReadStat_DateTimeWithNanoseconds(None)
+            }
+            Self::ReadStat_Date64(_) => Self::ReadStat_Date64(None),
+            Self::ReadStat_Bool(_) => Self::ReadStat_Bool(None),
+            Self::ReadStat_Time(_) => Self::ReadStat_Time(None),
+            Self::ReadStat_TimeWithMicroseconds(_) => Self::ReadStat_TimeWithMicroseconds(None),
+        }
+    }
+
+    /// The missing value stored as `data_type`, or `None` for a type no value is stored as
+    pub(crate) fn missing(data_type: &DataType) -> Option<Self> {
+        let missing = match data_type {
+            DataType::Utf8 => Self::ReadStat_String(None),
+            DataType::Int8 => Self::ReadStat_i8(None),
+            DataType::Int16 => Self::ReadStat_i16(None),
+            DataType::Int32 => Self::ReadStat_i32(None),
+            DataType::Int64 => Self::ReadStat_i64(None),
+            DataType::Float32 => Self::ReadStat_f32(None),
+            DataType::Float64 => Self::ReadStat_f64(None),
+            DataType::Date32 => Self::ReadStat_Date(None),
+            DataType::Timestamp(TimeUnit::Second, _) => Self::ReadStat_DateTime(None),
+            DataType::Timestamp(TimeUnit::Millisecond, _) => {
+                Self::ReadStat_DateTimeWithMilliseconds(None)
+            }
+            DataType::Timestamp(TimeUnit::Microsecond, _) => {
+                Self::ReadStat_DateTimeWithMicroseconds(None)
+            }
+            DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+                Self::ReadStat_DateTimeWithNanoseconds(None)
+            }
+            DataType::Date64 => Self::ReadStat_Date64(None),
+            DataType::Boolean => Self::ReadStat_Bool(None),
+            DataType::Time32(_) => Self::ReadStat_Time(None),
+            DataType::Time64(_) => Self::ReadStat_TimeWithMicroseconds(None),
+            _ => return None,
+        };
+
+        Some(missing)
+    }
+
+    /// The value as text, or `None` when missing
+    ///
+    /// Numbers are written as within `csv`; dates, times, and datetimes as the integer stored
+    /// within Arrow.
+    pub(crate) fn to_text(&self) -> Option<String> {
+        match self {
+            Self::ReadStat_String(v) => v.clone(),
+            Self::ReadStat_i8(v) => v.map(|v| v.to_string()),
+            Self::ReadStat_i16(v) => v.map(|v| v.to_string()),
+            Self::ReadStat_i32(v) | Self::ReadStat_Date(v) | Self::ReadStat_Time(v) => {
+                v.map(|v| v.to_string())
+            }
+            Self::ReadStat_i64(v) => v.map(|v| v.to_string()),
+            Self::ReadStat_f32(v) => v.map(format_f32),
+            Self::ReadStat_f64(v) => v.map(format_f64),
+            Self::ReadStat_DateTime(v)
+            | Self::ReadStat_DateTimeWithMilliseconds(v)
+            | Self::ReadStat_DateTimeWithMicroseconds(v)
+            | Self::ReadStat_DateTimeWithNanoseconds(v)
+            | Self::ReadStat_Date64(v)
+            | Self::ReadStat_TimeWithMicroseconds(v) => v.map(|v| v.to_string()),
+            Self::ReadStat_Bool(v) => v.map(|v| v.to_string()),
+        }
+    }
+
+    // SAS datetimes are seconds since 1960-01-01; the unit written is the one chosen for the schema
+    fn get_datetime_value(
+        value: Option<f64>,
+        data_type: &DataType,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let (unit, to_var): (TimeUnit, fn(Option<i64>) -> Self) = match data_type {
+            DataType::Timestamp(TimeUnit::Second, _) => (TimeUnit::Second, Self::ReadStat_DateTime),
+            DataType::Timestamp(TimeUnit::Millisecond, _) => {
+                (TimeUnit::Millisecond, Self::ReadStat_DateTimeWithMilliseconds)
+            }
+            DataType::Timestamp(TimeUnit::Microsecond, _) => {
+                (TimeUnit::Microsecond, Self::ReadStat_DateTimeWithMicroseconds)
+            }
+            DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+                (TimeUnit::Nanosecond, Self::ReadStat_DateTimeWithNanoseconds)
+            }
+            DataType::Date64 => (TimeUnit::Millisecond, Self::ReadStat_Date64),
+            _ => {
+                return Err(From::from(format!(
+                    "Unable to store a datetime as the data type {:?}",
+                    data_type
+                )))
+            }
+        };
+
+        match value {
+            None => Ok(to_var(None)),
+            Some(v) => sas_seconds_to_timestamp_in(v, unit)
+                .map(|t| to_var(Some(t)))
+                .ok_or_else(|| out_of_range(v, data_type)),
         }
     }
 }
 
+// A date, datetime, or time beyond the range of its type, which the callback reads as missing
+fn out_of_range(value: f64, data_type: &DataType) -> Box<dyn Error + Send + Sync> {
+    Box::new(OutOfRange {
+        value,
+        data_type: data_type.clone(),
+        window: None,
+    })
+}
+
+// A date or datetime beyond the date window, which the callback also reads as missing
+fn beyond_window(
+    value: f64,
+    data_type: &DataType,
+    window: ReadStatDateWindow,
+) -> Box<dyn Error + Send + Sync> {
+    Box::new(OutOfRange {
+        value,
+        data_type: data_type.clone(),
+        window: Some(window),
+    })
+}
+
+// The value rounded to DIGITS decimal places, exactly as formatting with {:.14} and parsing would
+//   yet without allocating
+//
+// At or beyond 64 in magnitude a 14th decimal place is finer than an f64 resolves so the value is
+//   unchanged, as are NaN and infinity.  Below, the error of scaling is recovered with a fused
+//   multiply-add so that the exact scaled value is rounded, with ties to even.
+pub(crate) fn round_to_digits(value: f64) -> f64 {
+    let scale = 10f64.powi(DIGITS);
+
+    if !value.is_finite() || value.abs() >= 64.0 {
+        return value;
+    }
+
+    let scaled = value * scale;
+    let error = value.mul_add(scale, -scaled);
+    let floor = scaled.floor();
+    // the sign of the exact scaled value less its floor less one half, which is exactly zero only
+    //   for a tie
+    let above_half = (scaled - floor - 0.5) + error;
+    let rounded = if above_half > 0.0 || (above_half == 0.0 && floor % 2.0 != 0.0) {
+        floor + 1.0
+    } else {
+        floor
+    };
+
+    // zero keeps the sign of the value, as would -0.00000000000000
+    (rounded / scale).copysign(value)
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
 pub enum ReadStatVarFormatClass {
     Date,
@@ -194,6 +502,27 @@ pub enum ReadStatVarFormatClass {
     DateTimeWithMicroseconds,
     DateTimeWithNanoseconds,
     Time,
+    TimeWithMicroseconds,
+}
+
+/// How ReadStat reports the values of a variable are aligned when displayed
+///
+/// Only stored by some formats; unknown alignment is `None` within [`ReadStatVarMetadata`].
+#[derive(Clone, Copy, Debug, Eq, FromPrimitive, PartialEq, Serialize)]
+pub enum ReadStatVarAlignment {
+    Left = readstat_sys::readstat_alignment_e_READSTAT_ALIGNMENT_LEFT as isize,
+    Center = readstat_sys::readstat_alignment_e_READSTAT_ALIGNMENT_CENTER as isize,
+    Right = readstat_sys::readstat_alignment_e_READSTAT_ALIGNMENT_RIGHT as isize,
+}
+
+/// Level of measurement ReadStat reports for a variable
+///
+/// Only stored by some formats; an unknown measure is `None` within [`ReadStatVarMetadata`].
+#[derive(Clone, Copy, Debug, Eq, FromPrimitive, PartialEq, Serialize)]
+pub enum ReadStatVarMeasure {
+    Nominal = readstat_sys::readstat_measure_e_READSTAT_MEASURE_NOMINAL as isize,
+    Ordinal = readstat_sys::readstat_measure_e_READSTAT_MEASURE_ORDINAL as isize,
+    Scale = readstat_sys::readstat_measure_e_READSTAT_MEASURE_SCALE as isize,
 }
 
 #[derive(Clone, Copy, Debug, FromPrimitive, Serialize)]
@@ -0,0 +1,160 @@
+use arrow2::{
+    array::{Array, PrimitiveArray},
+    chunk::Chunk,
+    datatypes::DataType,
+};
+use serde::Serialize;
+use std::error::Error;
+
+use crate::common::build_offsets;
+use crate::rs_metadata::ReadStatMetadata;
+use crate::rs_path::ReadStatPath;
+use crate::rs_stats::for_each_chunk;
+
+/// Magnitude beyond which a double is assumed to have been decoded wrongly
+pub const NUMERIC_SANITY_HUGE: f64 = 1e300;
+
+/// Fraction of non-missing values that must be suspect before a column is flagged, by default
+pub const NUMERIC_SANITY_THRESHOLD: f64 = 0.5;
+
+// Suspect values kept for each column
+const SAMPLES: usize = 5;
+
+/// A suspect value along with its bits, e.g. `0x7fe1ccf385ebc8a0`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ReadStatRawValue {
+    pub value: f64,
+    pub bits: String,
+}
+
+/// Counts of the suspect values within a single floating point column
+///
+/// Values read with the wrong byte order or floating point representation (e.g. from VMS or
+/// mainframe sources) are typically subnormal, of absurd magnitude, or NaN.
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadStatNumericSanity {
+    pub var_name: String,
+    /// Non-missing values observed
+    pub values: u64,
+    pub subnormal: u64,
+    /// Magnitude beyond [`NUMERIC_SANITY_HUGE`], including infinities
+    pub huge: u64,
+    pub nan: u64,
+    /// The first few suspect values
+    pub samples: Vec<ReadStatRawValue>,
+    #[serde(skip_serializing)]
+    index: usize,
+}
+
+impl ReadStatNumericSanity {
+    pub fn suspect(&self) -> u64 {
+        self.subnormal + self.huge + self.nan
+    }
+
+    /// Fraction of the non-missing values that are suspect; 0 without any
+    pub fn suspect_fraction(&self) -> f64 {
+        if self.values == 0 {
+            0.0
+        } else {
+            self.suspect() as f64 / self.values as f64
+        }
+    }
+
+    fn push(&mut self, value: f64, subnormal: bool, bits: String) {
+        self.values += 1;
+
+        let suspect = if value.is_nan() {
+            self.nan += 1;
+            true
+        } else if value.abs() > NUMERIC_SANITY_HUGE {
+            self.huge += 1;
+            true
+        } else if subnormal {
+            self.subnormal += 1;
+            true
+        } else {
+            false
+        };
+
+        if suspect && self.samples.len() < SAMPLES {
+            self.samples.push(ReadStatRawValue { value, bits });
+        }
+    }
+}
+
+/// Numeric sanity of every floating point column, counted as the data streams past
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadStatNumericSanityReport {
+    pub table_name: String,
+    pub threshold: f64,
+    pub columns: Vec<ReadStatNumericSanity>,
+}
+
+impl ReadStatNumericSanityReport {
+    pub fn new(md: &ReadStatMetadata, threshold: f64) -> Self {
+        let columns = md
+            .schema
+            .fields
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| matches!(f.data_type(), DataType::Float32 | DataType::Float64))
+            .map(|(index, f)| ReadStatNumericSanity {
+                var_name: f.name.clone(),
+                values: 0,
+                subnormal: 0,
+                huge: 0,
+                nan: 0,
+                samples: Vec::new(),
+                index,
+            })
+            .collect();
+
+        Self {
+            table_name: md.table_name.clone(),
+            threshold,
+            columns,
+        }
+    }
+
+    pub fn push_chunk(&mut self, chunk: &Chunk<Box<dyn Array>>) {
+        for c in self.columns.iter_mut() {
+            let a = chunk.arrays()[c.index].as_any();
+
+            // subnormals are judged within the width stored, as an f32 subnormal is a normal f64
+            if let Some(a) = a.downcast_ref::<PrimitiveArray<f64>>() {
+                for v in a.iter().flatten() {
+                    c.push(*v, v.is_subnormal(), format!("{:#018x}", v.to_bits()));
+                }
+            } else if let Some(a) = a.downcast_ref::<PrimitiveArray<f32>>() {
+                for v in a.iter().flatten() {
+                    c.push(*v as f64, v.is_subnormal(), format!("{:#010x}", v.to_bits()));
+                }
+            }
+        }
+    }
+
+    /// Columns whose fraction of suspect values is at least `threshold`
+    pub fn flagged(&self) -> Vec<&ReadStatNumericSanity> {
+        self.columns
+            .iter()
+            .filter(|c| c.suspect() > 0 && c.suspect_fraction() >= self.threshold)
+            .collect()
+    }
+}
+
+/// Check the numeric sanity of the file described by `rsp`, reading `stream_rows` rows at a time
+pub fn check_numeric_sanity(
+    rsp: &ReadStatPath,
+    threshold: f64,
+    stream_rows: u32,
+) -> Result<ReadStatNumericSanityReport, Box<dyn Error + Send + Sync>> {
+    // variables sharing a name are reported apart under their renamed names
+    let mut md = ReadStatMetadata::new().set_dedup_columns(true);
+    md.read_metadata(rsp, false)?;
+
+    let mut report = ReadStatNumericSanityReport::new(&md, threshold);
+    let offsets = build_offsets(md.row_count as u32, stream_rows)?;
+    for_each_chunk(rsp, &md, &offsets, |chunk| report.push_chunk(chunk))?;
+
+    Ok(report)
+}
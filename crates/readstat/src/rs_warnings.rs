@@ -0,0 +1,309 @@
+use colored::Colorize;
+use num_format::{Locale, ToFormattedString};
+use serde::Serialize;
+use std::{collections::BTreeMap, fmt};
+
+/// Messages retained verbatim by a [`ReadStatWarnings`] unless set otherwise
+pub const WARNINGS_RETAINED: usize = 1000;
+
+/// Kind of problem a warning reports
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadStatWarningCategory {
+    /// A string value was not valid UTF-8 and so was converted lossily
+    LossyUtf8,
+    /// A date, datetime, or time was beyond the range of its Arrow type, or a date beyond the date
+    /// window (e.g. via --date-window), and so read as missing
+    DateRange,
+    /// The bytes of strings suggest the file declares the wrong encoding
+    Encoding,
+    /// A plain numeric is read as a date as its values look like dates (e.g. via --detect-dates)
+    DateDetection,
+    /// A user-defined format is missing from the format catalog (e.g. via --catalog)
+    ValueLabels,
+    /// A label or format was truncated (e.g. via --max-label-length)
+    Truncation,
+    /// A flag took precedence over a column spec (e.g. via --spec)
+    ColumnSpec,
+    /// The mem reader would exceed its limit (e.g. via --mem-limit)
+    MemLimit,
+    /// A write failed and was retried (e.g. via --write-retries)
+    WriteRetry,
+    /// A column was renamed as its name is not valid within the output (e.g. via --format sav)
+    OutputName,
+    /// Output was written directly as it is a named pipe or device (e.g. via --output /dev/stdout)
+    DirectOutput,
+    /// Only part of a damaged file was read (e.g. via --best-effort)
+    Salvage,
+}
+
+impl fmt::Display for ReadStatWarningCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LossyUtf8 => write!(f, "lossy utf-8"),
+            Self::DateRange => write!(f, "date range"),
+            Self::Encoding => write!(f, "encoding"),
+            Self::DateDetection => write!(f, "date detection"),
+            Self::ValueLabels => write!(f, "value labels"),
+            Self::Truncation => write!(f, "truncation"),
+            Self::ColumnSpec => write!(f, "column spec"),
+            Self::MemLimit => write!(f, "mem limit"),
+            Self::WriteRetry => write!(f, "write retry"),
+            Self::OutputName => write!(f, "output name"),
+            Self::DirectOutput => write!(f, "direct output"),
+            Self::Salvage => write!(f, "salvage"),
+        }
+    }
+}
+
+/// Warnings of one category for one column, counted rather than each retained
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ReadStatWarningGroup {
+    pub category: ReadStatWarningCategory,
+    /// Column as written, or `None` for warnings about the file as a whole
+    pub column: Option<String>,
+    pub count: usize,
+    /// Lowest and highest rows warned about, which need not be the first and last recorded as
+    /// batches read in parallel arrive out of order
+    pub first_row: Option<usize>,
+    pub last_row: Option<usize>,
+    /// Message of the first warning recorded, as an example
+    pub example: String,
+}
+
+impl ReadStatWarningGroup {
+    /// Single line describing every warning of the group
+    pub fn line(&self) -> String {
+        if self.count == 1 {
+            return self.example.clone();
+        }
+
+        let column = match &self.column {
+            Some(c) => format!(" for column {}", c.bright_yellow()),
+            None => String::new(),
+        };
+        let rows = match (self.first_row, self.last_row) {
+            (Some(first), Some(last)) => format!(
+                " in rows {} to {}",
+                first.to_formatted_string(&Locale::en),
+                last.to_formatted_string(&Locale::en)
+            ),
+            _ => String::new(),
+        };
+        format!(
+            "{}: {} {} warnings{}{}; e.g. {}",
+            "Warning".bright_yellow(),
+            self.count.to_formatted_string(&Locale::en),
+            self.category,
+            column,
+            rows,
+            self.example
+        )
+    }
+}
+
+/// Every warning of a run, grouped by category and column
+///
+/// A damaged file may warn about millions of values, so each warning is counted within its
+/// group while only the first [`WARNINGS_RETAINED`] messages are kept verbatim.
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadStatWarnings {
+    /// In order of category, then column
+    groups: Vec<ReadStatWarningGroup>,
+    /// Messages kept verbatim, in the order recorded
+    pub retained: Vec<String>,
+    /// Messages counted within their groups yet not retained
+    pub dropped: usize,
+    #[serde(skip)]
+    retain: usize,
+    #[serde(skip)]
+    index: BTreeMap<(ReadStatWarningCategory, Option<String>), usize>,
+}
+
+impl Default for ReadStatWarnings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReadStatWarnings {
+    pub fn new() -> Self {
+        Self {
+            groups: Vec::new(),
+            retained: Vec::new(),
+            dropped: 0,
+            retain: WARNINGS_RETAINED,
+            index: BTreeMap::new(),
+        }
+    }
+
+    /// Keep at most `retain` messages verbatim
+    pub fn set_retain(self, retain: usize) -> Self {
+        Self { retain, ..self }
+    }
+
+    /// Record a warning about `column` (or the whole file) at `row`
+    ///
+    /// The message is only built should it be retained or become the example of a new group, so
+    /// recording a warning that is merely counted does not allocate.
+    pub fn record<F>(
+        &mut self,
+        category: ReadStatWarningCategory,
+        column: Option<&str>,
+        row: Option<usize>,
+        message: F,
+    ) where
+        F: FnOnce() -> String,
+    {
+        let key = (category, column.map(String::from));
+        let retain = self.retained.len() < self.retain;
+
+        match self.index.get(&key) {
+            Some(&i) => {
+                let g = &mut self.groups[i];
+                g.count += 1;
+                g.first_row = min_row(g.first_row, row);
+                g.last_row = max_row(g.last_row, row);
+                if retain {
+                    self.retained.push(message());
+                } else {
+                    self.dropped += 1;
+                }
+            }
+            None => {
+                let example = message();
+                if retain {
+                    self.retained.push(example.clone());
+                } else {
+                    self.dropped += 1;
+                }
+                self.insert(ReadStatWarningGroup {
+                    category,
+                    column: key.1.clone(),
+                    count: 1,
+                    first_row: row,
+                    last_row: row,
+                    example,
+                });
+            }
+        }
+    }
+
+    /// Record each of `messages`, e.g. those returned by a step of the conversion
+    pub fn record_all(&mut self, category: ReadStatWarningCategory, messages: &[String]) {
+        for m in messages {
+            self.record(category, None, None, || m.clone());
+        }
+    }
+
+    /// Fold in the warnings of another batch of the same run
+    pub fn merge(&mut self, other: ReadStatWarnings) {
+        for g in other.groups {
+            let key = (g.category, g.column.clone());
+            match self.index.get(&key) {
+                Some(&i) => {
+                    let mine = &mut self.groups[i];
+                    mine.count += g.count;
+                    mine.first_row = min_row(mine.first_row, g.first_row);
+                    mine.last_row = max_row(mine.last_row, g.last_row);
+                }
+                None => self.insert(g),
+            }
+        }
+
+        let room = self.retain.saturating_sub(self.retained.len());
+        let kept = std::cmp::min(room, other.retained.len());
+        self.dropped += other.dropped + other.retained.len() - kept;
+        self.retained.extend(other.retained.into_iter().take(kept));
+    }
+
+    /// Groups in order of category, then column
+    pub fn groups(&self) -> &[ReadStatWarningGroup] {
+        &self.groups
+    }
+
+    /// Warnings recorded, whether or not retained
+    pub fn count(&self) -> usize {
+        self.groups.iter().map(|g| g.count).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Plain text table with a line for each group
+    pub fn summary(&self) -> String {
+        let header: Vec<String> = ["Category", "Column", "Count", "First row", "Last row"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect();
+
+        let row = |r: Option<usize>| r.map_or(String::from("-"), |r| r.to_string());
+        let rows: Vec<Vec<String>> = self
+            .groups
+            .iter()
+            .map(|g| {
+                vec![
+                    g.category.to_string(),
+                    g.column.clone().unwrap_or_else(|| String::from("-")),
+                    g.count.to_string(),
+                    row(g.first_row),
+                    row(g.last_row),
+                ]
+            })
+            .collect();
+
+        // width of each column
+        let mut widths: Vec<usize> = header.iter().map(|h| h.len()).collect();
+        for r in &rows {
+            for (w, c) in widths.iter_mut().zip(r.iter()) {
+                *w = (*w).max(c.chars().count());
+            }
+        }
+
+        let line = |cells: &[String]| -> String {
+            cells
+                .iter()
+                .zip(widths.iter())
+                .map(|(c, w)| format!("{:<1$}", c, w))
+                .collect::<Vec<String>>()
+                .join("  ")
+                .trim_end()
+                .to_owned()
+        };
+
+        let dashes: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+
+        let mut summary = String::new();
+        for r in [&header, &dashes].into_iter().chain(rows.iter()) {
+            summary.push_str(&line(r));
+            summary.push('\n');
+        }
+        summary
+    }
+
+    // Groups are kept sorted, so indices past the new group shift along by one
+    fn insert(&mut self, group: ReadStatWarningGroup) {
+        let key = (group.category, group.column.clone());
+        let at = self.groups.partition_point(|g| (g.category, &g.column) < (key.0, &key.1));
+        for i in self.index.values_mut().filter(|i| **i >= at) {
+            *i += 1;
+        }
+        self.index.insert(key, at);
+        self.groups.insert(at, group);
+    }
+}
+
+fn min_row(a: Option<usize>, b: Option<usize>) -> Option<usize> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+fn max_row(a: Option<usize>, b: Option<usize>) -> Option<usize> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
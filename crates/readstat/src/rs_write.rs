@@ -1,75 +1,1345 @@
 use arrow2::{
-    array::Array,
+    array::{new_empty_array, Array, PrimitiveArray, StructArray, Utf8Array},
     chunk::Chunk,
+    compute::{
+        aggregate::estimated_bytes_size,
+        cast::{cast, CastOptions},
+        concatenate::concatenate,
+    },
+    datatypes::{DataType, Field, Schema},
     error::Error as ArrowError,
     io::{
-        csv as csv_arrow2, ipc as ipc_arrow2, ndjson as ndjson_arrow2,
+        csv as csv_arrow2, ipc as ipc_arrow2, iterator::StreamingIterator, ndjson as ndjson_arrow2,
         parquet::{self as parquet_arrow2, write::RowGroupIterator},
     },
+    temporal_conversions::{
+        date32_to_date, time32s_to_time, time64us_to_time, timestamp_to_naive_datetime,
+    },
 };
 use colored::Colorize;
 // use indicatif::{ProgressBar, ProgressStyle};
 use num_format::Locale;
 use num_format::ToFormattedString;
-use std::{error::Error, fs::OpenOptions, io::stdout};
+use std::{
+    error::Error,
+    fs::{File, OpenOptions},
+    io::{self, stdout, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    thread,
+    time::Duration,
+};
 
+use crate::common::{format_f32, format_f64};
 use crate::rs_data::ReadStatData;
-use crate::rs_metadata::ReadStatMetadata;
-use crate::rs_path::ReadStatPath;
+use crate::rs_metadata::{ReadStatFormatSpec, ReadStatMetadata};
+use crate::rs_path::{is_special_file, ReadStatPath, STDOUT_PATH};
+use crate::rs_info::has_arrow2_feature;
 use crate::rs_var::ReadStatVarFormatClass;
+use crate::rs_write_readstat::ReadStatFileWriter;
+use crate::CsvQuoteStyle;
+use crate::IpcCompression;
+use crate::LineEnding;
 use crate::OutFormat;
 use crate::ParquetCompression;
 
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+// Records are serialized ending in \n; for crlf each of those terminators becomes \r\n
+//   Newlines within quoted csv values are left as is, whereas ndjson escapes every newline
+fn with_line_ending(buf: Vec<u8>, line_ending: LineEnding, quoted: bool) -> Vec<u8> {
+    match line_ending {
+        LineEnding::Lf => buf,
+        LineEnding::Crlf => {
+            let mut out = Vec::with_capacity(buf.len() + buf.len() / 16);
+            let mut in_quotes = false;
+            for b in buf {
+                if quoted && b == b'"' {
+                    // an escaped quote ("") toggles twice
+                    in_quotes = !in_quotes;
+                } else if b == b'\n' && !in_quotes {
+                    out.push(b'\r');
+                }
+                out.push(b);
+            }
+            out
+        }
+    }
+}
+
+/// How csv output is written
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadStatCsvOptions {
+    /// Byte separating the fields of each record; defaults to `,`
+    pub delimiter: u8,
+    pub quote_style: CsvQuoteStyle,
+    /// Begin with a record of the column names; defaults to true
+    pub write_header: bool,
+    /// Written in place of each missing value; defaults to an empty field
+    pub null_value: String,
+    /// [chrono](https://docs.rs/chrono/latest/chrono/format/strftime/index.html) format of
+    /// datetimes, e.g. `%Y-%m-%dT%H:%M:%S`; defaults to e.g. `2021-01-01 10:49:39`
+    pub datetime_format: Option<String>,
+}
+
+impl Default for ReadStatCsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote_style: CsvQuoteStyle::Necessary,
+            write_header: true,
+            null_value: String::new(),
+            datetime_format: None,
+        }
+    }
+}
+
+impl ReadStatCsvOptions {
+    fn serialize_options(&self) -> csv_arrow2::write::SerializeOptions {
+        csv_arrow2::write::SerializeOptions {
+            delimiter: self.delimiter,
+            quote_style: match self.quote_style {
+                CsvQuoteStyle::Necessary => csv_arrow2::write::QuoteStyle::Necessary,
+                CsvQuoteStyle::Always => csv_arrow2::write::QuoteStyle::Always,
+                CsvQuoteStyle::Never => csv_arrow2::write::QuoteStyle::Never,
+            },
+            timestamp_format: self.datetime_format.clone(),
+            ..csv_arrow2::write::SerializeOptions::default()
+        }
+    }
+}
+
+// As csv_arrow2::write::write_chunk, which writes missing values as empty fields, yet writing
+//   null_value in their place
+fn write_csv_chunk<W: Write>(
+    w: &mut W,
+    chunk: &Chunk<Box<dyn Array>>,
+    options: &csv_arrow2::write::SerializeOptions,
+    null_value: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if null_value.is_empty() {
+        csv_arrow2::write::write_chunk(w, chunk, options)?;
+        return Ok(());
+    }
+
+    let mut serializers = chunk
+        .arrays()
+        .iter()
+        .map(|a| csv_arrow2::write::new_serializer(a.as_ref(), options))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut record = Vec::new();
+    for row in 0..chunk.len() {
+        record.clear();
+        for (i, (a, s)) in chunk.arrays().iter().zip(serializers.iter_mut()).enumerate() {
+            if i > 0 {
+                record.push(options.delimiter);
+            }
+            // every serializer advances a row, whether or not its value is written
+            let field = s.next().unwrap_or_default();
+            if a.is_null(row) {
+                record.extend_from_slice(null_value.as_bytes());
+            } else {
+                record.extend_from_slice(field);
+            }
+        }
+        record.push(b'\n');
+        w.write_all(&record)?;
+    }
+
+    Ok(())
+}
+
+/// Destination of the bytes of an output file
+///
+/// Files are written via [`ReadStatFileOutput`]; supply another implementation with
+/// [`ReadStatWriter::set_output`] to write elsewhere.
+pub trait OutputWriter: Write + Send {
+    /// Re-open the destination following a failed write, keeping only its first `len` bytes
+    fn reopen(&mut self, len: u64) -> io::Result<()>;
+
+    /// Move the output into place once it is completely written; by default does nothing
+    fn commit(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Ensure every byte written so far is held by the destination, e.g. on disk; by default
+    /// flushes
+    fn sync(&mut self) -> io::Result<()> {
+        self.flush()
+    }
+
+    /// Warnings raised since last taken, e.g. as [`commit`](OutputWriter::commit) could not
+    /// rename the output into place; by default none
+    fn take_warnings(&mut self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// An output file, written to a temporary file beside it and moved into place once complete
+///
+/// Nothing is written at `path` until [`commit`](OutputWriter::commit), which renames the
+/// temporary file over it, so readers never find a partly written file.  Should the output be
+/// dropped uncommitted, e.g. as a write failed, the temporary file is removed and any existing
+/// file at `path` is untouched.
+///
+/// A named pipe or device at `path` (see [`is_special_file`]) is instead written directly, as it
+/// can be neither renamed over nor given a temporary file beside it; see
+/// [`ReadStatFileOutput::is_direct`].  So is a file opened with [`ReadStatFileOutput::append`].
+pub struct ReadStatFileOutput {
+    path: PathBuf,
+    // the output path itself when written directly
+    temp_path: PathBuf,
+    // closed ahead of renaming
+    file: Option<File>,
+    overwrite: bool,
+    direct: bool,
+    committed: bool,
+    warnings: Vec<String>,
+}
+
+impl ReadStatFileOutput {
+    /// Create the temporary file of the file at `path`, or open `path` itself should it be a
+    /// named pipe or device
+    ///
+    /// Unless `overwrite`, an existing file at `path`, whether found now or once writing is
+    /// complete, is an error; a named pipe or device is never refused.
+    pub fn create(path: &Path, overwrite: bool) -> io::Result<Self> {
+        if is_special_file(path) {
+            let file = OpenOptions::new().write(true).open(path)?;
+            return Ok(Self {
+                path: path.to_path_buf(),
+                temp_path: path.to_path_buf(),
+                file: Some(file),
+                overwrite,
+                direct: true,
+                committed: false,
+                warnings: Vec::new(),
+            });
+        }
+
+        if !overwrite {
+            refuse_existing(path)?;
+        }
+
+        let temp_path = temp_path(path);
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            temp_path,
+            file: Some(file),
+            overwrite,
+            direct: false,
+            committed: false,
+            warnings: Vec::new(),
+        })
+    }
+
+    /// Open the file at `path`, creating it should it not exist, to append to once cut back to
+    /// its first `len` bytes
+    ///
+    /// The file is written directly, as a named pipe or device is, yet may be re-opened; it is
+    /// kept wherever writing stops.
+    pub fn append(path: &Path, len: u64) -> io::Result<Self> {
+        let mut file = OpenOptions::new().write(true).create(true).truncate(false).open(path)?;
+        file.set_len(len)?;
+        file.seek(SeekFrom::Start(len))?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            temp_path: path.to_path_buf(),
+            file: Some(file),
+            overwrite: true,
+            direct: true,
+            committed: false,
+            warnings: Vec::new(),
+        })
+    }
+
+    /// Temporary file written ahead of being moved to the output path, or the output path itself
+    /// when written directly
+    pub fn temp_path(&self) -> &Path {
+        &self.temp_path
+    }
+
+    /// Whether bytes are written straight to the output path rather than to a temporary file, so
+    /// that a reader may find the output partly written
+    pub fn is_direct(&self) -> bool {
+        self.direct
+    }
+
+    // Copy the temporary file to another beside path and rename that over path, removing the
+    //   copy should either fail
+    fn copy_into_place(&self) -> io::Result<()> {
+        let copy_path = temp_path(&self.path);
+        let copied = std::fs::copy(&self.temp_path, &copy_path)
+            .and_then(|_| OpenOptions::new().write(true).open(&copy_path)?.sync_all())
+            .and_then(|_| std::fs::rename(&copy_path, &self.path));
+        if copied.is_err() {
+            let _ = std::fs::remove_file(&copy_path);
+        }
+        copied
+    }
+
+    // Copy the temporary file straight over path, removing whatever was copied should the copy
+    //   fail so that no truncated file is left at path
+    fn copy_over(&self) -> io::Result<()> {
+        let copied = std::fs::copy(&self.temp_path, &self.path)
+            .and_then(|_| OpenOptions::new().write(true).open(&self.path)?.sync_all());
+        if copied.is_err() {
+            let _ = std::fs::remove_file(&self.path);
+        }
+        copied
+    }
+
+    fn file(&mut self) -> io::Result<&mut File> {
+        self.file.as_mut().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "The output file was already moved into place",
+            )
+        })
+    }
+}
+
+// An existing output file that is not to be overwritten
+fn refuse_existing(path: &Path) -> io::Result<()> {
+    if path.exists() {
+        Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!(
+                "The output file - {} - already exists!  To overwrite the file, utilize the {} parameter",
+                path.to_string_lossy().bright_yellow(),
+                String::from("--overwrite").bright_cyan()
+            ),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+// Hidden file within the directory of path, so that renaming it over path is atomic, and
+//   numbered so that concurrent writers never share one
+fn temp_path(path: &Path) -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(
+        ".{}.{}-{}.tmp",
+        name,
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+impl Write for ReadStatFileOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file()?.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file()?.flush()
+    }
+}
+
+impl OutputWriter for ReadStatFileOutput {
+    fn reopen(&mut self, len: u64) -> io::Result<()> {
+        // bytes written to a pipe or device cannot be taken back
+        if self.direct && is_special_file(&self.path) {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "{} cannot be re-opened as it is written directly",
+                    self.path.to_string_lossy()
+                ),
+            ));
+        }
+
+        let mut file = OpenOptions::new().write(true).open(&self.temp_path)?;
+        file.set_len(len)?;
+        file.seek(SeekFrom::Start(len))?;
+        self.file = Some(file);
+        Ok(())
+    }
+
+    fn commit(&mut self) -> io::Result<()> {
+        if self.committed {
+            return Ok(());
+        }
+
+        // a pipe or device holds every byte already, as does a file appended to once synced
+        if self.direct {
+            self.sync()?;
+            self.file = None;
+            self.committed = true;
+            return Ok(());
+        }
+
+        // every byte is on disk ahead of the file appearing at path
+        let file = self.file()?;
+        file.flush()?;
+        file.sync_all()?;
+        self.file = None;
+
+        if !self.overwrite {
+            refuse_existing(&self.path)?;
+        }
+        match std::fs::rename(&self.temp_path, &self.path) {
+            Ok(()) => (),
+            // the directory spans filesystems, e.g. through a bind mount, so the temporary file is
+            //   copied into place instead; only should renaming the copy fail too is it copied
+            //   straight to path, where a reader may find it partly written
+            Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+                let how = match self.copy_into_place() {
+                    Ok(()) => "copied into place",
+                    Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+                        self.copy_over()?;
+                        "copied directly"
+                    }
+                    Err(e) => return Err(e),
+                };
+                std::fs::remove_file(&self.temp_path)?;
+                self.warnings.push(format!(
+                    "{}: {} could not be renamed into place across filesystems, so was {}",
+                    "Warning".bright_yellow(),
+                    self.path.to_string_lossy().bright_yellow(),
+                    how
+                ));
+            }
+            Err(e) => return Err(e),
+        }
+        self.committed = true;
+        Ok(())
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        // a pipe or device cannot be synced
+        let syncs = !(self.direct && is_special_file(&self.path));
+        let file = self.file()?;
+        file.flush()?;
+        if syncs {
+            file.sync_all()?;
+        }
+        Ok(())
+    }
+
+    fn take_warnings(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.warnings)
+    }
+}
+
+impl Drop for ReadStatFileOutput {
+    fn drop(&mut self) {
+        if !self.committed && !self.direct {
+            self.file = None;
+            let _ = std::fs::remove_file(&self.temp_path);
+        }
+    }
+}
+
+/// Standard out, which cannot be re-opened following a failed write
+pub struct ReadStatStdoutOutput(io::Stdout);
+
+impl ReadStatStdoutOutput {
+    pub fn new() -> Self {
+        Self(stdout())
+    }
+}
+
+impl Default for ReadStatStdoutOutput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Write for ReadStatStdoutOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl OutputWriter for ReadStatStdoutOutput {
+    fn reopen(&mut self, _len: u64) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Standard out cannot be re-opened",
+        ))
+    }
+}
+
+struct ReadStatOutputState {
+    wtr: Box<dyn OutputWriter>,
+    // bytes written successfully
+    written: u64,
+    // kind of the last failed write, whatever error the format goes on to report
+    failure: Option<io::ErrorKind>,
+}
+
+/// Shared handle to the output of a [`ReadStatWriter`]
+///
+/// Shared so that the output may be re-opened while owned by a feather or parquet writer.
+#[derive(Clone)]
+pub struct ReadStatOutput(Arc<Mutex<ReadStatOutputState>>);
+
+impl ReadStatOutput {
+    fn new(wtr: Box<dyn OutputWriter>) -> Self {
+        Self::appending(wtr, 0)
+    }
+
+    // Output already holding written bytes, to which the rest are appended
+    fn appending(wtr: Box<dyn OutputWriter>, written: u64) -> Self {
+        Self(Arc::new(Mutex::new(ReadStatOutputState {
+            wtr,
+            written,
+            failure: None,
+        })))
+    }
+
+    fn state(&self) -> io::Result<MutexGuard<'_, ReadStatOutputState>> {
+        self.0
+            .lock()
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "Output lock poisoned"))
+    }
+
+    fn written(&self) -> u64 {
+        self.state().map(|s| s.written).unwrap_or(0)
+    }
+
+    fn take_failure(&self) -> Option<io::ErrorKind> {
+        self.state().ok().and_then(|mut s| s.failure.take())
+    }
+
+    fn reopen(&self, len: u64) -> io::Result<()> {
+        let mut s = self.state()?;
+        s.wtr.reopen(len)?;
+        s.written = len;
+        Ok(())
+    }
+
+    fn sync(&self) -> io::Result<()> {
+        self.state()?.wtr.sync()
+    }
+
+    // Commit the output, returning the warnings raised in doing so
+    fn commit(&self) -> io::Result<Vec<String>> {
+        let mut s = self.state()?;
+        s.wtr.commit()?;
+        Ok(s.wtr.take_warnings())
+    }
+}
+
+impl Write for ReadStatOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut s = self.state()?;
+        match s.wtr.write(buf) {
+            Ok(n) => {
+                s.written += n as u64;
+                Ok(n)
+            }
+            Err(e) => {
+                if e.kind() != io::ErrorKind::Interrupted {
+                    s.failure = Some(e.kind());
+                }
+                Err(e)
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut s = self.state()?;
+        let flushed = s.wtr.flush();
+        if let Err(e) = &flushed {
+            s.failure = Some(e.kind());
+        }
+        flushed
+    }
+}
+
+// Failures of the output that retrying will not fix; others, such as EIO or ESTALE from a
+// network filesystem, may be transient
+fn is_transient(kind: io::ErrorKind) -> bool {
+    !matches!(
+        kind,
+        io::ErrorKind::PermissionDenied
+            | io::ErrorKind::NotFound
+            | io::ErrorKind::AlreadyExists
+            | io::ErrorKind::InvalidInput
+            | io::ErrorKind::InvalidData
+            | io::ErrorKind::Unsupported
+            | io::ErrorKind::OutOfMemory
+    )
+}
+
+// Messages alongside data written to standard out go to standard error so as not to corrupt it
+pub(crate) fn print_message(rsp: &ReadStatPath, msg: &str) {
+    if rsp.writes_to_stdout() || rsp.writes_to_special_file() {
+        eprintln!("{}", msg);
+    } else {
+        println!("{}", msg);
+    }
+}
+
+/// Path of the part numbered `index` of output split by rows, e.g. `out_000.parquet` for the
+/// first part of `out.parquet`
+pub fn split_path(out_path: &Path, index: usize) -> PathBuf {
+    let stem = out_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name = match out_path.extension() {
+        Some(ext) => format!("{}_{:03}.{}", stem, index, ext.to_string_lossy()),
+        None => format!("{}_{:03}", stem, index),
+    };
+    out_path.with_file_name(name)
+}
+
+/// Path of the segment numbered `index` of checkpointed output, e.g. `.out.parquet.000.segment`
+/// for the first segment of `out.parquet`; hidden within the directory of the output
+pub fn segment_path(out_path: &Path, index: usize) -> PathBuf {
+    let name = out_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    out_path.with_file_name(format!(".{}.{:03}.segment", name, index))
+}
+
+// Whether checkpointed output of format is appended to a single segment, which is truncated to
+//   its last checkpoint on resuming, rather than split into segments that are each finished;
+//   only line-oriented text can be cut at any row
+pub(crate) fn appends_to_segment(format: OutFormat) -> bool {
+    matches!(format, OutFormat::csv | OutFormat::ndjson)
+}
+
+// Every chunk of the finished segment at path, written as format, in order
+fn read_segment(
+    path: &Path,
+    format: OutFormat,
+    schema: &Schema,
+) -> Result<Vec<Chunk<Box<dyn Array>>>, Box<dyn Error + Send + Sync>> {
+    let mut f = File::open(path)?;
+    let chunks = match format {
+        OutFormat::arrow => {
+            let metadata = ipc_arrow2::read::read_stream_metadata(&mut f)?;
+            ipc_arrow2::read::StreamReader::new(f, metadata, None)
+                .map_while(|s| match s {
+                    Ok(ipc_arrow2::read::StreamState::Some(c)) => Some(Ok(c)),
+                    Ok(ipc_arrow2::read::StreamState::Waiting) => None,
+                    Err(e) => Some(Err(e)),
+                })
+                .collect::<Result<Vec<_>, ArrowError>>()?
+        }
+        OutFormat::feather => {
+            let metadata = ipc_arrow2::read::read_file_metadata(&mut f)?;
+            ipc_arrow2::read::FileReader::new(f, metadata, None, None)
+                .collect::<Result<Vec<_>, ArrowError>>()?
+        }
+        OutFormat::parquet => {
+            let metadata = parquet_arrow2::read::read_metadata(&mut f)?;
+            parquet_arrow2::read::FileReader::new(
+                f,
+                metadata.row_groups,
+                schema.clone(),
+                None,
+                None,
+                None,
+            )
+            .collect::<Result<Vec<_>, ArrowError>>()?
+        }
+        _ => {
+            return Err(From::from(format!(
+                "Segments of {} output are not read back",
+                format
+            )))
+        }
+    };
+    Ok(chunks)
+}
+
+// Dictionary-encoded strings are written to text as their values rather than their keys
+fn decode_dictionaries(
+    chunk: &Chunk<Box<dyn Array>>,
+) -> Result<Chunk<Box<dyn Array>>, Box<dyn Error + Send + Sync>> {
+    let arrays = chunk
+        .arrays()
+        .iter()
+        .map(|a| match a.data_type() {
+            DataType::Dictionary(_, values, _) => cast(a.as_ref(), values, CastOptions::default()),
+            _ => Ok(a.clone()),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Chunk::new(arrays))
+}
+
+// Floats are written in their shortest form, rendered by a single routine so that output does not
+//   vary by platform (see common::format_f64), or as their SAS format displays them when formats
+//   are given for the columns
+fn format_floats_for_csv(
+    chunk: &Chunk<Box<dyn Array>>,
+    formats: &[Option<&ReadStatFormatSpec>],
+) -> Chunk<Box<dyn Array>> {
+    let arrays: Vec<Box<dyn Array>> = chunk
+        .arrays()
+        .iter()
+        .enumerate()
+        .map(|(i, a)| {
+            let spec = formats.get(i).copied().flatten();
+            let render = |v: f64| spec.and_then(|s| s.render(v));
+
+            match a.data_type() {
+                DataType::Float64 => {
+                    let a = a.as_any().downcast_ref::<PrimitiveArray<f64>>().unwrap();
+                    Utf8Array::<i32>::from_iter(
+                        a.iter()
+                            .map(|v| v.map(|v| render(*v).unwrap_or_else(|| format_f64(*v)))),
+                    )
+                    .boxed()
+                }
+                DataType::Float32 => {
+                    let a = a.as_any().downcast_ref::<PrimitiveArray<f32>>().unwrap();
+                    Utf8Array::<i32>::from_iter(a.iter().map(|v| {
+                        v.map(|v| render(*v as f64).unwrap_or_else(|| format_f32(*v)))
+                    }))
+                    .boxed()
+                }
+                // integers are left as is unless their format is one rendered
+                DataType::Int64 if spec.and_then(|s| s.render(0.0)).is_some() => {
+                    let a = a.as_any().downcast_ref::<PrimitiveArray<i64>>().unwrap();
+                    Utf8Array::<i32>::from_iter(
+                        a.iter()
+                            .map(|v| v.map(|v| render(*v as f64).unwrap_or_else(|| v.to_string()))),
+                    )
+                    .boxed()
+                }
+                _ => a.clone(),
+            }
+        })
+        .collect();
+
+    Chunk::new(arrays)
+}
+
+// The SAS format of each column, in order, when formats are applied; columns of missing value
+//   tags follow the variables and have none
+fn csv_formats(d: &ReadStatData, apply_formats: bool) -> Vec<Option<&ReadStatFormatSpec>> {
+    if !apply_formats {
+        return Vec::new();
+    }
+    d.vars
+        .values()
+        .map(|vm| vm.var_format_parts.as_ref())
+        .collect()
+}
+
+// Each row as one object keyed by variable name, with dates and times as ISO-8601 strings rather
+//   than the days or seconds they are stored as
+fn rows_for_ndjson(chunk: &Chunk<Box<dyn Array>>, schema: &Schema) -> StructArray {
+    let arrays: Vec<Box<dyn Array>> = chunk
+        .arrays()
+        .iter()
+        .map(|a| match a.data_type() {
+            DataType::Date32 => {
+                let a = a.as_any().downcast_ref::<PrimitiveArray<i32>>().unwrap();
+                Utf8Array::<i32>::from_iter(
+                    a.iter()
+                        .map(|v| v.map(|v| date32_to_date(*v).format("%Y-%m-%d").to_string())),
+                )
+                .boxed()
+            }
+            DataType::Timestamp(unit, None) => {
+                let a = a.as_any().downcast_ref::<PrimitiveArray<i64>>().unwrap();
+                Utf8Array::<i32>::from_iter(a.iter().map(|v| {
+                    v.map(|v| {
+                        timestamp_to_naive_datetime(*v, *unit)
+                            .format("%Y-%m-%dT%H:%M:%S%.f")
+                            .to_string()
+                    })
+                }))
+                .boxed()
+            }
+            DataType::Time32(_) => {
+                let a = a.as_any().downcast_ref::<PrimitiveArray<i32>>().unwrap();
+                Utf8Array::<i32>::from_iter(
+                    a.iter()
+                        .map(|v| v.map(|v| time32s_to_time(*v).format("%H:%M:%S").to_string())),
+                )
+                .boxed()
+            }
+            DataType::Time64(_) => {
+                let a = a.as_any().downcast_ref::<PrimitiveArray<i64>>().unwrap();
+                Utf8Array::<i32>::from_iter(
+                    a.iter()
+                        .map(|v| v.map(|v| time64us_to_time(*v).format("%H:%M:%S%.f").to_string())),
+                )
+                .boxed()
+            }
+            _ => a.clone(),
+        })
+        .collect();
+
+    let fields = schema
+        .fields
+        .iter()
+        .zip(arrays.iter())
+        .map(|(f, a)| Field::new(f.name.clone(), a.data_type().clone(), true))
+        .collect();
+
+    StructArray::new(DataType::Struct(fields), arrays, None)
+}
+
 pub struct ReadStatParquetWriter {
-    wtr: Box<parquet_arrow2::write::FileWriter<std::fs::File>>,
+    wtr: Box<parquet_arrow2::write::FileWriter<ReadStatOutput>>,
     options: parquet_arrow2::write::WriteOptions,
     encodings: Vec<Vec<parquet_arrow2::write::Encoding>>,
+    // chunks held back until their estimated size reaches row_group_bytes
+    row_group_bytes: Option<usize>,
+    pending: Vec<Chunk<Box<dyn Array>>>,
+    pending_bytes: usize,
 }
 
-impl ReadStatParquetWriter {
-    fn new(
-        wtr: Box<parquet_arrow2::write::FileWriter<std::fs::File>>,
-        options: parquet_arrow2::write::WriteOptions,
-        encodings: Vec<Vec<parquet_arrow2::write::Encoding>>,
-    ) -> Self {
-        Self {
-            wtr,
-            options,
-            encodings,
+impl ReadStatParquetWriter {
+    fn new(
+        wtr: Box<parquet_arrow2::write::FileWriter<ReadStatOutput>>,
+        options: parquet_arrow2::write::WriteOptions,
+        encodings: Vec<Vec<parquet_arrow2::write::Encoding>>,
+        row_group_bytes: Option<usize>,
+    ) -> Self {
+        Self {
+            wtr,
+            options,
+            encodings,
+            row_group_bytes,
+            pending: Vec::new(),
+            pending_bytes: 0,
+        }
+    }
+
+    // Without a target size every chunk becomes its own row group
+    fn push(
+        &mut self,
+        chunk: Chunk<Box<dyn Array>>,
+        schema: &Schema,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        match self.row_group_bytes {
+            None => self.write_row_group(chunk, schema),
+            Some(target) => {
+                self.pending_bytes += chunk
+                    .arrays()
+                    .iter()
+                    .map(|a| estimated_bytes_size(a.as_ref()))
+                    .sum::<usize>();
+                self.pending.push(chunk);
+
+                if self.pending_bytes >= target {
+                    self.flush(schema)
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    // Write any held back chunks as a single row group
+    fn flush(&mut self, schema: &Schema) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut chunks = std::mem::take(&mut self.pending);
+        self.pending_bytes = 0;
+
+        let chunk = match chunks.len() {
+            0 => return Ok(()),
+            1 => chunks.remove(0),
+            _ => {
+                let arrays = (0..schema.fields.len())
+                    .map(|i| {
+                        let column: Vec<&dyn Array> =
+                            chunks.iter().map(|c| c.arrays()[i].as_ref()).collect();
+                        concatenate(&column)
+                    })
+                    .collect::<Result<Vec<Box<dyn Array>>, ArrowError>>()?;
+                Chunk::new(arrays)
+            }
+        };
+
+        self.write_row_group(chunk, schema)
+    }
+
+    fn write_row_group(
+        &mut self,
+        chunk: Chunk<Box<dyn Array>>,
+        schema: &Schema,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let iter: Vec<Result<Chunk<Box<dyn Array>>, ArrowError>> = vec![Ok(chunk)];
+
+        let row_groups = RowGroupIterator::try_new(
+            iter.into_iter(),
+            schema,
+            self.options,
+            self.encodings.clone(),
+        )?;
+
+        for group in row_groups {
+            self.wtr.write(group?)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub enum ReadStatWriterFormat {
+    Arrow(Box<ipc_arrow2::write::StreamWriter<ReadStatOutput>>),
+    Csv(ReadStatOutput),
+    CsvStdout(std::io::Stdout),
+    Feather(Box<ipc_arrow2::write::FileWriter<ReadStatOutput>>),
+    Ndjson(ReadStatOutput),
+    Parquet(ReadStatParquetWriter),
+    /// SPSS or Stata output, written by ReadStat
+    ReadStat(Box<ReadStatFileWriter>),
+}
+
+#[derive(Default)]
+pub struct ReadStatWriter {
+    pub wtr: Option<ReadStatWriterFormat>,
+    pub wrote_header: bool,
+    pub wrote_start: bool,
+    pub quiet: bool,
+    pub row_group_bytes: Option<usize>,
+    pub ipc_compression: IpcCompression,
+    pub bom: bool,
+    pub line_ending: LineEnding,
+    /// Write numerics within csv output as their SAS formats display them; see
+    /// [`ReadStatWriter::set_apply_formats`]
+    pub apply_formats: bool,
+    pub csv: ReadStatCsvOptions,
+    pub write_retries: u32,
+    pub write_retry_delay: Duration,
+    /// A warning for each failed write that was retried
+    pub warnings: Vec<String>,
+    /// A warning for each column renamed as its name is not valid within SPSS or Stata output
+    pub name_warnings: Vec<String>,
+    /// A warning for each output written directly as it is a named pipe or device, or copied
+    /// into place as it could not be renamed there
+    pub direct_warnings: Vec<String>,
+    pub split_rows: Option<usize>,
+    /// Rows of each segment of checkpointed output; see [`ReadStatWriter::set_checkpoint_rows`]
+    pub checkpoint_rows: Option<usize>,
+    /// Bytes of the segment appended to by checkpointed csv or ndjson output, as of the last
+    /// checkpoint
+    pub segment_bytes: u64,
+    /// Every file finished, in order; a numbered file for each part when output is split
+    pub written_paths: Vec<PathBuf>,
+    /// Bytes of each file finished, in the order of `written_paths`
+    pub bytes_written: Vec<u64>,
+    /// Rows written across every batch, whatever the file
+    pub rows_written: usize,
+    output: Option<ReadStatOutput>,
+    output_supplied: bool,
+    batches_written: usize,
+    // Rows written to, and number of, the file being written when output is split or
+    //   checkpointed
+    part_rows: usize,
+    part: usize,
+    // Checkpoints taken, and whether the segment being written is appended to
+    checkpoints: usize,
+    appending: bool,
+}
+
+impl ReadStatWriter {
+    pub fn new() -> Self {
+        Self {
+            wtr: None,
+            wrote_header: false,
+            wrote_start: false,
+            quiet: false,
+            row_group_bytes: None,
+            ipc_compression: IpcCompression::Zstd,
+            bom: false,
+            line_ending: LineEnding::Lf,
+            apply_formats: false,
+            csv: ReadStatCsvOptions::default(),
+            write_retries: 0,
+            write_retry_delay: Duration::ZERO,
+            warnings: Vec::new(),
+            name_warnings: Vec::new(),
+            direct_warnings: Vec::new(),
+            split_rows: None,
+            checkpoint_rows: None,
+            segment_bytes: 0,
+            written_paths: Vec::new(),
+            bytes_written: Vec::new(),
+            rows_written: 0,
+            output: None,
+            output_supplied: false,
+            batches_written: 0,
+            part_rows: 0,
+            part: 0,
+            checkpoints: 0,
+            appending: false,
+        }
+    }
+
+    pub fn set_quiet(self, quiet: bool) -> Self {
+        Self { quiet, ..self }
+    }
+
+    /// Compression of the buffers within feather (Arrow IPC) output; defaults to zstd
+    pub fn set_ipc_compression(self, ipc_compression: IpcCompression) -> Self {
+        Self {
+            ipc_compression,
+            ..self
+        }
+    }
+
+    /// Target uncompressed size of each parquet row group
+    ///
+    /// Chunks are accumulated until their estimated in-memory size reaches the target, so row
+    /// groups need not follow the number of rows streamed at a time.  If unset, each chunk is
+    /// written as its own row group.
+    pub fn set_row_group_bytes(self, row_group_bytes: Option<usize>) -> Self {
+        Self {
+            row_group_bytes,
+            ..self
+        }
+    }
+
+    /// Begin csv and ndjson files with a UTF-8 byte order mark
+    pub fn set_bom(self, bom: bool) -> Self {
+        Self { bom, ..self }
+    }
+
+    /// Terminator of each record within csv and ndjson files; defaults to lf
+    pub fn set_line_ending(self, line_ending: LineEnding) -> Self {
+        Self {
+            line_ending,
+            ..self
+        }
+    }
+
+    /// Write numerics within csv output as their SAS formats display them, e.g. `12.5%` rather
+    /// than `0.125` for `PERCENT8.1`
+    ///
+    /// Only the text written changes, Arrow types do not.  `w.d`, `F`, `BEST`, `COMMA`, `DOLLAR`,
+    /// `PERCENT`, and `Z` formats are applied; numerics with any other format are written as if
+    /// unset.
+    pub fn set_apply_formats(self, apply_formats: bool) -> Self {
+        Self {
+            apply_formats,
+            ..self
+        }
+    }
+
+    /// Delimiter, quoting, header, missing values, and datetime format of csv output
+    pub fn set_csv_options(self, csv: ReadStatCsvOptions) -> Self {
+        Self { csv, ..self }
+    }
+
+    /// Times a failed write of a batch is retried, waiting `delay` before each retry
+    ///
+    /// Only failures of the output itself are retried.  Csv and ndjson output is re-opened and
+    /// the batch written again in place; feather and parquet output cannot be resumed, so is
+    /// restarted only when the first batch fails.
+    pub fn set_write_retries(self, write_retries: u32, delay: Duration) -> Self {
+        Self {
+            write_retries,
+            write_retry_delay: delay,
+            ..self
+        }
+    }
+
+    /// Write to `output` rather than to a file created at the output path
+    pub fn set_output(self, output: Box<dyn OutputWriter>) -> Self {
+        Self {
+            output: Some(ReadStatOutput::new(output)),
+            output_supplied: true,
+            ..self
+        }
+    }
+
+    /// Split output into numbered files of about `split_rows` rows each; see [`split_path`]
+    ///
+    /// A file is only closed between batches, once the rows written to it reach `split_rows`,
+    /// so each file holds `split_rows` rows rounded up to a whole number of batches.  Each is
+    /// finished as a file of its own, with its own header or footer.
+    pub fn set_split_rows(self, split_rows: Option<usize>) -> Self {
+        Self {
+            split_rows: split_rows.filter(|r| *r > 0),
+            ..self
+        }
+    }
+
+    /// Write output as hidden numbered segments, each finished and moved into place once it
+    /// holds at least `checkpoint_rows` rows, or for csv and ndjson as a single hidden segment
+    /// synced to disk every `checkpoint_rows` rows; see [`segment_path`]
+    ///
+    /// A checkpoint is taken within the [`write`](ReadStatWriter::write) of the batch that
+    /// reaches it, so its rows are on disk once the write returns.
+    /// [`finish`](ReadStatWriter::finish) moves the csv or ndjson segment into place as the
+    /// output, whereas arrow, feather, and parquet segments, whose footers cannot be reopened,
+    /// are read back and written again, batch by batch, into the output and then removed.
+    pub fn set_checkpoint_rows(self, checkpoint_rows: Option<usize>) -> Self {
+        Self {
+            checkpoint_rows: checkpoint_rows.map(|r| r.max(1)),
+            ..self
+        }
+    }
+
+    /// Continue checkpointed output from the segments finished by an interrupted conversion,
+    /// which hold `rows_written` rows, or for csv and ndjson from the first `segment_bytes`
+    /// bytes of the segment it appended to
+    pub fn set_resume_from(self, segments: usize, rows_written: usize, segment_bytes: u64) -> Self {
+        Self {
+            part: segments,
+            rows_written,
+            segment_bytes,
+            ..self
+        }
+    }
+
+    /// Segments of checkpointed output finished so far, including any kept from an interrupted
+    /// conversion
+    pub fn segments_finished(&self) -> usize {
+        self.part
+    }
+
+    /// Checkpoints taken so far, each upon finishing a segment or syncing the segment appended to
+    pub fn checkpoints(&self) -> usize {
+        self.checkpoints
+    }
+
+    // The output path of rsp, numbered by the part being written when output is split
+    fn part_rsp(&self, rsp: &ReadStatPath) -> Result<ReadStatPath, Box<dyn Error + Send + Sync>> {
+        let p = match &rsp.out_path {
+            Some(p) if !self.output_supplied && !rsp.writes_to_stdout() => p,
+            _ => {
+                return Err(From::from(
+                    "Splitting output requires an output file rather than standard out",
+                ))
+            }
+        };
+
+        let part = split_path(p, self.part);
+        if !rsp.overwrite && self.part_rows == 0 && self.batches_written == 0 && part.exists() {
+            return Err(From::from(format!(
+                "The output file - {} - already exists!  To overwrite the file, utilize the {} parameter",
+                part.to_string_lossy().bright_yellow(),
+                String::from("--overwrite").bright_cyan()
+            )));
+        }
+
+        Ok(ReadStatPath {
+            out_path: Some(part),
+            ..rsp.clone()
+        })
+    }
+
+    // The segment being written of the output path of rsp; a segment left by an interrupted
+    //   conversion that was never recorded as finished is replaced, or for csv and ndjson cut
+    //   back to its last checkpoint
+    fn segment_rsp(
+        &self,
+        rsp: &ReadStatPath,
+    ) -> Result<ReadStatPath, Box<dyn Error + Send + Sync>> {
+        match &rsp.out_path {
+            Some(p) if !self.output_supplied && !rsp.writes_to_stdout() => Ok(ReadStatPath {
+                out_path: Some(segment_path(p, self.part)),
+                overwrite: true,
+                ..rsp.clone()
+            }),
+            _ => Err(From::from(
+                "Checkpointing requires an output file rather than standard out",
+            )),
+        }
+    }
+
+    // Only the first segment of checkpointed output begins with a header (or byte order mark),
+    //   as the rest are appended to it, and a segment resumed already holds its header
+    fn header_written_elsewhere(&self) -> bool {
+        self.checkpoint_rows.is_some() && (self.part > 0 || self.segment_bytes > 0)
+    }
+
+    // Forget the file just finished, numbering the next
+    fn start_next_part(&mut self) {
+        self.wtr = None;
+        self.output = None;
+        self.wrote_start = false;
+        self.wrote_header = false;
+        self.batches_written = 0;
+        self.part_rows = 0;
+        self.part += 1;
+    }
+
+    // The output handle, created at the output path unless one was supplied
+    fn open_output(&mut self, p: &Path, overwrite: bool) -> io::Result<ReadStatOutput> {
+        match &self.output {
+            Some(o) => Ok(o.clone()),
+            None => {
+                let o: Box<dyn OutputWriter> = if p.as_os_str() == STDOUT_PATH {
+                    Box::new(ReadStatStdoutOutput::new())
+                } else if self.appending {
+                    let f = ReadStatFileOutput::append(p, self.segment_bytes)?;
+                    let o = ReadStatOutput::appending(Box::new(f), self.segment_bytes);
+                    self.output = Some(o.clone());
+                    return Ok(o);
+                } else {
+                    let f = ReadStatFileOutput::create(p, overwrite)?;
+                    if f.is_direct() {
+                        self.direct_warnings.push(format!(
+                            "{}: {} is not a regular file, so is written directly rather than moved into place once complete",
+                            "Warning".bright_yellow(),
+                            p.to_string_lossy().bright_yellow()
+                        ));
+                    }
+                    Box::new(f)
+                };
+                let o = ReadStatOutput::new(o);
+                self.output = Some(o.clone());
+                Ok(o)
+            }
+        }
+    }
+
+    pub fn finish(
+        &mut self,
+        d: &ReadStatData,
+        rsp: &ReadStatPath,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.checkpoint_rows.is_some() {
+            if self.wrote_start {
+                let segment = self.segment_rsp(rsp)?;
+                self.finish_segment(d, &segment)?;
+            }
+            return self.merge_segments(d, rsp);
+        }
+
+        let rsp = match self.split_rows {
+            Some(_) => self.part_rsp(rsp)?,
+            None => rsp.clone(),
+        };
+        self.finish_output(d, &rsp)?;
+        if let Some(o) = &self.output {
+            self.direct_warnings.extend(o.commit()?);
+        }
+
+        if let Some(p) = rsp.out_path.as_ref().filter(|_| !rsp.writes_to_stdout()) {
+            self.written_paths.push(p.clone());
+            self.bytes_written.push(self.output.as_ref().map_or(0, |o| o.written()));
+        }
+        Ok(())
+    }
+
+    // Finish the segment being written, moving it into place
+    fn finish_segment(
+        &mut self,
+        d: &ReadStatData,
+        segment: &ReadStatPath,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let quiet = self.quiet;
+        self.quiet = true;
+        let finished = self.finish_output(d, segment);
+        self.quiet = quiet;
+        finished?;
+
+        if let Some(o) = &self.output {
+            self.direct_warnings.extend(o.commit()?);
+            if self.appending {
+                self.segment_bytes = o.written();
+            }
         }
+        self.start_next_part();
+        self.checkpoints += 1;
+        Ok(())
     }
-}
 
-pub enum ReadStatWriterFormat {
-    Csv(std::fs::File),
-    CsvStdout(std::io::Stdout),
-    Feather(Box<ipc_arrow2::write::FileWriter<std::fs::File>>),
-    Ndjson(std::fs::File),
-    Parquet(ReadStatParquetWriter),
-}
+    // Move the csv or ndjson segment appended to into place as the output of rsp, or merge every
+    //   finished segment, in order, into the output, which is moved into place once complete,
+    //   and then remove the segments
+    fn merge_segments(
+        &mut self,
+        d: &ReadStatData,
+        rsp: &ReadStatPath,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let p = match &rsp.out_path {
+            Some(p) => p.clone(),
+            None => return Err(From::from("Error merging segments as output path is set to None")),
+        };
+        let segments: Vec<PathBuf> = (0..self.part).map(|i| segment_path(&p, i)).collect();
+
+        let bytes = match rsp.format {
+            // Text was appended to a single segment, which drops any rows written after its last
+            //   sync by an interrupted conversion
+            OutFormat::csv | OutFormat::ndjson => {
+                let s = segment_path(&p, 0);
+                let f = ReadStatFileOutput::append(&s, self.segment_bytes)?;
+                ReadStatOutput::appending(Box::new(f), self.segment_bytes).commit()?;
+                if !rsp.overwrite {
+                    refuse_existing(&p)?;
+                }
+                std::fs::rename(&s, &p)?;
+                self.segment_bytes
+            }
+            // Batches are written again, within a single file, and the segments removed
+            format => {
+                let mut merged = ReadStatWriter {
+                    quiet: true,
+                    row_group_bytes: self.row_group_bytes,
+                    ipc_compression: self.ipc_compression,
+                    ..ReadStatWriter::new()
+                };
+                for s in &segments {
+                    for c in read_segment(s, format, &d.schema)? {
+                        let sd = ReadStatData::new().set_chunk(d.schema.clone(), c);
+                        merged.write(&sd, rsp)?;
+                    }
+                }
 
-#[derive(Default)]
-pub struct ReadStatWriter {
-    pub wtr: Option<ReadStatWriterFormat>,
-    pub wrote_header: bool,
-    pub wrote_start: bool,
-}
+                // Holding no rows, the output still holds the schema
+                if merged.batches_written == 0 {
+                    let empty = Chunk::new(
+                        d.schema
+                            .fields
+                            .iter()
+                            .map(|f| new_empty_array(f.data_type().clone()))
+                            .collect(),
+                    );
+                    merged.write(&ReadStatData::new().set_chunk(d.schema.clone(), empty), rsp)?;
+                }
+                merged.finish(d, rsp)?;
+                for s in &segments {
+                    std::fs::remove_file(s)?;
+                }
+                merged.bytes_written.iter().sum()
+            }
+        };
 
-impl ReadStatWriter {
-    pub fn new() -> Self {
-        Self {
-            wtr: None,
-            wrote_header: false,
-            wrote_start: false,
-        }
+        self.finish_txt(d, rsp)?;
+        self.written_paths.push(p);
+        self.bytes_written.push(bytes);
+        Ok(())
     }
 
-    pub fn finish(
+    fn finish_output(
         &mut self,
         d: &ReadStatData,
         rsp: &ReadStatPath,
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
         match rsp {
+            // Write Arrow IPC stream
+            ReadStatPath {
+                format: OutFormat::arrow,
+                ..
+            } => self.finish_arrow(d, rsp),
             // Write csv data to file
             ReadStatPath {
                 out_path: Some(_),
@@ -91,6 +1361,11 @@ impl ReadStatWriter {
                 format: OutFormat::parquet,
                 ..
             } => self.finish_parquet(d, rsp),
+            // Write SPSS or Stata data to file
+            ReadStatPath {
+                format: OutFormat::sav | OutFormat::dta,
+                ..
+            } => self.finish_readstat(d, rsp),
             _ => Ok(()),
         }
     }
@@ -124,6 +1399,10 @@ impl ReadStatWriter {
         d: &ReadStatData,
         rsp: &ReadStatPath,
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.quiet {
+            return Ok(());
+        }
+
         //if let Some(pb) = &d.pb {
         let in_f = if let Some(f) = rsp.path.file_name() {
             f.to_string_lossy().bright_red()
@@ -148,7 +1427,7 @@ impl ReadStatWriter {
 
         let msg = format!("Wrote {} rows from file {} into {}", rows, in_f, out_f);
 
-        println!("{}", msg);
+        print_message(rsp, &msg);
         //pb.set_message(msg);
         //}
         Ok(())
@@ -159,6 +1438,10 @@ impl ReadStatWriter {
         d: &ReadStatData,
         rsp: &ReadStatPath,
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.quiet {
+            return Ok(());
+        }
+
         //if let Some(pb) = &d.pb {
         let in_f = if let Some(f) = rsp.path.file_name() {
             f.to_string_lossy().bright_red()
@@ -189,7 +1472,7 @@ impl ReadStatWriter {
             rows, in_f, out_f
         );
 
-        println!("{}", msg);
+        print_message(rsp, &msg);
 
         //pb.set_message(msg);
         //}
@@ -200,8 +1483,167 @@ impl ReadStatWriter {
         &mut self,
         d: &ReadStatData,
         rsp: &ReadStatPath,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if let Some(checkpoint_rows) = self.checkpoint_rows {
+            return self.write_segment(d, rsp, checkpoint_rows);
+        }
+        let split_rows = match self.split_rows {
+            Some(r) => r,
+            None => return self.write_with_retries(d, rsp),
+        };
+        // ReadStat records the rows of a file ahead of writing any
+        if matches!(rsp.format, OutFormat::sav | OutFormat::dta) {
+            return Err(From::from(format!(
+                "Splitting output is not supported for {} files",
+                rsp.format
+            )));
+        }
+
+        // Finish the file being written once it holds enough rows, then start the next
+        if self.part_rows >= split_rows {
+            self.finish(d, rsp)?;
+            self.start_next_part();
+        }
+
+        let part = self.part_rsp(rsp)?;
+        self.write_with_retries(d, &part)?;
+        self.part_rows += d.chunk_rows_processed;
+        Ok(())
+    }
+
+    // Write to the segment being written, finishing it once it holds checkpoint_rows rows, or
+    //   for csv and ndjson syncing the segment appended to every checkpoint_rows rows
+    fn write_segment(
+        &mut self,
+        d: &ReadStatData,
+        rsp: &ReadStatPath,
+        checkpoint_rows: usize,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if matches!(rsp.format, OutFormat::sav | OutFormat::dta) {
+            return Err(From::from(format!(
+                "Checkpointing is not supported for {} files",
+                rsp.format
+            )));
+        }
+
+        self.appending = appends_to_segment(rsp.format);
+        let segment = self.segment_rsp(rsp)?;
+        if !self.wrote_start {
+            self.wrote_header = self.header_written_elsewhere();
+        }
+        self.write_with_retries(d, &segment)?;
+        self.part_rows += d.chunk_rows_processed;
+
+        if self.part_rows >= checkpoint_rows {
+            if self.appending {
+                self.sync_segment()?;
+            } else {
+                self.finish_segment(d, &segment)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Sync the segment appended to, so every row written to it so far is on disk
+    fn sync_segment(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if let Some(o) = &self.output {
+            o.sync()?;
+            self.segment_bytes = o.written();
+        }
+        self.part_rows = 0;
+        self.checkpoints += 1;
+        Ok(())
+    }
+
+    fn write_with_retries(
+        &mut self,
+        d: &ReadStatData,
+        rsp: &ReadStatPath,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut retries = 0;
+        loop {
+            let committed = self.output.as_ref().map(|o| o.written()).unwrap_or(0);
+            if let Some(o) = &self.output {
+                o.take_failure();
+            }
+
+            let e = match self.write_batch(d, rsp) {
+                Ok(()) => {
+                    self.batches_written += 1;
+                    self.rows_written += d.chunk.as_ref().map_or(0, |c| c.len());
+                    return Ok(());
+                }
+                Err(e) => e,
+            };
+
+            // Only failures of the output itself may be transient
+            let output = match &self.output {
+                Some(o) if self.write_retries > 0 => o.clone(),
+                _ => return Err(e),
+            };
+            let kind = match output.take_failure() {
+                Some(k) if is_transient(k) => k,
+                _ => return Err(e),
+            };
+
+            let out_f = rsp
+                .out_path
+                .as_ref()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if retries == self.write_retries {
+                return Err(From::from(format!(
+                    "Writing rows {} onwards to {} failed after {} retries: {}",
+                    d.chunk_row_start, out_f, retries, e
+                )));
+            }
+
+            // Nothing of value is lost by starting afresh while no batch has been written
+            let restart = self.batches_written == 0;
+            if !restart && !matches!(rsp.format, OutFormat::csv | OutFormat::ndjson) {
+                return Err(From::from(format!(
+                    "Writing rows {} onwards to {} failed ({}), which may be transient, yet {} output cannot be resumed once a batch has been written: {}",
+                    d.chunk_row_start, out_f, kind, rsp.format, e
+                )));
+            }
+
+            retries += 1;
+            self.warnings.push(format!(
+                "{}: writing rows {} onwards to {} failed ({}); retry {} of {} after {:?}",
+                "Warning".bright_yellow(),
+                d.chunk_row_start,
+                out_f,
+                e,
+                retries,
+                self.write_retries,
+                self.write_retry_delay
+            ));
+            thread::sleep(self.write_retry_delay);
+
+            if restart {
+                // rows appended to a segment before this conversion are kept
+                output.reopen(if self.appending { self.segment_bytes } else { 0 })?;
+                self.wtr = None;
+                self.wrote_start = false;
+                self.wrote_header = self.header_written_elsewhere();
+            } else {
+                // Drop whatever part of the batch was written, then write it again
+                output.reopen(committed)?;
+            }
+        }
+    }
+
+    fn write_batch(
+        &mut self,
+        d: &ReadStatData,
+        rsp: &ReadStatPath,
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
         match rsp {
+            // Write Arrow IPC stream
+            ReadStatPath {
+                format: OutFormat::arrow,
+                ..
+            } => self.write_data_to_arrow(d, rsp),
             // Write data to standard out
             ReadStatPath {
                 out_path: None,
@@ -247,6 +1689,11 @@ impl ReadStatWriter {
                 format: OutFormat::parquet,
                 ..
             } => self.write_data_to_parquet(d, rsp),
+            // Write SPSS or Stata data to file
+            ReadStatPath {
+                format: OutFormat::sav | OutFormat::dta,
+                ..
+            } => self.write_data_to_readstat(d, rsp),
         }
     }
 
@@ -256,36 +1703,31 @@ impl ReadStatWriter {
         rsp: &ReadStatPath,
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
         if let Some(p) = &rsp.out_path {
-            // if already started writing, then need to append to file; otherwise create file
-            let f = if self.wrote_start {
-                OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open(p)?
-            } else {
-                OpenOptions::new()
-                    .write(true)
-                    .create(true)
-                    .truncate(true)
-                    .open(p)?
-            };
-
             // set message for what is being read/written
             self.write_message_for_rows(d, rsp)?;
 
-            // setup writer
+            // setup writer; once started, writing continues where it left off
             if !self.wrote_start {
+                let f = self.open_output(p, rsp.overwrite)?;
                 self.wtr = Some(ReadStatWriterFormat::Csv(f))
             };
 
             // write
             if let Some(ReadStatWriterFormat::Csv(f)) = &mut self.wtr {
-                let options = csv_arrow2::write::SerializeOptions::default();
-
-                if let Some(c) = d.chunk.clone() {
-                    let cols = &[c];
-                    cols.iter()
-                        .try_for_each(|batch| csv_arrow2::write::write_chunk(f, batch, &options))?;
+                let options = self.csv.serialize_options();
+
+                if let Some(c) = &d.chunk {
+                    let mut buf = Vec::new();
+                    write_csv_chunk(
+                        &mut buf,
+                        &format_floats_for_csv(
+                            &decode_dictionaries(c)?,
+                            &csv_formats(d, self.apply_formats),
+                        ),
+                        &options,
+                        &self.csv.null_value,
+                    )?;
+                    f.write_all(&with_line_ending(buf, self.line_ending, true))?;
                 };
 
                 // update
@@ -303,34 +1745,109 @@ impl ReadStatWriter {
         }
     }
 
-    fn write_data_to_feather(
+    fn ipc_write_options(
+        &self,
+    ) -> Result<ipc_arrow2::write::WriteOptions, Box<dyn Error + Send + Sync>> {
+        let compression = match self.ipc_compression {
+            IpcCompression::None => None,
+            IpcCompression::Lz4 => Some(ipc_arrow2::write::Compression::LZ4),
+            IpcCompression::Zstd => Some(ipc_arrow2::write::Compression::ZSTD),
+        };
+
+        // without the feature arrow2 only fails once the first compressed buffer is written
+        if compression.is_some() && !has_arrow2_feature("io_ipc_compression") {
+            return Err(From::from(format!(
+                "The feather compression {} is not supported as arrow2 was compiled without the feature {}",
+                self.ipc_compression.to_string().bright_yellow(),
+                String::from("io_ipc_compression").bright_cyan()
+            )));
+        }
+
+        Ok(ipc_arrow2::write::WriteOptions { compression })
+    }
+
+    // Each batch is flushed once written so that a reader of the stream, e.g. at the other end of
+    //   a pipe, receives it as soon as it is read
+    fn write_data_to_arrow(
         &mut self,
         d: &ReadStatData,
         rsp: &ReadStatPath,
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
         if let Some(p) = &rsp.out_path {
-            // if already started writing, then need to append to file; otherwise create file
-            let f = if self.wrote_start {
-                OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open(p)?
-            } else {
-                OpenOptions::new()
-                    .write(true)
-                    .create(true)
-                    .truncate(true)
-                    .open(p)?
+            // set message for what is being read/written
+            self.write_message_for_rows(d, rsp)?;
+
+            // setup writer
+            if !self.wrote_start {
+                let f = self.open_output(p, rsp.overwrite)?;
+                let options = self.ipc_write_options()?;
+
+                let mut wtr = ipc_arrow2::write::StreamWriter::new(f, options);
+                wtr.start(&d.schema, None)?;
+
+                self.wtr = Some(ReadStatWriterFormat::Arrow(Box::new(wtr)));
             };
 
+            // write
+            if let Some(ReadStatWriterFormat::Arrow(wtr)) = &mut self.wtr {
+                if let Some(c) = &d.chunk {
+                    wtr.write(c, None)?;
+                };
+                if let Some(o) = &mut self.output {
+                    o.flush()?;
+                }
+
+                // update
+                self.wrote_start = true;
+
+                Ok(())
+            } else {
+                Err(From::from(
+                    "Error writing Arrow IPC stream as associated writer is not for the arrow format",
+                ))
+            }
+        } else {
+            Err(From::from(
+                "Error writing Arrow IPC stream as output path is set to None",
+            ))
+        }
+    }
+
+    fn finish_arrow(
+        &mut self,
+        d: &ReadStatData,
+        rsp: &ReadStatPath,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if let Some(ReadStatWriterFormat::Arrow(wtr)) = &mut self.wtr {
+            wtr.finish()?;
+            if let Some(o) = &mut self.output {
+                o.flush()?;
+            }
+
+            // set message for what is being read/written
+            self.finish_txt(d, rsp)?;
+
+            Ok(())
+        } else {
+            Err(From::from(
+                "Error writing Arrow IPC stream as associated writer is not for the arrow format",
+            ))
+        }
+    }
+
+    fn write_data_to_feather(
+        &mut self,
+        d: &ReadStatData,
+        rsp: &ReadStatPath,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if let Some(p) = &rsp.out_path {
             // set message for what is being read/written
             self.write_message_for_rows(d, rsp)?;
 
             // setup writer
             if !self.wrote_start {
-                let options = ipc_arrow2::write::WriteOptions {
-                    compression: Some(ipc_arrow2::write::Compression::ZSTD),
-                };
+                let f = self.open_output(p, rsp.overwrite)?;
+                let options = self.ipc_write_options()?;
 
                 let wtr = ipc_arrow2::write::FileWriter::try_new(f, d.schema.clone(), None, options)?;
 
@@ -378,47 +1895,112 @@ impl ReadStatWriter {
         }
     }
 
-    fn write_data_to_ndjson(
+    fn write_data_to_readstat(
         &mut self,
         d: &ReadStatData,
         rsp: &ReadStatPath,
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
         if let Some(p) = &rsp.out_path {
-            // if already started writing, then need to append to file; otherwise create file
-            let f = if self.wrote_start {
-                OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open(p)?
+            // set message for what is being read/written
+            self.write_message_for_rows(d, rsp)?;
+
+            // setup writer, declaring every row to be written
+            if !self.wrote_start {
+                let f = self.open_output(p, rsp.overwrite)?;
+                let vars: Vec<_> = d.vars.values().map(Some).collect();
+                let rows = match d.total_rows_to_process {
+                    0 => d.chunk_rows_to_process,
+                    r => r,
+                };
+
+                let (wtr, warnings) = ReadStatFileWriter::begin(
+                    f,
+                    rsp.format,
+                    &d.schema.fields,
+                    &vars,
+                    d.chunk.as_ref(),
+                    rows,
+                )?;
+                self.name_warnings.extend(warnings);
+                self.wtr = Some(ReadStatWriterFormat::ReadStat(Box::new(wtr)));
+            }
+
+            // write
+            if let Some(ReadStatWriterFormat::ReadStat(wtr)) = &mut self.wtr {
+                if let Some(c) = &d.chunk {
+                    wtr.insert(c)?;
+                }
+
+                // update
+                self.wrote_start = true;
+
+                Ok(())
             } else {
-                OpenOptions::new()
-                    .write(true)
-                    .create(true)
-                    .truncate(true)
-                    .open(p)?
-            };
+                Err(From::from(format!(
+                    "Error writing {} as associated writer is not for the {} format",
+                    rsp.format, rsp.format
+                )))
+            }
+        } else {
+            Err(From::from(format!(
+                "Error writing {} file as output path is set to None",
+                rsp.format
+            )))
+        }
+    }
+
+    fn finish_readstat(
+        &mut self,
+        d: &ReadStatData,
+        rsp: &ReadStatPath,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if let Some(ReadStatWriterFormat::ReadStat(wtr)) = &mut self.wtr {
+            wtr.end()?;
+
+            // set message for what is being read/written
+            self.finish_txt(d, rsp)?;
+
+            Ok(())
+        } else {
+            Err(From::from(format!(
+                "Error writing {} as associated writer is not for the {} format",
+                rsp.format, rsp.format
+            )))
+        }
+    }
 
+    fn write_data_to_ndjson(
+        &mut self,
+        d: &ReadStatData,
+        rsp: &ReadStatPath,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if let Some(p) = &rsp.out_path {
             // set message for what is being read/written
             self.write_message_for_rows(d, rsp)?;
 
-            // setup writer
+            // setup writer; once started, writing continues where it left off
             if !self.wrote_start {
+                let mut f = self.open_output(p, rsp.overwrite)?;
+                if self.bom && !self.header_written_elsewhere() {
+                    f.write_all(UTF8_BOM)?;
+                }
                 self.wtr = Some(ReadStatWriterFormat::Ndjson(f));
             };
 
             // write
             if let Some(ReadStatWriterFormat::Ndjson(f)) = &mut self.wtr {
-                if let Some(c) = d.chunk.clone() {
-                    let arrays = c.columns().iter().map(Ok);
+                if let Some(c) = &d.chunk {
+                    let rows = rows_for_ndjson(&decode_dictionaries(c)?, &d.schema).boxed();
 
                     // serializer
-                    let serializer = ndjson_arrow2::write::Serializer::new(arrays, vec![]);
-
-                    // writer
-                    let mut wtr = ndjson_arrow2::write::FileWriter::new(f, serializer);
-
-                    // drive iterator
-                    wtr.by_ref().collect::<Result<(), ArrowError>>()?;
+                    let serializer =
+                        ndjson_arrow2::write::Serializer::new(std::iter::once(Ok(rows)), vec![]);
+
+                    // writer, driving the iterator
+                    let mut buf = Vec::new();
+                    ndjson_arrow2::write::FileWriter::new(&mut buf, serializer)
+                        .collect::<Result<(), ArrowError>>()?;
+                    f.write_all(&with_line_ending(buf, self.line_ending, false))?;
                 }
 
                 // update
@@ -443,25 +2025,12 @@ impl ReadStatWriter {
         rsp: &ReadStatPath,
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
         if let Some(p) = &rsp.out_path {
-            // if already started writing, then need to append to file; otherwise create file
-            let f = if self.wrote_start {
-                OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open(p)?
-            } else {
-                OpenOptions::new()
-                    .write(true)
-                    .create(true)
-                    .truncate(true)
-                    .open(p)?
-            };
-
             // set message for what is being read/written
             self.write_message_for_rows(d, rsp)?;
 
             // setup writer
             if !self.wrote_start {
+                let f = self.open_output(p, rsp.overwrite)?;
                 let options = parquet_arrow2::write::WriteOptions {
                     write_statistics: true,
                     compression: match rsp.compression {
@@ -509,8 +2078,12 @@ impl ReadStatWriter {
                     .fields
                     .iter()
                     .map(|f| {
-                        parquet_arrow2::write::transverse(&f.data_type, |_| {
-                            parquet_arrow2::write::Encoding::Plain
+                        // dictionaries are written as dictionary-encoded pages, as parquet requires
+                        parquet_arrow2::write::transverse(&f.data_type, |dt| match dt {
+                            DataType::Dictionary(..) => {
+                                parquet_arrow2::write::Encoding::RleDictionary
+                            }
+                            _ => parquet_arrow2::write::Encoding::Plain,
                         })
                     })
                     .collect();
@@ -521,24 +2094,14 @@ impl ReadStatWriter {
                     Box::new(wtr),
                     options,
                     encodings,
+                    self.row_group_bytes,
                 )));
             }
 
             // write
             if let Some(ReadStatWriterFormat::Parquet(pwtr)) = &mut self.wtr {
                 if let Some(c) = d.chunk.clone() {
-                    let iter: Vec<Result<Chunk<Box<dyn Array>>, ArrowError>> = vec![Ok(c)];
-
-                    let row_groups = RowGroupIterator::try_new(
-                        iter.into_iter(),
-                        &d.schema,
-                        pwtr.options,
-                        pwtr.encodings.clone(),
-                    )?;
-
-                    for group in row_groups {
-                        pwtr.wtr.write(group?)?;
-                    }
+                    pwtr.push(c, &d.schema)?;
                 }
 
                 // update
@@ -563,6 +2126,7 @@ impl ReadStatWriter {
         rsp: &ReadStatPath,
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
         if let Some(ReadStatWriterFormat::Parquet(pwtr)) = &mut self.wtr {
+            pwtr.flush(&d.schema)?;
             let _size = pwtr.wtr.end(None)?;
 
             // set message for what is being read/written
@@ -591,12 +2155,14 @@ impl ReadStatWriter {
 
         // write
         if let Some(ReadStatWriterFormat::CsvStdout(f)) = &mut self.wtr {
-            let options = csv_arrow2::write::SerializeOptions::default();
+            let options = self.csv.serialize_options();
 
-            if let Some(c) = d.chunk.clone() {
-                let cols = &[c];
-                cols.iter()
-                    .try_for_each(|batch| csv_arrow2::write::write_chunk(f, batch, &options))?;
+            if let Some(c) = &d.chunk {
+                let c = format_floats_for_csv(
+                    &decode_dictionaries(c)?,
+                    &csv_formats(d, self.apply_formats),
+                );
+                write_csv_chunk(f, &c, &options, &self.csv.null_value)?;
             };
 
             // update
@@ -673,14 +2239,25 @@ impl ReadStatWriter {
             */
 
             // create file
-            let mut f = std::fs::File::create(p)?;
+            let mut f = self.open_output(p, rsp.overwrite)?;
+            if self.bom {
+                f.write_all(UTF8_BOM)?;
+            }
 
             // Get variable names
-            let vars: Vec<String> = d.vars.values().map(|m| m.var_name.clone()).collect();
+            let vars: Vec<String> = d.schema.fields.iter().map(|f| f.name.clone()).collect();
+
+            // write, unless only the records are wanted
+            if self.csv.write_header {
+                let options = self.csv.serialize_options();
+                let mut buf = Vec::new();
+                csv_arrow2::write::write_header(&mut buf, &vars, &options)?;
+                f.write_all(&with_line_ending(buf, self.line_ending, true))?;
+            }
 
-            // write
-            let options = csv_arrow2::write::SerializeOptions::default();
-            csv_arrow2::write::write_header(&mut f, &vars, &options)?;
+            // data is appended to the file just created
+            self.wtr = Some(ReadStatWriterFormat::Csv(f));
+            self.wrote_start = true;
 
             // wrote header
             self.wrote_header = true;
@@ -703,11 +2280,13 @@ impl ReadStatWriter {
         }
 
         // Get variable names
-        let vars: Vec<String> = d.vars.values().map(|m| m.var_name.clone()).collect();
+        let vars: Vec<String> = d.schema.fields.iter().map(|f| f.name.clone()).collect();
 
-        // write
-        let options = csv_arrow2::write::SerializeOptions::default();
-        csv_arrow2::write::write_header(&mut stdout(), &vars, &options)?;
+        // write, unless only the records are wanted
+        if self.csv.write_header {
+            let options = self.csv.serialize_options();
+            csv_arrow2::write::write_header(&mut stdout(), &vars, &options)?;
+        }
 
         // wrote header
         self.wrote_header = true;
@@ -733,7 +2312,7 @@ impl ReadStatWriter {
         &self,
         md: &ReadStatMetadata,
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
-        match serde_json::to_string_pretty(md) {
+        match md.to_json() {
             Ok(s) => {
                 println!("{}", s);
                 Ok(())
@@ -777,7 +2356,7 @@ impl ReadStatWriter {
         println!("{}:", "Variable names".purple());
         for (k, v) in md.vars.iter() {
             println!(
-                "{}: {} {{ type class: {}, type: {}, label: {}, format class: {}, format: {}, arrow logical data type: {}, arrow physical data type: {} }}",
+                "{}: {} {{ type class: {}, type: {}, label: {}, format class: {}, format: {}, raw format: {}, format name: {}, format width: {}, format decimals: {}, arrow logical data type: {}, arrow physical data type: {} }}",
                 (*k).to_formatted_string(&Locale::en),
                 v.var_name.bright_purple(),
                 format!("{:#?}", v.var_type_class).bright_green(),
@@ -790,16 +2369,79 @@ impl ReadStatWriter {
                         ReadStatVarFormatClass::DateTimeWithMilliseconds | 
                         ReadStatVarFormatClass::DateTimeWithMicroseconds |
                         ReadStatVarFormatClass::DateTimeWithNanoseconds => "DateTime",
-                        ReadStatVarFormatClass::Time => "Time",
+                        ReadStatVarFormatClass::Time |
+                        ReadStatVarFormatClass::TimeWithMicroseconds => "Time",
                     },
                     None => "",
                 })
                 .bright_cyan(),
                 v.var_format.bright_yellow(),
+                v.var_format_raw.bright_yellow(),
+                v.var_format_parts.as_ref().map_or("", |p| p.name.as_str()),
+                v.var_format_parts
+                    .as_ref()
+                    .and_then(|p| p.width)
+                    .map_or(String::new(), |w| w.to_string()),
+                v.var_format_parts
+                    .as_ref()
+                    .and_then(|p| p.decimals)
+                    .map_or(String::new(), |d| d.to_string()),
                 format!("{:#?}", md.schema.fields[*k as usize].data_type().to_logical_type()).bright_green(),
                 format!("{:#?}", md.schema.fields[*k as usize].data_type().to_physical_type()).bright_red(),
             );
         }
+        if let Some(f) = &md.schema_fingerprint {
+            println!("{}: {}", "Schema fingerprint".blue(), f);
+        }
+        if let Some(e) = &md.size_estimate {
+            println!("{}:", "Estimated size in bytes".purple());
+            for (name, bytes) in [
+                ("Arrow (in memory)", e.arrow_bytes),
+                ("csv", e.csv_bytes),
+                ("feather", e.feather_bytes),
+                ("ndjson", e.ndjson_bytes),
+                ("parquet", e.parquet_bytes),
+            ] {
+                println!("{}: {}", name, bytes.to_formatted_string(&Locale::en));
+            }
+        }
+        if !md.value_labels.is_empty() {
+            println!("{}:", "Value labels".purple());
+            for (k, v) in md.vars.iter() {
+                if let Some((name, labels)) = md.label_set(*k) {
+                    println!(
+                        "{}: {} {{ label set: {}, labels: {} }}",
+                        (*k).to_formatted_string(&Locale::en),
+                        v.var_name.bright_purple(),
+                        name.bright_yellow(),
+                        serde_json::to_string(&labels.labels)?
+                    );
+                }
+            }
+        }
+        if md.vars.values().any(|vm| !vm.var_missing_ranges.is_empty()) {
+            println!("{}:", "Missing values".purple());
+            for (k, v) in md.vars.iter().filter(|(_, v)| !v.var_missing_ranges.is_empty()) {
+                let ranges: Vec<String> =
+                    v.var_missing_ranges.iter().map(|r| r.to_string()).collect();
+                println!(
+                    "{}: {} {{ missing values: {} }}",
+                    (*k).to_formatted_string(&Locale::en),
+                    v.var_name.bright_purple(),
+                    ranges.join(", ").bright_yellow()
+                );
+            }
+        }
+        if let Some(rows) = &md.sample_rows {
+            println!("{}:", "Sample rows".purple());
+            for r in rows {
+                println!(
+                    "{}: {}",
+                    r.row.to_formatted_string(&Locale::en),
+                    serde_json::to_string(&r.values)?
+                );
+            }
+        }
 
         Ok(())
     }
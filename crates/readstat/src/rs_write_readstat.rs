@@ -0,0 +1,529 @@
+use arrow2::{
+    array::{Array, PrimitiveArray, Utf8Array},
+    chunk::Chunk,
+    compute::cast::{cast, CastOptions},
+    datatypes::{DataType, Field, TimeUnit},
+};
+use colored::Colorize;
+use log::debug;
+use std::{
+    collections::BTreeSet,
+    error::Error,
+    ffi::{CStr, CString},
+    io::Write,
+    os::raw::{c_long, c_void},
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::rs_metadata::{ReadStatFormatSpec, ReadStatVarMetadata};
+use crate::rs_schema::arrow_type_name;
+use crate::rs_write::ReadStatOutput;
+use crate::OutFormat;
+
+// Days from the SPSS epoch, 1582-10-14, and from the Stata epoch, 1960-01-01, to 1970-01-01
+const SPSS_DAYS: f64 = 141428.0;
+const STATA_DAYS: f64 = 3653.0;
+const SECONDS_PER_DAY: f64 = 86400.0;
+
+// How the values of a column are converted ahead of being inserted
+#[derive(Clone, Copy, Debug)]
+enum ReadStatColumnKind {
+    String,
+    Number,
+    // days since 1970-01-01
+    Date,
+    Timestamp(TimeUnit),
+    // since midnight
+    Time(TimeUnit),
+}
+
+struct ReadStatColumn {
+    variable: *mut readstat_sys::readstat_variable_t,
+    name: String,
+    kind: ReadStatColumnKind,
+    // bytes of the longest string a string variable holds
+    width: usize,
+}
+
+// Values of a column, ready to be inserted
+enum ReadStatCells {
+    Numbers(Vec<Option<f64>>),
+    Strings(Utf8Array<i32>),
+}
+
+// The output written to, and the failure of the last write to it, in place of a file
+struct ReadStatSink {
+    output: ReadStatOutput,
+    error: Option<std::io::Error>,
+}
+
+/// SPSS (`sav`) or Stata (`dta`) output, written with the writer of ReadStat
+///
+/// ReadStat records the number of rows ahead of the first, so every row declared on
+/// [`begin`](Self::begin) must be inserted ahead of [`end`](Self::end).
+pub struct ReadStatFileWriter {
+    writer: *mut readstat_sys::readstat_writer_t,
+    // boxed as ReadStat holds on to its address
+    sink: Box<ReadStatSink>,
+    columns: Vec<ReadStatColumn>,
+    format: OutFormat,
+    rows_declared: usize,
+    rows_written: usize,
+}
+
+// The writer and its variables are only ever used by the thread owning them
+unsafe impl Send for ReadStatFileWriter {}
+
+impl ReadStatFileWriter {
+    /// Begin writing `rows` rows of the columns `fields` in `format` to `output`, returning the
+    /// writer along with a warning for each column renamed
+    ///
+    /// The variable each field was read from, when it was, provides its label, its format, and
+    /// the width of its strings; strings are widened to the longest of `first`, the first batch.
+    pub(crate) fn begin(
+        output: ReadStatOutput,
+        format: OutFormat,
+        fields: &[Field],
+        vars: &[Option<&ReadStatVarMetadata>],
+        first: Option<&Chunk<Box<dyn Array>>>,
+        rows: usize,
+    ) -> Result<(Self, Vec<String>), Box<dyn Error + Send + Sync>> {
+        let writer = unsafe { readstat_sys::readstat_writer_init() };
+        let mut w = Self {
+            writer,
+            sink: Box::new(ReadStatSink {
+                output,
+                error: None,
+            }),
+            columns: Vec::new(),
+            format,
+            rows_declared: rows,
+            rows_written: 0,
+        };
+        check(
+            unsafe { readstat_sys::readstat_set_data_writer(writer, Some(handle_write)) },
+            "set the data writer",
+            format,
+            &mut w.sink,
+        )?;
+
+        let mut warnings = Vec::new();
+        let mut names = BTreeSet::new();
+        for (i, f) in fields.iter().enumerate() {
+            let vm = vars.get(i).copied().flatten();
+            let kind = column_kind(f)?;
+
+            let name = valid_name(&f.name, format, &names);
+            if name != f.name {
+                warnings.push(format!(
+                    "{}: the column {} is named {} within the {} file, as its name is not valid there",
+                    "Warning".bright_yellow(),
+                    f.name.bright_yellow(),
+                    name.bright_yellow(),
+                    format
+                ));
+            }
+            names.insert(name.to_lowercase());
+
+            let (var_type, width) = match kind {
+                ReadStatColumnKind::String => {
+                    let longest = first
+                        .map(|c| longest_string(c.arrays()[i].as_ref()))
+                        .transpose()?
+                        .unwrap_or(0);
+                    let width = vm.map_or(0, |vm| vm.var_storage_width).max(longest).max(1);
+                    (readstat_sys::readstat_type_e_READSTAT_TYPE_STRING, width)
+                }
+                _ => (readstat_sys::readstat_type_e_READSTAT_TYPE_DOUBLE, 0),
+            };
+
+            let cname = to_cstring(&name, &name)?;
+            let variable = unsafe {
+                readstat_sys::readstat_add_variable(writer, cname.as_ptr(), var_type, width)
+            };
+            if variable.is_null() {
+                return Err(From::from(format!(
+                    "Unable to add the variable {} to the {} file",
+                    name.bright_yellow(),
+                    format
+                )));
+            }
+
+            if let Some(label) = vm.map(|vm| vm.var_label.as_str()).filter(|l| !l.is_empty()) {
+                let label = to_cstring(label, &name)?;
+                unsafe { readstat_sys::readstat_variable_set_label(variable, label.as_ptr()) };
+            }
+            if let Some(display) = display_format(kind, vm, format) {
+                let display = to_cstring(&display, &name)?;
+                unsafe { readstat_sys::readstat_variable_set_format(variable, display.as_ptr()) };
+            }
+
+            w.columns.push(ReadStatColumn {
+                variable,
+                name,
+                kind,
+                width,
+            });
+        }
+
+        let ctx = w.sink.as_mut() as *mut ReadStatSink as *mut c_void;
+        let error = match format {
+            OutFormat::sav => unsafe {
+                readstat_sys::readstat_begin_writing_sav(writer, ctx, rows as c_long)
+            },
+            OutFormat::dta => unsafe {
+                readstat_sys::readstat_begin_writing_dta(writer, ctx, rows as c_long)
+            },
+            _ => return Err(From::from(format!("ReadStat does not write {} files", format))),
+        };
+        check(error, "begin writing", format, &mut w.sink)?;
+
+        Ok((w, warnings))
+    }
+
+    /// Insert every row of `chunk`, with missing values as system missing
+    pub(crate) fn insert(
+        &mut self,
+        chunk: &Chunk<Box<dyn Array>>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.rows_written + chunk.len() > self.rows_declared {
+            return Err(From::from(format!(
+                "Unable to write more than the {} rows declared to the {} file",
+                self.rows_declared, self.format
+            )));
+        }
+
+        let cells = self
+            .columns
+            .iter()
+            .zip(chunk.arrays())
+            .map(|(c, a)| to_cells(c.kind, a.as_ref(), self.format))
+            .collect::<Result<Vec<ReadStatCells>, _>>()?;
+
+        let format = self.format;
+        for row in 0..chunk.len() {
+            let error = unsafe { readstat_sys::readstat_begin_row(self.writer) };
+            check(error, "begin a row", format, &mut self.sink)?;
+
+            for (c, cells) in self.columns.iter().zip(cells.iter()) {
+                let error = match cells {
+                    ReadStatCells::Numbers(v) => match v[row] {
+                        Some(v) => unsafe {
+                            readstat_sys::readstat_insert_double_value(self.writer, c.variable, v)
+                        },
+                        None => unsafe {
+                            readstat_sys::readstat_insert_missing_value(self.writer, c.variable)
+                        },
+                    },
+                    ReadStatCells::Strings(a) => match a.get(row) {
+                        Some(s) => {
+                            if s.len() > c.width {
+                                return Err(From::from(format!(
+                                    "The value of {} in row {} is {} bytes long, longer than the {} bytes its variable within the {} file holds",
+                                    c.name.bright_yellow(),
+                                    self.rows_written + row,
+                                    s.len(),
+                                    c.width,
+                                    format
+                                )));
+                            }
+                            let s = to_cstring(s, &c.name)?;
+                            unsafe {
+                                readstat_sys::readstat_insert_string_value(
+                                    self.writer,
+                                    c.variable,
+                                    s.as_ptr(),
+                                )
+                            }
+                        }
+                        None => unsafe {
+                            readstat_sys::readstat_insert_missing_value(self.writer, c.variable)
+                        },
+                    },
+                };
+                if error != readstat_sys::readstat_error_e_READSTAT_OK {
+                    let action = format!("insert the value of {}", c.name);
+                    check(error, &action, format, &mut self.sink)?;
+                }
+            }
+
+            let error = unsafe { readstat_sys::readstat_end_row(self.writer) };
+            check(error, "end a row", format, &mut self.sink)?;
+        }
+
+        self.rows_written += chunk.len();
+        Ok(())
+    }
+
+    /// Finish the file, once every row declared has been inserted
+    pub(crate) fn end(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.rows_written != self.rows_declared {
+            return Err(From::from(format!(
+                "Wrote {} rows to the {} file, yet {} were declared when writing began",
+                self.rows_written, self.format, self.rows_declared
+            )));
+        }
+
+        let error = unsafe { readstat_sys::readstat_end_writing(self.writer) };
+        check(error, "end writing", self.format, &mut self.sink)?;
+        self.sink.output.flush()?;
+        Ok(())
+    }
+}
+
+// The failure to write to the output, when that is what failed, or else the message of ReadStat
+fn check(
+    error: readstat_sys::readstat_error_t,
+    action: &str,
+    format: OutFormat,
+    sink: &mut ReadStatSink,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if error == readstat_sys::readstat_error_e_READSTAT_OK {
+        return Ok(());
+    }
+    debug!("Unable to {}, error ==> {}", action, error);
+
+    if let Some(e) = sink.error.take() {
+        return Err(Box::new(e));
+    }
+    let message = unsafe { CStr::from_ptr(readstat_sys::readstat_error_message(error)) };
+    Err(From::from(format!(
+        "Unable to {} of the {} file: {}",
+        action,
+        format,
+        message.to_string_lossy()
+    )))
+}
+
+impl Drop for ReadStatFileWriter {
+    fn drop(&mut self) {
+        debug!("Freeing writer");
+
+        unsafe { readstat_sys::readstat_writer_free(self.writer) };
+    }
+}
+
+// C callback function
+// As with the handlers of metadata and values, a panic must never unwind into ReadStat
+extern "C" fn handle_write(
+    data: *const c_void,
+    len: usize,
+    ctx: *mut c_void,
+) -> readstat_sys::ssize_t {
+    if len == 0 {
+        return 0;
+    }
+
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        let sink = unsafe { &mut *(ctx as *mut ReadStatSink) };
+        let buf = unsafe { std::slice::from_raw_parts(data as *const u8, len) };
+
+        match sink.output.write_all(buf) {
+            Ok(()) => len as readstat_sys::ssize_t,
+            Err(e) => {
+                sink.error = Some(e);
+                -1
+            }
+        }
+    }))
+    .unwrap_or(-1)
+}
+
+fn column_kind(f: &Field) -> Result<ReadStatColumnKind, Box<dyn Error + Send + Sync>> {
+    let kind = match f.data_type() {
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Dictionary(..) => {
+            ReadStatColumnKind::String
+        }
+        DataType::Boolean
+        | DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64
+        | DataType::Float32
+        | DataType::Float64 => ReadStatColumnKind::Number,
+        DataType::Date32 => ReadStatColumnKind::Date,
+        DataType::Timestamp(unit, _) => ReadStatColumnKind::Timestamp(*unit),
+        DataType::Time32(unit) | DataType::Time64(unit) => ReadStatColumnKind::Time(*unit),
+        dt => {
+            return Err(From::from(format!(
+                "The column {} is of the Arrow type {}, which cannot be written by ReadStat",
+                f.name.bright_yellow(),
+                arrow_type_name(dt)
+            )))
+        }
+    };
+
+    Ok(kind)
+}
+
+// SPSS names begin with a letter, whereas Stata names may also begin with an underscore; names
+//   that do not are prefixed with v, and numbered should that name be taken
+fn valid_name(name: &str, format: OutFormat, taken: &BTreeSet<String>) -> String {
+    let valid_start = |c: char| match format {
+        OutFormat::sav => c.is_ascii_alphabetic(),
+        _ => c.is_ascii_alphabetic() || c == '_',
+    };
+
+    let name = match name.chars().next() {
+        Some(c) if valid_start(c) => return name.to_owned(),
+        _ => format!("v{}", name),
+    };
+
+    let mut candidate = name.clone();
+    let mut n = 1;
+    while taken.contains(&candidate.to_lowercase()) {
+        n += 1;
+        candidate = format!("{}_{}", name, n);
+    }
+    candidate
+}
+
+// Display format of a column, leaving ReadStat to choose for strings and for numerics whose SAS
+//   format has no counterpart
+fn display_format(
+    kind: ReadStatColumnKind,
+    vm: Option<&ReadStatVarMetadata>,
+    format: OutFormat,
+) -> Option<String> {
+    let sav = matches!(format, OutFormat::sav);
+    let display = match kind {
+        ReadStatColumnKind::String => return None,
+        ReadStatColumnKind::Number => {
+            return vm
+                .and_then(|vm| vm.var_format_parts.as_ref())
+                .and_then(|spec| numeric_format(spec, format))
+        }
+        ReadStatColumnKind::Date if sav => "DATE11",
+        ReadStatColumnKind::Date => "%td",
+        ReadStatColumnKind::Timestamp(TimeUnit::Second) if sav => "DATETIME20",
+        ReadStatColumnKind::Timestamp(TimeUnit::Millisecond) if sav => "DATETIME23.3",
+        ReadStatColumnKind::Timestamp(_) if sav => "DATETIME26.6",
+        // Stata datetimes are always of milliseconds
+        ReadStatColumnKind::Timestamp(_) => "%tc",
+        ReadStatColumnKind::Time(TimeUnit::Second) if sav => "TIME8",
+        ReadStatColumnKind::Time(_) if sav => "TIME15.6",
+        ReadStatColumnKind::Time(TimeUnit::Second) => "%tcHH:MM:SS",
+        ReadStatColumnKind::Time(_) => "%tcHH:MM:SS.sss",
+    };
+
+    Some(String::from(display))
+}
+
+// The SPSS or Stata counterpart of the SAS format of a numeric, e.g. 8.2 as F8.2 or %8.2f
+fn numeric_format(spec: &ReadStatFormatSpec, format: OutFormat) -> Option<String> {
+    let width = spec.width?;
+    let decimals = spec.decimals.unwrap_or(0);
+    if width == 0 || width > 40 || decimals >= width {
+        return None;
+    }
+
+    let display = match (format, spec.name.to_uppercase().as_str()) {
+        (OutFormat::sav, "" | "F") => format!("F{}.{}", width, decimals),
+        (OutFormat::sav, "COMMA") => format!("COMMA{}.{}", width, decimals),
+        (OutFormat::sav, "DOLLAR") => format!("DOLLAR{}.{}", width, decimals),
+        (OutFormat::sav, "PERCENT") => format!("PCT{}.{}", width, decimals),
+        (OutFormat::dta, "" | "F") => format!("%{}.{}f", width, decimals),
+        (OutFormat::dta, "COMMA") => format!("%{}.{}fc", width, decimals),
+        _ => return None,
+    };
+
+    Some(display)
+}
+
+fn longest_string(a: &dyn Array) -> Result<usize, Box<dyn Error + Send + Sync>> {
+    let a = strings(a)?;
+    Ok(a.iter().flatten().map(|s| s.len()).max().unwrap_or(0))
+}
+
+fn strings(a: &dyn Array) -> Result<Utf8Array<i32>, Box<dyn Error + Send + Sync>> {
+    let a = match a.data_type() {
+        DataType::Utf8 => a.to_boxed(),
+        _ => cast(a, &DataType::Utf8, CastOptions::default())?,
+    };
+    Ok(a.as_any().downcast_ref::<Utf8Array<i32>>().unwrap().clone())
+}
+
+// Dates, datetimes, and times as the target counts them: SPSS in seconds since 1582-10-14,
+//   dates included, and Stata in days or milliseconds since 1960-01-01
+fn to_cells(
+    kind: ReadStatColumnKind,
+    a: &dyn Array,
+    format: OutFormat,
+) -> Result<ReadStatCells, Box<dyn Error + Send + Sync>> {
+    let sav = matches!(format, OutFormat::sav);
+
+    let cells = match kind {
+        ReadStatColumnKind::String => ReadStatCells::Strings(strings(a)?),
+        ReadStatColumnKind::Number => {
+            let a = cast(a, &DataType::Float64, CastOptions::default())?;
+            ReadStatCells::Numbers(values::<f64>(a.as_ref()))
+        }
+        ReadStatColumnKind::Date => {
+            let days = values::<i32>(a).into_iter().map(|v| v.map(f64::from)).collect();
+            if sav {
+                numbers(days, |d| (d + SPSS_DAYS) * SECONDS_PER_DAY)
+            } else {
+                numbers(days, |d| d + STATA_DAYS)
+            }
+        }
+        ReadStatColumnKind::Timestamp(unit) => {
+            let seconds = seconds(values::<i64>(a), unit);
+            if sav {
+                numbers(seconds, |s| s + SPSS_DAYS * SECONDS_PER_DAY)
+            } else {
+                numbers(seconds, |s| ((s + STATA_DAYS * SECONDS_PER_DAY) * 1e3).round())
+            }
+        }
+        ReadStatColumnKind::Time(unit) => {
+            let v = match a.data_type() {
+                DataType::Time32(_) => {
+                    values::<i32>(a).into_iter().map(|v| v.map(i64::from)).collect()
+                }
+                _ => values::<i64>(a),
+            };
+            let seconds = seconds(v, unit);
+            if sav {
+                ReadStatCells::Numbers(seconds)
+            } else {
+                numbers(seconds, |s| (s * 1e3).round())
+            }
+        }
+    };
+
+    Ok(cells)
+}
+
+fn numbers(values: Vec<Option<f64>>, f: impl Fn(f64) -> f64) -> ReadStatCells {
+    ReadStatCells::Numbers(values.into_iter().map(|v| v.map(&f)).collect())
+}
+
+fn values<T: arrow2::types::NativeType>(a: &dyn Array) -> Vec<Option<T>> {
+    let a = a.as_any().downcast_ref::<PrimitiveArray<T>>().unwrap();
+    a.iter().map(|v| v.copied()).collect()
+}
+
+fn seconds(values: Vec<Option<i64>>, unit: TimeUnit) -> Vec<Option<f64>> {
+    let per_second = match unit {
+        TimeUnit::Second => 1.0,
+        TimeUnit::Millisecond => 1e3,
+        TimeUnit::Microsecond => 1e6,
+        TimeUnit::Nanosecond => 1e9,
+    };
+    values
+        .into_iter()
+        .map(|v| v.map(|v| v as f64 / per_second))
+        .collect()
+}
+
+fn to_cstring(s: &str, name: &str) -> Result<CString, Box<dyn Error + Send + Sync>> {
+    CString::new(s).map_err(|_| {
+        From::from(format!(
+            "Unable to write {} as it holds a nul byte",
+            name.bright_yellow()
+        ))
+    })
+}